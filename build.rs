@@ -2,6 +2,9 @@ fn main() {
     // 编译 Slint UI
     slint_build::compile("ui/main.slint").unwrap();
 
+    // 注入 git 短哈希，便于诊断信息定位具体构建
+    emit_git_hash();
+
     // Windows 平台:嵌入应用图标
     #[cfg(windows)]
     {
@@ -10,3 +13,25 @@ fn main() {
         res.compile().unwrap();
     }
 }
+
+/// 读取 `git rev-parse --short HEAD` 并设置 `NANOMAIL_GIT_HASH`（非 git 工作区时不设置）
+fn emit_git_hash() {
+    for path in [".git/HEAD", ".git/index"] {
+        if std::path::Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output();
+
+    if let Ok(output) = output
+        && output.status.success()
+    {
+        let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !hash.is_empty() {
+            println!("cargo:rustc-env=NANOMAIL_GIT_HASH={}", hash);
+        }
+    }
+}
@@ -0,0 +1,836 @@
+/// 账户操作
+///
+/// Token 详情与手动刷新、重新授权、删除、修改账户设置以及手动添加 IMAP 账户；
+/// 均在事件循环中发起，耗时的部分交给 `AppState` 的后台任务或独立线程
+use slint::{ComponentHandle, Model};
+use std::sync::Arc;
+
+use crate::config::storage::StoredAccount;
+use crate::mail::gmail::api::GmailApiClient;
+use crate::mail::gmail::labels::{self, Label, LabelPlan, LabelRole};
+use crate::utils::redact::redact_email;
+use crate::{ImapSettings, MainWindow, config, mail, sync, tray, ui, utils};
+
+use super::AppState;
+use super::rows::{
+    refresh_groups, regroup, reload_accounts_ui, remove_account_row, set_reauth_reminder,
+    update_accounts_ui,
+};
+use super::status::apply_app_status;
+
+/// 显示账户的 Token 详情（重新读取账户文件，使剩余时间保持准确）
+pub fn show_token_details(window: &MainWindow, email: &str) {
+    let Some(stored) = find_stored_account(email) else {
+        window.set_token_details_visible(false);
+        return;
+    };
+    let Some(account) = stored.oauth() else {
+        tracing::warn!("{} 账户没有 Token", stored.kind());
+        return;
+    };
+
+    if !window.get_token_details_visible() || window.get_token_details().email != email {
+        window.set_token_details_busy(false);
+        window.set_token_details_status(Default::default());
+    }
+    let now = chrono::Utc::now();
+    let mut details = ui::token_details(account, now);
+    details.mail_access = ui::mail_access(&stored).into();
+    details.api_usage = ui::api_usage(&stored).into();
+    details.reauth_reminder = ui::reauth_reminder(&stored, now).into();
+    if let Some(plan) = stored.gmail_labels() {
+        ui::set_label_pickers(&mut details, &gmail_label_choices(email, &plan), &plan);
+    }
+    window.set_token_details(details);
+    window.set_token_details_visible(true);
+}
+
+/// 立即刷新账户的 Access Token（保存后更新详情并同步该账户）
+pub fn refresh_account_token(window: &MainWindow, state: &Arc<AppState>, email: String) {
+    let Some(stored) = find_stored_account(&email) else {
+        return;
+    };
+    let mut manager = match mail::token_manager(&stored) {
+        Some(Ok(manager)) => manager,
+        Some(Err(e)) => {
+            tracing::error!("无法刷新 Token: {:#}", e);
+            window.set_token_details_status(
+                utils::i18n::tr_fmt("details.refresh_failed", &[&e]).into(),
+            );
+            return;
+        }
+        None => return,
+    };
+
+    window.set_token_details_busy(true);
+    window.set_token_details_status(utils::i18n::tr("details.refreshing").into());
+
+    let weak = window.as_weak();
+    let task_state = state.clone();
+    let started = state.workers.spawn("刷新 Token", async move {
+        // 刷新成功时 TokenManager 已把新 Token 写回账户文件
+        let status = match manager.force_refresh().await {
+            Ok(()) => {
+                tracing::info!("✅ 已手动刷新 Token: {}", redact_email(&email));
+                task_state.sync_engine.sync_one(&email);
+                let details = ui::token_details(manager.account(), chrono::Utc::now());
+                utils::i18n::tr_fmt("details.refreshed", &[&details.expires_at])
+            }
+            Err(e) => {
+                tracing::error!("手动刷新 Token 失败: {:#}", e);
+                utils::i18n::tr_fmt(
+                    "details.refresh_failed",
+                    &[&mail::SyncFailure::from_error(&e)],
+                )
+            }
+        };
+
+        slint::invoke_from_event_loop(move || {
+            if let Some(window) = weak.upgrade() {
+                show_token_details(&window, &email);
+                window.set_token_details_busy(false);
+                window.set_token_details_status(status.into());
+            }
+        })
+        .ok();
+    });
+
+    if !started {
+        window.set_token_details_busy(false);
+    }
+}
+
+/// 修改账户的未读提醒阈值（详情面板中输入，留空表示不提醒），保存后立即同步该账户
+pub fn set_alert_threshold(window: &MainWindow, state: &AppState, email: &str, input: &str) {
+    let threshold = match ui::parse_alert_threshold(input) {
+        Ok(threshold) => threshold,
+        Err(message) => {
+            window.set_token_details_status(message.into());
+            return;
+        }
+    };
+    let Some(index) = window
+        .get_accounts()
+        .iter()
+        .position(|row| row.email.as_str() == email)
+    else {
+        return;
+    };
+
+    edit_account(window, state, index as i32, |stored| {
+        stored.set_alert_threshold(threshold);
+        true
+    });
+    if let Ok(accounts) = config::storage::load_accounts() {
+        tray::summary::set_accounts(&accounts);
+    }
+    apply_app_status(window);
+
+    show_token_details(window, email);
+    let status = match threshold {
+        Some(threshold) => utils::i18n::tr_fmt("details.alert_saved", &[&threshold]),
+        None => utils::i18n::tr("details.alert_cleared").to_string(),
+    };
+    window.set_token_details_status(status.into());
+}
+
+/// 修改账户所在的分组（详情面板或账户菜单中输入，留空表示取消分组），保存后重新排列账户列表
+pub fn set_account_group(window: &MainWindow, state: &AppState, email: &str, input: &str) {
+    let group = ui::groups::normalize(input);
+    if find_stored_account(email).is_none_or(|stored| stored.group() == group.as_deref()) {
+        return;
+    }
+    let Some(index) = window
+        .get_accounts()
+        .iter()
+        .position(|row| !row.header && row.email.as_str() == email)
+    else {
+        return;
+    };
+
+    edit_account(window, state, index as i32, |stored| {
+        stored.set_group(group.clone());
+        true
+    });
+
+    if window.get_token_details_visible() && window.get_token_details().email == email {
+        show_token_details(window, email);
+        let status = match &group {
+            Some(group) => utils::i18n::tr_fmt("details.group_saved", &[group]),
+            None => utils::i18n::tr("details.group_cleared").to_string(),
+        };
+        window.set_token_details_status(status.into());
+    }
+}
+
+/// 详情面板中的标签选项（缓存的标签列表加上账户当前使用的标签；缓存过期时仍然使用，直到重新读取）
+fn gmail_label_choices(email: &str, plan: &LabelPlan) -> Vec<Label> {
+    let cached = config::state::global()
+        .account(email)
+        .labels
+        .map(|cache| cache.labels)
+        .unwrap_or_default();
+    ui::label_choices(&cached, plan)
+}
+
+/// 读取 Gmail 账户的标签列表（打开详情面板或点击"刷新标签"时），完成后更新面板中的标签选择
+///
+/// 缓存未过期且不是手动刷新时不发出请求
+pub fn load_gmail_labels(window: &MainWindow, state: &AppState, email: String, refresh: bool) {
+    let Some(stored) = find_stored_account(&email) else {
+        return;
+    };
+    if stored.gmail_labels().is_none() {
+        return;
+    }
+    if !refresh && labels::cached(config::state::global(), &email, chrono::Utc::now()).is_some() {
+        return;
+    }
+    let mut manager = match mail::token_manager(&stored) {
+        Some(Ok(manager)) => manager,
+        Some(Err(e)) => {
+            tracing::warn!("无法读取标签列表: {:#}", e);
+            return;
+        }
+        None => return,
+    };
+
+    let weak = window.as_weak();
+    state.workers.spawn("读取 Gmail 标签", async move {
+        let result: anyhow::Result<Vec<Label>> = async {
+            let access_token = manager.get_valid_token().await?;
+            GmailApiClient::new(access_token)
+                .with_account(email.clone())
+                .list_labels(refresh)
+                .await
+        }
+        .await;
+
+        slint::invoke_from_event_loop(move || {
+            let Some(window) = weak.upgrade() else {
+                return;
+            };
+            let showing = window.get_token_details_visible()
+                && window.get_token_details().email.as_str() == email;
+            match result {
+                Ok(_) => {
+                    if showing {
+                        show_token_details(&window, &email);
+                        if refresh {
+                            window.set_token_details_status(
+                                utils::i18n::tr("details.labels_refreshed").into(),
+                            );
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("读取 {} 的标签列表失败: {:#}", redact_email(&email), e);
+                    if showing {
+                        window.set_token_details_status(
+                            utils::i18n::tr_fmt(
+                                "details.labels_failed",
+                                &[&mail::SyncFailure::from_error(&e)],
+                            )
+                            .into(),
+                        );
+                    }
+                }
+            }
+        })
+        .ok();
+    });
+}
+
+/// 修改 Gmail 账户徽章或通知使用的标签（详情面板中选择，两者可以相同），保存后立即同步该账户
+pub fn set_gmail_label(window: &MainWindow, state: &AppState, email: &str, role: &str, index: i32) {
+    let Some(role) = LabelRole::from_setting(role) else {
+        tracing::warn!("未知的标签用途: {:?}", role);
+        return;
+    };
+    let Some(plan) = find_stored_account(email).and_then(|stored| stored.gmail_labels()) else {
+        return;
+    };
+    let Some(label) = usize::try_from(index)
+        .ok()
+        .and_then(|index| gmail_label_choices(email, &plan).into_iter().nth(index))
+    else {
+        return;
+    };
+    let current = match role {
+        LabelRole::Badge => &plan.badge,
+        LabelRole::Notify => plan.notify.as_ref().unwrap_or(&plan.badge),
+    };
+    if label.id == *current {
+        return;
+    }
+    let Some(row_index) = window
+        .get_accounts()
+        .iter()
+        .position(|row| row.email.as_str() == email)
+    else {
+        return;
+    };
+
+    edit_account(window, state, row_index as i32, |stored| {
+        stored.set_gmail_label(role, &label.id)
+    });
+    show_token_details(window, email);
+    window.set_token_details_status(utils::i18n::tr("details.labels_saved").into());
+}
+
+/// 设置账户免打扰（托盘账户子菜单；`choice` 见 `sync::snooze_deadline`，无法识别时恢复提醒）
+///
+/// 窗口隐藏时账户列表为空，直接修改账户文件；托盘菜单与状态随即更新，不必等待同步
+pub fn snooze_account(window: &MainWindow, state: &AppState, email: &str, choice: &str) {
+    let until = sync::snooze_deadline(choice, chrono::Local::now());
+    let index = window
+        .get_accounts()
+        .iter()
+        .position(|row| row.email.as_str() == email);
+
+    match index {
+        Some(index) => edit_account(window, state, index as i32, |stored| {
+            stored.set_snoozed_until(until);
+            true
+        }),
+        None => {
+            let Some(mut stored) = find_stored_account(email) else {
+                return;
+            };
+            stored.set_snoozed_until(until);
+            if let Err(e) = config::storage::save_account(&stored) {
+                tracing::error!("保存账户设置失败: {}", e);
+                return;
+            }
+            state.sync_engine.sync_one(email);
+        }
+    }
+
+    if let Ok(accounts) = config::storage::load_accounts() {
+        tray::summary::set_accounts(&accounts);
+    }
+    apply_app_status(window);
+}
+
+/// 按邮箱查找已保存的账户
+pub fn find_stored_account(email: &str) -> Option<StoredAccount> {
+    match config::storage::load_accounts() {
+        Ok(accounts) => accounts.into_iter().find(|a| a.email() == email),
+        Err(e) => {
+            tracing::error!("加载账户失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 回调端口全部被占用时的提示：列出占用端口的程序，并说明如何改用自定义端口
+pub fn ports_occupied_message(error: &anyhow::Error) -> Option<String> {
+    let occupied = error.downcast_ref::<mail::oauth::PortsOccupied>()?;
+    let owners = if occupied.owners.is_empty() {
+        utils::i18n::tr("auth.ports_owner_unknown").to_string()
+    } else {
+        occupied
+            .owners
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    Some(utils::i18n::tr_fmt(
+        "auth.ports_occupied",
+        &[&occupied.port_list(), &owners],
+    ))
+}
+
+/// 重新授权：在浏览器中授权后只替换令牌，保留账户的其他设置，随后立即同步该账户
+///
+/// 结束后继续为等待中的下一个账户重新授权（见 [`reauthorize_all`]），用户取消或授权失败时不再继续
+pub fn reauthorize_account(window: &MainWindow, state: &Arc<AppState>, email: String) {
+    let Some(mut stored) = find_stored_account(&email) else {
+        return;
+    };
+    if stored.oauth_mut().is_none() {
+        tracing::warn!("{} 账户不需要重新授权", stored.kind());
+        return;
+    }
+    let Some(provider) = state.providers.for_account(&stored) else {
+        tracing::error!("未注册 {} 提供方，无法重新授权", stored.kind());
+        return;
+    };
+
+    // 授权结束前禁用添加按钮，避免同时打开多个授权页面
+    window.set_authorizing(true);
+
+    let weak = window.as_weak();
+    let task_state = state.clone();
+    let started = state.workers.spawn("重新授权", async move {
+        // 授权的是另一个账户时，该账户已被保存，需要加入列表
+        let mut added = None;
+        // 重新授权成功后 Refresh Token 重新计时，更新账户行的提醒
+        let mut reminder = None;
+        // 是否继续为等待中的下一个账户重新授权
+        let mut proceed = true;
+        let message = match provider.authenticate().await {
+            Ok(None) => {
+                tracing::info!("用户取消了重新授权");
+                proceed = false;
+                utils::i18n::tr("auth.declined").to_string()
+            }
+            Ok(Some(mut authorized)) if authorized.email() == email => {
+                if let (Some(current), Some(fresh)) = (stored.oauth_mut(), authorized.oauth_mut()) {
+                    current.adopt_tokens(fresh);
+                }
+                match config::storage::save_account_async(stored.clone()).await {
+                    Ok(()) => {
+                        tracing::info!("✅ 重新授权成功: {}", redact_email(&email));
+                        reminder = Some(ui::reauth_reminder(&stored, chrono::Utc::now()));
+                        task_state.sync_engine.sync_one(&email);
+                        utils::i18n::tr_fmt("account.reauthorized", &[&email])
+                    }
+                    Err(e) => {
+                        tracing::error!("保存账户失败: {}", e);
+                        utils::i18n::tr_fmt("account.reauth_failed", &[&e])
+                    }
+                }
+            }
+            Ok(Some(other)) => {
+                tracing::warn!(
+                    "重新授权得到的是另一个账户: {} -> {}",
+                    redact_email(&email),
+                    redact_email(other.email())
+                );
+                let message = utils::i18n::tr_fmt("account.reauth_mismatch", &[&other.email()]);
+                added = Some(other);
+                message
+            }
+            Err(e) => {
+                tracing::error!("❌ 重新授权失败: {}", e);
+                proceed = false;
+                if let Some(details) = ports_occupied_message(&e) {
+                    // 消息框是模态的，放到独立线程避免阻塞后台任务
+                    std::thread::spawn(move || utils::dialog::show_error(&details));
+                }
+                utils::i18n::tr_fmt("account.reauth_failed", &[&e])
+            }
+        };
+
+        slint::invoke_from_event_loop(move || {
+            if let Some(window) = weak.upgrade() {
+                window.set_authorizing(false);
+                if let Some(reminder) = reminder {
+                    set_reauth_reminder(&window, &email, &reminder);
+                }
+                if let Some(account) = added {
+                    update_accounts_ui(&window, account, false);
+                    task_state.sync_engine.trigger_sync();
+                }
+                ui::show_snackbar(&window, &message);
+                if let Some(next) = task_state.next_reauth(proceed) {
+                    reauthorize_account(&window, &task_state, next);
+                }
+            }
+        })
+        .ok();
+    });
+
+    // 已开始退出，未启动授权
+    if !started {
+        window.set_authorizing(false);
+    }
+}
+
+/// 凭据错误页面的"重新授权全部账户"
+///
+/// 先接受当前的本机密钥并启动同步（各账户暂时显示需要重新授权的提示），再依次为各 OAuth2 账户
+/// 打开授权页面；IMAP 账户的密码无法重新授权，需要删除后重新添加。仍无法使用本机密钥时保留错误页面
+pub fn reauthorize_all(window: &MainWindow, state: &Arc<AppState>) {
+    if let Err(e) = config::self_test::accept_current_key() {
+        tracing::error!("接受当前的本机密钥失败: {:#}", e);
+        ui::show_snackbar(
+            window,
+            &utils::i18n::tr_fmt("credentials.accept_failed", &[&e]),
+        );
+        return;
+    }
+    window.set_credentials_error(Default::default());
+    state.start_sync(window);
+
+    let emails: Vec<String> = config::storage::load_accounts()
+        .unwrap_or_default()
+        .iter()
+        .filter(|account| account.oauth().is_some())
+        .map(|account| account.email().to_string())
+        .collect();
+    tracing::info!("依次重新授权 {} 个账户", emails.len());
+    state.queue_reauth(emails);
+    if let Some(first) = state.next_reauth(true) {
+        reauthorize_account(window, state, first);
+    }
+}
+
+/// 删除账户的撤销时限
+const UNDO_REMOVE_WINDOW: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// 删除账户：先标记删除并立即从界面与同步中移除，提示条中可在 `UNDO_REMOVE_WINDOW` 内撤销
+///
+/// 到期后才真正删除（同时清除其运行时状态与头像缓存，Gmail 账户还会撤销授权）；
+/// 程序在此之前退出时，下次启动时由 `finish_pending_removals` 完成
+pub fn remove_account(window: &MainWindow, state: &Arc<AppState>, email: String) {
+    match config::storage::mark_pending_delete(&email, chrono::Utc::now()) {
+        Ok(true) => tracing::info!("🗑️ 已标记删除账户: {}", redact_email(&email)),
+        Ok(false) => {
+            tracing::debug!("账户已不存在: {}", redact_email(&email));
+            return;
+        }
+        Err(e) => {
+            tracing::error!("标记删除账户失败: {:#}", e);
+            ui::show_snackbar(window, &e.to_string());
+            return;
+        }
+    }
+
+    if let Ok(accounts) = config::storage::load_accounts() {
+        tray::summary::set_accounts(&accounts);
+    }
+    remove_account_row(window, &email);
+    apply_app_status(window);
+
+    let undo = {
+        let weak = window.as_weak();
+        let state = state.clone();
+        let email = email.clone();
+        move || {
+            if let Some(window) = weak.upgrade() {
+                undo_remove_account(&window, &state, &email);
+            }
+        }
+    };
+    ui::show_snackbar_with_action(
+        window,
+        &utils::i18n::tr_fmt("account.removed", &[&email]),
+        utils::i18n::tr("account.undo"),
+        UNDO_REMOVE_WINDOW,
+        undo,
+    );
+
+    // 到期后真正删除（已撤销、或撤销后再次删除而新的期限未到时什么也不做）
+    let state = state.clone();
+    slint::Timer::single_shot(UNDO_REMOVE_WINDOW, move || {
+        finish_remove_account(&state, &email, false);
+    });
+}
+
+/// 撤销删除：清除删除标记，账户原样恢复到界面并立即同步
+fn undo_remove_account(window: &MainWindow, state: &AppState, email: &str) {
+    match config::storage::undo_pending_delete(email) {
+        Ok(true) => tracing::info!("↩️ 已撤销删除账户: {}", redact_email(email)),
+        Ok(false) => {
+            tracing::debug!("账户已删除，无法撤销: {}", redact_email(email));
+            return;
+        }
+        Err(e) => {
+            tracing::error!("撤销删除账户失败: {:#}", e);
+            ui::show_snackbar(window, &e.to_string());
+            return;
+        }
+    }
+
+    if let Ok(accounts) = config::storage::load_accounts() {
+        tray::summary::set_accounts(&accounts);
+    }
+    reload_accounts_ui(window, state.cached_counts_max_age());
+    apply_app_status(window);
+    state.sync_engine.sync_one(email);
+}
+
+/// 真正删除已标记删除的账户：撤销 Gmail 授权并清除其运行时状态与头像缓存
+///
+/// 账户已撤销删除或已不存在时什么也不做；`force` 为 false 时还要求最近一次标记删除已超过
+/// `UNDO_REMOVE_WINDOW`（撤销后再次删除时，前一次删除的计时器不会提前删除账户）
+fn finish_remove_account(state: &AppState, email: &str, force: bool) {
+    let pending = match config::storage::pending_deletions() {
+        Ok(pending) => pending,
+        Err(e) => {
+            tracing::warn!("读取待删除账户失败: {}", e);
+            return;
+        }
+    };
+    let Some(stored) = pending.into_iter().find(|a| a.email() == email) else {
+        return;
+    };
+    let undo_window = chrono::Duration::from_std(UNDO_REMOVE_WINDOW).unwrap_or_default();
+    if !force
+        && let Some(at) = stored.pending_delete_at()
+        && chrono::Utc::now() < at + undo_window
+    {
+        tracing::debug!("账户的撤销期尚未结束，稍后删除: {}", redact_email(email));
+        return;
+    }
+
+    match config::storage::remove_account(&stored) {
+        Ok(true) => {
+            tracing::info!("🗑️ 已删除账户: {}", redact_email(email));
+            if let StoredAccount::Gmail(account) = &stored {
+                revoke_gmail_account(state, account.clone());
+            }
+        }
+        Ok(false) => return,
+        Err(e) => {
+            tracing::error!("删除账户失败: {:#}", e);
+            return;
+        }
+    }
+    if let Err(e) = config::state::global().update_account(email, |state| {
+        *state = config::state::AccountState::default();
+    }) {
+        tracing::warn!("清除账户运行时状态失败: {}", e);
+    }
+
+    // 仍在撤销期内的其他账户保留头像
+    if let (Ok(accounts), Ok(pending)) = (
+        config::storage::load_accounts(),
+        config::storage::pending_deletions(),
+    ) {
+        let emails: Vec<String> = accounts
+            .iter()
+            .chain(&pending)
+            .map(|a| a.email().to_string())
+            .collect();
+        utils::avatar::cleanup(&emails);
+    }
+}
+
+/// 完成上次运行中未到撤销期就退出的删除（启动时调用）
+pub fn finish_pending_removals(state: &AppState) {
+    match config::storage::pending_deletions() {
+        Ok(pending) => {
+            for stored in pending {
+                // 重启后撤销提示已不存在，直接删除
+                finish_remove_account(state, stored.email(), true);
+            }
+        }
+        Err(e) => tracing::warn!("读取待删除账户失败: {}", e),
+    }
+}
+
+/// 在后台撤销已删除的 Gmail 账户的授权（失败只记录日志）
+fn revoke_gmail_account(state: &AppState, account: mail::gmail::types::GmailAccount) {
+    state.workers.spawn("撤销授权", async move {
+        match mail::gmail::oauth::revoke(&account).await {
+            Ok(()) => tracing::info!("已撤销授权: {}", redact_email(&account.email)),
+            Err(e) => tracing::warn!("撤销授权失败: {:#}", e),
+        }
+    });
+}
+
+/// 修改账户设置并保存，随后立即同步该账户以刷新徽章
+///
+/// `change` 返回 false 表示该账户不支持此项设置，不做任何修改
+pub fn edit_account(
+    window: &MainWindow,
+    state: &AppState,
+    index: i32,
+    change: impl FnOnce(&mut StoredAccount) -> bool,
+) {
+    let rows = window.get_accounts();
+    let Some(mut row) = rows.row_data(index as usize) else {
+        return;
+    };
+
+    let accounts = match config::storage::load_accounts() {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            tracing::error!("加载账户失败: {}", e);
+            return;
+        }
+    };
+
+    let Some(mut stored) = accounts
+        .into_iter()
+        .find(|a| a.email() == row.email.as_str())
+    else {
+        return;
+    };
+
+    if !change(&mut stored) {
+        tracing::warn!("{} 账户不支持此设置", stored.kind());
+        return;
+    }
+
+    if let Err(e) = config::storage::save_account(&stored) {
+        tracing::error!("保存账户设置失败: {}", e);
+        return;
+    }
+
+    row.count_mode = stored
+        .count_mode()
+        .map(|mode| mode.as_setting())
+        .unwrap_or_default()
+        .into();
+    row.display_name = stored.display_name().into();
+    row.snoozed = stored.is_snoozed();
+    row.accent = utils::avatar::accent_color(stored.email(), stored.accent());
+    row.accent_choice = stored.accent().unwrap_or("auto").into();
+    row.watch_spam = ui::toggle_setting(stored.watch_spam()).into();
+    row.alert_threshold = ui::alert_threshold_setting(stored.alert_threshold());
+    // 关闭监视后立即隐藏垃圾邮件说明（随后的同步会一并清除托盘提示中的记录）
+    if stored.watch_spam() != Some(true) {
+        row.spam_caption = Default::default();
+    }
+    row.watch_vacation = ui::vacation_setting(&stored).into();
+    row.push = ui::push_setting(&stored).into();
+    if stored.watch_vacation() != Some(true) {
+        row.vacation = Default::default();
+    }
+    row.people_only = ui::toggle_setting(stored.people_only()).into();
+    if stored.people_only() != Some(true) {
+        row.filter_caption = Default::default();
+    }
+    let group = stored.group().unwrap_or_default();
+    let regrouped = row.group.as_str() != group;
+    row.group = group.into();
+    rows.set_row_data(index as usize, row);
+    // 分组变化时重新排列（账户行移到新的组中），否则只更新分组标题中的未读合计（免打扰等）
+    if regrouped {
+        regroup(window);
+    } else {
+        refresh_groups(window);
+    }
+    state.sync_engine.sync_one(stored.email());
+}
+
+/// 校验 IMAP 表单并尝试登录；`save` 为 true 时登录成功后保存账户并加入列表
+pub fn check_imap_form(
+    weak: &slint::Weak<MainWindow>,
+    state: &AppState,
+    settings: ImapSettings,
+    save: bool,
+) {
+    let Some(window) = weak.upgrade() else {
+        return;
+    };
+
+    let account = match imap_account_from_form(&settings) {
+        Ok(account) => account,
+        Err(message) => {
+            window.set_imap_form_status(message.into());
+            return;
+        }
+    };
+
+    window.set_imap_form_busy(true);
+    window.set_imap_form_status(utils::i18n::tr("imap.connecting").into());
+
+    let weak = weak.clone();
+    // 保存后的首次同步（只在登录成功并保存后运行）
+    let first_sync = state
+        .providers
+        .get("imap")
+        .map(|provider| state.first_sync(&weak, provider, StoredAccount::Imap(account.clone())));
+    let started = state.workers.spawn("IMAP 测试连接", async move {
+        if let Err(e) = mail::imap::test_connection(&account).await {
+            let message = utils::i18n::tr_fmt("imap.failed", &[&mail::SyncFailure::from_error(&e)]);
+            tracing::warn!(
+                "IMAP 测试连接失败 {}: {:#}",
+                redact_email(&account.email),
+                e
+            );
+            slint::invoke_from_event_loop(move || {
+                if let Some(window) = weak.upgrade() {
+                    window.set_imap_form_busy(false);
+                    window.set_imap_form_status(message.into());
+                }
+            })
+            .ok();
+            return;
+        }
+
+        if !save {
+            slint::invoke_from_event_loop(move || {
+                if let Some(window) = weak.upgrade() {
+                    window.set_imap_form_busy(false);
+                    window.set_imap_form_status(utils::i18n::tr("imap.test_ok").into());
+                }
+            })
+            .ok();
+            return;
+        }
+
+        let account = StoredAccount::Imap(account);
+        if let Err(e) = config::storage::save_account_async(account.clone()).await {
+            tracing::error!("保存 IMAP 账户失败: {}", e);
+            let message = utils::i18n::tr_fmt("imap.failed", &[&e]);
+            slint::invoke_from_event_loop(move || {
+                if let Some(window) = weak.upgrade() {
+                    window.set_imap_form_busy(false);
+                    window.set_imap_form_status(message.into());
+                }
+            })
+            .ok();
+            return;
+        }
+        tracing::info!("✅ IMAP 账户已保存: {}", redact_email(account.email()));
+
+        let ui = weak.clone();
+        slint::invoke_from_event_loop(move || {
+            if let Some(window) = ui.upgrade() {
+                window.set_imap_form_busy(false);
+                window.set_imap_form_visible(false);
+            }
+        })
+        .ok();
+
+        // 立即显示账户行（同步中），同步账户信息（获取未读数）后原地更新
+        match first_sync {
+            Some(task) => task.await,
+            None => {
+                slint::invoke_from_event_loop(move || {
+                    if let Some(window) = weak.upgrade() {
+                        update_accounts_ui(&window, account, false);
+                    }
+                })
+                .ok();
+            }
+        }
+    });
+
+    // 已开始退出，未启动测试
+    if !started {
+        window.set_imap_form_busy(false);
+    }
+}
+
+/// 由表单内容构造 IMAP 账户（密码立即加密），校验失败时返回提示文本
+fn imap_account_from_form(settings: &ImapSettings) -> Result<mail::imap::ImapAccount, String> {
+    let email = settings.email.trim().to_string();
+    let host = settings.host.trim().to_string();
+    if email.is_empty() || host.is_empty() || settings.password.is_empty() {
+        return Err(utils::i18n::tr("imap.incomplete").to_string());
+    }
+
+    let security = mail::imap::ImapSecurity::from_setting(&settings.security);
+    let port = match settings.port.trim() {
+        "" => security.default_port(),
+        port => port
+            .parse()
+            .ok()
+            .filter(|&port| port != 0)
+            .ok_or_else(|| utils::i18n::tr("imap.invalid_port").to_string())?,
+    };
+
+    let username = match settings.username.trim() {
+        "" => email.clone(),
+        username => username.to_string(),
+    };
+
+    mail::imap::ImapAccount::new(
+        email,
+        host,
+        port,
+        security,
+        username,
+        &settings.password,
+        settings.idle,
+    )
+    .map_err(|e| utils::i18n::tr_fmt("imap.failed", &[&e]))
+}
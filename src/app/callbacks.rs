@@ -0,0 +1,617 @@
+/// Slint 回调绑定
+///
+/// 窗口按钮、快捷键与账户菜单的回调在这里注册；回调持有窗口的弱引用与 `AppState`，
+/// 具体的账户操作见 `accounts` 模块
+use slint::{ComponentHandle, Global, Model};
+use std::sync::Arc;
+
+use crate::config::storage::StoredAccount;
+use crate::utils::browser::{self, UrlPurpose};
+use crate::utils::redact::redact_email;
+use crate::{MainWindow, Theme, config, mail, sync, tray, ui, utils};
+
+use super::AppState;
+use super::accounts::{
+    check_imap_form, edit_account, find_stored_account, load_gmail_labels, ports_occupied_message,
+    reauthorize_account, reauthorize_all, refresh_account_token, remove_account, set_account_group,
+    set_alert_threshold, set_gmail_label, show_token_details,
+};
+use super::rows::{
+    clear_accounts_ui, refit_window_near_tray, refresh_placeholder_avatars, regroup, set_compact,
+};
+
+/// 绑定所有 Slint 回调
+pub fn bind_callbacks(main_window: &MainWindow, state: &Arc<AppState>) {
+    // 主题切换
+    main_window.on_theme_toggled({
+        let weak = main_window.as_weak();
+        move || {
+            tracing::info!("[回调] 主题切换按钮被点击");
+            if let Some(window) = weak.upgrade() {
+                // 切换主题
+                let current_is_dark = Theme::get(&window).get_is_dark();
+                let new_is_dark = !current_is_dark;
+                Theme::get(&window).set_is_dark(new_is_dark);
+                refresh_placeholder_avatars(&window);
+                tracing::info!(
+                    "主题切换: {} -> {}",
+                    if current_is_dark { "dark" } else { "light" },
+                    if new_is_dark { "dark" } else { "light" }
+                );
+
+                // 持久化主题偏好（后台合并写入，连续切换只写一次文件）
+                let theme = if new_is_dark { "dark" } else { "light" };
+                config::persist_async(move |cfg| cfg.app.theme = theme.to_string());
+            }
+        }
+    });
+
+    // 提示条按钮（如撤销删除）
+    main_window.on_snackbar_action_clicked({
+        let weak = main_window.as_weak();
+        move || {
+            if let Some(window) = weak.upgrade() {
+                ui::run_snackbar_action(&window);
+            }
+        }
+    });
+
+    // 固定窗口切换
+    main_window.on_pin_toggled({
+        let weak = main_window.as_weak();
+        move || {
+            tracing::info!("[回调] 固定窗口按钮被点击");
+            if let Some(window) = weak.upgrade() {
+                // 置顶标志与自动隐藏抑制都由同一个 pinned 属性驱动，切换一次即同时生效
+                let new_pinned = !window.get_pinned();
+                window.set_pinned(new_pinned);
+                tracing::info!("窗口固定: {}", new_pinned);
+
+                // 持久化固定偏好
+                config::persist_async(move |cfg| cfg.app.pinned = new_pinned);
+            }
+        }
+    });
+
+    // 紧凑模式切换
+    main_window.on_compact_toggled({
+        let weak = main_window.as_weak();
+        move || {
+            if let Some(window) = weak.upgrade() {
+                let compact = !window.get_compact();
+                set_compact(&window, compact);
+                tracing::info!("紧凑模式: {}", compact);
+
+                config::persist_async(move |cfg| cfg.app.compact = compact);
+            }
+        }
+    });
+
+    // 凭据错误页面：重新授权全部账户 / 查看帮助
+    main_window.on_credentials_reauthorize_all({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move || {
+            tracing::info!("[回调] 重新授权全部账户");
+            if let Some(window) = weak.upgrade() {
+                reauthorize_all(&window, &state);
+            }
+        }
+    });
+
+    main_window.on_credentials_help({
+        move || {
+            tracing::info!("[回调] 凭据错误帮助被点击");
+            browser::open_url(
+                "https://github.com/Keriyar/NanoMail#凭据自检",
+                UrlPurpose::Link,
+            );
+        }
+    });
+
+    // 手动添加 IMAP 账户（表单：测试连接 / 保存 / 取消）
+    main_window.on_imap_test({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |settings| check_imap_form(&weak, &state, settings, false)
+    });
+
+    main_window.on_imap_save({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |settings| check_imap_form(&weak, &state, settings, true)
+    });
+
+    main_window.on_imap_cancel({
+        let weak = main_window.as_weak();
+        move || {
+            if let Some(window) = weak.upgrade() {
+                window.set_imap_form_visible(false);
+                refit_window_near_tray(&window);
+            }
+        }
+    });
+
+    // 添加账户（OAuth2 服务打开浏览器授权，IMAP 显示手动填写的表单）
+    main_window.on_add_account_clicked({
+        let window_weak = main_window.as_weak();
+        let state = state.clone();
+
+        move |kind| {
+            tracing::info!("[回调] 添加账户按钮被点击: {}", kind);
+            if state.workers.is_shutting_down() {
+                return;
+            }
+
+            if kind == "imap" {
+                if let Some(window) = window_weak.upgrade() {
+                    window.set_imap_form_status("".into());
+                    window.set_imap_form_busy(false);
+                    window.set_imap_form_visible(true);
+                    refit_window_near_tray(&window);
+                }
+                return;
+            }
+
+            let weak = window_weak.clone();
+            let task_state = state.clone();
+            let Some(provider) = state.providers.get(&kind) else {
+                tracing::error!("未注册 {} 提供方，无法添加账户", kind);
+                return;
+            };
+
+            // 授权结束前禁用添加按钮，避免重复打开授权页面
+            if let Some(window) = window_weak.upgrade() {
+                window.set_authorizing(true);
+            }
+
+            let started = state.workers.spawn("添加账户", async move {
+                // 执行 OAuth2 认证
+                let outcome = provider.authenticate().await;
+
+                // 无论结果如何都立即恢复添加按钮
+                let declined = matches!(outcome, Ok(None));
+                let ui = weak.clone();
+                slint::invoke_from_event_loop(move || {
+                    if let Some(window) = ui.upgrade() {
+                        window.set_authorizing(false);
+                        if declined {
+                            ui::show_snackbar(&window, utils::i18n::tr("auth.declined"));
+                        }
+                    }
+                })
+                .ok();
+
+                match outcome {
+                    Ok(None) => tracing::info!("用户取消了授权，未添加账户"),
+                    Ok(Some(account)) => {
+                        tracing::info!("✅ OAuth2 成功: {}", redact_email(account.email()));
+
+                        // 立即显示账户行（同步中），同步账户信息（获取未读数）后原地更新
+                        task_state.first_sync(&weak, provider, account).await;
+                    }
+                    Err(e) => {
+                        tracing::error!("❌ OAuth2 失败: {}", e);
+                        let message = ports_occupied_message(&e)
+                            .unwrap_or_else(|| utils::i18n::tr_fmt("auth.failed", &[&e]));
+                        // 消息框是模态的，放到独立线程避免阻塞后台任务
+                        std::thread::spawn(move || utils::dialog::show_error(&message));
+                    }
+                }
+            });
+
+            // 已开始退出，未启动授权
+            if !started && let Some(window) = window_weak.upgrade() {
+                window.set_authorizing(false);
+            }
+        }
+    });
+
+    // 打开 Gmail
+    main_window.on_open_gmail_clicked({
+        move || {
+            tracing::info!("[回调] 打开 Gmail 按钮被点击");
+            // 工具栏按钮始终打开网页版
+            open_gmail(OpenWith::Browser);
+        }
+    });
+
+    // 反馈按钮
+    main_window.on_feedback_clicked({
+        move || {
+            tracing::info!("[回调] 反馈按钮被点击");
+            let url = "https://github.com/Keriyar/NanoMail";
+            browser::open_url(url, UrlPurpose::Link);
+        }
+    });
+
+    // 打开日志目录
+    main_window.on_open_logs_clicked({
+        move || {
+            tracing::info!("[回调] 打开日志目录按钮被点击");
+            utils::logging::open_logs_dir();
+        }
+    });
+
+    // 新版本提示链接：打开发布页
+    main_window.on_update_clicked({
+        move || {
+            tracing::info!("[回调] 新版本提示被点击");
+            let url = config::load()
+                .ok()
+                .and_then(|cfg| cfg.updates.release_url)
+                .unwrap_or_else(|| "https://github.com/Keriyar/NanoMail/releases".to_string());
+            browser::open_url(&url, UrlPurpose::Link);
+        }
+    });
+
+    // 窗口中的"隐藏到托盘"按钮（之前名为退出）
+    // Ctrl+,：打开设置（配置文件保存后自动生效）
+    main_window.on_settings_requested(|| {
+        tracing::info!("[快捷键] 打开设置");
+        open_settings();
+    });
+
+    main_window.on_minimize_clicked({
+        let weak = main_window.as_weak();
+        move || {
+            tracing::info!("[回调] 隐藏到托盘按钮被点击，隐藏窗口并释放资源");
+            if let Some(window) = weak.upgrade() {
+                window.hide().ok();
+                // 清空 UI 资源以减少内存占用
+                clear_accounts_ui(&window);
+            }
+        }
+    });
+
+    // 头像重试
+    main_window.on_avatar_retry({
+        move |index| {
+            tracing::info!("[回调] 头像重试: 账户索引 {}", index);
+            // TODO: 阶段4 实现头像重新加载
+        }
+    });
+
+    bind_account_menu(main_window, state);
+}
+
+/// 绑定账户菜单回调（未读计数方式 / 计数清零 / 强调色 / 账户操作）
+fn bind_account_menu(main_window: &MainWindow, state: &Arc<AppState>) {
+    main_window.on_count_mode_changed({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |index, mode| {
+            tracing::info!("[回调] 切换未读计数方式: 账户索引 {} -> {}", index, mode);
+            let mode = mail::oauth::CountMode::from_setting(&mode);
+            if let Some(window) = weak.upgrade() {
+                edit_account(&window, &state, index, |stored| {
+                    stored
+                        .oauth_mut()
+                        .map(|account| account.set_count_mode(mode))
+                        .is_some()
+                });
+            }
+        }
+    });
+
+    main_window.on_reset_counter({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |index| {
+            tracing::info!("[回调] 未读计数清零: 账户索引 {}", index);
+            if let Some(window) = weak.upgrade() {
+                edit_account(&window, &state, index, |stored| {
+                    stored
+                        .oauth_mut()
+                        .map(|account| account.reset_unread_baseline())
+                        .is_some()
+                });
+            }
+        }
+    });
+
+    main_window.on_accent_changed({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |index, accent| {
+            tracing::info!("[回调] 修改强调色: 账户索引 {} -> {}", index, accent);
+            let accent = if accent == "auto" {
+                None
+            } else {
+                match utils::avatar::parse_accent(&accent) {
+                    Some(accent) => Some(accent),
+                    None => {
+                        tracing::warn!("强调色格式无效，已忽略: {:?}", accent);
+                        return;
+                    }
+                }
+            };
+            if let Some(window) = weak.upgrade() {
+                edit_account(&window, &state, index, |stored| {
+                    stored.set_accent(accent);
+                    true
+                });
+            }
+        }
+    });
+
+    // F5：同步全部账户
+    main_window.on_sync_all_requested({
+        let state = state.clone();
+        move || {
+            tracing::info!("[快捷键] 同步全部账户");
+            state.sync_engine.trigger_sync();
+        }
+    });
+
+    main_window.on_token_details_requested({
+        let weak = main_window.as_weak();
+        move |email| {
+            if let Some(window) = weak.upgrade() {
+                show_token_details(&window, &email);
+            }
+        }
+    });
+
+    main_window.on_token_refresh_clicked({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |email| {
+            tracing::info!("[回调] 立即刷新 Token");
+            if let Some(window) = weak.upgrade() {
+                refresh_account_token(&window, &state, email.to_string());
+            }
+        }
+    });
+
+    main_window.on_alert_threshold_edited({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |email, threshold| {
+            if let Some(window) = weak.upgrade() {
+                set_alert_threshold(&window, &state, &email, &threshold);
+            }
+        }
+    });
+
+    main_window.on_group_edited({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |email, group| {
+            if let Some(window) = weak.upgrade() {
+                set_account_group(&window, &state, &email, &group);
+            }
+        }
+    });
+
+    // 分组标题：折叠或展开该组并保存到配置
+    main_window.on_group_toggled({
+        let weak = main_window.as_weak();
+        move |group| {
+            let Some(window) = weak.upgrade() else {
+                return;
+            };
+            let collapsed = ui::groups::toggle(&group);
+            regroup(&window);
+            refit_window_near_tray(&window);
+            config::persist_async(move |cfg| cfg.app.collapsed_groups = collapsed);
+        }
+    });
+
+    // 键盘导航与滚动（跳过折叠组中的账户行，行高随行的种类不同）
+    main_window.on_next_row({
+        let weak = main_window.as_weak();
+        move |current, step| {
+            weak.upgrade().map_or(-1, |window| {
+                let rows: Vec<_> = window.get_accounts().iter().collect();
+                ui::groups::next_row(&rows, current, step)
+            })
+        }
+    });
+
+    main_window.on_row_top({
+        let weak = main_window.as_weak();
+        move |index| {
+            weak.upgrade().map_or(0.0, |window| {
+                let rows: Vec<_> = window.get_accounts().iter().collect();
+                ui::groups::row_top(&rows, index.max(0) as usize, window.get_compact())
+            })
+        }
+    });
+
+    main_window.on_labels_refresh_clicked({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |email| {
+            if let Some(window) = weak.upgrade() {
+                load_gmail_labels(&window, &state, email.to_string(), true);
+            }
+        }
+    });
+
+    // 同步记录页面定时重新读取（相对时间与新的一轮）
+    main_window.on_sync_history_requested({
+        let weak = main_window.as_weak();
+        move || {
+            if let Some(window) = weak.upgrade() {
+                ui::history::apply_sync_history(&window);
+            }
+        }
+    });
+
+    main_window.on_label_selected({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |email, role, index| {
+            if let Some(window) = weak.upgrade() {
+                set_gmail_label(&window, &state, &email, &role, index);
+            }
+        }
+    });
+
+    main_window.on_account_action({
+        let weak = main_window.as_weak();
+        let state = state.clone();
+        move |index, action| {
+            tracing::info!("[回调] 账户操作: 账户索引 {} -> {:?}", index, action);
+            if let Some(window) = weak.upgrade() {
+                on_account_action(&window, &state, index, &action);
+            }
+        }
+    });
+}
+
+/// 账户操作分发（打开收件箱 / 立即同步 / 免打扰 / 重新授权 / 重命名 / 删除）
+///
+/// 免打扰为 `snooze:<时长>`，重命名为 `rename:<新名称>`，分组为 `group:<组名>`（留空表示取消分组），
+/// 垃圾邮件监视为 `spam:on` / `spam:off`，自动回复提示为 `vacation:on` / `vacation:off`，
+/// 实时推送为 `push:on` / `push:off`，其余见 `AccountMenu.action`
+fn on_account_action(window: &MainWindow, state: &Arc<AppState>, index: i32, action: &str) {
+    let Some(row) = window.get_accounts().row_data(index as usize) else {
+        return;
+    };
+    let email = row.email.to_string();
+
+    if let Some(duration) = action.strip_prefix("snooze:") {
+        let until = sync::snooze_deadline(duration, chrono::Local::now());
+        edit_account(window, state, index, |stored| {
+            stored.set_snoozed_until(until);
+            true
+        });
+        return;
+    }
+
+    if let Some(watch) = action.strip_prefix("spam:") {
+        let watch = watch == "on";
+        edit_account(window, state, index, |stored| stored.set_watch_spam(watch));
+        return;
+    }
+
+    if let Some(watch) = action.strip_prefix("vacation:") {
+        let watch = watch == "on";
+        edit_account(window, state, index, |stored| {
+            stored.set_watch_vacation(watch)
+        });
+        return;
+    }
+
+    if let Some(push) = action.strip_prefix("push:") {
+        let push = push == "on";
+        edit_account(window, state, index, |stored| stored.set_push(push));
+        return;
+    }
+
+    if let Some(people) = action.strip_prefix("people:") {
+        let on = people == "on";
+        edit_account(window, state, index, |stored| stored.set_people_only(on));
+        return;
+    }
+
+    if let Some(group) = action.strip_prefix("group:") {
+        set_account_group(window, state, &email, group);
+        return;
+    }
+
+    if let Some(name) = action.strip_prefix("rename:") {
+        let name = name.trim();
+        if name.is_empty() || name == row.display_name.as_str() {
+            return;
+        }
+        edit_account(window, state, index, |stored| {
+            stored.set_display_name(name.to_string());
+            true
+        });
+        if let Ok(accounts) = config::storage::load_accounts() {
+            tray::summary::set_accounts(&accounts);
+        }
+        ui::show_snackbar(window, &utils::i18n::tr_fmt("account.renamed", &[&name]));
+        return;
+    }
+
+    match action {
+        "open" => {
+            if let Some(account) = find_stored_account(&email) {
+                open_gmail_for(&account, &state.providers, state.open_with());
+            }
+        }
+        "sync" => state.sync_engine.retry_now(&email),
+        "reauth" => reauthorize_account(window, state, email),
+        "remove" => remove_account(window, state, email),
+        "details" => {
+            show_token_details(window, &email);
+            load_gmail_labels(window, state, email, false);
+        }
+        _ => tracing::warn!("未知的账户操作: {:?}", action),
+    }
+}
+
+/// 打开账户的方式（`[app] open_with`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OpenWith {
+    /// 在浏览器中打开网页版收件箱
+    #[default]
+    Browser,
+
+    /// 以账户邮箱为收件人调用默认邮件程序（`mailto:`）
+    Mailto,
+}
+
+impl OpenWith {
+    /// 解析配置值（无法识别时使用浏览器）
+    pub fn from_setting(setting: &str) -> Self {
+        match setting.trim().to_ascii_lowercase().as_str() {
+            "mailto" => Self::Mailto,
+            _ => Self::Browser,
+        }
+    }
+}
+
+/// 打开第一个账户（没有账户时在浏览器中打开 Gmail 默认收件箱）
+pub fn open_gmail(open_with: OpenWith) {
+    let first = config::storage::load_accounts()
+        .ok()
+        .and_then(|accounts| accounts.into_iter().next());
+    match first {
+        Some(account) => open_gmail_for(
+            &account,
+            &mail::ProviderRegistry::with_defaults(),
+            open_with,
+        ),
+        None => {
+            browser::open_url(&mail::gmail::urls::inbox_url(None), UrlPurpose::Link);
+        }
+    }
+}
+
+/// 打开账户：浏览器中的收件箱，或默认邮件程序（系统没有 `mailto:` 处理程序时退回浏览器）
+pub fn open_gmail_for(
+    account: &StoredAccount,
+    providers: &mail::ProviderRegistry,
+    open_with: OpenWith,
+) {
+    if open_with == OpenWith::Mailto {
+        match webbrowser::open(&format!("mailto:{}", account.email())) {
+            Ok(()) => return,
+            Err(e) => tracing::warn!("无法打开默认邮件程序，改用浏览器: {}", e),
+        }
+    }
+
+    if let Some(provider) = providers.for_account(account) {
+        browser::open_url(&provider.open_inbox_url(account), UrlPurpose::Link);
+    }
+}
+
+/// 用默认程序打开配置文件
+fn open_settings() {
+    match config::config_path() {
+        Ok(path) => {
+            if let Err(e) = std::process::Command::new(utils::app_info::FILE_OPENER)
+                .arg(&path)
+                .spawn()
+            {
+                tracing::error!("打开配置文件失败: {}", e);
+            }
+        }
+        Err(e) => tracing::error!("获取配置文件路径失败: {}", e),
+    }
+}
@@ -0,0 +1,467 @@
+/// 应用层：界面与同步引擎、托盘、配置之间的衔接
+///
+/// `AppState` 保存需要跨线程共享的句柄（后台任务、邮件服务提供方、同步引擎、托盘命令通道
+/// 与配置快照），以 `Arc` 持有，可交给同步回调、托盘命令线程与配置监听线程。
+/// Slint 窗口及其账户模型只能在事件循环线程中访问，不放入其中：需要时由调用方传入窗口，
+/// 或在闭包中持有 `slint::Weak` 并通过 `upgrade_in_event_loop` 访问。
+/// 各账户的汇总状态（未读合计、应用状态）由 `tray::summary` 维护，窗口隐藏后仍然有效
+mod accounts;
+mod callbacks;
+mod rows;
+mod status;
+mod tray_commands;
+
+use slint::{ComponentHandle, Global};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::config::storage::StoredAccount;
+use crate::config::{self, Config};
+use crate::mail::{MailProvider, ProviderRegistry, RetryContext};
+use crate::sync::{SyncEngine, SyncEvent};
+use crate::tray::TrayCommand;
+use crate::ui::coalesce::Coalescer;
+use crate::utils::tasks::Workers;
+use crate::{Account, MainWindow, Theme, mail, notification, tray, ui, utils};
+
+pub use callbacks::bind_callbacks;
+pub use status::apply_app_status;
+pub use tray_commands::spawn_tray_commands;
+
+/// 跨线程共享的应用状态
+pub struct AppState {
+    /// 界面发起的后台任务（退出时统一取消）
+    pub workers: Workers,
+
+    /// 邮件服务提供方
+    pub providers: Arc<ProviderRegistry>,
+
+    /// 同步引擎
+    pub sync_engine: SyncEngine,
+
+    /// 托盘命令通道（其他实例的消息也转发为托盘命令）
+    pub tray_tx: tray::CommandSender,
+
+    /// 配置快照（启动时读取，配置文件热重载后更新）
+    config: Mutex<Config>,
+
+    /// 等待合并应用到界面的同步结果
+    row_updates: Arc<Coalescer<rows::RowUpdate>>,
+
+    /// 等待依次重新授权的账户（凭据错误页面的"重新授权全部账户"）
+    reauth_queue: Mutex<VecDeque<String>>,
+}
+
+impl AppState {
+    /// 创建应用状态（同步引擎尚未启动，见 [`AppState::start_sync`]）
+    pub fn new(
+        rt_handle: tokio::runtime::Handle,
+        tray_tx: tray::CommandSender,
+        config: Config,
+    ) -> Arc<Self> {
+        let providers = Arc::new(ProviderRegistry::with_defaults());
+        Arc::new(Self {
+            workers: Workers::new(rt_handle.clone()),
+            sync_engine: SyncEngine::new(rt_handle, providers.clone()),
+            providers,
+            tray_tx,
+            config: Mutex::new(config),
+            row_updates: Arc::new(Coalescer::default()),
+            reauth_queue: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    /// 当前的配置快照
+    pub fn config(&self) -> MutexGuard<'_, Config> {
+        self.config.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 按配置快照与已保存的账户初始化窗口（主题、固定状态、界面语言与账户列表）
+    ///
+    /// 同时应用影响同步结果的进程级设置，须在创建托盘与启动同步之前调用
+    pub fn init_window(&self, window: &MainWindow) {
+        let saved_accounts = match config::storage::load_accounts() {
+            Ok(accounts) if !accounts.is_empty() => {
+                tracing::info!("✅ 从文件加载 {} 个账户", accounts.len());
+                accounts
+            }
+            Ok(_) => {
+                tracing::info!("📭 无已保存账户");
+                vec![]
+            }
+            Err(e) => {
+                tracing::warn!("⚠️ 加载账户失败: {}, 使用空列表", e);
+                vec![]
+            }
+        };
+
+        // 托盘提示与复制摘要使用的账户状态
+        tray::summary::set_accounts(&saved_accounts);
+
+        // 完成上次退出前未到撤销期的删除
+        accounts::finish_pending_removals(self);
+
+        // 无法打开浏览器时（链接已复制）：窗口显示时用提示条说明，否则用托盘提示
+        let weak = window.as_weak();
+        utils::browser::set_notice_handler(move |message| {
+            weak.upgrade_in_event_loop(move |window| {
+                if window.window().is_visible() {
+                    ui::show_snackbar(&window, &message);
+                } else {
+                    tray::flash_tooltip(&message);
+                }
+            })
+            .ok();
+        });
+
+        // 界面开关等设置在后台写入配置文件，失败时（如文件被编辑器锁定）提示设置未保存
+        let weak = window.as_weak();
+        config::set_persist_error_handler(move || {
+            weak.upgrade_in_event_loop(|window| {
+                let message = utils::i18n::tr("config.persist_failed");
+                if window.window().is_visible() {
+                    ui::show_snackbar(&window, message);
+                } else {
+                    tray::flash_tooltip(message);
+                }
+            })
+            .ok();
+        });
+
+        // 清理已删除账户与旧格式的头像缓存（读取账户文件失败时跳过，避免误删）
+        if let Ok(accounts) = config::storage::load_accounts() {
+            let emails: Vec<String> = accounts.iter().map(|a| a.email().to_string()).collect();
+            utils::avatar::cleanup(&emails);
+        }
+
+        // 分组的折叠状态与紧凑模式（排列账户行、计算列表高度前设置）
+        ui::groups::set_collapsed(&self.config().app.collapsed_groups);
+        window.set_compact(self.config().app.compact);
+
+        // 先显示上次保存的同步结果，首轮同步完成后替换
+        let max_age = self.cached_counts_max_age();
+        let slint_accounts: Vec<Account> = saved_accounts
+            .into_iter()
+            .map(|acc| {
+                let mut row: Account = acc.into();
+                ui::apply_cached_sync(&mut row, max_age);
+                row
+            })
+            .collect();
+        rows::set_rows(window, slint_accounts);
+
+        // 初始应用状态为 Normal（绿色 N）
+        window.set_app_status(tray::summary::AppStatus::Normal.as_str().into());
+        tracing::debug!("应用状态初始化: Normal (绿色 N)");
+
+        let cfg = self.config();
+        let is_dark = cfg.app.theme == "dark";
+        Theme::get(window).set_is_dark(is_dark);
+        tracing::info!("主题初始化: {}", if is_dark { "dark" } else { "light" });
+
+        // 占位符头像随主题配色，并按窗口缩放比例栅格化
+        rows::refresh_placeholder_avatars(window);
+        rows::watch_scale_factor(window);
+
+        // 系统高对比度与动画设置（之后的变更由 main 中的监听更新）
+        let a11y = utils::accessibility::current();
+        ui::apply_accessibility(window, a11y);
+        tracing::info!("辅助功能设置: {:?}", a11y);
+
+        // 恢复窗口固定状态（always-on-top 绑定在 pinned 属性上）
+        window.set_pinned(cfg.app.pinned);
+        tracing::info!("窗口固定状态初始化: {}", cfg.app.pinned);
+
+        // 界面语言（需在创建托盘前设置）
+        utils::i18n::set_language(utils::i18n::Language::from_setting(&cfg.app.language));
+        utils::i18n::apply_to_ui(window);
+
+        // Gmail 服务地址覆盖、计数上限等（需在首次同步前设置）
+        crate::sync::configure(&cfg);
+
+        // 接着显示上次保存的同步记录
+        if cfg.app.persist_sync_history {
+            crate::sync::history::load_saved(config::state::global());
+        }
+
+        // 托盘未读圆点的分档颜色（首次刷新状态时生效）
+        tray::badge::configure(&cfg.tray);
+
+        // 配置文件中的未知字段、无效值与冲突的设置（记录警告并在标题栏下方列出位置）
+        let issues = config::validate::check_at_startup();
+        window.set_config_issues(config::validate::keys(&issues).into());
+
+        // 按流量计费的网络下节流同步时在操作栏显示提示，随系统连接提示更新
+        window.set_metered(mail::metered::is_reducing());
+        let weak = window.as_weak();
+        utils::connectivity::set_metered_handler(move |_| {
+            weak.upgrade_in_event_loop(|window| window.set_metered(mail::metered::is_reducing()))
+                .ok();
+        });
+    }
+
+    /// 启动时显示的上次同步结果的有效期（配置值过大时视为永不过期）
+    pub fn cached_counts_max_age(&self) -> chrono::Duration {
+        let hours = self.config().app.cached_counts_max_age_hours;
+        i64::try_from(hours)
+            .ok()
+            .and_then(chrono::Duration::try_hours)
+            .unwrap_or(chrono::Duration::MAX)
+    }
+
+    /// 账户行与托盘"打开"使用的方式
+    pub fn open_with(&self) -> callbacks::OpenWith {
+        callbacks::OpenWith::from_setting(&self.config().app.open_with)
+    }
+
+    /// 启动同步引擎：应用同步间隔与通知设置，同步结果合并后更新窗口中的账户行
+    pub fn start_sync(&self, window: &MainWindow) {
+        {
+            let cfg = self.config();
+            self.sync_engine.set_interval(cfg.app.sync_interval);
+            notification::set_enabled(cfg.notifications.enabled);
+            notification::set_detail(notification::NotificationDetail::from_setting(
+                &cfg.notifications.detail,
+            ));
+            notification::set_combine(cfg.notifications.combine);
+        }
+
+        // 点击多个账户的合并通知时打开窗口
+        let tray_tx = self.tray_tx.clone();
+        notification::set_open_window_handler(move || {
+            tray::send_command(&tray_tx, TrayCommand::ShowWindow);
+        });
+
+        // 回调只持有合并队列与窗口的弱引用，不持有 AppState（同步引擎本身属于 AppState）
+        let row_updates = self.row_updates.clone();
+        let weak = window.as_weak();
+        self.sync_engine.start(move |event| {
+            let (email, change) = rows::row_change(event);
+            rows::schedule(&row_updates, &weak, &email, change);
+        });
+    }
+
+    /// 凭据自检通过后启动同步引擎
+    ///
+    /// 自检需要派生本机密钥（Argon2，几十毫秒），在阻塞线程中进行，之后加解密 Token 不再占用工作线程；
+    /// 自检失败时不启动同步，窗口显示凭据错误页面（见 `accounts::reauthorize_all`）
+    pub fn start_sync_after_self_test(self: &Arc<Self>, window: &MainWindow) {
+        let state = self.clone();
+        let weak = window.as_weak();
+        self.workers.spawn("凭据自检", async move {
+            let failure = tokio::task::spawn_blocking(config::self_test::run_at_startup)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!("凭据自检任务异常结束: {}", e);
+                    None
+                });
+            weak.upgrade_in_event_loop(move |window| match failure {
+                None => state.start_sync(&window),
+                Some(failure) => window.set_credentials_error(failure.describe().into()),
+            })
+            .ok();
+        });
+    }
+
+    /// 加入等待依次重新授权的账户（替换原有的队列）
+    fn queue_reauth(&self, emails: Vec<String>) {
+        *self.reauth_queue.lock().unwrap_or_else(|e| e.into_inner()) = emails.into();
+    }
+
+    /// 下一个等待重新授权的账户；`proceed` 为 false（用户取消或授权失败）时清空队列
+    fn next_reauth(&self, proceed: bool) -> Option<String> {
+        let mut queue = self.reauth_queue.lock().unwrap_or_else(|e| e.into_inner());
+        if !proceed {
+            queue.clear();
+        }
+        queue.pop_front()
+    }
+
+    /// 显示新添加的账户并立即同步（返回的任务交给后台运行）
+    ///
+    /// 授权或登录完成后立即插入显示同步中的账户行，不必等待同步与头像下载；首次同步结束后
+    /// 与同步引擎的结果一样合并应用到该行，失败时显示错误状态与相应的提示，照常可以重试
+    pub fn first_sync(
+        &self,
+        weak: &slint::Weak<MainWindow>,
+        provider: Arc<dyn MailProvider>,
+        account: StoredAccount,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let row_updates = self.row_updates.clone();
+        let weak = weak.clone();
+        async move {
+            let email = account.email().to_string();
+            let row = account.clone();
+            let ui = weak.clone();
+            slint::invoke_from_event_loop(move || {
+                if let Some(window) = ui.upgrade() {
+                    rows::update_accounts_ui(&window, row, true);
+                }
+            })
+            .ok();
+
+            let event = match provider.sync(&account, &RetryContext::default()).await {
+                Ok(outcome) => SyncEvent::Synced(email, outcome.info),
+                Err(failure) => SyncEvent::Failed(email, failure),
+            };
+            let (email, change) = rows::row_change(event);
+            rows::schedule(&row_updates, &weak, &email, change);
+        }
+    }
+
+    /// 后台检查更新（每天最多一次，不阻塞启动）
+    pub fn check_updates(&self, window: &MainWindow) {
+        let weak = window.as_weak();
+        self.workers.spawn("检查更新", async move {
+            if let Some(update) = utils::updates::check().await {
+                tracing::info!("发现新版本: {}", update.tag);
+                weak.upgrade_in_event_loop(move |window| {
+                    window.set_update_version(update.tag.into());
+                    window.set_update_available(true);
+                })
+                .ok();
+            }
+        });
+    }
+
+    /// 处理配置热重载事件（在配置监听线程中调用）
+    pub fn apply_config_event(
+        &self,
+        event: config::watcher::ConfigEvent,
+        window_weak: &slint::Weak<MainWindow>,
+    ) {
+        match event {
+            config::watcher::ConfigEvent::Changed(delta) => {
+                match config::load() {
+                    Ok(cfg) => *self.config() = cfg,
+                    Err(e) => tracing::warn!("重新读取配置失败: {:#}", e),
+                }
+
+                if let Some(secs) = delta.sync_interval {
+                    self.sync_engine.set_interval(secs);
+                }
+                if let Some(level) = &delta.log_level
+                    && let Err(e) = utils::logging::reload_level(level)
+                {
+                    tracing::warn!("应用日志级别失败: {:#}", e);
+                }
+                if let Some(enabled) = delta.notifications_enabled {
+                    notification::set_enabled(enabled);
+                }
+                if let Some(detail) = &delta.notification_detail {
+                    notification::set_detail(notification::NotificationDetail::from_setting(
+                        detail,
+                    ));
+                }
+                if let Some(combine) = delta.notifications_combine {
+                    notification::set_combine(combine);
+                }
+                if let Some(ceiling) = delta.count_ceiling {
+                    mail::gmail::api::set_count_ceiling(ceiling);
+                }
+                if let Some(limit) = delta.api_rate_warning {
+                    mail::quota::set_rate_warning(limit);
+                }
+                if let Some(budget) = delta.retry_budget {
+                    mail::retry::set_round_budget(budget);
+                }
+                if let Some(patterns) = &delta.automated_sender_patterns {
+                    mail::gmail::people::set_patterns(patterns);
+                }
+                if let Some(respect) = delta.respect_metered {
+                    mail::metered::set_respect(respect);
+                    window_weak
+                        .upgrade_in_event_loop(|window| {
+                            window.set_metered(mail::metered::is_reducing())
+                        })
+                        .ok();
+                }
+                if let Some(automation) = &delta.automation {
+                    notification::webhook::configure(automation);
+                }
+                if let Some(tray_config) = delta.tray.clone() {
+                    window_weak
+                        .upgrade_in_event_loop(move |window| {
+                            tray::configure_badge(&tray_config);
+                            apply_app_status(&window);
+                        })
+                        .ok();
+                }
+                if let Some(groups) = &delta.collapsed_groups {
+                    ui::groups::set_collapsed(groups);
+                    window_weak
+                        .upgrade_in_event_loop(|window| {
+                            if window.window().is_visible() {
+                                rows::regroup(&window);
+                                rows::refit_window_near_tray(&window);
+                            }
+                        })
+                        .ok();
+                }
+                if let Some(language) = delta.language {
+                    utils::i18n::set_language(utils::i18n::Language::from_setting(&language));
+                    window_weak
+                        .upgrade_in_event_loop(|window| {
+                            utils::i18n::apply_to_ui(&window);
+                            tray::refresh_language();
+                            apply_app_status(&window);
+                        })
+                        .ok();
+                }
+                if let Some(compact) = delta.compact {
+                    window_weak
+                        .upgrade_in_event_loop(move |window| rows::set_compact(&window, compact))
+                        .ok();
+                }
+                if let Some(theme) = delta.theme {
+                    window_weak
+                        .upgrade_in_event_loop(move |window| {
+                            Theme::get(&window).set_is_dark(theme == "dark");
+                            rows::refresh_placeholder_avatars(&window);
+                            tracing::info!("主题已按配置文件切换为: {}", theme);
+                        })
+                        .ok();
+                }
+            }
+            config::watcher::ConfigEvent::AccountsChanged(delta) => {
+                let accounts = match config::storage::load_accounts() {
+                    Ok(accounts) => accounts,
+                    Err(e) => {
+                        tracing::warn!("重新读取账户失败: {:#}", e);
+                        return;
+                    }
+                };
+                tray::summary::set_accounts(&accounts);
+
+                // 窗口隐藏时账户列表已清空，下次显示时会重新加载
+                let max_age = self.cached_counts_max_age();
+                window_weak
+                    .upgrade_in_event_loop(move |window| {
+                        if window.window().is_visible() {
+                            rows::reload_accounts_ui(&window, max_age);
+                            rows::refit_window_near_tray(&window);
+                        }
+                        apply_app_status(&window);
+                    })
+                    .ok();
+
+                // 新增或修改（如重新启用）的账户立即同步，删除的账户下一轮起不再同步
+                if !delta.added.is_empty() || !delta.changed.is_empty() {
+                    self.sync_engine.trigger_sync();
+                }
+            }
+            config::watcher::ConfigEvent::Invalid(message) => {
+                // toml 错误信息可能有多行，提示条只显示首行，完整内容见日志
+                let first_line = message.lines().next().unwrap_or_default().to_string();
+                window_weak
+                    .upgrade_in_event_loop(move |window| {
+                        ui::show_snackbar(
+                            &window,
+                            &utils::i18n::tr_fmt("config.invalid", &[&first_line]),
+                        );
+                    })
+                    .ok();
+            }
+        }
+    }
+}
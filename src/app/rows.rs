@@ -0,0 +1,743 @@
+/// 账户行（Slint 账户模型）的更新
+///
+/// 同步事件在同步线程中先转换为对 `RowUpdate` 的修改并按邮箱合并，短暂延迟后由事件循环
+/// 一次性应用：`diff_row` 计算每行实际需要的修改，只有确有变化的行才写回模型
+use slint::{ComponentHandle, Global, Model, TimerMode, VecModel};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::crypto::CredentialError;
+use crate::config::storage::StoredAccount;
+use crate::mail::AccountSyncInfo;
+use crate::sync::SyncEvent;
+use crate::ui::SyncState;
+use crate::ui::coalesce::Coalescer;
+use crate::utils::redact::redact_email;
+use crate::{Account, MainWindow, Theme, tray, ui, utils};
+
+use super::status::apply_app_status;
+
+/// 同步结果合并的时间窗口（窗口内的多个结果只更新一次界面）
+const ROW_UPDATE_DELAY: Duration = Duration::from_millis(100);
+
+/// 窗口缩放比例的检查间隔（Slint 没有缩放比例变化的回调，窗口显示期间定期检查）
+const SCALE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+thread_local! {
+    /// 检查缩放比例的定时器（需在事件循环线程中持有）
+    static SCALE_TIMER: slint::Timer = slint::Timer::default();
+}
+
+/// 对等待应用的同步结果的修改
+pub type RowChange = Box<dyn FnOnce(&mut RowUpdate) + Send>;
+
+/// 单个账户行等待应用的同步结果
+#[derive(Debug, Default)]
+pub struct RowUpdate {
+    /// 是否正在同步
+    pub loading: bool,
+
+    /// 最新的同步结果（`None` 表示保留原有数据）
+    pub info: Option<AccountSyncInfo>,
+
+    /// 账户提示（随同步结果一起更新；`None` 表示清除）
+    pub notice: Option<&'static str>,
+}
+
+/// 同步结果对账户行的修改（字段为 `None` 表示不变）
+#[derive(Debug, Default, PartialEq)]
+struct RowDiff {
+    loading: Option<bool>,
+    unread_count: Option<i32>,
+    sync_state: Option<SyncState>,
+    notice: Option<&'static str>,
+
+    /// 重新加载头像（缓存路径，为空时使用缓存头像或占位符）
+    avatar: Option<String>,
+
+    /// 同步成功，刷新未读数走势
+    refresh_history: bool,
+
+    /// 垃圾邮件说明（本轮未检查垃圾邮件时不变）
+    spam_caption: Option<String>,
+
+    /// 自动回复提示（本轮未检查自动回复时不变）
+    vacation: Option<String>,
+
+    /// 真人来信过滤说明（只随成功的同步结果更新）
+    filter_caption: Option<String>,
+
+    /// 收到本轮同步结果后不再标注为上次保存的结果；同步成功后取消过期灰显
+    cached: Option<bool>,
+    stale: Option<bool>,
+}
+
+impl RowDiff {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 把同步事件转换为对账户行的修改（在同步线程中调用，同时更新托盘摘要）
+pub fn row_change(event: SyncEvent) -> (String, RowChange) {
+    match event {
+        SyncEvent::Started(email) => (email, Box::new(|row| row.loading = true)),
+        SyncEvent::Synced(email, sync_info) => {
+            tracing::info!(
+                "[DEBUG-UNREAD] 回调收到: email={}, unread_count={}",
+                redact_email(&email),
+                sync_info.unread()
+            );
+
+            tray::summary::update(&sync_info);
+
+            (
+                email,
+                Box::new(move |row| {
+                    row.loading = false;
+                    row.info = Some(sync_info);
+                    row.notice = None;
+                }),
+            )
+        }
+        SyncEvent::Failed(email, failure) => {
+            tracing::error!("同步账户失败: {} -> {}", redact_email(&email), failure);
+
+            // 单轮失败只将应用标为"降级"，连续失败或登录错误才升级为"错误"
+            tray::summary::record_failure(&email, &failure);
+
+            // 请求仅超过快速截止时间但网络可达：网络缓慢，保留账户原有数据
+            // 构造只有错误信息的结果以更新 UI（标为同步失败）
+            let info = (!failure.is_slow())
+                .then(|| AccountSyncInfo::failed(email.clone(), failure.message.clone()));
+
+            // 邮件服务被停用或凭据无法解密：显示明确的提示，而不是笼统的同步失败
+            let notice = match failure.credential_error() {
+                Some(CredentialError::ForeignMachine) => {
+                    Some(utils::i18n::tr("account.foreign_credentials"))
+                }
+                Some(CredentialError::Unreadable) => {
+                    Some(utils::i18n::tr("account.unreadable_credentials"))
+                }
+                Some(CredentialError::Unencrypted) => {
+                    Some(utils::i18n::tr("account.unencrypted_credentials"))
+                }
+                None => failure
+                    .is_account_unavailable()
+                    .then(|| utils::i18n::tr("account.unavailable")),
+            };
+
+            (
+                email,
+                Box::new(move |row| {
+                    row.loading = false;
+                    if info.is_some() {
+                        row.info = info;
+                        row.notice = notice;
+                    }
+                }),
+            )
+        }
+        SyncEvent::FailedQuietly(email) => (email, Box::new(|row| row.loading = false)),
+    }
+}
+
+/// 合并短时间内的多个结果，由一次事件循环回调统一更新界面（可从任意线程调用）
+pub fn schedule(
+    row_updates: &Arc<Coalescer<RowUpdate>>,
+    weak: &slint::Weak<MainWindow>,
+    email: &str,
+    change: RowChange,
+) {
+    if row_updates.update(email, change) {
+        let row_updates = row_updates.clone();
+        let weak = weak.clone();
+        slint::invoke_from_event_loop(move || {
+            slint::Timer::single_shot(ROW_UPDATE_DELAY, move || {
+                let updates = row_updates.drain();
+                if let Some(window) = weak.upgrade() {
+                    apply_row_updates(&window, updates);
+                }
+            });
+        })
+        .ok();
+    }
+}
+
+/// 合并后一次性应用同步结果（同一邮箱只保留最新的结果），并重新计算一次应用状态
+fn apply_row_updates(window: &MainWindow, updates: Vec<(String, RowUpdate)>) {
+    if updates.is_empty() {
+        return;
+    }
+    tracing::debug!("应用 {} 个账户的同步结果", updates.len());
+
+    // 按邮箱查找账户行（同步过程中账户行的位置可能变化）
+    let rows = window.get_accounts();
+    for (email, update) in updates {
+        let Some((index, mut row)) = rows
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.email.as_str() == email)
+        else {
+            continue;
+        };
+
+        let diff = diff_row(&row, &update);
+        if diff.is_empty() {
+            continue;
+        }
+        if let Some(info) = &update.info {
+            match &info.health.error {
+                Some(error_msg) => {
+                    tracing::error!("❌ 账户 {} 同步失败: {}", redact_email(&email), error_msg)
+                }
+                None => tracing::debug!(
+                    "更新账户 {} 未读数: {} -> {}",
+                    redact_email(&email),
+                    row.unread_count,
+                    info.unread()
+                ),
+            }
+        }
+        apply_diff(&mut row, diff);
+        rows.set_row_data(index, row);
+    }
+    refresh_groups(window);
+    apply_app_status(window);
+}
+
+/// 计算同步结果对账户行的修改
+///
+/// - 同步中标记与提示只在与当前值不同时修改
+/// - 同步失败时保留原有未读数，只标记错误；失败结果没有头像信息，保留原来的头像
+fn diff_row(row: &Account, update: &RowUpdate) -> RowDiff {
+    let mut diff = RowDiff {
+        loading: (row.is_loading != update.loading).then_some(update.loading),
+        ..RowDiff::default()
+    };
+
+    let Some(info) = &update.info else {
+        return diff;
+    };
+
+    if let Some(unread) = info.badge_count() {
+        diff.refresh_history = true;
+        let unread = unread as i32;
+        diff.unread_count = (row.unread_count != unread).then_some(unread);
+        diff.stale = row.stale.then_some(false);
+        let caption = ui::filter_caption(info.counters.people_filter);
+        diff.filter_caption = (row.filter_caption.as_str() != caption).then_some(caption);
+    }
+    diff.cached = row.cached.then_some(false);
+    diff.avatar = info.avatar_update().map(str::to_string);
+    let state = if info.has_error() {
+        SyncState::Error
+    } else {
+        SyncState::Synced
+    };
+    diff.sync_state = (row.sync_state.as_str() != state.as_str()).then_some(state);
+    if let Some(spam_unread) = info.counters.spam_unread {
+        let caption = ui::spam_caption(spam_unread);
+        diff.spam_caption = (row.spam_caption.as_str() != caption).then_some(caption);
+    }
+    if let Some(vacation) = &info.vacation {
+        let tip = ui::vacation_tip(vacation);
+        diff.vacation = (row.vacation.as_str() != tip).then_some(tip);
+    }
+
+    let notice = update.notice.unwrap_or_default();
+    diff.notice = (row.notice.as_str() != notice).then_some(notice);
+    diff
+}
+
+/// 应用对账户行的修改
+fn apply_diff(row: &mut Account, diff: RowDiff) {
+    if let Some(loading) = diff.loading {
+        row.is_loading = loading;
+    }
+    if diff.refresh_history {
+        ui::apply_unread_history(row);
+    }
+    if let Some(unread) = diff.unread_count {
+        row.unread_count = unread;
+    }
+    if let Some(path) = diff.avatar {
+        row.avatar_image = utils::avatar::load_avatar_from_path(&path);
+    }
+    if let Some(state) = diff.sync_state {
+        row.sync_state = state.as_str().into();
+    }
+    if let Some(notice) = diff.notice {
+        row.notice = notice.into();
+    }
+    if let Some(caption) = diff.spam_caption {
+        row.spam_caption = caption.into();
+    }
+    if let Some(tip) = diff.vacation {
+        row.vacation = tip.into();
+    }
+    if let Some(caption) = diff.filter_caption {
+        row.filter_caption = caption.into();
+    }
+    if let Some(cached) = diff.cached {
+        row.cached = cached;
+    }
+    if let Some(stale) = diff.stale {
+        row.stale = stale;
+    }
+}
+
+/// 将新账户添加到 UI 列表（列表中已有同一邮箱的行时原地替换）
+///
+/// 添加账户时分两步更新：授权完成后立即插入只有邮箱、显示名称与头像（缓存头像或占位符）的行，
+/// `loading` 为 true 时显示同步中；首次同步结束后由 [`row_change`] 像同步引擎的结果一样原地更新该行
+pub fn update_accounts_ui(window: &MainWindow, stored_account: StoredAccount, loading: bool) {
+    // 转换为 Slint Account 类型（未读数与同步状态等待首次同步）
+    let mut slint_account: Account = stored_account.into();
+    slint_account.is_loading = loading;
+
+    // 重新添加已有的账户：原地替换，不重复显示（保留所在组的折叠状态）
+    let rows = window.get_accounts();
+    if let Some((index, row)) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, row)| !row.header && row.email == slint_account.email)
+    {
+        slint_account.collapsed = row.collapsed;
+        rows.set_row_data(index, slint_account);
+        refresh_groups(window);
+        return;
+    }
+
+    // 在现有账户列表末尾添加新账户（设置了分组时排到所在组的末尾）
+    let mut new_accounts: Vec<Account> = rows.iter().collect();
+    new_accounts.push(slint_account);
+
+    let account_count = new_accounts.iter().filter(|row| !row.header).count();
+    set_rows(window, new_accounts);
+
+    tracing::info!("UI 已更新：显示 {} 个账户", account_count);
+
+    // 账户数量变化会改变窗口高度，重新贴合托盘位置
+    refit_window_near_tray(window);
+}
+
+/// 从 UI 列表中移除账户
+pub fn remove_account_row(window: &MainWindow, email: &str) {
+    let remaining: Vec<Account> = window
+        .get_accounts()
+        .iter()
+        .filter(|row| row.header || row.email.as_str() != email)
+        .collect();
+    set_rows(window, remaining);
+    refit_window_near_tray(window);
+}
+
+/// 按分组排列后设置账户列表，并更新列表高度
+///
+/// 重建模型后行的位置会变化，键盘焦点跟随原来的焦点行（所在组已折叠时移到该组的标题行）
+pub fn set_rows(window: &MainWindow, rows: Vec<Account>) {
+    let focused = usize::try_from(window.get_focused_index())
+        .ok()
+        .and_then(|index| window.get_accounts().row_data(index));
+
+    let arranged = ui::groups::arrange(rows, &ui::groups::collapsed());
+    window.set_rows_height(ui::groups::rows_height(&arranged, window.get_compact()));
+    if let Some(focused) = focused {
+        let index = ui::groups::locate(&arranged, &focused);
+        if index >= 0 {
+            window.set_focused_index(index);
+        }
+    }
+    window.set_accounts(Rc::new(VecModel::from(arranged)).into());
+}
+
+/// 分组或折叠状态变化后重新排列当前的账户行
+pub fn regroup(window: &MainWindow) {
+    set_rows(window, window.get_accounts().iter().collect());
+}
+
+/// 切换紧凑模式（Token 详情在紧凑模式中不可用，已打开时一并关闭）
+///
+/// 窗口可见时按新的窗口高度立即重新定位，而不是等下一次布局后再移动（避免窗口先变高再跳动）
+pub fn set_compact(window: &MainWindow, compact: bool) {
+    if window.get_compact() == compact {
+        return;
+    }
+    window.set_compact(compact);
+    if compact {
+        window.set_token_details_visible(false);
+    }
+    let rows: Vec<Account> = window.get_accounts().iter().collect();
+    window.set_rows_height(ui::groups::rows_height(&rows, compact));
+
+    if window.window().is_visible() {
+        tray::reposition_for_height(window, window.get_window_height());
+    }
+}
+
+/// 账户行的未读数或免打扰状态变化后更新分组标题中的未读合计
+pub fn refresh_groups(window: &MainWindow) {
+    let model = window.get_accounts();
+    let rows: Vec<Account> = model.iter().collect();
+    for (index, header) in ui::groups::summarize(&rows) {
+        model.set_row_data(index, header);
+    }
+}
+
+/// 更新账户行的重新授权提醒
+pub fn set_reauth_reminder(window: &MainWindow, email: &str, reminder: &str) {
+    let rows = window.get_accounts();
+    for index in 0..rows.row_count() {
+        let Some(mut row) = rows.row_data(index) else {
+            continue;
+        };
+        if row.email.as_str() == email && row.reauth_reminder.as_str() != reminder {
+            row.reauth_reminder = reminder.into();
+            rows.set_row_data(index, row);
+        }
+    }
+}
+
+/// 账户数量变化后重新计算窗口位置
+///
+/// 窗口高度由 Slint 绑定计算，需等待下一次布局完成后才能读到新尺寸，
+/// 因此延迟一小段时间再定位；窗口隐藏时无需处理（显示时会重新定位）
+pub fn refit_window_near_tray(window: &MainWindow) {
+    if !window.window().is_visible() {
+        return;
+    }
+
+    let weak = window.as_weak();
+    slint::Timer::single_shot(Duration::from_millis(50), move || {
+        if let Some(window) = weak.upgrade() {
+            tray::reposition_near_tray(&window);
+        }
+    });
+}
+
+/// 按当前主题与窗口缩放比例更新占位符头像
+///
+/// 样式变化时把账户行中已显示的占位符换成按新样式栅格化的图片，真实头像不受影响
+pub fn refresh_placeholder_avatars(window: &MainWindow) {
+    let dark = Theme::get(window).get_is_dark();
+    let scale = window.window().scale_factor();
+    let Some(previous) = utils::avatar::set_placeholder_style(dark, scale) else {
+        return;
+    };
+
+    let accounts = window.get_accounts();
+    for index in 0..accounts.row_count() {
+        let Some(mut row) = accounts.row_data(index) else {
+            continue;
+        };
+        if previous.contains(&row.avatar_image) {
+            row.avatar_image = utils::avatar::load_placeholder_avatar();
+            accounts.set_row_data(index, row);
+        }
+    }
+}
+
+/// 窗口显示期间定期检查缩放比例（窗口移到缩放比例不同的显示器后重新栅格化占位符头像）
+pub fn watch_scale_factor(window: &MainWindow) {
+    let weak = window.as_weak();
+    SCALE_TIMER.with(|timer| {
+        timer.start(TimerMode::Repeated, SCALE_CHECK_INTERVAL, move || {
+            if let Some(window) = weak.upgrade()
+                && window.window().is_visible()
+            {
+                refresh_placeholder_avatars(&window);
+            }
+        });
+    });
+}
+
+/// 清空 UI 账户数据（释放 Image 内存）
+///
+/// 在窗口隐藏时调用，减少内存占用
+pub fn clear_accounts_ui(window: &MainWindow) {
+    // 设置为空列表，释放所有 Image 对象
+    let empty_model: VecModel<Account> = VecModel::default();
+    window.set_accounts(Rc::new(empty_model).into());
+    window.set_rows_height(0.0);
+
+    tracing::info!("📦 UI 资源已释放（账户数据已清空）");
+}
+
+/// 重新加载 UI 账户数据
+///
+/// 在窗口显示时调用，从本地存储加载账户并填充 UI
+pub fn reload_accounts_ui(window: &MainWindow, cache_max_age: chrono::Duration) {
+    // 显示前按当前缩放比例准备占位符头像
+    refresh_placeholder_avatars(window);
+
+    // 从本地存储加载账户
+    let accounts = match crate::config::storage::load_accounts() {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            tracing::warn!("加载账户失败: {}", e);
+            return;
+        }
+    };
+
+    // 转换为 Slint 类型（会加载头像 Image），未读数先沿用上次的同步结果直到本轮同步完成；
+    // 垃圾邮件不是每轮都检查，说明沿用托盘摘要中的记录
+    let statuses = tray::summary::snapshot();
+    let slint_accounts: Vec<Account> = accounts
+        .into_iter()
+        .map(|acc| {
+            let mut row: Account = acc.into();
+            ui::apply_cached_sync(&mut row, cache_max_age);
+            ui::apply_unread_history(&mut row);
+            if let Some(status) = statuses.iter().find(|s| s.email == row.email.as_str()) {
+                row.spam_caption = ui::spam_caption(status.spam_unread).into();
+            }
+            row
+        })
+        .collect();
+    let count = slint_accounts.len();
+
+    set_rows(window, slint_accounts);
+
+    tracing::info!("📦 UI 资源已重新加载（{} 个账户）", count);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::{PeopleFilter, ProfileInfo, VacationInfo};
+
+    fn row(unread: i32) -> Account {
+        Account {
+            email: "me@example.com".into(),
+            unread_count: unread,
+            sync_state: SyncState::Synced.as_str().into(),
+            ..Account::default()
+        }
+    }
+
+    fn info(unread: u32, error: Option<&str>) -> AccountSyncInfo {
+        match error {
+            Some(error) => AccountSyncInfo::failed("me@example.com", error),
+            None => AccountSyncInfo {
+                profile: Some(ProfileInfo::default()),
+                ..AccountSyncInfo::new("me@example.com", unread)
+            },
+        }
+    }
+
+    #[test]
+    fn test_started_only_sets_loading() {
+        let update = RowUpdate {
+            loading: true,
+            ..RowUpdate::default()
+        };
+        assert_eq!(
+            diff_row(&row(3), &update),
+            RowDiff {
+                loading: Some(true),
+                ..RowDiff::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_unchanged_row_is_not_rewritten() {
+        // 静默失败：只结束同步中标记，而该行本来就没有在同步
+        assert!(diff_row(&row(3), &RowUpdate::default()).is_empty());
+    }
+
+    #[test]
+    fn test_success_updates_count_and_clears_error() {
+        let current = Account {
+            is_loading: true,
+            sync_state: SyncState::Error.as_str().into(),
+            notice: "stale".into(),
+            ..row(3)
+        };
+        let update = RowUpdate {
+            loading: false,
+            info: Some(info(5, None)),
+            notice: None,
+        };
+
+        assert_eq!(
+            diff_row(&current, &update),
+            RowDiff {
+                loading: Some(false),
+                unread_count: Some(5),
+                sync_state: Some(SyncState::Synced),
+                notice: Some(""),
+                avatar: Some(String::new()),
+                refresh_history: true,
+                spam_caption: None,
+                vacation: None,
+                filter_caption: None,
+                cached: None,
+                stale: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_same_count_still_refreshes_history() {
+        let update = RowUpdate {
+            info: Some(info(3, None)),
+            ..RowUpdate::default()
+        };
+        let diff = diff_row(&row(3), &update);
+
+        assert_eq!(diff.unread_count, None);
+        assert!(diff.refresh_history);
+    }
+
+    #[test]
+    fn test_spam_caption_follows_checked_rounds() {
+        let with_spam = |spam_unread| RowUpdate {
+            info: Some({
+                let mut info = info(3, None);
+                info.counters.spam_unread = spam_unread;
+                info
+            }),
+            ..RowUpdate::default()
+        };
+
+        let diff = diff_row(&row(3), &with_spam(Some(2)));
+        assert!(
+            diff.spam_caption
+                .is_some_and(|caption| caption.contains('2'))
+        );
+        // 垃圾邮件不影响徽章
+        assert_eq!(diff.unread_count, None);
+
+        // 本轮未检查时保留原有说明，回到 0 时立即清除
+        let current = Account {
+            spam_caption: ui::spam_caption(2).into(),
+            ..row(3)
+        };
+        assert_eq!(diff_row(&current, &with_spam(None)).spam_caption, None);
+        assert_eq!(
+            diff_row(&current, &with_spam(Some(0))).spam_caption,
+            Some(String::new())
+        );
+        assert_eq!(diff_row(&row(3), &with_spam(Some(0))).spam_caption, None);
+    }
+
+    #[test]
+    fn test_vacation_follows_checked_rounds() {
+        let with_vacation = |vacation| RowUpdate {
+            info: Some(AccountSyncInfo {
+                vacation,
+                ..info(3, None)
+            }),
+            ..RowUpdate::default()
+        };
+        let away = VacationInfo {
+            active: true,
+            subject: "Out of office".to_string(),
+        };
+
+        let diff = diff_row(&row(3), &with_vacation(Some(away.clone())));
+        assert_eq!(diff.vacation, Some(ui::vacation_tip(&away)));
+        assert_eq!(diff.unread_count, None);
+
+        // 本轮未检查时保留原有标记，自动回复结束后立即清除
+        let current = Account {
+            vacation: ui::vacation_tip(&away).into(),
+            ..row(3)
+        };
+        assert_eq!(diff_row(&current, &with_vacation(None)).vacation, None);
+        assert_eq!(
+            diff_row(&current, &with_vacation(Some(VacationInfo::default()))).vacation,
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn test_filter_caption_follows_successful_rounds() {
+        let with_filter = |people_filter| RowUpdate {
+            info: Some({
+                let mut info = info(3, None);
+                info.counters.people_filter = people_filter;
+                info
+            }),
+            ..RowUpdate::default()
+        };
+
+        let unfiltered = ui::filter_caption(Some(PeopleFilter::Unfiltered));
+        assert!(!unfiltered.is_empty());
+        assert_eq!(
+            diff_row(&row(3), &with_filter(Some(PeopleFilter::Unfiltered))).filter_caption,
+            Some(unfiltered.clone())
+        );
+
+        // 未读回落到上限以内（或关闭过滤）后清除说明；同步失败时保留
+        let current = Account {
+            filter_caption: unfiltered.into(),
+            ..row(3)
+        };
+        assert_eq!(
+            diff_row(&current, &with_filter(Some(PeopleFilter::Applied))).filter_caption,
+            Some(String::new())
+        );
+        assert_eq!(
+            diff_row(&current, &with_filter(None)).filter_caption,
+            Some(String::new())
+        );
+        let failed = RowUpdate {
+            info: Some(info(0, Some("boom"))),
+            ..RowUpdate::default()
+        };
+        assert_eq!(diff_row(&current, &failed).filter_caption, None);
+    }
+
+    #[test]
+    fn test_first_result_replaces_cached_row() {
+        let cached = Account {
+            cached: true,
+            stale: true,
+            ..row(3)
+        };
+
+        // 同步失败：不再标注"更新中"，但缓存的结果仍然过期
+        let failed = RowUpdate {
+            info: Some(info(0, Some("boom"))),
+            ..RowUpdate::default()
+        };
+        let diff = diff_row(&cached, &failed);
+        assert_eq!((diff.cached, diff.stale), (Some(false), None));
+
+        let synced = RowUpdate {
+            info: Some(info(3, None)),
+            ..RowUpdate::default()
+        };
+        let diff = diff_row(&cached, &synced);
+        assert_eq!((diff.cached, diff.stale), (Some(false), Some(false)));
+
+        // 同步开始时仍显示缓存的结果
+        let started = RowUpdate {
+            loading: true,
+            ..RowUpdate::default()
+        };
+        assert_eq!(diff_row(&cached, &started).cached, None);
+    }
+
+    #[test]
+    fn test_failure_keeps_count_and_avatar() {
+        let update = RowUpdate {
+            info: Some(info(0, Some("boom"))),
+            notice: Some("unavailable"),
+            ..RowUpdate::default()
+        };
+
+        assert_eq!(
+            diff_row(&row(3), &update),
+            RowDiff {
+                sync_state: Some(SyncState::Error),
+                notice: Some("unavailable"),
+                ..RowDiff::default()
+            }
+        );
+    }
+}
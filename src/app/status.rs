@@ -0,0 +1,114 @@
+/// 标题栏状态
+///
+/// 应用状态（N 的颜色与说明）与未读合计都由 `tray::summary` 中各账户的最新状态汇总得到，
+/// 这里只把汇总结果转换为标题栏显示的内容
+use crate::MainWindow;
+use crate::tray;
+use crate::tray::summary::{AppStatus, UnreadTotal};
+use crate::utils::i18n::tr_fmt;
+
+/// 标题栏显示的状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HeaderStatus {
+    /// Slint 端的状态标识
+    status: &'static str,
+
+    /// 状态说明（正常时为空）
+    label: &'static str,
+
+    /// 未读合计
+    total_unread: i32,
+
+    /// 计入合计的账户数
+    account_count: i32,
+
+    /// 合计说明（如"共 5 封未读（2 个账户）"）
+    total_label: String,
+}
+
+/// 由应用状态与未读合计得到标题栏内容
+fn header_status(status: AppStatus, total: UnreadTotal) -> HeaderStatus {
+    HeaderStatus {
+        status: status.as_str(),
+        label: status.label(),
+        total_unread: total.unread as i32,
+        account_count: total.accounts as i32,
+        total_label: tr_fmt("header.total_unread", &[&total.unread, &total.accounts]),
+    }
+}
+
+/// 按各账户最新状态更新标题栏 N 与托盘图标的状态（须在 UI 线程调用）
+pub fn apply_app_status(window: &MainWindow) {
+    let header = header_status(tray::refresh_status(), tray::summary::total());
+
+    window.set_app_status(header.status.into());
+    window.set_app_status_label(header.label.into());
+    window.set_total_unread(header.total_unread);
+    window.set_account_count(header.account_count);
+    window.set_total_label(header.total_label.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tray::summary::{AccountStatus, compute_status, compute_total};
+
+    fn account(email: &str, unread_count: u32, snoozed: bool) -> AccountStatus {
+        AccountStatus {
+            email: email.to_string(),
+            display_name: String::new(),
+            unread_count,
+            synced: true,
+            failures: 0,
+            fatal: false,
+            last_error: None,
+            partial: false,
+            snoozed,
+            active: true,
+            spam_unread: 0,
+            alert_threshold: None,
+        }
+    }
+
+    fn header_for(accounts: &[AccountStatus]) -> HeaderStatus {
+        header_status(compute_status(accounts, false), compute_total(accounts))
+    }
+
+    #[test]
+    fn test_header_totals_exclude_snoozed_accounts() {
+        let header = header_for(&[
+            account("a@example.com", 2, false),
+            account("b@example.com", 3, false),
+            account("c@example.com", 9, true),
+        ]);
+
+        assert_eq!(header.status, "unread");
+        assert_eq!(header.label, AppStatus::Unread.label());
+        assert_eq!(header.total_unread, 5);
+        assert_eq!(header.account_count, 2);
+        assert!(header.total_label.contains('5'));
+    }
+
+    #[test]
+    fn test_header_for_quiet_accounts() {
+        let header = header_for(&[account("a@example.com", 0, false)]);
+        assert_eq!(header.status, "normal");
+        assert_eq!(header.label, "");
+        assert_eq!(header.total_unread, 0);
+
+        // 全部在免打扰期
+        let header = header_for(&[account("a@example.com", 4, true)]);
+        assert_eq!(header.status, "paused");
+        assert_eq!(header.account_count, 0);
+    }
+
+    #[test]
+    fn test_header_reports_failing_account() {
+        let mut failing = account("b@example.com", 0, false);
+        failing.fatal = true;
+
+        let header = header_for(&[account("a@example.com", 1, false), failing]);
+        assert_eq!(header.status, "error");
+        assert_eq!(header.total_unread, 1);
+    }
+}
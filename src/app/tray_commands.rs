@@ -0,0 +1,388 @@
+/// 托盘命令处理
+///
+/// 托盘菜单、托盘图标点击与其他实例的消息都以 `TrayCommand` 的形式送到独立线程，
+/// 退出命令在该线程中直接处理，其余命令转到事件循环中执行
+use anyhow::{Context, Result};
+use slint::ComponentHandle;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, mpsc};
+
+use crate::tray::{self, TrayCommand};
+use crate::utils::shell;
+use crate::{MainWindow, config, doctor, sync, ui, utils};
+
+use super::AppState;
+use super::accounts::{find_stored_account, snooze_account};
+use super::callbacks::{open_gmail, open_gmail_for};
+use super::rows::{clear_accounts_ui, reload_accounts_ui};
+
+/// 项目主页
+const HOMEPAGE_URL: &str = "https://github.com/Keriyar/NanoMail";
+
+/// 启动托盘命令处理线程
+///
+/// 处理完退出命令后通过 `shutdown_tx` 通知主线程完成清理；线程 panic 时托盘将无法响应，
+/// 直接退出事件循环，避免留下僵尸进程
+pub fn spawn_tray_commands(
+    rx: mpsc::Receiver<TrayCommand>,
+    state: &Arc<AppState>,
+    window: &MainWindow,
+    shutdown_tx: mpsc::Sender<()>,
+) -> Result<()> {
+    let state = state.clone();
+    let window_weak = window.as_weak();
+    std::thread::Builder::new()
+        .name("tray-commands".to_string())
+        .spawn(move || {
+            let shutdown = shutdown_tx.clone();
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                handle_tray_commands(rx, window_weak, &state, shutdown);
+            }));
+
+            if result.is_err() {
+                tray::mark_degraded("托盘命令处理线程 panic");
+                slint::invoke_from_event_loop(|| {
+                    slint::quit_event_loop().ok();
+                })
+                .ok();
+                shutdown_tx.send(()).ok();
+            }
+        })?;
+    Ok(())
+}
+
+/// 处理托盘命令（在独立线程中运行）
+///
+/// 每批命令在事件循环中执行完后才读取下一批：事件循环卡住期间点击产生的命令留在有界通道中
+/// （已满时由发送方丢弃），恢复后一次取出并合并（见 [`coalesce`]），不会连续切换窗口多次
+fn handle_tray_commands(
+    rx: mpsc::Receiver<TrayCommand>,
+    window_weak: slint::Weak<MainWindow>,
+    state: &Arc<AppState>,
+    shutdown_tx: mpsc::Sender<()>,
+) {
+    while let Ok(first) = rx.recv() {
+        let pending: Vec<TrayCommand> = std::iter::once(first).chain(rx.try_iter()).collect();
+        let received = pending.len();
+        let commands = coalesce(pending);
+        if commands.len() < received {
+            tracing::info!("合并积压的托盘命令: {} → {}", received, commands.len());
+        }
+
+        // 退出需要先停止后台任务，直接在本线程处理
+        if commands == [TrayCommand::Exit] {
+            shut_down(window_weak, state, &shutdown_tx);
+            break;
+        }
+
+        // 确保 UI 更新在主线程执行，执行完成（或事件循环已退出、闭包被丢弃）后再继续
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let weak = window_weak.clone();
+        let state = state.clone();
+        let result = slint::invoke_from_event_loop(move || {
+            if let Some(window) = weak.upgrade() {
+                for cmd in commands {
+                    run_command(&window, &state, cmd);
+                }
+            } else {
+                tracing::warn!("窗口引用已失效，无法处理托盘命令");
+            }
+            done_tx.send(()).ok();
+        });
+
+        match result {
+            Ok(()) => {
+                done_rx.recv().ok();
+            }
+            Err(e) => tracing::error!("invoke_from_event_loop 失败: {:?}", e),
+        }
+    }
+}
+
+/// 退出程序：停止后台任务，在主线程隐藏窗口并退出事件循环，再通知主线程完成清理
+fn shut_down(weak: slint::Weak<MainWindow>, state: &AppState, shutdown_tx: &mpsc::Sender<()>) {
+    tracing::info!("========================================");
+    tracing::info!("托盘收到退出命令，开始优雅关机流程");
+    tracing::info!("========================================");
+
+    // 请求同步引擎停止（同步接口），并拒绝界面发起新的后台任务
+    state.sync_engine.request_stop();
+    state.workers.cancel();
+
+    // 在主线程执行 UI 隐藏并退出事件循环
+    let quit_result = slint::invoke_from_event_loop(move || {
+        if let Some(window) = weak.upgrade() {
+            tracing::info!("退出流程: 隐藏窗口");
+            window.hide().ok();
+        }
+        tracing::info!("退出流程: 调用 quit_event_loop()");
+        match slint::quit_event_loop() {
+            Ok(_) => tracing::info!("退出流程: quit_event_loop() 成功"),
+            Err(e) => tracing::error!("退出流程: quit_event_loop() 失败: {:?}", e),
+        }
+    });
+
+    match quit_result {
+        Ok(_) => tracing::info!("退出流程: invoke_from_event_loop 成功"),
+        Err(e) => {
+            tracing::error!("退出流程: invoke_from_event_loop 失败: {:?}", e);
+            // 如果 invoke_from_event_loop 失败，强制退出
+            tracing::error!("退出流程: 尝试强制退出...");
+            std::process::exit(0);
+        }
+    }
+
+    // 发送退出信号给主线程以完成优雅退出（主线程在 run_event_loop() 返回后会等待此信号）
+    if let Err(e) = shutdown_tx.send(()) {
+        tracing::error!("退出流程: 发送退出信号失败: {:?}", e);
+    }
+
+    tracing::info!("退出流程: 托盘线程退出循环");
+}
+
+/// 合并积压的托盘命令
+///
+/// - 有退出命令时只保留退出
+/// - 显示或隐藏窗口覆盖紧挨在它之前的窗口命令（显示后隐藏只剩隐藏，反之亦然）
+/// - 相邻的相同命令只保留一个（连续多次切换窗口只切换一次）
+pub fn coalesce(commands: Vec<TrayCommand>) -> Vec<TrayCommand> {
+    if commands.contains(&TrayCommand::Exit) {
+        return vec![TrayCommand::Exit];
+    }
+
+    let mut reduced: Vec<TrayCommand> = Vec::with_capacity(commands.len());
+    for cmd in commands {
+        if reduced.last() == Some(&cmd) {
+            continue;
+        }
+        if matches!(cmd, TrayCommand::ShowWindow | TrayCommand::HideWindow) {
+            while reduced.last().is_some_and(is_window_command) {
+                reduced.pop();
+            }
+        }
+        reduced.push(cmd);
+    }
+    reduced
+}
+
+/// 改变窗口显示状态的命令
+fn is_window_command(cmd: &TrayCommand) -> bool {
+    matches!(
+        cmd,
+        TrayCommand::ToggleWindow | TrayCommand::ShowWindow | TrayCommand::HideWindow
+    )
+}
+
+/// 在事件循环中执行托盘命令
+fn run_command(window: &MainWindow, state: &AppState, cmd: TrayCommand) {
+    match cmd {
+        TrayCommand::ToggleWindow => {
+            tracing::info!("处理托盘命令: ToggleWindow");
+            if !window.window().is_visible() {
+                // 窗口将要显示，重新加载 UI 资源并触发同步
+                reload_accounts_ui(window, state.cached_counts_max_age());
+                state.sync_engine.trigger_sync();
+            } else {
+                // 窗口将要隐藏，清空 UI 资源
+                clear_accounts_ui(window);
+            }
+            tray::toggle_window(window);
+        }
+        TrayCommand::ShowWindow => {
+            tracing::info!("处理托盘命令: ShowWindow");
+            // 重新加载 UI 资源并触发同步
+            reload_accounts_ui(window, state.cached_counts_max_age());
+            state.sync_engine.trigger_sync();
+            tray::show_window_near_tray(window);
+        }
+        TrayCommand::HideWindow => {
+            tracing::info!("处理托盘命令: HideWindow");
+            // 固定状态下不响应自动隐藏
+            if window.get_pinned() {
+                tracing::info!("窗口已固定，忽略 HideWindow");
+                return;
+            }
+            window.hide().ok();
+            // 清空 UI 资源以减少内存占用
+            clear_accounts_ui(window);
+        }
+        TrayCommand::SyncNow => {
+            tracing::info!("处理托盘命令: SyncNow");
+            state.sync_engine.trigger_sync();
+        }
+        TrayCommand::OpenGmail => {
+            tracing::info!("处理托盘命令: OpenGmail");
+            open_gmail(state.open_with());
+        }
+        TrayCommand::CopySummary => {
+            tracing::info!("处理托盘命令: CopySummary");
+            let message = match tray::copy_summary() {
+                Ok(Some(_)) => utils::i18n::tr("tray.summary_copied"),
+                Ok(None) => utils::i18n::tr("tray.summary_empty"),
+                Err(e) => {
+                    tracing::error!("复制未读摘要失败: {:#}", e);
+                    return;
+                }
+            };
+            tray::flash_tooltip(message);
+            if window.window().is_visible() {
+                ui::show_snackbar(window, message);
+            }
+        }
+        TrayCommand::RunDiagnostics => {
+            tracing::info!("处理托盘命令: RunDiagnostics");
+            show_diagnostics(state.sync_engine.status());
+        }
+        TrayCommand::OpenDataFolder => {
+            tracing::info!("处理托盘命令: OpenDataFolder");
+            // 已保存账户时在数据文件夹中选中 accounts.toml
+            let target = config::storage::accounts_path().map(|path| match path.parent() {
+                Some(dir) if !path.exists() => dir.to_path_buf(),
+                _ => path,
+            });
+            open_path(window, target, shell::reveal);
+        }
+        TrayCommand::ViewLogs => {
+            tracing::info!("处理托盘命令: ViewLogs");
+            let target =
+                utils::logging::logs_dir().map(|dir| utils::logging::log_view_target(&dir));
+            open_path(window, target, shell::open);
+        }
+        TrayCommand::ShowSyncHistory => {
+            tracing::info!("处理托盘命令: ShowSyncHistory");
+            ui::history::apply_sync_history(window);
+            window.set_token_details_visible(false);
+            window.set_sync_history_visible(true);
+            if !window.window().is_visible() {
+                reload_accounts_ui(window, state.cached_counts_max_age());
+                tray::show_window_near_tray(window);
+            }
+        }
+        TrayCommand::ShowAbout => {
+            tracing::info!("处理托盘命令: ShowAbout");
+            show_about_dialog();
+        }
+        TrayCommand::OpenInbox(email) => {
+            tracing::info!("处理托盘命令: OpenInbox");
+            if let Some(account) = find_stored_account(&email) {
+                open_gmail_for(&account, &state.providers, state.open_with());
+            }
+        }
+        TrayCommand::SyncAccount(email) => {
+            tracing::info!("处理托盘命令: SyncAccount");
+            state.sync_engine.retry_now(&email);
+        }
+        TrayCommand::SnoozeAccount { email, duration } => {
+            tracing::info!("处理托盘命令: SnoozeAccount {:?}", duration);
+            snooze_account(window, state, &email, &duration);
+        }
+        TrayCommand::Exit => {}
+    }
+}
+
+/// 打开数据文件夹或日志；失败时记录日志，并在托盘提示（窗口显示时另用提示条）中说明
+fn open_path(window: &MainWindow, target: Result<PathBuf>, open: fn(&Path) -> Result<()>) {
+    let result = target.and_then(|path| open(&path).with_context(|| path.display().to_string()));
+    if let Err(e) = result {
+        tracing::error!("打开失败: {:#}", e);
+        let message = utils::i18n::tr_fmt("tray.open_failed", &[&e]);
+        tray::flash_tooltip(&message);
+        if window.window().is_visible() {
+            ui::show_snackbar(window, &message);
+        }
+    }
+}
+
+/// 运行诊断并以消息框显示报告（附上同步引擎状态与最近的同步记录）
+fn show_diagnostics(engine: sync::EngineStatus) {
+    // 网络检查需要数秒，消息框也是模态的，放到独立线程避免阻塞 Slint 事件循环
+    std::thread::spawn(move || {
+        let results = doctor::run_blocking();
+        let mut report = doctor::format_report(&results);
+        report.push_str("\n\n");
+        report.push_str(&doctor::format_engine_status(&engine, chrono::Utc::now()));
+        report.push_str(&doctor::format_last_round(
+            utils::telemetry::last_round().as_ref(),
+        ));
+        let history = doctor::format_sync_history(&sync::history::snapshot(), chrono::Utc::now());
+        if !history.is_empty() {
+            report.push_str("\n\n");
+            report.push_str(&history);
+        }
+        if doctor::has_failures(&results) {
+            utils::dialog::show_error(&report);
+        } else {
+            utils::dialog::show_info(&report);
+        }
+    });
+}
+
+fn show_about_dialog() {
+    tracing::info!("显示关于对话框");
+    // 消息框是模态的，放到独立线程避免阻塞 Slint 事件循环
+    std::thread::spawn(|| {
+        let text = utils::i18n::tr_fmt(
+            "about.text",
+            &[&utils::app_info::version_string(), &HOMEPAGE_URL],
+        );
+        if utils::dialog::ask(&text) {
+            utils::browser::open_url(HOMEPAGE_URL, utils::browser::UrlPurpose::Link);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TrayCommand::*;
+
+    #[test]
+    fn test_repeated_toggles_collapse_to_one() {
+        let toggles = vec![ToggleWindow; 5];
+        assert_eq!(coalesce(toggles), [ToggleWindow]);
+        assert_eq!(
+            coalesce(vec![SyncNow, SyncNow, OpenGmail]),
+            [SyncNow, OpenGmail]
+        );
+        assert!(coalesce(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_show_and_hide_override_earlier_window_commands() {
+        assert_eq!(coalesce(vec![ShowWindow, HideWindow]), [HideWindow]);
+        assert_eq!(coalesce(vec![HideWindow, ShowWindow]), [ShowWindow]);
+        assert_eq!(
+            coalesce(vec![ToggleWindow, ToggleWindow, ShowWindow]),
+            [ShowWindow]
+        );
+        // 显示之后的切换仍然要执行
+        assert_eq!(
+            coalesce(vec![ShowWindow, ToggleWindow, ToggleWindow]),
+            [ShowWindow, ToggleWindow]
+        );
+    }
+
+    #[test]
+    fn test_other_commands_keep_order_and_separate_runs() {
+        assert_eq!(
+            coalesce(vec![ShowWindow, SyncNow, HideWindow]),
+            [ShowWindow, SyncNow, HideWindow]
+        );
+        assert_eq!(
+            coalesce(vec![
+                SyncAccount("a@example.com".to_string()),
+                SyncAccount("a@example.com".to_string()),
+                SyncAccount("b@example.com".to_string()),
+            ]),
+            [
+                SyncAccount("a@example.com".to_string()),
+                SyncAccount("b@example.com".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exit_wins() {
+        assert_eq!(coalesce(vec![ToggleWindow, Exit, ShowWindow]), [Exit]);
+    }
+}
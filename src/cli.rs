@@ -0,0 +1,163 @@
+/// 命令行参数模块
+///
+/// 程序以 `windows_subsystem = "windows"` 构建，没有控制台；
+/// `--version` / `--help` / `--doctor` / `--print-counts` / 参数错误时附加到父进程控制台输出，失败则改用消息框。
+/// Linux 上从终端启动时直接输出，从桌面启动器启动时改用消息框
+#[cfg(windows)]
+use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+use crate::utils::i18n::{tr, tr_fmt};
+use crate::utils::{app_info, dialog};
+
+/// 启动参数
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CliArgs {
+    /// 启动时不显示主窗口
+    pub hidden: bool,
+
+    /// 请求立即同步
+    pub sync_now: bool,
+
+    /// 重置配置文件
+    pub reset_config: bool,
+}
+
+/// 解析结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliAction {
+    /// 正常启动
+    Run(CliArgs),
+    /// 输出版本号后退出
+    PrintVersion,
+    /// 输出帮助后退出
+    PrintHelp,
+    /// 运行诊断并输出结果后退出
+    Doctor,
+    /// 输出各账户未读数（JSON）后退出
+    PrintCounts,
+}
+
+/// 解析命令行参数（不含程序名）
+///
+/// # Errors
+/// 遇到未知参数时返回错误描述，不静默忽略
+pub fn parse<I>(args: I) -> Result<CliAction, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = CliArgs::default();
+
+    for arg in args {
+        match arg.as_str() {
+            "--hidden" => parsed.hidden = true,
+            "--sync-now" => parsed.sync_now = true,
+            "--reset-config" => parsed.reset_config = true,
+            "--version" | "-V" => return Ok(CliAction::PrintVersion),
+            "--help" | "-h" => return Ok(CliAction::PrintHelp),
+            "--doctor" => return Ok(CliAction::Doctor),
+            "--print-counts" => return Ok(CliAction::PrintCounts),
+            other => return Err(tr_fmt("cli.unknown_argument", &[&other, &help_text()])),
+        }
+    }
+
+    Ok(CliAction::Run(parsed))
+}
+
+/// 版本文本
+pub fn version_text() -> String {
+    app_info::version_string()
+}
+
+/// 帮助文本（按当前语言）
+pub fn help_text() -> &'static str {
+    tr("cli.help")
+}
+
+/// 尝试附加到父进程控制台（从 cmd / PowerShell 启动时可见输出）
+#[cfg(windows)]
+fn attach_parent_console() -> bool {
+    // SAFETY: AttachConsole 无指针参数，失败时仅返回 0
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS) != 0 }
+}
+
+/// 标准输出是否连接到终端（从桌面启动器启动时没有终端）
+#[cfg(not(windows))]
+fn attach_parent_console() -> bool {
+    use std::io::IsTerminal;
+
+    std::io::stdout().is_terminal()
+}
+
+/// 输出普通信息：优先写到父控制台，否则弹出消息框
+pub fn print_info(text: &str) {
+    if attach_parent_console() {
+        println!("{}", text);
+    } else {
+        dialog::show_info(text);
+    }
+}
+
+/// 输出错误信息：优先写到父控制台，否则弹出消息框
+pub fn print_error(text: &str) {
+    if attach_parent_console() {
+        eprintln!("{}", text);
+    } else {
+        dialog::show_error(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert_eq!(parse(args(&[])), Ok(CliAction::Run(CliArgs::default())));
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        let action = parse(args(&["--hidden", "--sync-now"])).unwrap();
+        assert_eq!(
+            action,
+            CliAction::Run(CliArgs {
+                hidden: true,
+                sync_now: true,
+                reset_config: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_version_and_help() {
+        assert_eq!(parse(args(&["--version"])), Ok(CliAction::PrintVersion));
+        assert_eq!(
+            parse(args(&["--hidden", "--help"])),
+            Ok(CliAction::PrintHelp)
+        );
+    }
+
+    #[test]
+    fn test_parse_doctor() {
+        assert_eq!(parse(args(&["--doctor"])), Ok(CliAction::Doctor));
+        assert_eq!(
+            parse(args(&["--hidden", "--doctor"])),
+            Ok(CliAction::Doctor)
+        );
+    }
+
+    #[test]
+    fn test_parse_print_counts() {
+        assert_eq!(parse(args(&["--print-counts"])), Ok(CliAction::PrintCounts));
+    }
+
+    #[test]
+    fn test_parse_unknown_flag_fails() {
+        let err = parse(args(&["--hiden"])).unwrap_err();
+        assert!(err.contains("--hiden"));
+    }
+}
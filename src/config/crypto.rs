@@ -7,6 +7,7 @@ use aes_gcm::{
 };
 use anyhow::{Context, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use thiserror::Error;
 
 use crate::utils::machine_id;
 
@@ -16,6 +17,28 @@ const ENCRYPTED_PREFIX: &str = "encrypted:";
 /// AES-GCM Nonce 长度（12 字节）
 const NONCE_SIZE: usize = 12;
 
+/// 无法使用的凭据标记（旧版本在明文凭据无法加密时写入此标记，保存时原样写回，使用时报告 [`CredentialError::Unreadable`]）
+pub const UNREADABLE: &str = "unreadable";
+
+/// 无法加密的明文凭据在内存中的前缀（只存在于内存，保存时去掉前缀写回原值，见 [`TokenBlob::into_encrypted`]）
+const UNENCRYPTED_PREFIX: &str = "unencrypted:";
+
+/// 凭据无法解密的原因（作为 anyhow 上下文附加到解密错误上，同步时据此将账户标为需要重新授权）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum CredentialError {
+    /// 本机密钥无法解密：凭据是在另一台电脑上加密的（如随漫游配置文件同步过来）
+    #[error("凭据是在另一台电脑上创建的，需要重新授权")]
+    ForeignMachine,
+
+    /// 凭据不是有效的加密格式（文件被手动修改或已损坏）
+    #[error("凭据无法读取（账户文件中的值不是有效的加密格式），需要重新授权")]
+    Unreadable,
+
+    /// 账户文件中的明文凭据无法用本机密钥加密（文件中保留原值，未被改写）
+    #[error("账户文件中的明文凭据无法加密，需要处理")]
+    Unencrypted,
+}
+
 /// 加密明文 Token
 ///
 /// 使用 AES-256-GCM 模式加密数据，密钥从机器 GUID 派生
@@ -43,26 +66,32 @@ pub fn encrypt_token(plain: &str) -> Result<String> {
     let key_bytes = machine_id::derive_encryption_key()
         .context("无法派生加密密钥")?;
 
-    // 2. 创建 AES-256-GCM 密码器
-    let cipher = Aes256Gcm::new(&key_bytes.into());
+    // 2. 加密
+    encrypt_with_key(&key_bytes, plain)
+}
+
+/// 使用给定的密钥加密（格式与 [`encrypt_token`] 相同，凭据自检可注入其他密钥来源）
+pub fn encrypt_with_key(key: &[u8; 32], plain: &str) -> Result<String> {
+    // 1. 创建 AES-256-GCM 密码器
+    let cipher = Aes256Gcm::new(key.into());
 
-    // 3. 生成随机 nonce（12 字节）
+    // 2. 生成随机 nonce（12 字节）
     let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
 
-    // 4. 加密数据
+    // 3. 加密数据
     let ciphertext = cipher
         .encrypt(&nonce, plain.as_bytes())
         .map_err(|e| anyhow::anyhow!("AES-GCM 加密失败: {}", e))?;
 
-    // 5. 组合：nonce + ciphertext
+    // 4. 组合：nonce + ciphertext
     let mut combined = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
     combined.extend_from_slice(&nonce);
     combined.extend_from_slice(&ciphertext);
 
-    // 6. Base64 编码
+    // 5. Base64 编码
     let encoded = BASE64.encode(&combined);
 
-    // 7. 添加前缀
+    // 6. 添加前缀
     Ok(format!("{}{}", ENCRYPTED_PREFIX, encoded))
 }
 
@@ -77,11 +106,10 @@ pub fn encrypt_token(plain: &str) -> Result<String> {
 /// 解密后的明文字符串
 ///
 /// # Errors
-/// - 格式错误（缺少前缀）
-/// - Base64 解码失败
-/// - 数据长度不足
+/// - 格式错误（缺少前缀）、Base64 解码失败、数据长度不足：附加 [`CredentialError::Unreadable`]
+/// - 加载时无法加密的明文凭据：附加 [`CredentialError::Unencrypted`]
 /// - 密钥派生失败
-/// - 解密失败（密钥错误或数据损坏）
+/// - 解密失败（密钥错误或数据损坏）：附加 [`CredentialError::ForeignMachine`]
 ///
 /// # Example
 /// ```no_run
@@ -89,41 +117,69 @@ pub fn encrypt_token(plain: &str) -> Result<String> {
 /// println!("解密成功: {}", plain);
 /// ```
 pub fn decrypt_token(encrypted: &str) -> Result<String> {
+    // 1. 检查格式并解码（格式错误时不必派生密钥）
+    let combined = decode_blob(encrypted)?;
+
+    // 2. 获取加密密钥
+    let key_bytes = machine_id::derive_encryption_key()
+        .context("无法派生解密密钥")?;
+
+    // 3. 解密
+    decrypt_combined(&key_bytes, &combined)
+}
+
+/// 使用给定的密钥解密（错误与 [`decrypt_token`] 相同，凭据自检可注入其他密钥来源）
+pub fn decrypt_with_key(key: &[u8; 32], encrypted: &str) -> Result<String> {
+    decrypt_combined(key, &decode_blob(encrypted)?)
+}
+
+/// 检查格式并解码为 nonce + ciphertext（格式错误时附加 [`CredentialError::Unreadable`]）
+fn decode_blob(encrypted: &str) -> Result<Vec<u8>> {
     // 1. 检查前缀
+    if encrypted.starts_with(UNENCRYPTED_PREFIX) {
+        return Err(anyhow::Error::new(CredentialError::Unencrypted)
+            .context("凭据加载时无法加密，账户文件中仍是明文"));
+    }
     if !encrypted.starts_with(ENCRYPTED_PREFIX) {
-        anyhow::bail!("加密数据格式错误：缺少 'encrypted:' 前缀");
+        return Err(anyhow::Error::new(CredentialError::Unreadable)
+            .context("加密数据格式错误：缺少 'encrypted:' 前缀"));
     }
 
     // 2. 去除前缀并 Base64 解码
     let base64_data = &encrypted[ENCRYPTED_PREFIX.len()..];
     let combined = BASE64
         .decode(base64_data)
+        .context(CredentialError::Unreadable)
         .context("Base64 解码失败")?;
 
     // 3. 检查数据长度（至少包含 nonce）
     if combined.len() < NONCE_SIZE {
-        anyhow::bail!(
-            "加密数据长度不足（需要至少 {} 字节，实际 {} 字节）",
-            NONCE_SIZE,
-            combined.len()
+        return Err(
+            anyhow::Error::new(CredentialError::Unreadable).context(format!(
+                "加密数据长度不足（需要至少 {} 字节，实际 {} 字节）",
+                NONCE_SIZE,
+                combined.len()
+            )),
         );
     }
 
-    // 4. 分离 nonce 和 ciphertext
+    Ok(combined)
+}
+
+/// 解密 nonce + ciphertext（密钥不匹配时附加 [`CredentialError::ForeignMachine`]）
+fn decrypt_combined(key: &[u8; 32], combined: &[u8]) -> Result<String> {
+    // 1. 分离 nonce 和 ciphertext
     let (nonce_bytes, ciphertext) = combined.split_at(NONCE_SIZE);
     let nonce = Nonce::from_slice(nonce_bytes);
 
-    // 5. 获取加密密钥
-    let key_bytes = machine_id::derive_encryption_key()
-        .context("无法派生解密密钥")?;
-
-    // 6. 创建密码器并解密
-    let cipher = Aes256Gcm::new(&key_bytes.into());
-    let plaintext = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|e| anyhow::anyhow!("AES-GCM 解密失败（可能密钥错误或数据损坏）: {}", e))?;
+    // 2. 创建密码器并解密
+    let cipher = Aes256Gcm::new(key.into());
+    let plaintext = cipher.decrypt(nonce, ciphertext).map_err(|e| {
+        anyhow::anyhow!("AES-GCM 解密失败（可能密钥错误或数据损坏）: {}", e)
+            .context(CredentialError::ForeignMachine)
+    })?;
 
-    // 7. 转换为 UTF-8 字符串
+    // 3. 转换为 UTF-8 字符串
     let result = String::from_utf8(plaintext)
         .context("解密后的数据不是有效的 UTF-8 字符串")?;
 
@@ -141,6 +197,91 @@ pub fn is_encrypted(s: &str) -> bool {
     s.starts_with(ENCRYPTED_PREFIX)
 }
 
+/// serde 序列化辅助：写入文件前确保已加密
+///
+/// 如果字段未加密（明文），则先加密再序列化；用于 `#[serde(serialize_with = ...)]`。
+/// 加载时无法加密的明文凭据去掉内存前缀后原样写回，不会用标记覆盖文件中的原值
+pub fn serialize_encrypted<S>(value: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::Error;
+
+    if let Some(plain) = value.strip_prefix(UNENCRYPTED_PREFIX) {
+        return serializer.serialize_str(plain);
+    }
+
+    // 如果已加密（或是加载时无法识别的凭据），直接序列化
+    if is_encrypted(value) || value == UNREADABLE {
+        return serializer.serialize_str(value);
+    }
+
+    // 否则先加密
+    let encrypted = encrypt_token(value).map_err(S::Error::custom)?;
+    serializer.serialize_str(&encrypted)
+}
+
+/// 账户文件中读取到的凭据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenBlob {
+    /// 加密格式（`encrypted:...`），或无法使用的凭据标记 [`UNREADABLE`]
+    Encrypted(String),
+
+    /// 明文（手动恢复或手动填写的账户文件）
+    Plaintext(String),
+}
+
+impl TokenBlob {
+    /// 按格式区分读取到的值
+    pub fn parse(value: String) -> Self {
+        if is_encrypted(&value) || value == UNREADABLE {
+            Self::Encrypted(value)
+        } else {
+            Self::Plaintext(value)
+        }
+    }
+
+    /// 内存中保存的形式
+    ///
+    /// 明文立即用本机密钥加密，下次保存时以加密格式写回文件；
+    /// 无法加密时在内存中加上未加密前缀保留原值，保存时原样写回，
+    /// 使用时报告 [`CredentialError::Unencrypted`]，只有这个账户需要处理
+    pub fn into_encrypted(self) -> String {
+        match self {
+            Self::Encrypted(value) => value,
+            Self::Plaintext(plain) => {
+                tracing::warn!("账户文件中的凭据是明文，已在内存中加密，下次保存时以加密格式写回");
+                encrypt_token(&plain).unwrap_or_else(|e| {
+                    tracing::error!("加密账户文件中的明文凭据失败，保留原值: {:#}", e);
+                    format!("{}{}", UNENCRYPTED_PREFIX, plain)
+                })
+            }
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for TokenBlob {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <String as serde::Deserialize>::deserialize(deserializer).map(Self::parse)
+    }
+}
+
+/// serde 反序列化辅助：保持加密状态
+///
+/// 从文件读取时保持加密状态，不立即解密（按需解密）；用于 `#[serde(deserialize_with = ...)]`。
+/// 明文凭据不拒绝整个条目（否则手动恢复的账户无法加载），而是按 [`TokenBlob::into_encrypted`] 立即加密
+pub fn deserialize_encrypted<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    TokenBlob::deserialize(deserializer).map(TokenBlob::into_encrypted)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,7 +342,87 @@ mod tests {
         // 无效的 Base64
         let result = decrypt_token("encrypted:!!!invalid@@@");
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Base64"));
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("Base64"));
+        assert_eq!(
+            error.downcast_ref::<CredentialError>(),
+            Some(&CredentialError::Unreadable)
+        );
+    }
+
+    #[derive(Debug, serde::Deserialize, serde::Serialize)]
+    struct Secret {
+        #[serde(
+            serialize_with = "serialize_encrypted",
+            deserialize_with = "deserialize_encrypted"
+        )]
+        token: String,
+    }
+
+    #[test]
+    fn test_token_blob_parse() {
+        assert_eq!(
+            TokenBlob::parse("encrypted:AAAA".to_string()),
+            TokenBlob::Encrypted("encrypted:AAAA".to_string())
+        );
+        assert_eq!(
+            TokenBlob::parse(UNREADABLE.to_string()),
+            TokenBlob::Encrypted(UNREADABLE.to_string())
+        );
+        assert_eq!(
+            TokenBlob::parse("1//plain-refresh-token".to_string()),
+            TokenBlob::Plaintext("1//plain-refresh-token".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_encrypts_plain_value() {
+        // 明文不拒绝整个条目，而是立即加密，解密后仍是原值
+        let secret: Secret = toml::from_str(r#"token = "plain_text""#).unwrap();
+        assert!(is_encrypted(&secret.token));
+        assert_eq!(decrypt_token(&secret.token).unwrap(), "plain_text");
+
+        // 写回文件时是加密格式
+        let saved = toml::to_string(&secret).unwrap();
+        assert!(saved.contains("encrypted:"));
+        assert!(!saved.contains("plain_text"));
+    }
+
+    #[test]
+    fn test_deserialize_keeps_unreadable_marker() {
+        let secret: Secret = toml::from_str(r#"token = "unreadable""#).unwrap();
+        assert_eq!(secret.token, UNREADABLE);
+
+        let error = decrypt_token(&secret.token).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CredentialError>(),
+            Some(&CredentialError::Unreadable)
+        );
+
+        // 标记原样写回，不会被当作明文加密
+        assert_eq!(
+            toml::to_string(&secret).unwrap().trim(),
+            r#"token = "unreadable""#
+        );
+    }
+
+    #[test]
+    fn test_unencrypted_value_is_written_back_unchanged() {
+        let secret = Secret {
+            token: format!("{}1//plain-refresh-token", UNENCRYPTED_PREFIX),
+        };
+
+        let error = decrypt_token(&secret.token).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CredentialError>(),
+            Some(&CredentialError::Unencrypted)
+        );
+
+        // 文件中的原值不被标记覆盖
+        assert_eq!(
+            toml::to_string(&secret).unwrap().trim(),
+            r#"token = "1//plain-refresh-token""#
+        );
     }
 
     #[test]
@@ -0,0 +1,198 @@
+/// 配置文件文档编辑模块
+///
+/// 用 toml_edit 在原文件基础上合并配置，而不是整体重写：
+/// 注释、格式、`[oauth]` 段以及本版本不认识的字段（例如新版本写入的字段）都会原样保留
+use anyhow::{Context, Result};
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::Config;
+
+/// 不属于 `Config` 但由其他模块读取的顶层段（不视为未知字段）
+const FOREIGN_SECTIONS: &[&str] = &["oauth"];
+
+/// 把配置写入已有文件内容：已有字段更新值（保留注释），缺少的字段追加，其余内容保持不变
+pub fn merge(existing: &str, config: &Config) -> Result<String> {
+    render(existing, config, true)
+}
+
+/// 只补全已有文件内容中缺少的字段，已有字段保持原样（用于加载时自动修复配置文件）
+pub fn fill_missing(existing: &str, config: &Config) -> Result<String> {
+    render(existing, config, false)
+}
+
+/// 列出文件中本版本不认识的字段（以 `段.字段` 形式返回）
+///
+/// `config` 应为从同一内容解析出的配置：能被识别的字段都会在序列化结果中重新出现
+pub fn unknown_keys(existing: &str, config: &Config) -> Result<Vec<String>> {
+    let target = parse_document(existing)?;
+    let source = to_document(config)?;
+
+    let mut unknown = Vec::new();
+    for (key, item) in target.iter() {
+        if FOREIGN_SECTIONS.contains(&key) {
+            continue;
+        }
+        collect_unknown(key, item, source.get(key), &mut unknown);
+    }
+    Ok(unknown)
+}
+
+/// 文件中是否写有 `段.字段`（区分缺少的字段与显式写入的值）
+pub fn has_key(existing: &str, section: &str, key: &str) -> Result<bool> {
+    let document = parse_document(existing)?;
+    Ok(document
+        .get(section)
+        .and_then(|item| item.get(key))
+        .is_some())
+}
+
+fn render(existing: &str, config: &Config, overwrite: bool) -> Result<String> {
+    let mut target = parse_document(existing)?;
+    let source = to_document(config)?;
+
+    // 新追加的段排在已有段之后，保持文件原有顺序
+    let mut next_position = max_position(target.as_table()) + 1;
+    merge_table(
+        target.as_table_mut(),
+        source.as_table(),
+        overwrite,
+        &mut next_position,
+    );
+    Ok(target.to_string())
+}
+
+fn parse_document(content: &str) -> Result<DocumentMut> {
+    content.parse().context("解析配置文件失败")
+}
+
+fn to_document(config: &Config) -> Result<DocumentMut> {
+    let content = toml::to_string_pretty(config).context("序列化配置失败")?;
+    content.parse().context("解析序列化后的配置失败")
+}
+
+fn merge_table(target: &mut Table, source: &Table, overwrite: bool, next_position: &mut usize) {
+    for (key, source_item) in source.iter() {
+        match target.get_mut(key) {
+            Some(target_item) => merge_item(target_item, source_item, overwrite, next_position),
+            None => {
+                let mut item = source_item.clone();
+                if let Some(table) = item.as_table_mut() {
+                    reposition(table, next_position);
+                }
+                target.insert(key, item);
+            }
+        }
+    }
+}
+
+fn merge_item(target: &mut Item, source: &Item, overwrite: bool, next_position: &mut usize) {
+    if let (Some(target), Some(source)) = (target.as_table_mut(), source.as_table()) {
+        merge_table(target, source, overwrite, next_position);
+        return;
+    }
+    if !overwrite {
+        return;
+    }
+
+    match (target.as_value_mut(), source.as_value()) {
+        (Some(old), Some(new)) => {
+            if plain(old) != plain(new) {
+                // 保留原值前后的空白与行尾注释
+                let decor = old.decor().clone();
+                *old = new.clone();
+                *old.decor_mut() = decor;
+            }
+        }
+        _ => *target = source.clone(),
+    }
+}
+
+/// 去掉修饰后的值文本（用于判断值是否变化）
+fn plain(value: &toml_edit::Value) -> String {
+    value.clone().decorated("", "").to_string()
+}
+
+/// 给追加的段（及其子段）分配新的位置
+fn reposition(table: &mut Table, next_position: &mut usize) {
+    table.set_position(*next_position);
+    *next_position += 1;
+    for (_, item) in table.iter_mut() {
+        if let Some(child) = item.as_table_mut() {
+            reposition(child, next_position);
+        }
+    }
+}
+
+fn max_position(table: &Table) -> usize {
+    table
+        .iter()
+        .filter_map(|(_, item)| item.as_table())
+        .map(|child| child.position().unwrap_or(0).max(max_position(child)))
+        .max()
+        .unwrap_or(0)
+}
+
+fn collect_unknown(path: &str, target: &Item, source: Option<&Item>, unknown: &mut Vec<String>) {
+    let Some(source) = source else {
+        unknown.push(path.to_string());
+        return;
+    };
+
+    if let (Some(target), Some(source)) = (target.as_table_like(), source.as_table_like()) {
+        for (key, item) in target.iter() {
+            collect_unknown(&format!("{}.{}", path, key), item, source.get(key), unknown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRUNCATED: &str = include_str!("fixtures/truncated.toml");
+    const COMMENTED: &str = include_str!("fixtures/commented.toml");
+    const FUTURE: &str = include_str!("fixtures/future.toml");
+
+    #[test]
+    fn truncated_file_is_filled_with_defaults() {
+        let config = super::super::parse(TRUNCATED).unwrap();
+        assert_eq!(config.app.theme, "dark");
+        assert_eq!(config.app.sync_interval, 10);
+        assert!(config.notifications.enabled);
+
+        let healed = fill_missing(TRUNCATED, &config).unwrap();
+        assert!(healed.starts_with(TRUNCATED.trim_end()));
+        assert!(healed.contains("sync_interval = 10"));
+        assert!(healed.contains("[network]"));
+
+        // 修复后的文件再次加载不应有变化
+        let reparsed = super::super::parse(&healed).unwrap();
+        assert_eq!(fill_missing(&healed, &reparsed).unwrap(), healed);
+    }
+
+    #[test]
+    fn merge_keeps_comments_and_oauth_section() {
+        let mut config = super::super::parse(COMMENTED).unwrap();
+        config.app.theme = "dark".to_string();
+
+        let merged = merge(COMMENTED, &config).unwrap();
+        assert!(merged.contains("# 界面主题"));
+        assert!(merged.contains("theme = \"dark\" # light 或 dark"));
+        assert!(merged.contains("[oauth]"));
+        assert!(merged.contains("client_id = \"test-client-id\""));
+        assert!(unknown_keys(COMMENTED, &config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn future_version_keys_are_preserved() {
+        let config = super::super::parse(FUTURE).unwrap();
+        assert_eq!(config.app.version, "9.0.0");
+
+        let unknown = unknown_keys(FUTURE, &config).unwrap();
+        assert_eq!(unknown, vec!["app.compact_mode", "labels"]);
+
+        let merged = merge(FUTURE, &config).unwrap();
+        assert!(merged.contains("compact_mode = true"));
+        assert!(merged.contains("[labels]"));
+    }
+}
@@ -1,32 +1,401 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 // 新增模块
 pub mod crypto;
+pub mod document;
 pub mod oauth_config;
+pub mod self_test;
+pub mod state;
 pub mod storage;
+pub mod validate;
+pub mod watcher;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 本程序最近一次写入配置文件的内容哈希（文件监听据此忽略自身写入）
+static LAST_SAVED_HASH: Mutex<Option<u64>> = Mutex::new(None);
+
+/// 配置文件读写锁（保存是"读取-合并-写回"，并发的两次保存不能交错）
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 最近一次读取或保存的配置（后台写入在此基础上应用修改，不再重新读取文件）
+static SNAPSHOT: Mutex<Option<Config>> = Mutex::new(None);
+
+/// 等待后台写入的修改
+static PENDING: Mutex<Pending> = Mutex::new(Pending {
+    changes: Vec::new(),
+    last_change: None,
+});
+
+/// 后台写入失败时的提示方式（持有窗口句柄，只要求 `Send`）
+static PERSIST_ERROR: Mutex<Option<Box<dyn Fn() + Send>>> = Mutex::new(None);
+
+/// 后台写入的等待时间：最后一次修改后这么久没有新的修改才写入（连续点击只写一次文件）
+const PERSIST_DELAY: Duration = Duration::from_millis(500);
+
+/// 当前的配置文件修订号（见 [`migrate`]）
+const CONFIG_REVISION: u32 = 1;
+
+/// 早期版本写入但从未生效的同步间隔
+const LEGACY_SYNC_INTERVAL: u64 = 300;
+
+/// 一次配置修改
+type Change = Box<dyn FnOnce(&mut Config) + Send>;
+
+/// 等待后台写入的修改
+struct Pending {
+    /// 按提交顺序排列的修改
+    changes: Vec<Change>,
+
+    /// 最近一次提交修改的时间
+    last_change: Option<Instant>,
+}
+
+/// 应用配置
+///
+/// 所有段和字段缺失时都取默认值：旧版本的配置文件、被手动删减的配置文件都能正常加载
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub app: AppConfig,
+
+    /// 日志配置
+    pub logging: LoggingConfig,
+
+    /// 更新检查缓存
+    pub updates: UpdatesConfig,
+
+    /// 网络配置（代理等）
+    pub network: NetworkConfig,
+
+    /// 通知配置
+    pub notifications: NotificationsConfig,
+
+    /// 自动化配置（新邮件 Webhook）
+    pub automation: AutomationConfig,
+
+    /// 托盘图标配置
+    pub tray: TrayConfig,
+
+    /// 凭据自检
+    pub security: SecurityConfig,
+
+    /// Gmail 服务地址覆盖（隐藏配置：仅供调试，未设置时不写入文件，启动时生效）
+    #[serde(skip_serializing_if = "GmailEndpointsConfig::is_empty")]
+    pub gmail_endpoints: GmailEndpointsConfig,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub version: String,
+
+    /// 配置文件修订号（用于一次性迁移旧版本写入的值，请勿手动修改）
+    pub config_revision: u32,
+
     pub theme: String,
     pub sync_interval: u64,
+
+    /// 窗口是否固定在最前（置顶且不自动隐藏）
+    pub pinned: bool,
+
+    /// 紧凑模式（账户行只显示头像、名称与未读数）
+    pub compact: bool,
+
+    /// 是否每天检查一次新版本
+    pub check_updates: bool,
+
+    /// 界面语言："auto"（跟随系统）| "en" | "zh-CN"
+    pub language: String,
+
+    /// 逐页精确统计邮件数的上限，超出时显示为"上限+"
+    pub count_ceiling: u32,
+
+    /// 单个账户每分钟 API 请求数的警告阈值，超过时记录警告并暂缓同步该账户
+    pub api_rate_warning: u32,
+
+    /// 每轮同步额外尝试（网络探测、刷新 Token 后重试等）的总次数，用完后本轮立即结束
+    pub retry_budget: u32,
+
+    /// 启动时显示的上次同步结果超过多少小时后灰显（直到同步成功）
+    pub cached_counts_max_age_hours: u64,
+
+    /// 账户行与托盘"打开"的方式："browser"（网页版收件箱）| "mailto"（默认邮件程序）
+    pub open_with: String,
+
+    /// 账户列表中已折叠的分组（空字符串表示未分组账户所在的"其他"组）
+    pub collapsed_groups: Vec<String>,
+
+    /// "只统计真人来信"中视为自动邮件的发件人规则（正则表达式，匹配完整的 From 头）
+    pub automated_sender_patterns: Vec<String>,
+
+    /// 按流量计费的网络下是否节流同步（拉长间隔、不获取资料与头像；账户可单独设置 `ignore_metered`）
+    pub respect_metered: bool,
+
+    /// 退出前把最近的同步记录（"同步记录"页面的内容）写入运行时状态文件，下次启动时接着显示
+    pub persist_sync_history: bool,
 }
 
-impl Default for Config {
+impl Default for AppConfig {
     fn default() -> Self {
         Self {
-            app: AppConfig {
-                version: "0.1.0".to_string(),
-                theme: "light".to_string(),
-                sync_interval: 300,
-            },
+            version: crate::utils::app_info::VERSION.to_string(),
+            config_revision: CONFIG_REVISION,
+            theme: "light".to_string(),
+            sync_interval: 10,
+            pinned: false,
+            compact: false,
+            check_updates: true,
+            language: "auto".to_string(),
+            count_ceiling: crate::mail::gmail::api::DEFAULT_COUNT_CEILING,
+            api_rate_warning: crate::mail::quota::DEFAULT_RATE_WARNING,
+            retry_budget: crate::mail::retry::DEFAULT_RETRY_BUDGET,
+            cached_counts_max_age_hours: 24,
+            open_with: "browser".to_string(),
+            collapsed_groups: Vec::new(),
+            automated_sender_patterns: crate::mail::gmail::people::default_patterns(),
+            respect_metered: true,
+            persist_sync_history: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// 文件日志级别（EnvFilter 语法，例如 "info" 或 "nanomail=debug,info"）
+    pub level: String,
+
+    /// 文件日志改为每行一个 JSON 对象（含 span 字段，便于机器解析；修改后重启生效）
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdatesConfig {
+    /// 上次检查时间（Unix 秒，失败也会记录）
+    pub last_check: Option<i64>,
+
+    /// 上次成功获取到的最新版本标签
+    pub latest_tag: Option<String>,
+
+    /// 最新版本的发布页地址
+    pub release_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// 显式代理地址（例如 "http://host:port"）；未设置时使用系统代理，"direct" 表示不使用代理
+    pub proxy: Option<String>,
+
+    /// 代理认证用户名
+    pub proxy_username: Option<String>,
+
+    /// 代理认证密码（保存时加密，格式同 Token：`"encrypted:BASE64..."`；手动填写明文也可识别）
+    #[serde(serialize_with = "serialize_secret")]
+    pub proxy_password: Option<String>,
+
+    /// 请求失败后用于确认网络是否可用的探测端点（依次尝试，任一可达即视为在线）
+    pub probe_endpoints: Vec<String>,
+
+    /// 请求整体超时（秒）
+    pub request_timeout_secs: u64,
+
+    /// 连接建立超时（秒）
+    pub connect_timeout_secs: u64,
+
+    /// 未读数查询的快速截止时间（秒），超出后显示"网络缓慢"
+    pub count_timeout_secs: u64,
+
+    /// 头像下载超时（秒）
+    pub avatar_timeout_secs: u64,
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_count_timeout_secs() -> u64 {
+    8
+}
+
+fn default_avatar_timeout_secs() -> u64 {
+    60
+}
+
+fn default_probe_endpoints() -> Vec<String> {
+    vec![
+        "https://gmail.googleapis.com/".to_string(),
+        "https://www.google.com/generate_204".to_string(),
+    ]
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            proxy_username: None,
+            proxy_password: None,
+            probe_endpoints: default_probe_endpoints(),
+            request_timeout_secs: default_request_timeout_secs(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            count_timeout_secs: default_count_timeout_secs(),
+            avatar_timeout_secs: default_avatar_timeout_secs(),
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// 解密代理密码（未加密的明文原样返回）
+    pub fn decrypt_proxy_password(&self) -> Result<Option<String>> {
+        match &self.proxy_password {
+            Some(value) if crypto::is_encrypted(value) => Ok(Some(crypto::decrypt_token(value)?)),
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+/// 序列化敏感字段（明文先加密）
+fn serialize_secret<S>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::Error;
+
+    match value {
+        Some(v) if crypto::is_encrypted(v) => serializer.serialize_some(v),
+        Some(v) => {
+            let encrypted = crypto::encrypt_token(v).map_err(S::Error::custom)?;
+            serializer.serialize_some(&encrypted)
+        }
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Gmail 服务地址覆盖（未设置的地址使用线上服务，见 `mail::gmail::endpoints`）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GmailEndpointsConfig {
+    /// Gmail API 地址（`.../gmail/v1/users/me`）
+    pub api: Option<String>,
+
+    /// 授权端点
+    pub auth: Option<String>,
+
+    /// Token 端点
+    pub token: Option<String>,
+
+    /// 用户信息端点
+    pub userinfo: Option<String>,
+
+    /// 撤销授权端点
+    pub revoke: Option<String>,
+
+    /// Token 信息端点
+    pub tokeninfo: Option<String>,
+}
+
+impl GmailEndpointsConfig {
+    /// 是否没有任何覆盖
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// 是否在收到新邮件时弹出系统通知
+    pub enabled: bool,
+
+    /// 通知内容（"full" 发件人与主题 | "sender_only" 仅发件人 | "count_only" 仅新邮件数）
+    pub detail: String,
+
+    /// 同一轮同步中多个账户有新邮件时合并为一条通知
+    pub combine: bool,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            detail: "full".to_string(),
+            combine: true,
+        }
+    }
+}
+
+/// 新邮件 Webhook（见 `notification::webhook`，修改后立即生效）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutomationConfig {
+    /// 检测到新邮件时 POST 通知的地址（所有账户共用，未设置时不发送）
+    pub webhook_url: Option<String>,
+
+    /// 随请求发送的共享密钥（`X-NanoMail-Secret` 请求头；保存时加密，格式同代理密码）
+    #[serde(serialize_with = "serialize_secret")]
+    pub webhook_secret: Option<String>,
+
+    /// 按账户覆盖地址（`"邮箱" = "地址"`，空字符串表示该账户不发送）
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub accounts: BTreeMap<String, String>,
+}
+
+impl AutomationConfig {
+    /// 解密共享密钥（未加密的明文原样返回）
+    pub fn decrypt_webhook_secret(&self) -> Result<Option<String>> {
+        match &self.webhook_secret {
+            Some(value) if crypto::is_encrypted(value) => Ok(Some(crypto::decrypt_token(value)?)),
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+/// 托盘未读圆点的分档颜色（见 `tray::badge`，修改后立即生效）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrayConfig {
+    /// 分档的未读数上界（最多两个且严格递增；默认 [5, 20] 即 1-5、6-20、21 以上三档）
+    pub thresholds: Vec<u32>,
+
+    /// 各档颜色（`#RRGGBB`，比 `thresholds` 多一个）
+    pub colors: Vec<String>,
+}
+
+impl Default for TrayConfig {
+    fn default() -> Self {
+        Self {
+            thresholds: crate::tray::badge::DEFAULT_THRESHOLDS.to_vec(),
+            colors: crate::tray::badge::DEFAULT_COLORS
+                .map(str::to_string)
+                .to_vec(),
+        }
+    }
+}
+
+/// 凭据自检（见 `config::self_test`）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// 以本机密钥加密的固定样本（首次自检时写入，之后每次启动时解密以确认密钥没有变化）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_check: Option<String>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "nanomail=debug,info".to_string(),
+            json: false,
         }
     }
 }
@@ -44,22 +413,406 @@ pub fn config_path() -> Result<PathBuf> {
 /// 加载配置
 pub fn load() -> Result<Config> {
     let path = config_path()?;
+    let _guard = lock_file();
+    load_from(&path)
+}
+
+/// 修改配置并保存，返回修改后的配置
+///
+/// 在锁内重新读取文件、应用修改再合并写回，因此同时进行的修改（如切换主题与固定窗口、
+/// 更新检查）不会用各自读到的旧配置互相覆盖
+pub fn update(change: impl FnOnce(&mut Config)) -> Result<Config> {
+    let path = config_path()?;
+    let _guard = lock_file();
+    update_at(&path, change)
+}
+
+/// 在后台保存配置修改，不阻塞调用线程（用于界面上的开关等频繁操作）
+///
+/// 修改先在内存中排队，最后一次修改后 `PERSIST_DELAY` 内没有新的修改时，按顺序应用到最近一次
+/// 读取或保存的配置上并写入一次文件。写入失败时界面状态保持不变，由 `set_persist_error_handler`
+/// 注册的方式提示；修改仍保留在内存中的配置里，下次写入时一并保存
+pub fn persist_async(change: impl FnOnce(&mut Config) + Send + 'static) {
+    let mut pending = lock_pending();
+    let idle = pending.changes.is_empty();
+    pending.changes.push(Box::new(change));
+    pending.last_change = Some(Instant::now());
+    drop(pending);
+
+    if idle {
+        schedule_persist();
+    }
+}
+
+/// 注册后台写入失败时的提示方式；只有第一次注册有效
+pub fn set_persist_error_handler(handler: impl Fn() + Send + 'static) {
+    let mut slot = PERSIST_ERROR.lock().unwrap_or_else(|e| e.into_inner());
+    if slot.is_some() {
+        tracing::debug!("已注册过配置写入失败的提示方式，忽略");
+        return;
+    }
+    *slot = Some(Box::new(handler));
+}
+
+/// 立即写入等待中的修改（退出前调用）
+pub fn flush() -> Result<()> {
+    let path = config_path()?;
+    let _guard = lock_file();
+    let changes = std::mem::take(&mut lock_pending().changes);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let base = match snapshot() {
+        Some(config) => config,
+        None => load_from(&path)?,
+    };
+    persist_at(&path, base, changes)
+}
+
+/// 在 `base` 上按顺序应用修改并写入文件
+fn persist_at(path: &Path, mut base: Config, changes: Vec<Change>) -> Result<()> {
+    let count = changes.len();
+    for change in changes {
+        change(&mut base);
+    }
+    save_to(path, &base)?;
+    tracing::debug!("已写入 {} 项配置修改", count);
+    Ok(())
+}
+
+/// 等到最后一次修改后 `PERSIST_DELAY` 再在后台线程写入
+fn schedule_persist() {
+    let spawned = std::thread::Builder::new()
+        .name("config-persist".to_string())
+        .spawn(|| {
+            // 等待期间有新的修改时顺延
+            loop {
+                let since = lock_pending()
+                    .last_change
+                    .map_or(PERSIST_DELAY, |at| at.elapsed());
+                match PERSIST_DELAY.checked_sub(since) {
+                    Some(wait) if !wait.is_zero() => std::thread::sleep(wait),
+                    _ => break,
+                }
+            }
+            persist_pending();
+        });
+
+    if let Err(e) = spawned {
+        tracing::warn!("启动配置写入线程失败，立即写入: {}", e);
+        persist_pending();
+    }
+}
 
+/// 写入等待中的修改，失败时提示
+fn persist_pending() {
+    if let Err(e) = flush() {
+        tracing::error!("保存配置失败: {:#}", e);
+        let handler = PERSIST_ERROR.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(handler) = handler.as_ref() {
+            handler();
+        }
+    }
+}
+
+fn lock_pending() -> MutexGuard<'static, Pending> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn snapshot() -> Option<Config> {
+    SNAPSHOT.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 记录最近一次读取或保存的配置
+fn remember_snapshot(config: &Config) {
+    *SNAPSHOT.lock().unwrap_or_else(|e| e.into_inner()) = Some(config.clone());
+}
+
+fn lock_file() -> MutexGuard<'static, ()> {
+    FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn update_at(path: &Path, change: impl FnOnce(&mut Config)) -> Result<Config> {
+    let mut config = load_from(path)?;
+    change(&mut config);
+    save_to(path, &config)?;
+    Ok(config)
+}
+
+fn load_from(path: &Path) -> Result<Config> {
     if !path.exists() {
         let config = Config::default();
-        save(&config)?;
+        save_to(path, &config)?;
         return Ok(config);
     }
 
-    let content = std::fs::read_to_string(&path)?;
-    let config: Config = toml::from_str(&content)?;
+    let content = std::fs::read_to_string(path)?;
+    let mut config = parse(&content)?;
+
+    // 未知字段等问题由启动时的校验统一报告（见 `validate::check_at_startup`）
+
+    // 缺少的字段用默认值补全后写回，让旧版或被删减的配置文件自动修复；迁移改动的字段一并写回
+    let migrated = migrate(&content, &mut config)?;
+    let healed = if migrated {
+        document::merge(&content, &config)?
+    } else {
+        document::fill_missing(&content, &config)?
+    };
+    // 写回失败（目录只读、文件被编辑器占用等）不影响本次加载：解析出的配置已经完整可用，下次加载时再试
+    if healed != content {
+        match write_atomically(path, &healed) {
+            Ok(()) if migrated => {
+                tracing::info!("配置文件已迁移到修订版 {} 并写回", CONFIG_REVISION)
+            }
+            Ok(()) => tracing::info!("配置文件缺少部分字段，已用默认值补全"),
+            Err(e) => tracing::warn!("补全配置文件失败，本次使用内存中的配置: {:#}", e),
+        }
+    }
+
+    remember_snapshot(&config);
     Ok(config)
 }
 
-/// 保存配置
-pub fn save(config: &Config) -> Result<()> {
-    let path = config_path()?;
-    let content = toml::to_string_pretty(config)?;
-    std::fs::write(path, content)?;
+/// 一次性迁移旧版本写入的配置（文件中没有修订号或修订号较旧时），返回是否有改动
+///
+/// 早期版本首次运行时写入了 `sync_interval = 300`，但从未按它同步（实际每 10 秒一次）；
+/// 现在这个值会生效，原样沿用会让升级后的同步间隔悄悄变成 5 分钟，因此视为未设置
+fn migrate(content: &str, config: &mut Config) -> Result<bool> {
+    let revision = if document::has_key(content, "app", "config_revision")? {
+        config.app.config_revision
+    } else {
+        0
+    };
+    if revision >= CONFIG_REVISION {
+        return Ok(false);
+    }
+
+    if revision == 0 && config.app.sync_interval == LEGACY_SYNC_INTERVAL {
+        let interval = AppConfig::default().sync_interval;
+        tracing::warn!(
+            "旧版本写入的同步间隔 {} 秒从未生效，改为默认的 {} 秒",
+            LEGACY_SYNC_INTERVAL,
+            interval
+        );
+        config.app.sync_interval = interval;
+    }
+    config.app.config_revision = CONFIG_REVISION;
+    Ok(true)
+}
+
+/// 解析配置文件内容
+pub fn parse(content: &str) -> Result<Config> {
+    let config: Config = toml::from_str(content)?;
+    Ok(config)
+}
+
+/// 保存配置：在原文件基础上合并写入，注释、`[oauth]` 段和未知字段都会保留
+///
+/// 写入失败时内存中的配置仍按本次内容记录，之后的后台写入会一并保存
+fn save_to(path: &Path, config: &Config) -> Result<()> {
+    remember_snapshot(config);
+    let content = match std::fs::read_to_string(path) {
+        Ok(existing) => document::merge(&existing, config)?,
+        Err(_) => toml::to_string_pretty(config)?,
+    };
+    write_atomically(path, &content)
+}
+
+/// 原子写入配置文件（先写临时文件再替换），文件监听不会读到写了一半的内容
+fn write_atomically(path: &Path, content: &str) -> Result<()> {
+    remember_own_write(content);
+    let tmp = path.with_extension("toml.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)?;
     Ok(())
 }
+
+/// 计算配置内容哈希
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 记录本程序写入的内容
+fn remember_own_write(content: &str) {
+    *LAST_SAVED_HASH.lock().unwrap_or_else(|e| e.into_inner()) = Some(content_hash(content));
+}
+
+/// 判断文件内容是否为本程序最近一次写入的（用于避免热重载循环）
+pub fn is_own_write(content: &str) -> bool {
+    *LAST_SAVED_HASH.lock().unwrap_or_else(|e| e.into_inner()) == Some(content_hash(content))
+}
+
+/// 备份并重置配置文件为默认值
+///
+/// 原文件重命名为 `config.toml.bak-YYYYmmdd-HHMMSS`；`[oauth]` 段不属于应用配置，
+/// 会原样保留到新文件中，避免重置后无法登录。返回备份路径（原文件不存在时为 `None`）
+pub fn reset_to_default() -> Result<Option<PathBuf>> {
+    let path = config_path()?;
+    let _guard = lock_file();
+
+    let (backup, oauth_section) = if path.exists() {
+        let content = std::fs::read_to_string(&path)?;
+        let oauth_section = content
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut table| table.remove("oauth"));
+
+        let file_name = format!(
+            "config.toml.bak-{}",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        let backup = path.with_file_name(file_name);
+        std::fs::rename(&path, &backup)?;
+        (Some(backup), oauth_section)
+    } else {
+        (None, None)
+    };
+
+    let mut content = toml::to_string_pretty(&Config::default())?;
+    if let Some(oauth) = oauth_section {
+        let mut table = toml::Table::new();
+        table.insert("oauth".to_string(), oauth);
+        content.push('\n');
+        content.push_str(&toml::to_string_pretty(&table)?);
+    }
+    std::fs::write(&path, content)?;
+    remember_snapshot(&Config::default());
+
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("nanomail-config-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("config.toml")
+    }
+
+    /// 完整的配置文件，带注释与手动添加的 `[oauth]` 段
+    fn hand_edited() -> String {
+        let defaults = toml::to_string_pretty(&Config::default()).unwrap();
+        format!(
+            "# 手动维护的配置\n{}\n# Google OAuth 凭据\n[oauth]\nclient_id = \"id.apps.googleusercontent.com\" # 客户端 ID\nclient_secret = \"secret\"\n",
+            defaults
+        )
+    }
+
+    #[test]
+    fn test_theme_toggle_keeps_rest_of_file() {
+        let path = temp_path("theme");
+        let original = hand_edited();
+        assert!(original.contains("theme = \"light\""));
+        std::fs::write(&path, &original).unwrap();
+
+        update_at(&path, |cfg| cfg.app.theme = "dark".to_string()).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            saved,
+            original.replace("theme = \"light\"", "theme = \"dark\"")
+        );
+        assert!(!path.with_extension("toml.tmp").exists());
+    }
+
+    #[test]
+    fn test_legacy_sync_interval_is_migrated_once() {
+        let path = temp_path("legacy-interval");
+        std::fs::write(
+            &path,
+            "[app]\nversion = \"0.1.0\"\ntheme = \"light\"\nsync_interval = 300\n",
+        )
+        .unwrap();
+
+        // 旧版本写入的 300 从未生效：按默认值处理并写回，同时记录修订号
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.app.sync_interval, AppConfig::default().sync_interval);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("sync_interval = 10"));
+        assert!(saved.contains("config_revision = 1"));
+
+        // 迁移之后用户自己设置的 300 保持不变
+        update_at(&path, |cfg| cfg.app.sync_interval = 300).unwrap();
+        assert_eq!(load_from(&path).unwrap().app.sync_interval, 300);
+
+        // 其他旧值原样保留
+        std::fs::write(&path, "[app]\nsync_interval = 60\n").unwrap();
+        assert_eq!(load_from(&path).unwrap().app.sync_interval, 60);
+    }
+
+    #[test]
+    fn test_heal_write_failure_still_loads() {
+        let path = temp_path("heal-fails");
+        std::fs::write(&path, "[app]\ntheme = \"dark\"\n").unwrap();
+        // 临时文件位置被目录占用：补全后的内容无法写回
+        let tmp = path.with_extension("toml.tmp");
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.app.theme, "dark");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[app]\ntheme = \"dark\"\n"
+        );
+        std::fs::remove_dir(&tmp).unwrap();
+    }
+
+    #[test]
+    fn test_persist_applies_queued_changes_in_one_write() {
+        let path = temp_path("persist");
+        let original = hand_edited();
+        std::fs::write(&path, &original).unwrap();
+        let base = parse(&original).unwrap();
+
+        // 文件在排队期间被改动也不会被重新读取：修改应用在内存中的配置上
+        std::fs::write(&path, original.replace("pinned = false", "pinned = true")).unwrap();
+
+        let changes: Vec<Change> = vec![
+            Box::new(|cfg| cfg.app.theme = "dark".to_string()),
+            Box::new(|cfg| cfg.app.theme = "light".to_string()),
+            Box::new(|cfg| cfg.app.theme = "dark".to_string()),
+        ];
+        persist_at(&path, base, changes).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            saved,
+            original.replace("theme = \"light\"", "theme = \"dark\"")
+        );
+    }
+
+    #[test]
+    fn test_concurrent_updates_do_not_overwrite_each_other() {
+        let path = temp_path("concurrent");
+        std::fs::write(&path, hand_edited()).unwrap();
+
+        let write = |change: fn(&mut Config)| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for _ in 0..20 {
+                    let _guard = lock_file();
+                    update_at(&path, change).unwrap();
+                }
+            })
+        };
+        let pin = write(|cfg| cfg.app.pinned = true);
+        let interval = write(|cfg| cfg.app.sync_interval += 1);
+        pin.join().unwrap();
+        interval.join().unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let config = parse(&content).unwrap();
+        assert!(config.app.pinned);
+        assert_eq!(
+            config.app.sync_interval,
+            AppConfig::default().sync_interval + 20
+        );
+        assert!(content.contains("[oauth]"));
+    }
+}
@@ -17,6 +17,10 @@ pub struct OAuthConfig {
     /// 重定向 URI（本地服务器地址）
     pub redirect_uri: String,
 
+    /// 自定义回调端口（默认端口范围被其他程序占用时使用，须与 `redirect_uri` 中的端口一致）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_port: Option<u16>,
+
     /// 请求的 API 权限范围
     pub scopes: Vec<String>,
 }
@@ -27,6 +31,7 @@ impl Default for OAuthConfig {
             client_id: "YOUR_CLIENT_ID.apps.googleusercontent.com".to_string(),
             client_secret: "YOUR_CLIENT_SECRET".to_string(),
             redirect_uri: "http://localhost:8080".to_string(),
+            redirect_port: None,
             // 修改这里：添加 userinfo.email, userinfo.profile 和 openid
             scopes: vec![
                 "https://www.googleapis.com/auth/gmail.readonly".to_string(), // 读取邮件状态
@@ -69,6 +74,9 @@ impl OAuthConfig {
 
             cfg.redirect_uri =
                 std::env::var("OAUTH_REDIRECT_URI").unwrap_or_else(|_| cfg.redirect_uri.clone());
+            cfg.redirect_port = std::env::var("OAUTH_REDIRECT_PORT")
+                .ok()
+                .and_then(|port| port.parse().ok());
 
             return Ok(cfg);
         }
@@ -131,6 +139,95 @@ impl OAuthConfig {
             || self.client_secret.contains("YOUR_CLIENT_SECRET")
     }
 
+    /// Google OAuth 客户端是否处于"测试"发布状态（`[oauth]` 中的 `testing_mode`，默认否）
+    ///
+    /// 测试状态下 Refresh Token 7 天后失效，开启后 Gmail 账户会在到期前提醒重新授权。
+    /// 单独读取该项，只用环境变量提供客户端凭据时也可以设置
+    pub fn testing_mode() -> bool {
+        let Ok(path) = Self::config_file_path() else {
+            return false;
+        };
+        std::fs::read_to_string(path).is_ok_and(|content| testing_mode_from(&content))
+    }
+
+}
+
+/// 从配置文件内容中读取 `[oauth]` 的 `testing_mode`
+fn testing_mode_from(content: &str) -> bool {
+    toml::from_str::<toml::Value>(content).is_ok_and(|config| {
+        config
+            .get("oauth")
+            .and_then(|oauth| oauth.get("testing_mode"))
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(false)
+    })
+}
+
+/// Outlook（Microsoft 身份平台）OAuth2 配置
+///
+/// 作为公共客户端只使用 PKCE，不需要客户端密钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutlookOAuthConfig {
+    /// Azure 应用注册的客户端 ID
+    pub client_id: String,
+
+    /// 租户（"common" 同时支持个人账户和工作/学校账户）
+    pub tenant: String,
+}
+
+impl Default for OutlookOAuthConfig {
+    fn default() -> Self {
+        Self {
+            client_id: "YOUR_OUTLOOK_CLIENT_ID".to_string(),
+            tenant: "common".to_string(),
+        }
+    }
+}
+
+impl OutlookOAuthConfig {
+    /// 加载 Outlook OAuth2 配置
+    ///
+    /// 优先级（从高到低）：
+    /// 1. 环境变量：`OUTLOOK_CLIENT_ID`（可选 `OUTLOOK_TENANT`）
+    /// 2. 配置文件：`%APPDATA%\NanoMail\config.toml` 的 `[oauth.outlook]` 段
+    /// 3. 默认占位符
+    pub fn load() -> Result<Self> {
+        if let Ok(client_id) = std::env::var("OUTLOOK_CLIENT_ID") {
+            tracing::info!("✅ 从环境变量加载 Outlook OAuth2 配置");
+            return Ok(Self {
+                client_id,
+                tenant: std::env::var("OUTLOOK_TENANT").unwrap_or_else(|_| Self::default().tenant),
+            });
+        }
+
+        if let Ok(config) = Self::load_from_file() {
+            tracing::info!("✅ 从配置文件加载 Outlook OAuth2 配置");
+            return Ok(config);
+        }
+
+        tracing::warn!("⚠️ 未找到 Outlook OAuth2 配置，使用默认占位符");
+        Ok(Self::default())
+    }
+
+    /// 从配置文件的 `[oauth.outlook]` 段加载
+    fn load_from_file() -> Result<Self> {
+        let path = OAuthConfig::config_file_path()?;
+        let content = std::fs::read_to_string(&path)?;
+        let config_toml: toml::Value = toml::from_str(&content)?;
+
+        let section = config_toml
+            .get("oauth")
+            .and_then(|oauth| oauth.get("outlook"))
+            .ok_or_else(|| anyhow::anyhow!("配置文件缺少 [oauth.outlook] 段"))?;
+
+        Ok(section.clone().try_into()?)
+    }
+
+    /// 验证配置是否为默认占位符
+    pub fn is_placeholder(&self) -> bool {
+        self.client_id.contains("YOUR_OUTLOOK_CLIENT_ID")
+    }
 }
 
 #[cfg(test)]
@@ -174,4 +271,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_outlook_section_is_nested_under_oauth() {
+        let content = r#"
+[oauth]
+client_id = "google-id"
+client_secret = "google-secret"
+redirect_uri = "http://localhost:8080"
+scopes = ["openid"]
+
+[oauth.outlook]
+client_id = "outlook-id"
+"#;
+        let value: toml::Value = toml::from_str(content).unwrap();
+        let oauth = value.get("oauth").unwrap();
+
+        // Gmail 配置不受子段影响
+        let google: OAuthConfig = oauth.clone().try_into().unwrap();
+        assert_eq!(google.client_id, "google-id");
+        assert_eq!(google.redirect_port, None);
+
+        let outlook: OutlookOAuthConfig = oauth.get("outlook").unwrap().clone().try_into().unwrap();
+        assert_eq!(outlook.client_id, "outlook-id");
+        assert_eq!(outlook.tenant, "common");
+        assert!(!outlook.is_placeholder());
+        assert!(OutlookOAuthConfig::default().is_placeholder());
+    }
+
+    #[test]
+    fn test_redirect_port() {
+        let content = r#"
+[oauth]
+client_id = "google-id"
+client_secret = "google-secret"
+redirect_uri = "http://localhost:9123"
+redirect_port = 9123
+scopes = ["openid"]
+"#;
+        let value: toml::Value = toml::from_str(content).unwrap();
+        let config: OAuthConfig = value.get("oauth").unwrap().clone().try_into().unwrap();
+        assert_eq!(config.redirect_port, Some(9123));
+    }
+
+    #[test]
+    fn test_testing_mode_flag() {
+        assert!(testing_mode_from("[oauth]\ntesting_mode = true\n"));
+        assert!(!testing_mode_from("[oauth]\ntesting_mode = false\n"));
+        assert!(!testing_mode_from("[oauth]\nclient_id = \"id\"\n"));
+        assert!(!testing_mode_from(""));
+        assert!(!testing_mode_from("not toml ["));
+    }
 }
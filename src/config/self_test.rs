@@ -0,0 +1,240 @@
+/// 凭据自检
+///
+/// 账户凭据都以本机密钥加密保存（见 `crypto` 与 `utils::machine_id`）。注册表权限丢失、机器 GUID 变化、
+/// 更新后密钥派生参数变化等问题平时不会暴露，要等同步中解密 Token 失败时才逐个账户报错。
+/// 因此启动时先做一次快速自检：用本机密钥加密再解密一个固定样本，并解密配置中保存的样本
+/// （`[security] credential_check`，首次自检通过时写入）。自检失败时凭据不可用：
+/// 界面不启动同步并显示凭据错误页面，无界面模式直接报错；`--doctor` 与托盘"运行诊断"也包含自检结果
+use anyhow::Result;
+use thiserror::Error;
+
+use super::crypto::{self, CredentialError};
+use crate::utils::i18n::{tr, tr_fmt};
+use crate::utils::machine_id;
+
+/// 自检样本的明文
+const PROBE: &str = "NanoMail credential self-test";
+
+/// 加密密钥的来源（测试中可注入总是失败或返回其他密钥的实现）
+pub trait KeyProvider {
+    fn key(&self) -> Result<[u8; 32]>;
+}
+
+/// 本机密钥（从机器 ID 派生）
+pub struct MachineKey;
+
+impl KeyProvider for MachineKey {
+    fn key(&self) -> Result<[u8; 32]> {
+        machine_id::derive_encryption_key()
+    }
+}
+
+/// 自检失败的原因
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Failure {
+    /// 无法取得本机密钥（如无法读取机器 GUID）
+    #[error("无法取得本机加密密钥: {0}")]
+    KeyUnavailable(String),
+
+    /// 加密后无法解密
+    #[error("加密自检失败: {0}")]
+    Roundtrip(String),
+
+    /// 保存的样本无法用本机密钥解密：密钥已变化，之前保存的凭据都无法解密
+    #[error("本机加密密钥已变化，保存的凭据无法解密")]
+    KeyChanged,
+}
+
+impl Failure {
+    /// 界面与诊断报告中的说明
+    pub fn describe(&self) -> String {
+        match self {
+            Self::KeyUnavailable(reason) => tr_fmt("credentials.key_unavailable", &[reason]),
+            Self::Roundtrip(reason) => tr_fmt("credentials.roundtrip", &[reason]),
+            Self::KeyChanged => tr("credentials.key_changed").to_string(),
+        }
+    }
+}
+
+/// 自检结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// 通过；`sample` 为需要保存的新样本（还没有样本，或保存的样本已损坏）
+    Passed { sample: Option<String> },
+
+    /// 失败
+    Failed(Failure),
+}
+
+/// 运行自检
+///
+/// # Arguments
+/// * `keys` - 密钥来源
+/// * `sample` - 配置中保存的样本（没有时只检查加密再解密）
+pub fn run(keys: &dyn KeyProvider, sample: Option<&str>) -> Outcome {
+    let key = match keys.key() {
+        Ok(key) => key,
+        Err(e) => return Outcome::Failed(Failure::KeyUnavailable(format!("{:#}", e))),
+    };
+    let fresh = match roundtrip(&key) {
+        Ok(fresh) => fresh,
+        Err(reason) => return Outcome::Failed(Failure::Roundtrip(reason)),
+    };
+
+    let Some(sample) = sample else {
+        return Outcome::Passed {
+            sample: Some(fresh),
+        };
+    };
+    match crypto::decrypt_with_key(&key, sample) {
+        Ok(plain) if plain == PROBE => Outcome::Passed { sample: None },
+        Err(e) if e.downcast_ref::<CredentialError>() == Some(&CredentialError::ForeignMachine) => {
+            Outcome::Failed(Failure::KeyChanged)
+        }
+        // 样本被手动修改或已损坏，说明不了密钥的问题：换成新样本
+        _ => {
+            tracing::warn!("凭据自检样本无法读取，已替换为新样本");
+            Outcome::Passed {
+                sample: Some(fresh),
+            }
+        }
+    }
+}
+
+/// 用密钥加密样本再解密，返回加密后的样本
+fn roundtrip(key: &[u8; 32]) -> Result<String, String> {
+    let encrypted = crypto::encrypt_with_key(key, PROBE).map_err(|e| format!("{:#}", e))?;
+    match crypto::decrypt_with_key(key, &encrypted) {
+        Ok(plain) if plain == PROBE => Ok(encrypted),
+        Ok(_) => Err("解密结果与原文不一致".to_string()),
+        Err(e) => Err(format!("{:#}", e)),
+    }
+}
+
+/// 配置中保存的样本
+fn saved_sample() -> Option<String> {
+    super::load()
+        .ok()
+        .and_then(|config| config.security.credential_check)
+}
+
+/// 保存新样本（失败只记录警告，下次启动时重新写入）
+fn save_sample(sample: String) {
+    if let Err(e) = super::update(|config| config.security.credential_check = Some(sample)) {
+        tracing::warn!("保存凭据自检样本失败: {:#}", e);
+    }
+}
+
+/// 启动时运行自检（需要派生本机密钥，应在阻塞线程中调用）
+///
+/// 通过时按需保存新样本；失败时返回原因（调用方据此暂停同步）
+pub fn run_at_startup() -> Option<Failure> {
+    match run(&MachineKey, saved_sample().as_deref()) {
+        Outcome::Passed { sample } => {
+            tracing::info!("✅ 凭据自检通过");
+            if let Some(sample) = sample {
+                save_sample(sample);
+            }
+            None
+        }
+        Outcome::Failed(failure) => {
+            tracing::error!("❌ 凭据自检失败，暂停同步: {}", failure);
+            Some(failure)
+        }
+    }
+}
+
+/// 接受当前的本机密钥（重新授权全部账户前调用）
+///
+/// 保存以当前密钥加密的新样本；仍无法取得密钥或加解密失败时返回错误
+pub fn accept_current_key() -> Result<()> {
+    let key = MachineKey.key()?;
+    let sample = roundtrip(&key).map_err(anyhow::Error::msg)?;
+    super::update(|config| config.security.credential_check = Some(sample))?;
+    tracing::info!("已接受当前的本机加密密钥");
+    Ok(())
+}
+
+/// 只运行自检、不保存样本（诊断使用）
+pub fn check() -> Outcome {
+    run(&MachineKey, saved_sample().as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 固定的密钥
+    struct FixedKey([u8; 32]);
+
+    impl KeyProvider for FixedKey {
+        fn key(&self) -> Result<[u8; 32]> {
+            Ok(self.0)
+        }
+    }
+
+    /// 总是失败的密钥来源（如注册表权限丢失）
+    struct FailingKey;
+
+    impl KeyProvider for FailingKey {
+        fn key(&self) -> Result<[u8; 32]> {
+            anyhow::bail!("无法读取 MachineGuid 值")
+        }
+    }
+
+    fn saved(keys: &dyn KeyProvider) -> String {
+        match run(keys, None) {
+            Outcome::Passed {
+                sample: Some(sample),
+            } => sample,
+            other => panic!("首次自检应返回新样本: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_first_run_returns_sample_then_verifies_it() {
+        let keys = FixedKey([7; 32]);
+        let sample = saved(&keys);
+        assert!(crypto::is_encrypted(&sample));
+        assert_eq!(run(&keys, Some(&sample)), Outcome::Passed { sample: None });
+    }
+
+    #[test]
+    fn test_changed_key_is_detected() {
+        let sample = saved(&FixedKey([1; 32]));
+        assert_eq!(
+            run(&FixedKey([2; 32]), Some(&sample)),
+            Outcome::Failed(Failure::KeyChanged)
+        );
+    }
+
+    #[test]
+    fn test_failing_key_provider() {
+        let Outcome::Failed(Failure::KeyUnavailable(reason)) = run(&FailingKey, None) else {
+            panic!("密钥来源失败时自检应失败");
+        };
+        assert!(reason.contains("MachineGuid"));
+        assert!(!Failure::KeyUnavailable(reason).describe().is_empty());
+    }
+
+    #[test]
+    fn test_corrupted_sample_is_replaced() {
+        let keys = FixedKey([3; 32]);
+        for corrupted in ["plain", "encrypted:!!!", "encrypted:AAAA"] {
+            let Outcome::Passed {
+                sample: Some(sample),
+            } = run(&keys, Some(corrupted))
+            else {
+                panic!("损坏的样本应被替换: {corrupted}");
+            };
+            assert_eq!(run(&keys, Some(&sample)), Outcome::Passed { sample: None });
+        }
+
+        // 同一密钥加密的其他内容也不是密钥问题
+        let other = crypto::encrypt_with_key(&keys.0, "something else").unwrap();
+        assert!(matches!(
+            run(&keys, Some(&other)),
+            Outcome::Passed { sample: Some(_) }
+        ));
+    }
+}
@@ -0,0 +1,822 @@
+/// 运行时状态文件
+///
+/// 保存同步过程中产生的数据（如新邮件通知基准、上次同步的结果），与保存身份、凭据和用户设置的
+/// accounts.toml 分开，同步时只写这个文件。文件损坏时从空状态重建，只会丢失这些可重建的数据。
+///
+/// 邮件内容不写入这个文件：已通知的邮件只保存邮件 ID 与发件人、主题的加盐摘要（用于去重），
+/// 可读的发件人与主题只在本次运行的内存中
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use crate::mail::MailPreview;
+use crate::mail::gmail::labels::Label;
+use crate::sync::history::RoundRecord;
+
+/// 状态文件版本号
+const STATE_VERSION: u32 = 1;
+
+/// 未读数历史保留的时长
+pub const HISTORY_WINDOW: chrono::Duration = chrono::Duration::hours(24);
+
+/// 未读数历史的时间分辨率（同一区间内的多次同步只保留最后一次）
+const HISTORY_RESOLUTION: chrono::Duration = chrono::Duration::minutes(5);
+
+/// 未读数历史最多保留的采样点数（24 小时 / 5 分钟）
+const HISTORY_MAX_POINTS: usize = 288;
+
+/// Gmail 标签列表缓存的有效期
+pub const LABEL_CACHE_TTL: chrono::Duration = chrono::Duration::hours(24);
+
+/// 每个账户保留的已通知邮件记录数
+const NOTIFIED_MAX: usize = 200;
+
+/// 未读提醒触发后，未读数须回落到阈值减去此比例（至少 1 封）及以下才重新启用提醒，
+/// 避免未读数在阈值附近来回波动时反复提醒
+const ALERT_HYSTERESIS_PERCENT: u32 = 10;
+
+/// 新邮件通知基准：上一次同步的未读数及当时的计数基准时间与通知标签
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationBaseline {
+    /// 未读数
+    pub unread: u32,
+
+    /// 计数基准时间（统计全部未读时为 `None`）
+    #[serde(default)]
+    pub counted_since: Option<DateTime<Utc>>,
+
+    /// 通知依据的 Gmail 标签（收件箱或其他账户为 `None`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// 未读数采样点
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnreadSample {
+    /// 采样时间
+    pub at: DateTime<Utc>,
+
+    /// 未读数
+    pub unread: u32,
+}
+
+/// 上一次成功同步的结果（启动时在首次同步完成前显示）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncSnapshot {
+    /// 未读数
+    pub unread: u32,
+
+    /// 服务端返回的显示名称
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub display_name: String,
+
+    /// 头像缓存路径（为空时使用缓存头像或占位符）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub avatar_path: String,
+
+    /// 同步时间（内容不变时按 `HISTORY_RESOLUTION` 的间隔更新）
+    pub synced_at: DateTime<Utc>,
+}
+
+impl SyncSnapshot {
+    /// 距上次同步是否已超过 `max_age`（系统时间被调回时不算过期）
+    pub fn is_stale(&self, now: DateTime<Utc>, max_age: chrono::Duration) -> bool {
+        now - self.synced_at > max_age
+    }
+}
+
+/// 缓存的 Gmail 标签列表（详情面板的标签选择使用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelCache {
+    /// 读取时间
+    pub fetched_at: DateTime<Utc>,
+
+    /// 可供选择的标签（已按 `labels::pickable` 排序）
+    pub labels: Vec<Label>,
+}
+
+impl LabelCache {
+    /// 是否仍在有效期内（系统时间被调回时视为过期）
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        self.fetched_at <= now && now - self.fetched_at < LABEL_CACHE_TTL
+    }
+}
+
+/// 已通知的邮件（只有邮件 ID 与加盐摘要，不含可读的发件人与主题）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotifiedMail {
+    /// 邮件 ID（提供方没有 ID 时为空，按摘要去重）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+
+    /// 发件人与主题的加盐 SHA-256 摘要（十六进制）
+    pub digest: String,
+}
+
+/// 发件人与主题的加盐摘要（盐见 [`RuntimeState::salt`]，不同安装之间的摘要无法比较）
+pub fn preview_digest(salt: &str, preview: &MailPreview) -> String {
+    let mut hasher = Sha256::new();
+    for part in [salt, &preview.sender, &preview.subject] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 一分钟内的 API 请求数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestBucket {
+    /// 所在分钟的起始时间
+    pub minute: DateTime<Utc>,
+
+    /// 请求数
+    pub count: u32,
+}
+
+/// 单个账户的运行时状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountState {
+    /// 新邮件通知基准（尚未同步过时为 `None`）
+    pub notification: Option<NotificationBaseline>,
+
+    /// 最近 24 小时的未读数（按时间升序）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<UnreadSample>,
+
+    /// 邮件服务被停用时，在此时间之前不再重试（轮询同步跳过该账户）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unavailable_until: Option<DateTime<Utc>>,
+
+    /// 最近 24 小时的 API 请求数（按分钟，时间升序）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub api_requests: Vec<RequestBucket>,
+
+    /// 上一次成功同步的结果（尚未同步过时为 `None`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_sync: Option<SyncSnapshot>,
+
+    /// 未读提醒已触发，等待未读数回落后重新启用（重启后不重复提醒）
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub alert_fired: bool,
+
+    /// Gmail 标签列表缓存（尚未读取或已失效时为 `None`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<LabelCache>,
+
+    /// 最近通知过的邮件（按时间升序，用于去重）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notified: Vec<NotifiedMail>,
+}
+
+impl AccountState {
+    /// 记录一次同步得到的未读数
+    ///
+    /// 与上一个采样点处于同一分辨率区间时覆盖上一个点；系统时间被调回时丢弃"未来"的采样点，
+    /// 超出保留时长或点数上限的旧采样点随之删除
+    pub fn record_unread(&mut self, now: DateTime<Utc>, unread: u32) {
+        let history = &mut self.history;
+        history.retain(|sample| sample.at <= now && now - sample.at < HISTORY_WINDOW);
+
+        match history.last_mut() {
+            Some(last) if now - last.at < HISTORY_RESOLUTION => last.unread = unread,
+            _ => history.push(UnreadSample { at: now, unread }),
+        }
+
+        if history.len() > HISTORY_MAX_POINTS {
+            history.drain(..history.len() - HISTORY_MAX_POINTS);
+        }
+    }
+
+    /// 按未读提醒阈值检查本次同步的未读数，返回是否应发送提醒
+    ///
+    /// 未读数达到阈值且提醒未触发时触发一次；之后须回落到阈值减去滞后量（含）以下才重新启用。
+    /// 未设置阈值时清除触发记录
+    pub fn check_alert(&mut self, threshold: Option<u32>, unread: u32) -> bool {
+        let Some(threshold) = threshold else {
+            self.alert_fired = false;
+            return false;
+        };
+
+        if unread >= threshold {
+            let fire = !self.alert_fired;
+            self.alert_fired = true;
+            return fire;
+        }
+
+        let margin = (threshold.saturating_mul(ALERT_HYSTERESIS_PERCENT) / 100).max(1);
+        if unread <= threshold.saturating_sub(margin) {
+            self.alert_fired = false;
+        }
+        false
+    }
+
+    /// 记录一次成功同步的结果
+    ///
+    /// `profile` 为服务端返回的显示名称与头像路径，资料获取失败时传 `None`，保留上次的资料；
+    /// 内容没有变化时同步时间最多每 `HISTORY_RESOLUTION` 更新一次，避免每轮同步都写文件
+    pub fn record_sync(&mut self, now: DateTime<Utc>, unread: u32, profile: Option<(&str, &str)>) {
+        let previous = self.last_sync.take();
+        let (display_name, avatar_path) = match (profile, &previous) {
+            (Some((name, avatar)), _) => (name.to_string(), avatar.to_string()),
+            (None, Some(last)) => (last.display_name.clone(), last.avatar_path.clone()),
+            (None, None) => (String::new(), String::new()),
+        };
+
+        let unchanged = previous.as_ref().is_some_and(|last| {
+            last.unread == unread
+                && last.display_name == display_name
+                && last.avatar_path == avatar_path
+                && last.synced_at <= now
+                && now - last.synced_at < HISTORY_RESOLUTION
+        });
+        self.last_sync = if unchanged {
+            previous
+        } else {
+            Some(SyncSnapshot {
+                unread,
+                display_name,
+                avatar_path,
+                synced_at: now,
+            })
+        };
+    }
+
+    /// 还没有通知过的邮件（有邮件 ID 时按 ID 比较，否则按发件人与主题的摘要比较）
+    pub fn unseen_previews(&self, salt: &str, previews: &[MailPreview]) -> Vec<MailPreview> {
+        previews
+            .iter()
+            .filter(|preview| {
+                let digest = preview_digest(salt, preview);
+                !self.notified.iter().any(|seen| match preview.id.as_str() {
+                    "" => seen.digest == digest,
+                    id => seen.id == id,
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 记录已通知的邮件（只保存邮件 ID 与摘要）
+    pub fn record_notified(&mut self, salt: &str, previews: &[MailPreview]) {
+        // 摘要最新的在前，按时间升序追加时倒过来
+        self.notified
+            .extend(previews.iter().rev().map(|preview| NotifiedMail {
+                id: preview.id.clone(),
+                digest: preview_digest(salt, preview),
+            }));
+        if self.notified.len() > NOTIFIED_MAX {
+            self.notified.drain(..self.notified.len() - NOTIFIED_MAX);
+        }
+    }
+}
+
+/// 状态文件内容
+#[derive(Debug, Serialize, Deserialize)]
+struct StateFile {
+    /// 文件格式版本
+    version: u32,
+
+    /// 邮件摘要使用的随机盐（首次需要时生成）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+
+    /// 各账户状态（以邮箱地址为键）
+    #[serde(default)]
+    accounts: BTreeMap<String, AccountState>,
+
+    /// 上次退出前的同步记录（旧的在前；只在 `[app] persist_sync_history = true` 时写入）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sync_history: Vec<RoundRecord>,
+}
+
+impl Default for StateFile {
+    fn default() -> Self {
+        Self {
+            version: STATE_VERSION,
+            salt: None,
+            accounts: BTreeMap::new(),
+            sync_history: Vec::new(),
+        }
+    }
+}
+
+/// 运行时状态（首次访问时从文件加载，修改后立即写回）
+pub struct RuntimeState {
+    /// 状态文件路径（为 `None` 时只保存在内存中）
+    path: Option<PathBuf>,
+
+    /// 已加载的状态（`None` 表示尚未加载）
+    data: Mutex<Option<StateFile>>,
+}
+
+impl RuntimeState {
+    /// 使用指定的状态文件
+    pub fn open(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            data: Mutex::new(None),
+        }
+    }
+
+    /// 只保存在内存中（无法获取配置目录时使用）
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            data: Mutex::new(None),
+        }
+    }
+
+    /// 读取账户状态（没有记录时返回默认值）
+    pub fn account(&self, email: &str) -> AccountState {
+        let data = self.lock();
+        data.as_ref()
+            .expect("状态已加载")
+            .accounts
+            .get(email)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 修改账户状态，有变化时写回文件
+    ///
+    /// 写入失败时内存中的修改仍然生效，下次修改时会再次尝试写入
+    pub fn update_account(
+        &self,
+        email: &str,
+        change: impl FnOnce(&mut AccountState),
+    ) -> Result<()> {
+        let mut data = self.lock();
+        let state = data.as_mut().expect("状态已加载");
+
+        let entry = state.accounts.entry(email.to_string()).or_default();
+        let before = entry.clone();
+        change(entry);
+        if *entry == before {
+            return Ok(());
+        }
+        if *entry == AccountState::default() {
+            state.accounts.remove(email);
+        }
+
+        match &self.path {
+            Some(path) => write_state(path, state),
+            None => Ok(()),
+        }
+    }
+
+    /// 邮件摘要使用的随机盐（首次调用时生成并写入文件，写入失败时本次运行仍使用新生成的盐）
+    pub fn salt(&self) -> String {
+        let mut data = self.lock();
+        let state = data.as_mut().expect("状态已加载");
+        if let Some(salt) = &state.salt {
+            return salt.clone();
+        }
+
+        let salt: String = rand::random::<[u8; 16]>()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        state.salt = Some(salt.clone());
+        if let Some(path) = &self.path
+            && let Err(e) = write_state(path, state)
+        {
+            tracing::warn!("保存运行时状态失败: {:#}", e);
+        }
+        salt
+    }
+
+    /// 上次保存的同步记录（旧的在前）
+    pub fn sync_history(&self) -> Vec<RoundRecord> {
+        self.lock()
+            .as_ref()
+            .expect("状态已加载")
+            .sync_history
+            .clone()
+    }
+
+    /// 保存同步记录（为空时清除），有变化时写回文件
+    pub fn save_sync_history(&self, rounds: Vec<RoundRecord>) -> Result<()> {
+        let mut data = self.lock();
+        let state = data.as_mut().expect("状态已加载");
+        if state.sync_history == rounds {
+            return Ok(());
+        }
+        state.sync_history = rounds;
+
+        match &self.path {
+            Some(path) => write_state(path, state),
+            None => Ok(()),
+        }
+    }
+
+    /// 加锁，首次访问时加载文件（返回的值总是 `Some`）
+    fn lock(&self) -> MutexGuard<'_, Option<StateFile>> {
+        let mut data = self.data.lock().unwrap_or_else(|e| e.into_inner());
+        if data.is_none() {
+            *data = Some(match &self.path {
+                Some(path) => read_state(path),
+                None => StateFile::default(),
+            });
+        }
+        data
+    }
+}
+
+/// 读取状态文件；文件不存在或损坏时返回空状态
+fn read_state(path: &Path) -> StateFile {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            tracing::debug!("状态文件不存在，使用空状态");
+            return StateFile::default();
+        }
+        Err(e) => {
+            tracing::warn!("读取状态文件失败，使用空状态: {}", e);
+            return StateFile::default();
+        }
+    };
+
+    match serde_json::from_str::<StateFile>(&content) {
+        Ok(state) => {
+            if state.version != STATE_VERSION {
+                tracing::warn!(
+                    "状态文件版本不匹配（期望: {}, 实际: {}），尝试兼容加载",
+                    STATE_VERSION,
+                    state.version
+                );
+            }
+            state
+        }
+        Err(e) => {
+            // 只会丢失通知基准：下一轮同步重新记录
+            tracing::warn!("状态文件已损坏，从空状态重建: {}", e);
+            StateFile::default()
+        }
+    }
+}
+
+/// 原子写入状态文件（先写临时文件再替换，写到一半崩溃不会留下损坏的文件）
+fn write_state(path: &Path, state: &StateFile) -> Result<()> {
+    let content = serde_json::to_string_pretty(state).context("序列化运行时状态失败")?;
+
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content)
+        .with_context(|| format!("写入状态文件失败: {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("替换状态文件失败: {}", path.display()))?;
+
+    Ok(())
+}
+
+/// 获取状态文件路径
+///
+/// 返回：`%APPDATA%\NanoMail\state.json`
+pub fn state_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?
+        .join("NanoMail");
+
+    std::fs::create_dir_all(&config_dir).context("创建配置目录失败")?;
+
+    Ok(config_dir.join("state.json"))
+}
+
+/// 进程内共享的运行时状态
+pub fn global() -> &'static RuntimeState {
+    static STATE: OnceLock<RuntimeState> = OnceLock::new();
+
+    STATE.get_or_init(|| match state_path() {
+        Ok(path) => RuntimeState::open(path),
+        Err(e) => {
+            tracing::warn!("无法确定状态文件路径，运行时状态不会保存: {}", e);
+            RuntimeState::in_memory()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("nanomail-state-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        std::fs::remove_file(&path).ok();
+        path
+    }
+
+    fn baseline(unread: u32) -> Option<NotificationBaseline> {
+        Some(NotificationBaseline {
+            unread,
+            counted_since: None,
+            label: None,
+        })
+    }
+
+    #[test]
+    fn test_update_round_trip() {
+        let path = temp_path("round-trip");
+
+        let state = RuntimeState::open(path.clone());
+        assert_eq!(state.account("me@example.com"), AccountState::default());
+        state
+            .update_account("me@example.com", |s| s.notification = baseline(3))
+            .unwrap();
+
+        // 重新打开后仍然保留
+        let reopened = RuntimeState::open(path.clone());
+        assert_eq!(reopened.account("me@example.com").notification, baseline(3));
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+
+    #[test]
+    fn test_unchanged_update_does_not_write() {
+        let path = temp_path("unchanged");
+
+        let state = RuntimeState::open(path.clone());
+        state
+            .update_account("me@example.com", |s| s.notification = baseline(1))
+            .unwrap();
+
+        // 删除文件后做一次无变化的修改，不应重新写入
+        std::fs::remove_file(&path).unwrap();
+        state
+            .update_account("me@example.com", |s| s.notification = baseline(1))
+            .unwrap();
+        assert!(!path.exists());
+    }
+
+    fn sample(at: DateTime<Utc>, unread: u32) -> UnreadSample {
+        UnreadSample { at, unread }
+    }
+
+    #[test]
+    fn test_check_alert_fires_once_per_crossing() {
+        let mut state = AccountState::default();
+        let threshold = Some(20);
+
+        assert!(!state.check_alert(threshold, 19));
+        assert!(state.check_alert(threshold, 21));
+        assert!(!state.check_alert(threshold, 25));
+
+        // 在滞后区间内波动不会重新启用
+        assert!(!state.check_alert(threshold, 19));
+        assert!(!state.check_alert(threshold, 20));
+        assert!(state.alert_fired);
+
+        // 回落到阈值减去滞后量（20 的 10%，即 18）后重新启用
+        assert!(!state.check_alert(threshold, 18));
+        assert!(!state.alert_fired);
+        assert!(state.check_alert(threshold, 20));
+
+        // 取消阈值后清除记录
+        assert!(!state.check_alert(None, 30));
+        assert_eq!(state, AccountState::default());
+    }
+
+    #[test]
+    fn test_check_alert_small_threshold_rearms_at_zero() {
+        let mut state = AccountState::default();
+        assert!(state.check_alert(Some(1), 1));
+        assert!(!state.check_alert(Some(1), 1));
+        assert!(!state.check_alert(Some(1), 0));
+        assert!(state.check_alert(Some(1), 2));
+    }
+
+    #[test]
+    fn test_record_unread_downsamples() {
+        let start = Utc::now();
+        let mut state = AccountState::default();
+
+        // 同一 5 分钟区间内只保留最后一次
+        state.record_unread(start, 1);
+        state.record_unread(start + chrono::Duration::minutes(2), 3);
+        state.record_unread(start + chrono::Duration::minutes(6), 4);
+        assert_eq!(
+            state.history,
+            vec![
+                sample(start, 3),
+                sample(start + chrono::Duration::minutes(6), 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_unread_is_bounded() {
+        let start = Utc::now();
+        let mut state = AccountState::default();
+
+        // 两天内每 5 分钟同步一次，只保留最近 24 小时
+        for i in 0..(2 * HISTORY_MAX_POINTS as i64) {
+            state.record_unread(start + chrono::Duration::minutes(5 * i), i as u32);
+        }
+        assert!(state.history.len() <= HISTORY_MAX_POINTS);
+        let last = *state.history.last().unwrap();
+        assert!(
+            state
+                .history
+                .iter()
+                .all(|s| last.at - s.at < HISTORY_WINDOW)
+        );
+    }
+
+    #[test]
+    fn test_record_unread_survives_clock_changes() {
+        let start = Utc::now();
+        let mut state = AccountState::default();
+        state.record_unread(start, 1);
+        state.record_unread(start + chrono::Duration::minutes(10), 2);
+
+        // 时间被调回：丢弃"未来"的采样点
+        state.record_unread(start - chrono::Duration::hours(1), 5);
+        assert_eq!(
+            state.history,
+            vec![sample(start - chrono::Duration::hours(1), 5)]
+        );
+
+        // 时间大幅前跳：旧采样点全部过期
+        state.record_unread(start + chrono::Duration::days(3), 6);
+        assert_eq!(
+            state.history,
+            vec![sample(start + chrono::Duration::days(3), 6)]
+        );
+    }
+
+    #[test]
+    fn test_record_sync_keeps_profile_and_limits_writes() {
+        let start = Utc::now();
+        let mut state = AccountState::default();
+        state.record_sync(start, 3, Some(("Me", "/cache/me.png")));
+
+        // 内容不变时不更新时间（状态文件不用重写）
+        state.record_sync(
+            start + chrono::Duration::minutes(2),
+            3,
+            Some(("Me", "/cache/me.png")),
+        );
+        assert_eq!(state.last_sync.as_ref().unwrap().synced_at, start);
+
+        // 资料获取失败时保留上次的资料
+        let later = start + chrono::Duration::minutes(3);
+        state.record_sync(later, 5, None);
+        assert_eq!(
+            state.last_sync,
+            Some(SyncSnapshot {
+                unread: 5,
+                display_name: "Me".to_string(),
+                avatar_path: "/cache/me.png".to_string(),
+                synced_at: later,
+            })
+        );
+
+        let max_age = chrono::Duration::hours(24);
+        let snapshot = state.last_sync.unwrap();
+        assert!(!snapshot.is_stale(later + chrono::Duration::hours(23), max_age));
+        assert!(snapshot.is_stale(later + chrono::Duration::hours(25), max_age));
+        assert!(!snapshot.is_stale(later - chrono::Duration::hours(25), max_age));
+    }
+
+    #[test]
+    fn test_label_cache_expires() {
+        let now = Utc::now();
+        let cache = LabelCache {
+            fetched_at: now,
+            labels: Vec::new(),
+        };
+        assert!(cache.is_fresh(now + chrono::Duration::hours(23)));
+        assert!(!cache.is_fresh(now + LABEL_CACHE_TTL));
+        assert!(!cache.is_fresh(now - chrono::Duration::minutes(1)));
+    }
+
+    fn mail(id: &str, sender: &str, subject: &str) -> MailPreview {
+        MailPreview {
+            id: id.to_string(),
+            sender: sender.to_string(),
+            subject: subject.to_string(),
+        }
+    }
+
+    /// 模拟几轮同步：每轮只通知没有通知过的邮件并记录，返回各轮通知的邮件 ID
+    fn simulate_rounds(state: &RuntimeState, rounds: &[Vec<MailPreview>]) -> Vec<Vec<String>> {
+        let salt = state.salt();
+        rounds
+            .iter()
+            .map(|previews| {
+                let unseen = state
+                    .account("me@example.com")
+                    .unseen_previews(&salt, previews);
+                state
+                    .update_account("me@example.com", |s| s.record_notified(&salt, &unseen))
+                    .unwrap();
+                unseen.into_iter().map(|preview| preview.id).collect()
+            })
+            .collect()
+    }
+
+    const SECRET_SUBJECT: &str = "Salary review for Q3";
+    const SECRET_SENDER: &str = "Alice Example";
+
+    #[test]
+    fn test_state_file_never_contains_previews() {
+        let path = temp_path("previews");
+        let state = RuntimeState::open(path.clone());
+        let notified = simulate_rounds(
+            &state,
+            &[
+                vec![mail("m1", SECRET_SENDER, SECRET_SUBJECT)],
+                vec![
+                    mail("m2", "Bob", "Lunch?"),
+                    mail("m1", SECRET_SENDER, SECRET_SUBJECT),
+                ],
+                vec![mail("", "Carol", "Invoice 42")],
+                vec![mail("", "Carol", "Invoice 42")],
+            ],
+        );
+        // 按 ID 去重；没有 ID 的邮件按摘要去重
+        assert_eq!(notified, [vec!["m1"], vec!["m2"], vec![""], vec![]]);
+
+        let bytes = std::fs::read(&path).unwrap();
+        let content = String::from_utf8_lossy(&bytes);
+        for secret in [
+            SECRET_SUBJECT,
+            SECRET_SENDER,
+            "Lunch?",
+            "Bob",
+            "Carol",
+            "Invoice",
+        ] {
+            assert!(!content.contains(secret), "状态文件中出现了 {secret:?}");
+        }
+        assert!(content.contains("m1"));
+
+        // 重新打开后仍能去重（盐随状态文件保存）
+        let reopened = RuntimeState::open(path);
+        let again = simulate_rounds(
+            &reopened,
+            &[vec![mail("m1", SECRET_SENDER, SECRET_SUBJECT)]],
+        );
+        assert_eq!(again, [Vec::<String>::new()]);
+    }
+
+    #[test]
+    fn test_legacy_saved_previews_are_dropped() {
+        let path = temp_path("legacy-previews");
+        std::fs::write(
+            &path,
+            format!(
+                r#"{{"version":1,"accounts":{{"me@example.com":{{"previews":[{{"sender":"{}","subject":"{}","at":"2026-01-01T00:00:00Z"}}]}}}}}}"#,
+                SECRET_SENDER, SECRET_SUBJECT
+            ),
+        )
+        .unwrap();
+
+        // 旧版本开启 persist_previews 时保存的原文在下次写入时清除
+        let state = RuntimeState::open(path.clone());
+        state
+            .update_account("me@example.com", |s| s.record_unread(Utc::now(), 1))
+            .unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains(SECRET_SUBJECT));
+        assert!(!content.contains(SECRET_SENDER));
+    }
+
+    #[test]
+    fn test_digest_depends_on_salt() {
+        let preview = mail("", SECRET_SENDER, SECRET_SUBJECT);
+        let digest = preview_digest("salt-a", &preview);
+        assert_eq!(digest.len(), 64);
+        assert!(!digest.contains(SECRET_SUBJECT));
+        assert_eq!(digest, preview_digest("salt-a", &preview));
+        assert_ne!(digest, preview_digest("salt-b", &preview));
+        // 发件人与主题之间有分隔，拼接相同的不同组合摘要不同
+        assert_ne!(
+            preview_digest("s", &mail("", "ab", "c")),
+            preview_digest("s", &mail("", "a", "bc"))
+        );
+    }
+
+    #[test]
+    fn test_corrupt_file_is_rebuilt() {
+        let path = temp_path("corrupt");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let state = RuntimeState::open(path.clone());
+        assert_eq!(state.account("me@example.com"), AccountState::default());
+
+        state
+            .update_account("me@example.com", |s| s.notification = baseline(2))
+            .unwrap();
+        let reopened = RuntimeState::open(path);
+        assert_eq!(reopened.account("me@example.com").notification, baseline(2));
+    }
+}
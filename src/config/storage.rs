@@ -1,35 +1,395 @@
 /// 账户文件存储模块
 ///
-/// 负责将 Gmail 账户信息持久化到 TOML 文件
+/// 负责将各邮件服务的账户信息持久化到 TOML 文件
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
 
+use crate::mail::gmail::labels::{self, LabelPlan, LabelRole};
 use crate::mail::gmail::types::GmailAccount;
+use crate::mail::imap::ImapAccount;
+use crate::mail::oauth::{CountMode, OAuthAccount};
+use crate::utils::redact::redact_email;
 
 /// 账户存储文件版本号
 const STORAGE_VERSION: &str = "1.0";
 
+/// 合并写入的等待时间（期间的多次保存只写一次文件）
+const FLUSH_DELAY: Duration = Duration::from_secs(2);
+
+/// 应用自身维护的字段：与外部编辑冲突时以内存中的值为准（如刚刷新的 Token），其余字段以文件为准
+const APP_OWNED_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "expires_at",
+    "refreshed_at",
+    "refresh_token_issued_at",
+    "testing_client",
+    "scopes",
+    "password",
+    "counted_since",
+    "pending_delete_at",
+];
+
+/// 外部编辑后仍按编辑前的内容合并保存的时长
+///
+/// 同步线程每轮重新读取账户，编辑前读取的副本最多再被保存一轮
+const STALE_COPY_WINDOW: Duration = Duration::from_secs(5 * 60);
+
 /// 账户存储容器
 #[derive(Debug, Serialize, Deserialize)]
 struct AccountsStorage {
     /// 文件格式版本
     version: String,
 
-    /// Gmail 账户列表
-    accounts: Vec<AccountEntry>,
+    /// 账户列表（逐条解析，见 `load_accounts`）
+    accounts: Vec<toml::Value>,
 }
 
-/// 账户条目（包含类型标识）
-#[derive(Debug, Serialize, Deserialize)]
-struct AccountEntry {
-    /// 账户类型（gmail, netease, 等）
-    #[serde(rename = "type")]
-    account_type: String,
+/// 已保存的账户（以 `type` 字段区分邮件服务）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StoredAccount {
+    /// Gmail 账户
+    Gmail(GmailAccount),
+
+    /// Outlook（Microsoft 365 / Outlook.com）账户
+    Outlook(OAuthAccount),
+
+    /// 通用 IMAP 账户（密码登录）
+    Imap(ImapAccount),
+}
+
+impl StoredAccount {
+    /// 账户类型标识（与 `MailProvider::kind` 一致）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Gmail(_) => "gmail",
+            Self::Outlook(_) => "outlook",
+            Self::Imap(_) => "imap",
+        }
+    }
+
+    /// 邮箱地址
+    pub fn email(&self) -> &str {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => &account.email,
+            Self::Imap(account) => &account.email,
+        }
+    }
+
+    /// 显示名称
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => &account.display_name,
+            Self::Imap(account) => &account.display_name,
+        }
+    }
+
+    /// 修改显示名称
+    pub fn set_display_name(&mut self, name: String) {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.display_name = name,
+            Self::Imap(account) => account.display_name = name,
+        }
+    }
+
+    /// OAuth2 账户数据（密码登录的账户为 `None`）
+    pub fn oauth(&self) -> Option<&OAuthAccount> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => Some(account),
+            Self::Imap(_) => None,
+        }
+    }
+
+    /// OAuth2 账户数据（可修改）
+    pub fn oauth_mut(&mut self) -> Option<&mut OAuthAccount> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => Some(account),
+            Self::Imap(_) => None,
+        }
+    }
+
+    /// 未读计数方式（不支持切换计数方式的账户为 `None`）
+    pub fn count_mode(&self) -> Option<CountMode> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => Some(account.count_mode),
+            Self::Imap(_) => None,
+        }
+    }
+
+    /// 是否监视垃圾邮件（只有 Gmail 账户支持，其他账户为 `None`）
+    pub fn watch_spam(&self) -> Option<bool> {
+        match self {
+            Self::Gmail(account) => Some(account.watch_spam),
+            Self::Outlook(_) | Self::Imap(_) => None,
+        }
+    }
+
+    /// 开启或关闭垃圾邮件监视（账户不支持时返回 false）
+    pub fn set_watch_spam(&mut self, watch: bool) -> bool {
+        match self {
+            Self::Gmail(account) => {
+                account.watch_spam = watch;
+                true
+            }
+            Self::Outlook(_) | Self::Imap(_) => false,
+        }
+    }
+
+    /// 是否只统计真人来信（只有 Gmail 账户支持，其他账户为 `None`）
+    pub fn people_only(&self) -> Option<bool> {
+        match self {
+            Self::Gmail(account) => Some(account.people_only),
+            Self::Outlook(_) | Self::Imap(_) => None,
+        }
+    }
+
+    /// 开启或关闭"只统计真人来信"（账户不支持时返回 false）
+    pub fn set_people_only(&mut self, people_only: bool) -> bool {
+        match self {
+            Self::Gmail(account) => {
+                account.people_only = people_only;
+                true
+            }
+            Self::Outlook(_) | Self::Imap(_) => false,
+        }
+    }
+
+    /// 是否提示自动回复（只有 Gmail 账户支持，其他账户为 `None`）
+    pub fn watch_vacation(&self) -> Option<bool> {
+        match self {
+            Self::Gmail(account) => Some(account.watch_vacation),
+            Self::Outlook(_) | Self::Imap(_) => None,
+        }
+    }
+
+    /// 能否读取自动回复设置（Gmail 账户需要读取邮件或设置的权限）
+    pub fn can_watch_vacation(&self) -> bool {
+        match self {
+            Self::Gmail(account) => crate::mail::gmail::oauth::can_read_settings(account),
+            Self::Outlook(_) | Self::Imap(_) => false,
+        }
+    }
+
+    /// 开启或关闭自动回复提示（账户不支持时返回 false）
+    pub fn set_watch_vacation(&mut self, watch: bool) -> bool {
+        match self {
+            Self::Gmail(account) => {
+                account.watch_vacation = watch;
+                true
+            }
+            Self::Outlook(_) | Self::Imap(_) => false,
+        }
+    }
+
+    /// 实时推送设置（菜单中只有 Gmail 账户可以切换，其他账户为 `None`）
+    pub fn push(&self) -> Option<bool> {
+        match self {
+            Self::Gmail(account) => Some(account.push),
+            Self::Outlook(_) | Self::Imap(_) => None,
+        }
+    }
+
+    /// 能否通过 IMAP 推送（Gmail 账户需要完整邮箱权限）
+    pub fn can_push(&self) -> bool {
+        match self {
+            Self::Gmail(account) => crate::mail::gmail::oauth::can_use_imap(account),
+            Self::Outlook(_) | Self::Imap(_) => false,
+        }
+    }
+
+    /// 开启或关闭实时推送（账户不支持时返回 false）
+    pub fn set_push(&mut self, push: bool) -> bool {
+        match self {
+            Self::Gmail(account) => {
+                account.push = push;
+                true
+            }
+            Self::Outlook(_) | Self::Imap(_) => false,
+        }
+    }
+
+    /// 是否开启了实时推送（Gmail 的 `push`、IMAP 的 `idle`）
+    ///
+    /// Gmail 账户未授予完整邮箱权限时不启动推送，重新授权补齐权限后按新设置启动
+    pub fn push_enabled(&self) -> bool {
+        match self {
+            Self::Gmail(account) => account.push && self.can_push(),
+            Self::Outlook(_) => false,
+            Self::Imap(account) => account.idle,
+        }
+    }
+
+    /// 徽章与通知使用的标签 ID（只有 Gmail 账户支持，其他账户为 `None`）
+    pub fn gmail_labels(&self) -> Option<LabelPlan> {
+        match self {
+            Self::Gmail(account) => {
+                Some(LabelPlan::new(&account.badge_label, &account.notify_label))
+            }
+            Self::Outlook(_) | Self::Imap(_) => None,
+        }
+    }
+
+    /// 修改徽章或通知使用的标签（账户不支持时返回 false）
+    pub fn set_gmail_label(&mut self, role: LabelRole, label: &str) -> bool {
+        match self {
+            Self::Gmail(account) => {
+                let label = labels::normalize(label);
+                match role {
+                    LabelRole::Badge => account.badge_label = label,
+                    LabelRole::Notify => account.notify_label = label,
+                }
+                true
+            }
+            Self::Outlook(_) | Self::Imap(_) => false,
+        }
+    }
+
+    /// 新邮件通知依据的标签（收件箱或不支持标签的账户为 `None`，与运行时状态中的记录对应）
+    pub fn notify_label(&self) -> Option<String> {
+        let plan = self.gmail_labels()?;
+        let label = plan.notify.unwrap_or(plan.badge);
+        (label != labels::INBOX).then_some(label)
+    }
+
+    /// 未读计数基准时间（统计全部未读或不支持计数方式时为 `None`）
+    pub fn unread_baseline(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.unread_baseline(),
+            Self::Imap(_) => None,
+        }
+    }
+
+    /// 免打扰截止时间
+    pub fn snoozed_until(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.snoozed_until,
+            Self::Imap(account) => account.snoozed_until,
+        }
+    }
+
+    /// 设置免打扰截止时间（`None` 表示取消免打扰）
+    pub fn set_snoozed_until(&mut self, until: Option<DateTime<Utc>>) {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.snoozed_until = until,
+            Self::Imap(account) => account.snoozed_until = until,
+        }
+    }
+
+    /// 当前是否处于免打扰期
+    pub fn is_snoozed(&self) -> bool {
+        self.snoozed_until().is_some_and(|until| until > Utc::now())
+    }
+
+    /// 标记删除的时间（`None` 表示正常账户）
+    pub fn pending_delete_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.pending_delete_at,
+            Self::Imap(account) => account.pending_delete_at,
+        }
+    }
+
+    /// 标记删除或撤销标记（`None`）
+    pub fn set_pending_delete_at(&mut self, at: Option<DateTime<Utc>>) {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.pending_delete_at = at,
+            Self::Imap(account) => account.pending_delete_at = at,
+        }
+    }
+
+    /// 账户是否激活
+    pub fn is_active(&self) -> bool {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.is_active,
+            Self::Imap(account) => account.is_active,
+        }
+    }
+
+    /// 强调色（`None` 表示使用默认颜色）
+    pub fn accent(&self) -> Option<&str> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.accent.as_deref(),
+            Self::Imap(account) => account.accent.as_deref(),
+        }
+    }
+
+    /// 设置强调色（须为已校验的 `#RRGGBB`，`None` 表示恢复默认颜色）
+    pub fn set_accent(&mut self, accent: Option<String>) {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.accent = accent,
+            Self::Imap(account) => account.accent = accent,
+        }
+    }
+
+    /// 未读提醒阈值（`None` 表示不提醒）
+    pub fn alert_threshold(&self) -> Option<u32> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.alert_threshold,
+            Self::Imap(account) => account.alert_threshold,
+        }
+    }
+
+    /// 设置未读提醒阈值（`None` 表示不提醒）
+    pub fn set_alert_threshold(&mut self, threshold: Option<u32>) {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.alert_threshold = threshold,
+            Self::Imap(account) => account.alert_threshold = threshold,
+        }
+    }
+
+    /// 账户分组（`None` 表示未分组）
+    pub fn group(&self) -> Option<&str> {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.group.as_deref(),
+            Self::Imap(account) => account.group.as_deref(),
+        }
+    }
+
+    /// 设置账户分组（`None` 表示取消分组）
+    pub fn set_group(&mut self, group: Option<String>) {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.group = group,
+            Self::Imap(account) => account.group = group,
+        }
+    }
 
-    /// Gmail 账户数据
-    #[serde(flatten)]
-    gmail: GmailAccount,
+    /// 按流量计费的网络下是否照常同步（见 `mail::metered`）
+    pub fn ignore_metered(&self) -> bool {
+        match self {
+            Self::Gmail(account) | Self::Outlook(account) => account.ignore_metered,
+            Self::Imap(account) => account.ignore_metered,
+        }
+    }
+}
+
+/// 转换为 Slint UI 的 Account 类型
+impl From<StoredAccount> for crate::Account {
+    fn from(account: StoredAccount) -> Self {
+        let watch_spam = crate::ui::toggle_setting(account.watch_spam());
+        let people_only = crate::ui::toggle_setting(account.people_only());
+        let watch_vacation = crate::ui::vacation_setting(&account);
+        let push = crate::ui::push_setting(&account);
+        let reauth_reminder = crate::ui::reauth_reminder(&account, Utc::now());
+        let row: Self = match account {
+            StoredAccount::Gmail(account) | StoredAccount::Outlook(account) => account.into(),
+            StoredAccount::Imap(account) => account.into(),
+        };
+        Self {
+            watch_spam: watch_spam.into(),
+            people_only: people_only.into(),
+            watch_vacation: watch_vacation.into(),
+            push: push.into(),
+            reauth_reminder: reauth_reminder.into(),
+            ..row
+        }
+    }
 }
 
 impl Default for AccountsStorage {
@@ -56,30 +416,93 @@ pub fn accounts_path() -> Result<PathBuf> {
     Ok(config_dir.join("accounts.toml"))
 }
 
+/// 账户文件（进程内唯一）
+fn store() -> Result<&'static AccountStore> {
+    static STORE: OnceLock<AccountStore> = OnceLock::new();
+
+    if let Some(store) = STORE.get() {
+        return Ok(store);
+    }
+
+    // 测试中刷新 Token 等操作会保存账户，写到临时文件，不影响本机的真实账户
+    #[cfg(test)]
+    let path = std::env::temp_dir().join(format!(
+        "nanomail-accounts-test-{}.toml",
+        std::process::id()
+    ));
+    #[cfg(not(test))]
+    let path = accounts_path()?;
+
+    Ok(STORE.get_or_init(|| AccountStore::new(path)))
+}
+
 /// 加载所有账户
 ///
 /// # Returns
-/// 返回所有已保存的 Gmail 账户列表，文件不存在时返回空列表
+/// 返回所有已保存的账户列表（包含尚未写入文件的修改），文件不存在时返回空列表；
+/// 无法识别的账户条目（如新版本才支持的类型）会被跳过并记录警告，但仍保留在文件中，写入时原样写回；
+/// 凭据不是加密格式的账户照常返回，使用凭据时才报告需要重新授权
+/// （见 [`crate::config::crypto::deserialize_encrypted`]）；
+/// 已标记删除的账户不包含在内（见 [`pending_deletions`]）
 ///
 /// # Errors
 /// - 文件格式错误
 /// - 反序列化失败
-pub fn load_accounts() -> Result<Vec<GmailAccount>> {
-    let path = accounts_path()?;
+pub fn load_accounts() -> Result<Vec<StoredAccount>> {
+    store()?.load().map(without_pending_deletes)
+}
 
-    // 文件不存在时返回空列表
-    if !path.exists() {
-        tracing::debug!("账户文件不存在，返回空列表");
-        return Ok(Vec::new());
-    }
+/// 已标记删除、等待真正删除的账户
+pub fn pending_deletions() -> Result<Vec<StoredAccount>> {
+    let mut accounts = store()?.load()?;
+    accounts.retain(|account| account.pending_delete_at().is_some());
+    Ok(accounts)
+}
+
+/// 标记删除账户并立即写入文件（之后 `load_accounts` 不再返回该账户），返回账户是否存在
+///
+/// 真正的删除（`remove_account`）在撤销期结束后进行；程序在此之前退出时，下次启动时完成
+pub fn mark_pending_delete(email: &str, at: DateTime<Utc>) -> Result<bool> {
+    store()?.set_pending_delete(email, Some(at))
+}
 
-    // 读取文件
-    let content = std::fs::read_to_string(&path)
-        .with_context(|| format!("读取账户文件失败: {}", path.display()))?;
+/// 撤销删除标记并立即写入文件，账户恢复原样；返回账户是否仍在等待删除
+pub fn undo_pending_delete(email: &str) -> Result<bool> {
+    store()?.set_pending_delete(email, None)
+}
+
+fn without_pending_deletes(mut accounts: Vec<StoredAccount>) -> Vec<StoredAccount> {
+    accounts.retain(|account| account.pending_delete_at().is_none());
+    accounts
+}
 
+/// 内容是否与本程序最近一次读取或写入的账户文件相同（文件监听据此跳过自身写入）
+pub fn is_own_write(content: &str) -> bool {
+    let hash = content_hash(content);
+    store().is_ok_and(|store| {
+        store
+            .lock()
+            .known
+            .as_ref()
+            .is_some_and(|known| known.hash == hash)
+    })
+}
+
+/// 账户文件中的条目
+#[derive(Default)]
+struct ParsedFile {
+    /// 解析成功的账户
+    accounts: Vec<StoredAccount>,
+
+    /// 无法解析的条目（新版本才支持的类型、手工编辑出错等），写回文件时原样保留
+    unparsed: Vec<toml::Value>,
+}
+
+/// 解析账户文件内容
+fn parse_file(content: &str) -> Result<ParsedFile> {
     // 解析 TOML
-    let storage: AccountsStorage = toml::from_str(&content)
-        .context("解析账户文件失败（文件可能损坏）")?;
+    let storage: AccountsStorage =
+        toml::from_str(content).context("解析账户文件失败（文件可能损坏）")?;
 
     // 验证版本
     if storage.version != STORAGE_VERSION {
@@ -90,97 +513,965 @@ pub fn load_accounts() -> Result<Vec<GmailAccount>> {
         );
     }
 
-    // 提取 Gmail 账户
-    let accounts: Vec<GmailAccount> = storage
-        .accounts
-        .into_iter()
-        .map(|entry| entry.gmail)
-        .collect();
-
-    tracing::debug!("成功加载 {} 个账户", accounts.len());
+    // 逐条解析，单个条目损坏或类型未知时不影响其他账户；该条目保留在文件中，不会被之后的写入删除
+    let mut parsed = ParsedFile::default();
+    for (index, entry) in storage.accounts.into_iter().enumerate() {
+        match entry.clone().try_into::<StoredAccount>() {
+            Ok(account) => parsed.accounts.push(account),
+            Err(e) => {
+                tracing::warn!(
+                    "跳过无法识别的账户条目 #{}（保留在文件中）: {}",
+                    index + 1,
+                    e
+                );
+                parsed.unparsed.push(entry);
+            }
+        }
+    }
 
-    Ok(accounts)
+    Ok(parsed)
 }
 
-/// 保存所有账户
+/// 保存单个账户（追加或更新）
 ///
-/// 覆盖式保存，替换整个账户列表
+/// 如果账户已存在（类型和邮箱都相同），则更新；否则追加。
+/// 内容与上次写入相同时不写文件；有变化时稍后合并写入（`FLUSH_DELAY` 内的多次保存只写一次），
+/// 在此之前 `load_accounts` 已能读到新内容
 ///
 /// # Arguments
-/// * `accounts` - 要保存的账户列表
+/// * `account` - 要保存的账户
 ///
 /// # Errors
 /// - 序列化失败
-/// - 文件写入失败
-pub fn save_accounts(accounts: &[GmailAccount]) -> Result<()> {
-    let path = accounts_path()?;
+pub fn save_account(account: &StoredAccount) -> Result<()> {
+    if store()?.save_account(account)? {
+        schedule_flush();
+    }
+    Ok(())
+}
 
-    // 转换为存储格式
-    let entries: Vec<AccountEntry> = accounts
-        .iter()
-        .map(|gmail| AccountEntry {
-            account_type: "gmail".to_string(),
-            gmail: gmail.clone(),
-        })
-        .collect();
-
-    let storage = AccountsStorage {
-        version: STORAGE_VERSION.to_string(),
-        accounts: entries,
-    };
+/// 在阻塞线程中保存账户（供异步任务使用）
+///
+/// 序列化、合并外部编辑与首次读取账户文件不占用异步运行时的工作线程
+pub async fn save_account_async(account: StoredAccount) -> Result<()> {
+    tokio::task::spawn_blocking(move || save_account(&account))
+        .await
+        .context("保存账户的任务失败")?
+}
 
-    // 序列化为 TOML
-    let content = toml::to_string_pretty(&storage)
-        .context("序列化账户数据失败")?;
+/// 删除账户（类型和邮箱都相同的条目）
+///
+/// 连同尚未写入的修改一起立即写入文件，返回账户是否存在
+pub fn remove_account(account: &StoredAccount) -> Result<bool> {
+    store()?.remove_account(account)
+}
 
-    // 写入文件
-    std::fs::write(&path, content)
-        .with_context(|| format!("写入账户文件失败: {}", path.display()))?;
+/// 立即写入尚未保存的账户修改（退出前调用）
+pub fn flush() -> Result<()> {
+    store()?.flush()
+}
 
-    tracing::debug!("成功保存 {} 个账户到: {}", accounts.len(), path.display());
+/// 延迟 `FLUSH_DELAY` 后在后台线程写入
+fn schedule_flush() {
+    let spawned = std::thread::Builder::new()
+        .name("accounts-flush".to_string())
+        .spawn(|| {
+            std::thread::sleep(FLUSH_DELAY);
+            if let Err(e) = flush() {
+                tracing::error!("写入账户文件失败: {:#}", e);
+            }
+        });
 
-    Ok(())
+    if let Err(e) = spawned {
+        tracing::warn!("启动账户写入线程失败，立即写入: {}", e);
+        if let Err(e) = flush() {
+            tracing::error!("写入账户文件失败: {:#}", e);
+        }
+    }
 }
 
-/// 保存单个账户（追加或更新）
-///
-/// 如果账户已存在（邮箱相同），则更新；否则追加
-///
-/// # Arguments
-/// * `account` - 要保存的账户
-///
-/// # Errors
-/// - 加载或保存失败
-pub fn save_account(account: &GmailAccount) -> Result<()> {
-    let mut accounts = load_accounts()?;
+/// 账户条目摘要（序列化内容的哈希）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EntryDigest(u64);
 
-    // 查找是否已存在
-    if let Some(existing) = accounts.iter_mut().find(|a| a.email == account.email) {
-        tracing::debug!("更新已存在的账户: {}", account.email);
-        *existing = account.clone();
-    } else {
-        tracing::debug!("添加新账户: {}", account.email);
-        accounts.push(account.clone());
+impl EntryDigest {
+    fn of(account: &StoredAccount) -> Result<Self> {
+        let value = toml::Value::try_from(account).context("序列化账户数据失败")?;
+        Ok(Self::of_value(&value))
     }
 
-    save_accounts(&accounts)?;
+    fn of_value(value: &toml::Value) -> Self {
+        Self(content_hash(&value.to_string()))
+    }
+}
 
-    Ok(())
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 账户的唯一键（类型 + 邮箱）
+type AccountKey = (&'static str, String);
+
+fn account_key(account: &StoredAccount) -> AccountKey {
+    (account.kind(), account.email().to_string())
+}
+
+/// 本程序最近一次读取或写入的账户文件
+struct KnownFile {
+    /// 文件内容哈希
+    hash: u64,
+
+    /// 各账户条目
+    entries: HashMap<AccountKey, toml::Value>,
+}
+
+impl KnownFile {
+    fn of(hash: u64, accounts: &[StoredAccount]) -> Result<Self> {
+        let entries = accounts
+            .iter()
+            .map(|account| {
+                let value = toml::Value::try_from(account).context("序列化账户数据失败")?;
+                Ok((account_key(account), value))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { hash, entries })
+    }
+}
+
+/// 外部编辑前的账户条目（合并编辑前读取的副本时作为共同祖先）
+struct ExternalEdit {
+    base: toml::Value,
+    at: Instant,
+}
+
+/// 账户文件写入状态
+#[derive(Default)]
+struct WriteState {
+    /// 各账户在文件中（或等待写入）的内容摘要
+    digests: HashMap<AccountKey, EntryDigest>,
+
+    /// 等待写入的账户
+    pending: Vec<StoredAccount>,
+
+    /// 是否已安排写入
+    flush_scheduled: bool,
+
+    /// 已标记删除的账户（同步线程拿着标记前读取的账户保存时，不能清除删除标记）
+    deleting: HashSet<AccountKey>,
+
+    /// 最近一次读取或写入的文件内容（识别外部编辑）
+    known: Option<KnownFile>,
+
+    /// 在外部编辑过（或删除）的账户
+    external: HashMap<AccountKey, ExternalEdit>,
+}
+
+/// 账户文件：负责变更检测与合并写入
+struct AccountStore {
+    path: PathBuf,
+    state: Mutex<WriteState>,
+
+    /// 实际写入文件的次数
+    writes: AtomicUsize,
+}
+
+impl AccountStore {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            state: Mutex::new(WriteState::default()),
+            writes: AtomicUsize::new(0),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, WriteState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 读取文件中的账户，并叠加尚未写入的修改
+    fn load(&self) -> Result<Vec<StoredAccount>> {
+        let (file, hash) = self.read_file()?;
+        let mut accounts = file.accounts;
+        let mut state = self.lock();
+        Self::rebase(&mut state, &accounts, hash)?;
+
+        // 记录文件中的内容摘要（等待写入的账户以待写入内容为准）
+        for account in &accounts {
+            let key = account_key(account);
+            if !state.pending.iter().any(|p| account_key(p) == key) {
+                state.digests.insert(key, EntryDigest::of(account)?);
+            }
+        }
+
+        merge_accounts(&mut accounts, &state.pending);
+        for account in &accounts {
+            if account.pending_delete_at().is_some() {
+                state.deleting.insert(account_key(account));
+            }
+        }
+        tracing::debug!("成功加载 {} 个账户", accounts.len());
+
+        Ok(accounts)
+    }
+
+    /// 暂存单个账户的修改，返回是否需要安排写入
+    fn save_account(&self, account: &StoredAccount) -> Result<bool> {
+        let key = account_key(account);
+        let mut state = self.lock();
+
+        if account.pending_delete_at().is_none() && state.deleting.contains(&key) {
+            tracing::debug!("账户等待删除，跳过保存: {}", account.email());
+            return Ok(false);
+        }
+
+        // 外部编辑前读取的副本：保留外部编辑的字段
+        let WriteState {
+            known, external, ..
+        } = &mut *state;
+        let account = match known {
+            Some(file) => match resolve(external, file, account.clone())? {
+                Some(account) => account,
+                None => return Ok(false),
+            },
+            None => account.clone(),
+        };
+        let account = &account;
+        let digest = EntryDigest::of(account)?;
+
+        if state.digests.get(&key) == Some(&digest) {
+            tracing::trace!("账户内容未变化，跳过写入: {}", account.email());
+            return Ok(false);
+        }
+
+        state.digests.insert(key, digest);
+        merge_accounts(&mut state.pending, std::slice::from_ref(account));
+
+        if state.flush_scheduled {
+            return Ok(false);
+        }
+
+        state.flush_scheduled = true;
+        Ok(true)
+    }
+
+    /// 设置或清除账户的删除标记并立即写入文件，返回账户是否存在
+    fn set_pending_delete(&self, email: &str, at: Option<DateTime<Utc>>) -> Result<bool> {
+        let Some(mut account) = self.load()?.into_iter().find(|a| a.email() == email) else {
+            return Ok(false);
+        };
+        if account.pending_delete_at().is_none() && at.is_none() {
+            return Ok(false);
+        }
+
+        let key = account_key(&account);
+        match at {
+            Some(_) => self.lock().deleting.insert(key),
+            None => self.lock().deleting.remove(&key),
+        };
+
+        account.set_pending_delete_at(at);
+        self.save_account(&account)?;
+        self.flush()?;
+        Ok(true)
+    }
+
+    /// 删除账户并立即写入文件，返回账户是否存在
+    fn remove_account(&self, account: &StoredAccount) -> Result<bool> {
+        let key = account_key(account);
+        let mut state = self.lock();
+        state.deleting.remove(&key);
+
+        let pending_len = state.pending.len();
+        state.pending.retain(|p| account_key(p) != key);
+        let mut found = state.pending.len() != pending_len;
+
+        let (file, hash) = self.read_file()?;
+        let mut accounts = file.accounts;
+        Self::rebase(&mut state, &accounts, hash)?;
+        let file_len = accounts.len();
+        accounts.retain(|a| account_key(a) != key);
+        found |= accounts.len() != file_len;
+
+        if !found {
+            return Ok(false);
+        }
+
+        merge_accounts(&mut accounts, &state.pending);
+        let hash = self.write_file(&accounts, &file.unparsed)?;
+        Self::written(&mut state, &accounts, hash)?;
+        Ok(true)
+    }
+
+    /// 写入等待中的修改（没有修改时不写文件）
+    fn flush(&self) -> Result<()> {
+        let mut state = self.lock();
+        state.flush_scheduled = false;
+        if state.pending.is_empty() {
+            return Ok(());
+        }
+
+        let (file, hash) = self.read_file()?;
+        let mut accounts = file.accounts;
+        Self::rebase(&mut state, &accounts, hash)?;
+        merge_accounts(&mut accounts, &state.pending);
+        let hash = self.write_file(&accounts, &file.unparsed)?;
+        Self::written(&mut state, &accounts, hash)
+    }
+
+    /// 与上次读写的文件内容比较，识别外部编辑，并把等待写入的修改与外部编辑逐字段合并
+    fn rebase(state: &mut WriteState, accounts: &[StoredAccount], hash: u64) -> Result<()> {
+        let file = KnownFile::of(hash, accounts)?;
+        let old = match state.known.take() {
+            Some(old) if old.hash != hash => old,
+            _ => {
+                state.known = Some(file);
+                return Ok(());
+            }
+        };
+
+        let now = Instant::now();
+        for (key, base) in old.entries {
+            let entry = file.entries.get(&key);
+            if entry == Some(&base) {
+                continue;
+            }
+            match entry {
+                Some(value) => state
+                    .digests
+                    .insert(key.clone(), EntryDigest::of_value(value)),
+                None => state.digests.remove(&key),
+            };
+            state.external.insert(key, ExternalEdit { base, at: now });
+        }
+        state
+            .external
+            .retain(|_, edit| edit.at.elapsed() < STALE_COPY_WINDOW);
+
+        let pending = std::mem::take(&mut state.pending);
+        for account in pending {
+            let key = account_key(&account);
+            match resolve(&mut state.external, &file, account)? {
+                Some(account) => {
+                    state.digests.insert(key, EntryDigest::of(&account)?);
+                    state.pending.push(account);
+                }
+                None => {
+                    state.digests.remove(&key);
+                }
+            }
+        }
+
+        tracing::info!("检测到账户文件被外部修改，已重新读取");
+        state.known = Some(file);
+        Ok(())
+    }
+
+    /// 文件已写入：清空等待中的修改，摘要以写入的内容为准
+    fn written(state: &mut WriteState, accounts: &[StoredAccount], hash: u64) -> Result<()> {
+        state.pending.clear();
+        state.digests.clear();
+        for account in accounts {
+            state
+                .digests
+                .insert(account_key(account), EntryDigest::of(account)?);
+        }
+        state.known = Some(KnownFile::of(hash, accounts)?);
+        Ok(())
+    }
+
+    /// 读取文件中的账户（不含等待写入的修改）及文件内容哈希
+    fn read_file(&self) -> Result<(ParsedFile, u64)> {
+        // 文件不存在时返回空列表
+        if !self.path.exists() {
+            tracing::debug!("账户文件不存在，返回空列表");
+            return Ok((ParsedFile::default(), content_hash("")));
+        }
+
+        // 读取文件
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("读取账户文件失败: {}", self.path.display()))?;
+
+        Ok((parse_file(&content)?, content_hash(&content)))
+    }
+
+    /// 写入文件，返回写入内容的哈希；`unparsed` 为读取时无法解析的条目，原样写回
+    fn write_file(&self, accounts: &[StoredAccount], unparsed: &[toml::Value]) -> Result<u64> {
+        // 转换为存储格式
+        let mut entries = accounts
+            .iter()
+            .map(toml::Value::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .context("序列化账户数据失败")?;
+        entries.extend_from_slice(unparsed);
+
+        let storage = AccountsStorage {
+            version: STORAGE_VERSION.to_string(),
+            accounts: entries,
+        };
+
+        // 序列化为 TOML
+        let content = toml::to_string_pretty(&storage).context("序列化账户数据失败")?;
+
+        // 写入文件
+        std::fs::write(&self.path, &content)
+            .with_context(|| format!("写入账户文件失败: {}", self.path.display()))?;
+
+        let writes = self.writes.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::debug!(
+            "成功保存 {} 个账户到: {}（本次运行第 {} 次写入）",
+            accounts.len(),
+            self.path.display(),
+            writes
+        );
+
+        Ok(content_hash(&content))
+    }
+}
+
+/// 按外部编辑调整要保存的账户：外部编辑前读取的副本与文件内容逐字段合并；
+/// 账户已在外部删除时返回 `None`
+fn resolve(
+    external: &mut HashMap<AccountKey, ExternalEdit>,
+    file: &KnownFile,
+    account: StoredAccount,
+) -> Result<Option<StoredAccount>> {
+    let key = account_key(&account);
+    let Some(edit) = external.get(&key) else {
+        return Ok(Some(account));
+    };
+    if edit.at.elapsed() >= STALE_COPY_WINDOW {
+        external.remove(&key);
+        return Ok(Some(account));
+    }
+
+    let ours = toml::Value::try_from(&account).context("序列化账户数据失败")?;
+    let Some(theirs) = file.entries.get(&key) else {
+        // 凭据不同说明是删除后重新添加的账户，照常保存
+        if !same_credentials(&edit.base, &ours) {
+            external.remove(&key);
+            return Ok(Some(account));
+        }
+        tracing::info!(
+            "账户已在外部删除，丢弃未写入的修改: {}",
+            redact_email(account.email())
+        );
+        return Ok(None);
+    };
+
+    let merged = merge_entry(&edit.base, &ours, theirs);
+    if merged == ours {
+        return Ok(Some(account));
+    }
+
+    match merged.try_into::<StoredAccount>() {
+        Ok(merged) => {
+            tracing::debug!(
+                "账户在外部修改过，保留外部修改的字段: {}",
+                redact_email(account.email())
+            );
+            Ok(Some(merged))
+        }
+        Err(e) => {
+            tracing::warn!("合并外部修改失败，以内存中的账户为准: {}", e);
+            Ok(Some(account))
+        }
+    }
+}
+
+/// 两个账户条目是否使用同一份凭据（重新授权或重新输入密码后凭据不同）
+fn same_credentials(a: &toml::Value, b: &toml::Value) -> bool {
+    ["refresh_token", "password"]
+        .iter()
+        .any(|field| a.get(field).is_some() && a.get(field) == b.get(field))
+}
+
+/// 逐字段三方合并：只有一方修改的字段取修改后的值；
+/// 双方都修改时，`APP_OWNED_FIELDS` 以内存为准，其余以文件为准
+fn merge_entry(base: &toml::Value, ours: &toml::Value, theirs: &toml::Value) -> toml::Value {
+    let (Some(base), Some(ours), Some(theirs)) =
+        (base.as_table(), ours.as_table(), theirs.as_table())
+    else {
+        return theirs.clone();
+    };
+
+    let mut merged = theirs.clone();
+    let fields: HashSet<&String> = ours.keys().chain(theirs.keys()).collect();
+    for field in fields {
+        let (old, mine) = (base.get(field), ours.get(field));
+        if mine == old {
+            continue;
+        }
+        if theirs.get(field) == old || APP_OWNED_FIELDS.contains(&field.as_str()) {
+            match mine {
+                Some(value) => merged.insert(field.clone(), value.clone()),
+                None => merged.remove(field),
+            };
+        }
+    }
+    toml::Value::Table(merged)
+}
+
+/// 用 `updates` 中的账户替换同类型同邮箱的账户，其余追加到末尾
+fn merge_accounts(accounts: &mut Vec<StoredAccount>, updates: &[StoredAccount]) {
+    for update in updates {
+        match accounts
+            .iter_mut()
+            .find(|a| a.kind() == update.kind() && a.email() == update.email())
+        {
+            Some(existing) => *existing = update.clone(),
+            None => accounts.push(update.clone()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
 
-    fn create_test_account(email: &str) -> GmailAccount {
-        GmailAccount::new(
-            email.to_string(),
-            format!("{} User", email),
-            "test_access_token".to_string(),
-            "test_refresh_token".to_string(),
-            3600,
-        ).expect("创建测试账户失败")
+    fn create_test_account(email: &str) -> StoredAccount {
+        StoredAccount::Gmail(
+            GmailAccount::new(
+                email.to_string(),
+                format!("{} User", email),
+                "test_access_token".to_string(),
+                "test_refresh_token".to_string(),
+                3600,
+            )
+            .expect("创建测试账户失败"),
+        )
+    }
+
+    #[test]
+    fn test_parse_skips_unknown_account_type() {
+        let content = r#"
+version = "1.0"
+
+[[accounts]]
+type = "gmail"
+email = "user@gmail.com"
+display_name = "User"
+access_token = "encrypted:AAAA"
+refresh_token = "encrypted:BBBB"
+expires_at = "2030-01-01T00:00:00Z"
+
+[[accounts]]
+type = "netease"
+email = "user@163.com"
+"#;
+
+        let parsed = parse_file(content).unwrap();
+        assert_eq!(parsed.unparsed.len(), 1);
+        let accounts = parsed.accounts;
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].kind(), "gmail");
+        assert_eq!(accounts[0].email(), "user@gmail.com");
+
+        // 重新序列化后仍带有 type 字段
+        let value = toml::Value::try_from(&accounts[0]).unwrap();
+        assert_eq!(value.get("type").and_then(|v| v.as_str()), Some("gmail"));
+    }
+
+    #[test]
+    fn test_parse_keeps_account_with_unreadable_token() {
+        let content = r#"
+version = "1.0"
+
+[[accounts]]
+type = "gmail"
+email = "user@gmail.com"
+display_name = "User"
+access_token = "encrypted:AAAA"
+refresh_token = "encrypted:BBBB"
+expires_at = "2030-01-01T00:00:00Z"
+
+[[accounts]]
+type = "gmail"
+email = "other@gmail.com"
+display_name = "Other"
+access_token = "encrypted:not base64!"
+refresh_token = "encrypted:CCCC"
+expires_at = "2030-01-01T00:00:00Z"
+"#;
+
+        // 凭据损坏的账户照常加载，只有使用凭据时才报告需要重新授权
+        let accounts = parse_file(content).unwrap().accounts;
+        assert_eq!(accounts.len(), 2);
+        let other = accounts[1].oauth().unwrap();
+        assert_eq!(other.refresh_token, "encrypted:CCCC");
+        let error = other.decrypt_access_token().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<crate::config::crypto::CredentialError>(),
+            Some(&crate::config::crypto::CredentialError::Unreadable)
+        );
+    }
+
+    #[test]
+    fn test_parse_outlook_account() {
+        let content = r#"
+version = "1.0"
+
+[[accounts]]
+type = "outlook"
+email = "user@outlook.com"
+display_name = "User"
+access_token = "encrypted:AAAA"
+refresh_token = "encrypted:BBBB"
+expires_at = "2030-01-01T00:00:00Z"
+"#;
+
+        let accounts = parse_file(content).unwrap().accounts;
+        assert_eq!(accounts.len(), 1);
+        assert!(matches!(accounts[0], StoredAccount::Outlook(_)));
+        assert_eq!(accounts[0].kind(), "outlook");
+        assert_eq!(accounts[0].email(), "user@outlook.com");
+    }
+
+    #[test]
+    fn test_snoozed_until_round_trip() {
+        let content = r#"
+version = "1.0"
+
+[[accounts]]
+type = "imap"
+email = "me@example.org"
+display_name = "me"
+host = "imap.example.org"
+port = 993
+username = "me@example.org"
+password = "encrypted:AAAA"
+"#;
+
+        let mut account = parse_file(content).unwrap().accounts.remove(0);
+        assert_eq!(account.snoozed_until(), None);
+        assert!(!account.is_snoozed());
+
+        let until = Utc::now() + chrono::Duration::hours(1);
+        account.set_snoozed_until(Some(until));
+        assert!(account.is_snoozed());
+
+        // 免打扰截止时间随账户保存，重启后仍然有效
+        let value = toml::Value::try_from(&account).unwrap();
+        let loaded: StoredAccount = value.try_into().unwrap();
+        assert_eq!(loaded.snoozed_until(), Some(until));
+
+        // 截止时间已过则不再处于免打扰期
+        account.set_snoozed_until(Some(Utc::now() - chrono::Duration::minutes(1)));
+        assert!(!account.is_snoozed());
+    }
+
+    /// 不需要加密的测试账户（IMAP 密码以密文形式直接写入）
+    fn imap_account(email: &str) -> StoredAccount {
+        let content = format!(
+            r#"
+version = "1.0"
+
+[[accounts]]
+type = "imap"
+email = "{email}"
+display_name = "me"
+host = "imap.example.org"
+port = 993
+username = "{email}"
+password = "encrypted:AAAA"
+"#
+        );
+        parse_file(&content).unwrap().accounts.remove(0)
+    }
+
+    /// 使用临时目录中的账户文件
+    fn temp_store(name: &str) -> AccountStore {
+        let dir =
+            std::env::temp_dir().join(format!("nanomail-storage-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("accounts.toml");
+        std::fs::remove_file(&path).ok();
+        AccountStore::new(path)
+    }
+
+    /// 模拟后台写入线程：需要安排写入时立即写入
+    fn save(store: &AccountStore, account: &StoredAccount) {
+        if store.save_account(account).unwrap() {
+            store.flush().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_identical_saves_write_once() {
+        let store = temp_store("identical");
+        let account = imap_account("me@example.org");
+
+        for _ in 0..50 {
+            save(&store, &account);
+        }
+        assert_eq!(store.writes.load(Ordering::Relaxed), 1);
+
+        // 重新加载后内容相同，仍然不写文件
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        save(&store, &loaded[0]);
+        assert_eq!(store.writes.load(Ordering::Relaxed), 1);
+
+        // 内容变化时才再次写入
+        let mut changed = account.clone();
+        changed.set_accent(Some("#33B679".to_string()));
+        save(&store, &changed);
+        save(&store, &changed);
+        assert_eq!(store.writes.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_remove_account() {
+        let store = temp_store("remove");
+        let kept = imap_account("a@example.org");
+        let removed = imap_account("b@example.org");
+        save(&store, &kept);
+        save(&store, &removed);
+
+        // 尚未写入的修改也一并删除
+        let mut changed = removed.clone();
+        changed.set_accent(Some("#33B679".to_string()));
+        store.save_account(&changed).unwrap();
+
+        assert!(store.remove_account(&removed).unwrap());
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].email(), "a@example.org");
+
+        // 已删除的账户再次删除时不写文件
+        let writes = store.writes.load(Ordering::Relaxed);
+        assert!(!store.remove_account(&removed).unwrap());
+        assert_eq!(store.writes.load(Ordering::Relaxed), writes);
+    }
+
+    #[test]
+    fn test_unparsed_entries_survive_writes() {
+        let store = temp_store("unparsed");
+        std::fs::write(
+            &store.path,
+            r#"
+version = "1.0"
+
+[[accounts]]
+type = "netease"
+email = "user@163.com"
+refresh_token = "encrypted:CCCC"
+"#,
+        )
+        .unwrap();
+        assert!(store.load().unwrap().is_empty());
+
+        // 保存、删除其他账户后，无法识别的条目仍原样保留在文件中
+        let account = imap_account("me@example.org");
+        save(&store, &account);
+        let other = imap_account("other@example.org");
+        save(&store, &other);
+        assert!(store.remove_account(&other).unwrap());
+
+        let saved = std::fs::read_to_string(&store.path).unwrap();
+        let parsed = parse_file(&saved).unwrap();
+        assert_eq!(parsed.accounts.len(), 1);
+        assert_eq!(parsed.accounts[0].email(), "me@example.org");
+        assert_eq!(parsed.unparsed.len(), 1);
+        let entry = &parsed.unparsed[0];
+        assert_eq!(entry.get("type").and_then(|v| v.as_str()), Some("netease"));
+        assert_eq!(
+            entry.get("refresh_token").and_then(|v| v.as_str()),
+            Some("encrypted:CCCC")
+        );
+    }
+
+    #[test]
+    fn test_pending_delete_undo_restores_entry() {
+        let store = temp_store("undo");
+        let account = imap_account("me@example.org");
+        save(&store, &account);
+        let original = std::fs::read_to_string(&store.path).unwrap();
+
+        assert!(
+            store
+                .set_pending_delete("me@example.org", Some(Utc::now()))
+                .unwrap()
+        );
+        assert!(without_pending_deletes(store.load().unwrap()).is_empty());
+
+        assert!(store.set_pending_delete("me@example.org", None).unwrap());
+        assert_eq!(without_pending_deletes(store.load().unwrap()).len(), 1);
+        assert_eq!(std::fs::read_to_string(&store.path).unwrap(), original);
+
+        // 没有删除标记时撤销不做任何事
+        assert!(!store.set_pending_delete("me@example.org", None).unwrap());
+    }
+
+    #[test]
+    fn test_pending_delete_timeout_removes_entry() {
+        let store = temp_store("timeout");
+        save(&store, &imap_account("a@example.org"));
+        save(&store, &imap_account("b@example.org"));
+        store
+            .set_pending_delete("b@example.org", Some(Utc::now()))
+            .unwrap();
+
+        // 撤销期结束：删除等待中的账户
+        let pending: Vec<StoredAccount> = store
+            .load()
+            .unwrap()
+            .into_iter()
+            .filter(|a| a.pending_delete_at().is_some())
+            .collect();
+        assert_eq!(pending.len(), 1);
+        assert!(store.remove_account(&pending[0]).unwrap());
+
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].email(), "a@example.org");
+    }
+
+    #[test]
+    fn test_pending_delete_survives_restart() {
+        let store = temp_store("restart");
+        let account = imap_account("me@example.org");
+        save(&store, &account);
+        let at = Utc::now();
+        store
+            .set_pending_delete("me@example.org", Some(at))
+            .unwrap();
+
+        // 同步线程拿着标记前读取的账户保存：不清除删除标记
+        let mut stale = account.clone();
+        stale.set_accent(Some("#33B679".to_string()));
+        save(&store, &stale);
+
+        // 撤销期结束前退出：下次启动时仍能找到等待删除的账户
+        let reopened = AccountStore::new(store.path.clone());
+        let loaded = reopened.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].pending_delete_at(), Some(at));
+        assert!(without_pending_deletes(loaded).is_empty());
+    }
+
+    #[test]
+    fn test_pending_saves_are_batched() {
+        let store = temp_store("batched");
+        let first = imap_account("a@example.org");
+        let second = imap_account("b@example.org");
+        let mut first_changed = first.clone();
+        first_changed.set_accent(Some("#039BE5".to_string()));
+
+        // 只有第一次保存需要安排写入，之后的修改合并到同一次写入中
+        assert!(store.save_account(&first).unwrap());
+        assert!(!store.save_account(&second).unwrap());
+        assert!(!store.save_account(&first_changed).unwrap());
+
+        // 写入前已能读到最新内容
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].accent(), Some("#039BE5"));
+        assert_eq!(store.writes.load(Ordering::Relaxed), 0);
+
+        store.flush().unwrap();
+        store.flush().unwrap();
+        assert_eq!(store.writes.load(Ordering::Relaxed), 1);
+        assert_eq!(store.read_file().unwrap().0.accounts.len(), 2);
+    }
+
+    /// 模拟在程序运行中手动编辑账户文件
+    fn write_externally(store: &AccountStore, accounts: &[StoredAccount]) {
+        let storage = AccountsStorage {
+            version: STORAGE_VERSION.to_string(),
+            accounts: accounts
+                .iter()
+                .map(|a| toml::Value::try_from(a).unwrap())
+                .collect(),
+        };
+        std::fs::write(&store.path, toml::to_string_pretty(&storage).unwrap()).unwrap();
+    }
+
+    fn with_password(account: &StoredAccount, password: &str) -> StoredAccount {
+        let mut account = account.clone();
+        if let StoredAccount::Imap(imap) = &mut account {
+            imap.password = password.to_string();
+        }
+        account
+    }
+
+    fn password(account: &StoredAccount) -> &str {
+        match account {
+            StoredAccount::Imap(imap) => &imap.password,
+            _ => "",
+        }
+    }
+
+    #[test]
+    fn test_stale_save_keeps_external_edit() {
+        let store = temp_store("external");
+        let account = imap_account("me@example.org");
+        save(&store, &account);
+
+        // 运行中在外部停用账户
+        let mut edited = account.clone();
+        if let StoredAccount::Imap(imap) = &mut edited {
+            imap.is_active = false;
+        }
+        write_externally(&store, &[edited]);
+        assert!(!store.load().unwrap()[0].is_active());
+
+        // 同步线程拿着编辑前读取的副本保存新凭据：凭据以内存为准，其余字段保留外部编辑
+        save(&store, &with_password(&account, "encrypted:BBBB"));
+        let loaded = store.read_file().unwrap().0.accounts;
+        assert!(!loaded[0].is_active());
+        assert_eq!(password(&loaded[0]), "encrypted:BBBB");
+
+        // 之后在界面中修改的字段照常保存
+        let mut renamed = loaded[0].clone();
+        renamed.set_display_name("Work".to_string());
+        save(&store, &renamed);
+        let loaded = store.read_file().unwrap().0.accounts;
+        assert_eq!(loaded[0].display_name(), "Work");
+        assert!(!loaded[0].is_active());
+    }
+
+    #[test]
+    fn test_pending_changes_merge_with_external_edit() {
+        let store = temp_store("rebase");
+        let account = imap_account("me@example.org");
+        save(&store, &account);
+
+        // 刷新的凭据尚未写入时在外部改名
+        assert!(
+            store
+                .save_account(&with_password(&account, "encrypted:BBBB"))
+                .unwrap()
+        );
+        let mut edited = account.clone();
+        edited.set_display_name("Renamed".to_string());
+        write_externally(&store, &[edited]);
+
+        store.flush().unwrap();
+        let loaded = store.read_file().unwrap().0.accounts;
+        assert_eq!(loaded[0].display_name(), "Renamed");
+        assert_eq!(password(&loaded[0]), "encrypted:BBBB");
+    }
+
+    #[test]
+    fn test_stale_save_does_not_restore_externally_deleted_account() {
+        let store = temp_store("external-delete");
+        let kept = imap_account("a@example.org");
+        let deleted = imap_account("b@example.org");
+        save(&store, &kept);
+        save(&store, &deleted);
+
+        write_externally(&store, std::slice::from_ref(&kept));
+        assert_eq!(store.load().unwrap().len(), 1);
+
+        // 删除前读取的副本不会让账户重新出现
+        save(&store, &with_password(&deleted, "encrypted:AAAA"));
+        assert_eq!(store.read_file().unwrap().0.accounts.len(), 1);
+
+        // 重新添加（凭据不同）的账户照常保存
+        save(&store, &with_password(&deleted, "encrypted:CCCC"));
+        assert_eq!(store.read_file().unwrap().0.accounts.len(), 2);
     }
 
     #[test]
@@ -204,17 +1495,18 @@ mod tests {
         let loaded = load_accounts().unwrap();
         assert!(!loaded.is_empty());
 
-        let found = loaded.iter().find(|a| a.email == "test1@gmail.com");
+        let found = loaded.iter().find(|a| a.email() == "test1@gmail.com");
         assert!(found.is_some());
 
         let found = found.unwrap();
-        assert_eq!(found.email, "test1@gmail.com");
-        assert_eq!(found.display_name, "test1@gmail.com User");
+        assert_eq!(found.email(), "test1@gmail.com");
+        assert_eq!(found.display_name(), "test1@gmail.com User");
     }
 
     #[test]
     #[ignore] // 需要 Windows 环境和文件系统权限
     fn test_save_multiple_accounts() {
+        std::fs::remove_file(accounts_path().unwrap()).ok();
         let accounts = vec![
             create_test_account("user1@gmail.com"),
             create_test_account("user2@gmail.com"),
@@ -222,7 +1514,10 @@ mod tests {
         ];
 
         // 保存多个
-        save_accounts(&accounts).unwrap();
+        for account in &accounts {
+            save_account(account).unwrap();
+        }
+        flush().unwrap();
 
         // 加载验证
         let loaded = load_accounts().unwrap();
@@ -238,16 +1533,25 @@ mod tests {
         save_account(&account).unwrap();
 
         // 修改并再次保存
-        account.display_name = "Updated Name".to_string();
+        let StoredAccount::Gmail(gmail) = &mut account else {
+            unreachable!();
+        };
+        gmail.display_name = "Updated Name".to_string();
         save_account(&account).unwrap();
 
         // 验证更新
         let loaded = load_accounts().unwrap();
-        let found = loaded.iter().find(|a| a.email == "update@gmail.com").unwrap();
-        assert_eq!(found.display_name, "Updated Name");
+        let found = loaded
+            .iter()
+            .find(|a| a.email() == "update@gmail.com")
+            .unwrap();
+        assert_eq!(found.display_name(), "Updated Name");
 
         // 验证没有重复
-        let count = loaded.iter().filter(|a| a.email == "update@gmail.com").count();
+        let count = loaded
+            .iter()
+            .filter(|a| a.email() == "update@gmail.com")
+            .count();
         assert_eq!(count, 1);
     }
 
@@ -264,4 +1568,162 @@ mod tests {
         let loaded = load_accounts().unwrap();
         assert!(loaded.is_empty());
     }
+
+    /// 历次版本写出的账户文件（及手动恢复的文件）都能加载，保存时不丢失字段
+    mod migration {
+        use super::*;
+        use crate::config::crypto::{self, CredentialError, UNREADABLE};
+        use crate::mail::gmail::labels::INBOX;
+
+        const V1_GMAIL_ONLY: &str = include_str!("fixtures/accounts/v1_gmail_only.toml");
+        const V2_ACCOUNT_SETTINGS: &str =
+            include_str!("fixtures/accounts/v2_account_settings.toml");
+        const V3_CURRENT: &str = include_str!("fixtures/accounts/v3_current.toml");
+        const UNREADABLE_MARKER: &str = include_str!("fixtures/accounts/unreadable_marker.toml");
+        const PLAINTEXT_RECOVERED: &str =
+            include_str!("fixtures/accounts/plaintext_recovered.toml");
+
+        /// 序列化后重新解析，内容不变
+        fn assert_round_trip(accounts: &[StoredAccount]) {
+            for account in accounts {
+                let value = toml::Value::try_from(account).unwrap();
+                let reparsed: StoredAccount = value.clone().try_into().unwrap();
+                assert_eq!(toml::Value::try_from(&reparsed).unwrap(), value);
+            }
+        }
+
+        fn gmail(account: &StoredAccount) -> &GmailAccount {
+            match account {
+                StoredAccount::Gmail(account) => account,
+                other => panic!("应为 Gmail 账户，实际为 {}", other.kind()),
+            }
+        }
+
+        #[test]
+        fn test_every_fixture_loads_all_entries() {
+            for (content, expected) in [
+                (V1_GMAIL_ONLY, 1),
+                (V2_ACCOUNT_SETTINGS, 3),
+                (V3_CURRENT, 1),
+                (UNREADABLE_MARKER, 1),
+                (PLAINTEXT_RECOVERED, 2),
+            ] {
+                let accounts = parse_file(content).unwrap().accounts;
+                assert_eq!(accounts.len(), expected);
+                assert_round_trip(&accounts);
+            }
+        }
+
+        #[test]
+        fn test_v1_fields_get_defaults() {
+            let accounts = parse_file(V1_GMAIL_ONLY).unwrap().accounts;
+            let account = gmail(&accounts[0]);
+            assert_eq!(account.display_name, "First");
+            assert_eq!(account.count_mode, CountMode::AllUnread);
+            assert_eq!(account.unread_baseline(), None);
+            assert!(account.is_active);
+            assert!(!account.watch_spam);
+            assert!(!account.people_only);
+            assert!(account.scopes.is_empty());
+            assert_eq!(account.refresh_token_issued_at, None);
+            assert_eq!(account.reauth_due(true), None);
+            assert_eq!(account.alert_threshold, None);
+            assert_eq!(
+                accounts[0].gmail_labels(),
+                Some(LabelPlan::new(INBOX, INBOX))
+            );
+            assert_eq!(accounts[0].notify_label(), None);
+
+            // 默认值不写回文件
+            let saved = toml::to_string(&accounts[0]).unwrap();
+            assert!(!saved.contains("badge_label"));
+            assert!(!saved.contains("testing_client"));
+        }
+
+        #[test]
+        fn test_v2_settings_and_account_types() {
+            let accounts = parse_file(V2_ACCOUNT_SETTINGS).unwrap().accounts;
+            let kinds: Vec<_> = accounts.iter().map(StoredAccount::kind).collect();
+            assert_eq!(kinds, ["gmail", "outlook", "imap"]);
+
+            assert_eq!(accounts[0].count_mode(), Some(CountMode::SinceAdded));
+            assert!(accounts[0].unread_baseline().is_some());
+            assert!(accounts[0].snoozed_until().is_some());
+            assert_eq!(accounts[0].accent(), Some("#1A73E8"));
+
+            // 标签与垃圾邮件监视只对 Gmail 生效
+            assert_eq!(accounts[1].gmail_labels(), None);
+            assert_eq!(accounts[1].watch_spam(), None);
+            assert_eq!(accounts[1].people_only(), None);
+            assert_eq!(accounts[2].count_mode(), None);
+        }
+
+        #[test]
+        fn test_v3_current_fields() {
+            let accounts = parse_file(V3_CURRENT).unwrap().accounts;
+            let account = gmail(&accounts[0]);
+            assert_eq!(accounts[0].watch_spam(), Some(true));
+            assert_eq!(accounts[0].people_only(), Some(true));
+            assert_eq!(account.badge_label, "STARRED");
+            assert_eq!(accounts[0].notify_label().as_deref(), Some("Label_7"));
+            assert!(account.testing_client);
+            assert!(account.refreshed_at.is_some());
+            assert!(account.reauth_due(false).is_some());
+            assert!(account.has_scope("https://www.googleapis.com/auth/gmail.readonly"));
+            assert_eq!(accounts[0].alert_threshold(), Some(20));
+        }
+
+        #[test]
+        fn test_unreadable_marker_is_kept() {
+            let accounts = parse_file(UNREADABLE_MARKER).unwrap().accounts;
+            let account = gmail(&accounts[0]);
+            assert_eq!(account.refresh_token, UNREADABLE);
+
+            let error = account.decrypt_refresh_token().unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<CredentialError>(),
+                Some(&CredentialError::Unreadable)
+            );
+            assert!(
+                toml::to_string(&accounts[0])
+                    .unwrap()
+                    .contains(r#"refresh_token = "unreadable""#)
+            );
+        }
+
+        #[test]
+        fn test_plaintext_credentials_are_encrypted() {
+            let accounts = parse_file(PLAINTEXT_RECOVERED).unwrap().accounts;
+
+            let account = gmail(&accounts[0]);
+            assert_eq!(account.display_name, "");
+            assert!(crypto::is_encrypted(&account.refresh_token));
+            assert_eq!(
+                account.decrypt_refresh_token().unwrap(),
+                "1//plain-refresh-token"
+            );
+            // 缺少 Access Token 与过期时间：视为已过期，首次使用前刷新
+            assert_eq!(account.expires_at, DateTime::<Utc>::default());
+            assert!(account.is_token_expiring(5));
+
+            let StoredAccount::Imap(imap) = &accounts[1] else {
+                panic!("应为 IMAP 账户");
+            };
+            assert_eq!(
+                crypto::decrypt_token(&imap.password).unwrap(),
+                "plain-password"
+            );
+
+            // 下次保存（任一账户）时整个文件以加密格式写回，文件中不再有明文
+            let store = temp_store("plaintext");
+            std::fs::write(&store.path, PLAINTEXT_RECOVERED).unwrap();
+            let mut account = store.load().unwrap().remove(0);
+            account.set_display_name("First".to_string());
+            save(&store, &account);
+            let saved = std::fs::read_to_string(&store.path).unwrap();
+            assert!(!saved.contains("plain-refresh-token"));
+            assert!(!saved.contains("plain-password"));
+            assert_eq!(parse_file(&saved).unwrap().accounts.len(), 2);
+        }
+    }
 }
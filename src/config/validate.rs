@@ -0,0 +1,426 @@
+/// 配置文件校验
+///
+/// 配置中无法识别的值大多会静默回退到默认行为（例如拼错的主题按亮色处理），用户很难发现设置没有生效。
+/// 启动时对配置文件做一次校验，找出三类问题：本版本不认识的字段、无效或超出范围的值、互相冲突
+/// （其中一项不生效）的设置组合。每个问题以 `段.字段` 标明位置，记录警告并在窗口标题栏下方列出。
+/// 校验只依赖文件内容与解析结果，不读取任何全局状态
+use std::fmt;
+
+use super::Config;
+use crate::tray::badge::{self, BadgeLevels};
+
+/// `[app] theme` 可用的值（区分大小写）
+const THEMES: &[&str] = &["light", "dark"];
+
+/// `[app] language` 可用的值（见 `i18n::Language::from_setting`，不区分大小写）
+const LANGUAGES: &[&str] = &["auto", "en", "en-us", "en-gb", "zh", "zh-cn", "zh-hans"];
+
+/// `[app] open_with` 可用的值（见 `callbacks::OpenWith::from_setting`，不区分大小写）
+const OPEN_WITH: &[&str] = &["browser", "mailto"];
+
+/// `[notifications] detail` 可用的值（见 `NotificationDetail::from_setting`，不区分大小写）
+const NOTIFICATION_DETAILS: &[&str] = &["full", "sender_only", "count_only"];
+
+/// 问题类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// 本版本不认识的字段（原样保留在文件中，但不起作用）
+    Unknown,
+
+    /// 值无效或超出范围（附说明）
+    Invalid(String),
+
+    /// 与其他设置冲突，该项不生效（附说明）
+    Conflict(String),
+}
+
+/// 一个配置问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// 问题所在的位置（`段.字段`）
+    pub key: String,
+
+    pub problem: Problem,
+}
+
+impl Issue {
+    fn invalid(key: &str, detail: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            problem: Problem::Invalid(detail.into()),
+        }
+    }
+
+    fn conflict(key: &str, detail: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            problem: Problem::Conflict(detail.into()),
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            Problem::Unknown => write!(f, "{}: 本版本不认识的字段（将原样保留）", self.key),
+            Problem::Invalid(detail) => write!(f, "{}: 值无效，{}", self.key, detail),
+            Problem::Conflict(detail) => write!(f, "{}: 设置冲突，{}", self.key, detail),
+        }
+    }
+}
+
+/// 校验配置文件内容（`config` 为从同一内容解析出的配置）
+pub fn validate(content: &str, config: &Config) -> Vec<Issue> {
+    // 内容已能解析为 `config`，列出未知字段不会失败
+    let mut issues: Vec<Issue> = super::document::unknown_keys(content, config)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|key| Issue {
+            key,
+            problem: Problem::Unknown,
+        })
+        .collect();
+
+    check_app(config, &mut issues);
+    check_logging(config, &mut issues);
+    check_network(config, &mut issues);
+    check_notifications(config, &mut issues);
+    check_automation(config, &mut issues);
+    check_tray(config, &mut issues);
+    issues
+}
+
+/// 校验值是否为可选值之一
+fn check_choice(key: &str, value: &str, choices: &[&str], issues: &mut Vec<Issue>) {
+    if !choices.contains(&value) {
+        issues.push(Issue::invalid(
+            key,
+            format!("{:?} 不是可用的值（可选 {}）", value, choices.join(" / ")),
+        ));
+    }
+}
+
+/// 不区分大小写的可选值按小写比较（与各 `from_setting` 一致）
+fn normalize(value: &str) -> String {
+    value.trim().to_ascii_lowercase()
+}
+
+fn check_app(config: &Config, issues: &mut Vec<Issue>) {
+    let app = &config.app;
+    check_choice("app.theme", &app.theme, THEMES, issues);
+    check_choice("app.language", &normalize(&app.language), LANGUAGES, issues);
+    check_choice(
+        "app.open_with",
+        &normalize(&app.open_with),
+        OPEN_WITH,
+        issues,
+    );
+
+    if app.sync_interval < crate::sync::MIN_SYNC_INTERVAL_SECS {
+        issues.push(Issue::invalid(
+            "app.sync_interval",
+            format!(
+                "{} 秒过短，按最短间隔 {} 秒同步",
+                app.sync_interval,
+                crate::sync::MIN_SYNC_INTERVAL_SECS
+            ),
+        ));
+    }
+
+    for pattern in &app.automated_sender_patterns {
+        if let Err(e) = regex::Regex::new(pattern) {
+            issues.push(Issue::invalid(
+                "app.automated_sender_patterns",
+                format!("规则 {:?} 不是有效的正则表达式（已忽略）: {}", pattern, e),
+            ));
+        }
+    }
+}
+
+fn check_logging(config: &Config, issues: &mut Vec<Issue>) {
+    if let Err(e) = tracing_subscriber::EnvFilter::try_new(&config.logging.level) {
+        issues.push(Issue::invalid(
+            "logging.level",
+            format!("{:?} 不是有效的日志级别: {}", config.logging.level, e),
+        ));
+    }
+}
+
+fn check_network(config: &Config, issues: &mut Vec<Issue>) {
+    let network = &config.network;
+    let proxy = network
+        .proxy
+        .as_deref()
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty());
+    let direct = proxy.is_some_and(|proxy| proxy.eq_ignore_ascii_case("direct"));
+
+    if let Some(proxy) = proxy.filter(|_| !direct)
+        && reqwest::Proxy::all(proxy).is_err()
+    {
+        issues.push(Issue::invalid(
+            "network.proxy",
+            format!("{:?} 不是有效的代理地址", proxy),
+        ));
+    }
+    if direct && network.proxy_username.is_some() {
+        issues.push(Issue::conflict(
+            "network.proxy_username",
+            "proxy 为 \"direct\"（不使用代理），代理认证不生效",
+        ));
+    }
+    if network.proxy_username.is_none() && network.proxy_password.is_some() {
+        issues.push(Issue::conflict(
+            "network.proxy_password",
+            "未设置 proxy_username，代理密码不生效",
+        ));
+    }
+
+    let timeouts = [
+        ("network.request_timeout_secs", network.request_timeout_secs),
+        ("network.connect_timeout_secs", network.connect_timeout_secs),
+        ("network.count_timeout_secs", network.count_timeout_secs),
+        ("network.avatar_timeout_secs", network.avatar_timeout_secs),
+    ];
+    for (key, secs) in timeouts {
+        if secs == 0 {
+            issues.push(Issue::invalid(key, "超时须大于 0 秒"));
+        }
+    }
+    if network.connect_timeout_secs > network.request_timeout_secs {
+        issues.push(Issue::conflict(
+            "network.connect_timeout_secs",
+            format!(
+                "大于请求整体超时 request_timeout_secs（{} 秒），实际以整体超时为准",
+                network.request_timeout_secs
+            ),
+        ));
+    }
+}
+
+fn check_notifications(config: &Config, issues: &mut Vec<Issue>) {
+    let notifications = &config.notifications;
+    check_choice(
+        "notifications.detail",
+        &normalize(&notifications.detail),
+        NOTIFICATION_DETAILS,
+        issues,
+    );
+
+    let defaults = super::NotificationsConfig::default();
+    if !notifications.enabled && notifications.detail != defaults.detail {
+        issues.push(Issue::conflict(
+            "notifications.detail",
+            "通知已关闭（enabled = false），通知内容设置不生效",
+        ));
+    }
+}
+
+/// Webhook 地址须为 http(s) URL
+fn is_webhook_url(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn check_automation(config: &Config, issues: &mut Vec<Issue>) {
+    let automation = &config.automation;
+    let url = automation
+        .webhook_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+
+    if let Some(url) = url
+        && !is_webhook_url(url)
+    {
+        issues.push(Issue::invalid(
+            "automation.webhook_url",
+            format!("{:?} 不是有效的 http(s) 地址", url),
+        ));
+    }
+
+    let mut overrides = 0;
+    for (email, account_url) in &automation.accounts {
+        let account_url = account_url.trim();
+        if account_url.is_empty() {
+            continue;
+        }
+        overrides += 1;
+        if !is_webhook_url(account_url) {
+            issues.push(Issue::invalid(
+                &format!("automation.accounts.{}", email),
+                format!("{:?} 不是有效的 http(s) 地址", account_url),
+            ));
+        }
+    }
+
+    if automation.webhook_secret.is_some() && url.is_none() && overrides == 0 {
+        issues.push(Issue::conflict(
+            "automation.webhook_secret",
+            "未设置 webhook_url 或账户地址，共享密钥不生效",
+        ));
+    }
+}
+
+fn check_tray(config: &Config, issues: &mut Vec<Issue>) {
+    if let Err(e) = badge::check_thresholds(&config.tray.thresholds) {
+        issues.push(Issue::invalid(
+            "tray.thresholds",
+            format!("{:#}（使用默认分档）", e),
+        ));
+    } else if let Err(e) = BadgeLevels::from_config(&config.tray) {
+        issues.push(Issue::invalid(
+            "tray.colors",
+            format!("{:#}（使用默认分档）", e),
+        ));
+    }
+}
+
+/// 横幅中列出的位置（去重，保持出现顺序）
+pub fn keys(issues: &[Issue]) -> String {
+    let mut keys: Vec<&str> = Vec::new();
+    for issue in issues {
+        if !keys.contains(&issue.key.as_str()) {
+            keys.push(&issue.key);
+        }
+    }
+    keys.join(", ")
+}
+
+/// 启动时校验配置文件并记录警告，返回发现的问题
+///
+/// 文件不存在或无法解析时返回空列表（加载配置时已另行处理）
+pub fn check_at_startup() -> Vec<Issue> {
+    let content = match super::config_path().and_then(|path| Ok(std::fs::read_to_string(path)?)) {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::debug!("读取配置文件失败，跳过校验: {:#}", e);
+            return Vec::new();
+        }
+    };
+    let Ok(config) = super::parse(&content) else {
+        return Vec::new();
+    };
+
+    let issues = validate(&content, &config);
+    for issue in &issues {
+        tracing::warn!("⚠️ 配置文件: {}", issue);
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 校验一段配置文件内容，返回问题所在的位置
+    fn issue_keys(content: &str) -> Vec<String> {
+        let config = super::super::parse(content).unwrap();
+        validate(content, &config)
+            .into_iter()
+            .map(|issue| issue.key)
+            .collect()
+    }
+
+    #[test]
+    fn test_defaults_have_no_issues() {
+        let content = toml::to_string_pretty(&Config::default()).unwrap();
+        assert_eq!(issue_keys(&content), Vec::<String>::new());
+        assert_eq!(issue_keys(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_bad_configs() {
+        let cases: &[(&str, &[&str])] = &[
+            (
+                "[app]\ncompact_mode = true\n\n[labels]\nwatched = [\"INBOX\"]\n",
+                &["app.compact_mode", "labels"],
+            ),
+            ("[app]\ntheme = \"blue\"\n", &["app.theme"]),
+            ("[app]\ntheme = \"Dark\"\n", &["app.theme"]),
+            ("[app]\nlanguage = \"fr\"\n", &["app.language"]),
+            ("[app]\nlanguage = \"zh-CN\"\n", &[]),
+            ("[app]\nopen_with = \"outlook\"\n", &["app.open_with"]),
+            ("[app]\nsync_interval = 1\n", &["app.sync_interval"]),
+            (
+                "[app]\nautomated_sender_patterns = [\"(unclosed\", \"noreply\"]\n",
+                &["app.automated_sender_patterns"],
+            ),
+            ("[logging]\nlevel = \"nanomail=loud\"\n", &["logging.level"]),
+            ("[network]\nproxy = \"direct\"\n", &[]),
+            (
+                "[network]\nproxy = \"direct\"\nproxy_username = \"me\"\n",
+                &["network.proxy_username"],
+            ),
+            (
+                "[network]\nproxy_password = \"secret\"\n",
+                &["network.proxy_password"],
+            ),
+            (
+                "[network]\nrequest_timeout_secs = 0\n",
+                &[
+                    "network.request_timeout_secs",
+                    "network.connect_timeout_secs",
+                ],
+            ),
+            (
+                "[network]\nconnect_timeout_secs = 60\n",
+                &["network.connect_timeout_secs"],
+            ),
+            (
+                "[notifications]\ndetail = \"everything\"\n",
+                &["notifications.detail"],
+            ),
+            (
+                "[notifications]\nenabled = false\ndetail = \"count_only\"\n",
+                &["notifications.detail"],
+            ),
+            ("[notifications]\nenabled = false\n", &[]),
+            (
+                "[automation]\nwebhook_url = \"hub.local/mail\"\n",
+                &["automation.webhook_url"],
+            ),
+            (
+                "[automation]\nwebhook_secret = \"s3cret\"\n",
+                &["automation.webhook_secret"],
+            ),
+            (
+                "[automation]\nwebhook_secret = \"s3cret\"\n\n[automation.accounts]\n\"a@example.com\" = \"ftp://hub.local\"\n",
+                &["automation.accounts.a@example.com"],
+            ),
+            (
+                "[automation]\nwebhook_url = \"http://hub.local/mail\"\nwebhook_secret = \"s3cret\"\n",
+                &[],
+            ),
+            ("[tray]\nthresholds = [20, 5]\n", &["tray.thresholds"]),
+            (
+                "[tray]\nthresholds = [5, 20]\ncolors = [\"#34A853\", \"red\", \"#EA4335\"]\n",
+                &["tray.colors"],
+            ),
+            ("[oauth]\nclient_id = \"id\"\n", &[]),
+        ];
+
+        for (content, expected) in cases {
+            assert_eq!(&issue_keys(content), expected, "配置内容:\n{}", content);
+        }
+    }
+
+    #[test]
+    fn test_problem_kinds_and_banner_keys() {
+        let content = "[app]\ntheme = \"blue\"\ncompact_mode = true\n\n[notifications]\nenabled = false\ndetail = \"loud\"\n";
+        let config = super::super::parse(content).unwrap();
+        let issues = validate(content, &config);
+
+        assert_eq!(issues[0].problem, Problem::Unknown);
+        assert!(matches!(issues[1].problem, Problem::Invalid(_)));
+        assert!(issues[1].to_string().starts_with("app.theme: "));
+        assert!(
+            issues
+                .iter()
+                .any(|issue| matches!(issue.problem, Problem::Conflict(_)))
+        );
+        assert_eq!(
+            keys(&issues),
+            "app.compact_mode, app.theme, notifications.detail"
+        );
+    }
+}
@@ -0,0 +1,371 @@
+/// 配置文件热重载模块
+///
+/// 监听 `config.toml` 所在目录（编辑器常以"写临时文件再重命名"的方式保存，直接监听文件会丢事件），
+/// 对连续事件去抖后重新解析，与内存中的配置比较，仅把变化的部分交给回调处理。
+/// 同一目录下的 `accounts.toml` 被手动编辑时重新读取账户，报告新增、删除和修改的账户。
+/// 本程序自身写入的内容通过哈希识别并跳过，避免重载循环
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use super::storage::{self, StoredAccount};
+use super::{AutomationConfig, Config, TrayConfig};
+use crate::utils::redact::redact_email;
+
+/// 去抖间隔：最后一次文件事件后等待这么久再重载
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 配置变化（字段为 `None` 表示未变化）
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConfigDelta {
+    /// 同步间隔（秒）
+    pub sync_interval: Option<u64>,
+    /// 主题（"light" | "dark"）
+    pub theme: Option<String>,
+    /// 紧凑模式
+    pub compact: Option<bool>,
+    /// 日志级别（EnvFilter 语法）
+    pub log_level: Option<String>,
+    /// 是否启用新邮件通知
+    pub notifications_enabled: Option<bool>,
+    /// 通知内容级别（配置原值）
+    pub notification_detail: Option<String>,
+    /// 是否合并同一轮的新邮件通知
+    pub notifications_combine: Option<bool>,
+    /// 界面语言（配置原值）
+    pub language: Option<String>,
+    /// 逐页计数上限
+    pub count_ceiling: Option<u32>,
+    /// 每分钟 API 请求数的警告阈值
+    pub api_rate_warning: Option<u32>,
+    /// 每轮同步的重试预算
+    pub retry_budget: Option<u32>,
+    /// 已折叠的账户分组
+    pub collapsed_groups: Option<Vec<String>>,
+    /// 自动邮件发件人规则
+    pub automated_sender_patterns: Option<Vec<String>>,
+    /// 按流量计费的网络下是否节流同步
+    pub respect_metered: Option<bool>,
+    /// 新邮件 Webhook 设置
+    pub automation: Option<AutomationConfig>,
+    /// 托盘未读圆点的分档
+    pub tray: Option<TrayConfig>,
+}
+
+impl ConfigDelta {
+    /// 计算两份配置之间的差异
+    pub fn between(old: &Config, new: &Config) -> Self {
+        fn changed<T: PartialEq + Clone>(old: &T, new: &T) -> Option<T> {
+            (old != new).then(|| new.clone())
+        }
+
+        Self {
+            sync_interval: changed(&old.app.sync_interval, &new.app.sync_interval),
+            theme: changed(&old.app.theme, &new.app.theme),
+            compact: changed(&old.app.compact, &new.app.compact),
+            log_level: changed(&old.logging.level, &new.logging.level),
+            notifications_enabled: changed(&old.notifications.enabled, &new.notifications.enabled),
+            notification_detail: changed(&old.notifications.detail, &new.notifications.detail),
+            notifications_combine: changed(&old.notifications.combine, &new.notifications.combine),
+            language: changed(&old.app.language, &new.app.language),
+            count_ceiling: changed(&old.app.count_ceiling, &new.app.count_ceiling),
+            api_rate_warning: changed(&old.app.api_rate_warning, &new.app.api_rate_warning),
+            retry_budget: changed(&old.app.retry_budget, &new.app.retry_budget),
+            collapsed_groups: changed(&old.app.collapsed_groups, &new.app.collapsed_groups),
+            automated_sender_patterns: changed(
+                &old.app.automated_sender_patterns,
+                &new.app.automated_sender_patterns,
+            ),
+            respect_metered: changed(&old.app.respect_metered, &new.app.respect_metered),
+            automation: changed(&old.automation, &new.automation),
+            tray: changed(&old.tray, &new.tray),
+        }
+    }
+
+    /// 是否没有任何变化
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 账户文件的外部修改（按邮箱区分）
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccountsDelta {
+    /// 新增的账户
+    pub added: Vec<String>,
+    /// 删除的账户
+    pub removed: Vec<String>,
+    /// 内容有变化的账户
+    pub changed: Vec<String>,
+}
+
+impl AccountsDelta {
+    /// 计算两份账户列表之间的差异
+    pub fn between(old: &[StoredAccount], new: &[StoredAccount]) -> Self {
+        fn find<'a>(accounts: &'a [StoredAccount], email: &str) -> Option<&'a StoredAccount> {
+            accounts.iter().find(|a| a.email() == email)
+        }
+        fn value(account: &StoredAccount) -> Option<toml::Value> {
+            toml::Value::try_from(account).ok()
+        }
+
+        let mut delta = Self::default();
+        for account in new {
+            let email = account.email().to_string();
+            match find(old, &email) {
+                None => delta.added.push(email),
+                Some(previous) if value(previous) != value(account) => delta.changed.push(email),
+                Some(_) => {}
+            }
+        }
+        delta.removed = old
+            .iter()
+            .filter(|a| find(new, a.email()).is_none())
+            .map(|a| a.email().to_string())
+            .collect();
+        delta
+    }
+
+    /// 是否没有任何变化
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl std::fmt::Display for AccountsDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |emails: &[String]| {
+            emails
+                .iter()
+                .map(|e| redact_email(e))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(
+            f,
+            "新增 [{}]，删除 [{}]，修改 [{}]",
+            list(&self.added),
+            list(&self.removed),
+            list(&self.changed)
+        )
+    }
+}
+
+/// 监听事件
+#[derive(Debug)]
+pub enum ConfigEvent {
+    /// 配置已重载，携带变化部分
+    Changed(Box<ConfigDelta>),
+    /// 文件内容无效，已保留之前的配置
+    Invalid(String),
+    /// 账户文件在外部被修改，已重新读取
+    AccountsChanged(AccountsDelta),
+}
+
+/// 配置监听守卫（drop 后停止监听）
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// 一批文件事件涉及的文件
+#[derive(Default)]
+struct Touched {
+    config: bool,
+    accounts: bool,
+}
+
+impl Touched {
+    fn record(&mut self, event: notify::Result<notify::Event>, config: &Path, accounts: &Path) {
+        let event = match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => event,
+            Ok(_) => return,
+            Err(e) => {
+                tracing::warn!("配置文件监听出错: {}", e);
+                return;
+            }
+        };
+
+        let touches = |path: &Path| {
+            event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == path.file_name())
+        };
+        self.config |= touches(config);
+        self.accounts |= touches(accounts);
+    }
+}
+
+/// 启动配置文件与账户文件监听
+///
+/// `handler` 在后台线程中调用，需要操作 UI 时请使用 `invoke_from_event_loop`
+pub fn start<F>(handler: F) -> Result<ConfigWatcher>
+where
+    F: Fn(ConfigEvent) + Send + 'static,
+{
+    let path = super::config_path()?;
+    let accounts_path = storage::accounts_path()?;
+    let dir = path
+        .parent()
+        .context("配置文件路径没有父目录")?
+        .to_path_buf();
+    let mut current = super::load().context("加载初始配置失败")?;
+    let mut accounts = storage::load_accounts().context("加载初始账户失败")?;
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx).context("创建文件监听器失败")?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("监听配置目录失败: {}", dir.display()))?;
+
+    std::thread::Builder::new()
+        .name("config-watcher".to_string())
+        .spawn(move || {
+            while let Ok(event) = rx.recv() {
+                let mut touched = Touched::default();
+                touched.record(event, &path, &accounts_path);
+                if !touched.config && !touched.accounts {
+                    continue;
+                }
+
+                // 去抖：合并后续紧邻的事件
+                while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+                    touched.record(event, &path, &accounts_path);
+                }
+
+                if touched.config {
+                    reload_config(&path, &mut current, &handler);
+                }
+                if touched.accounts {
+                    reload_accounts(&accounts_path, &mut accounts, &handler);
+                }
+            }
+            tracing::debug!("配置文件监听线程退出");
+        })
+        .context("启动配置监听线程失败")?;
+
+    tracing::info!("已开始监听配置文件: {}", dir.display());
+    Ok(ConfigWatcher { _watcher: watcher })
+}
+
+/// 重新解析配置文件，把变化部分交给回调
+fn reload_config(path: &Path, current: &mut Config, handler: &impl Fn(ConfigEvent)) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            // 保存过程中文件可能短暂不存在，等待下一次事件
+            tracing::debug!("读取配置文件失败，等待下一次变更: {}", e);
+            return;
+        }
+    };
+
+    let own_write = super::is_own_write(&content);
+    match super::parse(&content) {
+        Ok(new) => {
+            let delta = ConfigDelta::between(current, &new);
+            *current = new;
+            if own_write {
+                tracing::debug!("忽略本程序写入的配置变更");
+            } else if delta.is_empty() {
+                tracing::debug!("配置文件已变更，但无需热重载的字段未变化");
+            } else {
+                tracing::info!("配置文件已变更，热重载: {:?}", delta);
+                handler(ConfigEvent::Changed(Box::new(delta)));
+            }
+        }
+        Err(e) => {
+            tracing::warn!("配置文件格式错误，保留之前的配置: {}", e);
+            handler(ConfigEvent::Invalid(e.to_string()));
+        }
+    }
+}
+
+/// 重新读取账户文件（与未写入的修改合并，见 `storage`），把增删改的账户交给回调
+fn reload_accounts(path: &Path, current: &mut Vec<StoredAccount>, handler: &impl Fn(ConfigEvent)) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::debug!("读取账户文件失败，等待下一次变更: {}", e);
+            return;
+        }
+    };
+
+    let own_write = storage::is_own_write(&content);
+    match storage::load_accounts() {
+        Ok(accounts) => {
+            let delta = AccountsDelta::between(current, &accounts);
+            *current = accounts;
+            if own_write {
+                tracing::debug!("忽略本程序写入的账户变更");
+            } else if delta.is_empty() {
+                tracing::debug!("账户文件已变更，但账户内容未变化");
+            } else {
+                tracing::info!("账户文件已在外部修改，重新加载: {}", delta);
+                handler(ConfigEvent::AccountsChanged(delta));
+            }
+        }
+        Err(e) => {
+            tracing::warn!("账户文件格式错误，保留之前的账户: {:#}", e);
+            handler(ConfigEvent::Invalid(format!("{:#}", e)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_empty_for_identical_configs() {
+        assert!(ConfigDelta::between(&Config::default(), &Config::default()).is_empty());
+    }
+
+    #[test]
+    fn test_delta_reports_changed_fields_only() {
+        let old = Config::default();
+        let mut new = Config::default();
+        new.app.theme = "dark".to_string();
+        new.app.sync_interval = 60;
+        new.notifications.enabled = false;
+        new.app.compact = true;
+
+        let delta = ConfigDelta::between(&old, &new);
+        assert_eq!(delta.theme.as_deref(), Some("dark"));
+        assert_eq!(delta.compact, Some(true));
+        assert_eq!(delta.sync_interval, Some(60));
+        assert_eq!(delta.notifications_enabled, Some(false));
+        assert_eq!(delta.log_level, None);
+    }
+
+    fn account(email: &str, display_name: &str) -> StoredAccount {
+        toml::from_str(&format!(
+            r#"
+type = "imap"
+email = "{email}"
+display_name = "{display_name}"
+host = "imap.example.org"
+port = 993
+username = "{email}"
+password = "encrypted:AAAA"
+"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_accounts_delta_by_email() {
+        let old = vec![account("a@example.org", "A"), account("b@example.org", "B")];
+        let new = vec![
+            account("a@example.org", "Work"),
+            account("c@example.org", "C"),
+        ];
+
+        let delta = AccountsDelta::between(&old, &new);
+        assert_eq!(delta.added, ["c@example.org"]);
+        assert_eq!(delta.removed, ["b@example.org"]);
+        assert_eq!(delta.changed, ["a@example.org"]);
+        assert!(AccountsDelta::between(&new, &new).is_empty());
+    }
+}
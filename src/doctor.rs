@@ -0,0 +1,722 @@
+/// 诊断模块
+///
+/// 依次检查配置文件、凭据自检、OAuth 配置、账户凭据与 API 用量、网络连通性和系统通知设置，
+/// 命令行 `--doctor` 与托盘菜单"运行诊断"共用同一组检查
+use chrono::{DateTime, Local, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::oauth_config::{OAuthConfig, OutlookOAuthConfig};
+use crate::config::self_test;
+use crate::config::storage::{self, StoredAccount};
+use crate::config::{self, NetworkConfig};
+use crate::mail;
+use crate::mail::gmail::endpoints::GmailEndpoints;
+use crate::mail::quota::{self, Usage};
+use crate::notification;
+use crate::sync::history::{AccountOutcome, RoundRecord};
+use crate::sync::{AccountEngineState, EngineStatus};
+use crate::ui;
+use crate::utils::http_client::HttpClientFactory;
+use crate::utils::i18n::{tr, tr_fmt};
+use crate::utils::telemetry::RoundSummary;
+
+/// 单个连通性检查的超时时间
+const REACH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 检查结论
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// 通过
+    Pass,
+
+    /// 暂不影响使用（如未使用的服务缺少配置）
+    Warn,
+
+    /// 失败，需要处理
+    Fail,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    /// 检查项名称
+    pub name: String,
+
+    /// 结论
+    pub status: CheckStatus,
+
+    /// 一行说明
+    pub detail: String,
+
+    /// 一行修复建议（通过时为空）
+    pub hint: String,
+}
+
+impl CheckResult {
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: String::new(),
+        }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>, hint: &str) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            hint: hint.to_string(),
+            ..Self::pass(name, detail)
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, hint: &str) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            hint: hint.to_string(),
+            ..Self::pass(name, detail)
+        }
+    }
+}
+
+/// 运行全部检查
+pub async fn run_all() -> Vec<CheckResult> {
+    let mut results = Vec::new();
+
+    let config_file = config::config_path()
+        .map_err(|e| std::io::Error::other(e.to_string()))
+        .and_then(std::fs::read_to_string);
+    let (result, network) = check_config(config_file);
+    results.push(result);
+
+    results.push(check_self_test(self_test::check()));
+
+    let accounts = storage::load_accounts();
+    results.push(check_accounts_file(&accounts));
+    let accounts = accounts.unwrap_or_default();
+    let uses = |kind: &str| accounts.iter().any(|a| a.kind() == kind);
+
+    let gmail = OAuthConfig::load().unwrap_or_default();
+    results.push(check_oauth(
+        "Gmail",
+        gmail.is_placeholder(),
+        uses("gmail"),
+        tr("doctor.oauth_hint_gmail"),
+    ));
+    let outlook = OutlookOAuthConfig::load().unwrap_or_default();
+    results.push(check_oauth(
+        "Outlook",
+        outlook.is_placeholder(),
+        uses("outlook"),
+        tr("doctor.oauth_hint_outlook"),
+    ));
+
+    for account in &accounts {
+        results.push(check_account_secrets(
+            account.email(),
+            decrypt_secrets(account),
+        ));
+        if let StoredAccount::Gmail(gmail) = account {
+            results.push(check_api_usage(
+                &gmail.email,
+                quota::usage(&gmail.email),
+                quota::rate_warning(),
+            ));
+        }
+    }
+
+    // 使用独立的客户端：诊断可能运行在临时的运行时上，不与同步共享连接池
+    match HttpClientFactory::build(&network) {
+        Ok(client) => {
+            // 未携带令牌的请求会被拒绝，但收到任何响应都说明服务可达
+            let gmail = GmailEndpoints::current();
+            let mut endpoints = vec![
+                ("Google OAuth", gmail.token.clone()),
+                ("Gmail API", gmail.profile_url()),
+            ];
+            if uses("outlook")
+                && let Ok(outlook) = mail::outlook::oauth::client()
+            {
+                endpoints.push(("Microsoft OAuth", outlook.endpoints.token));
+            }
+
+            for (name, url) in endpoints {
+                let outcome = client
+                    .get(&url)
+                    .timeout(REACH_TIMEOUT)
+                    .send()
+                    .await
+                    .map(|response| response.status().as_u16())
+                    .map_err(|e| e.to_string());
+                results.push(check_reachable(name, outcome));
+            }
+        }
+        Err(e) => results.push(check_reachable("HTTP", Err(format!("{:#}", e)))),
+    }
+
+    #[cfg(windows)]
+    {
+        let (app_enabled, toasts_enabled) = read_toast_settings();
+        results.push(check_toast(app_enabled, toasts_enabled));
+    }
+    #[cfg(target_os = "linux")]
+    results.push(check_notification_service(notification::server_name()));
+
+    results
+}
+
+/// 在临时运行时中运行全部检查（用于命令行和独立线程）
+pub fn run_blocking() -> Vec<CheckResult> {
+    match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(rt) => rt.block_on(run_all()),
+        Err(e) => vec![CheckResult::fail(
+            "Tokio",
+            e.to_string(),
+            tr("doctor.runtime_hint"),
+        )],
+    }
+}
+
+/// 是否存在失败项
+pub fn has_failures(results: &[CheckResult]) -> bool {
+    results.iter().any(|r| r.status == CheckStatus::Fail)
+}
+
+/// 格式化为诊断报告（每项一行，未通过的项附修复建议）
+pub fn format_report(results: &[CheckResult]) -> String {
+    let mut report = String::new();
+    for result in results {
+        let mark = match result.status {
+            CheckStatus::Pass => "✓",
+            CheckStatus::Warn => "!",
+            CheckStatus::Fail => "✗",
+        };
+        report.push_str(&format!("{} {}: {}\n", mark, result.name, result.detail));
+        if !result.hint.is_empty() {
+            report.push_str(&format!("    → {}\n", result.hint));
+        }
+    }
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    report.push('\n');
+    report.push_str(&tr_fmt(
+        "doctor.summary",
+        &[
+            &count(CheckStatus::Pass),
+            &count(CheckStatus::Warn),
+            &count(CheckStatus::Fail),
+        ],
+    ));
+    report
+}
+
+/// 格式化同步引擎状态（附在托盘"运行诊断"的报告之后）
+///
+/// 依次为引擎是否运行、下一轮同步时间和本次运行中同步过的各账户的状态
+pub fn format_engine_status(status: &EngineStatus, now: DateTime<Utc>) -> String {
+    let state = if !status.running {
+        tr("doctor.engine_stopped")
+    } else if status.paused {
+        tr("doctor.engine_paused")
+    } else {
+        tr("doctor.engine_running")
+    };
+    let mut text = format!("{}: {}\n", tr("doctor.engine"), state);
+
+    if status.running {
+        let next_round = match status.next_round_at {
+            Some(at) if at > now => tr_fmt(
+                "doctor.engine_next_round",
+                &[&clock_time(at), &(at - now).num_seconds()],
+            ),
+            _ => tr("doctor.engine_next_round_due").to_string(),
+        };
+        text.push_str(&format!("  {}\n", next_round));
+    }
+
+    if status.per_account.is_empty() {
+        text.push_str(&format!("  {}\n", tr("doctor.engine_no_accounts")));
+    }
+    for account in &status.per_account {
+        let state = match (account.state, account.next_attempt_at) {
+            (AccountEngineState::Syncing, _) => tr("doctor.engine_syncing").to_string(),
+            (AccountEngineState::Synced, _) => tr("doctor.engine_synced").to_string(),
+            (AccountEngineState::Failed, _) => tr("doctor.engine_failed").to_string(),
+            (AccountEngineState::Backoff, Some(at)) => {
+                tr_fmt("doctor.engine_backoff", &[&clock_time(at)])
+            }
+            (AccountEngineState::Backoff, None) => tr("doctor.engine_failed").to_string(),
+        };
+        text.push_str(&format!("  {}: {}\n", account.email, state));
+    }
+    text
+}
+
+/// 格式化最近一轮同步各阶段的耗时（附在同步引擎状态之后）
+pub fn format_last_round(summary: Option<&RoundSummary>) -> String {
+    match summary {
+        Some(summary) => tr_fmt(
+            "doctor.last_round",
+            &[&summary.round, &summary.total.as_millis(), summary],
+        ),
+        None => tr("doctor.last_round_none").to_string(),
+    }
+}
+
+/// 运行诊断的报告中列出的同步记录轮数（完整记录见窗口的"同步记录"页面）
+const REPORT_HISTORY_ROUNDS: usize = 10;
+
+/// 格式化最近几轮同步的记录（附在最近一轮的耗时之后；还没有记录时为空）
+///
+/// 每轮一行：开始时间、相对时间、有账户未同步成功时标记 ✗、耗时、未读合计与各账户的结果
+pub fn format_sync_history(rounds: &[Arc<RoundRecord>], now: DateTime<Utc>) -> String {
+    if rounds.is_empty() {
+        return String::new();
+    }
+    let shown = &rounds[..rounds.len().min(REPORT_HISTORY_ROUNDS)];
+    let mut text = tr_fmt("doctor.history", &[&shown.len(), &rounds.len()]);
+    text.push('\n');
+    for round in shown {
+        let accounts: Vec<String> = round
+            .accounts
+            .iter()
+            .map(|account| format!("{} {}", account.email, outcome_label(account.outcome)))
+            .collect();
+        text.push_str(&format!(
+            "  {} {}\n",
+            if round.has_failures() { "✗" } else { "✓" },
+            tr_fmt(
+                "doctor.history_round",
+                &[
+                    &clock_time(round.started_at),
+                    &ui::history::relative_time(round.started_at, now),
+                    &round.duration_ms,
+                    &round.unread,
+                    &accounts.join(", "),
+                ],
+            )
+        ));
+    }
+    text
+}
+
+/// 账户结果的说明
+fn outcome_label(outcome: AccountOutcome) -> &'static str {
+    match outcome {
+        AccountOutcome::Synced => tr("history.synced"),
+        AccountOutcome::Failed => tr("history.failed"),
+        AccountOutcome::Offline => tr("history.offline"),
+        AccountOutcome::Unavailable => tr("history.unavailable"),
+        AccountOutcome::Suppressed => tr("history.suppressed"),
+    }
+}
+
+/// 本地时间（时:分:秒）
+fn clock_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%H:%M:%S").to_string()
+}
+
+/// 配置文件能否解析（文件不存在时使用默认配置，也算通过）
+///
+/// 同时返回其中的网络配置，供连通性检查使用相同的代理设置
+fn check_config(read: std::io::Result<String>) -> (CheckResult, NetworkConfig) {
+    let name = tr("doctor.config");
+    match read {
+        Ok(content) => match config::parse(&content) {
+            Ok(config) => (
+                CheckResult::pass(name, tr("doctor.config_ok")),
+                config.network,
+            ),
+            Err(e) => (
+                CheckResult::fail(
+                    name,
+                    tr_fmt("doctor.config_bad", &[&first_line(&e.to_string())]),
+                    tr("doctor.config_hint"),
+                ),
+                NetworkConfig::default(),
+            ),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (
+            CheckResult::pass(name, tr("doctor.config_missing")),
+            NetworkConfig::default(),
+        ),
+        Err(e) => (
+            CheckResult::fail(
+                name,
+                tr_fmt("doctor.config_bad", &[&e]),
+                tr("doctor.config_hint"),
+            ),
+            NetworkConfig::default(),
+        ),
+    }
+}
+
+/// 凭据自检（本机密钥能否加解密，以及是否与保存样本时的密钥相同）
+fn check_self_test(outcome: self_test::Outcome) -> CheckResult {
+    let name = tr("doctor.self_test");
+    match outcome {
+        self_test::Outcome::Passed { .. } => CheckResult::pass(name, tr("doctor.self_test_ok")),
+        self_test::Outcome::Failed(failure) => {
+            CheckResult::fail(name, failure.describe(), tr("doctor.self_test_hint"))
+        }
+    }
+}
+
+/// 账户文件能否读取
+fn check_accounts_file(accounts: &anyhow::Result<Vec<StoredAccount>>) -> CheckResult {
+    let name = tr("doctor.accounts");
+    match accounts {
+        Ok(accounts) => CheckResult::pass(name, tr_fmt("doctor.accounts_ok", &[&accounts.len()])),
+        Err(e) => CheckResult::fail(
+            name,
+            tr_fmt("doctor.accounts_bad", &[&first_line(&format!("{:#}", e))]),
+            tr("doctor.accounts_hint"),
+        ),
+    }
+}
+
+/// OAuth 客户端是否仍为默认占位符（已有该服务的账户时为失败，否则只是无法添加）
+fn check_oauth(provider: &str, placeholder: bool, in_use: bool, hint: &str) -> CheckResult {
+    let name = tr_fmt("doctor.oauth", &[&provider]);
+    match (placeholder, in_use) {
+        (false, _) => CheckResult::pass(name, tr("doctor.oauth_ok")),
+        (true, true) => CheckResult::fail(name, tr("doctor.oauth_placeholder"), hint),
+        (true, false) => CheckResult::warn(name, tr("doctor.oauth_placeholder"), hint),
+    }
+}
+
+/// 解密账户保存的全部凭据
+fn decrypt_secrets(account: &StoredAccount) -> anyhow::Result<()> {
+    match account {
+        StoredAccount::Gmail(account) | StoredAccount::Outlook(account) => {
+            account.decrypt_access_token()?;
+            account.decrypt_refresh_token()?;
+        }
+        StoredAccount::Imap(account) => {
+            account.decrypt_password()?;
+        }
+    }
+    Ok(())
+}
+
+/// 本机密钥能否解密账户凭据
+fn check_account_secrets(email: &str, decrypted: anyhow::Result<()>) -> CheckResult {
+    let name = tr_fmt("doctor.secrets", &[&email]);
+    match decrypted {
+        Ok(()) => CheckResult::pass(name, tr("doctor.secrets_ok")),
+        Err(e) => CheckResult::fail(
+            name,
+            tr_fmt("doctor.secrets_bad", &[&first_line(&format!("{:#}", e))]),
+            tr("doctor.secrets_hint"),
+        ),
+    }
+}
+
+/// 账户最近的 API 请求数是否超过每分钟的警告阈值
+fn check_api_usage(email: &str, usage: Usage, limit: u32) -> CheckResult {
+    let name = tr_fmt("doctor.api_usage", &[&email]);
+    let detail = tr_fmt(
+        "details.api_usage_value",
+        &[&usage.last_minute, &usage.last_day],
+    );
+    if usage.last_minute > limit {
+        CheckResult::warn(name, detail, &tr_fmt("doctor.api_usage_hint", &[&limit]))
+    } else {
+        CheckResult::pass(name, detail)
+    }
+}
+
+/// 端点能否访问（收到任何 HTTP 响应即视为可达）
+fn check_reachable(endpoint: &str, outcome: Result<u16, String>) -> CheckResult {
+    let name = tr_fmt("doctor.reach", &[&endpoint]);
+    match outcome {
+        Ok(status) => CheckResult::pass(name, tr_fmt("doctor.reach_ok", &[&status])),
+        Err(e) => CheckResult::fail(
+            name,
+            tr_fmt("doctor.reach_bad", &[&first_line(&e)]),
+            tr("doctor.reach_hint"),
+        ),
+    }
+}
+
+/// 读取系统通知设置：(本程序所用 AUMID 的通知开关, 全局通知开关)，未设置时为 `None`
+#[cfg(windows)]
+fn read_toast_settings() -> (Option<u32>, Option<u32>) {
+    use winreg::RegKey;
+    use winreg::enums::HKEY_CURRENT_USER;
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let read = |path: &str, value: &str| {
+        hkcu.open_subkey(path)
+            .and_then(|key| key.get_value::<u32, _>(value))
+            .ok()
+    };
+
+    (
+        read(
+            &format!(
+                "Software\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings\\{}",
+                notification::AUM_ID
+            ),
+            "Enabled",
+        ),
+        read(
+            "Software\\Microsoft\\Windows\\CurrentVersion\\PushNotifications",
+            "ToastEnabled",
+        ),
+    )
+}
+
+/// 系统通知是否可用（开关值为 0 表示被用户关闭，未设置表示默认开启）
+#[cfg(windows)]
+fn check_toast(app_enabled: Option<u32>, toasts_enabled: Option<u32>) -> CheckResult {
+    let name = tr("doctor.toast");
+    if toasts_enabled == Some(0) {
+        CheckResult::fail(name, tr("doctor.toast_all_off"), tr("doctor.toast_hint"))
+    } else if app_enabled == Some(0) {
+        CheckResult::fail(name, tr("doctor.toast_app_off"), tr("doctor.toast_hint"))
+    } else {
+        CheckResult::pass(name, tr("doctor.toast_ok"))
+    }
+}
+
+/// 桌面通知服务是否在运行（Linux：org.freedesktop.Notifications 的实现名称）
+#[cfg(target_os = "linux")]
+fn check_notification_service(server: anyhow::Result<String>) -> CheckResult {
+    let name = tr("doctor.toast");
+    match server {
+        Ok(server) => CheckResult::pass(name, tr_fmt("doctor.toast_service_ok", &[&server])),
+        Err(e) => CheckResult::fail(
+            name,
+            tr_fmt(
+                "doctor.toast_service_missing",
+                &[&first_line(&format!("{:#}", e))],
+            ),
+            tr("doctor.toast_service_hint"),
+        ),
+    }
+}
+
+/// 错误信息只取首行，保持报告每项一行
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_config() {
+        let (result, network) = check_config(Ok("[app]\nsync_interval = 30\n".to_string()));
+        assert_eq!(result.status, CheckStatus::Pass);
+        assert!(!network.probe_endpoints.is_empty());
+
+        let (result, _) = check_config(Ok("[app\n".to_string()));
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(!result.hint.is_empty());
+
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(check_config(Err(missing)).0.status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_check_self_test() {
+        let passed = self_test::Outcome::Passed { sample: None };
+        assert_eq!(check_self_test(passed).status, CheckStatus::Pass);
+
+        let result = check_self_test(self_test::Outcome::Failed(self_test::Failure::KeyChanged));
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(!result.detail.is_empty());
+        assert!(!result.hint.is_empty());
+    }
+
+    #[test]
+    fn test_check_oauth() {
+        assert_eq!(
+            check_oauth("Gmail", false, true, "hint").status,
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            check_oauth("Outlook", true, false, "hint").status,
+            CheckStatus::Warn
+        );
+
+        let result = check_oauth("Gmail", true, true, "hint");
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert_eq!(result.hint, "hint");
+    }
+
+    #[test]
+    fn test_check_account_secrets() {
+        assert_eq!(
+            check_account_secrets("me@example.com", Ok(())).status,
+            CheckStatus::Pass
+        );
+
+        let result = check_account_secrets(
+            "me@example.com",
+            Err(anyhow::anyhow!("aead::Error\nmore details")),
+        );
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(result.name.contains("me@example.com"));
+        assert!(!result.detail.contains("more details"));
+    }
+
+    #[test]
+    fn test_check_api_usage() {
+        let usage = |last_minute| Usage {
+            last_minute,
+            last_day: 500,
+        };
+
+        let result = check_api_usage("me@example.com", usage(12), 60);
+        assert_eq!(result.status, CheckStatus::Pass);
+        assert!(result.detail.contains("500"));
+
+        let result = check_api_usage("me@example.com", usage(61), 60);
+        assert_eq!(result.status, CheckStatus::Warn);
+        assert!(result.hint.contains("60"));
+    }
+
+    #[test]
+    fn test_check_reachable() {
+        assert_eq!(
+            check_reachable("Gmail API", Ok(404)).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            check_reachable("Gmail API", Err("dns error".to_string())).status,
+            CheckStatus::Fail
+        );
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_check_toast() {
+        assert_eq!(check_toast(None, None).status, CheckStatus::Pass);
+        assert_eq!(check_toast(Some(1), Some(1)).status, CheckStatus::Pass);
+        assert_eq!(check_toast(Some(0), None).status, CheckStatus::Fail);
+        assert_eq!(check_toast(None, Some(0)).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_check_notification_service() {
+        let ok = check_notification_service(Ok("Plasma".to_string()));
+        assert_eq!(ok.status, CheckStatus::Pass);
+        assert!(ok.detail.contains("Plasma"));
+
+        let missing = check_notification_service(Err(anyhow::anyhow!(
+            "org.freedesktop.DBus.Error.ServiceUnknown\nmore"
+        )));
+        assert_eq!(missing.status, CheckStatus::Fail);
+        assert!(!missing.detail.contains("more"));
+    }
+
+    #[test]
+    fn test_format_report() {
+        let results = vec![
+            CheckResult::pass("A", "ok"),
+            CheckResult::fail("B", "broken", "fix it"),
+        ];
+        let report = format_report(&results);
+
+        assert!(report.contains("✓ A: ok"));
+        assert!(report.contains("✗ B: broken\n    → fix it"));
+        assert!(has_failures(&results));
+        assert!(!has_failures(&results[..1]));
+    }
+
+    #[test]
+    fn test_format_engine_status() {
+        let now = Utc::now();
+        let retry_at = now + chrono::Duration::minutes(30);
+        let status = EngineStatus {
+            running: true,
+            paused: false,
+            next_round_at: Some(now + chrono::Duration::seconds(8)),
+            per_account: vec![
+                crate::sync::AccountEngineStatus {
+                    email: "a@example.com".to_string(),
+                    state: AccountEngineState::Synced,
+                    next_attempt_at: None,
+                },
+                crate::sync::AccountEngineStatus {
+                    email: "b@example.com".to_string(),
+                    state: AccountEngineState::Backoff,
+                    next_attempt_at: Some(retry_at),
+                },
+            ],
+        };
+        let text = format_engine_status(&status, now);
+
+        assert!(text.contains(tr("doctor.engine_running")));
+        assert!(text.contains(&tr_fmt(
+            "doctor.engine_next_round",
+            &[&clock_time(now + chrono::Duration::seconds(8)), &8]
+        )));
+        assert!(text.contains(&format!("a@example.com: {}", tr("doctor.engine_synced"))));
+        assert!(text.contains(&format!(
+            "b@example.com: {}",
+            tr_fmt("doctor.engine_backoff", &[&clock_time(retry_at)])
+        )));
+
+        // 已到期的下一轮显示为即将开始；未运行时不显示下一轮
+        let due = EngineStatus {
+            next_round_at: Some(now),
+            ..status.clone()
+        };
+        assert!(format_engine_status(&due, now).contains(tr("doctor.engine_next_round_due")));
+
+        let stopped = format_engine_status(&EngineStatus::default(), now);
+        assert!(stopped.contains(tr("doctor.engine_stopped")));
+        assert!(stopped.contains(tr("doctor.engine_no_accounts")));
+        assert!(!stopped.contains(tr("doctor.engine_next_round_due")));
+    }
+
+    #[test]
+    fn test_format_sync_history() {
+        use crate::sync::history::AccountResult;
+
+        let now = Utc::now();
+        let round = |minutes: i64, outcome| {
+            Arc::new(RoundRecord {
+                started_at: now - chrono::Duration::minutes(minutes),
+                duration_ms: 300,
+                accounts: vec![AccountResult {
+                    email: "a@example.com".to_string(),
+                    outcome,
+                }],
+                unread: 4,
+            })
+        };
+        assert!(format_sync_history(&[], now).is_empty());
+
+        let rounds: Vec<_> = (0..REPORT_HISTORY_ROUNDS as i64 + 2)
+            .map(|minutes| {
+                let outcome = if minutes == 0 {
+                    AccountOutcome::Offline
+                } else {
+                    AccountOutcome::Synced
+                };
+                round(minutes, outcome)
+            })
+            .collect();
+        let text = format_sync_history(&rounds, now);
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            tr_fmt("doctor.history", &[&REPORT_HISTORY_ROUNDS, &rounds.len()])
+        );
+        assert_eq!(lines.len(), REPORT_HISTORY_ROUNDS + 1);
+        assert!(lines[1].trim_start().starts_with('✗'));
+        assert!(lines[1].contains(&format!("a@example.com {}", tr("history.offline"))));
+        assert!(lines[2].trim_start().starts_with('✓'));
+    }
+}
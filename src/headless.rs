@@ -0,0 +1,192 @@
+/// 无界面模式（`--print-counts`）
+///
+/// 不创建窗口和托盘，直接复用同步引擎与邮件服务提供方同步一轮，输出各账户未读数；
+/// 已有实例运行时通过单实例通道读取它缓存的结果，避免两个进程同时同步同一组账户
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, storage};
+use crate::mail::provider::ProviderRegistry;
+use crate::notification;
+use crate::sync::{self, SyncEngine, SyncEvent};
+use crate::tray::summary;
+use crate::utils::http_client::HttpClientFactory;
+use crate::utils::i18n::tr;
+use crate::utils::single_instance::{self, InstanceMessage, InstanceRole};
+
+/// 单个账户的未读数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountCount {
+    /// 邮箱地址
+    pub email: String,
+
+    /// 未读数（同步失败时为上次成功的结果或 0）
+    pub unread: u32,
+
+    /// 同步失败的原因（成功时为 `None`）
+    pub error: Option<String>,
+}
+
+/// 获取各账户未读数：有运行中的实例时读取其缓存结果，否则自行同步一轮
+pub fn collect() -> Result<Vec<AccountCount>> {
+    match single_instance::ensure_single_instance(None) {
+        InstanceRole::Secondary => {
+            let reply = single_instance::request(InstanceMessage::Counts)?;
+            serde_json::from_str(&reply).context("解析运行中实例的回复失败")
+        }
+        // 同步期间持有单实例锁，防止此时启动的界面实例重复同步
+        InstanceRole::Primary(_guard) => sync_once(),
+    }
+}
+
+/// 同步所有账户一轮
+///
+/// 新邮件通知基准照常更新（与界面实例的一轮同步相同），但不发送通知；凭据自检失败时不同步
+fn sync_once() -> Result<Vec<AccountCount>> {
+    if let Some(failure) = config::self_test::run_at_startup() {
+        anyhow::bail!(failure.describe());
+    }
+
+    let cfg = config::load().unwrap_or_default();
+    sync::configure(&cfg);
+    notification::set_enabled(false);
+    HttpClientFactory::init();
+
+    let emails: Vec<String> = storage::load_accounts()?
+        .iter()
+        .map(|account| account.email().to_string())
+        .collect();
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("创建 Tokio 运行时失败")?;
+    let engine = SyncEngine::new(
+        rt.handle().clone(),
+        Arc::new(ProviderRegistry::with_defaults()),
+    );
+
+    let events = Mutex::new(Vec::new());
+    rt.block_on(engine.sync_now(|event| {
+        events.lock().unwrap_or_else(|e| e.into_inner()).push(event);
+    }))?;
+
+    let events = events.into_inner().unwrap_or_else(|e| e.into_inner());
+    Ok(counts_from_events(&emails, events))
+}
+
+/// 由一轮同步的事件得到各账户结果（按账户顺序；没有结果的账户视为失败）
+fn counts_from_events(emails: &[String], events: Vec<SyncEvent>) -> Vec<AccountCount> {
+    let mut counts: Vec<AccountCount> = emails
+        .iter()
+        .map(|email| AccountCount {
+            email: email.clone(),
+            unread: 0,
+            error: Some(tr("counts.not_synced").to_string()),
+        })
+        .collect();
+
+    for event in events {
+        let (email, result) = match event {
+            SyncEvent::Started(_) => continue,
+            SyncEvent::Synced(email, info) => (email, Ok(info.unread())),
+            SyncEvent::Failed(email, failure) => (email, Err(failure.message)),
+            SyncEvent::FailedQuietly(email) => (email, Err(tr("counts.not_synced").to_string())),
+        };
+        let Some(count) = counts.iter_mut().find(|c| c.email == email) else {
+            continue;
+        };
+        match result {
+            Ok(unread) => {
+                count.unread = unread;
+                count.error = None;
+            }
+            Err(message) => count.error = Some(message),
+        }
+    }
+
+    counts
+}
+
+/// 由托盘摘要得到各账户结果（运行中的实例回复查询时使用）
+pub fn from_summary() -> Vec<AccountCount> {
+    summary::snapshot()
+        .into_iter()
+        .map(|status| AccountCount {
+            error: if status.failures > 0 {
+                status
+                    .last_error
+                    .or_else(|| Some(tr("counts.not_synced").to_string()))
+            } else {
+                None
+            },
+            email: status.email,
+            unread: status.unread_count,
+        })
+        .collect()
+}
+
+/// 序列化为 JSON 数组
+pub fn to_json(counts: &[AccountCount]) -> String {
+    serde_json::to_string_pretty(counts).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// 是否有账户同步失败
+pub fn any_failed(counts: &[AccountCount]) -> bool {
+    counts.iter().any(|count| count.error.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::AccountSyncInfo;
+    use crate::mail::error::SyncFailure;
+
+    fn synced(email: &str, unread: u32) -> SyncEvent {
+        SyncEvent::Synced(email.to_string(), AccountSyncInfo::new(email, unread))
+    }
+
+    #[test]
+    fn test_counts_from_events() {
+        let emails = vec![
+            "a@example.com".to_string(),
+            "b@example.com".to_string(),
+            "c@example.com".to_string(),
+        ];
+        let events = vec![
+            SyncEvent::Started("a@example.com".to_string()),
+            synced("a@example.com", 3),
+            SyncEvent::Failed(
+                "b@example.com".to_string(),
+                SyncFailure::from_error(&anyhow::anyhow!("boom")),
+            ),
+        ];
+
+        let counts = counts_from_events(&emails, events);
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[0].unread, 3);
+        assert_eq!(counts[0].error, None);
+        assert_eq!(counts[1].error.as_deref(), Some("boom"));
+        // 没有收到结果的账户视为失败
+        assert!(counts[2].error.is_some());
+        assert!(any_failed(&counts));
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let counts = vec![AccountCount {
+            email: "a@example.com".to_string(),
+            unread: 3,
+            error: None,
+        }];
+
+        let value: serde_json::Value = serde_json::from_str(&to_json(&counts)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([{ "email": "a@example.com", "unread": 3, "error": null }])
+        );
+        assert!(!any_failed(&counts));
+    }
+}
@@ -0,0 +1,234 @@
+/// 同步错误分类
+///
+/// 区分"网络不可用"、"请求超时（网络缓慢）"与"连接失败"，
+/// 以便 UI 对仅仅是超过快速截止时间的请求显示较温和的"网络缓慢"状态。
+/// 与具体邮件服务无关，各提供方共用
+use thiserror::Error;
+
+use super::RetryContext;
+use crate::config::crypto::CredentialError;
+use crate::utils::connectivity;
+
+/// 已分类的同步错误（作为 anyhow 上下文附加到原始错误上）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum SyncError {
+    /// 请求失败且所有探测端点均不可达
+    #[error("网络不可用")]
+    Offline,
+
+    /// 请求超时，但探测端点可达（网络缓慢）
+    #[error("网络缓慢：请求超时")]
+    Timeout,
+
+    /// 连接被拒绝或中断，但探测端点可达
+    #[error("连接失败")]
+    Connection,
+
+    /// 服务器证书无效（自签名、过期或与主机名不符）
+    #[error("服务器证书验证失败")]
+    Certificate,
+
+    /// 服务器拒绝登录（用户名或密码错误）
+    #[error("登录失败：用户名或密码错误")]
+    AuthFailed,
+
+    /// 授权已失效（Refresh Token 过期或被撤销），需要重新添加账户
+    #[error("授权已失效，请重新授权")]
+    ReauthRequired,
+
+    /// 服务端停用了该账户的邮件服务（如 Workspace 管理员关闭了 Gmail），重试无济于事
+    #[error("该账户的 Gmail 已被管理员停用")]
+    AccountUnavailable,
+
+    /// 保存的凭据无法用本机密钥解密（只影响这一个账户），需要重新授权
+    #[error("{0}")]
+    Credentials(CredentialError),
+
+    /// 本轮同步的重试预算已用完（见 [`RetryContext`]），下一轮再试
+    #[error("本轮重试次数已用完，下一轮再试")]
+    Budgeted,
+}
+
+impl SyncError {
+    /// 从 anyhow 错误（含各层上下文）中提取分类（凭据解密失败归为 [`SyncError::Credentials`]）
+    pub fn classify(error: &anyhow::Error) -> Option<Self> {
+        error.downcast_ref::<SyncError>().copied().or_else(|| {
+            error
+                .downcast_ref::<CredentialError>()
+                .copied()
+                .map(Self::Credentials)
+        })
+    }
+}
+
+/// 请求失败后确认网络状态并附加 [`SyncError`] 分类
+///
+/// 传输层失败（连接/超时）且确认网络不可用（最近没有 API 请求成功，主动探测所有端点也失败）时，
+/// 归类为"网络不可用"；网络可用时区分超时（网络缓慢）与连接失败；非传输层错误原样返回（问题出在 API 本身）。
+/// 主动探测计入本轮的重试预算，预算用完时不再探测，归类为 [`SyncError::Budgeted`]
+pub async fn classify_failure(error: anyhow::Error, retry: &RetryContext) -> anyhow::Error {
+    if !connectivity::is_transport_error(&error) {
+        return error;
+    }
+
+    let timed_out = connectivity::is_timeout_error(&error);
+    classify_transport_failure(error, timed_out, retry).await
+}
+
+/// 为已确定是传输层失败的错误附加网络分类
+///
+/// 不经过 reqwest 的协议（如 IMAP）自行判断连接/超时后调用
+pub async fn classify_transport_failure(
+    error: anyhow::Error,
+    timed_out: bool,
+    retry: &RetryContext,
+) -> anyhow::Error {
+    if connectivity::needs_probe()
+        && let Err(budgeted) = retry.spend("探测网络")
+    {
+        return error.context(budgeted);
+    }
+
+    if !connectivity::ensure_network_available().await {
+        error.context(SyncError::Offline)
+    } else if timed_out {
+        error.context(SyncError::Timeout)
+    } else {
+        error.context(SyncError::Connection)
+    }
+}
+
+/// 传递给同步回调的失败信息
+#[derive(Debug, Clone)]
+pub struct SyncFailure {
+    /// 错误描述（用于界面）
+    pub message: String,
+
+    /// 完整的错误链（含各层上下文，用于日志）
+    pub detail: String,
+
+    /// 错误分类（未分类的错误为 `None`）
+    pub kind: Option<SyncError>,
+}
+
+impl SyncFailure {
+    /// 由 anyhow 错误构造
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        let kind = SyncError::classify(error);
+        Self {
+            // 凭据无法解密时直接说明原因，而不是外层的"解密 Refresh Token 失败"
+            message: match kind {
+                Some(kind @ SyncError::Credentials(_)) => kind.to_string(),
+                _ => error.to_string(),
+            },
+            detail: format!("{:#}", error),
+            kind,
+        }
+    }
+
+    /// 邮件服务已被停用、暂不重试的账户使用
+    pub fn account_unavailable() -> Self {
+        Self {
+            message: SyncError::AccountUnavailable.to_string(),
+            detail: SyncError::AccountUnavailable.to_string(),
+            kind: Some(SyncError::AccountUnavailable),
+        }
+    }
+
+    /// 确认无网络连接（重新探测仍失败）、跳过同步时使用
+    pub fn offline(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            detail: message.clone(),
+            message,
+            kind: Some(SyncError::Offline),
+        }
+    }
+
+    /// 错误类型（日志限流用，未分类的错误统一归为一类）
+    pub fn kind_label(&self) -> String {
+        self.kind
+            .map_or_else(|| "未分类错误".to_string(), |kind| kind.to_string())
+    }
+
+    /// 是否为网络不可用
+    pub fn is_offline(&self) -> bool {
+        self.kind == Some(SyncError::Offline)
+    }
+
+    /// 是否仅为网络缓慢（请求超时但网络可达）
+    pub fn is_slow(&self) -> bool {
+        self.kind == Some(SyncError::Timeout)
+    }
+
+    /// 是否为账户的邮件服务已被停用
+    pub fn is_account_unavailable(&self) -> bool {
+        self.kind == Some(SyncError::AccountUnavailable)
+    }
+
+    /// 是否因本轮重试预算用完而放弃
+    pub fn is_budgeted(&self) -> bool {
+        self.kind == Some(SyncError::Budgeted)
+    }
+
+    /// 凭据无法解密的原因（其他错误为 `None`）
+    pub fn credential_error(&self) -> Option<CredentialError> {
+        match self.kind {
+            Some(SyncError::Credentials(reason)) => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SyncFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_classify_from_context() {
+        let error: anyhow::Error = Err::<(), _>(anyhow::anyhow!("operation timed out"))
+            .context("获取未读数失败")
+            .context(SyncError::Timeout)
+            .unwrap_err();
+
+        assert_eq!(SyncError::classify(&error), Some(SyncError::Timeout));
+
+        let failure = SyncFailure::from_error(&error);
+        assert!(failure.is_slow());
+        assert!(!failure.is_offline());
+        assert_eq!(failure.message, "网络缓慢：请求超时");
+    }
+
+    #[test]
+    fn test_classify_credential_error() {
+        let error: anyhow::Error = Err::<(), _>(anyhow::anyhow!("aead::Error"))
+            .context(CredentialError::ForeignMachine)
+            .context("解密 Refresh Token 失败")
+            .unwrap_err();
+
+        assert_eq!(
+            SyncError::classify(&error),
+            Some(SyncError::Credentials(CredentialError::ForeignMachine))
+        );
+        let failure = SyncFailure::from_error(&error);
+        assert_eq!(
+            failure.credential_error(),
+            Some(CredentialError::ForeignMachine)
+        );
+        assert_eq!(failure.message, CredentialError::ForeignMachine.to_string());
+        assert!(failure.detail.contains("解密 Refresh Token 失败"));
+    }
+
+    #[test]
+    fn test_classify_plain_error() {
+        let error = anyhow::anyhow!("Gmail Labels API 返回错误 500");
+        assert_eq!(SyncError::classify(&error), None);
+    }
+}
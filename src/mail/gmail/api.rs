@@ -2,64 +2,27 @@
 ///
 /// 负责调用 Gmail API 获取邮件信息、未读数量以及用户信息（头像、昵称）
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{LazyLock, Mutex};
 
+use crate::config::state::{self, LabelCache, RuntimeState};
+use crate::config::storage::StoredAccount;
+use crate::mail::error::{SyncError, classify_failure};
+use crate::mail::gmail::endpoints::GmailEndpoints;
+use crate::mail::gmail::labels::{self, Label, LabelNotFound, LabelPlan};
+use crate::mail::gmail::people::{self, Header};
 use crate::mail::gmail::types::GmailAccount;
-use crate::utils::{avatar, http_client};
-use std::time::Duration;
-use tokio::time::timeout;
-
-/// 在同步前检测网络可用性并在失败时按指数退避重试
-async fn ensure_network_available() -> Result<bool> {
-    const CHECK_URL: &str = "https://www.google.com/generate_204";
-    const MAX_ATTEMPTS: usize = 4;
-    const PER_REQUEST_TIMEOUT_SECS: u64 = 3;
-
-    let client = http_client::get_client();
-    let mut attempt = 0usize;
-    let mut delay_secs = 1u64;
-    let mut had_failure = false;
-
-    loop {
-        attempt += 1;
-        tracing::debug!("网络检测: 第 {} 次，尝试连接 {}", attempt, CHECK_URL);
-
-        match timeout(
-            Duration::from_secs(PER_REQUEST_TIMEOUT_SECS),
-            client.get(CHECK_URL).send(),
-        )
-        .await
-        {
-            Ok(Ok(resp)) => {
-                // 204 表示连接成功且无内容
-                if resp.status().is_success() {
-                    tracing::debug!("网络检测成功 (HTTP {})", resp.status());
-                    return Ok(had_failure);
-                } else {
-                    tracing::warn!("网络检测返回非成功状态: {}", resp.status());
-                    had_failure = true;
-                }
-            }
-            Ok(Err(e)) => {
-                tracing::warn!("网络检测请求失败: {}", e);
-                had_failure = true;
-            }
-            Err(_) => {
-                tracing::warn!("网络检测超时 ({}s)", PER_REQUEST_TIMEOUT_SECS);
-                had_failure = true;
-            }
-        }
-
-        if attempt >= MAX_ATTEMPTS {
-            tracing::error!("网络不可用：连续 {} 次检测失败", MAX_ATTEMPTS);
-            return Err(anyhow::anyhow!("网络不可用"));
-        }
-
-        tracing::info!("网络检测失败，{} 秒后重试（指数退避）...", delay_secs);
-        tokio::time::sleep(Duration::from_secs(delay_secs)).await;
-        delay_secs = std::cmp::min(delay_secs * 2, 30);
-    }
-}
+use crate::mail::gmail::vacation::{self, VacationSettings};
+use crate::mail::oauth::{OAuthClient, TokenManager};
+use crate::mail::{
+    AccountId, AccountSyncInfo, Counters, PeopleFilter, ProfileInfo, RetryContext, SyncHealth,
+    VacationInfo, metered, quota,
+};
+use crate::utils::redact::{log_bodies_enabled, redact_email};
+use crate::utils::{avatar, connectivity, http_client};
 
 /// Google UserInfo 响应 (OIDC 标准)
 /// 替代了原本分散的 ProfileResponse 和 People API
@@ -83,9 +46,159 @@ struct LabelInfo {
     messages_unread: Option<u32>,
 }
 
+/// labels.list 结果
+#[derive(Debug, Deserialize)]
+struct LabelList {
+    /// 账户的全部标签（没有标签时字段缺失）
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+/// messages.list 单页结果（只选取邮件 ID）
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageListPage {
+    /// 本页邮件（无结果时字段缺失）
+    #[serde(default)]
+    messages: Vec<serde_json::Value>,
+
+    /// 下一页标记
+    next_page_token: Option<String>,
+}
+
+/// messages.get（`format=metadata`）结果中的邮件头
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MessageMetadata {
+    payload: MessagePayload,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MessagePayload {
+    headers: Vec<Header>,
+}
+
+/// Google API 错误响应（`{"error": {...}}`）
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ErrorBody {
+    /// 规范错误码（如 `FAILED_PRECONDITION`）
+    status: String,
+
+    /// 详细原因
+    errors: Vec<ErrorReason>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ErrorReason {
+    /// 原因代码（如 `domainPolicy`）
+    reason: String,
+}
+
+/// 表示账户的 Gmail 服务已被停用的原因代码
+const UNAVAILABLE_REASONS: &[&str] = &[
+    "failedPrecondition",
+    "mailServiceNotEnabled",
+    "accountDisabled",
+    "domainPolicy",
+];
+
+/// 错误响应是否表示账户的 Gmail 服务已被停用（管理员关闭了 Gmail、账户被停用等）
+fn is_account_unavailable(body: &str) -> bool {
+    let Ok(response) = serde_json::from_str::<ErrorResponse>(body) else {
+        return false;
+    };
+    response.error.status == "FAILED_PRECONDITION"
+        || response
+            .error
+            .errors
+            .iter()
+            .any(|e| UNAVAILABLE_REASONS.contains(&e.reason.as_str()))
+}
+
+/// 由非成功响应构造错误（Gmail 服务被停用时附加 [`SyncError::AccountUnavailable`]）
+fn api_error(api: &str, status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+    let error = anyhow::anyhow!("{} 返回错误 {}: {}", api, status, body);
+    if is_account_unavailable(body) {
+        error.context(SyncError::AccountUnavailable)
+    } else {
+        error
+    }
+}
+
+/// messages.list 单页最多返回的邮件数
+const PAGE_SIZE: u32 = 500;
+
+/// 逐页精确计数的默认上限
+pub const DEFAULT_COUNT_CEILING: u32 = 1000;
+
+/// 当前的计数上限（随配置更新）
+static COUNT_CEILING: AtomicU32 = AtomicU32::new(DEFAULT_COUNT_CEILING);
+
+/// 设置逐页计数的上限（至少为 1），之后创建的客户端生效
+pub fn set_count_ceiling(ceiling: u32) {
+    COUNT_CEILING.store(ceiling.max(1), Ordering::Relaxed);
+}
+
+/// 开启垃圾邮件监视的账户每隔多少次同步检查一次 SPAM 标签（额外的请求同样计入 API 用量）
+const SPAM_CHECK_EVERY: u32 = 6;
+
+/// 各账户自上次检查垃圾邮件以来的同步次数
+static SPAM_ROUNDS: LazyLock<Mutex<HashMap<String, u32>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 本次同步是否需要检查垃圾邮件（开启监视后的首次同步立即检查，之后每 `SPAM_CHECK_EVERY` 次一次）
+fn spam_check_due(email: &str) -> bool {
+    let mut rounds = SPAM_ROUNDS.lock().unwrap_or_else(|e| e.into_inner());
+    let round = rounds.entry(email.to_string()).or_insert(0);
+    let due = *round == 0;
+    *round = (*round + 1) % SPAM_CHECK_EVERY;
+    due
+}
+
+/// 关闭监视后重新计数，再次开启时立即检查
+fn reset_spam_rounds(email: &str) {
+    SPAM_ROUNDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(email);
+}
+
+/// 按搜索条件统计的邮件数
+///
+/// 达到计数上限时停止翻页，`exact` 为 false、`value` 为上限，界面应显示为 `1000+`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountResult {
+    /// 是否为精确值
+    pub exact: bool,
+
+    /// 邮件数（不精确时为计数上限）
+    pub value: u32,
+}
+
 /// Gmail API 客户端
 pub struct GmailApiClient {
     access_token: String,
+
+    /// 服务地址
+    endpoints: GmailEndpoints,
+
+    /// 逐页计数的上限
+    count_ceiling: u32,
+
+    /// 请求计入哪个账户的 API 用量（`None` 表示不计数）
+    account: Option<String>,
+
+    /// 保存标签列表缓存的运行时状态（`None` 表示进程内共享的状态）
+    state: Option<&'static RuntimeState>,
 }
 
 impl GmailApiClient {
@@ -94,28 +207,110 @@ impl GmailApiClient {
     /// # Arguments
     /// * `access_token` - 已解密的 Access Token（明文）
     pub fn new(access_token: String) -> Self {
-        Self { access_token }
+        Self {
+            access_token,
+            endpoints: GmailEndpoints::current(),
+            count_ceiling: COUNT_CEILING.load(Ordering::Relaxed),
+            account: None,
+            state: None,
+        }
     }
 
-    /// 获取未读邮件数量
+    /// 使用指定的服务地址创建客户端（如本地模拟服务器）
+    pub fn with_endpoints(access_token: String, endpoints: GmailEndpoints) -> Self {
+        Self {
+            endpoints,
+            ..Self::new(access_token)
+        }
+    }
+
+    /// 把请求计入指定账户的 API 用量
+    pub fn with_account(self, email: String) -> Self {
+        Self {
+            account: Some(email),
+            ..self
+        }
+    }
+
+    /// 使用指定的运行时状态保存标签列表缓存（测试中的内存状态）
+    #[cfg(test)]
+    pub fn with_state(self, state: &'static RuntimeState) -> Self {
+        Self {
+            state: Some(state),
+            ..self
+        }
+    }
+
+    /// 保存标签列表缓存的运行时状态
+    fn state(&self) -> &'static RuntimeState {
+        self.state.unwrap_or_else(state::global)
+    }
+
+    /// 创建 GET 请求（同时计入账户的 API 用量）
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        if let Some(email) = &self.account {
+            quota::record(email);
+        }
+        http_client::get_client().get(url)
+    }
+
+    /// 获取标签中的未读邮件数量
     ///
-    /// 使用 Gmail Labels API 获取 INBOX 标签的 messagesUnread 字段
+    /// 使用 Gmail Labels API 获取标签的 messagesUnread 字段
     /// 这比 messages.list 的 resultSizeEstimate 更精确
     ///
+    /// # Arguments
+    /// * `label` - 标签 ID（如 `INBOX`、`Label_123`）
+    ///
     /// # Returns
     /// 返回未读邮件数量
-    pub async fn get_unread_count(&self) -> Result<u32> {
-        tracing::debug!("正在获取未读邮件数量...");
+    pub async fn get_unread_count(&self, label: &str) -> Result<u32> {
+        tracing::debug!("正在获取 {} 的未读邮件数量...", label);
+
+        // 使用 Labels API 获取标签信息（包含精确的未读数）
+        let messages_unread = self
+            .get_label_unread(&self.endpoints.label_url(label), label)
+            .await?;
+        let unread_count = messages_unread.unwrap_or(0);
 
-        // 使用 Labels API 获取 INBOX 标签信息（包含精确的未读数）
-        let url = "https://gmail.googleapis.com/gmail/v1/users/me/labels/INBOX";
+        tracing::info!(
+            "[DEBUG-UNREAD] {} messagesUnread = {:?}, 最终 unread_count = {}",
+            label,
+            messages_unread,
+            unread_count
+        );
 
-        let response = http_client::get_client()
+        Ok(unread_count)
+    }
+
+    /// 获取垃圾邮件（SPAM 标签）的未读数量
+    pub async fn get_spam_unread_count(&self) -> Result<u32> {
+        tracing::debug!("正在获取垃圾邮件未读数量...");
+        let messages_unread = self
+            .get_label_unread(&self.endpoints.spam_label_url(), "SPAM")
+            .await?;
+        Ok(messages_unread.unwrap_or(0))
+    }
+
+    /// 读取标签的 messagesUnread 字段（`label` 只用于错误信息）
+    #[tracing::instrument(
+        name = "gmail.request",
+        skip_all,
+        fields(
+            gmail.endpoint = "labels",
+            gmail.label = label,
+            http.status = tracing::field::Empty
+        )
+    )]
+    async fn get_label_unread(&self, url: &str, label: &str) -> Result<Option<u32>> {
+        let response = self
             .get(url)
             .bearer_auth(&self.access_token)
+            .timeout(http_client::count_timeout())
             .send()
             .await
-            .context("请求 INBOX 标签信息失败")?;
+            .with_context(|| format!("请求 {} 标签信息失败", label))?;
+        record_status(&response);
 
         if !response.status().is_success() {
             let status = response.status();
@@ -125,25 +320,229 @@ impl GmailApiClient {
                 anyhow::bail!("Token 已过期，需要刷新");
             }
 
-            anyhow::bail!("Gmail Labels API 返回错误 {}: {}", status, error_text);
+            let error = api_error("Gmail Labels API", status, &error_text);
+            if status == 404 {
+                return Err(error.context(LabelNotFound(label.to_string())));
+            }
+            return Err(error);
         }
+        connectivity::record_api_success();
 
-        // 获取原始响应体用于调试
+        // 原始响应体仅在显式开启 NANOMAIL_LOG_BODIES=1 时记录
         let response_text = response.text().await.context("读取响应体失败")?;
-        tracing::info!("[DEBUG-UNREAD] Gmail Labels API 原始响应: {}", response_text);
+        if log_bodies_enabled() {
+            tracing::debug!(
+                "[DEBUG-UNREAD] Gmail Labels API 原始响应: {}",
+                response_text
+            );
+        }
 
         let label_info: LabelInfo =
             serde_json::from_str(&response_text).context("解析标签信息响应失败")?;
 
-        let unread_count = label_info.messages_unread.unwrap_or(0);
+        Ok(label_info.messages_unread)
+    }
 
-        tracing::info!(
-            "[DEBUG-UNREAD] messagesUnread = {:?}, 最终 unread_count = {}",
-            label_info.messages_unread,
-            unread_count
-        );
+    /// 获取标签中指定时间之后收到的未读邮件数量
+    ///
+    /// 按 `is:unread after:<秒级时间戳>` 逐页计数
+    pub async fn get_unread_count_since(
+        &self,
+        label: &str,
+        since: DateTime<Utc>,
+    ) -> Result<CountResult> {
+        tracing::debug!("正在获取 {} 中 {} 之后的未读邮件数量...", label, since);
+        self.count_messages(label, &unread_since_query(since)).await
+    }
 
-        Ok(unread_count)
+    /// 统计标签中符合搜索条件的邮件数
+    ///
+    /// resultSizeEstimate 只是估算值，不能用作徽章数字；这里按 nextPageToken 逐页
+    /// 累计邮件 ID，达到计数上限时立即停止翻页
+    #[tracing::instrument(
+        name = "gmail.request",
+        skip_all,
+        fields(gmail.endpoint = "messages.list", http.status = tracing::field::Empty)
+    )]
+    pub async fn count_messages(&self, label: &str, query: &str) -> Result<CountResult> {
+        let url = self.endpoints.messages_url();
+        let page_size = PAGE_SIZE.to_string();
+        let mut count = 0;
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut request = self
+                .get(&url)
+                .bearer_auth(&self.access_token)
+                .timeout(http_client::count_timeout())
+                .query(&[
+                    ("labelIds", label),
+                    ("q", query),
+                    ("maxResults", page_size.as_str()),
+                    ("fields", "messages/id,nextPageToken"),
+                ]);
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+
+            let response = request.send().await.context("请求邮件列表失败")?;
+            record_status(&response);
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+
+                if status == 401 {
+                    anyhow::bail!("Token 已过期，需要刷新");
+                }
+
+                return Err(api_error("Gmail messages API", status, &error_text));
+            }
+            connectivity::record_api_success();
+
+            let page: MessageListPage = response.json().await.context("解析邮件列表响应失败")?;
+            count += page.messages.len() as u32;
+
+            let more = page.next_page_token.is_some();
+            if let Some(result) = settle_count(count, more, self.count_ceiling) {
+                if !result.exact {
+                    tracing::debug!("邮件数达到计数上限 {}，停止翻页", self.count_ceiling);
+                }
+                return Ok(result);
+            }
+            page_token = page.next_page_token;
+        }
+    }
+
+    /// 列出标签中符合搜索条件的邮件 ID（只取第一页，最多 `limit` 封）
+    #[tracing::instrument(
+        name = "gmail.request",
+        skip_all,
+        fields(gmail.endpoint = "messages.list", http.status = tracing::field::Empty)
+    )]
+    pub async fn list_message_ids(
+        &self,
+        label: &str,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<String>> {
+        let limit = limit.to_string();
+        let response = self
+            .get(&self.endpoints.messages_url())
+            .bearer_auth(&self.access_token)
+            .timeout(http_client::count_timeout())
+            .query(&[
+                ("labelIds", label),
+                ("q", query),
+                ("maxResults", limit.as_str()),
+                ("fields", "messages/id"),
+            ])
+            .send()
+            .await
+            .context("请求邮件列表失败")?;
+        record_status(&response);
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(api_error("Gmail messages API", status, &error_text));
+        }
+        connectivity::record_api_success();
+
+        let page: MessageListPage = response.json().await.context("解析邮件列表响应失败")?;
+        Ok(page
+            .messages
+            .iter()
+            .filter_map(|message| message["id"].as_str().map(str::to_string))
+            .collect())
+    }
+
+    /// 读取邮件的部分邮件头（`format=metadata`，不下载正文）
+    #[tracing::instrument(
+        name = "gmail.request",
+        skip_all,
+        fields(gmail.endpoint = "messages.get", http.status = tracing::field::Empty)
+    )]
+    pub async fn get_message_headers(&self, id: &str, names: &[&str]) -> Result<Vec<Header>> {
+        let mut query = vec![("format", "metadata"), ("fields", "payload/headers")];
+        query.extend(names.iter().map(|name| ("metadataHeaders", *name)));
+        let response = self
+            .get(&self.endpoints.message_url(id))
+            .bearer_auth(&self.access_token)
+            .timeout(http_client::count_timeout())
+            .query(&query)
+            .send()
+            .await
+            .context("请求邮件头失败")?;
+        record_status(&response);
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(api_error("Gmail messages API", status, &error_text));
+        }
+        connectivity::record_api_success();
+
+        let metadata: MessageMetadata = response.json().await.context("解析邮件头响应失败")?;
+        Ok(metadata.payload.headers)
+    }
+
+    /// 列出可供选择的标签（收件箱在前，不含已发送、草稿等系统标签）
+    ///
+    /// 账户的标签列表缓存（见 [`LabelCache`]）未过期时直接返回缓存；`refresh` 为 true 时
+    /// 忽略缓存重新读取。读取成功后更新缓存（未指定账户时不使用缓存）
+    pub async fn list_labels(&self, refresh: bool) -> Result<Vec<Label>> {
+        let Some(email) = &self.account else {
+            return self.fetch_labels().await;
+        };
+        if !refresh && let Some(cached) = labels::cached(self.state(), email, Utc::now()) {
+            tracing::debug!("使用缓存的标签列表: {}", redact_email(email));
+            return Ok(cached);
+        }
+
+        let labels = self.fetch_labels().await?;
+        let cache = LabelCache {
+            fetched_at: Utc::now(),
+            labels: labels.clone(),
+        };
+        if let Err(e) = self
+            .state()
+            .update_account(email, |state| state.labels = Some(cache))
+        {
+            tracing::warn!("保存标签列表缓存失败: {:#}", e);
+        }
+        Ok(labels)
+    }
+
+    /// 请求 labels.list
+    #[tracing::instrument(
+        name = "gmail.request",
+        skip_all,
+        fields(gmail.endpoint = "labels.list", http.status = tracing::field::Empty)
+    )]
+    async fn fetch_labels(&self) -> Result<Vec<Label>> {
+        let response = self
+            .get(&self.endpoints.labels_url())
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .context("请求标签列表失败")?;
+        record_status(&response);
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+
+            if status == 401 {
+                anyhow::bail!("Token 已过期，需要刷新");
+            }
+
+            return Err(api_error("Gmail Labels API", status, &error_text));
+        }
+        connectivity::record_api_success();
+
+        let list: LabelList = response.json().await.context("解析标签列表响应失败")?;
+        Ok(labels::pickable(list.labels))
     }
 
     /// 获取用户信息（包含头像、名字、邮箱）
@@ -153,19 +552,22 @@ impl GmailApiClient {
     ///
     /// # Returns
     /// 返回 GoogleUserInfo 结构体
+    #[tracing::instrument(
+        name = "gmail.request",
+        skip_all,
+        fields(gmail.endpoint = "userinfo", http.status = tracing::field::Empty)
+    )]
     pub async fn get_user_info(&self) -> Result<GoogleUserInfo> {
         tracing::debug!("正在获取用户资料(头像/邮箱)...");
 
-        // Google 标准 OIDC 用户信息端点
         // 需要 scope: "https://www.googleapis.com/auth/userinfo.profile"
-        let url = "https://www.googleapis.com/oauth2/v3/userinfo";
-
-        let response = http_client::get_client()
-            .get(url)
+        let response = self
+            .get(&self.endpoints.userinfo)
             .bearer_auth(&self.access_token)
             .send()
             .await
             .context("请求用户信息失败")?;
+        record_status(&response);
 
         if !response.status().is_success() {
             let status = response.status();
@@ -180,77 +582,249 @@ impl GmailApiClient {
 
             anyhow::bail!("UserInfo API 返回错误 {}: {}", status, error_text);
         }
+        connectivity::record_api_success();
 
         let info: GoogleUserInfo = response.json().await.context("解析用户信息响应失败")?;
 
         tracing::debug!(
             "✅ 获取到用户信息: {} (头像是否存在: {})",
-            info.email,
+            redact_email(&info.email),
             info.picture.is_some()
         );
 
         Ok(info)
     }
+
+    /// 查询当前 Access Token 授予的权限范围（tokeninfo）
+    #[tracing::instrument(
+        name = "gmail.request",
+        skip_all,
+        fields(gmail.endpoint = "tokeninfo", http.status = tracing::field::Empty)
+    )]
+    pub async fn get_granted_scopes(&self) -> Result<Vec<String>> {
+        let response = self
+            .get(&self.endpoints.tokeninfo)
+            .query(&[("access_token", &self.access_token)])
+            .send()
+            .await
+            .context("请求 Token 信息失败")?;
+        record_status(&response);
+
+        if !response.status().is_success() {
+            anyhow::bail!("TokenInfo API 返回错误 {}", response.status());
+        }
+
+        let json: serde_json::Value = response.json().await.context("解析 Token 信息失败")?;
+        Ok(json["scope"]
+            .as_str()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// 读取自动回复设置（settings.getVacation）
+    #[tracing::instrument(
+        name = "gmail.request",
+        skip_all,
+        fields(gmail.endpoint = "settings.vacation", http.status = tracing::field::Empty)
+    )]
+    pub async fn get_vacation(&self) -> Result<VacationSettings> {
+        let response = self
+            .get(&self.endpoints.vacation_url())
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .context("请求自动回复设置失败")?;
+        record_status(&response);
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+
+            if status == 401 {
+                anyhow::bail!("Token 已过期，需要刷新");
+            }
+
+            return Err(api_error("Gmail Settings API", status, &error_text));
+        }
+        connectivity::record_api_success();
+
+        response.json().await.context("解析自动回复设置失败")
+    }
+}
+
+/// 在当前的 `gmail.request` span 上记录响应状态码
+fn record_status(response: &reqwest::Response) {
+    tracing::Span::current().record("http.status", response.status().as_u16());
+}
+
+/// 根据已累计的邮件数判断能否结束计数（需要继续翻页时返回 `None`）
+fn settle_count(count: u32, more: bool, ceiling: u32) -> Option<CountResult> {
+    if count > ceiling || (more && count >= ceiling) {
+        Some(CountResult {
+            exact: false,
+            value: ceiling,
+        })
+    } else if !more {
+        Some(CountResult {
+            exact: true,
+            value: count,
+        })
+    } else {
+        None
+    }
+}
+
+/// 获取标签的未读数（`baseline` 为计数基准时间，不为 `None` 时只统计此后收到的未读邮件）
+async fn label_unread(
+    client: &GmailApiClient,
+    label: &str,
+    baseline: Option<DateTime<Utc>>,
+) -> Result<u32> {
+    let result = match baseline {
+        Some(since) => client
+            .get_unread_count_since(label, since)
+            .await
+            .map(|count| {
+                if !count.exact {
+                    tracing::warn!("{} 的未读邮件超过 {} 封，只统计到上限", label, count.value);
+                }
+                count.value
+            }),
+        None => client.get_unread_count(label).await,
+    };
+
+    // 标签已被删除：缓存的标签列表随之失效，下次打开详情面板时重新读取
+    if let Err(e) = &result
+        && labels::is_not_found(e)
+        && let Some(email) = &client.account
+    {
+        labels::invalidate(client.state(), email);
+    }
+    result
+}
+
+/// 只统计真人来信的未读数（见 [`people`] 模块）
+///
+/// `unread` 为标签的全部未读数，超过 [`people::FILTER_CEILING`] 时不过滤，返回 `None`；
+/// 已判断过的邮件不再读取邮件头
+async fn people_unread(
+    client: &GmailApiClient,
+    email: &str,
+    label: &str,
+    baseline: Option<DateTime<Utc>>,
+    unread: u32,
+) -> Result<Option<u32>> {
+    if unread > people::FILTER_CEILING {
+        tracing::debug!(
+            "{} 的未读邮件超过 {} 封，不按发件人过滤",
+            redact_email(email),
+            people::FILTER_CEILING
+        );
+        return Ok(None);
+    }
+
+    let query = match baseline {
+        Some(since) => unread_since_query(since),
+        None => "is:unread".to_string(),
+    };
+    let ids = client
+        .list_message_ids(label, &query, people::FILTER_CEILING)
+        .await?;
+
+    let mut known = people::classified(email);
+    let mut classified = HashMap::with_capacity(ids.len());
+    for id in &ids {
+        let person = match known.remove(id) {
+            Some(person) => person,
+            None => {
+                let headers = client
+                    .get_message_headers(id, people::METADATA_HEADERS)
+                    .await?;
+                people::classify(&headers)
+            }
+        };
+        classified.insert(id.clone(), person);
+    }
+
+    let count = people::count_people(&ids, &classified);
+    people::remember(email, classified);
+    Ok(Some(count))
+}
+
+/// 构造"指定时间之后的未读邮件"搜索条件（`after:` 接受秒级时间戳）
+fn unread_since_query(since: DateTime<Utc>) -> String {
+    format!("is:unread after:{}", since.timestamp())
 }
 
 /// 下载头像并生成缩略图缓存（48x48），返回本地路径
 ///
 /// 优先使用已缓存的缩略图，避免重复下载
-async fn download_avatar_to_cache(url: &str, email: &str) -> Option<String> {
+#[tracing::instrument(name = "avatar.download", skip_all)]
+async fn download_avatar_to_cache(url: &str, email: &str) -> Result<String, avatar::AvatarError> {
     // 先检查是否已有缓存
     if let Some(cached) = avatar::get_cached_avatar_path(email) {
-        tracing::debug!("使用已缓存的头像: {}", cached);
-        return Some(cached);
+        tracing::debug!("使用已缓存的头像: {}", redact_email(email));
+        return Ok(cached);
     }
 
     // 下载并生成缩略图
-    avatar::download_and_resize_avatar(url, email).await
-}
-
-/// 账户同步信息（包含未读数、头像和错误状态）
-#[derive(Debug, Clone)]
-pub struct AccountSyncInfo {
-    pub email: String,
-    pub unread_count: u32,
-    pub avatar_url: String,
-    pub display_name: String,
-    pub error_message: Option<String>, // 新增：错误消息（如果同步失败）
-    pub network_issue: bool,           // 新增：同步过程中是否曾检测到网络问题（即临时失败）
+    avatar::download_and_resize_avatar(url, email)
+        .await
+        .map(|path| path.display().to_string())
 }
 
 /// 同步账户信息（获取未读数和头像）
 ///
 /// # Arguments
 /// * `account` - Gmail 账户（需要有效的 Token）
+/// * `retry` - 本轮的重试预算（网络探测与 401 后的强制刷新从中扣除）
 ///
 /// # Returns
 /// 返回同步后的账户信息和更新后的账户（如果 Token 被刷新）
 pub async fn sync_account_info(
     account: &GmailAccount,
+    retry: &RetryContext,
 ) -> Result<(AccountSyncInfo, Option<GmailAccount>)> {
-    tracing::info!("🔄 同步账户信息: {}", account.email);
+    let client = super::oauth::client()?;
+    sync_with_endpoints(account, client, GmailEndpoints::current(), retry).await
+}
 
-    // 同步前执行网络检测与重连（若网络不可用则进行重试）。
-    tracing::debug!("同步前执行网络检测...");
-    let had_network_issue = match ensure_network_available().await {
-        Ok(had) => had,
-        Err(e) => {
-            tracing::error!("网络检测最终失败，跳过同步 {}: {}", account.email, e);
-            return Err(e).context("网络检测失败，取消本次同步");
-        }
+/// 同步账户信息的实现
+///
+/// # Arguments
+/// * `oauth_client` - 刷新 Token 使用的 OAuth2 客户端参数
+/// * `endpoints` - Gmail API 与用户信息端点的地址
+/// * `retry` - 本轮的重试预算
+async fn sync_with_endpoints(
+    account: &GmailAccount,
+    oauth_client: OAuthClient,
+    endpoints: GmailEndpoints,
+    retry: &RetryContext,
+) -> Result<(AccountSyncInfo, Option<GmailAccount>)> {
+    tracing::info!("🔄 同步账户信息: {}", redact_email(&account.email));
+
+    let api_client = |access_token: String| {
+        GmailApiClient::with_endpoints(access_token, endpoints.clone())
+            .with_account(account.email.clone())
     };
 
     // 使用 TokenManager 获取有效的 Access Token（自动刷新过期的 Token）
-    let mut token_manager = crate::mail::gmail::token::TokenManager::new(account.clone())
-        .context("创建 TokenManager 失败")?;
+    let mut token_manager = TokenManager::new(account.clone(), oauth_client, StoredAccount::Gmail)
+        .with_retry(retry.clone());
 
-    let access_token = token_manager
+    let access_token = match token_manager
         .get_valid_token()
         .await
-        .context("获取有效 Access Token 失败")?;
+        .context("获取有效 Access Token 失败")
+    {
+        Ok(token) => token,
+        Err(e) => return Err(classify_failure(e, retry).await),
+    };
 
     // 检查 Token 是否被刷新（如果刷新了，需要返回更新后的账户）
-    let updated_account = if token_manager.account().expires_at != account.expires_at {
+    let mut updated_account = if token_manager.account().expires_at != account.expires_at {
         tracing::info!("✅ Token 已自动刷新，更新账户信息");
         Some(token_manager.account().clone())
     } else {
@@ -258,136 +832,241 @@ pub async fn sync_account_info(
     };
 
     // 创建 API 客户端
-    let client = GmailApiClient::new(access_token);
+    let client = api_client(access_token);
 
-    // 获取未读数（并行/先行请求可提升性能，但这里先获取未读数）
-    let unread_count = client.get_unread_count().await.context("获取未读数失败")?;
-
-    // 处理用户信息，失败时降级处理；如果是 401，尝试强制刷新 Token 并重试一次
-    let info_result = client.get_user_info().await;
+    // 获取徽章标签的未读数；通知标签不同时再请求一次（两者相同时只请求一次）
+    // since_added 模式只统计计数基准时间之后的未读邮件
+    let plan = LabelPlan::new(&account.badge_label, &account.notify_label);
+    let baseline = account.unread_baseline();
+    let unread_count = match label_unread(&client, &plan.badge, baseline)
+        .await
+        .context("获取未读数失败")
+    {
+        Ok(count) => count,
+        Err(e) => return Err(classify_failure(e, retry).await),
+    };
+    // 只统计真人来信：逐封判断发件人，未读数超过上限或读取邮件头失败时沿用全部未读数
+    let (unread_count, people_filter) = if account.people_only {
+        match people_unread(&client, &account.email, &plan.badge, baseline, unread_count).await {
+            Ok(Some(count)) => (count, Some(PeopleFilter::Applied)),
+            Ok(None) => (unread_count, Some(PeopleFilter::Unfiltered)),
+            Err(e) => {
+                tracing::debug!("按发件人过滤未读邮件失败，使用全部未读数: {:#}", e);
+                (unread_count, Some(PeopleFilter::Unfiltered))
+            }
+        }
+    } else {
+        people::forget(&account.email);
+        (unread_count, None)
+    };
+    let notify_unread = match &plan.notify {
+        Some(label) => match label_unread(&client, label, baseline)
+            .await
+            .with_context(|| format!("获取通知标签 {} 的未读数失败", label))
+        {
+            Ok(count) => Some(count),
+            Err(e) => return Err(classify_failure(e, retry).await),
+        },
+        None => None,
+    };
 
-    let (email, avatar_url, display_name, error_message) = match info_result {
-        Ok(info) => {
-            // 尝试下载头像到本地缓存，若失败则使用远程 URL
-            let avatar = if let Some(pic_url) = info.picture {
-                match download_avatar_to_cache(&pic_url, &info.email).await {
-                    Some(local_uri) => local_uri,
-                    None => pic_url,
-                }
-            } else {
-                String::new()
-            };
+    // 垃圾邮件每隔几次同步才检查一次，失败不影响本次同步（界面保留上次的值）；
+    // 未开启监视时报告为 0，关闭监视后界面与托盘提示中的记录随之清除
+    let spam_unread = if !account.watch_spam {
+        reset_spam_rounds(&account.email);
+        Some(0)
+    } else if spam_check_due(&account.email) {
+        match client.get_spam_unread_count().await {
+            Ok(count) => Some(count),
+            Err(e) => {
+                tracing::debug!("获取垃圾邮件未读数失败: {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-            (
-                info.email,
-                avatar,
-                info.name.unwrap_or_else(|| account.email.clone()),
-                None,
-            )
+    // 旧版本保存的账户没有记录授权范围：查询后补齐（失败时下次同步再试）
+    if account.scopes.is_empty() {
+        match client.get_granted_scopes().await {
+            Ok(scopes) if !scopes.is_empty() => {
+                tracing::info!("已补齐 {} 的授权范围", redact_email(&account.email));
+                let mut backfilled =
+                    updated_account.unwrap_or_else(|| token_manager.account().clone());
+                backfilled.scopes = scopes;
+                updated_account = Some(backfilled);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::debug!("查询授权范围失败: {:#}", e),
         }
-        Err(e) => {
-            let error_str = e.to_string();
-
-            if error_str.contains("401") {
-                tracing::error!("❌ 获取用户信息失败 [401 Unauthorized]: {}", error_str);
-                tracing::error!("   💡 尝试使用 Refresh Token 刷新 Access Token 并重试");
-
-                // 尝试刷新 Token 并重试一次
-                match token_manager.force_refresh().await {
-                    Ok(_) => {
-                        tracing::info!("✅ 强制刷新 Token 成功，重试 UserInfo 请求");
-                        match token_manager.get_valid_token().await {
-                            Ok(new_token) => {
-                                let new_client = GmailApiClient::new(new_token);
-                                match new_client.get_user_info().await {
-                                    Ok(info2) => {
-                                        // 同样尝试缓存重试获取到的头像
-                                        let avatar2 = if let Some(pic2) = info2.picture {
-                                            match download_avatar_to_cache(&pic2, &info2.email)
-                                                .await
-                                            {
-                                                Some(local_uri2) => local_uri2,
-                                                None => pic2,
-                                            }
-                                        } else {
-                                            String::new()
-                                        };
-
-                                        (
-                                            info2.email,
-                                            avatar2,
-                                            info2.name.unwrap_or_else(|| account.email.clone()),
-                                            None,
-                                        )
-                                    }
-                                    Err(e2) => {
-                                        tracing::error!("❌ 重试 UserInfo 仍失败: {}", e2);
-                                        (
-                                            account.email.clone(),
-                                            String::new(),
-                                            account.email.clone(),
-                                            Some("Token 无效或已过期，请重新授权".to_string()),
-                                        )
+    }
+
+    // 按流量计费的网络下不获取用户信息与头像，界面沿用缓存的资料
+    let reduced = metered::current(account.ignore_metered).is_reduced();
+    let (email, profile, error_message) = if reduced {
+        tracing::debug!("按流量计费的网络，跳过获取用户信息");
+        (account.email.clone(), None, None)
+    } else {
+        // 处理用户信息，失败时降级处理；如果是 401，尝试强制刷新 Token 并重试一次
+        match client.get_user_info().await {
+            Ok(info) => {
+                // 尝试下载头像到本地缓存，若失败则使用远程 URL
+                let avatar = if let Some(pic_url) = info.picture {
+                    match download_avatar_to_cache(&pic_url, &info.email).await {
+                        Ok(local_uri) => local_uri,
+                        Err(e) => {
+                            avatar::warn_failed(&info.email, &e);
+                            pic_url
+                        }
+                    }
+                } else {
+                    String::new()
+                };
+
+                let profile = ProfileInfo {
+                    display_name: info.name.unwrap_or_else(|| account.email.clone()),
+                    avatar_url: avatar,
+                };
+                (info.email, Some(profile), None)
+            }
+            Err(e) => {
+                let error_str = e.to_string();
+
+                if error_str.contains("401") {
+                    tracing::error!("❌ 获取用户信息失败 [401 Unauthorized]: {}", error_str);
+                    tracing::error!("   💡 尝试使用 Refresh Token 刷新 Access Token 并重试");
+
+                    // 尝试刷新 Token 并重试一次
+                    match token_manager.force_refresh().await {
+                        Ok(_) => {
+                            tracing::info!("✅ 强制刷新 Token 成功，重试 UserInfo 请求");
+                            match token_manager.get_valid_token().await {
+                                Ok(new_token) => {
+                                    let new_client = api_client(new_token);
+                                    match new_client.get_user_info().await {
+                                        Ok(info2) => {
+                                            // 同样尝试缓存重试获取到的头像
+                                            let avatar2 = if let Some(pic2) = info2.picture {
+                                                match download_avatar_to_cache(&pic2, &info2.email)
+                                                    .await
+                                                {
+                                                    Ok(local_uri2) => local_uri2,
+                                                    Err(e) => {
+                                                        avatar::warn_failed(&info2.email, &e);
+                                                        pic2
+                                                    }
+                                                }
+                                            } else {
+                                                String::new()
+                                            };
+
+                                            let profile = ProfileInfo {
+                                                display_name: info2
+                                                    .name
+                                                    .unwrap_or_else(|| account.email.clone()),
+                                                avatar_url: avatar2,
+                                            };
+                                            (info2.email, Some(profile), None)
+                                        }
+                                        Err(e2) => {
+                                            tracing::error!("❌ 重试 UserInfo 仍失败: {}", e2);
+                                            (
+                                                account.email.clone(),
+                                                None,
+                                                Some("Token 无效或已过期，请重新授权".to_string()),
+                                            )
+                                        }
                                     }
                                 }
-                            }
-                            Err(e3) => {
-                                tracing::error!("无法获取刷新后的 Access Token: {}", e3);
-                                (
-                                    account.email.clone(),
-                                    String::new(),
-                                    account.email.clone(),
-                                    Some("Token 无效或已过期，请重新授权".to_string()),
-                                )
+                                Err(e3) => {
+                                    tracing::error!("无法获取刷新后的 Access Token: {}", e3);
+                                    (
+                                        account.email.clone(),
+                                        None,
+                                        Some("Token 无效或已过期，请重新授权".to_string()),
+                                    )
+                                }
                             }
                         }
+                        // 本轮重试预算已用完：不再重试，本轮随即结束
+                        Err(refresh_err)
+                            if SyncError::classify(&refresh_err) == Some(SyncError::Budgeted) =>
+                        {
+                            return Err(refresh_err);
+                        }
+                        Err(refresh_err) => {
+                            tracing::error!("强制刷新 Token 失败: {}", refresh_err);
+                            tracing::error!(
+                                "   💡 可能原因:\n   - Refresh Token 已过期或被撤销\n   - 用户撤销了应用授权\n   - 需要用户重新授权，请移除后重新添加账户"
+                            );
+
+                            (
+                                account.email.clone(),
+                                None,
+                                Some("Token 无效或已过期，请重新授权".to_string()),
+                            )
+                        }
                     }
-                    Err(refresh_err) => {
-                        tracing::error!("强制刷新 Token 失败: {}", refresh_err);
-                        tracing::error!(
-                            "   💡 可能原因:\n   - Refresh Token 已过期或被撤销\n   - 用户撤销了应用授权\n   - 需要用户重新授权，请移除后重新添加账户"
-                        );
-
-                        (
-                            account.email.clone(),
-                            String::new(),
-                            account.email.clone(),
-                            Some("Token 无效或已过期，请重新授权".to_string()),
-                        )
-                    }
+                } else {
+                    tracing::warn!("⚠️ 获取用户信息失败 (使用本地缓存): {}", error_str);
+                    (
+                        account.email.clone(),
+                        None,
+                        Some(format!("获取用户信息失败: {}", error_str)),
+                    )
                 }
-            } else {
-                tracing::warn!("⚠️ 获取用户信息失败 (使用本地缓存): {}", error_str);
-                (
-                    account.email.clone(),
-                    String::new(),
-                    account.email.clone(),
-                    Some(format!("获取用户信息失败: {}", error_str)),
-                )
             }
         }
     };
 
     tracing::info!(
         "[DEBUG-UNREAD] sync_account_info 完成: email={}, unread_count={}, error={:?}",
-        email,
+        redact_email(&email),
         unread_count,
         error_message
     );
 
+    // 自动回复每隔几次同步才检查一次，与用户信息一样在按流量计费的网络下跳过，失败不影响本次同步；
+    // 未开启提示时报告为未生效，关闭提示后界面标记随之清除
+    let vacation = if !account.watch_vacation {
+        vacation::reset_rounds(&account.email);
+        Some(VacationInfo::default())
+    } else if reduced || !super::oauth::can_read_settings(account) {
+        None
+    } else if vacation::check_due(&account.email) {
+        match client.get_vacation().await {
+            Ok(settings) => Some(settings.to_info(Utc::now())),
+            Err(e) => {
+                tracing::debug!("获取自动回复设置失败: {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let sync_info = AccountSyncInfo {
-        email: email.clone(),
-        unread_count,
-        avatar_url,
-        display_name,
-        error_message,
-        network_issue: had_network_issue,
+        identity: AccountId { email },
+        counters: Counters {
+            unread: Some(unread_count),
+            notify_unread,
+            spam_unread,
+            people_filter,
+        },
+        profile,
+        health: SyncHealth {
+            error: error_message,
+            snoozed: false,
+        },
+        previews: Vec::new(),
+        vacation,
     };
 
     tracing::info!(
         "[DEBUG-UNREAD] 返回 AccountSyncInfo: email={}, unread_count={}",
-        sync_info.email,
-        sync_info.unread_count
+        redact_email(sync_info.email()),
+        sync_info.unread()
     );
 
     Ok((sync_info, updated_account))
@@ -396,6 +1075,9 @@ pub async fn sync_account_info(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mail::gmail::mock::{MockGmail, MockResponse};
+
+    const MESSAGES_PATH: &str = "/gmail/v1/users/me/messages";
 
     #[test]
     fn test_api_client_creation() {
@@ -403,6 +1085,719 @@ mod tests {
         assert_eq!(client.access_token, "test_token");
     }
 
+    #[test]
+    fn test_unread_since_query_and_page() {
+        let since = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(unread_since_query(since), "is:unread after:1767225600");
+
+        // 无结果时 messages 字段缺失
+        let page: MessageListPage = serde_json::from_str(r#"{"resultSizeEstimate":0}"#).unwrap();
+        assert!(page.messages.is_empty());
+        assert!(page.next_page_token.is_none());
+
+        let page: MessageListPage =
+            serde_json::from_str(r#"{"messages":[{"id":"a"},{"id":"b"}],"nextPageToken":"n"}"#)
+                .unwrap();
+        assert_eq!(page.messages.len(), 2);
+        assert_eq!(page.next_page_token.as_deref(), Some("n"));
+    }
+
+    #[test]
+    fn test_settle_count() {
+        let exact = |value| Some(CountResult { exact: true, value });
+        let capped = |value| {
+            Some(CountResult {
+                exact: false,
+                value,
+            })
+        };
+
+        assert_eq!(settle_count(0, false, 1000), exact(0));
+        assert_eq!(settle_count(500, true, 1000), None);
+        // 恰好等于上限且没有下一页时仍是精确值
+        assert_eq!(settle_count(1000, false, 1000), exact(1000));
+        assert_eq!(settle_count(1000, true, 1000), capped(1000));
+        // 上限不是页大小的整数倍
+        assert_eq!(settle_count(500, false, 300), capped(300));
+    }
+
+    /// 一页 messages.list 结果（`next` 为下一页标记）
+    fn message_page(index: usize, len: u32, next: Option<&str>) -> MockResponse {
+        let ids: Vec<_> = (0..len)
+            .map(|i| serde_json::json!({ "id": format!("{}-{}", index, i) }))
+            .collect();
+        let mut body = serde_json::json!({ "messages": ids });
+        if let Some(next) = next {
+            body["nextPageToken"] = next.into();
+        }
+        MockResponse::json(200, body)
+    }
+
+    fn mock_client(server: &MockGmail, count_ceiling: u32) -> GmailApiClient {
+        GmailApiClient {
+            count_ceiling,
+            ..GmailApiClient::with_endpoints(
+                "test_token".to_string(),
+                GmailEndpoints::with_base_url(server.base_url()),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_count_messages_follows_page_tokens() {
+        let server = MockGmail::start().await;
+        server.on_messages(message_page(0, 500, Some("p1")));
+        server.on_messages(message_page(1, 500, Some("p2")));
+        server.on_messages(message_page(2, 120, None));
+
+        let count = mock_client(&server, 5000)
+            .count_messages("INBOX", "is:unread")
+            .await
+            .unwrap();
+        assert_eq!(
+            count,
+            CountResult {
+                exact: true,
+                value: 1120
+            }
+        );
+
+        let requests = server.requests(MESSAGES_PATH);
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].query.contains("maxResults=500"));
+        assert!(!requests[0].query.contains("pageToken"));
+        assert!(requests[1].query.contains("pageToken=p1"));
+        assert!(requests[2].query.contains("pageToken=p2"));
+    }
+
+    #[tokio::test]
+    async fn test_count_messages_stops_at_ceiling() {
+        let server = MockGmail::start().await;
+        for index in 0..4 {
+            let next = format!("p{}", index + 1);
+            server.on_messages(message_page(index, 500, Some(&next)));
+        }
+
+        let count = mock_client(&server, 1000)
+            .count_messages("INBOX", "is:unread")
+            .await
+            .unwrap();
+        assert_eq!(
+            count,
+            CountResult {
+                exact: false,
+                value: 1000
+            }
+        );
+        // 达到上限后不再请求第三页
+        assert_eq!(server.requests(MESSAGES_PATH).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_labels() {
+        let server = MockGmail::start().await;
+        server.on(
+            "/gmail/v1/users/me/labels",
+            MockResponse::json(
+                200,
+                serde_json::json!({
+                    "labels": [
+                        { "id": "Label_7", "name": "urgent", "type": "user" },
+                        { "id": "DRAFT", "name": "DRAFT", "type": "system" },
+                        { "id": "INBOX", "name": "INBOX", "type": "system" }
+                    ]
+                }),
+            ),
+        );
+
+        let labels = mock_client(&server, 1000).list_labels(false).await.unwrap();
+        let ids: Vec<_> = labels.iter().map(|label| label.id.as_str()).collect();
+        assert_eq!(ids, ["INBOX", "Label_7"]);
+    }
+
+    const LABEL_LIST_PATH: &str = "/gmail/v1/users/me/labels";
+
+    /// 使用独立内存状态、带账户的客户端（标签列表缓存保存在该状态中）
+    fn caching_client(server: &MockGmail) -> (GmailApiClient, &'static RuntimeState) {
+        let state: &'static RuntimeState = Box::leak(Box::new(RuntimeState::in_memory()));
+        let client = mock_client(server, 1000)
+            .with_account("labels@example.com".to_string())
+            .with_state(state);
+        (client, state)
+    }
+
+    fn on_label_list(server: &MockGmail) {
+        server.on(
+            LABEL_LIST_PATH,
+            MockResponse::json(
+                200,
+                serde_json::json!({
+                    "labels": [
+                        { "id": "Label_7", "name": "urgent", "type": "user" },
+                        { "id": "Label_3", "name": "Receipts", "type": "user" },
+                        { "id": "INBOX", "name": "INBOX", "type": "system" }
+                    ]
+                }),
+            ),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_labels_uses_cache() {
+        let server = MockGmail::start().await;
+        on_label_list(&server);
+        let (client, state) = caching_client(&server);
+
+        let first = client.list_labels(false).await.unwrap();
+        let ids: Vec<_> = first.iter().map(|label| label.id.as_str()).collect();
+        assert_eq!(ids, ["INBOX", "Label_3", "Label_7"]);
+        assert_eq!(first[0].kind, "system");
+
+        // 有效期内直接使用缓存
+        assert_eq!(client.list_labels(false).await.unwrap(), first);
+        assert_eq!(server.requests(LABEL_LIST_PATH).len(), 1);
+        let cache = state.account("labels@example.com").labels.unwrap();
+        assert_eq!(cache.labels, first);
+
+        // 刷新按钮忽略缓存
+        client.list_labels(true).await.unwrap();
+        assert_eq!(server.requests(LABEL_LIST_PATH).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_labels_refetches_after_ttl() {
+        let server = MockGmail::start().await;
+        on_label_list(&server);
+        let (client, state) = caching_client(&server);
+
+        let fetched_at = Utc::now() - state::LABEL_CACHE_TTL - chrono::Duration::minutes(1);
+        state
+            .update_account("labels@example.com", |s| {
+                s.labels = Some(LabelCache {
+                    fetched_at,
+                    labels: Vec::new(),
+                })
+            })
+            .unwrap();
+
+        let labels = client.list_labels(false).await.unwrap();
+        assert_eq!(labels.len(), 3);
+        assert_eq!(server.requests(LABEL_LIST_PATH).len(), 1);
+        let cache = state.account("labels@example.com").labels.unwrap();
+        assert!(cache.fetched_at > fetched_at);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_label_invalidates_cache() {
+        let server = MockGmail::start().await;
+        on_label_list(&server);
+        server.on(
+            "/gmail/v1/users/me/labels/Label_7",
+            MockResponse::raw(404, r#"{"error":{"code":404,"status":"NOT_FOUND"}}"#),
+        );
+        let (client, state) = caching_client(&server);
+        client.list_labels(false).await.unwrap();
+
+        let error = label_unread(&client, "Label_7", None).await.unwrap_err();
+        assert!(labels::is_not_found(&error));
+        assert_eq!(state.account("labels@example.com").labels, None);
+
+        // 下次打开标签选择时重新读取
+        client.list_labels(false).await.unwrap();
+        assert_eq!(server.requests(LABEL_LIST_PATH).len(), 2);
+    }
+
+    const MAIL_SERVICE_NOT_ENABLED: &str = include_str!("fixtures/mail_service_not_enabled.json");
+    const ACCOUNT_DISABLED: &str = include_str!("fixtures/account_disabled.json");
+    const DOMAIN_POLICY: &str = include_str!("fixtures/domain_policy.json");
+    const INSUFFICIENT_PERMISSIONS: &str = include_str!("fixtures/insufficient_permissions.json");
+
+    fn classify_body(status: u16, body: &str) -> Option<SyncError> {
+        let status = reqwest::StatusCode::from_u16(status).unwrap();
+        SyncError::classify(&api_error("Gmail Labels API", status, body))
+    }
+
+    #[test]
+    fn test_mail_service_not_enabled_is_unavailable() {
+        assert_eq!(
+            classify_body(400, MAIL_SERVICE_NOT_ENABLED),
+            Some(SyncError::AccountUnavailable)
+        );
+    }
+
+    #[test]
+    fn test_account_disabled_is_unavailable() {
+        assert_eq!(
+            classify_body(403, ACCOUNT_DISABLED),
+            Some(SyncError::AccountUnavailable)
+        );
+    }
+
+    #[test]
+    fn test_domain_policy_is_unavailable() {
+        assert_eq!(
+            classify_body(403, DOMAIN_POLICY),
+            Some(SyncError::AccountUnavailable)
+        );
+    }
+
+    #[test]
+    fn test_other_errors_are_not_unavailable() {
+        // 权限不足需要重新授权，不是服务被停用；无法解析的响应体保持未分类
+        assert_eq!(classify_body(403, INSUFFICIENT_PERMISSIONS), None);
+        assert_eq!(classify_body(500, "<html>Internal Error</html>"), None);
+        assert_eq!(classify_body(400, ""), None);
+    }
+
+    /// 整条同步路径（Token 管理、未读数、用户信息）
+    mod sync_path {
+        use super::*;
+        use crate::mail::error::SyncError;
+        use crate::mail::oauth::RedirectPort;
+
+        const LABELS_PATH: &str = "/gmail/v1/users/me/labels/INBOX";
+        const SPAM_PATH: &str = "/gmail/v1/users/me/labels/SPAM";
+        const USERINFO_PATH: &str = "/oauth2/v3/userinfo";
+
+        /// Access Token 为 `old-token` 的账户；`expired` 时同步前需要先刷新
+        fn account(expired: bool) -> GmailAccount {
+            let expires_in = if expired { -3600 } else { 3600 };
+            GmailAccount::new(
+                "me@example.com".to_string(),
+                "me".to_string(),
+                "old-token".to_string(),
+                "refresh-token".to_string(),
+                expires_in,
+            )
+            .unwrap()
+        }
+
+        fn oauth_client(server: &MockGmail) -> OAuthClient {
+            OAuthClient {
+                client_id: "client-id".to_string(),
+                client_secret: Some("client-secret".to_string()),
+                endpoints: GmailEndpoints::with_base_url(server.base_url()).oauth(),
+                scopes: Vec::new(),
+                include_granted_scopes: true,
+                redirect_port: RedirectPort::Range,
+            }
+        }
+
+        async fn sync(
+            server: &MockGmail,
+            account: &GmailAccount,
+        ) -> Result<(AccountSyncInfo, Option<GmailAccount>)> {
+            sync_within(server, account, &RetryContext::default()).await
+        }
+
+        async fn sync_within(
+            server: &MockGmail,
+            account: &GmailAccount,
+            retry: &RetryContext,
+        ) -> Result<(AccountSyncInfo, Option<GmailAccount>)> {
+            sync_with_endpoints(
+                account,
+                oauth_client(server),
+                GmailEndpoints::with_base_url(server.base_url()),
+                retry,
+            )
+            .await
+        }
+
+        fn userinfo() -> MockResponse {
+            MockResponse::json(
+                200,
+                serde_json::json!({ "email": "me@example.com", "name": "Me" }),
+            )
+        }
+
+        fn new_token() -> MockResponse {
+            MockResponse::json(
+                200,
+                serde_json::json!({
+                    "access_token": "new-token",
+                    "token_type": "Bearer",
+                    "expires_in": 3600
+                }),
+            )
+        }
+
+        #[tokio::test]
+        async fn test_successful_sync() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 7 }),
+            ));
+            server.on_userinfo(userinfo());
+
+            let (info, updated) = sync(&server, &account(false)).await.unwrap();
+            assert_eq!(info.email(), "me@example.com");
+            assert_eq!(
+                info.profile.as_ref().map(|p| p.display_name.as_str()),
+                Some("Me")
+            );
+            assert_eq!(info.counters.unread, Some(7));
+            assert!(!info.has_error());
+            assert!(updated.is_none());
+
+            let labels = server.requests(LABELS_PATH);
+            assert_eq!(labels[0].method, "GET");
+            assert_eq!(labels[0].authorization.as_deref(), Some("Bearer old-token"));
+            assert!(server.requests("/token").is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_sync_backfills_missing_scopes() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 1 }),
+            ));
+            server.on_userinfo(userinfo());
+            server.on(
+                "/tokeninfo",
+                MockResponse::json(
+                    200,
+                    serde_json::json!({
+                        "scope": "openid https://www.googleapis.com/auth/gmail.modify"
+                    }),
+                ),
+            );
+
+            // 旧账户没有记录授权范围：同步时补齐
+            let (_, updated) = sync(&server, &account(false)).await.unwrap();
+            let updated = updated.expect("补齐授权范围后应返回更新后的账户");
+            assert!(updated.has_scope("https://www.googleapis.com/auth/gmail.modify"));
+            assert_eq!(updated.scopes.len(), 2);
+
+            // 已有记录时不再查询
+            let (_, updated) = sync(&server, &updated).await.unwrap();
+            assert!(updated.is_none());
+            assert_eq!(server.requests("/tokeninfo").len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_spam_checked_every_few_syncs() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 1 }),
+            ));
+            server.on_spam_label(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 3 }),
+            ));
+            server.on_userinfo(userinfo());
+
+            // 未开启监视时不请求 SPAM 标签
+            let (info, _) = sync(&server, &account(false)).await.unwrap();
+            assert_eq!(info.counters.spam_unread, Some(0));
+            assert!(server.requests(SPAM_PATH).is_empty());
+
+            // 开启后首次同步立即检查，之后每 SPAM_CHECK_EVERY 次检查一次；垃圾邮件不计入未读数
+            let mut watching = account(false);
+            watching.email = "spam-watch@example.com".to_string();
+            watching.watch_spam = true;
+            let mut checked = Vec::new();
+            for _ in 0..=SPAM_CHECK_EVERY {
+                let (info, _) = sync(&server, &watching).await.unwrap();
+                assert_eq!(info.counters.unread, Some(1));
+                checked.push(info.counters.spam_unread);
+            }
+            assert_eq!(checked[0], Some(3));
+            assert!(
+                checked[1..SPAM_CHECK_EVERY as usize]
+                    .iter()
+                    .all(Option::is_none)
+            );
+            assert_eq!(checked[SPAM_CHECK_EVERY as usize], Some(3));
+            assert_eq!(server.requests(SPAM_PATH).len(), 2);
+        }
+
+        #[tokio::test]
+        async fn test_spam_failure_does_not_fail_sync() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 2 }),
+            ));
+            server.on_spam_label(MockResponse::raw(500, "boom"));
+            server.on_userinfo(userinfo());
+
+            let mut watching = account(false);
+            watching.email = "spam-failure@example.com".to_string();
+            watching.watch_spam = true;
+            let (info, _) = sync(&server, &watching).await.unwrap();
+            assert_eq!(info.counters.unread, Some(2));
+            assert_eq!(info.counters.spam_unread, None);
+        }
+
+        #[tokio::test]
+        async fn test_vacation_checked_when_watched() {
+            const VACATION_PATH: &str = "/gmail/v1/users/me/settings/vacation";
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 1 }),
+            ));
+            server.on_userinfo(userinfo());
+            server.on(
+                VACATION_PATH,
+                MockResponse::json(
+                    200,
+                    serde_json::json!({ "enableAutoReply": true, "responseSubject": "OOO" }),
+                ),
+            );
+
+            // 未开启提示时不读取设置，报告为未生效
+            let (info, _) = sync(&server, &account(false)).await.unwrap();
+            assert_eq!(info.vacation, Some(VacationInfo::default()));
+            assert!(server.requests(VACATION_PATH).is_empty());
+
+            let mut watching = account(false);
+            watching.email = "vacation-watch@example.com".to_string();
+            watching.watch_vacation = true;
+            let (info, _) = sync(&server, &watching).await.unwrap();
+            assert_eq!(
+                info.vacation,
+                Some(VacationInfo {
+                    active: true,
+                    subject: "OOO".to_string(),
+                })
+            );
+            // 下一次同步不在检查周期内
+            let (info, _) = sync(&server, &watching).await.unwrap();
+            assert_eq!(info.vacation, None);
+            assert_eq!(server.requests(VACATION_PATH).len(), 1);
+
+            // 没有读取设置的权限时不检查
+            let mut limited = account(false);
+            limited.email = "vacation-limited@example.com".to_string();
+            limited.watch_vacation = true;
+            limited.scopes = vec!["https://www.googleapis.com/auth/gmail.labels".to_string()];
+            let (info, _) = sync(&server, &limited).await.unwrap();
+            assert_eq!(info.vacation, None);
+            assert_eq!(server.requests(VACATION_PATH).len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_notify_label_requested_only_when_different() {
+            const URGENT_PATH: &str = "/gmail/v1/users/me/labels/Label_7";
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 9 }),
+            ));
+            server.on(
+                URGENT_PATH,
+                MockResponse::json(200, serde_json::json!({ "messagesUnread": 2 })),
+            );
+            server.on_userinfo(userinfo());
+
+            // 两个标签相同：只请求一次
+            let mut same = account(false);
+            same.notify_label = " INBOX ".to_string();
+            let (info, _) = sync(&server, &same).await.unwrap();
+            assert_eq!(info.counters.unread, Some(9));
+            assert_eq!(info.counters.notify_unread, None);
+            assert_eq!(server.requests(LABELS_PATH).len(), 1);
+
+            // 通知标签不同：徽章仍统计收件箱，通知按 Label_7 的未读数
+            let mut urgent = account(false);
+            urgent.notify_label = "Label_7".to_string();
+            let (info, _) = sync(&server, &urgent).await.unwrap();
+            assert_eq!(info.counters.unread, Some(9));
+            assert_eq!(info.counters.notify_unread, Some(2));
+            assert_eq!(server.requests(LABELS_PATH).len(), 2);
+            assert_eq!(server.requests(URGENT_PATH).len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_unauthorized_then_refresh_then_success() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 2 }),
+            ));
+            // UserInfo 第一次返回 401，强制刷新 Token 后重试成功
+            server.on_userinfo(MockResponse::json(
+                401,
+                serde_json::json!({ "error": "invalid_token" }),
+            ));
+            server.on_userinfo(userinfo());
+            server.on_token(new_token());
+
+            let (info, _) = sync(&server, &account(false)).await.unwrap();
+            assert_eq!(info.counters.unread, Some(2));
+            assert_eq!(
+                info.profile.as_ref().map(|p| p.display_name.as_str()),
+                Some("Me")
+            );
+            assert!(!info.has_error());
+
+            let token_requests = server.requests("/token");
+            assert_eq!(token_requests.len(), 1);
+            assert_eq!(token_requests[0].method, "POST");
+            assert!(token_requests[0].body.contains("grant_type=refresh_token"));
+            assert!(
+                token_requests[0]
+                    .body
+                    .contains("refresh_token=refresh-token")
+            );
+
+            let userinfo_requests = server.requests(USERINFO_PATH);
+            assert_eq!(userinfo_requests.len(), 2);
+            assert_eq!(
+                userinfo_requests[1].authorization.as_deref(),
+                Some("Bearer new-token")
+            );
+        }
+
+        #[tokio::test]
+        async fn test_unauthorized_without_budget_ends_round() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 2 }),
+            ));
+            server.on_userinfo(MockResponse::json(
+                401,
+                serde_json::json!({ "error": "invalid_token" }),
+            ));
+
+            // 预算已用完：不再强制刷新重试，直接以 Budgeted 失败
+            let retry = RetryContext::with_budget(0);
+            let error = sync_within(&server, &account(false), &retry)
+                .await
+                .unwrap_err();
+            assert_eq!(SyncError::classify(&error), Some(SyncError::Budgeted));
+            assert!(server.requests("/token").is_empty());
+            assert_eq!(server.requests(USERINFO_PATH).len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_expired_token_is_refreshed_before_sync() {
+            let server = MockGmail::start().await;
+            server.on_token(new_token());
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 1 }),
+            ));
+            server.on_userinfo(userinfo());
+
+            let (_, updated) = sync(&server, &account(true)).await.unwrap();
+            let updated = updated.expect("Token 刷新后应返回更新后的账户");
+            assert_eq!(updated.decrypt_access_token().unwrap(), "new-token");
+            assert_eq!(
+                server.requests(LABELS_PATH)[0].authorization.as_deref(),
+                Some("Bearer new-token")
+            );
+        }
+
+        #[tokio::test]
+        async fn test_sync_emits_phase_spans() {
+            use crate::utils::telemetry::{self, PhaseTimingLayer, testing::SpanRecorder};
+            use tracing::Instrument;
+            use tracing_subscriber::layer::SubscriberExt;
+
+            let server = MockGmail::start().await;
+            server.on_token(new_token());
+            server.on_labels(MockResponse::json(
+                200,
+                serde_json::json!({ "messagesUnread": 3 }),
+            ));
+            server.on_userinfo(userinfo());
+
+            let recorder = SpanRecorder::default();
+            let subscriber = tracing_subscriber::registry()
+                .with(PhaseTimingLayer)
+                .with(recorder.clone());
+            let _guard = tracing::subscriber::set_default(subscriber);
+
+            sync(&server, &account(true))
+                .instrument(tracing::info_span!("sync.round", sync.round = 9001_u64))
+                .await
+                .unwrap();
+
+            let spans = recorder.spans();
+            let request = |endpoint: &str| {
+                spans.iter().find(|span| {
+                    span.name == "gmail.request"
+                        && span.fields.get("gmail.endpoint").map(String::as_str) == Some(endpoint)
+                })
+            };
+            let labels = request("labels").expect("读取未读数应有 gmail.request span");
+            assert_eq!(labels.fields["gmail.label"], "INBOX");
+            assert_eq!(labels.fields["http.status"], "200");
+            assert_eq!(request("userinfo").unwrap().fields["http.status"], "200");
+            assert!(spans.iter().any(|span| span.name == "oauth.refresh"));
+
+            // 一轮结束后按阶段汇总耗时
+            let summary = telemetry::last_round()
+                .filter(|summary| summary.round == 9001)
+                .expect("sync.round 结束后应有汇总");
+            let phases: Vec<&str> = summary.phases.iter().map(|phase| phase.name).collect();
+            assert!(phases.contains(&"oauth.refresh"));
+            assert!(phases.contains(&"gmail.request"));
+        }
+
+        #[tokio::test]
+        async fn test_refresh_failure_requires_reauth() {
+            let server = MockGmail::start().await;
+            server.on_token(MockResponse::json(
+                400,
+                serde_json::json!({
+                    "error": "invalid_grant",
+                    "error_description": "Token has been expired or revoked."
+                }),
+            ));
+
+            let error = sync(&server, &account(true)).await.unwrap_err();
+            assert_eq!(SyncError::classify(&error), Some(SyncError::ReauthRequired));
+            assert!(server.requests(LABELS_PATH).is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_rate_limited() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::json(
+                429,
+                serde_json::json!({ "error": { "code": 429, "status": "RESOURCE_EXHAUSTED" } }),
+            ));
+
+            let error = sync(&server, &account(false)).await.unwrap_err();
+            assert!(format!("{:#}", error).contains("429"));
+            // 限流不是授权问题，不应要求重新授权
+            assert_eq!(SyncError::classify(&error), None);
+            assert!(server.requests(USERINFO_PATH).is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_disabled_mail_service() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::raw(400, MAIL_SERVICE_NOT_ENABLED));
+
+            let error = sync(&server, &account(false)).await.unwrap_err();
+            assert_eq!(
+                SyncError::classify(&error),
+                Some(SyncError::AccountUnavailable)
+            );
+            assert!(server.requests(USERINFO_PATH).is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_malformed_json() {
+            let server = MockGmail::start().await;
+            server.on_labels(MockResponse::raw(200, r#"{"messagesUnread": "#));
+
+            let error = sync(&server, &account(false)).await.unwrap_err();
+            assert!(format!("{:#}", error).contains("解析标签信息响应失败"));
+        }
+    }
+
     #[tokio::test]
     #[ignore] // 需要有效的 Access Token
     async fn test_get_unread_count() {
@@ -410,7 +1805,7 @@ mod tests {
             std::env::var("TEST_ACCESS_TOKEN").expect("请设置 TEST_ACCESS_TOKEN 环境变量");
 
         let client = GmailApiClient::new(access_token);
-        let count = client.get_unread_count().await.unwrap();
+        let count = client.get_unread_count("INBOX").await.unwrap();
 
         println!("未读邮件数: {}", count);
         assert!(count >= 0);
@@ -0,0 +1,257 @@
+/// Gmail 与 Google OAuth2 服务地址
+///
+/// 默认指向线上服务。每个地址都可以通过环境变量或配置文件中的隐藏段 `[gmail_endpoints]`
+/// 覆盖（环境变量优先），用于指向本地模拟服务器或调试代理
+use std::sync::Mutex;
+
+use crate::config::GmailEndpointsConfig;
+use crate::mail::oauth::OAuthEndpoints;
+
+/// 覆盖 Gmail API 地址的环境变量（`.../gmail/v1/users/me`）
+const API_ENV: &str = "NANOMAIL_GMAIL_API_URL";
+
+/// 覆盖授权端点的环境变量
+const AUTH_ENV: &str = "NANOMAIL_GOOGLE_AUTH_URL";
+
+/// 覆盖 Token 端点的环境变量
+const TOKEN_ENV: &str = "NANOMAIL_GOOGLE_TOKEN_URL";
+
+/// 覆盖用户信息端点的环境变量
+const USERINFO_ENV: &str = "NANOMAIL_GOOGLE_USERINFO_URL";
+
+/// 覆盖撤销授权端点的环境变量
+const REVOKE_ENV: &str = "NANOMAIL_GOOGLE_REVOKE_URL";
+
+/// 覆盖 Token 信息端点的环境变量
+const TOKENINFO_ENV: &str = "NANOMAIL_GOOGLE_TOKENINFO_URL";
+
+/// 配置文件中的覆盖（随配置更新）
+static CONFIGURED: Mutex<Option<GmailEndpointsConfig>> = Mutex::new(None);
+
+/// Gmail 相关的服务地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GmailEndpoints {
+    /// Gmail API 地址（当前用户，`.../gmail/v1/users/me`）
+    pub api: String,
+
+    /// 授权端点
+    pub auth: String,
+
+    /// Token 端点
+    pub token: String,
+
+    /// OIDC 用户信息端点
+    pub userinfo: String,
+
+    /// 撤销授权端点
+    pub revoke: String,
+
+    /// Token 信息端点（查询授予的权限范围）
+    pub tokeninfo: String,
+}
+
+impl Default for GmailEndpoints {
+    fn default() -> Self {
+        Self {
+            api: "https://gmail.googleapis.com/gmail/v1/users/me".to_string(),
+            auth: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
+            token: "https://oauth2.googleapis.com/token".to_string(),
+            userinfo: "https://www.googleapis.com/oauth2/v3/userinfo".to_string(),
+            revoke: "https://oauth2.googleapis.com/revoke".to_string(),
+            tokeninfo: "https://oauth2.googleapis.com/tokeninfo".to_string(),
+        }
+    }
+}
+
+impl GmailEndpoints {
+    /// 当前生效的地址：默认值，依次应用配置文件与环境变量中的覆盖
+    pub fn current() -> Self {
+        let configured = CONFIGURED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_default();
+
+        let mut endpoints = Self::default();
+        endpoints.apply(&configured);
+        endpoints.apply(&GmailEndpointsConfig {
+            api: std::env::var(API_ENV).ok(),
+            auth: std::env::var(AUTH_ENV).ok(),
+            token: std::env::var(TOKEN_ENV).ok(),
+            userinfo: std::env::var(USERINFO_ENV).ok(),
+            revoke: std::env::var(REVOKE_ENV).ok(),
+            tokeninfo: std::env::var(TOKENINFO_ENV).ok(),
+        });
+        endpoints
+    }
+
+    /// 所有地址都位于 `base_url` 下（如本地模拟服务器）
+    ///
+    /// Gmail API 与用户信息端点的路径与线上服务相同，OAuth2 端点为 `/auth`、`/token`、`/revoke`、`/tokeninfo`
+    #[cfg(test)]
+    pub fn with_base_url(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Self {
+            api: format!("{}/gmail/v1/users/me", base_url),
+            auth: format!("{}/auth", base_url),
+            token: format!("{}/token", base_url),
+            userinfo: format!("{}/oauth2/v3/userinfo", base_url),
+            revoke: format!("{}/revoke", base_url),
+            tokeninfo: format!("{}/tokeninfo", base_url),
+        }
+    }
+
+    /// labels.list（账户的全部标签）
+    pub fn labels_url(&self) -> String {
+        format!("{}/labels", self.api)
+    }
+
+    /// 指定标签（未读数；标签 ID 按路径段编码）
+    pub fn label_url(&self, id: &str) -> String {
+        let labels = self.labels_url();
+        match reqwest::Url::parse(&labels) {
+            Ok(mut url) => {
+                if let Ok(mut segments) = url.path_segments_mut() {
+                    segments.push(id);
+                }
+                url.into()
+            }
+            Err(_) => format!("{}/{}", labels, id),
+        }
+    }
+
+    /// SPAM 标签（垃圾邮件未读数）
+    pub fn spam_label_url(&self) -> String {
+        self.label_url("SPAM")
+    }
+
+    /// messages.list
+    pub fn messages_url(&self) -> String {
+        format!("{}/messages", self.api)
+    }
+
+    /// messages.get（单封邮件；邮件 ID 只含字母与数字，无需编码）
+    pub fn message_url(&self, id: &str) -> String {
+        format!("{}/{}", self.messages_url(), id)
+    }
+
+    /// 当前用户的 Profile（邮箱地址）
+    pub fn profile_url(&self) -> String {
+        format!("{}/profile", self.api)
+    }
+
+    /// 自动回复设置
+    pub fn vacation_url(&self) -> String {
+        format!("{}/settings/vacation", self.api)
+    }
+
+    /// Google OAuth2 授权与 Token 端点
+    pub fn oauth(&self) -> OAuthEndpoints {
+        OAuthEndpoints {
+            auth: self.auth.clone(),
+            token: self.token.clone(),
+        }
+    }
+
+    /// 用非空的覆盖值替换对应地址（去掉末尾的 `/`）
+    fn apply(&mut self, overrides: &GmailEndpointsConfig) {
+        let fields = [
+            (&mut self.api, &overrides.api),
+            (&mut self.auth, &overrides.auth),
+            (&mut self.token, &overrides.token),
+            (&mut self.userinfo, &overrides.userinfo),
+            (&mut self.revoke, &overrides.revoke),
+            (&mut self.tokeninfo, &overrides.tokeninfo),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                *field = value.trim_end_matches('/').to_string();
+            }
+        }
+    }
+}
+
+/// 应用配置文件中的覆盖，之后创建的客户端生效
+pub fn configure(overrides: &GmailEndpointsConfig) {
+    if !overrides.is_empty() {
+        tracing::info!("使用配置文件中的 Gmail 服务地址: {:?}", overrides);
+    }
+    *CONFIGURED.lock().unwrap_or_else(|e| e.into_inner()) = Some(overrides.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(url: &str) -> String {
+        reqwest::Url::parse(url)
+            .unwrap_or_else(|e| panic!("无法解析地址 {}: {}", url, e))
+            .path()
+            .to_string()
+    }
+
+    #[test]
+    fn test_default_urls_parse_to_expected_paths() {
+        let endpoints = GmailEndpoints::default();
+        assert_eq!(
+            path(&endpoints.label_url("INBOX")),
+            "/gmail/v1/users/me/labels/INBOX"
+        );
+        assert_eq!(path(&endpoints.labels_url()), "/gmail/v1/users/me/labels");
+        assert_eq!(
+            path(&endpoints.spam_label_url()),
+            "/gmail/v1/users/me/labels/SPAM"
+        );
+        assert_eq!(
+            path(&endpoints.messages_url()),
+            "/gmail/v1/users/me/messages"
+        );
+        assert_eq!(path(&endpoints.profile_url()), "/gmail/v1/users/me/profile");
+        assert_eq!(
+            path(&endpoints.vacation_url()),
+            "/gmail/v1/users/me/settings/vacation"
+        );
+        assert_eq!(path(&endpoints.auth), "/o/oauth2/v2/auth");
+        assert_eq!(path(&endpoints.token), "/token");
+        assert_eq!(path(&endpoints.userinfo), "/oauth2/v3/userinfo");
+        assert_eq!(path(&endpoints.revoke), "/revoke");
+        assert_eq!(path(&endpoints.tokeninfo), "/tokeninfo");
+    }
+
+    #[test]
+    fn test_base_url_keeps_api_paths() {
+        let endpoints = GmailEndpoints::with_base_url("http://127.0.0.1:8080/");
+        assert_eq!(
+            endpoints.messages_url(),
+            "http://127.0.0.1:8080/gmail/v1/users/me/messages"
+        );
+        assert_eq!(path(&endpoints.userinfo), "/oauth2/v3/userinfo");
+        assert_eq!(path(&endpoints.token), "/token");
+        assert_eq!(
+            endpoints.label_url("Label_1"),
+            "http://127.0.0.1:8080/gmail/v1/users/me/labels/Label_1"
+        );
+        // 标签 ID 中的特殊字符按路径段编码
+        assert_eq!(
+            path(&endpoints.label_url("a/b c")),
+            "/gmail/v1/users/me/labels/a%2Fb%20c"
+        );
+    }
+
+    #[test]
+    fn test_overrides_replace_only_given_urls() {
+        let mut endpoints = GmailEndpoints::default();
+        endpoints.apply(&GmailEndpointsConfig {
+            api: Some("http://localhost:9000/gmail/v1/users/me/".to_string()),
+            token: Some("  ".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            endpoints.profile_url(),
+            "http://localhost:9000/gmail/v1/users/me/profile"
+        );
+        assert_eq!(endpoints.token, GmailEndpoints::default().token);
+        assert_eq!(endpoints.auth, GmailEndpoints::default().auth);
+    }
+}
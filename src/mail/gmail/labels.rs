@@ -0,0 +1,200 @@
+/// 徽章与通知使用的 Gmail 标签
+///
+/// 每个 Gmail 账户可以分别指定徽章（托盘数字、提示与汇总）和新邮件通知依据的标签，
+/// 默认都是收件箱。两者相同时只请求一次未读数。
+///
+/// 标签选择使用的标签列表缓存在运行时状态中（见 `state::LabelCache`），24 小时内打开详情面板
+/// 不再请求 labels.list；面板中的"刷新标签"按钮重新读取，同步中已设置的标签返回 404
+/// （标签被删除）时清除缓存，下次打开面板时重新读取
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::state::RuntimeState;
+use crate::utils::redact::redact_email;
+
+/// 收件箱标签（徽章与通知的默认标签）
+pub const INBOX: &str = "INBOX";
+
+/// 不适合作为未读提醒的系统标签（已发送、草稿等没有"未读"的意义）
+const HIDDEN_SYSTEM_LABELS: &[&str] = &["SENT", "DRAFT", "TRASH", "SPAM", "CHAT", "UNREAD"];
+
+/// 标签的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelRole {
+    /// 徽章、托盘提示与汇总
+    Badge,
+
+    /// 新邮件通知
+    Notify,
+}
+
+impl LabelRole {
+    /// 解析界面选项（`"badge"` 或 `"notify"`）
+    pub fn from_setting(setting: &str) -> Option<Self> {
+        match setting {
+            "badge" => Some(Self::Badge),
+            "notify" => Some(Self::Notify),
+            _ => None,
+        }
+    }
+}
+
+/// 本次同步需要查询的标签
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelPlan {
+    /// 徽章标签
+    pub badge: String,
+
+    /// 通知标签（与徽章标签相同时为 `None`，只请求一次）
+    pub notify: Option<String>,
+}
+
+impl LabelPlan {
+    /// 由账户设置生成（留空的设置视为收件箱）
+    pub fn new(badge: &str, notify: &str) -> Self {
+        let badge = normalize(badge);
+        let notify = Some(normalize(notify)).filter(|notify| *notify != badge);
+        Self { badge, notify }
+    }
+}
+
+/// 规范化标签 ID（去掉首尾空白，留空时为收件箱）
+pub fn normalize(label: &str) -> String {
+    match label.trim() {
+        "" => INBOX.to_string(),
+        label => label.to_string(),
+    }
+}
+
+/// 已设置的标签不存在（labels.get 返回 404，标签已被删除）
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("标签 {0} 不存在")]
+pub struct LabelNotFound(pub String);
+
+/// 错误是否由标签不存在引起
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| cause.downcast_ref::<LabelNotFound>().is_some())
+}
+
+/// Gmail 标签（labels.list 的一项）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Label {
+    /// 标签 ID（系统标签为 `INBOX` 等，用户标签为 `Label_123`）
+    pub id: String,
+
+    /// 显示名称
+    pub name: String,
+
+    /// `system` 或 `user`
+    #[serde(rename = "type", default)]
+    pub kind: String,
+}
+
+impl Label {
+    /// 是否为系统标签
+    fn is_system(&self) -> bool {
+        self.kind == "system"
+    }
+}
+
+/// 可供选择的标签：收件箱在前，其次是其他系统标签，最后是按名称排序的用户标签
+pub fn pickable(labels: Vec<Label>) -> Vec<Label> {
+    let mut labels: Vec<_> = labels
+        .into_iter()
+        .filter(|label| !(label.is_system() && HIDDEN_SYSTEM_LABELS.contains(&label.id.as_str())))
+        .collect();
+    labels.sort_by(|a, b| {
+        (a.id != INBOX, !a.is_system(), a.name.to_lowercase()).cmp(&(
+            b.id != INBOX,
+            !b.is_system(),
+            b.name.to_lowercase(),
+        ))
+    });
+    labels
+}
+
+/// 账户未过期的标签列表缓存
+pub fn cached(state: &RuntimeState, email: &str, now: DateTime<Utc>) -> Option<Vec<Label>> {
+    state
+        .account(email)
+        .labels
+        .filter(|cache| cache.is_fresh(now))
+        .map(|cache| cache.labels)
+}
+
+/// 清除账户的标签列表缓存
+pub fn invalidate(state: &RuntimeState, email: &str) {
+    if let Err(e) = state.update_account(email, |state| state.labels = None) {
+        tracing::warn!("清除 {} 的标签列表缓存失败: {:#}", redact_email(email), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(id: &str, name: &str, kind: &str) -> Label {
+        Label {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_plan_collapses_same_label() {
+        assert_eq!(
+            LabelPlan::new("INBOX", "INBOX"),
+            LabelPlan {
+                badge: "INBOX".to_string(),
+                notify: None
+            }
+        );
+        // 留空视为收件箱，与收件箱相同时同样只请求一次
+        assert_eq!(LabelPlan::new(" ", "INBOX").notify, None);
+        assert_eq!(LabelPlan::new("Label_1", " Label_1 ").notify, None);
+
+        let plan = LabelPlan::new("INBOX", "Label_7");
+        assert_eq!(plan.badge, "INBOX");
+        assert_eq!(plan.notify.as_deref(), Some("Label_7"));
+    }
+
+    #[test]
+    fn test_pickable_orders_and_hides_labels() {
+        let labels = vec![
+            label("Label_2", "urgent", "user"),
+            label("SENT", "SENT", "system"),
+            label("STARRED", "STARRED", "system"),
+            label("Label_1", "Receipts", "user"),
+            label("INBOX", "INBOX", "system"),
+            label("SPAM", "SPAM", "system"),
+        ];
+        let ids: Vec<_> = pickable(labels).into_iter().map(|l| l.id).collect();
+        assert_eq!(ids, ["INBOX", "STARRED", "Label_1", "Label_2"]);
+    }
+
+    #[test]
+    fn test_label_deserializes_without_type() {
+        let label: Label = serde_json::from_str(r#"{"id":"Label_1","name":"urgent"}"#).unwrap();
+        assert_eq!(label.kind, "");
+    }
+
+    #[test]
+    fn test_label_serializes_type() {
+        let label = label("Label_1", "urgent", "user");
+        let json = serde_json::to_string(&label).unwrap();
+        assert_eq!(json, r#"{"id":"Label_1","name":"urgent","type":"user"}"#);
+        assert_eq!(serde_json::from_str::<Label>(&json).unwrap(), label);
+    }
+
+    #[test]
+    fn test_not_found_survives_context() {
+        let error =
+            anyhow::Error::new(LabelNotFound("Label_7".to_string())).context("获取未读数失败");
+        assert!(is_not_found(&error));
+        assert!(!is_not_found(&anyhow::anyhow!("返回错误 404")));
+    }
+}
@@ -0,0 +1,260 @@
+/// 本地模拟 Gmail 服务（仅测试使用）
+///
+/// 在 127.0.0.1 上监听随机端口，按路径返回预先登记的响应，并记录收到的请求。
+/// 同一路径登记多个响应时依次返回，最后一个会一直重复；未登记的路径返回 404。
+/// 路径与线上服务一致：`/gmail/v1/users/me/...`、`/oauth2/v3/userinfo`、`/token`。
+/// 也可以用 [`MockGmail::on`] 登记任意路径，作为其他出站请求（如新邮件 Webhook）的测试服务器
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 预设响应
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    body: String,
+}
+
+impl MockResponse {
+    /// JSON 响应
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    /// 原样返回的响应体（用于构造格式错误的 JSON）
+    pub fn raw(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+        }
+    }
+}
+
+/// 收到的请求
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// 请求方法
+    pub method: String,
+
+    /// 路径（不含查询参数）
+    pub path: String,
+
+    /// 查询参数（原始字符串）
+    pub query: String,
+
+    /// Authorization 头
+    pub authorization: Option<String>,
+
+    /// 全部请求头（名称与值）
+    pub headers: Vec<(String, String)>,
+
+    /// 请求体
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// 按名称查找请求头（不区分大小写）
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    routes: HashMap<String, VecDeque<MockResponse>>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// 模拟服务器（drop 后停止监听）
+pub struct MockGmail {
+    base_url: String,
+    state: Arc<Mutex<MockState>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl MockGmail {
+    /// 启动模拟服务器
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let server_state = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, server_state.clone()));
+            }
+        });
+
+        Self {
+            base_url,
+            state,
+            server,
+        }
+    }
+
+    /// 服务地址（传给 `GmailEndpoints::with_base_url`）
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Token 端点
+    pub fn token_url(&self) -> String {
+        format!("{}/token", self.base_url)
+    }
+
+    /// 为路径登记一个响应
+    pub fn on(&self, path: &str, response: MockResponse) {
+        self.lock()
+            .routes
+            .entry(path.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// 登记 INBOX 标签响应
+    pub fn on_labels(&self, response: MockResponse) {
+        self.on("/gmail/v1/users/me/labels/INBOX", response);
+    }
+
+    /// 登记 SPAM 标签响应
+    pub fn on_spam_label(&self, response: MockResponse) {
+        self.on("/gmail/v1/users/me/labels/SPAM", response);
+    }
+
+    /// 登记 messages.list 响应
+    pub fn on_messages(&self, response: MockResponse) {
+        self.on("/gmail/v1/users/me/messages", response);
+    }
+
+    /// 登记 UserInfo 响应
+    pub fn on_userinfo(&self, response: MockResponse) {
+        self.on("/oauth2/v3/userinfo", response);
+    }
+
+    /// 登记 Profile 响应
+    pub fn on_profile(&self, response: MockResponse) {
+        self.on("/gmail/v1/users/me/profile", response);
+    }
+
+    /// 登记 Token 端点响应
+    pub fn on_token(&self, response: MockResponse) {
+        self.on("/token", response);
+    }
+
+    /// 指定路径收到的请求
+    pub fn requests(&self, path: &str) -> Vec<RecordedRequest> {
+        self.lock()
+            .requests
+            .iter()
+            .filter(|r| r.path == path)
+            .cloned()
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockGmail {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// 处理一个连接（每个连接只处理一个请求，响应后关闭）
+async fn serve(mut stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        let response = state.routes.get_mut(&request.path).and_then(|queue| {
+            if queue.len() > 1 {
+                queue.pop_front()
+            } else {
+                queue.front().cloned()
+            }
+        });
+        state.requests.push(request);
+        response.unwrap_or_else(|| MockResponse::raw(404, "not found"))
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await.ok();
+    stream.write_all(response.body.as_bytes()).await.ok();
+    stream.shutdown().await.ok();
+}
+
+/// 读取请求行、请求头与请求体
+async fn read_request(stream: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut request_line = head.lines().next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let headers: Vec<(String, String)> = head
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    };
+    let content_length: usize = header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body_end = buf.len().min(header_end + content_length);
+
+    Some(RecordedRequest {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        authorization: header("authorization"),
+        body: String::from_utf8_lossy(&buf[header_end..body_end]).to_string(),
+        headers,
+    })
+}
@@ -1,144 +1,83 @@
 /// Gmail OAuth2 认证流程
 ///
-/// 实现完整的 OAuth2 授权码流程（带 PKCE）
+/// 在通用授权码流程（带 PKCE）之上提供 Google 端点、凭据和用户信息获取
 use anyhow::{Context, Result};
-use oauth2::{
-    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge,
-    PkceCodeVerifier, RedirectUrl, Scope, TokenResponse, TokenUrl, basic::BasicClient,
-};
-use std::time::Duration;
-use tiny_http::{Header, Response, Server};
-use tokio::sync::oneshot;
-use url::Url;
 
+use crate::config::storage::StoredAccount;
 use crate::config::{oauth_config::OAuthConfig, storage};
+use crate::mail::gmail::endpoints::GmailEndpoints;
 use crate::mail::gmail::types::GmailAccount;
+use crate::mail::oauth::{self, AuthOutcome, OAuthClient, RedirectPort};
+use crate::utils::http_client;
+use crate::utils::redact::redact_email;
+
+/// 修改邮件（标记已读、归档）所需的权限范围
+pub const MODIFY_SCOPE: &str = "https://www.googleapis.com/auth/gmail.modify";
+
+/// 完整邮箱权限（包含修改邮件）
+const FULL_ACCESS_SCOPE: &str = "https://mail.google.com/";
+
+/// 可以读取设置（如自动回复）的权限范围（读取、修改邮件或基本设置权限都包含读取设置）
+const SETTINGS_READ_SCOPES: &[&str] = &[
+    "https://www.googleapis.com/auth/gmail.readonly",
+    "https://www.googleapis.com/auth/gmail.settings.basic",
+    MODIFY_SCOPE,
+    FULL_ACCESS_SCOPE,
+];
+
+/// 由 `[oauth]` 配置构造 Google OAuth2 客户端参数
+pub fn client() -> Result<OAuthClient> {
+    let config = OAuthConfig::load().context("加载 OAuth2 配置失败")?;
+    Ok(client_from_config(&config, GmailEndpoints::current()))
+}
+
+fn client_from_config(config: &OAuthConfig, endpoints: GmailEndpoints) -> OAuthClient {
+    OAuthClient {
+        client_id: config.client_id.clone(),
+        client_secret: Some(config.client_secret.clone()),
+        endpoints: endpoints.oauth(),
+        scopes: config.scopes.clone(),
+        // 重新授权时保留此前授予的权限（如修改邮件），不会因配置中的范围较少而降级
+        include_granted_scopes: true,
+        // Token 刷新不使用回调地址；授权时按配置校验后替换
+        redirect_port: RedirectPort::Range,
+    }
+}
 
-/// OAuth2 回调超时时间（秒）
-const CALLBACK_TIMEOUT_SECS: u64 = 60;
-
-/// 本地服务器端口范围
-const PORT_RANGE: std::ops::Range<u16> = 8080..8090;
-
-/// OAuth2 成功页面 HTML
-const SUCCESS_HTML: &str = r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="utf-8">
-    <title>授权成功 - NanoMail</title>
-    <style>
-        body {
-            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Arial, sans-serif;
-            display: flex;
-            justify-content: center;
-            align-items: center;
-            height: 100vh;
-            margin: 0;
-            background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
-        }
-        .container {
-            background: white;
-            padding: 40px;
-            border-radius: 12px;
-            box-shadow: 0 10px 40px rgba(0,0,0,0.2);
-            text-align: center;
-            max-width: 400px;
-        }
-        h1 {
-            color: #667eea;
-            margin-bottom: 20px;
-        }
-        p {
-            color: #666;
-            line-height: 1.6;
-        }
-        .checkmark {
-            font-size: 64px;
-            color: #4caf50;
-        }
-    </style>
-</head>
-<body>
-    <div class="container">
-        <div class="checkmark">✓</div>
-        <h1>授权成功</h1>
-        <p>您的 Gmail 账户已成功连接到 NanoMail。</p>
-        <p>现在可以关闭此页面并返回应用程序。</p>
-    </div>
-</body>
-</html>"#;
-
-/// OAuth2 错误页面 HTML
-const ERROR_HTML: &str = r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="utf-8">
-    <title>授权失败 - NanoMail</title>
-    <style>
-        body {
-            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Arial, sans-serif;
-            display: flex;
-            justify-content: center;
-            align-items: center;
-            height: 100vh;
-            margin: 0;
-            background: linear-gradient(135deg, #f093fb 0%, #f5576c 100%);
-        }
-        .container {
-            background: white;
-            padding: 40px;
-            border-radius: 12px;
-            box-shadow: 0 10px 40px rgba(0,0,0,0.2);
-            text-align: center;
-            max-width: 400px;
-        }
-        h1 {
-            color: #f5576c;
-            margin-bottom: 20px;
-        }
-        p {
-            color: #666;
-            line-height: 1.6;
-        }
-        .cross {
-            font-size: 64px;
-            color: #f44336;
-        }
-    </style>
-</head>
-<body>
-    <div class="container">
-        <div class="cross">✗</div>
-        <h1>授权失败</h1>
-        <p>Gmail 账户连接失败，请稍后重试。</p>
-        <p>如果问题持续，请检查网络连接或联系支持。</p>
-    </div>
-</body>
-</html>"#;
+/// 账户能否修改邮件（没有记录授权范围的旧账户按只读处理，直到重新授权或同步时补齐记录）
+pub fn can_modify(account: &GmailAccount) -> bool {
+    account.has_scope(MODIFY_SCOPE) || account.has_scope(FULL_ACCESS_SCOPE)
+}
+
+/// 账户能否通过 IMAP 登录（XOAUTH2 需要完整邮箱权限；没有记录授权范围的旧账户先尝试登录）
+pub fn can_use_imap(account: &GmailAccount) -> bool {
+    account.scopes.is_empty() || account.has_scope(FULL_ACCESS_SCOPE)
+}
+
+/// 账户能否读取邮箱设置（自动回复提示；没有记录授权范围的旧账户先尝试读取）
+pub fn can_read_settings(account: &GmailAccount) -> bool {
+    account.scopes.is_empty()
+        || SETTINGS_READ_SCOPES
+            .iter()
+            .any(|scope| account.has_scope(scope))
+}
 
 /// 执行 Gmail OAuth2 认证
 ///
-/// 完整的八步流程：
-/// 1. 生成授权 URL
-/// 2. 启动本地服务器
-/// 3. 打开浏览器
-/// 4. 等待回调
-/// 5. 验证 CSRF state
-/// 6. 交换 Token
-/// 7. 获取用户信息
-/// 8. 加密保存
+/// 完整流程：
+/// 1. 加载并校验配置
+/// 2. 浏览器授权并交换 Token（见 `oauth::authorize`）
+/// 3. 获取用户信息
+/// 4. 加密保存
 ///
 /// # Returns
-/// 返回已保存的 Gmail 账户信息
+/// 返回已保存的 Gmail 账户信息；用户在授权页面取消时返回 `None`
 ///
 /// # Errors
 /// - OAuth2 配置无效（占位符）
-/// - 无法启动本地服务器（端口被占用）
-/// - 浏览器打开失败
-/// - 用户拒绝授权
-/// - Token 交换失败
+/// - 授权流程失败（端口被占用、授权超时、Token 交换失败等）
 /// - 网络错误
-pub async fn authenticate() -> Result<GmailAccount> {
+pub async fn authenticate() -> Result<Option<GmailAccount>> {
     tracing::info!("🔐 开始 Gmail OAuth2 认证流程");
 
     // 步骤 1：加载配置
@@ -152,291 +91,57 @@ pub async fn authenticate() -> Result<GmailAccount> {
         );
     }
 
-    // 步骤 2：生成授权 URL
-    let (auth_url, csrf_state, pkce_verifier, port) = build_auth_url(&config)?;
-    tracing::info!("✅ 授权 URL 生成成功");
-    tracing::debug!("授权 URL: {}", auth_url);
-
-    // 步骤 3：启动本地服务器
-    let (code_tx, code_rx) = oneshot::channel();
-    let server_handle = std::thread::spawn(move || start_local_server(port, code_tx));
-    tracing::info!("✅ 本地服务器启动成功: http://localhost:{}", port);
-
-    // 步骤 4：打开浏览器
-    webbrowser::open(auth_url.as_str()).context("无法打开浏览器，请手动复制以下 URL：")?;
-    tracing::info!("✅ 浏览器已打开，等待用户授权...");
-
-    // 步骤 5：等待回调（带超时）
-    let (received_code, received_state) =
-        tokio::time::timeout(Duration::from_secs(CALLBACK_TIMEOUT_SECS), code_rx)
-            .await
-            .context("授权超时：用户未在规定时间内完成授权")?
-            .context("本地服务器接收回调失败")?;
-
-    tracing::info!("✅ 收到授权回调");
-
-    // 等待服务器线程结束
-    server_handle
-        .join()
-        .map_err(|_| anyhow::anyhow!("服务器线程 panic"))?
-        .context("服务器关闭时出错")?;
-
-    // 步骤 6：验证 CSRF state
-    if received_state.secret() != csrf_state.secret() {
-        anyhow::bail!(
-            "CSRF 验证失败：state 不匹配\n期望: {}...\n实际: {}...",
-            &csrf_state.secret()[..8],
-            &received_state.secret()[..8]
-        );
-    }
-    tracing::info!("✅ CSRF 验证通过");
-
-    // 步骤 7：交换 Token
-    tracing::debug!("开始交换 Token，使用 redirect_uri: {}", config.redirect_uri);
-    let token_response = exchange_code_for_token(received_code, pkce_verifier, &config, port)
-        .await
-        .context("Token 交换失败")?;
-
-    let access_token = token_response.access_token().secret().to_string();
-    let refresh_token = token_response
-        .refresh_token()
-        .ok_or_else(|| anyhow::anyhow!("未收到 refresh_token"))?
-        .secret()
-        .to_string();
-
-    let expires_in = token_response
-        .expires_in()
-        .unwrap_or(Duration::from_secs(3600))
-        .as_secs() as i64;
-
-    tracing::info!("✅ Token 交换成功");
-    tracing::debug!(
-        "Access Token: {}...{} (有效期: {} 秒)",
-        &access_token[..5],
-        &access_token[access_token.len() - 5..],
-        expires_in
-    );
-
-    // 步骤 8：获取用户信息
-    let (email, display_name) = fetch_user_info(&access_token)
+    // 自定义回调端口必须与登记的回调地址一致，在打开浏览器之前检查
+    let redirect_port = RedirectPort::from_config(&config.redirect_uri, config.redirect_port)?;
+
+    // 步骤 2：浏览器授权并交换 Token
+    let endpoints = GmailEndpoints::current();
+    let client = OAuthClient {
+        redirect_port,
+        ..client_from_config(&config, endpoints.clone())
+    };
+    let tokens = match oauth::authorize(&client).await? {
+        AuthOutcome::Authorized(tokens) => tokens,
+        AuthOutcome::Declined => return Ok(None),
+    };
+
+    // 步骤 3：获取用户信息
+    let (email, display_name) = fetch_user_info(&endpoints, &tokens.access_token)
         .await
         .context("获取用户信息失败")?;
 
-    tracing::info!("✅ 用户信息获取成功: {}", email);
+    tracing::info!("✅ 用户信息获取成功: {}", redact_email(&email));
 
-    // 步骤 9：创建账户（Token 在创建时自动加密）
-    let account = GmailAccount::new(email, display_name, access_token, refresh_token, expires_in)
-        .context("创建账户失败")?;
+    // 步骤 4：创建账户（Token 在创建时自动加密）
+    let mut account = GmailAccount::new(
+        email,
+        display_name,
+        tokens.access_token,
+        tokens.refresh_token,
+        tokens.expires_in,
+    )
+    .context("创建账户失败")?;
+    account.scopes = tokens.scopes;
 
-    storage::save_account(&account).context("保存账户失败")?;
+    storage::save_account_async(StoredAccount::Gmail(account.clone()))
+        .await
+        .context("保存账户失败")?;
 
     tracing::info!("✅ 账户已保存（Token 已加密）");
     tracing::info!("🎉 OAuth2 认证流程完成");
 
-    Ok(account)
-}
-
-/// 生成授权 URL
-///
-/// 使用 PKCE (RFC 7636) 提升安全性
-fn build_auth_url(config: &OAuthConfig) -> Result<(Url, CsrfToken, PkceCodeVerifier, u16)> {
-    // 尝试端口范围
-    let mut last_error = None;
-    for port in PORT_RANGE {
-        match try_build_auth_url(config, port) {
-            Ok(result) => return Ok(result),
-            Err(e) => last_error = Some(e),
-        }
-    }
-
-    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("所有端口均被占用")))
-}
-
-fn try_build_auth_url(
-    config: &OAuthConfig,
-    port: u16,
-) -> Result<(Url, CsrfToken, PkceCodeVerifier, u16)> {
-    // 构建 OAuth2 客户端
-    let client = BasicClient::new(
-        ClientId::new(config.client_id.clone()),
-        Some(ClientSecret::new(config.client_secret.clone())),
-        AuthUrl::new("https://accounts.google.com/o/oauth2/v2/auth".to_string())?,
-        Some(TokenUrl::new(
-            "https://oauth2.googleapis.com/token".to_string(),
-        )?),
-    )
-    .set_redirect_uri(RedirectUrl::new(format!("http://localhost:{}", port))?);
-
-    // 生成 PKCE 挑战
-    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
-
-    // 生成授权 URL
-    let (auth_url, csrf_state) = client
-        .authorize_url(CsrfToken::new_random)
-        .add_scopes(config.scopes.iter().map(|s| Scope::new(s.clone())))
-        .set_pkce_challenge(pkce_challenge)
-        .url();
-
-    Ok((auth_url, csrf_state, pkce_verifier, port))
-}
-
-/// 启动本地 HTTP 服务器接收 OAuth2 回调
-fn start_local_server(
-    port: u16,
-    code_tx: oneshot::Sender<(AuthorizationCode, CsrfToken)>,
-) -> Result<()> {
-    let addr = format!("127.0.0.1:{}", port);
-    let server = Server::http(&addr)
-        .map_err(|e| anyhow::anyhow!("无法启动本地服务器（端口可能被占用）: {}", e))?;
-
-    tracing::debug!("本地服务器监听: {}", addr);
-
-    for request in server.incoming_requests() {
-        let url_str = format!("http://localhost:{}{}", port, request.url());
-        tracing::debug!("收到请求: {}", url_str);
-
-        let parsed_url = Url::parse(&url_str)?;
-
-        // 解析 query 参数
-        let params: std::collections::HashMap<_, _> =
-            parsed_url.query_pairs().into_owned().collect();
-
-        // 检查是否有错误
-        if let Some(error) = params.get("error") {
-            tracing::error!("用户拒绝授权: {}", error);
-
-            // 返回错误页面
-            let response = Response::from_string(ERROR_HTML).with_header(
-                Header::from_bytes(b"Content-Type", b"text/html; charset=utf-8").unwrap(),
-            );
-            request.respond(response)?;
-
-            return Err(anyhow::anyhow!("用户拒绝授权: {}", error));
-        }
-
-        // 提取 code 和 state
-        let code = params
-            .get("code")
-            .ok_or_else(|| anyhow::anyhow!("回调缺少 code 参数"))?;
-
-        let state = params
-            .get("state")
-            .ok_or_else(|| anyhow::anyhow!("回调缺少 state 参数"))?;
-
-        tracing::debug!("Code: {}...", &code[..10]);
-        tracing::debug!("State: {}...", &state[..10]);
-
-        // 返回成功页面
-        let response = Response::from_string(SUCCESS_HTML)
-            .with_header(Header::from_bytes(b"Content-Type", b"text/html; charset=utf-8").unwrap());
-        request.respond(response)?;
-
-        // 发送结果
-        code_tx
-            .send((
-                AuthorizationCode::new(code.clone()),
-                CsrfToken::new(state.clone()),
-            ))
-            .ok();
-
-        break;
-    }
-
-    Ok(())
-}
-
-/// 交换授权码为 Token
-async fn exchange_code_for_token(
-    code: AuthorizationCode,
-    verifier: PkceCodeVerifier,
-    config: &OAuthConfig,
-    port: u16,
-) -> Result<
-    oauth2::StandardTokenResponse<oauth2::EmptyExtraTokenFields, oauth2::basic::BasicTokenType>,
-> {
-    // 使用实际的 redirect_uri（带端口号）
-    let actual_redirect_uri = format!("http://localhost:{}", port);
-
-    let client = BasicClient::new(
-        ClientId::new(config.client_id.clone()),
-        Some(ClientSecret::new(config.client_secret.clone())),
-        AuthUrl::new("https://accounts.google.com/o/oauth2/v2/auth".to_string())?,
-        Some(TokenUrl::new(
-            "https://oauth2.googleapis.com/token".to_string(),
-        )?),
-    )
-    .set_redirect_uri(RedirectUrl::new(actual_redirect_uri.clone())?);
-
-    tracing::debug!("交换 Token：client_id={}...", &config.client_id[..20]);
-    tracing::debug!("交换 Token：client_id={}...", &config.client_id[..20]);
-
-    // 为了支持重试（不带 client_secret 的 PKCE-only），先把 code/verifier 的字符串保存下来，
-    // 每次重试都重新构造对应对象（AuthorizationCode/ PkceCodeVerifier）
-    let code_secret = code.secret().to_string();
-    let verifier_secret = verifier.secret().to_string();
-
-    // 首次尝试（带 client_secret）
-    let first_code = AuthorizationCode::new(code_secret.clone());
-    let first_verifier = PkceCodeVerifier::new(verifier_secret.clone());
-
-    match client
-        .exchange_code(first_code)
-        .set_pkce_verifier(first_verifier)
-        .request_async(oauth2::reqwest::async_http_client)
-        .await
-    {
-        Ok(tok) => return Ok(tok),
-        Err(e) => {
-            tracing::error!("Token 交换详细错误: {:?}", e);
-
-            let err_str = format!("{:?}", e);
-
-            // 如果是 invalid_client/Unauthorized，尝试不带 client_secret 的 PKCE-only 重试（适配部分 native 客户端配置）
-            if err_str.contains("invalid_client") || err_str.contains("Unauthorized") {
-                tracing::warn!(
-                    "首次交换返回 invalid_client/Unauthorized，尝试使用不带 client_secret 的公共客户端重试（PKCE-only）"
-                );
-
-                let client_public = BasicClient::new(
-                    ClientId::new(config.client_id.clone()),
-                    None,
-                    AuthUrl::new("https://accounts.google.com/o/oauth2/v2/auth".to_string())?,
-                    Some(TokenUrl::new(
-                        "https://oauth2.googleapis.com/token".to_string(),
-                    )?),
-                )
-                .set_redirect_uri(RedirectUrl::new(actual_redirect_uri.clone())?);
-
-                let retry_code = AuthorizationCode::new(code_secret);
-                let retry_verifier = PkceCodeVerifier::new(verifier_secret);
-
-                match client_public
-                    .exchange_code(retry_code)
-                    .set_pkce_verifier(retry_verifier)
-                    .request_async(oauth2::reqwest::async_http_client)
-                    .await
-                {
-                    Ok(tok2) => return Ok(tok2),
-                    Err(e2) => {
-                        tracing::error!("使用 PKCE-only 重试仍失败: {:?}", e2);
-                        return Err(anyhow::anyhow!("Token 交换失败: {}", e2));
-                    }
-                }
-            }
-
-            return Err(anyhow::anyhow!("Token 交换请求失败: {}", e));
-        }
-    }
+    Ok(Some(account))
 }
 
 /// 获取用户信息
 ///
-/// 调用 Gmail API 获取邮箱地址
-async fn fetch_user_info(access_token: &str) -> Result<(String, String)> {
-    let client = reqwest::Client::new();
-
-    let response = client
-        .get("https://gmail.googleapis.com/gmail/v1/users/me/profile")
+/// 调用 Gmail API 的 Profile 端点获取邮箱地址
+async fn fetch_user_info(
+    endpoints: &GmailEndpoints,
+    access_token: &str,
+) -> Result<(String, String)> {
+    let response = http_client::get_client()
+        .get(endpoints.profile_url())
         .bearer_auth(access_token)
         .send()
         .await
@@ -463,20 +168,107 @@ async fn fetch_user_info(access_token: &str) -> Result<(String, String)> {
     Ok((email, display_name))
 }
 
+/// 撤销账户的授权（删除账户后调用）
+///
+/// 尽力而为：令牌已失效或网络不可用时返回错误，由调用方记录日志
+pub async fn revoke(account: &GmailAccount) -> Result<()> {
+    let refresh_token = account
+        .decrypt_refresh_token()
+        .context("解密 Refresh Token 失败")?;
+
+    let response = http_client::oauth_client()
+        .post(GmailEndpoints::current().revoke)
+        .form(&[("token", refresh_token)])
+        .send()
+        .await
+        .context("请求撤销授权失败")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("撤销授权失败: {}", response.status());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_fetch_user_info_from_profile() {
+        use crate::mail::gmail::mock::{MockGmail, MockResponse};
+
+        let server = MockGmail::start().await;
+        server.on_profile(MockResponse::json(
+            200,
+            serde_json::json!({ "emailAddress": "me@example.com" }),
+        ));
+
+        let endpoints = GmailEndpoints::with_base_url(server.base_url());
+        let (email, display_name) = fetch_user_info(&endpoints, "token").await.unwrap();
+        assert_eq!(email, "me@example.com");
+        assert_eq!(display_name, "me");
+        assert_eq!(
+            server.requests("/gmail/v1/users/me/profile")[0]
+                .authorization
+                .as_deref(),
+            Some("Bearer token")
+        );
+    }
+
+    #[test]
+    fn test_client_uses_google_endpoints() {
+        let endpoints = GmailEndpoints::default();
+        let client = client_from_config(&OAuthConfig::default(), endpoints.clone());
+        assert_eq!(client.endpoints.auth, endpoints.auth);
+        assert_eq!(client.endpoints.token, endpoints.token);
+        assert!(client.client_secret.is_some());
+        assert!(client.scopes.iter().any(|s| s == "openid"));
+        assert!(client.include_granted_scopes);
+    }
+
     #[test]
-    fn test_port_range() {
-        assert!(PORT_RANGE.contains(&8080));
-        assert!(PORT_RANGE.contains(&8089));
-        assert!(!PORT_RANGE.contains(&8090));
+    fn test_can_modify_requires_scope() {
+        let mut account = GmailAccount::new(
+            "me@example.com".to_string(),
+            "me".to_string(),
+            "token".to_string(),
+            "refresh".to_string(),
+            3600,
+        )
+        .unwrap();
+        // 没有记录授权范围的旧账户按只读处理
+        assert!(!can_modify(&account));
+
+        account.scopes = vec!["https://www.googleapis.com/auth/gmail.readonly".to_string()];
+        assert!(!can_modify(&account));
+
+        account.scopes.push(MODIFY_SCOPE.to_string());
+        assert!(can_modify(&account));
+
+        account.scopes = vec!["https://mail.google.com/".to_string()];
+        assert!(can_modify(&account));
     }
 
     #[test]
-    fn test_html_contains_charset() {
-        assert!(SUCCESS_HTML.contains("utf-8"));
-        assert!(ERROR_HTML.contains("utf-8"));
+    fn test_can_read_settings() {
+        let mut account = GmailAccount::new(
+            "me@example.com".to_string(),
+            "me".to_string(),
+            "token".to_string(),
+            "refresh".to_string(),
+            3600,
+        )
+        .unwrap();
+        // 没有记录授权范围的旧账户先尝试读取
+        assert!(can_read_settings(&account));
+
+        account.scopes = vec!["https://www.googleapis.com/auth/gmail.metadata".to_string()];
+        assert!(!can_read_settings(&account));
+
+        account.scopes = vec!["https://www.googleapis.com/auth/gmail.readonly".to_string()];
+        assert!(can_read_settings(&account));
+
+        account.scopes = vec!["https://www.googleapis.com/auth/gmail.settings.basic".to_string()];
+        assert!(can_read_settings(&account));
     }
 }
@@ -0,0 +1,268 @@
+/// "只统计真人来信"过滤
+///
+/// 开启后（账户设置 `people_only = true`）徽章只统计看起来由真人发出的未读邮件：
+/// 发件人（From）不匹配自动邮件规则（`[app] automated_sender_patterns`，默认包含 no-reply 等），
+/// 且没有 `List-Unsubscribe`、`List-Id` 等邮件列表头。判断需要逐封读取邮件头，因此只在
+/// 未读数不超过 [`FILTER_CEILING`] 时进行；超过时沿用全部未读数，账户行标注"未过滤"。
+/// 每封邮件的判断结果按邮件 ID 缓存，之后的同步只读取新邮件的邮件头
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, RwLock};
+
+use regex::Regex;
+use serde::Deserialize;
+
+/// 逐封判断的未读数上限（超过时不过滤，避免一次同步发出大量请求）
+pub const FILTER_CEILING: u32 = 50;
+
+/// messages.get 需要读取的邮件头
+pub const METADATA_HEADERS: &[&str] = &["From", "List-Unsubscribe", "List-Id", "Precedence"];
+
+/// 表示群发邮件的 `Precedence` 值
+const BULK_PRECEDENCE: &[&str] = &["bulk", "list", "junk"];
+
+/// 默认的自动邮件发件人规则（正则表达式，匹配完整的 From 头）
+pub fn default_patterns() -> Vec<String> {
+    [
+        r"(?i)no[-_.]?reply",
+        r"(?i)do[-_.]?not[-_.]?reply",
+        r"(?i)\b(mailer-daemon|postmaster)@",
+        r"(?i)\bnotifications?@",
+        r"(?i)\bbounces?[-+.@]",
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+/// 当前的自动邮件发件人规则（随配置更新）
+static PATTERNS: LazyLock<RwLock<Vec<Regex>>> =
+    LazyLock::new(|| RwLock::new(compile(&default_patterns())));
+
+/// 各账户已判断过的邮件（邮件 ID → 是否来自真人）
+static CLASSIFIED: LazyLock<Mutex<HashMap<String, HashMap<String, bool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 设置自动邮件发件人规则（来自配置，无效的表达式记录警告后忽略）
+///
+/// 已缓存的判断结果随之清除，下次同步按新规则重新判断
+pub fn set_patterns(patterns: &[String]) {
+    *PATTERNS.write().unwrap_or_else(|e| e.into_inner()) = compile(patterns);
+    CLASSIFIED.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+fn compile(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                tracing::warn!("忽略无效的自动邮件发件人规则 {:?}: {}", pattern, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// 邮件头（messages.get 的 `payload.headers`）
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+/// 邮件是否来自真人
+///
+/// 有邮件列表头（`List-Unsubscribe`、`List-Id`）、`Precedence` 为群发、没有 From 头，
+/// 或 From 头匹配任一规则时视为自动邮件
+pub fn is_from_person(headers: &[Header], patterns: &[Regex]) -> bool {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    if header("List-Unsubscribe").is_some() || header("List-Id").is_some() {
+        return false;
+    }
+    if header("Precedence").is_some_and(|value| {
+        BULK_PRECEDENCE
+            .iter()
+            .any(|bulk| value.eq_ignore_ascii_case(bulk))
+    }) {
+        return false;
+    }
+    let Some(from) = header("From") else {
+        return false;
+    };
+    !patterns.iter().any(|pattern| pattern.is_match(from))
+}
+
+/// 按当前规则判断（见 [`is_from_person`]）
+pub fn classify(headers: &[Header]) -> bool {
+    is_from_person(headers, &PATTERNS.read().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// 账户已判断过的邮件
+pub fn classified(email: &str) -> HashMap<String, bool> {
+    CLASSIFIED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(email)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// 保存账户本轮的判断结果（只保留当前未读的邮件）
+pub fn remember(email: &str, classified: HashMap<String, bool>) {
+    CLASSIFIED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(email.to_string(), classified);
+}
+
+/// 关闭过滤后清除账户的判断结果
+pub fn forget(email: &str) {
+    CLASSIFIED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(email);
+}
+
+/// 未读邮件中来自真人的数量（`classified` 中没有的邮件不计入）
+pub fn count_people(ids: &[String], classified: &HashMap<String, bool>) -> u32 {
+    ids.iter()
+        .filter(|id| classified.get(*id).copied().unwrap_or(false))
+        .count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<Header> {
+        pairs
+            .iter()
+            .map(|(name, value)| Header {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+            .collect()
+    }
+
+    fn defaults() -> Vec<Regex> {
+        compile(&default_patterns())
+    }
+
+    fn person(pairs: &[(&str, &str)]) -> bool {
+        is_from_person(&headers(pairs), &defaults())
+    }
+
+    #[test]
+    fn test_plain_senders_are_people() {
+        assert!(person(&[("From", "Alice Example <alice@example.com>")]));
+        assert!(person(&[("From", "bob@example.org")]));
+        assert!(person(&[("from", "\"Reply, Carol\" <carol@example.com>")]));
+        // 名字里带 reply 不等于 no-reply
+        assert!(person(&[("From", "Replyn Smith <replyn@example.com>")]));
+        assert!(person(&[(
+            "From",
+            "Dana <dana@notifications-team.example.com>"
+        )]));
+    }
+
+    #[test]
+    fn test_no_reply_senders_are_automated() {
+        for from in [
+            "GitHub <noreply@github.com>",
+            "no-reply@accounts.google.com",
+            "Shop <No_Reply@shop.example>",
+            "Bank <donotreply@bank.example>",
+            "Airline <do-not-reply@air.example>",
+            "Mail Delivery Subsystem <MAILER-DAEMON@googlemail.com>",
+            "postmaster@example.com",
+            "Service <notifications@service.example>",
+            "Tracker <notification@tracker.example>",
+            "bounce+abc123@mailgun.example",
+            "Vendor <bounces.news@vendor.example>",
+        ] {
+            assert!(!person(&[("From", from)]), "{from}");
+        }
+    }
+
+    #[test]
+    fn test_list_headers_mark_automated() {
+        let from = ("From", "Alice <alice@example.com>");
+        assert!(!person(&[
+            from,
+            ("List-Unsubscribe", "<mailto:unsub@example.com>")
+        ]));
+        assert!(!person(&[
+            from,
+            ("list-unsubscribe", "<https://example.com/u?id=1>")
+        ]));
+        assert!(!person(&[
+            from,
+            ("List-Id", "Rust Users <users.rust-lang.org>")
+        ]));
+        assert!(!person(&[from, ("Precedence", "bulk")]));
+        assert!(!person(&[from, ("Precedence", "List")]));
+
+        // 空的列表头与普通的 Precedence 不影响判断
+        assert!(person(&[from, ("List-Unsubscribe", "  ")]));
+        assert!(person(&[from, ("Precedence", "first-class")]));
+    }
+
+    #[test]
+    fn test_missing_from_is_automated() {
+        assert!(!person(&[]));
+        assert!(!person(&[("From", "")]));
+        assert!(!person(&[("Subject", "hello")]));
+    }
+
+    #[test]
+    fn test_custom_patterns_replace_defaults() {
+        let patterns = compile(&[r"(?i)@newsletter\.example\b".to_string()]);
+        let is_person = |from| is_from_person(&headers(&[("From", from)]), &patterns);
+        assert!(!is_person("News <weekly@newsletter.example>"));
+        assert!(is_person("noreply@github.com"));
+
+        // 没有规则时只按邮件列表头判断
+        assert!(is_from_person(&headers(&[("From", "noreply@x.com")]), &[]));
+    }
+
+    #[test]
+    fn test_invalid_patterns_are_skipped() {
+        let patterns = compile(&["(unclosed".to_string(), "(?i)robot@".to_string()]);
+        assert_eq!(patterns.len(), 1);
+        assert!(!is_from_person(
+            &headers(&[("From", "robot@x.com")]),
+            &patterns
+        ));
+    }
+
+    #[test]
+    fn test_headers_from_metadata_response() {
+        let response = r#"{"payload":{"headers":[
+            {"name":"From","value":"Alice <alice@example.com>"},
+            {"name":"List-Id","value":"<team.example.com>"}
+        ]}}"#;
+        let value: serde_json::Value = serde_json::from_str(response).unwrap();
+        let parsed: Vec<Header> =
+            serde_json::from_value(value["payload"]["headers"].clone()).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert!(!is_from_person(&parsed, &defaults()));
+    }
+
+    #[test]
+    fn test_count_people_ignores_unknown_ids() {
+        let ids = ["a", "b", "c", "d"].map(String::from);
+        let classified = HashMap::from([
+            ("a".to_string(), true),
+            ("b".to_string(), false),
+            ("c".to_string(), true),
+        ]);
+        assert_eq!(count_people(&ids, &classified), 2);
+        assert_eq!(count_people(&[], &classified), 0);
+    }
+}
@@ -0,0 +1,55 @@
+/// Gmail 提供方实现
+///
+/// 将现有的 OAuth2 登录与 Gmail API 同步函数包装为 [`MailProvider`]；开启 `push` 的账户
+/// 通过 IMAP IDLE 接收推送（见 `push` 模块）
+use async_trait::async_trait;
+
+use crate::config::storage::StoredAccount;
+use crate::mail::{MailProvider, PushEvent, RetryContext, SyncFailure, SyncOutcome};
+
+/// Gmail 提供方
+pub struct GmailProvider;
+
+#[async_trait]
+impl MailProvider for GmailProvider {
+    fn kind(&self) -> &'static str {
+        "gmail"
+    }
+
+    async fn sync(
+        &self,
+        account: &StoredAccount,
+        retry: &RetryContext,
+    ) -> Result<SyncOutcome, SyncFailure> {
+        let StoredAccount::Gmail(gmail) = account else {
+            return Err(SyncFailure::from_error(&anyhow::anyhow!(
+                "账户类型不匹配: {}",
+                account.kind()
+            )));
+        };
+
+        match super::sync_account_info(gmail, retry).await {
+            Ok((info, updated)) => Ok(SyncOutcome {
+                info,
+                updated_account: updated.map(StoredAccount::Gmail),
+            }),
+            // 失败日志由同步引擎统一记录（对重复的错误限流）
+            Err(e) => Err(SyncFailure::from_error(&e)),
+        }
+    }
+
+    async fn authenticate(&self) -> anyhow::Result<Option<StoredAccount>> {
+        Ok(super::authenticate().await?.map(StoredAccount::Gmail))
+    }
+
+    async fn wait_for_change(&self, account: &StoredAccount) -> anyhow::Result<PushEvent> {
+        match account {
+            StoredAccount::Gmail(gmail) if gmail.push => super::push::wait_for_change(gmail).await,
+            _ => Ok(PushEvent::Unsupported),
+        }
+    }
+
+    fn open_inbox_url(&self, account: &StoredAccount) -> String {
+        super::urls::inbox_url(Some(account.email()))
+    }
+}
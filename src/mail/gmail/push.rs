@@ -0,0 +1,117 @@
+/// Gmail 实时推送（IMAP IDLE）
+///
+/// 账户开启 `push = true` 后，用现有的 Access Token 以 SASL XOAUTH2 登录 imap.gmail.com，
+/// 在收件箱上 IDLE；服务器推送 EXISTS / EXPUNGE 等变化时由同步引擎只同步该账户，
+/// 未读数仍通过 Gmail API 获取，其余流程不变。IMAP 登录需要完整邮箱权限（`https://mail.google.com/`）；
+/// 没有记录授权范围的旧账户先尝试登录，被拒绝时按不支持处理，不反复重试
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+use super::oauth;
+use super::types::GmailAccount;
+use crate::config::storage::StoredAccount;
+use crate::mail::PushEvent;
+use crate::mail::imap::ImapSecurity;
+use crate::mail::imap::client::{ImapError, ImapSession};
+use crate::mail::oauth::TokenManager;
+use crate::utils::redact::redact_email;
+
+/// Gmail 的 IMAP 服务器
+const IMAP_HOST: &str = "imap.gmail.com";
+
+/// Gmail 的 IMAP 端口（直接 TLS）
+const IMAP_PORT: u16 = 993;
+
+/// 推送监听的邮箱
+const INBOX: &str = "INBOX";
+
+/// 单次 IDLE 的最长等待时间（Gmail 在约 30 分钟无活动后断开连接）
+const IDLE_MAX_WAIT: Duration = Duration::from_secs(25 * 60);
+
+/// 单次 IDLE 的最短等待时间（避免 Token 即将到期时反复重连）
+const IDLE_MIN_WAIT: Duration = Duration::from_secs(60);
+
+/// 在 Access Token 到期前多久结束 IDLE（下一次以刷新后的 Token 重新登录）
+const TOKEN_MARGIN: chrono::Duration = chrono::Duration::minutes(5);
+
+/// 通过 IMAP IDLE 等待收件箱变化
+///
+/// 每次等待都重新登录：IMAP 会话不能在已登录状态下重新认证，因此 IDLE 在 Token 到期前结束，
+/// 下一次等待前由 [`TokenManager`] 刷新（刷新后的 Token 同时保存到账户文件）
+pub async fn wait_for_change(account: &GmailAccount) -> Result<PushEvent> {
+    if !oauth::can_use_imap(account) {
+        tracing::info!(
+            "{} 未授予完整邮箱权限（https://mail.google.com/），无法使用 IMAP 推送，仅使用定时轮询",
+            redact_email(&account.email)
+        );
+        return Ok(PushEvent::Unsupported);
+    }
+
+    let mut token_manager =
+        TokenManager::new(account.clone(), oauth::client()?, StoredAccount::Gmail);
+    let access_token = token_manager
+        .get_valid_token()
+        .await
+        .context("获取有效 Access Token 失败")?;
+
+    let mut session = ImapSession::connect_to(IMAP_HOST, IMAP_PORT, ImapSecurity::Tls).await?;
+    if let Err(e) = session
+        .authenticate_xoauth2(&account.email, &access_token)
+        .await
+    {
+        // 登录被拒绝说明 Token 没有 IMAP 权限（或邮箱停用了 IMAP），重试也不会成功
+        if matches!(
+            e.downcast_ref::<ImapError>(),
+            Some(ImapError::LoginRejected(_))
+        ) {
+            tracing::info!(
+                "{} 的 IMAP 登录被拒绝（{}），停止实时推送，仅使用定时轮询",
+                redact_email(&account.email),
+                e
+            );
+            return Ok(PushEvent::Unsupported);
+        }
+        return Err(e);
+    }
+    session.select(INBOX).await?;
+
+    let wait = idle_window(token_manager.account().expires_at, Utc::now());
+    tracing::debug!(
+        "{} 开始 IMAP IDLE（最长 {} 秒）",
+        redact_email(&account.email),
+        wait.as_secs()
+    );
+    let changed = session.idle(wait).await?;
+    session.logout().await;
+
+    Ok(if changed {
+        PushEvent::Changed
+    } else {
+        PushEvent::Timeout
+    })
+}
+
+/// 本次 IDLE 的最长等待时间：在 Access Token 到期前结束，且介于上下限之间
+fn idle_window(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> Duration {
+    (expires_at - TOKEN_MARGIN - now)
+        .to_std()
+        .unwrap_or(Duration::ZERO)
+        .clamp(IDLE_MIN_WAIT, IDLE_MAX_WAIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_window_ends_before_token_expiry() {
+        let now = Utc::now();
+        let minutes = |m| now + chrono::Duration::minutes(m);
+
+        assert_eq!(idle_window(minutes(60), now), IDLE_MAX_WAIT);
+        assert_eq!(idle_window(minutes(15), now), Duration::from_secs(10 * 60));
+        assert_eq!(idle_window(minutes(5), now), IDLE_MIN_WAIT);
+        assert_eq!(idle_window(minutes(-10), now), IDLE_MIN_WAIT);
+    }
+}
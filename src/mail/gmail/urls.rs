@@ -0,0 +1,90 @@
+/// 打开 Gmail 网页版的地址
+///
+/// 浏览器中同时登录了多个 Google 账户时，`/mail/u/0/` 指向的是浏览器中第一个登录的账户，
+/// 不一定是要打开的账户；邮件 ID 也只在所属账户的会话中有效。因此地址总是以
+/// `authuser={邮箱}` 指定账户，只有不知道邮箱时才退回 `/mail/u/0/`
+use url::Url;
+
+/// 指定账户时使用的地址（账户由 `authuser` 参数选择）
+const ACCOUNT_BASE: &str = "https://mail.google.com/mail/u/";
+
+/// 不知道邮箱时使用的地址（浏览器中第一个登录的账户）
+const DEFAULT_BASE: &str = "https://mail.google.com/mail/u/0/";
+
+/// 收件箱
+pub fn inbox_url(email: Option<&str>) -> String {
+    build(email, "inbox")
+}
+
+/// 收件箱中的一封邮件（`message_id` 为 Gmail API 返回的邮件 ID）
+pub fn message_url(email: Option<&str>, message_id: &str) -> String {
+    build(email, &format!("inbox/{}", message_id))
+}
+
+/// 生成地址：邮箱按表单编码写入查询参数（`+`、`@` 与非 ASCII 字符都会编码），视图写入片段
+fn build(email: Option<&str>, view: &str) -> String {
+    let email = email.map(str::trim).filter(|email| !email.is_empty());
+    let base = if email.is_some() {
+        ACCOUNT_BASE
+    } else {
+        DEFAULT_BASE
+    };
+    let mut url = Url::parse(base).expect("Gmail 地址有效");
+    if let Some(email) = email {
+        url.query_pairs_mut().append_pair("authuser", email);
+    }
+    url.set_fragment(Some(view));
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbox_url_selects_account() {
+        assert_eq!(
+            inbox_url(Some("me@gmail.com")),
+            "https://mail.google.com/mail/u/?authuser=me%40gmail.com#inbox"
+        );
+        assert_eq!(inbox_url(None), "https://mail.google.com/mail/u/0/#inbox");
+        assert_eq!(inbox_url(Some(" ")), inbox_url(None));
+    }
+
+    #[test]
+    fn test_plus_addressed_email_is_encoded() {
+        // 未编码的 `+` 在查询参数中会被解析为空格，选中的就不是这个账户
+        assert_eq!(
+            inbox_url(Some("me+news@gmail.com")),
+            "https://mail.google.com/mail/u/?authuser=me%2Bnews%40gmail.com#inbox"
+        );
+    }
+
+    #[test]
+    fn test_unicode_local_part_is_encoded() {
+        let url = inbox_url(Some("josé@example.com"));
+        assert_eq!(
+            url,
+            "https://mail.google.com/mail/u/?authuser=jos%C3%A9%40example.com#inbox"
+        );
+
+        let parsed = Url::parse(&url).unwrap();
+        let authuser = parsed
+            .query_pairs()
+            .find(|(key, _)| key == "authuser")
+            .map(|(_, value)| value.into_owned());
+        assert_eq!(authuser.as_deref(), Some("josé@example.com"));
+    }
+
+    #[test]
+    fn test_message_url() {
+        assert_eq!(
+            message_url(Some("me+work@gmail.com"), "18c2f0a9b1d3e4f5"),
+            "https://mail.google.com/mail/u/?authuser=me%2Bwork%40gmail.com#inbox/18c2f0a9b1d3e4f5"
+        );
+        assert_eq!(
+            message_url(None, "18c2f0a9b1d3e4f5"),
+            "https://mail.google.com/mail/u/0/#inbox/18c2f0a9b1d3e4f5"
+        );
+    }
+}
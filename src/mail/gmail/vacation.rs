@@ -0,0 +1,140 @@
+/// 自动回复（休假回复）提示
+///
+/// 开启后（账户设置 `watch_vacation = true`）每隔 [`CHECK_EVERY`] 次同步读取一次
+/// `users/me/settings/vacation`，自动回复生效时账户行显示 🌴，悬停显示回复主题，便于一眼看出
+/// 共享或团队邮箱正在自动回复。读取设置需要读取邮件或设置的权限（见 `oauth::can_read_settings`），
+/// 没有时不检查；与用户资料一样，按流量计费的网络下跳过。读取失败不影响同步，界面保留上次的状态
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::mail::VacationInfo;
+
+/// 每隔几次同步检查一次自动回复（设置很少变化）
+pub const CHECK_EVERY: u32 = 30;
+
+/// 各账户自上次检查自动回复以来的同步次数
+static ROUNDS: LazyLock<Mutex<HashMap<String, u32>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// settings.getVacation 的响应（起止时间为毫秒时间戳字符串，未设置时不限）
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct VacationSettings {
+    /// 是否开启自动回复
+    pub enable_auto_reply: bool,
+
+    /// 回复主题
+    pub response_subject: Option<String>,
+
+    /// 开始时间
+    pub start_time: Option<String>,
+
+    /// 结束时间
+    pub end_time: Option<String>,
+}
+
+impl VacationSettings {
+    /// 自动回复当前是否生效（已开启且在起止时间内）
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.enable_auto_reply
+            && millis(&self.start_time).is_none_or(|start| start <= now)
+            && millis(&self.end_time).is_none_or(|end| now < end)
+    }
+
+    /// 转换为同步结果中的状态
+    pub fn to_info(&self, now: DateTime<Utc>) -> VacationInfo {
+        let active = self.is_active(now);
+        VacationInfo {
+            active,
+            subject: self
+                .response_subject
+                .as_deref()
+                .filter(|_| active)
+                .map(str::trim)
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+/// 毫秒时间戳字符串（无法解析时视为未设置）
+fn millis(value: &Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .as_deref()?
+        .parse::<i64>()
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+}
+
+/// 本次同步是否需要检查自动回复（开启后的首次同步立即检查，之后每 `CHECK_EVERY` 次一次）
+pub fn check_due(email: &str) -> bool {
+    let mut rounds = ROUNDS.lock().unwrap_or_else(|e| e.into_inner());
+    let round = rounds.entry(email.to_string()).or_insert(0);
+    let due = *round == 0;
+    *round = (*round + 1) % CHECK_EVERY;
+    due
+}
+
+/// 关闭提示后重新计数，再次开启时立即检查
+pub fn reset_rounds(email: &str) {
+    ROUNDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(email);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(json: serde_json::Value) -> VacationSettings {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_active_within_schedule() {
+        let now = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+
+        let off = settings(serde_json::json!({ "enableAutoReply": false }));
+        assert_eq!(off.to_info(now), VacationInfo::default());
+
+        let open_ended = settings(serde_json::json!({
+            "enableAutoReply": true,
+            "responseSubject": " Out of office ",
+        }));
+        assert_eq!(
+            open_ended.to_info(now),
+            VacationInfo {
+                active: true,
+                subject: "Out of office".to_string(),
+            }
+        );
+
+        let scheduled = |start: i64, end: i64| {
+            settings(serde_json::json!({
+                "enableAutoReply": true,
+                "startTime": start.to_string(),
+                "endTime": end.to_string(),
+            }))
+            .is_active(now)
+        };
+        let now_ms = now.timestamp_millis();
+        assert!(scheduled(now_ms - 1000, now_ms + 1000));
+        assert!(!scheduled(now_ms + 1000, now_ms + 2000));
+        assert!(!scheduled(now_ms - 2000, now_ms - 1000));
+    }
+
+    #[test]
+    fn test_checked_every_few_rounds() {
+        let email = "vacation-rounds@example.com";
+        let due: Vec<bool> = (0..=CHECK_EVERY).map(|_| check_due(email)).collect();
+        assert!(due[0]);
+        assert!(due[1..CHECK_EVERY as usize].iter().all(|due| !due));
+        assert!(due[CHECK_EVERY as usize]);
+
+        reset_rounds(email);
+        assert!(check_due(email));
+    }
+}
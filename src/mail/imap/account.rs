@@ -0,0 +1,219 @@
+/// IMAP 账户数据结构
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::crypto;
+
+/// 连接加密方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImapSecurity {
+    /// 直接建立 TLS 连接（通常为 993 端口）
+    #[default]
+    Tls,
+
+    /// 明文连接后通过 STARTTLS 升级（通常为 143 端口）
+    StartTls,
+}
+
+impl ImapSecurity {
+    /// 解析界面选项（无法识别时使用 TLS）
+    pub fn from_setting(setting: &str) -> Self {
+        match setting.trim().to_ascii_lowercase().as_str() {
+            "starttls" => Self::StartTls,
+            _ => Self::Tls,
+        }
+    }
+
+    /// 默认端口
+    pub fn default_port(self) -> u16 {
+        match self {
+            Self::Tls => 993,
+            Self::StartTls => 143,
+        }
+    }
+}
+
+/// IMAP 账户信息（使用应用专用密码或普通密码登录）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImapAccount {
+    /// 邮箱地址
+    pub email: String,
+
+    /// 显示名称
+    pub display_name: String,
+
+    /// 服务器地址
+    pub host: String,
+
+    /// 服务器端口
+    pub port: u16,
+
+    /// 连接加密方式
+    #[serde(default)]
+    pub security: ImapSecurity,
+
+    /// 登录用户名（通常即邮箱地址）
+    pub username: String,
+
+    /// 登录密码（加密存储）
+    ///
+    /// 格式：`"encrypted:BASE64..."`
+    #[serde(
+        serialize_with = "crypto::serialize_encrypted",
+        deserialize_with = "crypto::deserialize_encrypted"
+    )]
+    pub password: String,
+
+    /// 服务器支持时使用 IDLE 实时推送新邮件
+    #[serde(default)]
+    pub idle: bool,
+
+    /// 免打扰截止时间（期间照常同步，但不计入提醒、不发送通知）
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+
+    /// 强调色（`#RRGGBB`，`None` 表示使用由邮箱地址散列得到的默认颜色）
+    #[serde(default)]
+    pub accent: Option<String>,
+
+    /// 账户是否激活
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+
+    /// 标记删除的时间（撤销期结束或下次启动时才真正删除；`None` 表示正常账户）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_delete_at: Option<DateTime<Utc>>,
+
+    /// 未读提醒阈值（未读数达到后应用状态变为"需要关注"并发送一次提醒；`None` 表示不提醒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_threshold: Option<u32>,
+
+    /// 账户分组（`None` 表示未分组，列在"其他"中）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// 按流量计费的网络下是否照常同步（默认节流，见 `mail::metered`）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_metered: bool,
+}
+
+/// 默认值：true
+fn default_true() -> bool {
+    true
+}
+
+impl ImapAccount {
+    /// 创建新账户（密码为明文，会立即加密）
+    pub fn new(
+        email: String,
+        host: String,
+        port: u16,
+        security: ImapSecurity,
+        username: String,
+        password: &str,
+        idle: bool,
+    ) -> Result<Self> {
+        let password = crypto::encrypt_token(password).context("加密密码失败")?;
+        let display_name = email.split('@').next().unwrap_or(&email).to_string();
+
+        Ok(Self {
+            email,
+            display_name,
+            host,
+            port,
+            security,
+            username,
+            password,
+            idle,
+            snoozed_until: None,
+            accent: None,
+            is_active: true,
+            pending_delete_at: None,
+            alert_threshold: None,
+            group: None,
+            ignore_metered: false,
+        })
+    }
+
+    /// 解密登录密码
+    pub fn decrypt_password(&self) -> Result<String> {
+        crypto::decrypt_token(&self.password)
+    }
+}
+
+/// 转换为 Slint UI 的 Account 类型
+impl From<ImapAccount> for crate::Account {
+    fn from(account: ImapAccount) -> Self {
+        Self {
+            avatar_image: crate::utils::avatar::load_avatar_image(&account.email),
+            accent: crate::utils::avatar::accent_color(&account.email, account.accent.as_deref()),
+            accent_choice: account.accent.as_deref().unwrap_or("auto").into(),
+            count_mode: "".into(), // 不支持切换计数方式
+            snoozed: account
+                .snoozed_until
+                .is_some_and(|until| until > Utc::now()),
+            email: account.email.into(),
+            display_name: account.display_name.into(),
+            unread_count: 0, // 由同步引擎更新
+            is_loading: false,
+            sync_state: crate::ui::SyncState::NeverSynced.as_str().into(),
+            sparkline: "".into(), // 由同步引擎更新
+            sparkline_tip: "".into(),
+            notice: "".into(),
+            watch_spam: "".into(), // 不支持垃圾邮件监视
+            spam_caption: "".into(),
+            people_only: "".into(), // 不支持真人来信过滤
+            filter_caption: "".into(),
+            watch_vacation: "".into(), // 不支持自动回复提示
+            vacation: "".into(),
+            push: "".into(), // IDLE 只在账户文件中设置
+            cached: false,
+            stale: false,
+            alert_threshold: crate::ui::alert_threshold_setting(account.alert_threshold),
+            reauth_reminder: "".into(),
+            group: account.group.unwrap_or_default().into(),
+            header: false,
+            collapsed: false, // 由分组排列时填写
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_security_setting_and_ports() {
+        assert_eq!(
+            ImapSecurity::from_setting("STARTTLS"),
+            ImapSecurity::StartTls
+        );
+        assert_eq!(ImapSecurity::from_setting("tls"), ImapSecurity::Tls);
+        assert_eq!(ImapSecurity::from_setting("unknown"), ImapSecurity::Tls);
+        assert_eq!(ImapSecurity::Tls.default_port(), 993);
+        assert_eq!(ImapSecurity::StartTls.default_port(), 143);
+    }
+
+    #[test]
+    fn test_deserialize_keeps_password_encrypted() {
+        let account: ImapAccount = toml::from_str(
+            r#"
+email = "me@example.org"
+display_name = "me"
+host = "imap.example.org"
+port = 993
+username = "me@example.org"
+password = "encrypted:AAAA"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(account.password, "encrypted:AAAA");
+        assert_eq!(account.security, ImapSecurity::Tls);
+        assert!(!account.idle);
+        assert!(account.snoozed_until.is_none());
+        assert!(account.is_active);
+    }
+}
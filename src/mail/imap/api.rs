@@ -0,0 +1,165 @@
+/// IMAP 同步逻辑
+///
+/// 登录后以 `STATUS INBOX (UNSEEN)` 获取未读数，并把证书、登录与网络错误映射为 [`SyncError`]
+use anyhow::Result;
+use std::time::Duration;
+use tokio_rustls::rustls;
+
+use super::account::ImapAccount;
+use super::client::{ImapError, ImapSession};
+use crate::mail::error::{SyncError, classify_transport_failure};
+use crate::mail::{AccountSyncInfo, ProfileInfo, PushEvent, RetryContext};
+use crate::utils::avatar;
+use crate::utils::redact::redact_email;
+
+/// 未读数统计的邮箱
+const INBOX: &str = "INBOX";
+
+/// 单次 IDLE 的最长等待时间（RFC 2177 建议不超过 29 分钟）
+const IDLE_MAX_WAIT: Duration = Duration::from_secs(25 * 60);
+
+/// 连接并登录
+async fn open_session(account: &ImapAccount) -> Result<ImapSession> {
+    let password = account.decrypt_password()?;
+    let mut session = ImapSession::connect(account).await?;
+    session.login(&account.username, &password).await?;
+    Ok(session)
+}
+
+/// 同步账户信息（获取未读数，网络探测从 `retry` 的预算中扣除）
+pub async fn sync_account_info(
+    account: &ImapAccount,
+    retry: &RetryContext,
+) -> Result<AccountSyncInfo> {
+    tracing::info!("🔄 同步账户信息: {}", redact_email(&account.email));
+
+    let unread_count = match fetch_unseen(account).await {
+        Ok(count) => count,
+        Err(e) => return Err(classify(e, retry).await),
+    };
+
+    // IMAP 没有资料接口：沿用保存的名称与缓存的头像
+    Ok(AccountSyncInfo {
+        profile: Some(ProfileInfo {
+            display_name: account.display_name.clone(),
+            avatar_url: avatar::get_cached_avatar_path(&account.email).unwrap_or_default(),
+        }),
+        ..AccountSyncInfo::new(account.email.clone(), unread_count)
+    })
+}
+
+async fn fetch_unseen(account: &ImapAccount) -> Result<u32> {
+    let mut session = open_session(account).await?;
+    let count = session.unseen_count(INBOX).await?;
+    session.logout().await;
+    Ok(count)
+}
+
+/// 测试连接：尝试登录后立即退出（添加账户前调用）
+pub async fn test_connection(account: &ImapAccount) -> Result<()> {
+    match open_session(account).await {
+        Ok(session) => {
+            session.logout().await;
+            Ok(())
+        }
+        Err(e) => Err(classify(e, &RetryContext::default()).await),
+    }
+}
+
+/// 通过 IDLE 等待收件箱变化
+pub async fn wait_for_change(account: &ImapAccount) -> Result<PushEvent> {
+    let mut session = open_session(account).await?;
+
+    if !session.supports_idle().await? {
+        tracing::info!(
+            "{} 的服务器不支持 IDLE，仅使用定时轮询",
+            redact_email(&account.email)
+        );
+        session.logout().await;
+        return Ok(PushEvent::Unsupported);
+    }
+
+    session.select(INBOX).await?;
+    let changed = session.idle(IDLE_MAX_WAIT).await?;
+    session.logout().await;
+
+    Ok(if changed {
+        PushEvent::Changed
+    } else {
+        PushEvent::Timeout
+    })
+}
+
+/// 附加错误分类：证书错误、登录被拒、网络错误分别映射为对应的 [`SyncError`]
+async fn classify(error: anyhow::Error, retry: &RetryContext) -> anyhow::Error {
+    if is_certificate_error(&error) {
+        return error.context(SyncError::Certificate);
+    }
+
+    // 用户名、密码或邮箱名含有无法发送的字符时同样需要用户修改账户设置
+    let login_rejected = error.chain().any(|e| {
+        matches!(
+            e.downcast_ref::<ImapError>(),
+            Some(ImapError::LoginRejected(_) | ImapError::InvalidArgument)
+        )
+    });
+    if login_rejected {
+        return error.context(SyncError::AuthFailed);
+    }
+
+    let io_error = error
+        .chain()
+        .find_map(|e| e.downcast_ref::<std::io::Error>());
+    match io_error {
+        Some(io_error) => {
+            let timed_out = io_error.kind() == std::io::ErrorKind::TimedOut;
+            classify_transport_failure(error, timed_out, retry).await
+        }
+        None => error,
+    }
+}
+
+/// 错误链中是否包含 TLS 证书校验失败
+fn is_certificate_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|e| {
+        let tls_error = e.downcast_ref::<rustls::Error>().or_else(|| {
+            e.downcast_ref::<std::io::Error>()
+                .and_then(|io| io.get_ref())
+                .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+        });
+        matches!(tls_error, Some(rustls::Error::InvalidCertificate(_)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_certificate_error_is_detected_inside_io_error() {
+        let tls_error = rustls::Error::InvalidCertificate(rustls::CertificateError::Expired);
+        let error: anyhow::Error = Err::<(), _>(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            tls_error,
+        ))
+        .context("TLS 握手失败")
+        .unwrap_err();
+
+        assert!(is_certificate_error(&error));
+        assert!(!is_certificate_error(&anyhow::anyhow!("plain")));
+    }
+
+    #[tokio::test]
+    async fn test_login_rejection_maps_to_auth_failed() {
+        let error = anyhow::Error::from(ImapError::LoginRejected(
+            "NO [AUTHENTICATIONFAILED] Invalid credentials".into(),
+        ));
+
+        let classified = classify(error, &RetryContext::default()).await;
+        assert_eq!(
+            SyncError::classify(&classified),
+            Some(SyncError::AuthFailed)
+        );
+    }
+}
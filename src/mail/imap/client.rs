@@ -0,0 +1,539 @@
+/// 精简 IMAP 客户端
+///
+/// 只实现查询未读数与推送所需的命令：LOGIN、AUTHENTICATE XOAUTH2、STATUS、SELECT、IDLE、LOGOUT，
+/// 运行在 Tokio 上，TLS 使用 rustls（与 HTTP 请求相同的根证书集）。
+///
+/// 没有使用 async-imap：它基于 async-std/futures 的 IO 与 async-native-tls，而这里只需要几条命令，
+/// 自行实现可以复用 Tokio 运行时和 rustls 配置。协议中容易出错的部分按 RFC 3501 处理：
+/// 参数含非 ASCII 或控制字符时以同步字面量发送，含 CR/LF 时直接拒绝（见 [`quote`]）；
+/// 超过 [`MAX_LINE_LEN`] 的响应行报告错误而不是截断
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, pki_types::ServerName};
+
+use super::account::{ImapAccount, ImapSecurity};
+use crate::utils::http_client;
+
+/// 单行响应的最大长度（防止异常服务器耗尽内存）
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+/// 服务器拒绝命令（NO / BAD）
+#[derive(Debug, Error)]
+pub enum ImapError {
+    /// LOGIN 被拒绝
+    #[error("服务器拒绝登录: {0}")]
+    LoginRejected(String),
+
+    /// 其他命令被拒绝
+    #[error("IMAP 命令 {command} 失败: {text}")]
+    Rejected { command: String, text: String },
+
+    /// 响应不符合协议
+    #[error("IMAP 响应格式错误: {0}")]
+    Protocol(String),
+
+    /// 参数含有无法在 IMAP 命令中发送的字符（CR、LF 或 NUL）
+    #[error("参数包含换行符或空字符，无法发送")]
+    InvalidArgument,
+}
+
+/// 命令参数
+#[derive(Debug, PartialEq, Eq)]
+enum Argument {
+    /// 原样发送的固定部分（如 `(UNSEEN)`）
+    Atom(&'static str),
+
+    /// 带引号字符串（只含可打印 ASCII）
+    Quoted(String),
+
+    /// 同步字面量 `{n}`：先发送长度，收到服务器的续行响应后再发送内容
+    Literal(String),
+}
+
+/// 可读写的连接（明文或 TLS）
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+/// 已连接的 IMAP 会话
+pub struct ImapSession {
+    stream: BufReader<Box<dyn Connection>>,
+    next_tag: u32,
+    timeout: Duration,
+}
+
+/// 共享的 TLS 配置（根证书集构造一次）
+fn tls_connector() -> TlsConnector {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+
+    let config = CONFIG.get_or_init(|| {
+        let roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        Arc::new(
+            ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        )
+    });
+    TlsConnector::from(config.clone())
+}
+
+/// 在已建立的连接上进行 TLS 握手
+async fn start_tls(stream: Box<dyn Connection>, host: &str) -> Result<Box<dyn Connection>> {
+    let server_name = ServerName::try_from(host.to_string()).context("服务器地址无效")?;
+    let tls = tls_connector()
+        .connect(server_name, stream)
+        .await
+        .context("TLS 握手失败")?;
+    Ok(Box::new(tls))
+}
+
+/// 带超时的操作（超时表现为 `io::ErrorKind::TimedOut`，便于统一分类）
+async fn with_timeout<T>(timeout: Duration, future: impl Future<Output = Result<T>>) -> Result<T> {
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => result,
+        Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "IMAP 请求超时").into()),
+    }
+}
+
+impl ImapSession {
+    /// 连接账户的服务器并完成 TLS 协商（尚未登录）
+    pub async fn connect(account: &ImapAccount) -> Result<Self> {
+        Self::connect_to(&account.host, account.port, account.security).await
+    }
+
+    /// 连接指定服务器并完成 TLS 协商（尚未登录）
+    pub async fn connect_to(host: &str, port: u16, security: ImapSecurity) -> Result<Self> {
+        let timeout = http_client::count_timeout();
+
+        let tcp = with_timeout(timeout, async {
+            Ok(TcpStream::connect((host, port)).await?)
+        })
+        .await
+        .with_context(|| format!("连接 {}:{} 失败", host, port))?;
+
+        match security {
+            ImapSecurity::Tls => {
+                let tls = with_timeout(timeout, start_tls(Box::new(tcp), host)).await?;
+                let mut session = Self::new(tls, timeout);
+                session.read_greeting().await?;
+                Ok(session)
+            }
+            ImapSecurity::StartTls => {
+                let mut plain = Self::new(Box::new(tcp), timeout);
+                plain.read_greeting().await?;
+                plain.command("STARTTLS").await?;
+
+                // 升级前缓冲区中不应残留数据（防止明文注入）
+                if !plain.stream.buffer().is_empty() {
+                    return Err(ImapError::Protocol("STARTTLS 后存在多余数据".into()).into());
+                }
+                let next_tag = plain.next_tag;
+                let tls = with_timeout(timeout, start_tls(plain.stream.into_inner(), host)).await?;
+                let mut session = Self::new(tls, timeout);
+                session.next_tag = next_tag;
+                Ok(session)
+            }
+        }
+    }
+
+    fn new(stream: Box<dyn Connection>, timeout: Duration) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+            next_tag: 1,
+            timeout,
+        }
+    }
+
+    async fn read_greeting(&mut self) -> Result<()> {
+        let line = self.read_line().await?;
+        if line.starts_with("* OK") || line.starts_with("* PREAUTH") {
+            Ok(())
+        } else {
+            Err(ImapError::Protocol(format!("意外的问候语: {}", line)).into())
+        }
+    }
+
+    /// 使用用户名和密码登录
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<()> {
+        let args = [quote(username)?, quote(password)?];
+        match self.command_args("LOGIN", &args).await {
+            Ok(_) => Ok(()),
+            Err(e) => match e.downcast::<ImapError>() {
+                Ok(ImapError::Rejected { text, .. }) => Err(ImapError::LoginRejected(text).into()),
+                Ok(other) => Err(other.into()),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// 使用 OAuth2 Access Token 登录（SASL XOAUTH2，初始响应随命令发送）
+    pub async fn authenticate_xoauth2(&mut self, user: &str, access_token: &str) -> Result<()> {
+        let command = format!(
+            "AUTHENTICATE XOAUTH2 {}",
+            xoauth2_response(user, access_token)
+        );
+        let tag = self.send(&command).await?;
+
+        loop {
+            let line = self.read_line().await?;
+            if let Some(details) = line.strip_prefix('+') {
+                // 认证失败时服务器先以续行发送错误详情（Base64 编码的 JSON），回复空行后才返回 NO
+                let details = BASE64.decode(details.trim()).unwrap_or_default();
+                tracing::debug!("XOAUTH2 认证失败: {}", String::from_utf8_lossy(&details));
+                self.write_line("").await?;
+                continue;
+            }
+            let Some(rest) = line.strip_prefix(&tag).and_then(|r| r.strip_prefix(' ')) else {
+                continue;
+            };
+            return if rest.starts_with("OK") {
+                Ok(())
+            } else {
+                Err(ImapError::LoginRejected(rest.to_string()).into())
+            };
+        }
+    }
+
+    /// 查询邮箱的未读邮件数（`STATUS mailbox (UNSEEN)`）
+    pub async fn unseen_count(&mut self, mailbox: &str) -> Result<u32> {
+        let args = [quote(mailbox)?, Argument::Atom("(UNSEEN)")];
+        let lines = self.command_args("STATUS", &args).await?;
+
+        lines
+            .iter()
+            .find_map(|line| parse_status_unseen(line))
+            .ok_or_else(|| ImapError::Protocol("STATUS 响应缺少 UNSEEN".into()).into())
+    }
+
+    /// 服务器是否支持 IDLE
+    pub async fn supports_idle(&mut self) -> Result<bool> {
+        let lines = self.command("CAPABILITY").await?;
+        Ok(lines.iter().any(|line| {
+            line.starts_with("* CAPABILITY")
+                && line
+                    .split_ascii_whitespace()
+                    .any(|cap| cap.eq_ignore_ascii_case("IDLE"))
+        }))
+    }
+
+    /// 选中邮箱（IDLE 前需要）
+    pub async fn select(&mut self, mailbox: &str) -> Result<()> {
+        self.command_args("SELECT", &[quote(mailbox)?]).await?;
+        Ok(())
+    }
+
+    /// 进入 IDLE 等待邮箱变化
+    ///
+    /// 收到 EXISTS / EXPUNGE / RECENT / FETCH 推送时返回 true，等待 `max_wait` 仍无变化时返回 false
+    pub async fn idle(&mut self, max_wait: Duration) -> Result<bool> {
+        let tag = self.send("IDLE").await?;
+
+        let line = self.read_line().await?;
+        if !line.starts_with('+') {
+            return Err(ImapError::Rejected {
+                command: "IDLE".into(),
+                text: line,
+            }
+            .into());
+        }
+
+        let deadline = tokio::time::Instant::now() + max_wait;
+        let changed = loop {
+            let line = match tokio::time::timeout_at(deadline, self.read_raw_line()).await {
+                Ok(line) => line?,
+                Err(_) => break false,
+            };
+            if is_mailbox_change(&line) {
+                break true;
+            }
+        };
+
+        self.write_line("DONE").await?;
+        self.read_tagged(&tag, "IDLE").await?;
+        Ok(changed)
+    }
+
+    /// 退出登录（失败时忽略，连接随后关闭）
+    pub async fn logout(mut self) {
+        if let Err(e) = self.command("LOGOUT").await {
+            tracing::debug!("IMAP LOGOUT 失败: {}", e);
+        }
+    }
+
+    /// 发送命令并读取到对应的完成响应，返回期间收到的未标记响应
+    async fn command(&mut self, command: &str) -> Result<Vec<String>> {
+        let tag = self.send(command).await?;
+        let name = command.split(' ').next().unwrap_or(command).to_string();
+        self.read_tagged(&tag, &name).await
+    }
+
+    /// 发送带参数的命令并读取到对应的完成响应（参数编码见 [`quote`]）
+    async fn command_args(&mut self, name: &str, args: &[Argument]) -> Result<Vec<String>> {
+        let tag = self.send_args(name, args).await?;
+        self.read_tagged(&tag, name).await
+    }
+
+    async fn send(&mut self, command: &str) -> Result<String> {
+        self.send_args(command, &[]).await
+    }
+
+    /// 发送命令，字面量参数在收到服务器的续行响应后才发送内容
+    async fn send_args(&mut self, command: &str, args: &[Argument]) -> Result<String> {
+        let tag = format!("A{:04}", self.next_tag);
+        self.next_tag += 1;
+
+        let mut line = format!("{} {}", tag, command);
+        for arg in args {
+            line.push(' ');
+            match arg {
+                Argument::Atom(value) => line.push_str(value),
+                Argument::Quoted(value) => line.push_str(value),
+                Argument::Literal(value) => {
+                    line.push_str(&format!("{{{}}}", value.len()));
+                    self.write_line(&line).await?;
+                    self.read_continuation(&tag, command).await?;
+                    // 字面量内容与命令的剩余部分一起发送
+                    line = value.clone();
+                }
+            }
+        }
+        self.write_line(&line).await?;
+        Ok(tag)
+    }
+
+    /// 等待服务器允许发送字面量（`+` 续行）；服务器直接返回完成响应时视为拒绝
+    async fn read_continuation(&mut self, tag: &str, command: &str) -> Result<()> {
+        loop {
+            let line = self.read_line().await?;
+            if line.starts_with('+') {
+                return Ok(());
+            }
+            if let Some(rest) = line.strip_prefix(tag).and_then(|r| r.strip_prefix(' ')) {
+                let name = command.split(' ').next().unwrap_or(command);
+                return Err(ImapError::Rejected {
+                    command: name.to_string(),
+                    text: rest.to_string(),
+                }
+                .into());
+            }
+        }
+    }
+
+    async fn read_tagged(&mut self, tag: &str, command: &str) -> Result<Vec<String>> {
+        let mut untagged = Vec::new();
+        loop {
+            let line = self.read_line().await?;
+            let Some(rest) = line.strip_prefix(tag).and_then(|r| r.strip_prefix(' ')) else {
+                untagged.push(line);
+                continue;
+            };
+
+            return if rest.starts_with("OK") {
+                Ok(untagged)
+            } else {
+                Err(ImapError::Rejected {
+                    command: command.to_string(),
+                    text: rest.to_string(),
+                }
+                .into())
+            };
+        }
+    }
+
+    async fn write_line(&mut self, line: &str) -> Result<()> {
+        let timeout = self.timeout;
+        let stream = self.stream.get_mut();
+        with_timeout(timeout, async {
+            stream.write_all(line.as_bytes()).await?;
+            stream.write_all(b"\r\n").await?;
+            stream.flush().await?;
+            Ok(())
+        })
+        .await
+        .context("发送 IMAP 命令失败")
+    }
+
+    async fn read_line(&mut self) -> Result<String> {
+        let timeout = self.timeout;
+        with_timeout(timeout, self.read_raw_line())
+            .await
+            .context("读取 IMAP 响应失败")
+    }
+
+    /// 读取一条完整响应（含服务器以 `{n}` 形式发送的字面量）
+    async fn read_raw_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        loop {
+            let mut chunk = Vec::new();
+            let read = (&mut self.stream)
+                .take(MAX_LINE_LEN as u64)
+                .read_until(b'\n', &mut chunk)
+                .await?;
+            if read == 0 {
+                anyhow::bail!(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "服务器关闭了连接"
+                ));
+            }
+            // 读满上限仍未遇到换行：报告错误，不把截断的内容当作完整响应
+            if read >= MAX_LINE_LEN && !chunk.ends_with(b"\n") {
+                return Err(ImapError::Protocol("响应行过长".into()).into());
+            }
+            let chunk = String::from_utf8_lossy(&chunk);
+            let chunk = chunk.trim_end_matches(['\r', '\n']);
+            line.push_str(chunk);
+
+            let Some(size) = literal_size(chunk) else {
+                return Ok(line);
+            };
+            if size > MAX_LINE_LEN || line.len() + size > MAX_LINE_LEN {
+                return Err(ImapError::Protocol("字面量过长".into()).into());
+            }
+            let mut literal = vec![0; size];
+            self.stream.read_exact(&mut literal).await?;
+            line.push_str(&String::from_utf8_lossy(&literal));
+        }
+    }
+}
+
+/// 按参数内容选择编码方式
+///
+/// 只含可打印 ASCII 时转义为带引号字符串；含非 ASCII 或控制字符时以字面量发送
+/// （带引号字符串不允许这些字符）；CR、LF 与 NUL 在任何形式中都不能发送，直接拒绝
+fn quote(value: &str) -> Result<Argument> {
+    if value.contains(['\r', '\n', '\0']) {
+        return Err(ImapError::InvalidArgument.into());
+    }
+    if !value.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        return Ok(Argument::Literal(value.to_string()));
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    Ok(Argument::Quoted(quoted))
+}
+
+/// SASL XOAUTH2 的初始响应：`user=<邮箱>^Aauth=Bearer <Token>^A^A` 的 Base64 编码
+fn xoauth2_response(user: &str, access_token: &str) -> String {
+    BASE64.encode(format!(
+        "user={}\x01auth=Bearer {}\x01\x01",
+        user, access_token
+    ))
+}
+
+/// 行尾的字面量长度标记 `{n}`
+fn literal_size(line: &str) -> Option<usize> {
+    let rest = line.strip_suffix('}')?;
+    let start = rest.rfind('{')?;
+    rest[start + 1..].trim_end_matches('+').parse().ok()
+}
+
+/// 解析 `* STATUS INBOX (UNSEEN 3)` 中的未读数
+fn parse_status_unseen(line: &str) -> Option<u32> {
+    if !line.starts_with("* STATUS") {
+        return None;
+    }
+    let items = &line[line.rfind('(')? + 1..line.rfind(')')?];
+    let mut tokens = items.split_ascii_whitespace();
+    while let Some(name) = tokens.next() {
+        let value = tokens.next()?;
+        if name.eq_ignore_ascii_case("UNSEEN") {
+            return value.parse().ok();
+        }
+    }
+    None
+}
+
+/// IDLE 期间的推送是否表示邮箱内容变化
+fn is_mailbox_change(line: &str) -> bool {
+    let mut parts = line.split_ascii_whitespace();
+    if parts.next() != Some("*") {
+        return false;
+    }
+    let (Some(_), Some(kind)) = (parts.next(), parts.next()) else {
+        return false;
+    };
+    ["EXISTS", "EXPUNGE", "RECENT", "FETCH"]
+        .iter()
+        .any(|k| kind.eq_ignore_ascii_case(k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status_unseen() {
+        assert_eq!(parse_status_unseen("* STATUS INBOX (UNSEEN 3)"), Some(3));
+        assert_eq!(
+            parse_status_unseen("* STATUS \"INBOX\" (MESSAGES 10 UNSEEN 0)"),
+            Some(0)
+        );
+        assert_eq!(parse_status_unseen("* OK still here"), None);
+    }
+
+    #[test]
+    fn test_quote_escapes() {
+        assert_eq!(
+            quote("INBOX").unwrap(),
+            Argument::Quoted("\"INBOX\"".into())
+        );
+        assert_eq!(
+            quote("pa\"ss\\word").unwrap(),
+            Argument::Quoted("\"pa\\\"ss\\\\word\"".into())
+        );
+    }
+
+    #[test]
+    fn test_quote_uses_literal_or_rejects() {
+        assert_eq!(
+            quote("pässwort").unwrap(),
+            Argument::Literal("pässwort".into())
+        );
+        assert_eq!(
+            quote("tab\there").unwrap(),
+            Argument::Literal("tab\there".into())
+        );
+        for value in ["pass\r\nA2 LOGOUT", "line\n", "nul\0"] {
+            let error = quote(value).unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<ImapError>(),
+                Some(ImapError::InvalidArgument)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_xoauth2_response() {
+        let response = xoauth2_response("me@gmail.com", "ya29.token");
+        let decoded = BASE64.decode(response).unwrap();
+        assert_eq!(
+            decoded,
+            b"user=me@gmail.com\x01auth=Bearer ya29.token\x01\x01"
+        );
+    }
+
+    #[test]
+    fn test_literal_and_idle_lines() {
+        assert_eq!(literal_size("* STATUS {5}"), Some(5));
+        assert_eq!(literal_size("* STATUS INBOX (UNSEEN 1)"), None);
+        assert!(is_mailbox_change("* 12 EXISTS"));
+        assert!(is_mailbox_change("* 3 FETCH (FLAGS (\\Seen))"));
+        assert!(!is_mailbox_change("* OK Still here"));
+    }
+}
@@ -0,0 +1,10 @@
+/// IMAP 模块 - 使用密码（或应用专用密码）登录的通用邮件服务
+pub mod account;
+pub mod api;
+pub mod client;
+pub mod provider;
+
+// 重新导出常用类型和函数
+pub use account::{ImapAccount, ImapSecurity};
+pub use api::{sync_account_info, test_connection};
+pub use provider::ImapProvider;
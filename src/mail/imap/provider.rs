@@ -0,0 +1,56 @@
+/// IMAP 提供方实现
+///
+/// 账户由添加账户表单创建（无浏览器授权流程），支持 IDLE 实时推送
+use async_trait::async_trait;
+
+use crate::config::storage::StoredAccount;
+use crate::mail::{MailProvider, PushEvent, RetryContext, SyncFailure, SyncOutcome};
+
+/// IMAP 提供方
+pub struct ImapProvider;
+
+#[async_trait]
+impl MailProvider for ImapProvider {
+    fn kind(&self) -> &'static str {
+        "imap"
+    }
+
+    async fn sync(
+        &self,
+        account: &StoredAccount,
+        retry: &RetryContext,
+    ) -> Result<SyncOutcome, SyncFailure> {
+        let StoredAccount::Imap(imap) = account else {
+            return Err(SyncFailure::from_error(&anyhow::anyhow!(
+                "账户类型不匹配: {}",
+                account.kind()
+            )));
+        };
+
+        match super::sync_account_info(imap, retry).await {
+            Ok(info) => Ok(SyncOutcome {
+                info,
+                updated_account: None,
+            }),
+            // 失败日志由同步引擎统一记录（对重复的错误限流）
+            Err(e) => Err(SyncFailure::from_error(&e)),
+        }
+    }
+
+    async fn authenticate(&self) -> anyhow::Result<Option<StoredAccount>> {
+        anyhow::bail!("IMAP 账户需要在添加账户表单中填写服务器信息")
+    }
+
+    async fn wait_for_change(&self, account: &StoredAccount) -> anyhow::Result<PushEvent> {
+        match account {
+            StoredAccount::Imap(imap) if imap.idle => super::api::wait_for_change(imap).await,
+            _ => Ok(PushEvent::Unsupported),
+        }
+    }
+
+    fn open_inbox_url(&self, account: &StoredAccount) -> String {
+        // 没有统一的网页版地址，打开邮箱域名主页
+        let domain = account.email().rsplit('@').next().unwrap_or_default();
+        format!("https://{}", domain)
+    }
+}
@@ -0,0 +1,148 @@
+/// 按流量计费网络下的节流同步
+///
+/// 系统报告当前连接按流量计费（见 `connectivity::is_metered`），且 `[app] respect_metered`
+/// 开启（默认）时，账户进入节流同步：两次同步至少间隔 [`METERED_MIN_INTERVAL`]，
+/// 不获取用户资料与头像（界面沿用缓存）。账户设置 `ignore_metered = true` 时照常同步；
+/// 手动同步单个账户不受间隔限制
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::utils::connectivity;
+
+/// 节流同步时两次同步的最短间隔
+pub const METERED_MIN_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// 是否在按流量计费的网络下节流同步（随配置更新）
+static RESPECT_METERED: AtomicBool = AtomicBool::new(true);
+
+/// 各账户上次节流同步的时间
+static LAST_SYNC: LazyLock<Mutex<HashMap<String, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 账户的同步策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// 照常同步
+    Normal,
+
+    /// 节流同步：拉长间隔，不获取用户资料与头像
+    Reduced,
+}
+
+impl SyncPolicy {
+    /// 是否节流同步
+    pub fn is_reduced(self) -> bool {
+        self == Self::Reduced
+    }
+
+    /// 距上次同步（`last`）是否已到下一次同步的时间
+    pub fn is_due(self, last: Option<Instant>, now: Instant) -> bool {
+        match self {
+            Self::Normal => true,
+            Self::Reduced => {
+                last.is_none_or(|last| now.saturating_duration_since(last) >= METERED_MIN_INTERVAL)
+            }
+        }
+    }
+}
+
+/// 决定账户的同步策略
+///
+/// # Arguments
+/// * `metered` - 当前连接是否按流量计费
+/// * `respect_metered` - 配置是否在按流量计费时节流（`[app] respect_metered`）
+/// * `ignore_metered` - 账户是否不受计费网络影响（账户设置 `ignore_metered`）
+pub fn decide(metered: bool, respect_metered: bool, ignore_metered: bool) -> SyncPolicy {
+    if metered && respect_metered && !ignore_metered {
+        SyncPolicy::Reduced
+    } else {
+        SyncPolicy::Normal
+    }
+}
+
+/// 设置是否在按流量计费的网络下节流同步（来自配置）
+pub fn set_respect(respect: bool) {
+    RESPECT_METERED.store(respect, Ordering::Relaxed);
+}
+
+/// 当前是否在节流同步（不考虑账户设置，窗口底部据此显示提示）
+pub fn is_reducing() -> bool {
+    connectivity::is_metered() && RESPECT_METERED.load(Ordering::Relaxed)
+}
+
+/// 账户当前的同步策略
+pub fn current(ignore_metered: bool) -> SyncPolicy {
+    decide(
+        connectivity::is_metered(),
+        RESPECT_METERED.load(Ordering::Relaxed),
+        ignore_metered,
+    )
+}
+
+fn last_sync() -> std::sync::MutexGuard<'static, HashMap<String, Instant>> {
+    LAST_SYNC.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 本轮是否同步该账户（节流时距上次同步不足 [`METERED_MIN_INTERVAL`] 则跳过）
+pub fn is_due(email: &str, policy: SyncPolicy) -> bool {
+    policy.is_due(last_sync().get(email).copied(), Instant::now())
+}
+
+/// 记录一次同步（照常同步时清除记录，恢复计费网络后的首轮立即同步）
+pub fn record(email: &str, policy: SyncPolicy) {
+    match policy {
+        SyncPolicy::Reduced => {
+            last_sync().insert(email.to_string(), Instant::now());
+        }
+        SyncPolicy::Normal => forget(email),
+    }
+}
+
+/// 清除账户的记录（手动同步不受间隔限制）
+pub fn forget(email: &str) {
+    last_sync().remove(email);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide() {
+        assert_eq!(decide(true, true, false), SyncPolicy::Reduced);
+        // 不计费、关闭节流或账户不受影响时照常同步
+        assert_eq!(decide(false, true, false), SyncPolicy::Normal);
+        assert_eq!(decide(true, false, false), SyncPolicy::Normal);
+        assert_eq!(decide(true, true, true), SyncPolicy::Normal);
+    }
+
+    #[test]
+    fn test_reduced_policy_waits_min_interval() {
+        let start = Instant::now();
+        let reduced = SyncPolicy::Reduced;
+        assert!(reduced.is_due(None, start));
+        assert!(!reduced.is_due(Some(start), start + Duration::from_secs(60)));
+        assert!(reduced.is_due(Some(start), start + METERED_MIN_INTERVAL));
+
+        assert!(SyncPolicy::Normal.is_due(Some(start), start));
+    }
+
+    #[test]
+    fn test_record_and_forget() {
+        let email = "metered@example.com";
+        assert!(is_due(email, SyncPolicy::Reduced));
+        record(email, SyncPolicy::Reduced);
+        assert!(!is_due(email, SyncPolicy::Reduced));
+        assert!(is_due(email, SyncPolicy::Normal));
+
+        // 照常同步一次后，再次节流时立即同步
+        record(email, SyncPolicy::Normal);
+        assert!(is_due(email, SyncPolicy::Reduced));
+
+        record(email, SyncPolicy::Reduced);
+        forget(email);
+        assert!(is_due(email, SyncPolicy::Reduced));
+    }
+}
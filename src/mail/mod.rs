@@ -1,3 +1,39 @@
-// TODO: 实现邮件服务模块
-
+/// 邮件服务模块
+///
+/// `provider` 定义与具体服务无关的提供方接口，各服务（Gmail、Outlook、IMAP）在子模块中实现，
+/// 基于 OAuth2 的服务共用 `oauth` 模块的授权流程与 Token 管理
+pub mod error;
 pub mod gmail;
+pub mod imap;
+pub mod metered;
+pub mod oauth;
+pub mod outlook;
+pub mod provider;
+pub mod quota;
+pub mod retry;
+pub mod sync_info;
+
+use anyhow::Result;
+
+use crate::config::storage::StoredAccount;
+
+pub use error::SyncFailure;
+pub use provider::{MailProvider, ProviderRegistry, PushEvent, SyncOutcome};
+pub use retry::RetryContext;
+pub use sync_info::{
+    AccountId, AccountSyncInfo, Counters, MailPreview, PeopleFilter, ProfileInfo, SyncHealth,
+    VacationInfo,
+};
+
+/// 账户对应服务的 Token 管理器（密码登录的账户为 `None`）
+pub fn token_manager(account: &StoredAccount) -> Option<Result<oauth::TokenManager>> {
+    let manager = match account {
+        StoredAccount::Gmail(account) => gmail::oauth::client()
+            .map(|client| oauth::TokenManager::new(account.clone(), client, StoredAccount::Gmail)),
+        StoredAccount::Outlook(account) => outlook::oauth::client().map(|client| {
+            oauth::TokenManager::new(account.clone(), client, StoredAccount::Outlook)
+        }),
+        StoredAccount::Imap(_) => return None,
+    };
+    Some(manager)
+}
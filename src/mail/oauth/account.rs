@@ -0,0 +1,539 @@
+/// OAuth2 账户数据结构
+///
+/// Gmail、Outlook 等通过 OAuth2 登录的邮件服务共用，Token 始终以加密形式保存
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::reauth;
+use crate::config::crypto;
+use crate::mail::gmail::labels;
+
+/// 未读计数方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountMode {
+    /// 统计收件箱全部未读邮件
+    #[default]
+    AllUnread,
+
+    /// 只统计计数基准时间之后收到的未读邮件（忽略添加账户前积压的历史未读）
+    SinceAdded,
+}
+
+impl CountMode {
+    /// 解析界面选项（无法识别时统计全部未读）
+    pub fn from_setting(setting: &str) -> Self {
+        match setting {
+            "since_added" => Self::SinceAdded,
+            _ => Self::AllUnread,
+        }
+    }
+
+    /// 界面选项值（与配置文件中的取值一致）
+    pub fn as_setting(self) -> &'static str {
+        match self {
+            Self::AllUnread => "all_unread",
+            Self::SinceAdded => "since_added",
+        }
+    }
+}
+
+/// OAuth2 账户信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthAccount {
+    /// 邮箱地址
+    pub email: String,
+
+    /// 显示名称（缺失时为空，界面显示邮箱地址）
+    #[serde(default)]
+    pub display_name: String,
+
+    /// 访问令牌（加密存储）
+    ///
+    /// 格式：`"encrypted:BASE64..."`；文件中为明文时加载后立即加密，下次保存时写回加密格式。
+    /// 缺失时为空（过期时间同样缺失，首次使用前用 Refresh Token 刷新）
+    #[serde(
+        default,
+        serialize_with = "crypto::serialize_encrypted",
+        deserialize_with = "crypto::deserialize_encrypted"
+    )]
+    pub access_token: String,
+
+    /// 刷新令牌（加密存储）
+    ///
+    /// 格式：`"encrypted:BASE64..."`；文件中为明文时同样在加载后立即加密
+    #[serde(
+        serialize_with = "crypto::serialize_encrypted",
+        deserialize_with = "crypto::deserialize_encrypted"
+    )]
+    pub refresh_token: String,
+
+    /// Token 过期时间（UTC；缺失时视为已过期）
+    #[serde(default)]
+    pub expires_at: DateTime<Utc>,
+
+    /// 未读计数方式
+    #[serde(default)]
+    pub count_mode: CountMode,
+
+    /// 未读计数基准时间（添加账户或重置计数的时间，`since_added` 模式只统计此后的邮件）
+    #[serde(default)]
+    pub counted_since: Option<DateTime<Utc>>,
+
+    /// 免打扰截止时间（期间照常同步，但不计入提醒、不发送通知）
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+
+    /// 强调色（`#RRGGBB`，`None` 表示使用由邮箱地址散列得到的默认颜色）
+    #[serde(default)]
+    pub accent: Option<String>,
+
+    /// 是否同时监视垃圾邮件的未读数（仅 Gmail，不影响徽章与通知）
+    #[serde(default)]
+    pub watch_spam: bool,
+
+    /// 徽章是否只统计真人来信（仅 Gmail，见 `mail::gmail::people`）
+    #[serde(default)]
+    pub people_only: bool,
+
+    /// 自动回复生效时是否在账户行显示提示（仅 Gmail，见 `mail::gmail::vacation`）
+    #[serde(default)]
+    pub watch_vacation: bool,
+
+    /// 徽章（托盘数字、提示与汇总）统计的标签 ID（仅 Gmail，默认收件箱）
+    #[serde(default = "default_label", skip_serializing_if = "is_default_label")]
+    pub badge_label: String,
+
+    /// 新邮件通知依据的标签 ID（仅 Gmail，默认收件箱；与徽章标签相同时只请求一次）
+    #[serde(default = "default_label", skip_serializing_if = "is_default_label")]
+    pub notify_label: String,
+
+    /// 账户是否激活
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+
+    /// 最近一次刷新 Access Token 的时间（尚未刷新过时为 `None`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refreshed_at: Option<DateTime<Utc>>,
+
+    /// 当前 Refresh Token 的签发时间（授权或服务轮换 Refresh Token 时记录；旧版本保存的账户为 `None`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token_issued_at: Option<DateTime<Utc>>,
+
+    /// 是否检测到 OAuth 客户端处于测试状态（Refresh Token 约 7 天后以 `invalid_grant` 失效）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub testing_client: bool,
+
+    /// 授予的权限范围（旧版本保存的账户为空）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+
+    /// 标记删除的时间（撤销期结束或下次启动时才真正删除；`None` 表示正常账户）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_delete_at: Option<DateTime<Utc>>,
+
+    /// 未读提醒阈值（未读数达到后应用状态变为"需要关注"并发送一次提醒；`None` 表示不提醒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_threshold: Option<u32>,
+
+    /// 账户分组（如"工作"、"个人"；`None` 表示未分组，列在"其他"中）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// 按流量计费的网络下是否照常同步（默认节流，见 `mail::metered`）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_metered: bool,
+
+    /// 是否通过 IMAP IDLE 实时推送收件箱变化（仅 Gmail，见 `mail::gmail::push`）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub push: bool,
+}
+
+/// 默认值：true
+fn default_true() -> bool {
+    true
+}
+
+/// 默认标签：收件箱
+fn default_label() -> String {
+    labels::INBOX.to_string()
+}
+
+/// 是否为默认标签（保存时省略）
+fn is_default_label(label: &str) -> bool {
+    label == labels::INBOX
+}
+
+impl OAuthAccount {
+    /// 创建新账户（Token 为明文，会自动加密）
+    ///
+    /// Token 在创建时会立即加密，确保内存中不存储明文
+    pub fn new(
+        email: String,
+        display_name: String,
+        access_token: String,
+        refresh_token: String,
+        expires_in_seconds: i64,
+    ) -> Result<Self> {
+        // 在创建时立即加密 Token，保护内存安全
+        let encrypted_access_token =
+            crypto::encrypt_token(&access_token).context("加密 Access Token 失败")?;
+        let encrypted_refresh_token =
+            crypto::encrypt_token(&refresh_token).context("加密 Refresh Token 失败")?;
+
+        Ok(Self {
+            email,
+            display_name,
+            access_token: encrypted_access_token,
+            refresh_token: encrypted_refresh_token,
+            expires_at: Utc::now() + chrono::Duration::seconds(expires_in_seconds),
+            count_mode: CountMode::default(),
+            counted_since: Some(Utc::now()),
+            snoozed_until: None,
+            accent: None,
+            watch_spam: false,
+            people_only: false,
+            watch_vacation: false,
+            badge_label: default_label(),
+            notify_label: default_label(),
+            is_active: true,
+            refreshed_at: None,
+            refresh_token_issued_at: Some(Utc::now()),
+            testing_client: false,
+            scopes: Vec::new(),
+            pending_delete_at: None,
+            alert_threshold: None,
+            group: None,
+            ignore_metered: false,
+            push: false,
+        })
+    }
+
+    /// 解密访问令牌
+    pub fn decrypt_access_token(&self) -> Result<String> {
+        crypto::decrypt_token(&self.access_token)
+    }
+
+    /// 解密刷新令牌
+    pub fn decrypt_refresh_token(&self) -> Result<String> {
+        crypto::decrypt_token(&self.refresh_token)
+    }
+
+    /// 检查 Token 是否即将过期
+    ///
+    /// # Arguments
+    /// * `threshold_minutes` - 提前多少分钟算作"即将过期"
+    pub fn is_token_expiring(&self, threshold_minutes: i64) -> bool {
+        let threshold = Utc::now() + chrono::Duration::minutes(threshold_minutes);
+        self.expires_at <= threshold
+    }
+
+    /// 更新访问令牌（自动加密）
+    pub fn update_access_token(
+        &mut self,
+        new_token: String,
+        expires_in_seconds: i64,
+    ) -> Result<()> {
+        self.access_token = crypto::encrypt_token(&new_token)?;
+        self.expires_at = Utc::now() + chrono::Duration::seconds(expires_in_seconds);
+        self.refreshed_at = Some(Utc::now());
+        Ok(())
+    }
+
+    /// 更新刷新令牌（自动加密，部分服务如 Microsoft 每次刷新都会轮换 Refresh Token）
+    pub fn update_refresh_token(&mut self, new_token: String) -> Result<()> {
+        self.refresh_token = crypto::encrypt_token(&new_token)?;
+        self.refresh_token_issued_at = Some(Utc::now());
+        Ok(())
+    }
+
+    /// 是否已获得指定的权限范围（旧版本保存的账户在补齐记录之前视为未获得）
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|granted| granted == scope)
+    }
+
+    /// 采用重新授权得到的令牌（保留计数方式、免打扰、强调色等设置）
+    pub fn adopt_tokens(&mut self, authorized: &OAuthAccount) {
+        self.access_token = authorized.access_token.clone();
+        self.refresh_token = authorized.refresh_token.clone();
+        self.expires_at = authorized.expires_at;
+        self.refresh_token_issued_at = authorized.refresh_token_issued_at;
+        self.scopes = authorized.scopes.clone();
+    }
+
+    /// 需要重新授权的时间（`testing_mode` 为 `[oauth]` 中的 `testing_mode` 设置；不是测试状态时为 `None`）
+    pub fn reauth_due(&self, testing_mode: bool) -> Option<DateTime<Utc>> {
+        reauth::reauth_due(
+            self.refresh_token_issued_at,
+            testing_mode || self.testing_client,
+        )
+    }
+
+    /// 未读计数基准时间（统计全部未读时为 `None`）
+    pub fn unread_baseline(&self) -> Option<DateTime<Utc>> {
+        match self.count_mode {
+            CountMode::AllUnread => None,
+            CountMode::SinceAdded => self.counted_since,
+        }
+    }
+
+    /// 切换未读计数方式（旧版本保存的账户没有基准时间，切换时以当前时间为基准）
+    pub fn set_count_mode(&mut self, mode: CountMode) {
+        self.count_mode = mode;
+        if mode == CountMode::SinceAdded && self.counted_since.is_none() {
+            self.counted_since = Some(Utc::now());
+        }
+    }
+
+    /// 将未读计数清零：以当前时间为新基准，只统计此后收到的邮件
+    pub fn reset_unread_baseline(&mut self) {
+        self.count_mode = CountMode::SinceAdded;
+        self.counted_since = Some(Utc::now());
+    }
+}
+
+/// 转换为 Slint UI 的 Account 类型
+impl From<OAuthAccount> for crate::Account {
+    fn from(account: OAuthAccount) -> Self {
+        Self {
+            avatar_image: crate::utils::avatar::load_avatar_image(&account.email),
+            accent: crate::utils::avatar::accent_color(&account.email, account.accent.as_deref()),
+            accent_choice: account.accent.as_deref().unwrap_or("auto").into(),
+            count_mode: account.count_mode.as_setting().into(),
+            snoozed: account
+                .snoozed_until
+                .is_some_and(|until| until > Utc::now()),
+            email: account.email.into(),
+            display_name: account.display_name.into(),
+            unread_count: 0, // 由同步引擎更新
+            is_loading: false,
+            sync_state: crate::ui::SyncState::NeverSynced.as_str().into(),
+            sparkline: "".into(), // 由同步引擎更新
+            sparkline_tip: "".into(),
+            notice: "".into(),
+            watch_spam: "".into(), // 由 StoredAccount 按账户类型填写（仅 Gmail 支持）
+            spam_caption: "".into(),
+            people_only: "".into(), // 由 StoredAccount 按账户类型填写（仅 Gmail 支持）
+            filter_caption: "".into(),
+            watch_vacation: "".into(), // 由 StoredAccount 按账户类型与授权范围填写（仅 Gmail 支持）
+            vacation: "".into(),       // 由同步引擎更新
+            push: "".into(),           // 由 StoredAccount 按账户类型与授权范围填写（仅 Gmail 支持）
+            cached: false,
+            stale: false,
+            alert_threshold: crate::ui::alert_threshold_setting(account.alert_threshold),
+            reauth_reminder: "".into(), // 由 StoredAccount 按账户类型填写（Gmail 另有 `[oauth]` 设置）
+            group: account.group.unwrap_or_default().into(),
+            header: false,
+            collapsed: false, // 由分组排列时填写
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore] // 需要在 Windows 环境运行
+    fn test_create_account() {
+        let account = OAuthAccount::new(
+            "test@gmail.com".to_string(),
+            "Test User".to_string(),
+            "plain_access_token".to_string(),
+            "plain_refresh_token".to_string(),
+            3600,
+        )
+        .expect("创建账户失败");
+
+        assert_eq!(account.email, "test@gmail.com");
+        assert_eq!(account.display_name, "Test User");
+        assert!(account.is_active);
+    }
+
+    #[test]
+    #[ignore] // 需要在 Windows 环境运行
+    fn test_serialize_with_encryption() {
+        let account = OAuthAccount::new(
+            "test@gmail.com".to_string(),
+            "Test User".to_string(),
+            "plain_access_token".to_string(),
+            "plain_refresh_token".to_string(),
+            3600,
+        )
+        .expect("创建账户失败");
+
+        // 序列化为 TOML
+        let toml = toml::to_string(&account).unwrap();
+        println!("序列化结果:\n{}", toml);
+
+        // 验证 Token 已加密
+        assert!(toml.contains("encrypted:"));
+        assert!(!toml.contains("plain_access_token"));
+        assert!(!toml.contains("plain_refresh_token"));
+    }
+
+    #[test]
+    #[ignore] // 需要在 Windows 环境运行
+    fn test_decrypt_tokens() {
+        let account = OAuthAccount::new(
+            "test@gmail.com".to_string(),
+            "Test User".to_string(),
+            "plain_access_token".to_string(),
+            "plain_refresh_token".to_string(),
+            3600,
+        )
+        .expect("创建账户失败");
+
+        // Token 现在已经在创建时加密了，直接解密
+        let decrypted_access = account.decrypt_access_token().unwrap();
+        let decrypted_refresh = account.decrypt_refresh_token().unwrap();
+
+        assert_eq!(decrypted_access, "plain_access_token");
+        assert_eq!(decrypted_refresh, "plain_refresh_token");
+    }
+
+    #[test]
+    fn test_has_scope() {
+        let mut account = OAuthAccount::new(
+            "test@gmail.com".to_string(),
+            "Test User".to_string(),
+            "token".to_string(),
+            "refresh".to_string(),
+            3600,
+        )
+        .expect("创建账户失败");
+
+        // 旧版本保存的账户没有记录
+        assert!(!account.has_scope("openid"));
+
+        account.scopes = vec!["openid".to_string(), "email".to_string()];
+        assert!(account.has_scope("email"));
+        assert!(!account.has_scope("mail"));
+    }
+
+    #[test]
+    fn test_is_token_expiring() {
+        let mut account = OAuthAccount::new(
+            "test@gmail.com".to_string(),
+            "Test User".to_string(),
+            "token".to_string(),
+            "refresh".to_string(),
+            3600, // 1 小时后过期
+        )
+        .expect("创建账户失败");
+
+        // 未过期（提前 10 分钟检查）
+        assert!(!account.is_token_expiring(10));
+
+        // 即将过期（提前 120 分钟检查）
+        assert!(account.is_token_expiring(120));
+
+        // 设置为已过期
+        account.expires_at = Utc::now() - chrono::Duration::minutes(10);
+        assert!(account.is_token_expiring(0));
+    }
+
+    #[test]
+    #[ignore] // 需要在 Windows 环境运行
+    fn test_update_access_token() {
+        let mut account = OAuthAccount::new(
+            "test@gmail.com".to_string(),
+            "Test User".to_string(),
+            "old_token".to_string(),
+            "refresh".to_string(),
+            3600,
+        )
+        .expect("创建账户失败");
+
+        let old_expires = account.expires_at;
+
+        // 等待 1 秒确保时间戳变化
+        std::thread::sleep(std::time::Duration::from_secs(1));
+
+        // 更新 Token
+        account
+            .update_access_token("new_token".to_string(), 7200)
+            .unwrap();
+
+        // 验证
+        assert!(crypto::is_encrypted(&account.access_token));
+        assert_ne!(account.expires_at, old_expires);
+
+        let decrypted = account.decrypt_access_token().unwrap();
+        assert_eq!(decrypted, "new_token");
+    }
+
+    #[test]
+    fn test_convert_to_slint_account() {
+        let oauth_account = OAuthAccount::new(
+            "test@gmail.com".to_string(),
+            "Test User".to_string(),
+            "token".to_string(),
+            "refresh".to_string(),
+            3600,
+        )
+        .expect("创建账户失败");
+
+        let slint_account: crate::Account = oauth_account.into();
+
+        assert_eq!(slint_account.email.as_str(), "test@gmail.com");
+        assert_eq!(slint_account.display_name.as_str(), "Test User");
+        assert_eq!(slint_account.unread_count, 0);
+        assert!(!slint_account.is_loading);
+        assert_eq!(slint_account.sync_state.as_str(), "never");
+        assert_eq!(slint_account.count_mode.as_str(), "all_unread");
+        assert!(!slint_account.snoozed);
+    }
+
+    #[test]
+    fn test_count_mode_defaults_for_existing_accounts() {
+        let account: OAuthAccount = toml::from_str(
+            r#"
+email = "user@gmail.com"
+display_name = "User"
+access_token = "encrypted:AAAA"
+refresh_token = "encrypted:BBBB"
+expires_at = "2030-01-01T00:00:00Z"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(account.count_mode, CountMode::AllUnread);
+        assert!(account.counted_since.is_none());
+        assert!(account.unread_baseline().is_none());
+        assert!(account.snoozed_until.is_none());
+    }
+
+    #[test]
+    fn test_count_mode_baseline() {
+        let mut account: OAuthAccount = toml::from_str(
+            r#"
+email = "user@gmail.com"
+display_name = "User"
+access_token = "encrypted:AAAA"
+refresh_token = "encrypted:BBBB"
+expires_at = "2030-01-01T00:00:00Z"
+count_mode = "since_added"
+counted_since = "2026-01-01T00:00:00Z"
+"#,
+        )
+        .unwrap();
+
+        let added = account.counted_since;
+        assert_eq!(account.count_mode, CountMode::SinceAdded);
+        assert_eq!(account.unread_baseline(), added);
+
+        // 切回全部未读时保留基准时间，再切换回来仍以添加时间为准
+        account.set_count_mode(CountMode::AllUnread);
+        assert!(account.unread_baseline().is_none());
+        account.set_count_mode(CountMode::SinceAdded);
+        assert_eq!(account.unread_baseline(), added);
+
+        // 重置计数后基准移到当前时间
+        account.set_count_mode(CountMode::AllUnread);
+        account.reset_unread_baseline();
+        assert_eq!(account.count_mode, CountMode::SinceAdded);
+        assert!(account.unread_baseline() > added);
+    }
+}
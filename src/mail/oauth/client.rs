@@ -0,0 +1,99 @@
+/// OAuth2 客户端参数
+///
+/// 描述一个邮件服务的授权端点与凭据，授权流程与 Token 刷新都通过 [`client_builder`] 由此构造 oauth2 客户端
+use anyhow::Result;
+use oauth2::{AuthUrl, ClientId, ClientSecret, RedirectUrl, TokenUrl, basic::BasicClient};
+
+use super::RedirectPort;
+
+/// OAuth2 服务端点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthEndpoints {
+    /// 授权端点
+    pub auth: String,
+
+    /// Token 端点
+    pub token: String,
+}
+
+/// OAuth2 客户端参数
+#[derive(Debug, Clone)]
+pub struct OAuthClient {
+    /// 客户端 ID
+    pub client_id: String,
+
+    /// 客户端密钥（公共客户端只使用 PKCE，为 `None`）
+    pub client_secret: Option<String>,
+
+    /// 授权与 Token 端点
+    pub endpoints: OAuthEndpoints,
+
+    /// 请求的权限范围
+    pub scopes: Vec<String>,
+
+    /// 增量授权：授权页面附带 `include_granted_scopes=true`，新 Token 同时包含此前授予的权限（Google）
+    pub include_granted_scopes: bool,
+
+    /// 授权时本地回调服务器的端口选择（Token 刷新不使用）
+    pub redirect_port: RedirectPort,
+}
+
+/// 开始构造 oauth2 客户端
+///
+/// 默认附带配置中的客户端密钥、不设回调地址（Token 刷新）；授权码流程用 [`OAuthClientBuilder::with_redirect`]
+/// 设置与授权请求一致的回调地址，PKCE-only 重试用 [`OAuthClientBuilder::public`] 去掉客户端密钥
+pub fn client_builder(config: &OAuthClient) -> OAuthClientBuilder<'_> {
+    OAuthClientBuilder {
+        config,
+        redirect_port: None,
+        public: false,
+    }
+}
+
+/// oauth2 客户端构造器（见 [`client_builder`]）
+#[derive(Debug)]
+pub struct OAuthClientBuilder<'a> {
+    config: &'a OAuthClient,
+    redirect_port: Option<u16>,
+    public: bool,
+}
+
+impl OAuthClientBuilder<'_> {
+    /// 回调地址为本地回调服务器的端口（授权码流程需要，交换 Token 时必须与授权请求一致）
+    pub fn with_redirect(mut self, port: u16) -> Self {
+        self.redirect_port = Some(port);
+        self
+    }
+
+    /// 公共客户端：不附带客户端密钥，只使用 PKCE
+    pub fn public(mut self) -> Self {
+        self.public = true;
+        self
+    }
+
+    /// 构造 oauth2 客户端
+    pub fn build(self) -> Result<BasicClient> {
+        let secret = if self.public {
+            None
+        } else {
+            self.config.client_secret.clone().map(ClientSecret::new)
+        };
+
+        let client = BasicClient::new(
+            ClientId::new(self.config.client_id.clone()),
+            secret,
+            AuthUrl::new(self.config.endpoints.auth.clone())?,
+            Some(TokenUrl::new(self.config.endpoints.token.clone())?),
+        );
+
+        Ok(match self.redirect_port {
+            Some(port) => client.set_redirect_uri(RedirectUrl::new(redirect_uri(port))?),
+            None => client,
+        })
+    }
+}
+
+/// 本地回调地址
+fn redirect_uri(port: u16) -> String {
+    format!("http://localhost:{}", port)
+}
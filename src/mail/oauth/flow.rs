@@ -0,0 +1,1012 @@
+/// OAuth2 授权码流程（带 PKCE）
+///
+/// 打开浏览器授权、在本地端口接收回调并交换 Token，与具体邮件服务无关
+use anyhow::{Context, Result};
+use oauth2::{
+    AuthorizationCode, CsrfToken, PkceCodeChallenge, PkceCodeVerifier, Scope, TokenResponse,
+};
+use std::ops::RangeInclusive;
+use std::time::Duration;
+use tiny_http::{Header, Response, Server};
+use url::Url;
+
+use super::{OAuthClient, client_builder};
+use crate::utils::browser::{self, UrlPurpose};
+use crate::utils::http_client;
+use crate::utils::netstat::{self, PortOwner};
+use crate::utils::redact::redact_token;
+
+/// OAuth2 回调超时时间（秒）
+const CALLBACK_TIMEOUT_SECS: u64 = 60;
+
+/// 本地服务器默认端口范围
+const PORT_RANGE: RangeInclusive<u16> = 8080..=8089;
+
+/// 回调地址的最大长度（授权码与 state 都远小于此）
+const MAX_URL_LEN: usize = 4096;
+
+/// 回调请求体的最大长度（回调是 GET 请求，不应携带请求体）
+const MAX_BODY_LEN: usize = 1024;
+
+/// OAuth2 成功页面 HTML
+const SUCCESS_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>授权成功 - NanoMail</title>
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Arial, sans-serif;
+            display: flex;
+            justify-content: center;
+            align-items: center;
+            height: 100vh;
+            margin: 0;
+            background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
+        }
+        .container {
+            background: white;
+            padding: 40px;
+            border-radius: 12px;
+            box-shadow: 0 10px 40px rgba(0,0,0,0.2);
+            text-align: center;
+            max-width: 400px;
+        }
+        h1 {
+            color: #667eea;
+            margin-bottom: 20px;
+        }
+        p {
+            color: #666;
+            line-height: 1.6;
+        }
+        .checkmark {
+            font-size: 64px;
+            color: #4caf50;
+        }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="checkmark">✓</div>
+        <h1>授权成功</h1>
+        <p>您的邮箱账户已成功连接到 NanoMail。</p>
+        <p>现在可以关闭此页面并返回应用程序。</p>
+    </div>
+</body>
+</html>"#;
+
+/// 用户取消授权时的页面 HTML
+const DECLINED_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>已取消授权 - NanoMail</title>
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Arial, sans-serif;
+            display: flex;
+            justify-content: center;
+            align-items: center;
+            height: 100vh;
+            margin: 0;
+            background: linear-gradient(135deg, #f093fb 0%, #f5576c 100%);
+        }
+        .container {
+            background: white;
+            padding: 40px;
+            border-radius: 12px;
+            box-shadow: 0 10px 40px rgba(0,0,0,0.2);
+            text-align: center;
+            max-width: 400px;
+        }
+        h1 {
+            color: #f5576c;
+            margin-bottom: 20px;
+        }
+        p {
+            color: #666;
+            line-height: 1.6;
+        }
+        .cross {
+            font-size: 64px;
+            color: #f44336;
+        }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="cross">✗</div>
+        <h1>已取消授权</h1>
+        <p>邮箱账户没有连接到 NanoMail。</p>
+        <p>可以关闭此页面，需要时在应用中重新添加账户。</p>
+    </div>
+</body>
+</html>"#;
+
+/// OAuth2 错误页面 HTML
+const ERROR_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>授权失败 - NanoMail</title>
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Arial, sans-serif;
+            display: flex;
+            justify-content: center;
+            align-items: center;
+            height: 100vh;
+            margin: 0;
+            background: linear-gradient(135deg, #f093fb 0%, #f5576c 100%);
+        }
+        .container {
+            background: white;
+            padding: 40px;
+            border-radius: 12px;
+            box-shadow: 0 10px 40px rgba(0,0,0,0.2);
+            text-align: center;
+            max-width: 400px;
+        }
+        h1 {
+            color: #f5576c;
+            margin-bottom: 20px;
+        }
+        p {
+            color: #666;
+            line-height: 1.6;
+        }
+        .cross {
+            font-size: 64px;
+            color: #f44336;
+        }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="cross">✗</div>
+        <h1>授权失败</h1>
+        <p>授权请求无效或已过期，邮箱账户没有连接到 NanoMail。</p>
+        <p>请关闭此页面，回到应用中重新添加账户。</p>
+    </div>
+</body>
+</html>"#;
+
+/// 授权得到的 Token（明文，调用方应立即加密保存）
+pub struct TokenSet {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// 有效期（秒）
+    pub expires_in: i64,
+    /// 授予的权限范围（服务未返回时为请求的范围）
+    pub scopes: Vec<String>,
+}
+
+/// 授权流程的结果
+pub enum AuthOutcome {
+    /// 用户同意授权，已换得 Token
+    Authorized(TokenSet),
+
+    /// 用户在授权页面取消或拒绝
+    Declined,
+}
+
+/// 本地回调服务器的端口选择
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedirectPort {
+    /// 依次尝试默认端口范围（8080-8089），服务端需登记这些回调地址
+    #[default]
+    Range,
+
+    /// 配置中指定的端口（`[oauth]` 的 `redirect_port`）
+    Fixed(u16),
+
+    /// 由系统分配空闲端口（服务端允许回环地址使用任意端口，如 Google 桌面应用客户端）
+    Any,
+}
+
+impl RedirectPort {
+    /// 按配置的回调地址与自定义端口确定端口选择
+    ///
+    /// - 指定了 `redirect_port`：回调地址中写明的端口必须与之相同，否则授权时会因回调地址不匹配被拒绝
+    /// - 回调地址不带端口（如 `http://localhost`）：由系统分配端口
+    /// - 其余情况：使用默认端口范围
+    pub fn from_config(redirect_uri: &str, redirect_port: Option<u16>) -> Result<Self> {
+        let registered = Url::parse(redirect_uri)
+            .with_context(|| format!("redirect_uri 无效: {}", redirect_uri))?
+            .port();
+
+        match (redirect_port, registered) {
+            (Some(0), _) => anyhow::bail!("redirect_port 不能为 0"),
+            (Some(port), Some(registered)) if port != registered => anyhow::bail!(
+                "redirect_port（{}）与 redirect_uri（{}）中的端口不一致，\
+                 请同时修改 redirect_uri 与服务端登记的回调地址",
+                port,
+                redirect_uri
+            ),
+            (Some(port), _) => Ok(Self::Fixed(port)),
+            (None, None) => Ok(Self::Any),
+            (None, Some(_)) => Ok(Self::Range),
+        }
+    }
+
+    /// 依次尝试的端口（0 表示由系统分配）
+    fn candidates(self) -> RangeInclusive<u16> {
+        match self {
+            Self::Range => PORT_RANGE,
+            Self::Fixed(port) => port..=port,
+            Self::Any => 0..=0,
+        }
+    }
+}
+
+/// 回调端口全部被占用
+///
+/// 附带占用端口的进程，界面据此提示用户关闭这些程序或改用自定义端口
+#[derive(Debug, thiserror::Error)]
+#[error("无法启动本地服务器（端口 {} 均被占用）: {last_error}", port_list(.ports))]
+pub struct PortsOccupied {
+    /// 尝试过的端口
+    pub ports: RangeInclusive<u16>,
+
+    /// 占用这些端口的进程（查询失败时为空）
+    pub owners: Vec<PortOwner>,
+
+    /// 最后一个端口的监听错误
+    last_error: String,
+}
+
+impl PortsOccupied {
+    /// 端口列表的显示形式（如 `8080-8089`）
+    pub fn port_list(&self) -> String {
+        port_list(&self.ports)
+    }
+}
+
+fn port_list(ports: &RangeInclusive<u16>) -> String {
+    if ports.start() == ports.end() {
+        ports.start().to_string()
+    } else {
+        format!("{}-{}", ports.start(), ports.end())
+    }
+}
+
+/// 本地服务器收到的回调（state 已验证）
+#[derive(Debug, PartialEq, Eq)]
+enum Callback {
+    /// 授权码
+    Code(String),
+
+    /// 用户取消或拒绝授权（`error=access_denied`）
+    Declined,
+
+    /// 授权服务返回的其他错误（附错误码，如 `invalid_scope`、`server_error`）
+    Error(String),
+}
+
+/// 执行 OAuth2 授权码流程
+///
+/// 1. 启动本地服务器
+/// 2. 生成授权 URL
+/// 3. 打开浏览器
+/// 4. 等待回调并验证 CSRF state
+/// 5. 交换 Token
+///
+/// 用户在授权页面取消时返回 [`AuthOutcome::Declined`]，不视为错误。
+/// 无法打开浏览器时不中止：显示授权链接供用户手动打开，继续等待回调
+///
+/// # Errors
+/// - 无法启动本地服务器（端口全部被占用时为 [`PortsOccupied`]）
+/// - 等待回调超时
+/// - 授权服务返回 `access_denied` 以外的错误
+/// - Token 交换失败
+/// - 网络错误
+pub async fn authorize(client: &OAuthClient) -> Result<AuthOutcome> {
+    // 步骤 1：启动本地服务器（等待结束时随之关闭，释放端口供下次使用）
+    let (server, port) = bind_local_server(client.redirect_port)?;
+    tracing::info!("✅ 本地服务器启动成功: http://localhost:{}", port);
+
+    // 步骤 2：生成授权 URL
+    let (auth_url, csrf_state, pkce_verifier) = build_auth_url(client, port)?;
+    tracing::info!("✅ 授权 URL 生成成功");
+    tracing::debug!("授权 URL: {}", auth_url);
+
+    // 步骤 3：打开浏览器（失败时弹出对话框显示授权链接，仍等待回调）
+    if browser::open_url(auth_url.as_str(), UrlPurpose::Authorization) {
+        tracing::info!("✅ 浏览器已打开，等待用户授权...");
+    } else {
+        tracing::info!("已显示授权链接，等待用户在浏览器中手动打开...");
+    }
+
+    // 步骤 4：等待回调（带超时）
+    let expected_state = csrf_state.secret().clone();
+    let callback = tokio::task::spawn_blocking(move || {
+        wait_for_callback(
+            server,
+            &expected_state,
+            Duration::from_secs(CALLBACK_TIMEOUT_SECS),
+        )
+    })
+    .await
+    .context("本地服务器线程异常退出")?
+    .context("本地服务器接收回调失败")?;
+
+    // state 已在本地服务器中验证（CSRF），不匹配的回调不会到达这里
+    let received_code = match callback {
+        Some(Callback::Code(code)) => code,
+        Some(Callback::Declined) => {
+            tracing::info!("用户取消了授权");
+            return Ok(AuthOutcome::Declined);
+        }
+        Some(Callback::Error(code)) => anyhow::bail!("授权服务返回错误: {}", code),
+        None => anyhow::bail!("授权超时：用户未在规定时间内完成授权"),
+    };
+
+    tracing::info!("✅ 收到授权回调，CSRF 验证通过");
+
+    // 步骤 5：交换 Token
+    let token_response = exchange_code_for_token(
+        AuthorizationCode::new(received_code),
+        pkce_verifier,
+        client,
+        port,
+    )
+    .await
+    .context("Token 交换失败")?;
+
+    let access_token = token_response.access_token().secret().to_string();
+    let refresh_token = token_response
+        .refresh_token()
+        .ok_or_else(|| anyhow::anyhow!("未收到 refresh_token"))?
+        .secret()
+        .to_string();
+
+    let expires_in = token_response
+        .expires_in()
+        .unwrap_or(Duration::from_secs(3600))
+        .as_secs() as i64;
+
+    let scopes = match token_response.scopes() {
+        Some(scopes) => scopes
+            .iter()
+            .map(|scope| scope.as_str().to_string())
+            .collect(),
+        None => client.scopes.clone(),
+    };
+
+    tracing::info!("✅ Token 交换成功");
+    tracing::debug!(
+        "Access Token: {} (有效期: {} 秒)",
+        redact_token(&access_token),
+        expires_in
+    );
+
+    Ok(AuthOutcome::Authorized(TokenSet {
+        access_token,
+        refresh_token,
+        expires_in,
+        scopes,
+    }))
+}
+
+/// 启动本地服务器，返回服务器及实际使用的端口
+///
+/// 只监听 127.0.0.1，不接受来自其他主机的连接。候选端口全部被占用时返回 [`PortsOccupied`]，
+/// 附带占用端口的进程
+fn bind_local_server(redirect_port: RedirectPort) -> Result<(Server, u16)> {
+    let ports = redirect_port.candidates();
+    let bound = probe_ports(ports.clone(), |port| {
+        let server = Server::http(("127.0.0.1", port))?;
+        // 系统分配端口时取实际监听的端口
+        let port = server
+            .server_addr()
+            .to_ip()
+            .map_or(port, |addr| addr.port());
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>((server, port))
+    });
+
+    match bound {
+        Ok((server, port)) => {
+            tracing::debug!("本地服务器监听: 127.0.0.1:{}", port);
+            Ok((server, port))
+        }
+        Err(last_error) if redirect_port == RedirectPort::Any => {
+            Err(anyhow::anyhow!("无法启动本地服务器: {}", last_error))
+        }
+        Err(last_error) => {
+            let owners = netstat::port_owners(&ports.clone().collect::<Vec<_>>());
+            for owner in &owners {
+                tracing::warn!("回调端口被占用: {}", owner);
+            }
+            Err(PortsOccupied {
+                ports,
+                owners,
+                last_error,
+            }
+            .into())
+        }
+    }
+}
+
+/// 依次尝试候选端口，返回第一个监听成功的结果；全部失败时返回最后一个错误
+fn probe_ports<T, E: std::fmt::Display>(
+    ports: RangeInclusive<u16>,
+    mut bind: impl FnMut(u16) -> std::result::Result<T, E>,
+) -> std::result::Result<T, String> {
+    let mut last_error = String::new();
+    for port in ports {
+        match bind(port) {
+            Ok(bound) => return Ok(bound),
+            Err(e) => {
+                tracing::debug!("端口 {} 不可用: {}", port, e);
+                last_error = e.to_string();
+            }
+        }
+    }
+    Err(last_error)
+}
+
+/// 生成授权 URL
+///
+/// 使用 PKCE (RFC 7636) 提升安全性
+fn build_auth_url(client: &OAuthClient, port: u16) -> Result<(Url, CsrfToken, PkceCodeVerifier)> {
+    let oauth_client = client_builder(client).with_redirect(port).build()?;
+
+    // 生成 PKCE 挑战
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+    // 生成授权 URL
+    let mut request = oauth_client
+        .authorize_url(CsrfToken::new_random)
+        .add_scopes(client.scopes.iter().map(|s| Scope::new(s.clone())))
+        .set_pkce_challenge(pkce_challenge);
+    if client.include_granted_scopes {
+        request = request.add_extra_param("include_granted_scopes", "true");
+    }
+    let (auth_url, csrf_state) = request.url();
+
+    Ok((auth_url, csrf_state, pkce_verifier))
+}
+
+/// 等待 OAuth2 回调
+///
+/// 收到 state 匹配的授权码或取消授权的回调后返回；超时返回 `None`。
+/// 无效的请求按 [`CallbackHandler`] 的判断回应后继续等待；
+/// 收到回调后，已在排队的请求一律回应 410。服务器在函数返回时关闭，端口随即释放
+fn wait_for_callback(
+    server: Server,
+    expected_state: &str,
+    timeout: Duration,
+) -> Result<Option<Callback>> {
+    let deadline = std::time::Instant::now() + timeout;
+    let mut handler = CallbackHandler::new(expected_state);
+
+    loop {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+        let Some(request) = server.recv_timeout(remaining).context("接收回调请求失败")?
+        else {
+            return Ok(None);
+        };
+
+        let (status, callback) = handler.handle(request.url(), request.body_length());
+        respond(request, status, callback.as_ref());
+
+        if callback.is_some() {
+            // 授权码已交付：重放或重复的请求不再处理
+            while let Ok(Some(request)) = server.try_recv() {
+                respond(request, 410, None);
+            }
+            return Ok(callback);
+        }
+    }
+}
+
+/// 回应一个回调请求
+fn respond(request: tiny_http::Request, status: u16, callback: Option<&Callback>) {
+    let page = match (status, callback) {
+        (200, Some(Callback::Code(_))) => Some(SUCCESS_HTML),
+        (200, Some(Callback::Declined)) => Some(DECLINED_HTML),
+        (200, Some(Callback::Error(_))) | (400, _) => Some(ERROR_HTML),
+        _ => None,
+    };
+
+    let result = match page {
+        Some(page) => {
+            let header = Header::from_bytes(b"Content-Type", b"text/html; charset=utf-8").unwrap();
+            request.respond(
+                Response::from_string(page)
+                    .with_status_code(status)
+                    .with_header(header),
+            )
+        }
+        None => request.respond(Response::empty(status)),
+    };
+
+    // 页面发送失败不影响结果：浏览器可能已关闭
+    if let Err(e) = result {
+        tracing::warn!("回应授权回调失败: {}", e);
+    }
+}
+
+/// 回调请求的判断逻辑（与网络无关，便于测试）
+struct CallbackHandler {
+    /// 期望的 CSRF state
+    expected_state: String,
+
+    /// 是否已交付回调
+    delivered: bool,
+}
+
+impl CallbackHandler {
+    fn new(expected_state: &str) -> Self {
+        Self {
+            expected_state: expected_state.to_string(),
+            delivered: false,
+        }
+    }
+
+    /// 判断一个请求，返回 HTTP 状态码，以及需要交付的回调
+    ///
+    /// - 已交付过回调：410
+    /// - 地址或请求体过长：414 / 413
+    /// - 不带回调参数的请求（如 favicon）：404
+    /// - 缺少参数或 state 不匹配：400（显示错误页面，继续等待）
+    fn handle(&mut self, url: &str, body_length: Option<usize>) -> (u16, Option<Callback>) {
+        if self.delivered {
+            tracing::warn!("授权回调已处理，拒绝重复请求");
+            return (410, None);
+        }
+        if url.len() > MAX_URL_LEN {
+            tracing::warn!("回调地址过长（{} 字节），已拒绝", url.len());
+            return (414, None);
+        }
+        if body_length.unwrap_or(0) > MAX_BODY_LEN {
+            tracing::warn!("回调请求体过大，已拒绝");
+            return (413, None);
+        }
+
+        let Some(params) = callback_params(url) else {
+            tracing::debug!("忽略非回调请求: {}", url);
+            return (404, None);
+        };
+
+        // 授权码与取消授权都必须带有本次发起时的 state，否则可能是伪造的请求
+        let state_ok = params
+            .get("state")
+            .is_some_and(|state| *state == self.expected_state);
+        if !state_ok {
+            tracing::warn!("CSRF 验证失败：回调 state 缺失或不匹配");
+            return (400, None);
+        }
+
+        let callback = if let Some(error) = params.get("error") {
+            if error == "access_denied" {
+                Callback::Declined
+            } else {
+                tracing::warn!("授权服务返回错误: {}", error);
+                Callback::Error(error.clone())
+            }
+        } else if let Some(code) = params.get("code") {
+            tracing::debug!("Code: {}", redact_token(code));
+            Callback::Code(code.clone())
+        } else {
+            tracing::warn!("回调缺少 code 参数");
+            return (400, None);
+        };
+
+        self.delivered = true;
+        (200, Some(callback))
+    }
+}
+
+/// 解析回调地址的查询参数（不含 `code`、`state`、`error` 任一参数时返回 `None`）
+fn callback_params(path: &str) -> Option<std::collections::HashMap<String, String>> {
+    let url = Url::parse("http://localhost").ok()?.join(path).ok()?;
+    let params: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+    ["code", "state", "error"]
+        .iter()
+        .any(|key| params.contains_key(*key))
+        .then_some(params)
+}
+
+/// 交换授权码为 Token
+async fn exchange_code_for_token(
+    code: AuthorizationCode,
+    verifier: PkceCodeVerifier,
+    client: &OAuthClient,
+    port: u16,
+) -> Result<
+    oauth2::StandardTokenResponse<oauth2::EmptyExtraTokenFields, oauth2::basic::BasicTokenType>,
+> {
+    // 使用实际的 redirect_uri（带端口号）
+    let oauth_client = client_builder(client).with_redirect(port).build()?;
+
+    tracing::debug!("交换 Token：client_id={}", redact_token(&client.client_id));
+
+    // 为了支持重试（不带 client_secret 的 PKCE-only），先把 code/verifier 的字符串保存下来，
+    // 每次重试都重新构造对应对象（AuthorizationCode/ PkceCodeVerifier）
+    let code_secret = code.secret().to_string();
+    let verifier_secret = verifier.secret().to_string();
+
+    // 首次尝试（配置了 client_secret 时附带）
+    let first_code = AuthorizationCode::new(code_secret.clone());
+    let first_verifier = PkceCodeVerifier::new(verifier_secret.clone());
+
+    match oauth_client
+        .exchange_code(first_code)
+        .set_pkce_verifier(first_verifier)
+        .request_async(http_client::oauth_http_client)
+        .await
+    {
+        Ok(tok) => Ok(tok),
+        Err(e) => {
+            tracing::error!("Token 交换详细错误: {:?}", e);
+
+            let err_str = format!("{:?}", e);
+
+            // 如果是 invalid_client/Unauthorized，尝试不带 client_secret 的 PKCE-only 重试（适配部分 native 客户端配置）
+            if client.client_secret.is_some()
+                && (err_str.contains("invalid_client") || err_str.contains("Unauthorized"))
+            {
+                tracing::warn!(
+                    "首次交换返回 invalid_client/Unauthorized，尝试使用不带 client_secret 的公共客户端重试（PKCE-only）"
+                );
+
+                let client_public = client_builder(client)
+                    .with_redirect(port)
+                    .public()
+                    .build()?;
+
+                let retry_code = AuthorizationCode::new(code_secret);
+                let retry_verifier = PkceCodeVerifier::new(verifier_secret);
+
+                return match client_public
+                    .exchange_code(retry_code)
+                    .set_pkce_verifier(retry_verifier)
+                    .request_async(http_client::oauth_http_client)
+                    .await
+                {
+                    Ok(tok2) => Ok(tok2),
+                    Err(e2) => {
+                        tracing::error!("使用 PKCE-only 重试仍失败: {:?}", e2);
+                        Err(anyhow::anyhow!("Token 交换失败: {}", e2))
+                    }
+                };
+            }
+
+            Err(anyhow::anyhow!("Token 交换请求失败: {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::gmail::mock::{MockGmail, MockResponse};
+    use crate::mail::oauth::OAuthEndpoints;
+
+    #[test]
+    fn test_port_range() {
+        assert!(PORT_RANGE.contains(&8080));
+        assert!(PORT_RANGE.contains(&8089));
+        assert!(!PORT_RANGE.contains(&8090));
+    }
+
+    #[test]
+    fn test_probe_skips_occupied_ports() {
+        let occupied = [8080, 8081, 8082];
+        let mut attempts = Vec::new();
+        let bound = probe_ports(PORT_RANGE, |port| {
+            attempts.push(port);
+            if occupied.contains(&port) {
+                Err(format!("端口 {} 已被占用", port))
+            } else {
+                Ok(port)
+            }
+        });
+        assert_eq!(bound, Ok(8083));
+        assert_eq!(attempts, [8080, 8081, 8082, 8083]);
+    }
+
+    #[test]
+    fn test_probe_reports_last_error_when_all_occupied() {
+        let mut attempts = 0;
+        let bound = probe_ports(PORT_RANGE, |port| {
+            attempts += 1;
+            Err::<(), _>(format!("端口 {} 已被占用", port))
+        });
+        assert_eq!(bound, Err("端口 8089 已被占用".to_string()));
+        assert_eq!(attempts, 10);
+
+        let bound = probe_ports(RedirectPort::Fixed(9000).candidates(), |port| {
+            Err::<(), _>(port)
+        });
+        assert_eq!(bound, Err("9000".to_string()));
+    }
+
+    #[test]
+    fn test_redirect_port_from_config() {
+        assert_eq!(
+            RedirectPort::from_config("http://localhost:8080", None).unwrap(),
+            RedirectPort::Range
+        );
+        assert_eq!(
+            RedirectPort::from_config("http://localhost", None).unwrap(),
+            RedirectPort::Any
+        );
+        assert_eq!(
+            RedirectPort::from_config("http://localhost:9123", Some(9123)).unwrap(),
+            RedirectPort::Fixed(9123)
+        );
+        assert_eq!(
+            RedirectPort::from_config("http://127.0.0.1", Some(9123)).unwrap(),
+            RedirectPort::Fixed(9123)
+        );
+
+        // 与登记的回调地址不一致的端口在打开浏览器之前就拒绝
+        assert!(RedirectPort::from_config("http://localhost:8080", Some(9123)).is_err());
+        assert!(RedirectPort::from_config("http://localhost", Some(0)).is_err());
+        assert!(RedirectPort::from_config("not a url", None).is_err());
+    }
+
+    #[test]
+    fn test_ports_occupied_message() {
+        let error = PortsOccupied {
+            ports: PORT_RANGE,
+            owners: Vec::new(),
+            last_error: "os error 10048".to_string(),
+        };
+        assert_eq!(error.port_list(), "8080-8089");
+        assert_eq!(
+            error.to_string(),
+            "无法启动本地服务器（端口 8080-8089 均被占用）: os error 10048"
+        );
+
+        let error = anyhow::Error::from(PortsOccupied {
+            ports: 9123..=9123,
+            owners: Vec::new(),
+            last_error: String::new(),
+        })
+        .context("授权失败");
+        let occupied = error.downcast_ref::<PortsOccupied>().unwrap();
+        assert_eq!(occupied.port_list(), "9123");
+    }
+
+    #[test]
+    fn test_fixed_port_occupied() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let Err(error) = bind_local_server(RedirectPort::Fixed(port)) else {
+            panic!("端口 {} 已被占用，应当绑定失败", port);
+        };
+        let occupied = error.downcast_ref::<PortsOccupied>().unwrap();
+        assert_eq!(occupied.ports, port..=port);
+
+        drop(listener);
+        let (_server, bound) = bind_local_server(RedirectPort::Any).unwrap();
+        assert_ne!(bound, 0);
+    }
+
+    #[test]
+    fn test_auth_url_include_granted_scopes() {
+        let mut client = OAuthClient {
+            client_id: "client-id".to_string(),
+            client_secret: None,
+            endpoints: OAuthEndpoints {
+                auth: "https://example.com/auth".to_string(),
+                token: "https://example.com/token".to_string(),
+            },
+            scopes: vec!["openid".to_string()],
+            include_granted_scopes: false,
+            redirect_port: RedirectPort::Range,
+        };
+        let has_param = |client: &OAuthClient| {
+            let (url, _, _) = build_auth_url(client, 8080).unwrap();
+            url.query_pairs()
+                .any(|(key, value)| key == "include_granted_scopes" && value == "true")
+        };
+
+        assert!(!has_param(&client));
+        client.include_granted_scopes = true;
+        assert!(has_param(&client));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_retries_without_secret_after_invalid_client() {
+        let server = MockGmail::start().await;
+        server.on_token(MockResponse::json(
+            401,
+            serde_json::json!({ "error": "invalid_client" }),
+        ));
+        server.on_token(MockResponse::json(
+            200,
+            serde_json::json!({
+                "access_token": "new-token",
+                "token_type": "Bearer",
+                "expires_in": 3600
+            }),
+        ));
+        let client = OAuthClient {
+            client_id: "client-id".to_string(),
+            client_secret: Some("client-secret".to_string()),
+            endpoints: OAuthEndpoints {
+                auth: format!("{}/auth", server.base_url()),
+                token: server.token_url(),
+            },
+            scopes: Vec::new(),
+            include_granted_scopes: false,
+            redirect_port: RedirectPort::Range,
+        };
+
+        let token = exchange_code_for_token(
+            AuthorizationCode::new("auth-code".to_string()),
+            PkceCodeVerifier::new("pkce-verifier".to_string()),
+            &client,
+            8080,
+        )
+        .await
+        .unwrap();
+        assert_eq!(token.access_token().secret(), "new-token");
+
+        // 首次附带客户端密钥（HTTP Basic），invalid_client 后只用 PKCE 重试；两次的回调地址与授权码相同
+        let requests = server.requests("/token");
+        assert_eq!(requests.len(), 2);
+        assert!(
+            requests[0]
+                .authorization
+                .as_deref()
+                .is_some_and(|auth| auth.starts_with("Basic "))
+        );
+        assert_eq!(requests[1].authorization, None);
+        assert!(requests[1].body.contains("client_id=client-id"));
+        for request in &requests {
+            assert!(
+                request
+                    .body
+                    .contains("redirect_uri=http%3A%2F%2Flocalhost%3A8080")
+            );
+            assert!(request.body.contains("code=auth-code"));
+            assert!(request.body.contains("code_verifier=pkce-verifier"));
+        }
+    }
+
+    #[test]
+    fn test_html_contains_charset() {
+        assert!(SUCCESS_HTML.contains("utf-8"));
+        assert!(ERROR_HTML.contains("utf-8"));
+        assert!(DECLINED_HTML.contains("utf-8"));
+    }
+
+    #[test]
+    fn test_handler_accepts_matching_state() {
+        let mut handler = CallbackHandler::new("xyz");
+        assert_eq!(
+            handler.handle("/?code=abc&state=xyz&scope=email", None),
+            (200, Some(Callback::Code("abc".to_string())))
+        );
+
+        let mut handler = CallbackHandler::new("xyz");
+        assert_eq!(
+            handler.handle("/?error=access_denied&state=xyz", None),
+            (200, Some(Callback::Declined))
+        );
+    }
+
+    #[test]
+    fn test_handler_reports_other_errors_as_failures() {
+        let mut handler = CallbackHandler::new("xyz");
+        assert_eq!(
+            handler.handle("/?error=invalid_scope&state=xyz", None),
+            (200, Some(Callback::Error("invalid_scope".to_string())))
+        );
+
+        let mut handler = CallbackHandler::new("xyz");
+        assert_eq!(
+            handler.handle("/?error=server_error&state=forged", None),
+            (400, None)
+        );
+    }
+
+    #[test]
+    fn test_handler_rejects_wrong_state_without_consuming() {
+        let mut handler = CallbackHandler::new("xyz");
+        assert_eq!(handler.handle("/?code=abc&state=forged", None), (400, None));
+        assert_eq!(
+            handler.handle("/?error=access_denied&state=forged", None),
+            (400, None)
+        );
+
+        // 之后真正的回调仍然可以交付
+        assert_eq!(
+            handler.handle("/?code=abc&state=xyz", None),
+            (200, Some(Callback::Code("abc".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_handler_rejects_missing_parameters() {
+        let mut handler = CallbackHandler::new("xyz");
+        assert_eq!(handler.handle("/favicon.ico", None), (404, None));
+        assert_eq!(handler.handle("/?code=abc", None), (400, None));
+        assert_eq!(handler.handle("/?state=xyz", None), (400, None));
+    }
+
+    #[test]
+    fn test_handler_rejects_oversized_requests() {
+        let mut handler = CallbackHandler::new("xyz");
+        let long_url = format!("/?code={}&state=xyz", "a".repeat(MAX_URL_LEN));
+        assert_eq!(handler.handle(&long_url, None), (414, None));
+        assert_eq!(
+            handler.handle("/?code=abc&state=xyz", Some(MAX_BODY_LEN + 1)),
+            (413, None)
+        );
+    }
+
+    #[test]
+    fn test_handler_rejects_replay() {
+        let mut handler = CallbackHandler::new("xyz");
+        assert_eq!(handler.handle("/?code=abc&state=xyz", None).0, 200);
+        assert_eq!(handler.handle("/?code=abc&state=xyz", None), (410, None));
+        assert_eq!(handler.handle("/favicon.ico", None), (410, None));
+    }
+
+    /// 向本地服务器发送一个 GET 请求，返回响应状态行
+    fn get(port: u16, path: &str) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            path
+        )
+        .unwrap();
+
+        let mut status = String::new();
+        BufReader::new(stream).read_line(&mut status).unwrap();
+        status
+    }
+
+    #[test]
+    fn test_declined_callback_releases_port() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let port = server.server_addr().to_ip().unwrap().port();
+
+        let client = std::thread::spawn(move || {
+            let favicon = get(port, "/favicon.ico");
+            let forged = get(port, "/?error=access_denied&state=forged");
+            let declined = get(port, "/?error=access_denied&state=xyz");
+            (favicon, forged, declined)
+        });
+
+        let outcome = wait_for_callback(server, "xyz", Duration::from_secs(10)).unwrap();
+        assert_eq!(outcome, Some(Callback::Declined));
+
+        let (favicon, forged, declined) = client.join().unwrap();
+        assert!(favicon.contains(" 404"));
+        assert!(forged.contains(" 400"));
+        assert!(declined.contains(" 200"));
+
+        // 服务器已关闭，端口可以重新使用（监听线程在后台退出，稍等片刻）
+        let rebound = (0..50).any(|_| {
+            let ok = Server::http(("127.0.0.1", port)).is_ok();
+            if !ok {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            ok
+        });
+        assert!(rebound, "端口 {} 未释放", port);
+    }
+
+    #[test]
+    fn test_wait_for_callback_times_out() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let outcome = wait_for_callback(server, "xyz", Duration::from_millis(50)).unwrap();
+        assert_eq!(outcome, None);
+    }
+}
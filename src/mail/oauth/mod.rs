@@ -0,0 +1,13 @@
+/// OAuth2 公共模块
+///
+/// 浏览器授权流程、加密保存的账户结构与 Token 自动刷新，由 Gmail、Outlook 等提供方共用
+pub mod account;
+pub mod client;
+pub mod flow;
+pub mod reauth;
+pub mod token;
+
+pub use account::{CountMode, OAuthAccount};
+pub use client::{OAuthClient, OAuthEndpoints, client_builder};
+pub use flow::{AuthOutcome, PortsOccupied, RedirectPort, authorize};
+pub use token::TokenManager;
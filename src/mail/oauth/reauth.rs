@@ -0,0 +1,171 @@
+/// Refresh Token 到期提醒
+///
+/// Google 对处于"测试"发布状态的 OAuth 客户端签发的 Refresh Token 只有 7 天有效期，
+/// 到期后同步会以 `invalid_grant` 失败。根据签发时间推算到期时间，临近时提前提醒用户重新授权
+use chrono::{DateTime, Duration, Utc};
+
+use crate::utils::i18n::{tr, tr_fmt};
+
+/// 测试状态的客户端签发的 Refresh Token 的有效期
+pub const TESTING_TOKEN_LIFETIME: Duration = Duration::days(7);
+
+/// 判定为测试状态时允许的偏差（`invalid_grant` 出现在签发后 7 天左右才视为到期失效）
+const DETECTION_TOLERANCE: Duration = Duration::days(1);
+
+/// 距到期多久开始提醒
+const REMINDER_LEAD: Duration = Duration::days(3);
+
+/// 首次出现 `invalid_grant` 时判断是否因测试状态的 7 天有效期到期
+///
+/// 签发时间未知（旧版本保存的账户）时无法判断；签发后很快就失效的更可能是用户撤销了授权
+pub fn looks_like_testing_client(
+    issued_at: Option<DateTime<Utc>>,
+    failed_at: DateTime<Utc>,
+) -> bool {
+    let Some(issued_at) = issued_at else {
+        return false;
+    };
+    let age = failed_at - issued_at;
+    age >= TESTING_TOKEN_LIFETIME - DETECTION_TOLERANCE
+        && age <= TESTING_TOKEN_LIFETIME + DETECTION_TOLERANCE
+}
+
+/// 需要重新授权的时间（不是测试状态或签发时间未知时为 `None`）
+pub fn reauth_due(issued_at: Option<DateTime<Utc>>, testing: bool) -> Option<DateTime<Utc>> {
+    issued_at
+        .filter(|_| testing)
+        .map(|at| at + TESTING_TOKEN_LIFETIME)
+}
+
+/// 距需要重新授权的剩余时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Countdown {
+    /// 剩余超过 1 天（向上取整的天数）
+    Days(u64),
+
+    /// 剩余不足 1 天（向上取整的小时数）
+    Hours(u64),
+
+    /// 已到期
+    Due,
+}
+
+impl Countdown {
+    /// 提醒文字（如"2 天后需要重新授权"）
+    pub fn label(self) -> String {
+        match self {
+            Self::Days(days) => tr_fmt("reauth.in_days", &[&days]),
+            Self::Hours(hours) => tr_fmt("reauth.in_hours", &[&hours]),
+            Self::Due => tr("reauth.due").to_string(),
+        }
+    }
+}
+
+/// 计算剩余时间（距到期还早于 `REMINDER_LEAD` 时不提醒，返回 `None`）
+pub fn countdown(due: DateTime<Utc>, now: DateTime<Utc>) -> Option<Countdown> {
+    let remaining = due - now;
+    if remaining > REMINDER_LEAD {
+        return None;
+    }
+    if remaining <= Duration::zero() {
+        return Some(Countdown::Due);
+    }
+    let hours = (remaining.num_seconds() as u64).div_ceil(3600);
+    Some(if hours > 24 {
+        Countdown::Days(hours.div_ceil(24))
+    } else {
+        Countdown::Hours(hours)
+    })
+}
+
+/// Refresh Token 的签发时长（如"3 天前"，不足 1 天按小时）
+pub fn age_text(issued_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = (now - issued_at).max(Duration::zero());
+    if age >= Duration::days(1) {
+        tr_fmt("reauth.issued_days_ago", &[&age.num_days()])
+    } else {
+        tr_fmt("reauth.issued_hours_ago", &[&age.num_hours()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_testing_client_detected_near_seven_days() {
+        let issued = Utc::now();
+        assert!(looks_like_testing_client(
+            Some(issued),
+            issued + Duration::days(7)
+        ));
+        assert!(looks_like_testing_client(
+            Some(issued),
+            issued + Duration::days(6) + Duration::hours(12)
+        ));
+
+        // 签发后很快失效（撤销授权）或很久之后才失效都不是测试状态
+        assert!(!looks_like_testing_client(
+            Some(issued),
+            issued + Duration::hours(3)
+        ));
+        assert!(!looks_like_testing_client(
+            Some(issued),
+            issued + Duration::days(30)
+        ));
+        assert!(!looks_like_testing_client(None, issued));
+    }
+
+    #[test]
+    fn test_reauth_due_only_in_testing_mode() {
+        let issued = Utc::now();
+        assert_eq!(
+            reauth_due(Some(issued), true),
+            Some(issued + Duration::days(7))
+        );
+        assert_eq!(reauth_due(Some(issued), false), None);
+        assert_eq!(reauth_due(None, true), None);
+    }
+
+    #[test]
+    fn test_countdown() {
+        let now = Utc::now();
+        assert_eq!(countdown(now + Duration::days(5), now), None);
+        assert_eq!(
+            countdown(now + Duration::days(3), now),
+            Some(Countdown::Days(3))
+        );
+        assert_eq!(
+            countdown(now + Duration::days(1) + Duration::minutes(1), now),
+            Some(Countdown::Days(2))
+        );
+        assert_eq!(
+            countdown(now + Duration::days(1), now),
+            Some(Countdown::Hours(24))
+        );
+        assert_eq!(
+            countdown(now + Duration::minutes(90), now),
+            Some(Countdown::Hours(2))
+        );
+        assert_eq!(countdown(now, now), Some(Countdown::Due));
+        assert_eq!(
+            countdown(now - Duration::hours(5), now),
+            Some(Countdown::Due)
+        );
+
+        assert_eq!(Countdown::Days(2).label(), tr_fmt("reauth.in_days", &[&2]));
+    }
+
+    #[test]
+    fn test_age_text() {
+        let now = Utc::now();
+        assert_eq!(
+            age_text(now - Duration::days(3) - Duration::hours(5), now),
+            tr_fmt("reauth.issued_days_ago", &[&3])
+        );
+        assert_eq!(
+            age_text(now - Duration::hours(5), now),
+            tr_fmt("reauth.issued_hours_ago", &[&5])
+        );
+    }
+}
@@ -0,0 +1,266 @@
+/// Token 自动刷新管理模块
+///
+/// 各 OAuth2 邮件服务共用，服务差异（端点、凭据、账户类型）由 `OAuthClient` 与包装函数提供
+use anyhow::{Context, Result};
+use chrono::Utc;
+use oauth2::basic::BasicErrorResponseType;
+use oauth2::{RefreshToken, RequestTokenError, TokenResponse};
+
+use super::{OAuthAccount, OAuthClient, client_builder, reauth};
+use crate::config::storage::{self, StoredAccount};
+use crate::mail::RetryContext;
+use crate::mail::error::SyncError;
+use crate::utils::http_client;
+use crate::utils::redact::redact_token;
+
+/// Token 刷新阈值（提前多少分钟刷新）
+const REFRESH_THRESHOLD_MINUTES: i64 = 5;
+
+/// Token 管理器
+///
+/// 负责自动刷新过期的 Access Token
+pub struct TokenManager {
+    /// 关联的账户
+    account: OAuthAccount,
+
+    /// OAuth2 客户端参数
+    client: OAuthClient,
+
+    /// 把账户包装为对应服务的存储类型（用于持久化刷新后的 Token）
+    wrap: fn(OAuthAccount) -> StoredAccount,
+
+    /// 本轮的重试预算（强制刷新从中扣除）
+    retry: RetryContext,
+}
+
+impl TokenManager {
+    /// 创建 Token 管理器
+    ///
+    /// # Arguments
+    /// * `account` - 账户（包含加密的 Token）
+    /// * `client` - 账户所属服务的 OAuth2 客户端参数
+    /// * `wrap` - 账户对应的存储类型构造器，如 `StoredAccount::Gmail`
+    pub fn new(
+        account: OAuthAccount,
+        client: OAuthClient,
+        wrap: fn(OAuthAccount) -> StoredAccount,
+    ) -> Self {
+        Self {
+            account,
+            client,
+            wrap,
+            retry: RetryContext::default(),
+        }
+    }
+
+    /// 从指定的重试预算中扣除强制刷新（默认不限次数）
+    pub fn with_retry(mut self, retry: RetryContext) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 获取有效的 Access Token
+    ///
+    /// 如果 Token 即将过期（默认提前 5 分钟），则自动刷新
+    ///
+    /// # Returns
+    /// 返回解密后的 Access Token（明文）
+    ///
+    /// # Errors
+    /// - Token 刷新失败
+    /// - 解密失败
+    pub async fn get_valid_token(&mut self) -> Result<String> {
+        // 检查是否需要刷新
+        if self.account.is_token_expiring(REFRESH_THRESHOLD_MINUTES) {
+            tracing::info!(
+                "Access Token 即将过期（{}），自动刷新",
+                self.account.expires_at
+            );
+            self.refresh_access_token().await?;
+        }
+
+        // 解密并返回
+        self.account.decrypt_access_token()
+    }
+
+    /// 强制刷新 Access Token
+    ///
+    /// 使用 Refresh Token 从服务的 Token 端点获取新的 Access Token
+    ///
+    /// # Errors
+    /// - Refresh Token 解密失败
+    /// - 网络请求失败
+    /// - OAuth2 配置无效
+    /// - 保存账户失败
+    #[tracing::instrument(name = "oauth.refresh", skip_all)]
+    async fn refresh_access_token(&mut self) -> Result<()> {
+        tracing::debug!("开始刷新 Access Token");
+
+        // 1. 解密 Refresh Token
+        let refresh_token = self
+            .account
+            .decrypt_refresh_token()
+            .context("解密 Refresh Token 失败")?;
+
+        // 2. 构建 OAuth2 客户端
+        let client = client_builder(&self.client).build()?;
+
+        // 3. 使用 Refresh Token 交换新的 Access Token
+        let response = client
+            .exchange_refresh_token(&RefreshToken::new(refresh_token))
+            .request_async(http_client::oauth_http_client)
+            .await
+            .map_err(|e| match e {
+                // 错误响应的 Display 只有一句 "Server returned error response"，需按解析后的错误码判断
+                RequestTokenError::ServerResponse(response)
+                    if *response.error() == BasicErrorResponseType::InvalidGrant =>
+                {
+                    tracing::error!("❌ Token 刷新失败 [授权被拒绝/已过期]: {}", response);
+                    tracing::error!(
+                        "   💡 可能原因:\n   \
+                         - Refresh Token 已过期或被撤销\n   \
+                         - 用户撤销了应用授权\n   \
+                         - 需要用户重新授权，请移除后重新添加账户"
+                    );
+                    anyhow::anyhow!("Refresh Token 交换失败（可能已过期或被撤销）：{}", response)
+                        .context(SyncError::ReauthRequired)
+                }
+                RequestTokenError::ServerResponse(response) => {
+                    anyhow::anyhow!("Refresh Token 交换失败: {}", response)
+                }
+                other => anyhow::anyhow!("Refresh Token 交换失败: {}", other),
+            });
+        let token_response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                if SyncError::classify(&e) == Some(SyncError::ReauthRequired) {
+                    self.note_invalid_grant().await;
+                }
+                return Err(e);
+            }
+        };
+
+        let new_access_token = token_response.access_token().secret().to_string();
+        let expires_in = token_response
+            .expires_in()
+            .unwrap_or(std::time::Duration::from_secs(3600))
+            .as_secs() as i64;
+
+        // 4. 更新账户（自动加密）
+        self.account
+            .update_access_token(new_access_token.clone(), expires_in)
+            .context("更新 Access Token 失败")?;
+
+        // 服务轮换了 Refresh Token 时一并更新，旧的可能随即失效
+        if let Some(new_refresh_token) = token_response.refresh_token() {
+            self.account
+                .update_refresh_token(new_refresh_token.secret().to_string())
+                .context("更新 Refresh Token 失败")?;
+        }
+
+        if let Some(scopes) = token_response.scopes() {
+            self.account.scopes = scopes
+                .iter()
+                .map(|scope| scope.as_str().to_string())
+                .collect();
+        }
+
+        // 5. 持久化到文件
+        storage::save_account_async((self.wrap)(self.account.clone()))
+            .await
+            .context("保存账户失败")?;
+
+        tracing::info!(
+            "✅ Access Token 刷新成功（新的过期时间: {}）",
+            self.account.expires_at
+        );
+
+        tracing::debug!("新 Token: {}", redact_token(&new_access_token));
+
+        Ok(())
+    }
+
+    /// Refresh Token 被拒绝（`invalid_grant`）时判断 OAuth 客户端是否处于测试状态
+    ///
+    /// 首次在签发后 7 天左右失效时记录到账户上，重新授权后据此提前提醒下一次到期
+    async fn note_invalid_grant(&mut self) {
+        if self.account.testing_client
+            || !reauth::looks_like_testing_client(self.account.refresh_token_issued_at, Utc::now())
+        {
+            return;
+        }
+
+        tracing::warn!(
+            "Refresh Token 在签发后约 7 天失效，OAuth 客户端可能处于测试状态（此后每 7 天需要重新授权）"
+        );
+        self.account.testing_client = true;
+        if let Err(e) = storage::save_account_async((self.wrap)(self.account.clone())).await {
+            tracing::warn!("保存账户失败: {:#}", e);
+        }
+    }
+
+    /// 对外暴露的强制刷新方法
+    ///
+    /// 在某些情况下（例如调用 UserInfo 返回 401），需要立即尝试使用
+    /// Refresh Token 交换新的 Access Token。该方法包装内部的刷新实现。
+    /// 每次调用从重试预算中扣除一次，预算用完时返回 [`SyncError::Budgeted`]
+    pub async fn force_refresh(&mut self) -> Result<()> {
+        self.retry.spend("强制刷新 Token 后重试")?;
+        self.refresh_access_token().await
+    }
+
+    /// 获取账户引用
+    pub fn account(&self) -> &OAuthAccount {
+        &self.account
+    }
+
+    /// 获取可变账户引用
+    pub fn account_mut(&mut self) -> &mut OAuthAccount {
+        &mut self.account
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_threshold() {
+        assert_eq!(REFRESH_THRESHOLD_MINUTES, 5);
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要有效的 Refresh Token 和网络连接
+    async fn test_token_refresh() {
+        // 创建一个过期的账户
+        let mut account = OAuthAccount::new(
+            "test@gmail.com".to_string(),
+            "Test User".to_string(),
+            "old_access_token".to_string(),
+            "valid_refresh_token".to_string(),
+            -3600, // 已过期 1 小时
+        )
+        .expect("创建账户失败");
+
+        // 设置为已过期
+        account.expires_at = Utc::now() - chrono::Duration::hours(1);
+
+        // 创建管理器
+        let client = crate::mail::gmail::oauth::client().unwrap();
+        let mut manager = TokenManager::new(account, client, StoredAccount::Gmail);
+
+        // 应该触发刷新
+        assert!(manager.account.is_token_expiring(0));
+
+        // 尝试获取有效 Token（会自动刷新）
+        // 注意：此测试需要有效的 OAuth2 配置和 Refresh Token
+        let result = manager.get_valid_token().await;
+
+        if let Ok(token) = result {
+            println!("刷新成功，新 Token: {}...", &token[..10]);
+            assert!(!token.is_empty());
+        } else {
+            println!("刷新失败（预期：需要有效的 Refresh Token）");
+        }
+    }
+}
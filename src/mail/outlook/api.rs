@@ -0,0 +1,349 @@
+/// Microsoft Graph API 调用模块
+///
+/// 负责获取收件箱未读数、用户资料与头像
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::config::storage::StoredAccount;
+use crate::mail::error::classify_failure;
+use crate::mail::oauth::{OAuthAccount, TokenManager};
+use crate::mail::{
+    AccountId, AccountSyncInfo, Counters, ProfileInfo, RetryContext, SyncHealth, metered,
+};
+use crate::utils::redact::{log_bodies_enabled, redact_email};
+use crate::utils::{avatar, http_client};
+
+/// Microsoft Graph API 根地址
+const GRAPH_BASE_URL: &str = "https://graph.microsoft.com/v1.0";
+
+/// Graph 用户资料（`GET /me`）
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphUser {
+    /// 主邮箱（部分个人账户为空）
+    pub mail: Option<String>,
+
+    /// 登录名（通常即邮箱地址）
+    pub user_principal_name: String,
+
+    /// 显示名称
+    pub display_name: Option<String>,
+}
+
+impl GraphUser {
+    /// 邮箱地址（`mail` 为空时使用登录名）
+    pub fn email(&self) -> &str {
+        self.mail
+            .as_deref()
+            .filter(|mail| !mail.is_empty())
+            .unwrap_or(&self.user_principal_name)
+    }
+}
+
+/// 邮件文件夹信息（只选取未读数）
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MailFolder {
+    unread_item_count: u32,
+}
+
+/// Microsoft Graph API 客户端
+pub struct GraphApiClient {
+    access_token: String,
+}
+
+impl GraphApiClient {
+    /// 创建新的 Graph API 客户端
+    ///
+    /// # Arguments
+    /// * `access_token` - 已解密的 Access Token（明文）
+    pub fn new(access_token: String) -> Self {
+        Self { access_token }
+    }
+
+    /// 获取收件箱未读邮件数量
+    pub async fn get_unread_count(&self) -> Result<u32> {
+        tracing::debug!("正在获取未读邮件数量...");
+
+        let url = format!(
+            "{}/me/mailFolders/inbox?$select=unreadItemCount",
+            GRAPH_BASE_URL
+        );
+
+        let response = http_client::get_client()
+            .get(&url)
+            .bearer_auth(&self.access_token)
+            .timeout(http_client::count_timeout())
+            .send()
+            .await
+            .context("请求收件箱信息失败")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+
+            if status == 401 {
+                anyhow::bail!("Token 已过期，需要刷新");
+            }
+
+            anyhow::bail!("Graph mailFolders API 返回错误 {}: {}", status, error_text);
+        }
+
+        // 原始响应体仅在显式开启 NANOMAIL_LOG_BODIES=1 时记录
+        let response_text = response.text().await.context("读取响应体失败")?;
+        if log_bodies_enabled() {
+            tracing::debug!("Graph mailFolders API 原始响应: {}", response_text);
+        }
+
+        let folder: MailFolder =
+            serde_json::from_str(&response_text).context("解析收件箱信息响应失败")?;
+
+        Ok(folder.unread_item_count)
+    }
+
+    /// 获取指定时间之后收到的未读邮件数量（`$count` 返回纯文本数字）
+    pub async fn get_unread_count_since(&self, since: DateTime<Utc>) -> Result<u32> {
+        tracing::debug!("正在获取 {} 之后的未读邮件数量...", since);
+
+        let response = http_client::get_client()
+            .get(format!(
+                "{}/me/mailFolders/inbox/messages/$count",
+                GRAPH_BASE_URL
+            ))
+            .query(&[("$filter", unread_since_filter(since))])
+            .bearer_auth(&self.access_token)
+            .header("ConsistencyLevel", "eventual")
+            .timeout(http_client::count_timeout())
+            .send()
+            .await
+            .context("请求未读邮件数量失败")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+
+            if status == 401 {
+                anyhow::bail!("Token 已过期，需要刷新");
+            }
+
+            anyhow::bail!(
+                "Graph messages/$count API 返回错误 {}: {}",
+                status,
+                error_text
+            );
+        }
+
+        let text = response.text().await.context("读取响应体失败")?;
+        text.trim()
+            .parse()
+            .with_context(|| format!("解析未读邮件数量失败: {}", text))
+    }
+
+    /// 获取用户资料（邮箱与显示名称）
+    pub async fn get_profile(&self) -> Result<GraphUser> {
+        tracing::debug!("正在获取用户资料...");
+
+        let response = http_client::get_client()
+            .get(format!(
+                "{}/me?$select=mail,userPrincipalName,displayName",
+                GRAPH_BASE_URL
+            ))
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .context("请求用户信息失败")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Graph /me API 返回错误 {}: {}", status, error_text);
+        }
+
+        response.json().await.context("解析用户信息响应失败")
+    }
+
+    /// 下载头像原图（账户未设置头像时返回 `None`）
+    pub async fn get_photo(&self) -> Result<Option<Vec<u8>>> {
+        let response = http_client::get_client()
+            .get(format!("{}/me/photo/$value", GRAPH_BASE_URL))
+            .bearer_auth(&self.access_token)
+            .timeout(http_client::avatar_timeout())
+            .send()
+            .await
+            .context("请求头像失败")?;
+
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("Graph photo API 返回错误 {}", response.status());
+        }
+
+        let bytes = response.bytes().await.context("读取头像响应体失败")?;
+        Ok(Some(bytes.to_vec()))
+    }
+}
+
+/// 构造"指定时间之后的未读邮件"过滤条件
+fn unread_since_filter(since: DateTime<Utc>) -> String {
+    format!(
+        "isRead eq false and receivedDateTime ge {}",
+        since.to_rfc3339_opts(SecondsFormat::Secs, true)
+    )
+}
+
+/// 获取头像本地缓存路径（已缓存时不再下载）
+async fn cache_avatar(client: &GraphApiClient, email: &str) -> String {
+    if let Some(cached) = avatar::get_cached_avatar_path(email) {
+        tracing::debug!("使用已缓存的头像: {}", redact_email(email));
+        return cached;
+    }
+
+    match client.get_photo().await {
+        Ok(Some(bytes)) => match avatar::cache_avatar_bytes_async(bytes, email).await {
+            Ok(path) => path.display().to_string(),
+            Err(e) => {
+                avatar::warn_failed(email, &e);
+                String::new()
+            }
+        },
+        Ok(None) => String::new(),
+        Err(e) => {
+            tracing::warn!("下载头像失败: {}", e);
+            String::new()
+        }
+    }
+}
+
+/// 同步账户信息（获取未读数和头像）
+///
+/// # Arguments
+/// * `account` - Outlook 账户（需要有效的 Token）
+/// * `retry` - 本轮的重试预算（网络探测从中扣除）
+///
+/// # Returns
+/// 返回同步后的账户信息和更新后的账户（如果 Token 被刷新）
+pub async fn sync_account_info(
+    account: &OAuthAccount,
+    retry: &RetryContext,
+) -> Result<(AccountSyncInfo, Option<OAuthAccount>)> {
+    tracing::info!("🔄 同步账户信息: {}", redact_email(&account.email));
+
+    // 使用 TokenManager 获取有效的 Access Token（自动刷新过期的 Token）
+    let client = super::oauth::client()?;
+    let mut token_manager = TokenManager::new(account.clone(), client, StoredAccount::Outlook)
+        .with_retry(retry.clone());
+
+    let access_token = match token_manager
+        .get_valid_token()
+        .await
+        .context("获取有效 Access Token 失败")
+    {
+        Ok(token) => token,
+        Err(e) => return Err(classify_failure(e, retry).await),
+    };
+
+    let updated_account = if token_manager.account().expires_at != account.expires_at {
+        tracing::info!("✅ Token 已自动刷新，更新账户信息");
+        Some(token_manager.account().clone())
+    } else {
+        None
+    };
+
+    let client = GraphApiClient::new(access_token);
+
+    // since_added 模式只统计计数基准时间之后的未读邮件
+    let unread_count = match account.unread_baseline() {
+        Some(since) => client.get_unread_count_since(since).await,
+        None => client.get_unread_count().await,
+    };
+    let unread_count = match unread_count.context("获取未读数失败") {
+        Ok(count) => count,
+        Err(e) => return Err(classify_failure(e, retry).await),
+    };
+
+    // 按流量计费的网络下不获取资料与头像，界面沿用缓存的资料
+    if metered::current(account.ignore_metered).is_reduced() {
+        tracing::debug!("按流量计费的网络，跳过获取用户资料");
+        let sync_info = AccountSyncInfo::new(account.email.clone(), unread_count);
+        return Ok((sync_info, updated_account));
+    }
+
+    // 用户资料获取失败时沿用已保存的信息
+    let (display_name, error_message) = match client.get_profile().await {
+        Ok(profile) => (
+            profile
+                .display_name
+                .unwrap_or_else(|| account.display_name.clone()),
+            None,
+        ),
+        Err(e) => {
+            tracing::warn!("⚠️ 获取用户信息失败 (使用本地缓存): {}", e);
+            (
+                account.display_name.clone(),
+                Some(format!("获取用户信息失败: {}", e)),
+            )
+        }
+    };
+
+    let avatar_url = cache_avatar(&client, &account.email).await;
+
+    // 头像与资料分开获取：资料获取失败时仍带上沿用的名称与缓存的头像
+    let sync_info = AccountSyncInfo {
+        identity: AccountId {
+            email: account.email.clone(),
+        },
+        counters: Counters {
+            unread: Some(unread_count),
+            ..Counters::default()
+        },
+        profile: Some(ProfileInfo {
+            display_name,
+            avatar_url,
+        }),
+        health: SyncHealth {
+            error: error_message,
+            snoozed: false,
+        },
+        previews: Vec::new(),
+        vacation: None,
+    };
+
+    Ok((sync_info, updated_account))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_user_email_falls_back_to_upn() {
+        let user: GraphUser = serde_json::from_str(
+            r#"{"mail":null,"userPrincipalName":"user@outlook.com","displayName":"User"}"#,
+        )
+        .unwrap();
+        assert_eq!(user.email(), "user@outlook.com");
+        assert_eq!(user.display_name.as_deref(), Some("User"));
+    }
+
+    #[test]
+    fn test_parse_unread_item_count() {
+        let folder: MailFolder = serde_json::from_str(
+            r#"{"@odata.context":"https://graph.microsoft.com/v1.0/$metadata","unreadItemCount":7}"#,
+        )
+        .unwrap();
+        assert_eq!(folder.unread_item_count, 7);
+    }
+
+    #[test]
+    fn test_unread_since_filter() {
+        let since = DateTime::parse_from_rfc3339("2026-01-01T08:30:00.250+08:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            unread_since_filter(since),
+            "isRead eq false and receivedDateTime ge 2026-01-01T00:30:00Z"
+        );
+    }
+}
@@ -0,0 +1,9 @@
+/// Outlook 模块 - Microsoft 身份平台 OAuth2 认证与 Microsoft Graph API 调用
+pub mod api;
+pub mod oauth;
+pub mod provider;
+
+// 重新导出常用类型和函数
+pub use api::sync_account_info;
+pub use oauth::authenticate;
+pub use provider::OutlookProvider;
@@ -0,0 +1,120 @@
+/// Outlook OAuth2 认证流程
+///
+/// 在通用授权码流程（带 PKCE）之上提供 Microsoft 身份平台端点与用户资料获取。
+/// 应用注册为公共客户端（移动和桌面应用），回调地址为 `http://localhost`
+use anyhow::{Context, Result};
+
+use crate::config::oauth_config::OutlookOAuthConfig;
+use crate::config::storage::{self, StoredAccount};
+use crate::mail::oauth::{
+    self, AuthOutcome, OAuthAccount, OAuthClient, OAuthEndpoints, RedirectPort,
+};
+use crate::utils::redact::redact_email;
+
+/// 请求的权限范围（offline_access 用于获取 Refresh Token）
+const SCOPES: &[&str] = &[
+    "https://graph.microsoft.com/Mail.Read",
+    "https://graph.microsoft.com/User.Read",
+    "offline_access",
+];
+
+/// 由 `[oauth.outlook]` 配置构造 Microsoft OAuth2 客户端参数
+pub fn client() -> Result<OAuthClient> {
+    let config = OutlookOAuthConfig::load().context("加载 Outlook OAuth2 配置失败")?;
+    Ok(client_from_config(&config))
+}
+
+fn client_from_config(config: &OutlookOAuthConfig) -> OAuthClient {
+    let base = format!(
+        "https://login.microsoftonline.com/{}/oauth2/v2.0",
+        config.tenant
+    );
+
+    OAuthClient {
+        client_id: config.client_id.clone(),
+        client_secret: None,
+        endpoints: OAuthEndpoints {
+            auth: format!("{}/authorize", base),
+            token: format!("{}/token", base),
+        },
+        scopes: SCOPES.iter().map(|s| s.to_string()).collect(),
+        include_granted_scopes: false,
+        // 回环回调地址 `http://localhost` 允许任意端口，不与其他程序争用固定端口
+        redirect_port: RedirectPort::Any,
+    }
+}
+
+/// 执行 Outlook OAuth2 认证
+///
+/// # Returns
+/// 返回已保存的 Outlook 账户信息；用户在授权页面取消时返回 `None`
+///
+/// # Errors
+/// - OAuth2 配置无效（占位符）
+/// - 授权流程失败（端口被占用、授权超时、Token 交换失败等）
+/// - 网络错误
+pub async fn authenticate() -> Result<Option<OAuthAccount>> {
+    tracing::info!("🔐 开始 Outlook OAuth2 认证流程");
+
+    let config = OutlookOAuthConfig::load()?;
+    if config.is_placeholder() {
+        anyhow::bail!(
+            "Outlook OAuth2 配置无效：请设置环境变量 OUTLOOK_CLIENT_ID 或在配置文件中添加 [oauth.outlook] 段\n\
+             参考：docs/setup_oauth.md"
+        );
+    }
+
+    let tokens = match oauth::authorize(&client_from_config(&config)).await? {
+        AuthOutcome::Authorized(tokens) => tokens,
+        AuthOutcome::Declined => return Ok(None),
+    };
+
+    let profile = super::api::GraphApiClient::new(tokens.access_token.clone())
+        .get_profile()
+        .await
+        .context("获取用户信息失败")?;
+
+    let email = profile.email().to_string();
+    let display_name = profile.display_name.unwrap_or_else(|| email.clone());
+    tracing::info!("✅ 用户信息获取成功: {}", redact_email(&email));
+
+    let mut account = OAuthAccount::new(
+        email,
+        display_name,
+        tokens.access_token,
+        tokens.refresh_token,
+        tokens.expires_in,
+    )
+    .context("创建账户失败")?;
+    account.scopes = tokens.scopes;
+
+    storage::save_account_async(StoredAccount::Outlook(account.clone()))
+        .await
+        .context("保存账户失败")?;
+
+    tracing::info!("✅ 账户已保存（Token 已加密）");
+    tracing::info!("🎉 Outlook OAuth2 认证流程完成");
+
+    Ok(Some(account))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_uses_tenant_endpoints() {
+        let client = client_from_config(&OutlookOAuthConfig::default());
+        assert_eq!(
+            client.endpoints.auth,
+            "https://login.microsoftonline.com/common/oauth2/v2.0/authorize"
+        );
+        assert_eq!(
+            client.endpoints.token,
+            "https://login.microsoftonline.com/common/oauth2/v2.0/token"
+        );
+        assert!(client.client_secret.is_none());
+        assert!(client.scopes.iter().any(|s| s == "offline_access"));
+        assert_eq!(client.redirect_port, RedirectPort::Any);
+    }
+}
@@ -0,0 +1,50 @@
+/// Outlook 提供方实现
+///
+/// 将 Microsoft 身份平台登录与 Microsoft Graph 同步函数包装为 [`MailProvider`]
+use async_trait::async_trait;
+
+use crate::config::storage::StoredAccount;
+use crate::mail::{MailProvider, RetryContext, SyncFailure, SyncOutcome};
+
+/// Outlook 网页版收件箱（个人账户会自动跳转到 outlook.live.com）
+const INBOX_URL: &str = "https://outlook.office.com/mail/inbox";
+
+/// Outlook 提供方
+pub struct OutlookProvider;
+
+#[async_trait]
+impl MailProvider for OutlookProvider {
+    fn kind(&self) -> &'static str {
+        "outlook"
+    }
+
+    async fn sync(
+        &self,
+        account: &StoredAccount,
+        retry: &RetryContext,
+    ) -> Result<SyncOutcome, SyncFailure> {
+        let StoredAccount::Outlook(outlook) = account else {
+            return Err(SyncFailure::from_error(&anyhow::anyhow!(
+                "账户类型不匹配: {}",
+                account.kind()
+            )));
+        };
+
+        match super::sync_account_info(outlook, retry).await {
+            Ok((info, updated)) => Ok(SyncOutcome {
+                info,
+                updated_account: updated.map(StoredAccount::Outlook),
+            }),
+            // 失败日志由同步引擎统一记录（对重复的错误限流）
+            Err(e) => Err(SyncFailure::from_error(&e)),
+        }
+    }
+
+    async fn authenticate(&self) -> anyhow::Result<Option<StoredAccount>> {
+        Ok(super::authenticate().await?.map(StoredAccount::Outlook))
+    }
+
+    fn open_inbox_url(&self, _account: &StoredAccount) -> String {
+        INBOX_URL.to_string()
+    }
+}
@@ -0,0 +1,90 @@
+/// 邮件服务提供方抽象
+///
+/// 同步引擎与界面只依赖 [`MailProvider`] 和与服务无关的 [`AccountSyncInfo`]，
+/// 按账户文件中的 `type` 字段从 [`ProviderRegistry`] 取出对应实现，新增邮件服务无需改动引擎
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{AccountSyncInfo, RetryContext, SyncFailure};
+use crate::config::storage::StoredAccount;
+
+/// 一次同步的结果
+#[derive(Debug, Clone)]
+pub struct SyncOutcome {
+    /// 同步到的账户信息
+    pub info: AccountSyncInfo,
+
+    /// 同步过程中账户数据发生变化（如 Token 被刷新）时需要保存的新账户
+    pub updated_account: Option<StoredAccount>,
+}
+
+/// 等待推送的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushEvent {
+    /// 账户有变化，需要立即同步
+    Changed,
+
+    /// 等待超时，没有变化（可以继续等待）
+    Timeout,
+
+    /// 账户或服务器不支持推送，仅使用定时轮询
+    Unsupported,
+}
+
+/// 邮件服务提供方
+#[async_trait]
+pub trait MailProvider: Send + Sync {
+    /// 类型标识（与账户文件中的 `type` 字段一致）
+    fn kind(&self) -> &'static str;
+
+    /// 同步账户的未读数与资料（额外的尝试从 `retry` 扣除，见 [`RetryContext`]）
+    async fn sync(
+        &self,
+        account: &StoredAccount,
+        retry: &RetryContext,
+    ) -> Result<SyncOutcome, SyncFailure>;
+
+    /// 交互式登录并返回新账户（用户取消授权时返回 `None`）
+    async fn authenticate(&self) -> anyhow::Result<Option<StoredAccount>>;
+
+    /// 等待服务器推送账户变化（如 IMAP IDLE），默认不支持推送
+    async fn wait_for_change(&self, _account: &StoredAccount) -> anyhow::Result<PushEvent> {
+        Ok(PushEvent::Unsupported)
+    }
+
+    /// 账户收件箱的网页地址
+    fn open_inbox_url(&self, account: &StoredAccount) -> String;
+}
+
+/// 按类型标识索引的提供方集合
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    providers: HashMap<&'static str, Arc<dyn MailProvider>>,
+}
+
+impl ProviderRegistry {
+    /// 包含所有内置提供方的注册表
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::default();
+        registry.register(Arc::new(super::gmail::GmailProvider));
+        registry.register(Arc::new(super::outlook::OutlookProvider));
+        registry.register(Arc::new(super::imap::ImapProvider));
+        registry
+    }
+
+    /// 注册提供方（同类型已存在时替换）
+    pub fn register(&mut self, provider: Arc<dyn MailProvider>) {
+        self.providers.insert(provider.kind(), provider);
+    }
+
+    /// 按类型标识查找提供方
+    pub fn get(&self, kind: &str) -> Option<Arc<dyn MailProvider>> {
+        self.providers.get(kind).cloned()
+    }
+
+    /// 查找账户对应的提供方
+    pub fn for_account(&self, account: &StoredAccount) -> Option<Arc<dyn MailProvider>> {
+        self.get(account.kind())
+    }
+}
@@ -0,0 +1,223 @@
+/// API 请求计数
+///
+/// 按账户统计最近 1 分钟与 24 小时内发往邮件服务 API 的请求数（目前由 Gmail API 客户端计数），
+/// 在 Token 详情与诊断中显示。单个账户每分钟的请求数超过阈值时记录警告，并暂缓同步该账户
+/// 直到速率回落，相当于自动拉长它的同步间隔，避免撞上服务端配额后出现难以理解的 429。
+/// 计数保存在内存中，每次同步该账户后写入运行时状态文件，重启后大致延续
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+
+use crate::config::state::{self, RequestBucket};
+use crate::utils::redact::redact_email;
+
+/// 每分钟请求数的默认警告阈值
+pub const DEFAULT_RATE_WARNING: u32 = 60;
+
+/// 计数的时间分辨率
+const BUCKET: Duration = Duration::minutes(1);
+
+/// 计数保留的时长
+const WINDOW: Duration = Duration::hours(24);
+
+/// 当前的警告阈值（每分钟请求数）
+static RATE_WARNING: AtomicU32 = AtomicU32::new(DEFAULT_RATE_WARNING);
+
+/// 各账户的请求计数（首次访问某账户时从运行时状态加载）
+static REQUESTS: LazyLock<Mutex<HashMap<String, Vec<RequestBucket>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 账户的 API 用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// 最近 1 分钟的请求数（按滑动窗口估算）
+    pub last_minute: u32,
+
+    /// 最近 24 小时的请求数
+    pub last_day: u32,
+}
+
+/// 设置每分钟请求数的警告阈值（来自配置，0 按 1 处理）
+pub fn set_rate_warning(limit: u32) {
+    RATE_WARNING.store(limit.max(1), Ordering::Relaxed);
+}
+
+/// 当前的警告阈值
+pub fn rate_warning() -> u32 {
+    RATE_WARNING.load(Ordering::Relaxed)
+}
+
+/// 记录账户的一次 API 请求，速率刚超过阈值时记录警告
+pub fn record(email: &str) {
+    let now = Utc::now();
+    let limit = rate_warning();
+
+    let mut requests = lock();
+    let buckets = load(&mut requests, email);
+    let before = usage_at(buckets, now).last_minute;
+    add_request(buckets, now);
+    let after = usage_at(buckets, now).last_minute;
+
+    if before <= limit && after > limit {
+        tracing::warn!(
+            "⚠️ {} 最近 1 分钟发出了 {} 个 API 请求（阈值 {}），暂缓同步该账户直到速率回落",
+            redact_email(email),
+            after,
+            limit
+        );
+    }
+}
+
+/// 账户当前的 API 用量
+pub fn usage(email: &str) -> Usage {
+    let mut requests = lock();
+    usage_at(load(&mut requests, email), Utc::now())
+}
+
+/// 账户每分钟的请求数是否超过阈值（超过时本轮跳过该账户）
+///
+/// 只看本次运行中记录的请求：重启前的计数早已滑出 1 分钟的窗口，不必为此读取状态文件
+pub fn is_over_limit(email: &str) -> bool {
+    lock()
+        .get(email)
+        .is_some_and(|buckets| usage_at(buckets, Utc::now()).last_minute > rate_warning())
+}
+
+/// 把账户的计数写入运行时状态（每次同步该账户后调用）
+pub fn persist(email: &str) {
+    let Some(buckets) = lock().get(email).cloned() else {
+        return;
+    };
+    if let Err(e) = state::global().update_account(email, |state| state.api_requests = buckets) {
+        tracing::warn!("保存运行时状态失败: {:#}", e);
+    }
+}
+
+fn lock() -> MutexGuard<'static, HashMap<String, Vec<RequestBucket>>> {
+    REQUESTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 账户的计数，首次访问时从运行时状态加载
+fn load<'a>(
+    requests: &'a mut HashMap<String, Vec<RequestBucket>>,
+    email: &str,
+) -> &'a mut Vec<RequestBucket> {
+    requests
+        .entry(email.to_string())
+        .or_insert_with(|| state::global().account(email).api_requests)
+}
+
+/// 在所在分钟的计数上加一，同时删除过期（或系统时间调回后位于"未来"）的计数
+fn add_request(buckets: &mut Vec<RequestBucket>, now: DateTime<Utc>) {
+    buckets.retain(|bucket| bucket.minute <= now && now - bucket.minute < WINDOW);
+
+    let minute = now.duration_trunc(BUCKET).unwrap_or(now);
+    match buckets.last_mut() {
+        Some(last) if last.minute == minute => last.count += 1,
+        _ => buckets.push(RequestBucket { minute, count: 1 }),
+    }
+}
+
+/// 统计用量
+///
+/// 最近 1 分钟按滑动窗口估算：当前分钟的计数，加上前一分钟的计数乘以它仍落在窗口内的比例
+fn usage_at(buckets: &[RequestBucket], now: DateTime<Utc>) -> Usage {
+    let minute = now.duration_trunc(BUCKET).unwrap_or(now);
+    let elapsed_ms = (now - minute)
+        .num_milliseconds()
+        .clamp(0, BUCKET.num_milliseconds());
+    let remaining_ms = BUCKET.num_milliseconds() - elapsed_ms;
+
+    let count_at = |start: DateTime<Utc>| {
+        buckets
+            .iter()
+            .find(|bucket| bucket.minute == start)
+            .map_or(0, |bucket| bucket.count)
+    };
+    let previous = i64::from(count_at(minute - BUCKET)) * remaining_ms / BUCKET.num_milliseconds();
+
+    Usage {
+        last_minute: count_at(minute) + previous as u32,
+        last_day: buckets
+            .iter()
+            .filter(|bucket| bucket.minute <= now && now - bucket.minute < WINDOW)
+            .map(|bucket| bucket.count)
+            .sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, hour, minute, second)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_counts_per_minute() {
+        let mut buckets = Vec::new();
+        for second in [5, 20, 40] {
+            add_request(&mut buckets, at(10, 0, second));
+        }
+        add_request(&mut buckets, at(10, 1, 0));
+
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].count, 3);
+        assert_eq!(
+            usage_at(&buckets, at(10, 1, 0)),
+            Usage {
+                last_minute: 4,
+                last_day: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_last_minute_slides() {
+        let mut buckets = Vec::new();
+        for _ in 0..10 {
+            add_request(&mut buckets, at(10, 0, 30));
+        }
+
+        // 下一分钟过去一半时，前一分钟的请求按一半计入
+        assert_eq!(usage_at(&buckets, at(10, 1, 30)).last_minute, 5);
+        // 两分钟后不再计入最近 1 分钟，仍计入 24 小时
+        let usage = usage_at(&buckets, at(10, 2, 0));
+        assert_eq!(usage.last_minute, 0);
+        assert_eq!(usage.last_day, 10);
+    }
+
+    #[test]
+    fn test_old_buckets_expire() {
+        let mut buckets = Vec::new();
+        add_request(&mut buckets, at(10, 0, 0));
+        add_request(&mut buckets, at(20, 0, 0));
+
+        let next_day = at(10, 0, 0) + Duration::hours(24) + Duration::minutes(1);
+        assert_eq!(usage_at(&buckets, next_day).last_day, 1);
+
+        add_request(&mut buckets, next_day);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].minute, at(20, 0, 0));
+    }
+
+    #[test]
+    fn test_clock_moved_back_drops_future_buckets() {
+        let mut buckets = Vec::new();
+        add_request(&mut buckets, at(12, 0, 0));
+        add_request(&mut buckets, at(11, 0, 0));
+
+        assert_eq!(
+            buckets,
+            vec![RequestBucket {
+                minute: at(11, 0, 0),
+                count: 1
+            }]
+        );
+    }
+}
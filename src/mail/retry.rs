@@ -0,0 +1,103 @@
+/// 每轮同步的重试预算
+///
+/// 网络探测、401 后强制刷新 Token 再重试、邮件服务停用账户的定期重试等机制各自都会额外发出请求，
+/// 叠加起来一轮最坏要发出几十个请求才放弃。同步引擎每轮创建一个 [`RetryContext`]，逐层传给
+/// 提供方、Token 管理器与错误分类，所有额外的尝试都从同一份预算中扣除；预算用完后其余的额外尝试
+/// 立即以 [`SyncError::Budgeted`] 失败，本轮随即结束
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use super::error::SyncError;
+
+/// 每轮额外尝试次数的默认预算
+pub const DEFAULT_RETRY_BUDGET: u32 = 10;
+
+/// 当前的每轮预算（随配置更新）
+static ROUND_BUDGET: AtomicU32 = AtomicU32::new(DEFAULT_RETRY_BUDGET);
+
+/// 设置每轮的重试预算（来自配置，0 表示不做任何额外尝试），之后开始的同步轮次生效
+pub fn set_round_budget(budget: u32) {
+    ROUND_BUDGET.store(budget, Ordering::Relaxed);
+}
+
+/// 当前的每轮重试预算
+pub fn round_budget() -> u32 {
+    ROUND_BUDGET.load(Ordering::Relaxed)
+}
+
+/// 一轮同步共用的重试预算（克隆后共享同一份剩余次数）
+///
+/// 默认值不限次数，用于界面发起的单次操作（添加账户后的首次同步、手动刷新 Token 等）
+#[derive(Debug, Clone, Default)]
+pub struct RetryContext {
+    /// 剩余次数（`None` 表示不限）
+    remaining: Option<Arc<AtomicU32>>,
+}
+
+impl RetryContext {
+    /// 按当前配置的预算开始新的一轮
+    pub fn for_round() -> Self {
+        Self::with_budget(round_budget())
+    }
+
+    /// 指定预算
+    pub fn with_budget(budget: u32) -> Self {
+        Self {
+            remaining: Some(Arc::new(AtomicU32::new(budget))),
+        }
+    }
+
+    /// 为一次额外尝试扣除预算（`what` 只用于日志），预算已用完时返回 [`SyncError::Budgeted`]
+    pub fn spend(&self, what: &str) -> Result<(), SyncError> {
+        let Some(remaining) = &self.remaining else {
+            return Ok(());
+        };
+        match remaining.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)) {
+            Ok(left) => {
+                tracing::debug!("额外尝试: {}（本轮剩余 {} 次）", what, left - 1);
+                Ok(())
+            }
+            Err(_) => {
+                tracing::warn!("本轮重试预算已用完，不再{}", what);
+                Err(SyncError::Budgeted)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_is_shared_between_clones() {
+        let retry = RetryContext::with_budget(2);
+        let token_layer = retry.clone();
+
+        assert_eq!(retry.spend("网络探测"), Ok(()));
+        assert_eq!(token_layer.spend("强制刷新 Token"), Ok(()));
+
+        // 用完后所有层都立即失败，反复尝试也不会让剩余次数回绕
+        for _ in 0..3 {
+            assert_eq!(retry.spend("网络探测"), Err(SyncError::Budgeted));
+            assert_eq!(
+                token_layer.spend("强制刷新 Token"),
+                Err(SyncError::Budgeted)
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_budget_allows_no_retries() {
+        let retry = RetryContext::with_budget(0);
+        assert_eq!(retry.spend("网络探测"), Err(SyncError::Budgeted));
+    }
+
+    #[test]
+    fn test_default_is_unlimited() {
+        let retry = RetryContext::default();
+        for _ in 0..100 {
+            assert_eq!(retry.spend("网络探测"), Ok(()));
+        }
+    }
+}
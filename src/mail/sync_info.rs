@@ -0,0 +1,331 @@
+/// 账户同步信息
+///
+/// 一次同步的结果按用途分组：账户标识、计数、资料与健康状况。`Option` 字段统一表示
+/// "本轮没有获取"（`None`，界面与托盘保留上次的值）与"获取到了"（`Some`，包括 `Some(0)`），
+/// 消费方通过下面的转换方法取值，不再各自组合判断
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSyncInfo {
+    /// 账户标识
+    pub identity: AccountId,
+
+    /// 未读计数
+    pub counters: Counters,
+
+    /// 显示名称与头像（本轮没有获取到时为 `None`，沿用上次保存的资料）
+    pub profile: Option<ProfileInfo>,
+
+    /// 本轮的健康状况
+    pub health: SyncHealth,
+
+    /// 新邮件摘要（最新的在前，提供方不支持时为空）
+    pub previews: Vec<MailPreview>,
+
+    /// 自动回复状态（本轮没有检查时为 `None`，界面保留上次的状态）
+    pub vacation: Option<VacationInfo>,
+}
+
+/// 账户标识
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountId {
+    /// 服务报告的邮箱地址（界面按已保存账户的邮箱定位账户行，两者大小写可能不同）
+    pub email: String,
+}
+
+/// 未读计数
+///
+/// 提供方返回的同步结果中 `unread` 总是 `Some`；只有界面为失败的同步构造的结果
+/// （[`AccountSyncInfo::failed`]）没有计数
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// 徽章标签的未读数
+    pub unread: Option<u32>,
+
+    /// 通知标签的未读数（与徽章标签相同、未单独查询时为 `None`）
+    pub notify_unread: Option<u32>,
+
+    /// 垃圾邮件未读数（本轮未检查时为 `None`）
+    pub spam_unread: Option<u32>,
+
+    /// "只统计真人来信"过滤的结果（账户没有开启过滤时为 `None`）
+    pub people_filter: Option<PeopleFilter>,
+}
+
+/// "只统计真人来信"过滤的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeopleFilter {
+    /// 徽章未读数只包含真人来信
+    Applied,
+
+    /// 未读邮件过多（或无法读取邮件头），徽章为全部未读数
+    Unfiltered,
+}
+
+/// 账户资料
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileInfo {
+    /// 显示名称
+    pub display_name: String,
+
+    /// 头像（本地缓存路径或远程地址，没有头像时为空）
+    pub avatar_url: String,
+}
+
+/// 自动回复（休假回复）状态
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VacationInfo {
+    /// 自动回复当前是否生效
+    pub active: bool,
+
+    /// 回复主题（没有设置时为空）
+    pub subject: String,
+}
+
+/// 本轮的健康状况
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncHealth {
+    /// 错误说明：未读数已获取但资料获取失败，或（界面构造的结果）整轮同步失败。
+    /// 有错误时账户行标记为同步失败并保留原有未读数，资料不写入运行时状态
+    pub error: Option<String>,
+
+    /// 账户处于免打扰期（由同步引擎填写）
+    pub snoozed: bool,
+}
+
+/// 新邮件摘要（用于通知内容）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailPreview {
+    /// 邮件 ID（通知去重使用，提供方没有 ID 时为空）
+    pub id: String,
+
+    /// 发件人（显示名称或邮箱地址）
+    pub sender: String,
+
+    /// 主题
+    pub subject: String,
+}
+
+impl AccountSyncInfo {
+    /// 只有徽章未读数的同步结果（其余字段为"本轮没有获取"）
+    pub fn new(email: impl Into<String>, unread: u32) -> Self {
+        Self {
+            identity: AccountId {
+                email: email.into(),
+            },
+            counters: Counters {
+                unread: Some(unread),
+                ..Counters::default()
+            },
+            profile: None,
+            health: SyncHealth::default(),
+            previews: Vec::new(),
+            vacation: None,
+        }
+    }
+
+    /// 同步失败时界面使用的结果（没有计数与资料，只有错误说明）
+    pub fn failed(email: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            counters: Counters::default(),
+            health: SyncHealth {
+                error: Some(message.into()),
+                snoozed: false,
+            },
+            ..Self::new(email, 0)
+        }
+    }
+
+    /// 邮箱地址
+    pub fn email(&self) -> &str {
+        &self.identity.email
+    }
+
+    /// 徽章未读数（没有计数时为 0，用于日志与无界面输出）
+    pub fn unread(&self) -> u32 {
+        self.counters.unread.unwrap_or_default()
+    }
+
+    /// 通知依据的未读数（未单独查询通知标签时即徽章未读数）
+    pub fn notify_unread(&self) -> u32 {
+        self.counters.notify_unread.unwrap_or_else(|| self.unread())
+    }
+
+    /// 本轮是否有错误（账户行标记为同步失败）
+    pub fn has_error(&self) -> bool {
+        self.health.error.is_some()
+    }
+
+    /// 账户行应显示的未读数（有错误时为 `None`，保留原有未读数）
+    pub fn badge_count(&self) -> Option<u32> {
+        self.counters.unread.filter(|_| !self.has_error())
+    }
+
+    /// 账户行应重新加载的头像（为空时使用缓存头像或占位符；`None` 表示保留原来的头像）
+    ///
+    /// 有错误时只有确实拿到了头像才更新，避免把已显示的头像换成占位符
+    pub fn avatar_update(&self) -> Option<&str> {
+        self.profile
+            .as_ref()
+            .filter(|profile| !self.has_error() || !profile.avatar_url.is_empty())
+            .map(|profile| profile.avatar_url.as_str())
+    }
+
+    /// 写入运行时状态的资料 `(显示名称, 头像)`（有错误时为 `None`，保留上次的资料）
+    pub fn saved_profile(&self) -> Option<(&str, &str)> {
+        self.profile
+            .as_ref()
+            .filter(|_| !self.has_error())
+            .map(|profile| (profile.display_name.as_str(), profile.avatar_url.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 重构前的扁平结构（只保留影响消费方判断的字段）
+    struct Flat {
+        unread_count: u32,
+        avatar_url: &'static str,
+        display_name: &'static str,
+        error_message: Option<&'static str>,
+    }
+
+    /// 重构前各消费方的判断：(账户行未读数, 账户行头像, 写入运行时状态的资料, 是否标记失败)
+    type Decisions<'a> = (
+        Option<u32>,
+        Option<&'a str>,
+        Option<(&'a str, &'a str)>,
+        bool,
+    );
+
+    fn flat_decisions(flat: &Flat) -> Decisions<'static> {
+        let failed = flat.error_message.is_some();
+        (
+            (!failed).then_some(flat.unread_count),
+            (!flat.avatar_url.is_empty() || !failed).then_some(flat.avatar_url),
+            (!failed).then_some((flat.display_name, flat.avatar_url)),
+            failed,
+        )
+    }
+
+    fn decisions(info: &AccountSyncInfo) -> Decisions<'_> {
+        (
+            info.badge_count(),
+            info.avatar_update(),
+            info.saved_profile(),
+            info.has_error(),
+        )
+    }
+
+    fn with_profile(
+        unread: u32,
+        display_name: &str,
+        avatar_url: &str,
+        error: Option<&str>,
+    ) -> AccountSyncInfo {
+        AccountSyncInfo {
+            profile: Some(ProfileInfo {
+                display_name: display_name.to_string(),
+                avatar_url: avatar_url.to_string(),
+            }),
+            health: SyncHealth {
+                error: error.map(str::to_string),
+                snoozed: false,
+            },
+            ..AccountSyncInfo::new("me@example.com", unread)
+        }
+    }
+
+    #[test]
+    fn test_matches_previous_behavior_matrix() {
+        let profile_failed = AccountSyncInfo {
+            health: SyncHealth {
+                error: Some("获取用户信息失败".to_string()),
+                snoozed: false,
+            },
+            ..AccountSyncInfo::new("me@example.com", 4)
+        };
+        let cases = [
+            // 完整成功
+            (
+                Flat {
+                    unread_count: 5,
+                    avatar_url: "/cache/me.png",
+                    display_name: "Me",
+                    error_message: None,
+                },
+                with_profile(5, "Me", "/cache/me.png", None),
+            ),
+            // 成功但没有头像：头像换成占位符
+            (
+                Flat {
+                    unread_count: 0,
+                    avatar_url: "",
+                    display_name: "Me",
+                    error_message: None,
+                },
+                with_profile(0, "Me", "", None),
+            ),
+            // Gmail 资料获取失败：旧结构以空头像、邮箱作为名称占位
+            (
+                Flat {
+                    unread_count: 4,
+                    avatar_url: "",
+                    display_name: "me@example.com",
+                    error_message: Some("获取用户信息失败"),
+                },
+                profile_failed,
+            ),
+            // Outlook 资料获取失败但头像已缓存
+            (
+                Flat {
+                    unread_count: 4,
+                    avatar_url: "/cache/me.png",
+                    display_name: "Me",
+                    error_message: Some("获取用户信息失败"),
+                },
+                with_profile(4, "Me", "/cache/me.png", Some("获取用户信息失败")),
+            ),
+            // 界面为失败的同步构造的结果
+            (
+                Flat {
+                    unread_count: 0,
+                    avatar_url: "",
+                    display_name: "me@example.com",
+                    error_message: Some("boom"),
+                },
+                AccountSyncInfo::failed("me@example.com", "boom"),
+            ),
+        ];
+
+        for (flat, info) in &cases {
+            assert_eq!(
+                decisions(info),
+                flat_decisions(flat),
+                "{:?}",
+                flat.error_message
+            );
+        }
+    }
+
+    #[test]
+    fn test_not_fetched_differs_from_zero() {
+        let failed = AccountSyncInfo::failed("me@example.com", "boom");
+        assert_eq!(failed.counters.unread, None);
+        assert_eq!(failed.unread(), 0);
+
+        let zero = AccountSyncInfo::new("me@example.com", 0);
+        assert_eq!(zero.badge_count(), Some(0));
+        assert_eq!(zero.counters.spam_unread, None);
+        assert_eq!(zero.profile, None);
+    }
+
+    #[test]
+    fn test_notify_unread_falls_back_to_badge() {
+        let mut info = AccountSyncInfo::new("me@example.com", 9);
+        assert_eq!(info.notify_unread(), 9);
+
+        info.counters.notify_unread = Some(2);
+        assert_eq!(info.notify_unread(), 2);
+    }
+}
@@ -4,150 +4,195 @@
 slint::include_modules!();
 
 use anyhow::Result;
-use slint::Model;
-use std::sync::{Arc, mpsc};
+use slint::ComponentHandle;
+use startup::{Phase, StartupError};
+use std::sync::mpsc;
 
+mod app;
+mod cli;
 mod config;
+mod doctor;
+mod headless;
 mod mail;
 mod notification;
+mod startup;
 mod sync;
 mod tray;
 mod ui;
 mod utils;
 
 fn main() -> Result<()> {
-    // 1. 初始化日志
-    init_logger()?;
+    // 0. 解析命令行参数（--version / --help / --doctor / --reset-config 处理完即退出）
+    // 帮助与参数错误在读取配置前输出，跟随系统语言（之后按配置重新设置）
+    utils::i18n::set_language(utils::i18n::Language::system());
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(cli::CliAction::Run(args)) => args,
+        Ok(cli::CliAction::PrintVersion) => {
+            cli::print_info(&cli::version_text());
+            return Ok(());
+        }
+        Ok(cli::CliAction::PrintHelp) => {
+            cli::print_info(cli::help_text());
+            return Ok(());
+        }
+        Ok(cli::CliAction::Doctor) => {
+            run_doctor();
+            return Ok(());
+        }
+        Ok(cli::CliAction::PrintCounts) => {
+            run_print_counts();
+            return Ok(());
+        }
+        Err(message) => {
+            cli::print_error(&message);
+            std::process::exit(2);
+        }
+    };
 
-    // 2. 创建 Tokio 运行时（用于 async OAuth2）
-    let rt = tokio::runtime::Runtime::new()?;
-    let rt_handle = rt.handle().clone();
+    if args.reset_config {
+        reset_config();
+        return Ok(());
+    }
 
-    // 3. 创建通信通道
-    let (tray_tx, tray_rx) = mpsc::channel::<tray::TrayCommand>();
+    // 1. 初始化日志（guard 需保持到程序结束，确保文件日志全部落盘）
+    let _log_guard = startup::run(Phase::Logger, init_logger).inspect_err(startup::report_fatal)?;
+    utils::crash::install_panic_hook();
+
+    // 1.1 单实例检查：已有实例时通知其显示窗口（或立即同步）并退出；--hidden 时不打扰已有实例
+    let instance_message = if args.sync_now {
+        Some(utils::single_instance::InstanceMessage::SyncNow)
+    } else if args.hidden {
+        None
+    } else {
+        Some(utils::single_instance::InstanceMessage::Show)
+    };
+    let _instance_guard = match utils::single_instance::ensure_single_instance(instance_message) {
+        utils::single_instance::InstanceRole::Primary(guard) => guard,
+        utils::single_instance::InstanceRole::Secondary => return Ok(()),
+    };
 
-    // 4. 创建 Slint UI
-    let main_window = MainWindow::new()?;
+    // 致命的启动失败弹出提示框说明失败的阶段（日志 guard 此时仍存活，错误已写入日志）
+    run_app(&args).inspect_err(startup::report_fatal)?;
+    Ok(())
+}
 
-    // 5. 加载已保存的账户
-    let saved_accounts = match config::storage::load_accounts() {
-        Ok(accounts) if !accounts.is_empty() => {
-            tracing::info!("✅ 从文件加载 {} 个账户", accounts.len());
-            accounts
-        }
-        Ok(_) => {
-            tracing::info!("📭 无已保存账户");
-            vec![]
-        }
+/// 启动并运行托盘应用，直到用户退出
+fn run_app(args: &cli::CliArgs) -> Result<(), StartupError> {
+    // 1.2 按配置构建共享 HTTP 客户端（含代理设置）
+    utils::http_client::HttpClientFactory::init();
+
+    // 1.3 订阅系统网络连接状态（同步引擎据此跳过离线时的请求）
+    utils::connectivity::start_monitor();
+
+    // 2. 创建 Tokio 运行时（用于 async OAuth2 与同步）
+    let rt = startup::run(Phase::Runtime, || Ok(build_runtime()?))?;
+
+    // 3. 创建托盘命令通道与应用状态
+    let (tray_tx, tray_rx) = tray::command_channel();
+    let cfg = config::load().unwrap_or_else(|e| {
+        tracing::warn!("⚠️ 加载配置失败: {:#}, 使用默认配置", e);
+        config::Config::default()
+    });
+    let state = app::AppState::new(rt.handle().clone(), tray_tx, cfg);
+
+    // 4. 创建 Slint UI 并加载已保存的账户（同时应用主题、语言等配置）
+    // 窗口无法创建时 Slint 后端不可用，托盘依赖的事件循环也无法运行，只能退出
+    let main_window = startup::run(Phase::Window, || Ok(MainWindow::new()?))?;
+    state.init_window(&main_window);
+
+    // 5. 创建系统托盘（开机自启时任务栏可能尚未就绪，失败时稍后重试）
+    // 仍然失败时以无托盘模式运行：窗口中显示说明，再次运行程序可重新打开窗口
+    let tray_handle = match startup::retry(
+        Phase::Tray,
+        startup::TRAY_ATTEMPTS,
+        startup::TRAY_RETRY_DELAY,
+        || tray::create_tray_icon(state.tray_tx.clone()),
+    ) {
+        Ok(guard) => Some(guard),
         Err(e) => {
-            tracing::warn!("⚠️ 加载账户失败: {}, 使用空列表", e);
-            vec![]
+            tracing::error!("⚠️ {}，以无托盘模式运行", e);
+            main_window.set_tray_missing(true);
+            None
         }
     };
 
-    // 转换为 Slint 类型
-    let slint_accounts: Vec<Account> = saved_accounts.into_iter().map(|acc| acc.into()).collect();
-
-    let account_model = slint::VecModel::from(slint_accounts);
-    main_window.set_accounts(std::rc::Rc::new(account_model).into());
+    // 5.1 监听其他实例的消息（转发为托盘命令）
+    let instance_tx = state.tray_tx.clone();
+    if let Err(e) = utils::single_instance::start_listener(move |message| match message {
+        utils::single_instance::InstanceMessage::Show => {
+            tray::send_command(&instance_tx, tray::TrayCommand::ShowWindow);
+            None
+        }
+        utils::single_instance::InstanceMessage::SyncNow => {
+            tray::send_command(&instance_tx, tray::TrayCommand::SyncNow);
+            None
+        }
+        // 无界面模式查询：直接回复托盘摘要中缓存的结果，不额外同步
+        utils::single_instance::InstanceMessage::Counts => {
+            Some(headless::to_json(&headless::from_summary()))
+        }
+    }) {
+        tracing::warn!("启动单实例监听失败: {}", e);
+    }
 
-    // 6. 设置初始应用状态为 Normal（绿色 N）
-    main_window.set_app_status("normal".into());
-    tracing::debug!("应用状态初始化: Normal (绿色 N)");
-    tracing::info!("app_status set -> normal (初始化)");
+    // 6. 绑定 Slint 回调，后台检查更新（每天最多一次，不阻塞启动）
+    app::bind_callbacks(&main_window, &state);
+    state.check_updates(&main_window);
 
-    // 6.1 从配置加载并初始化主题
-    if let Ok(cfg) = config::load() {
-        let is_dark = cfg.app.theme == "dark";
-        Theme::get(&main_window).set_is_dark(is_dark);
-        tracing::info!("主题初始化: {}", if is_dark { "dark" } else { "light" });
-    }
+    // 7. 凭据自检（在阻塞线程中派生加密密钥）通过后启动同步引擎
+    state.start_sync_after_self_test(&main_window);
 
-    // 7. 创建系统托盘
-    let _tray_handle = tray::create_tray_icon(tray_tx.clone())?;
-
-    // 8. 绑定 Slint 回调（传入 Tokio 运行时）
-    bind_callbacks(&main_window, rt_handle.clone())?;
-
-    // 9. 启动同步引擎
-    let sync_engine = Arc::new(sync::SyncEngine::new(rt_handle.clone()));
-    let window_weak_for_sync = main_window.as_weak();
-
-    sync_engine.start(move |email, res| {
-        match res {
-            Ok(sync_info) => {
-                tracing::info!(
-                    "[DEBUG-UNREAD] 回调收到: email={}, unread_count={}",
-                    email,
-                    sync_info.unread_count
-                );
-
-                // 更新UI（必须在事件循环中）
-                let weak = window_weak_for_sync.clone();
-                let sync_info_cloned = sync_info.clone();
-                slint::invoke_from_event_loop(move || {
-                    if let Some(window) = weak.upgrade() {
-                        update_account_sync_info(&window, sync_info_cloned.clone());
-
-                        // 优先检查网络问题：若同步过程中曾检测到网络问题，显示红色
-                        if sync_info_cloned.network_issue {
-                            window.set_app_status("error".into());
-                            tracing::info!("app_status set -> error (network_issue)");
-                            tracing::error!(
-                                "账户 {} 同步过程中检测到网络问题",
-                                sync_info_cloned.email
-                            );
-                            // 网络和 Token 均正常 -> 绿色
-                            window.set_app_status("normal".into());
-                        }
-                    }
-                })
-                .ok();
-            }
-            Err(err_msg) => {
-                tracing::error!("同步账户失败: {} -> {}", email, err_msg);
-
-                // 构造带错误信息的 AccountSyncInfo 以更新 UI（标为 has_error）
-                let info = mail::gmail::AccountSyncInfo {
-                    email: email.clone(),
-                    unread_count: 0,
-                    avatar_url: String::new(),
-                    display_name: email.clone(),
-                    error_message: Some(err_msg.clone()),
-                    network_issue: true,
-                };
-
-                let weak = window_weak_for_sync.clone();
-                let err_clone = err_msg.clone();
-                slint::invoke_from_event_loop(move || {
-                    if let Some(window) = weak.upgrade() {
-                        update_account_sync_info(&window, info);
-
-                        // 网络不可用 -> 红色；Token或其他错误 -> 也是红色（用户要求）
-                        window.set_app_status("error".into());
-                        tracing::info!("app_status set -> error (callback Err: {})", err_clone);
-                    }
-                })
-                .ok();
-            }
+    // 7.1 监听配置文件变更（手动编辑后无需重启即可生效）
+    let _config_watcher = match config::watcher::start({
+        let state = state.clone();
+        let weak = main_window.as_weak();
+        move |event| state.apply_config_event(event, &weak)
+    }) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            tracing::warn!("启动配置文件监听失败，修改配置需重启生效: {:#}", e);
+            None
         }
+    };
+
+    // 7.2 监听系统睡眠恢复：宽限期后立即同步
+    let resume_state = state.clone();
+    utils::power::start_resume_listener(move || resume_state.sync_engine.notify_resumed());
+
+    // 7.3 监听系统高对比度与动画设置（切换后无需重启即可生效）
+    let a11y_window = main_window.as_weak();
+    utils::accessibility::start_listener(move |settings| {
+        a11y_window
+            .upgrade_in_event_loop(move |window| ui::apply_accessibility(&window, settings))
+            .ok();
     });
 
-    // 10. 启动托盘事件监听线程（传入 SyncEngine 引用与退出信号以便优雅退出）
-    let window_weak = main_window.as_weak();
-    let tray_sync = sync_engine.clone();
-    // 创建退出信号通道，主线程将在 UI 事件循环返回后等待此信号
+    // 8. 启动托盘命令处理线程（退出时通过退出信号通知主线程完成清理）
     let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>();
-    let shutdown_tx_clone = shutdown_tx.clone();
-    std::thread::spawn(move || {
-        handle_tray_commands(tray_rx, window_weak, tray_sync, shutdown_tx_clone);
-    });
+    startup::run(Phase::Commands, || {
+        app::spawn_tray_commands(tray_rx, &state, &main_window, shutdown_tx)
+    })?;
+
+    // 9. 窗口初始显示（默认在启动时打开主界面，--hidden 时仅驻留托盘；没有托盘时总是显示）
+    if args.hidden && tray_handle.is_some() {
+        tracing::info!(
+            "{} 启动（--hidden），仅驻留托盘",
+            utils::app_info::version_string()
+        );
+    } else {
+        tracing::info!(
+            "{} 启动，显示主界面于右下角",
+            utils::app_info::version_string()
+        );
+        tray::show_window_near_tray(&main_window);
+    }
 
-    // 11. 窗口初始显示（默认在启动时打开主界面）
-    tracing::info!("NanoMail v0.1.0 启动，显示主界面于右下角");
-    tray::show_window_near_tray(&main_window);
+    // 9.1 --sync-now 且本进程为首个实例时，启动后立即同步一次
+    if args.sync_now {
+        state.sync_engine.trigger_sync();
+    }
 
-    // 12. 运行 Slint 全局事件循环（保持运行，即使窗口被隐藏）
+    // 10. 运行 Slint 全局事件循环（保持运行，即使窗口被隐藏）
     // 使用 run_event_loop_until_quit() 确保即使窗口隐藏也能继续处理事件
     let _ = slint::run_event_loop_until_quit();
 
@@ -157,415 +202,180 @@ fn main() -> Result<()> {
     let _ = shutdown_rx.recv();
 
     tracing::info!("收到推出信号，开始优雅关机...");
-    sync_engine.request_stop();
+    if tray::is_degraded() {
+        tracing::warn!("托盘处于降级状态，详见 logs 目录中的崩溃报告");
+    }
+    state.sync_engine.request_stop();
+
+    // 取消仍在进行的授权、测试连接等任务，最多等待片刻
+    rt.block_on(
+        state
+            .workers
+            .shutdown(std::time::Duration::from_millis(500)),
+    );
     std::thread::sleep(std::time::Duration::from_millis(200));
 
-    Ok(())
-}
-
-/// 处理托盘命令（在独立线程中运行）
-fn handle_tray_commands(
-    rx: mpsc::Receiver<tray::TrayCommand>,
-    window_weak: slint::Weak<MainWindow>,
-    sync_engine: std::sync::Arc<sync::SyncEngine>,
-    shutdown_tx: mpsc::Sender<()>,
-) {
-    while let Ok(cmd) = rx.recv() {
-        let weak = window_weak.clone();
-
-        // 对于可能影响运行时或需要先停止后台任务的命令，优先处理
-        match cmd {
-            tray::TrayCommand::Exit => {
-                tracing::info!("========================================");
-                tracing::info!("托盘收到退出命令，开始优雅关机流程");
-                tracing::info!("========================================");
-
-                // 请求同步引擎停止（同步接口）
-                sync_engine.request_stop();
-
-                // 在主线程执行 UI 隐藏并退出事件循环
-                let quit_result = slint::invoke_from_event_loop(move || {
-                    if let Some(window) = weak.upgrade() {
-                        tracing::info!("退出流程: 隐藏窗口");
-                        window.hide().ok();
-                    }
-                    tracing::info!("退出流程: 调用 quit_event_loop()");
-                    match slint::quit_event_loop() {
-                        Ok(_) => tracing::info!("退出流程: quit_event_loop() 成功"),
-                        Err(e) => tracing::error!("退出流程: quit_event_loop() 失败: {:?}", e),
-                    }
-                });
-
-                match quit_result {
-                    Ok(_) => tracing::info!("退出流程: invoke_from_event_loop 成功"),
-                    Err(e) => {
-                        tracing::error!("退出流程: invoke_from_event_loop 失败: {:?}", e);
-                        // 如果 invoke_from_event_loop 失败，强制退出
-                        tracing::error!("退出流程: 尝试强制退出...");
-                        std::process::exit(0);
-                    }
-                }
-
-                // 发送退出信号给主线程以完成优雅退出（主线程在 run_event_loop() 返回后会等待此信号）
-                if let Err(e) = shutdown_tx.send(()) {
-                    tracing::error!("退出流程: 发送退出信号失败: {:?}", e);
-                }
-
-                tracing::info!("退出流程: 托盘线程退出循环");
-                // 退出循环
-                break;
-            }
-            _ => {}
-        }
-
-        // 确保 UI 更新在主线程执行
-        let sync_engine_clone = sync_engine.clone();
-        let result = slint::invoke_from_event_loop(move || {
-            if let Some(window) = weak.upgrade() {
-                match cmd {
-                    tray::TrayCommand::ToggleWindow => {
-                        tracing::info!("处理托盘命令: ToggleWindow");
-                        if !window.window().is_visible() {
-                            // 窗口将要显示，重新加载 UI 资源并触发同步
-                            reload_accounts_ui(&window);
-                            sync_engine_clone.trigger_sync();
-                        } else {
-                            // 窗口将要隐藏，清空 UI 资源
-                            clear_accounts_ui(&window);
-                        }
-                        tray::toggle_window(&window);
-                    }
-                    tray::TrayCommand::ShowWindow => {
-                        tracing::info!("处理托盘命令: ShowWindow");
-                        // 重新加载 UI 资源并触发同步
-                        reload_accounts_ui(&window);
-                        sync_engine_clone.trigger_sync();
-                        tray::show_window_near_tray(&window);
-                    }
-                    tray::TrayCommand::HideWindow => {
-                        tracing::info!("处理托盘命令: HideWindow");
-                        window.hide().ok();
-                        // 清空 UI 资源以减少内存占用
-                        clear_accounts_ui(&window);
-                    }
-                    tray::TrayCommand::OpenGmail => {
-                        tracing::info!("处理托盘命令: OpenGmail");
-                        open_gmail();
-                    }
-                    tray::TrayCommand::ShowAbout => {
-                        tracing::info!("处理托盘命令: ShowAbout");
-                        show_about_dialog();
-                    }
-                    _ => {}
-                }
-            } else {
-                tracing::warn!("窗口引用已失效，无法处理托盘命令");
-            }
-        });
-
-        if let Err(e) = result {
-            tracing::error!("invoke_from_event_loop 失败: {:?}", e);
-        }
+    // 写入界面上刚切换、尚在等待后台写入的设置
+    if let Err(e) = config::flush() {
+        tracing::error!("退出前写入配置文件失败: {:#}", e);
     }
-}
-
-fn show_about_dialog() {
-    tracing::info!("显示关于对话框");
-    // MVP: 打开 GitHub 页面
-    webbrowser::open("https://github.com/Keriyar/NanoMail").ok();
-}
 
-fn open_gmail() {
-    let url = "https://mail.google.com/mail/u/0/#inbox";
-    if let Err(e) = webbrowser::open(url) {
-        tracing::error!("无法打开浏览器: {}", e);
+    // 写入尚未保存的账户修改（账户文件合并写入，可能仍有等待中的修改）
+    if let Err(e) = config::storage::flush() {
+        tracing::error!("退出前写入账户文件失败: {:#}", e);
     }
-}
 
-/// 绑定所有 Slint 回调
-fn bind_callbacks(main_window: &MainWindow, rt_handle: tokio::runtime::Handle) -> Result<()> {
-    // 主题切换
-    main_window.on_theme_toggled({
-        let weak = main_window.as_weak();
-        move || {
-            tracing::info!("[回调] 主题切换按钮被点击");
-            if let Some(window) = weak.upgrade() {
-                // 切换主题
-                let current_is_dark = Theme::get(&window).get_is_dark();
-                let new_is_dark = !current_is_dark;
-                Theme::get(&window).set_is_dark(new_is_dark);
-                tracing::info!(
-                    "主题切换: {} -> {}",
-                    if current_is_dark { "dark" } else { "light" },
-                    if new_is_dark { "dark" } else { "light" }
-                );
-
-                // 持久化主题偏好
-                if let Ok(mut cfg) = config::load() {
-                    cfg.app.theme = if new_is_dark {
-                        "dark".to_string()
-                    } else {
-                        "light".to_string()
-                    };
-                    if let Err(e) = config::save(&cfg) {
-                        tracing::error!("保存主题配置失败: {}", e);
-                    }
-                }
-            }
-        }
-    });
-
-    // 添加账户（集成 OAuth2）
-    main_window.on_add_account_clicked({
-        let window_weak = main_window.as_weak();
-
-        move || {
-            tracing::info!("[回调] 添加账户按钮被点击");
-
-            let weak = window_weak.clone();
-            let handle = rt_handle.clone();
-
-            std::thread::spawn(move || {
-                handle.block_on(async {
-                    // 执行 OAuth2 认证
-                    match mail::gmail::authenticate().await {
-                        Ok(account) => {
-                            tracing::info!("✅ OAuth2 成功: {}", account.email);
-
-                            // 立即同步账户信息（获取未读数）
-                            let (sync_info, updated_account) =
-                                match mail::gmail::sync_account_info(&account).await {
-                                    Ok((info, updated)) => (Some(info), updated),
-                                    Err(e) => {
-                                        tracing::error!("立即同步失败: {}", e);
-                                        (None, None)
-                                    }
-                                };
-
-                            // 使用更新后的账户（如果 Token 被刷新）
-                            let final_account = updated_account.unwrap_or(account);
-
-                            // 更新 UI（必须在事件循环中）
-                            slint::invoke_from_event_loop(move || {
-                                if let Some(window) = weak.upgrade() {
-                                    update_accounts_ui(&window, final_account, sync_info);
-                                }
-                            })
-                            .ok();
-                        }
-                        Err(e) => {
-                            tracing::error!("❌ OAuth2 失败: {}", e);
-                            // TODO: 显示错误对话框
-                        }
-                    }
-                });
-            });
-        }
-    });
-
-    // 打开 Gmail
-    main_window.on_open_gmail_clicked({
-        move || {
-            tracing::info!("[回调] 打开 Gmail 按钮被点击");
-            open_gmail();
-        }
-    });
-
-    // 反馈按钮
-    main_window.on_feedback_clicked({
-        move || {
-            tracing::info!("[回调] 反馈按钮被点击");
-            let url = "https://github.com/Keriyar/NanoMail";
-            webbrowser::open(url).ok();
-        }
-    });
+    // 保存同步记录（未开启 persist_sync_history 时清除上次保存的记录）
+    let persist_history = state.config().app.persist_sync_history;
+    sync::history::flush(config::state::global(), persist_history);
 
-    // 窗口中的"隐藏到托盘"按钮（之前名为退出）
-    main_window.on_minimize_clicked({
-        let weak = main_window.as_weak();
-        move || {
-            tracing::info!("[回调] 隐藏到托盘按钮被点击，隐藏窗口并释放资源");
-            if let Some(window) = weak.upgrade() {
-                window.hide().ok();
-                // 清空 UI 资源以减少内存占用
-                clear_accounts_ui(&window);
-            }
-        }
-    });
-
-    // 头像重试
-    main_window.on_avatar_retry({
-        move |index| {
-            tracing::info!("[回调] 头像重试: 账户索引 {}", index);
-            // TODO: 阶段4 实现头像重新加载
-        }
-    });
+    // 等待浏览器回调的授权线程不会自行结束，不能无限期等待运行时关闭
+    rt.shutdown_timeout(std::time::Duration::from_millis(500));
 
     Ok(())
 }
 
-/// 将新账户添加到 UI 列表
-fn update_accounts_ui(
-    window: &MainWindow,
-    gmail_account: mail::gmail::GmailAccount,
-    sync_info: Option<mail::gmail::AccountSyncInfo>,
-) {
-    use slint::VecModel;
-    use std::rc::Rc;
-
-    // 转换为 Slint Account 类型
-    let mut slint_account: Account = gmail_account.into();
-
-    // 如果有同步信息，更新未读数和头像
-    if let Some(info) = sync_info {
-        slint_account.unread_count = info.unread_count as i32;
-
-        // 将头像路径转换为 Slint Image（若路径为空或加载失败则使用默认 image）
-        if !info.avatar_url.is_empty() {
-            match slint::Image::load_from_path(std::path::Path::new(&info.avatar_url)) {
-                Ok(img) => slint_account.avatar_image = img,
-                Err(_) => slint_account.avatar_image = slint::Image::default(),
-            }
-        } else {
-            slint_account.avatar_image = slint::Image::default();
-        }
-    }
+/// 异步运行时的工作线程数
+///
+/// 程序大部分时间在两轮同步之间休眠，每轮只发出少量短小的 HTTP 请求，两个工作线程足够；
+/// 默认按 CPU 核数创建的工作线程在核数多的电脑上只会增加空闲时的占用。
+/// 会阻塞的操作（密钥派生、头像解码与缩放、读取与保存账户）通过 `spawn_blocking` 交给按需创建、
+/// 空闲后自动退出的阻塞线程，不会卡住这两个工作线程
+const IO_WORKER_THREADS: usize = 2;
+
+/// 创建异步运行时（只启用计时器与网络 IO）
+fn build_runtime() -> std::io::Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(IO_WORKER_THREADS)
+        .thread_name("nanomail-io")
+        .enable_time()
+        .enable_io()
+        .build()
+}
 
-    // 获取现有账户列表
-    let accounts = window.get_accounts();
-    let mut new_accounts = Vec::new();
+/// 处理 --reset-config：确认后备份并重置配置文件
+fn reset_config() {
+    // 配置文件可能已损坏，读取失败时跟随系统语言
+    let language = config::load()
+        .map(|cfg| cfg.app.language)
+        .unwrap_or_else(|_| "auto".to_string());
+    utils::i18n::set_language(utils::i18n::Language::from_setting(&language));
+
+    let path = config::config_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| "config.toml".to_string());
+    let prompt = utils::i18n::tr_fmt("reset.confirm", &[&path]);
+    if !utils::dialog::confirm(&prompt) {
+        return;
+    }
 
-    for i in 0..accounts.row_count() {
-        if let Some(acc) = accounts.row_data(i) {
-            new_accounts.push(acc);
+    match config::reset_to_default() {
+        Ok(Some(backup)) => {
+            cli::print_info(&utils::i18n::tr_fmt("reset.done", &[&backup.display()]))
         }
+        Ok(None) => cli::print_info(utils::i18n::tr("reset.created")),
+        Err(e) => cli::print_error(&utils::i18n::tr_fmt("reset.failed", &[&e])),
     }
+}
 
-    // 添加新账户
-    new_accounts.push(slint_account);
-
-    let account_count = new_accounts.len();
-
-    // 更新 UI
-    let model = VecModel::from(new_accounts);
-    window.set_accounts(Rc::new(model).into());
-
-    tracing::info!("UI 已更新：显示 {} 个账户", account_count);
+/// 处理 --doctor：运行诊断并输出报告，存在失败项时以非零状态退出
+fn run_doctor() {
+    let cfg = config::load().unwrap_or_default();
+    utils::i18n::set_language(utils::i18n::Language::from_setting(&cfg.app.language));
+    sync::configure(&cfg);
+
+    let results = doctor::run_blocking();
+    let report = doctor::format_report(&results);
+    if doctor::has_failures(&results) {
+        cli::print_error(&report);
+        std::process::exit(1);
+    }
+    cli::print_info(&report);
 }
 
-/// 更新账户同步信息（未读数、头像和错误状态）
-fn update_account_sync_info(window: &MainWindow, sync_info: mail::gmail::AccountSyncInfo) {
-    use slint::VecModel;
-    use std::rc::Rc;
-
-    let accounts = window.get_accounts();
-    let mut new_accounts = Vec::new();
-
-    // 找到对应账户并更新
-    for i in 0..accounts.row_count() {
-        if let Some(mut acc) = accounts.row_data(i) {
-            if acc.email.as_str() == sync_info.email {
-                // 若同步成功，更新未读数；若失败则保持旧值（或者在 AccountSyncInfo 里处理逻辑）
-                // 当前逻辑：sync_info 包含即时数据。如果失败，external sync_info.unread_count 默认为0
-                // 但 callback 处理时手动构造了 unread_count=0 的 info
-                // 这里我们要判断：如果 error_message 存在，则忽略 unread_count 的更新，仅更新错误状态
-                if sync_info.error_message.is_none() {
-                    tracing::info!(
-                        "[DEBUG-UNREAD] UI更新前: 旧值={}, 新值={}",
-                        acc.unread_count,
-                        sync_info.unread_count
-                    );
-                    acc.unread_count = sync_info.unread_count as i32;
-                    tracing::info!(
-                        "[DEBUG-UNREAD] UI更新后: acc.unread_count={}",
-                        acc.unread_count
-                    );
-                }
-                if !sync_info.avatar_url.is_empty() {
-                    match slint::Image::load_from_path(std::path::Path::new(&sync_info.avatar_url))
-                    {
-                        Ok(img) => acc.avatar_image = img,
-                        Err(_) => acc.avatar_image = slint::Image::default(),
-                    }
-                } else {
-                    acc.avatar_image = slint::Image::default();
-                }
-
-                // 如果有错误，标记为 has_error 并显示错误消息
-                if let Some(error_msg) = &sync_info.error_message {
-                    acc.has_error = true;
-                    tracing::error!("❌ 账户 {} 同步失败: {}", sync_info.email, error_msg);
-                } else {
-                    acc.has_error = false;
-                }
-
-                tracing::debug!(
-                    "更新账户 {} 未读数: {} (错误: {})",
-                    sync_info.email,
-                    sync_info.unread_count,
-                    sync_info.error_message.as_deref().unwrap_or("无")
-                );
+/// 处理 --print-counts：输出各账户未读数（JSON 数组），有账户同步失败时以非零状态退出
+fn run_print_counts() {
+    let language = config::load()
+        .map(|cfg| cfg.app.language)
+        .unwrap_or_else(|_| "auto".to_string());
+    utils::i18n::set_language(utils::i18n::Language::from_setting(&language));
+
+    match headless::collect() {
+        Ok(counts) => {
+            cli::print_info(&headless::to_json(&counts));
+            if headless::any_failed(&counts) {
+                std::process::exit(1);
             }
-            new_accounts.push(acc);
+        }
+        Err(e) => {
+            cli::print_error(&format!("{:#}", e));
+            std::process::exit(1);
         }
     }
-
-    // 更新 UI
-    let model = VecModel::from(new_accounts);
-    window.set_accounts(Rc::new(model).into());
 }
 
-/// 清空 UI 账户数据（释放 Image 内存）
+/// 初始化日志系统
 ///
-/// 在窗口隐藏时调用，减少内存占用
-fn clear_accounts_ui(window: &MainWindow) {
-    use slint::VecModel;
-    use std::rc::Rc;
-
-    // 设置为空列表，释放所有 Image 对象
-    let empty_model: VecModel<Account> = VecModel::default();
-    window.set_accounts(Rc::new(empty_model).into());
-    
-    tracing::info!("📦 UI 资源已释放（账户数据已清空）");
-}
-
-/// 重新加载 UI 账户数据
+/// - 文件日志：`%APPDATA%\NanoMail\logs\nanomail.log`，按天或 5 MB 轮转，保留 5 个历史文件
+/// - 控制台日志：仅 debug 构建启用（release 构建没有控制台）
+/// - 过滤级别：优先使用 `RUST_LOG` 环境变量，其次使用配置中的 `logging.level`
+/// - `logging.json = true` 时文件日志每行输出一个 JSON 对象
+/// - 同步各阶段的 span 耗时由 `PhaseTimingLayer` 汇总（不受日志级别影响）
 ///
-/// 在窗口显示时调用，从本地存储加载账户并填充 UI
-fn reload_accounts_ui(window: &MainWindow) {
-    use slint::VecModel;
-    use std::rc::Rc;
-
-    // 从本地存储加载账户
-    let accounts = match config::storage::load_accounts() {
-        Ok(accounts) => accounts,
+/// 返回的 guard 必须在整个程序生命周期内保持存活，否则后台写入线程退出后日志会丢失
+fn init_logger() -> Result<Option<tracing_appender::non_blocking::WorkerGuard>> {
+    use tracing_subscriber::{
+        EnvFilter, Layer, layer::SubscriberExt, reload, util::SubscriberInitExt,
+    };
+
+    let logging = config::load().map(|cfg| cfg.logging).unwrap_or_default();
+    let level = logging.level;
+    let make_filter =
+        || EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&level));
+
+    // 过滤器包在 reload 层中，配置热重载时可替换日志级别
+    let (console_filter, console_handle) = reload::Layer::new(make_filter());
+    let (file_filter, file_handle) = reload::Layer::new(make_filter());
+
+    let console_layer = cfg!(debug_assertions)
+        .then(|| tracing_subscriber::fmt::layer().with_filter(console_filter));
+
+    // 日志目录不可用时退化为仅控制台输出，不阻止程序启动
+    let writer = utils::logging::logs_dir()
+        .and_then(|dir| Ok(utils::logging::RollingFileWriter::new(&dir)?));
+    let (file_layer, guard) = match writer {
+        Ok(writer) => {
+            // panic hook 经由同一个写入器同步追加，轮转大小保持准确
+            let writer = utils::logging::SharedWriter::install(writer);
+            let (non_blocking, guard) = tracing_appender::non_blocking(writer);
+            let layer = tracing_subscriber::fmt::layer()
+                .with_writer(non_blocking)
+                .with_ansi(false);
+            let layer = if logging.json {
+                layer.json().with_filter(file_filter).boxed()
+            } else {
+                layer.with_filter(file_filter).boxed()
+            };
+            (Some(layer), Some(guard))
+        }
         Err(e) => {
-            tracing::warn!("加载账户失败: {}", e);
-            return;
+            eprintln!("初始化文件日志失败: {}", e);
+            (None, None)
         }
     };
 
-    // 转换为 Slint 类型（会加载头像 Image）
-    let slint_accounts: Vec<Account> = accounts.into_iter().map(|acc| acc.into()).collect();
-    let count = slint_accounts.len();
-
-    let model = VecModel::from(slint_accounts);
-    window.set_accounts(Rc::new(model).into());
-
-    tracing::info!("📦 UI 资源已重新加载（{} 个账户）", count);
-}
-
-/// 初始化日志系统
-fn init_logger() -> Result<()> {
-    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "nanomail=debug,info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
+        .with(utils::telemetry::PhaseTimingLayer)
+        .with(console_layer)
+        .with(file_layer)
         .init();
 
-    Ok(())
+    utils::logging::set_level_reloader(Box::new(move |level| {
+        let filter = EnvFilter::try_new(level)?;
+        console_handle.reload(filter.clone())?;
+        file_handle.reload(filter)?;
+        Ok(())
+    }));
+
+    Ok(guard)
 }
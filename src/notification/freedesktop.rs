@@ -0,0 +1,118 @@
+/// Linux 桌面通知后端
+///
+/// 通过 D-Bus 调用 org.freedesktop.Notifications，KDE、GNOME 等桌面环境都会显示在
+/// 各自的通知区域。新邮件通知点击后在浏览器中打开收件箱，合并通知点击后打开窗口
+use anyhow::{Context, Result};
+use notify_rust::{Hint, Notification};
+use std::path::Path;
+
+use crate::utils::app_info;
+use crate::utils::browser::{self, UrlPurpose};
+use crate::utils::i18n::tr;
+
+/// 没有头像时使用的图标（图标主题中的标准名称）
+const FALLBACK_ICON: &str = "mail-unread";
+
+/// 显示桌面通知
+pub fn show(title: &str, body: &str, avatar: Option<&Path>) -> Result<()> {
+    notification(title, body, avatar)
+        .show()
+        .context("调用 org.freedesktop.Notifications 失败")?;
+    Ok(())
+}
+
+/// 显示桌面通知，点击通知时调用 `on_click`
+///
+/// 在单独的线程中等待点击，通知关闭后线程退出
+pub fn show_with_click(
+    title: &str,
+    body: &str,
+    avatar: Option<&Path>,
+    on_click: impl FnOnce() + Send + 'static,
+) -> Result<()> {
+    show_with_action(
+        title,
+        body,
+        avatar,
+        tr("notification.open_window"),
+        on_click,
+    )
+}
+
+/// 显示桌面通知，点击通知时在浏览器中打开 `url`
+pub fn show_with_url(title: &str, body: &str, avatar: Option<&Path>, url: &str) -> Result<()> {
+    let url = url.to_string();
+    show_with_action(title, body, avatar, tr("menu.open_inbox"), move || {
+        browser::open_url(&url, UrlPurpose::Link);
+    })
+}
+
+/// 显示带默认操作（点击通知）的桌面通知，在单独的线程中等待点击
+fn show_with_action(
+    title: &str,
+    body: &str,
+    avatar: Option<&Path>,
+    label: &str,
+    on_click: impl FnOnce() + Send + 'static,
+) -> Result<()> {
+    let handle = notification(title, body, avatar)
+        .action("default", label)
+        .show()
+        .context("调用 org.freedesktop.Notifications 失败")?;
+
+    std::thread::Builder::new()
+        .name("notification-action".to_string())
+        .spawn(move || {
+            handle.wait_for_action(|action| {
+                if action == "default" {
+                    on_click();
+                }
+            })
+        })
+        .context("启动通知点击监听线程失败")?;
+    Ok(())
+}
+
+fn notification(title: &str, body: &str, avatar: Option<&Path>) -> Notification {
+    let icon = avatar
+        .filter(|path| path.is_file())
+        .and_then(|path| path.to_str())
+        .unwrap_or(FALLBACK_ICON);
+
+    let mut notification = Notification::new();
+    notification
+        .appname(app_info::APP_NAME)
+        .summary(title)
+        .body(&escape_markup(body))
+        .icon(icon)
+        .hint(Hint::Category("email.arrived".to_string()));
+    notification
+}
+
+/// 正在运行的通知服务名称（供诊断使用）
+pub fn server_name() -> Result<String> {
+    let info = notify_rust::get_server_information().context("未找到桌面通知服务")?;
+    Ok(format!("{} {}", info.name, info.version))
+}
+
+/// 转义正文中的标记字符
+///
+/// 多数通知服务把正文当作简单标记解析，发件人中的 `<me@example.com>` 会被当成标签吞掉
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_markup() {
+        assert_eq!(
+            escape_markup("Alice <alice@example.com> & Bob"),
+            "Alice &lt;alice@example.com&gt; &amp; Bob"
+        );
+    }
+}
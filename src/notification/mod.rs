@@ -1,56 +1,485 @@
-/// Windows 原生 Toast 通知模块
+/// 系统通知模块
 ///
-/// 使用 WinRT API 发送系统级通知，显示在 Windows 通知中心
-use winrt_toast_reborn::{Toast, ToastManager};
+/// 通知内容（标题、正文）在这里统一生成，再交给平台后端显示：
+/// Windows 使用 WinRT Toast（进入通知中心），Linux 使用 org.freedesktop.Notifications。
+/// 新邮件通知按同步轮次收集（见 [`RoundNotifications`]），多个账户同时有新邮件时合并为一条
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
 
-/// 获取或创建 ToastManager
-/// 使用 PowerShell 的 AUMID 作为临时方案
-fn get_toast_manager() -> ToastManager {
-    ToastManager::new(ToastManager::POWERSHELL_AUM_ID)
+#[cfg(target_os = "linux")]
+mod freedesktop;
+#[cfg(windows)]
+mod toast;
+pub mod webhook;
+
+#[cfg(target_os = "linux")]
+use freedesktop as backend;
+#[cfg(windows)]
+use toast as backend;
+
+#[cfg(target_os = "linux")]
+pub use freedesktop::server_name;
+#[cfg(windows)]
+pub use toast::AUM_ID;
+
+use crate::mail::MailPreview;
+use crate::utils::i18n::{tr, tr_fmt};
+use crate::utils::redact::redact_email;
+
+/// 是否启用新邮件通知（对应配置 `[notifications] enabled`）
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// 通知内容级别（对应配置 `[notifications] detail`）
+static DETAIL: Mutex<NotificationDetail> = Mutex::new(NotificationDetail::Full);
+
+/// 同一轮中多个账户有新邮件时是否合并为一条通知（对应配置 `[notifications] combine`）
+static COMBINE: AtomicBool = AtomicBool::new(true);
+
+/// 点击合并通知时打开窗口（界面模式启动同步时注册）
+static OPEN_WINDOW: OnceLock<Box<dyn Fn() + Send + Sync>> = OnceLock::new();
+
+/// 通知正文最多列出的邮件数
+const MAX_PREVIEW_LINES: usize = 3;
+
+/// 发件人最多显示的字符数
+const MAX_SENDER_CHARS: usize = 32;
+
+/// 主题最多显示的字符数
+const MAX_SUBJECT_CHARS: usize = 60;
+
+/// 通知中显示的邮件信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotificationDetail {
+    /// 发件人与主题
+    #[default]
+    Full,
+
+    /// 仅发件人（共享屏幕时不显示主题）
+    SenderOnly,
+
+    /// 仅新邮件数
+    CountOnly,
+}
+
+impl NotificationDetail {
+    /// 解析配置值（无法识别时显示完整内容）
+    pub fn from_setting(setting: &str) -> Self {
+        match setting.trim().to_ascii_lowercase().as_str() {
+            "sender_only" => Self::SenderOnly,
+            "count_only" => Self::CountOnly,
+            _ => Self::Full,
+        }
+    }
+}
+
+/// 启用或关闭新邮件通知
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+    tracing::info!("新邮件通知: {}", if enabled { "已启用" } else { "已关闭" });
+}
+
+/// 设置通知内容级别
+pub fn set_detail(detail: NotificationDetail) {
+    *DETAIL.lock().unwrap_or_else(|e| e.into_inner()) = detail;
+    tracing::info!("通知内容: {:?}", detail);
+}
+
+/// 设置是否合并同一轮中多个账户的新邮件通知
+pub fn set_combine(combine: bool) {
+    COMBINE.store(combine, Ordering::SeqCst);
+    tracing::info!("合并新邮件通知: {}", if combine { "开启" } else { "关闭" });
+}
+
+/// 注册点击合并通知时的操作（打开窗口）；只有第一次注册有效
+pub fn set_open_window_handler(handler: impl Fn() + Send + Sync + 'static) {
+    if OPEN_WINDOW.set(Box::new(handler)).is_err() {
+        tracing::debug!("已注册过打开窗口的操作，忽略");
+    }
+}
+
+/// 一个账户在本轮同步中收到的新邮件
+#[derive(Debug, Clone)]
+pub struct NewMail {
+    /// 账户邮箱
+    pub email: String,
+
+    /// 合并通知中显示的名称（显示名称，没有时为邮箱地址）
+    pub name: String,
+
+    /// 新增的未读邮件数量
+    pub count: u32,
+
+    /// 新邮件摘要（最新的在前，可以为空）
+    pub previews: Vec<MailPreview>,
+
+    /// 账户头像缓存路径
+    pub avatar: Option<PathBuf>,
+
+    /// 点击通知时打开的地址（Gmail 以 `authuser` 指定账户）
+    pub open_url: String,
+}
+
+/// 一轮同步中的新邮件通知，本轮结束时统一发送
+///
+/// 只有一个账户有新邮件（或关闭了合并）时每个账户各发一条；
+/// 多个账户都有新邮件时合并为一条，点击后打开窗口而不是某个账户的收件箱
+#[derive(Debug, Default)]
+pub struct RoundNotifications {
+    mails: Vec<NewMail>,
+}
+
+impl RoundNotifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一个账户的新邮件
+    pub fn push(&mut self, mail: NewMail) {
+        self.mails.push(mail);
+    }
+
+    /// 本轮结束：发送收集到的通知
+    pub fn finish(self) {
+        if self.mails.len() > 1 && COMBINE.load(Ordering::SeqCst) {
+            show_combined_notification(&self.mails);
+            return;
+        }
+
+        for mail in &self.mails {
+            show_new_mail_notification(
+                &mail.email,
+                mail.count,
+                &mail.previews,
+                mail.avatar.as_deref(),
+                &mail.open_url,
+            );
+        }
+    }
+}
+
+/// 显示多个账户的合并通知，如"3 个账户收到新邮件：personal (2), work (1), support (4)"
+fn show_combined_notification(mails: &[NewMail]) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        tracing::debug!("新邮件通知已关闭，跳过合并通知");
+        return;
+    }
+
+    let counts: Vec<(&str, u32)> = mails.iter().map(|m| (m.name.as_str(), m.count)).collect();
+    let (title, body) = format_combined(&counts);
+    let open_window = || {
+        if let Some(open) = OPEN_WINDOW.get() {
+            open();
+        }
+    };
+
+    match backend::show_with_click(&title, &body, None, open_window) {
+        Ok(()) => tracing::info!("✅ 已发送合并新邮件通知: {} 个账户", mails.len()),
+        Err(e) => tracing::error!("❌ 发送通知失败: {:#}", e),
+    }
+}
+
+/// 生成合并通知的标题与正文（各账户按本轮完成同步的顺序列出）
+pub fn format_combined(counts: &[(&str, u32)]) -> (String, String) {
+    let list = counts
+        .iter()
+        .map(|(name, count)| {
+            format!(
+                "\u{2068}{}\u{2069} ({})",
+                truncate(name, MAX_SENDER_CHARS),
+                count
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    (
+        tr("notification.title").to_string(),
+        tr_fmt("notification.combined", &[&counts.len(), &list]),
+    )
 }
 
 /// 显示新邮件系统通知
 ///
-/// 通知会显示在 Windows 右下角，并进入通知中心
+/// Windows 上显示在屏幕右下角并进入通知中心；Linux 上由桌面环境的通知服务显示
 ///
 /// # Arguments
 /// * `email` - 账户邮箱
 /// * `new_count` - 新增的未读邮件数量
-pub fn show_new_mail_notification(email: &str, new_count: u32) {
-    let manager = get_toast_manager();
-    
+/// * `previews` - 新邮件摘要（最新的在前，可以为空）
+/// * `avatar` - 账户头像缓存路径（没有时显示应用图标）
+/// * `open_url` - 点击通知时在浏览器中打开的地址
+pub fn show_new_mail_notification(
+    email: &str,
+    new_count: u32,
+    previews: &[MailPreview],
+    avatar: Option<&Path>,
+    open_url: &str,
+) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        tracing::debug!("新邮件通知已关闭，跳过: {}", redact_email(email));
+        return;
+    }
+
     // 构建通知内容
-    let title = "📬 NanoMail - 新邮件";
-    let body = if new_count == 1 {
-        format!("{} 收到 1 封新邮件", email)
-    } else {
-        format!("{} 收到 {} 封新邮件", email, new_count)
-    };
-    
-    // 创建 Toast 通知
-    let mut toast = Toast::new();
-    toast
-        .text1(title)
-        .text2(&body);
-    
+    let detail = *DETAIL.lock().unwrap_or_else(|e| e.into_inner());
+    let (title, body) = format_notification(detail, email, new_count, previews);
+
     // 发送通知
-    match manager.show(&toast) {
-        Ok(_) => {
-            tracing::info!("✅ 已发送新邮件通知: {} (+{} 封)", email, new_count);
+    match backend::show_with_url(&title, &body, avatar, open_url) {
+        Ok(()) => {
+            tracing::info!(
+                "✅ 已发送新邮件通知: {} (+{} 封)",
+                redact_email(email),
+                new_count
+            );
         }
         Err(e) => {
-            tracing::error!("❌ 发送通知失败: {}", e);
+            tracing::error!("❌ 发送通知失败: {:#}", e);
         }
     }
 }
 
+/// 显示未读提醒通知（未读数达到账户设置的提醒阈值，每次越过阈值只提醒一次）
+///
+/// # Arguments
+/// * `email` - 账户邮箱
+/// * `threshold` - 提醒阈值
+/// * `avatar` - 账户头像缓存路径（没有时显示应用图标）
+pub fn show_alert_notification(email: &str, threshold: u32, avatar: Option<&Path>) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        tracing::debug!("通知已关闭，跳过未读提醒: {}", redact_email(email));
+        return;
+    }
+
+    let body = tr_fmt("notification.alert_body", &[&email, &threshold]);
+    match backend::show(tr("notification.alert_title"), &body, avatar) {
+        Ok(()) => tracing::info!(
+            "🔔 已发送未读提醒: {} (阈值 {})",
+            redact_email(email),
+            threshold
+        ),
+        Err(e) => tracing::error!("❌ 发送未读提醒失败: {:#}", e),
+    }
+}
+
+/// 生成通知的标题与正文
+///
+/// `CountOnly` 或没有邮件摘要（提供方不支持）时保持原来的"收到 N 封新邮件"；
+/// 其余级别以新邮件数为标题，正文每封邮件一行，最多列出 3 封，其余合并为"还有 N 封"
+pub fn format_notification(
+    detail: NotificationDetail,
+    email: &str,
+    new_count: u32,
+    previews: &[MailPreview],
+) -> (String, String) {
+    let summary = if new_count == 1 {
+        tr_fmt("notification.body_one", &[&email])
+    } else {
+        tr_fmt("notification.body_many", &[&email, &new_count])
+    };
+
+    let shown = previews
+        .len()
+        .min(MAX_PREVIEW_LINES)
+        .min(new_count as usize);
+    if detail == NotificationDetail::CountOnly || shown == 0 {
+        return (tr("notification.title").to_string(), summary);
+    }
+
+    let mut lines: Vec<String> = previews[..shown]
+        .iter()
+        .map(|preview| {
+            // 隔离发件人的书写方向，避免从右到左的名称把后面的主题一起翻转
+            let sender = format!(
+                "\u{2068}{}\u{2069}",
+                truncate(&preview.sender, MAX_SENDER_CHARS)
+            );
+            if detail == NotificationDetail::SenderOnly {
+                return sender;
+            }
+            let subject = truncate(&preview.subject, MAX_SUBJECT_CHARS);
+            let subject = if subject.is_empty() {
+                tr("notification.no_subject").to_string()
+            } else {
+                subject
+            };
+            tr_fmt("notification.preview", &[&sender, &subject])
+        })
+        .collect();
+
+    let rest = new_count as usize - shown;
+    if rest > 0 {
+        lines.push(tr_fmt("notification.more", &[&rest]));
+    }
+
+    (summary, lines.join("\n"))
+}
+
+/// 清理控制字符与方向控制符后截断到 `max` 个字符（超出时以"…"结尾）
+fn truncate(text: &str, max: usize) -> String {
+    let cleaned: String = text
+        .chars()
+        .filter(|c| !matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'))
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if cleaned.chars().count() <= max {
+        return cleaned;
+    }
+    let mut truncated: String = cleaned.chars().take(max - 1).collect();
+    // 不在表情组合序列的连接符处截断
+    while truncated.ends_with(['\u{200D}', '\u{FE0F}', ' ']) {
+        truncated.pop();
+    }
+    truncated.push('…');
+    truncated
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
-    #[ignore] // 需要在 Windows 环境下运行
+    #[ignore] // 需要桌面环境（Windows 通知中心或 Linux 通知服务）
     fn test_show_notification() {
-        show_new_mail_notification("test@gmail.com", 3);
+        let email = "test@gmail.com";
+        let url = crate::mail::gmail::urls::inbox_url(Some(email));
+        show_new_mail_notification(email, 3, &[], None, &url);
+    }
+
+    #[test]
+    #[ignore] // 需要桌面环境，并且该账户已有头像缓存
+    fn test_show_notification_with_avatar() {
+        let email = "test@gmail.com";
+        let avatar = crate::utils::avatar::get_cached_avatar_path(email)
+            .expect("先添加该账户以生成头像缓存");
+        show_new_mail_notification(
+            email,
+            1,
+            &[preview("Alice", "Lunch?")],
+            Some(Path::new(&avatar)),
+            &crate::mail::gmail::urls::inbox_url(Some(email)),
+        );
+    }
+
+    fn preview(sender: &str, subject: &str) -> MailPreview {
+        MailPreview {
+            id: String::new(),
+            sender: sender.to_string(),
+            subject: subject.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_combined_lists_accounts_with_counts() {
+        let (title, body) = format_combined(&[("personal", 2), ("work", 1), ("support", 4)]);
+
+        assert_eq!(title, tr("notification.title"));
+        assert_eq!(
+            body,
+            tr_fmt(
+                "notification.combined",
+                &[
+                    &3,
+                    &"\u{2068}personal\u{2069} (2), \u{2068}work\u{2069} (1), \u{2068}support\u{2069} (4)"
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_full_lists_senders_and_subjects() {
+        let previews = [
+            preview("Alice", "Lunch?"),
+            preview("Bob", "Quarterly report"),
+        ];
+        let (title, body) =
+            format_notification(NotificationDetail::Full, "me@example.com", 2, &previews);
+
+        assert_eq!(
+            title,
+            tr_fmt("notification.body_many", &[&"me@example.com", &2])
+        );
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("Alice") && lines[0].contains("Lunch?"));
+        assert!(lines[1].contains("Bob") && lines[1].contains("Quarterly report"));
+    }
+
+    #[test]
+    fn test_sender_only_hides_subjects() {
+        let previews = [
+            preview("Alice", "Salary review"),
+            preview("Bob", "Secret"),
+            preview("Carol", "Hi"),
+            preview("Dave", "Hello"),
+        ];
+        let (_, body) = format_notification(
+            NotificationDetail::SenderOnly,
+            "me@example.com",
+            5,
+            &previews,
+        );
+
+        assert!(!body.contains("Salary") && !body.contains("Secret"));
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), MAX_PREVIEW_LINES + 1);
+        assert!(lines[2].contains("Carol"));
+        assert_eq!(lines[3], tr_fmt("notification.more", &[&2]));
+    }
+
+    #[test]
+    fn test_count_only_keeps_generic_text() {
+        let previews = [preview("Alice", "Lunch?")];
+        let (title, body) = format_notification(
+            NotificationDetail::CountOnly,
+            "me@example.com",
+            1,
+            &previews,
+        );
+
+        assert_eq!(title, tr("notification.title"));
+        assert_eq!(body, tr_fmt("notification.body_one", &[&"me@example.com"]));
+        assert!(!body.contains("Alice"));
+
+        // 没有摘要时任何级别都显示新邮件数
+        assert_eq!(
+            format_notification(NotificationDetail::Full, "me@example.com", 1, &[]),
+            (title, body)
+        );
+    }
+
+    #[test]
+    fn test_long_subjects_are_truncated() {
+        let subject = "很长的主题".repeat(40);
+        let previews = [preview("Alice", &subject), preview("Bob", "  ")];
+        let (_, body) =
+            format_notification(NotificationDetail::Full, "me@example.com", 2, &previews);
+
+        let lines: Vec<&str> = body.lines().collect();
+        assert!(lines[0].ends_with('…'));
+        assert!(lines[0].chars().count() < MAX_SENDER_CHARS + MAX_SUBJECT_CHARS + 8);
+        assert!(lines[1].contains(tr("notification.no_subject")));
+    }
+
+    #[test]
+    fn test_rtl_and_emoji_senders() {
+        // 从右到左的名称被隔离，嵌入的方向控制符被移除
+        let previews = [preview("\u{202E}محمد", "Re: hello")];
+        let (_, body) =
+            format_notification(NotificationDetail::Full, "me@example.com", 1, &previews);
+        assert!(!body.contains('\u{202E}'));
+        assert!(body.starts_with("\u{2068}محمد\u{2069}"));
+
+        // 截断不落在表情组合序列的连接符上
+        let family = "👨\u{200D}👩\u{200D}👧";
+        let sender = format!("{}{}", "a".repeat(MAX_SENDER_CHARS - 3), family);
+        let truncated = truncate(&sender, MAX_SENDER_CHARS);
+        assert!(!truncated.contains("\u{200D}…"));
+        assert!(truncated.ends_with('…'));
+        assert!(truncated.chars().count() <= MAX_SENDER_CHARS);
+
+        assert_eq!(truncate("Ann\r\nSmith", MAX_SENDER_CHARS), "Ann Smith");
     }
 }
@@ -0,0 +1,108 @@
+/// Windows 原生 Toast 通知后端
+///
+/// 使用 WinRT API 发送系统级通知，显示在 Windows 通知中心
+use anyhow::Result;
+use std::path::Path;
+use winrt_toast_reborn::content::action::ActivationType;
+use winrt_toast_reborn::content::image::{ImageHintCrop, ImagePlacement};
+use winrt_toast_reborn::{Action, Image, Toast, ToastManager};
+
+use crate::utils::i18n::tr;
+
+/// 发送通知使用的 AUMID（使用 PowerShell 的 AUMID 作为临时方案）
+pub const AUM_ID: &str = ToastManager::POWERSHELL_AUM_ID;
+
+/// 获取或创建 ToastManager
+fn get_toast_manager() -> ToastManager {
+    ToastManager::new(AUM_ID)
+}
+
+/// 显示 Toast 通知
+pub fn show(title: &str, body: &str, avatar: Option<&Path>) -> Result<()> {
+    send(&new_toast(title, body, avatar))
+}
+
+/// 显示 Toast 通知，点击后在浏览器中打开 `url`
+///
+/// `url` 作为通知的启动参数，并附带以协议激活的"打开收件箱"按钮：协议激活由系统直接交给
+/// 默认浏览器，不需要激活本程序（目前以 PowerShell 的 AUMID 发送，见 [`show_with_click`]）
+pub fn show_with_url(title: &str, body: &str, avatar: Option<&Path>, url: &str) -> Result<()> {
+    let mut toast = new_toast(title, body, avatar);
+    toast.launch(url).action(
+        Action::new(tr("menu.open_inbox"), url, "").with_activation_type(ActivationType::Protocol),
+    );
+    send(&toast)
+}
+
+/// 创建 Toast 通知（有头像时替换应用图标）
+fn new_toast(title: &str, body: &str, avatar: Option<&Path>) -> Toast {
+    let mut toast = Toast::new();
+    toast.text1(title).text2(body);
+    if let Some(logo) = avatar.and_then(avatar_logo) {
+        toast.image(1, logo);
+    }
+    toast
+}
+
+fn send(toast: &Toast) -> Result<()> {
+    get_toast_manager()
+        .show(toast)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    Ok(())
+}
+
+/// 显示 Toast 通知（点击后应调用 `on_click`）
+///
+/// 目前以 PowerShell 的 AUMID 发送，点击通知时系统激活的是 PowerShell 而不是本程序，
+/// 暂时无法响应点击；注册本程序自己的 AUMID 后再接入 `on_click`
+pub fn show_with_click(
+    title: &str,
+    body: &str,
+    avatar: Option<&Path>,
+    _on_click: impl FnOnce() + Send + 'static,
+) -> Result<()> {
+    show(title, body, avatar)
+}
+
+/// 以账户头像替换通知中的应用图标（由系统裁剪为圆形）
+///
+/// 通知平台在显示时才以 `file:///` 地址读取图片，因此必须是已存在的绝对路径；
+/// 否则返回 `None`，通知照常显示应用图标
+fn avatar_logo(path: &Path) -> Option<Image> {
+    if !path.is_file() {
+        tracing::debug!("头像缓存不存在，通知使用应用图标: {}", path.display());
+        return None;
+    }
+
+    // new_local 对相对路径返回错误
+    match Image::new_local(path) {
+        Ok(image) => Some(
+            image
+                .with_placement(ImagePlacement::AppLogoOverride)
+                .with_hint_crop(ImageHintCrop::Circle),
+        ),
+        Err(e) => {
+            tracing::debug!("头像路径无法用于通知 {}: {:?}", path.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_avatar_logo_requires_existing_absolute_path() {
+        assert!(avatar_logo(Path::new("avatars/missing_thumb.png")).is_none());
+
+        let dir = std::env::temp_dir().join(format!("nanomail-toast-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("me_example_com_thumb.png");
+        assert!(avatar_logo(&path).is_none());
+
+        std::fs::write(&path, b"png").unwrap();
+        assert!(avatar_logo(&path).is_some());
+        std::fs::remove_file(&path).ok();
+    }
+}
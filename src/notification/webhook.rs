@@ -0,0 +1,303 @@
+/// 新邮件 Webhook（家庭自动化等本地集成）
+///
+/// 在 `config.toml` 的 `[automation]` 中设置 `webhook_url` 后，同步引擎每次检测到新邮件
+/// （与新邮件通知的判断相同，免打扰期间不发送）都向该地址 POST 一个 JSON：
+///
+/// ```json
+/// {
+///   "email": "me@example.com",
+///   "new_count": 2,
+///   "total_unread": 7,
+///   "timestamp": "2026-05-01T08:00:00Z"
+/// }
+/// ```
+///
+/// - `email`：账户邮箱（与 `accounts.toml` 中保存的一致）
+/// - `new_count`：本轮新增的未读邮件数（按通知标签计）
+/// - `total_unread`：账户当前的未读数（徽章计数）
+/// - `timestamp`：检测到新邮件的时间（UTC，RFC 3339，精确到秒）
+///
+/// 设置了 `webhook_secret` 时随请求发送 `X-NanoMail-Secret` 请求头，接收方可据此校验来源。
+/// `[automation.accounts]` 可以为单个账户指定另一个地址，空字符串表示该账户不发送。
+/// 请求通过共享的 HTTP 客户端在后台发出：超时很短，失败后只重试一次，结果不影响同步状态，
+/// 失败只以 debug 级别限流记录
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::config::AutomationConfig;
+use crate::utils::redact::redact_email;
+use crate::utils::{http_client, log_throttle};
+
+/// 共享密钥请求头
+pub const SECRET_HEADER: &str = "X-NanoMail-Secret";
+
+/// 单次请求的超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 失败后的重试次数
+const RETRIES: u32 = 1;
+
+/// 日志限流的来源标识
+const LOG_SOURCE: &str = "webhook";
+
+/// 当前的 Webhook 设置（随配置更新）
+static SETTINGS: RwLock<Settings> = RwLock::new(Settings {
+    url: None,
+    secret: None,
+    accounts: BTreeMap::new(),
+});
+
+/// 解析后的 Webhook 设置
+#[derive(Debug, Clone, Default)]
+struct Settings {
+    /// 所有账户共用的地址
+    url: Option<String>,
+
+    /// 共享密钥（已解密）
+    secret: Option<String>,
+
+    /// 按账户覆盖的地址（键为小写邮箱，空字符串表示不发送）
+    accounts: BTreeMap<String, String>,
+}
+
+impl Settings {
+    /// 从配置解析（共享密钥无法解密时不发送密钥头）
+    fn from_config(config: &AutomationConfig) -> Self {
+        let secret = config.decrypt_webhook_secret().unwrap_or_else(|e| {
+            tracing::warn!("解密 Webhook 共享密钥失败，不发送密钥请求头: {:#}", e);
+            None
+        });
+        Self {
+            url: config
+                .webhook_url
+                .as_deref()
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string),
+            secret: secret.filter(|secret| !secret.is_empty()),
+            accounts: config
+                .accounts
+                .iter()
+                .map(|(email, url)| (email.trim().to_lowercase(), url.trim().to_string()))
+                .collect(),
+        }
+    }
+
+    /// 账户使用的地址（没有配置或该账户已关闭时为 `None`）
+    fn url_for(&self, email: &str) -> Option<&str> {
+        match self.accounts.get(&email.to_lowercase()) {
+            Some(url) => Some(url.as_str()).filter(|url| !url.is_empty()),
+            None => self.url.as_deref(),
+        }
+    }
+
+    /// 是否有任何账户会发送
+    fn is_enabled(&self) -> bool {
+        self.url.is_some() || self.accounts.values().any(|url| !url.is_empty())
+    }
+}
+
+/// 应用 `[automation]` 配置
+pub fn configure(config: &AutomationConfig) {
+    let settings = Settings::from_config(config);
+    tracing::info!(
+        "新邮件 Webhook: {}",
+        if settings.is_enabled() {
+            "已启用"
+        } else {
+            "未配置"
+        }
+    );
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+/// 发送给 Webhook 的内容（字段说明见模块文档）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Payload {
+    pub email: String,
+    pub new_count: u32,
+    pub total_unread: u32,
+    pub timestamp: String,
+}
+
+impl Payload {
+    pub fn new(email: &str, new_count: u32, total_unread: u32, at: DateTime<Utc>) -> Self {
+        Self {
+            email: email.to_string(),
+            new_count,
+            total_unread,
+            timestamp: at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        }
+    }
+}
+
+/// 检测到新邮件：该账户配置了地址时在后台发送，不等待结果（需在 Tokio 运行时中调用）
+///
+/// # Arguments
+/// * `email` - 账户邮箱
+/// * `new_count` - 本轮新增的未读邮件数
+/// * `total_unread` - 账户当前的未读数
+pub fn fire(email: &str, new_count: u32, total_unread: u32) {
+    let (url, secret) = {
+        let settings = SETTINGS.read().unwrap_or_else(|e| e.into_inner());
+        let Some(url) = settings.url_for(email) else {
+            return;
+        };
+        (url.to_string(), settings.secret.clone())
+    };
+    let payload = Payload::new(email, new_count, total_unread, Utc::now());
+
+    tokio::spawn(async move {
+        match deliver(&url, secret.as_deref(), &payload).await {
+            Ok(()) => {
+                tracing::debug!("已发送新邮件 Webhook: {}", redact_email(&payload.email));
+                log_throttle::recovered(LOG_SOURCE);
+            }
+            Err(e) => log_throttle::debug(
+                LOG_SOURCE,
+                error_kind(&e),
+                format_args!(
+                    "发送新邮件 Webhook 失败（{}）: {}",
+                    redact_email(&payload.email),
+                    e.without_url()
+                ),
+            ),
+        }
+    });
+}
+
+/// 发送一次（失败后重试 [`RETRIES`] 次）
+async fn deliver(url: &str, secret: Option<&str>, payload: &Payload) -> Result<(), reqwest::Error> {
+    let mut attempt = 0;
+    loop {
+        match send_once(url, secret, payload).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < RETRIES => {
+                attempt += 1;
+                tracing::debug!("新邮件 Webhook 请求失败，重试: {}", e.without_url());
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn send_once(
+    url: &str,
+    secret: Option<&str>,
+    payload: &Payload,
+) -> Result<(), reqwest::Error> {
+    let mut request = http_client::get_client()
+        .post(url)
+        .timeout(REQUEST_TIMEOUT)
+        .json(payload);
+    if let Some(secret) = secret {
+        request = request.header(SECRET_HEADER, secret);
+    }
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+/// 日志限流使用的错误类型
+fn error_kind(error: &reqwest::Error) -> &'static str {
+    if error.is_timeout() {
+        "timeout"
+    } else if error.is_connect() {
+        "connect"
+    } else if error.is_status() {
+        "status"
+    } else {
+        "request"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::gmail::mock::{MockGmail, MockResponse};
+
+    const HOOK_PATH: &str = "/hook";
+
+    fn payload() -> Payload {
+        let at = DateTime::parse_from_rfc3339("2026-05-01T08:00:00.250Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        Payload::new("me@example.com", 2, 7, at)
+    }
+
+    #[tokio::test]
+    async fn test_posts_payload_and_secret_header() {
+        let server = MockGmail::start().await;
+        server.on(HOOK_PATH, MockResponse::raw(204, ""));
+
+        let url = format!("{}{}", server.base_url(), HOOK_PATH);
+        deliver(&url, Some("s3cret"), &payload()).await.unwrap();
+
+        let requests = server.requests(HOOK_PATH);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].header(SECRET_HEADER), Some("s3cret"));
+        assert_eq!(requests[0].header("content-type"), Some("application/json"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "email": "me@example.com",
+                "new_count": 2,
+                "total_unread": 7,
+                "timestamp": "2026-05-01T08:00:00Z",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retries_once_then_gives_up() {
+        let server = MockGmail::start().await;
+        server.on(HOOK_PATH, MockResponse::raw(500, ""));
+
+        let url = format!("{}{}", server.base_url(), HOOK_PATH);
+        let error = deliver(&url, None, &payload()).await.unwrap_err();
+        assert_eq!(error_kind(&error), "status");
+
+        let requests = server.requests(HOOK_PATH);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].header(SECRET_HEADER), None);
+    }
+
+    #[test]
+    fn test_per_account_override() {
+        let config = AutomationConfig {
+            webhook_url: Some("http://hub.local/mail".to_string()),
+            webhook_secret: None,
+            accounts: BTreeMap::from([
+                (
+                    "Work@Example.com".to_string(),
+                    "http://hub.local/work".to_string(),
+                ),
+                ("spam@example.com".to_string(), "".to_string()),
+            ]),
+        };
+        let settings = Settings::from_config(&config);
+        assert_eq!(
+            settings.url_for("work@example.com"),
+            Some("http://hub.local/work")
+        );
+        assert_eq!(settings.url_for("spam@example.com"), None);
+        assert_eq!(
+            settings.url_for("me@example.com"),
+            Some("http://hub.local/mail")
+        );
+
+        // 只为个别账户设置地址
+        let only_work = Settings::from_config(&AutomationConfig {
+            webhook_url: None,
+            ..config
+        });
+        assert!(only_work.is_enabled());
+        assert_eq!(only_work.url_for("me@example.com"), None);
+        assert!(!Settings::from_config(&AutomationConfig::default()).is_enabled());
+    }
+}
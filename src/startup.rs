@@ -0,0 +1,181 @@
+/// 启动流程
+///
+/// release 构建没有控制台，启动中途出错时进程会无声退出。启动按阶段执行，失败时标明阶段：
+/// 托盘图标可重试（开机自启时任务栏可能尚未就绪），仍然失败则以无托盘模式继续运行并在窗口中说明；
+/// 其余阶段失败时弹出原生提示框，说明失败的阶段与日志位置后退出
+use std::path::Path;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::config;
+use crate::utils::i18n::{self, tr, tr_fmt};
+use crate::utils::{dialog, logging};
+
+/// 创建托盘图标的尝试次数
+pub const TRAY_ATTEMPTS: u32 = 5;
+
+/// 两次创建托盘图标之间的等待时间
+pub const TRAY_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// 启动阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// 初始化日志
+    Logger,
+
+    /// 创建异步运行时
+    Runtime,
+
+    /// 创建主窗口
+    Window,
+
+    /// 创建托盘图标
+    Tray,
+
+    /// 启动托盘命令处理线程
+    Commands,
+}
+
+impl Phase {
+    /// 提示框中显示的阶段名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Logger => tr("startup.phase_logger"),
+            Self::Runtime => tr("startup.phase_runtime"),
+            Self::Window => tr("startup.phase_window"),
+            Self::Tray => tr("startup.phase_tray"),
+            Self::Commands => tr("startup.phase_commands"),
+        }
+    }
+}
+
+/// 启动失败（失败的阶段与原因）
+#[derive(Debug, Error)]
+#[error("启动阶段 {phase:?} 失败: {cause:#}")]
+pub struct StartupError {
+    pub phase: Phase,
+    cause: anyhow::Error,
+}
+
+/// 执行一个启动阶段，失败时标明阶段
+pub fn run<T>(phase: Phase, f: impl FnOnce() -> anyhow::Result<T>) -> Result<T, StartupError> {
+    tracing::debug!("启动阶段: {:?}", phase);
+    f().map_err(|cause| StartupError { phase, cause })
+}
+
+/// 执行一个可重试的启动阶段：最多尝试 `attempts` 次，每次失败后等待 `delay`
+pub fn retry<T>(
+    phase: Phase,
+    attempts: u32,
+    delay: Duration,
+    mut f: impl FnMut() -> anyhow::Result<T>,
+) -> Result<T, StartupError> {
+    let mut attempt = 1;
+    loop {
+        tracing::debug!("启动阶段: {:?}（第 {} 次）", phase, attempt);
+        match f() {
+            Ok(value) => {
+                if attempt > 1 {
+                    tracing::info!("启动阶段 {:?} 第 {} 次尝试成功", phase, attempt);
+                }
+                return Ok(value);
+            }
+            Err(cause) if attempt < attempts => {
+                tracing::warn!(
+                    "启动阶段 {:?} 失败（第 {}/{} 次），{:?} 后重试: {:#}",
+                    phase,
+                    attempt,
+                    attempts,
+                    delay,
+                    cause
+                );
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(cause) => return Err(StartupError { phase, cause }),
+        }
+    }
+}
+
+/// 致命启动失败的提示文字（失败的阶段、原因与日志目录）
+pub fn fatal_message(error: &StartupError, logs_dir: Option<&Path>) -> String {
+    let logs = logs_dir
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| tr("startup.no_logs").to_string());
+    tr_fmt(
+        "startup.fatal",
+        &[&error.phase.label(), &format!("{:#}", error.cause), &logs],
+    )
+}
+
+/// 报告致命的启动失败：记录日志并弹出原生提示框
+pub fn report_fatal(error: &StartupError) {
+    tracing::error!("❌ {}", error);
+
+    // 窗口创建前失败时界面语言尚未设置，按配置选择（配置读取失败时跟随系统语言）
+    let language = config::load()
+        .map(|cfg| cfg.app.language)
+        .unwrap_or_else(|_| "auto".to_string());
+    i18n::set_language(i18n::Language::from_setting(&language));
+
+    dialog::show_error(&fatal_message(error, logging::logs_dir().ok().as_deref()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_names_the_failed_phase() {
+        let value = run(Phase::Runtime, || Ok(42)).unwrap();
+        assert_eq!(value, 42);
+
+        let error = run::<()>(Phase::Window, || anyhow::bail!("no backend")).unwrap_err();
+        assert_eq!(error.phase, Phase::Window);
+        assert!(error.to_string().contains("no backend"));
+    }
+
+    #[test]
+    fn test_retry_recovers_from_transient_failures() {
+        let mut calls = 0;
+        let value = retry(Phase::Tray, TRAY_ATTEMPTS, Duration::ZERO, || {
+            calls += 1;
+            if calls < 3 {
+                anyhow::bail!("shell not ready");
+            }
+            Ok(calls)
+        })
+        .unwrap();
+
+        assert_eq!(value, 3);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_retry_gives_up_after_all_attempts() {
+        let mut calls = 0;
+        let error = retry::<()>(Phase::Tray, 3, Duration::ZERO, || {
+            calls += 1;
+            anyhow::bail!("shell not ready")
+        })
+        .unwrap_err();
+
+        assert_eq!(calls, 3);
+        assert_eq!(error.phase, Phase::Tray);
+    }
+
+    #[test]
+    fn test_fatal_message_names_phase_and_logs() {
+        let error = run::<()>(Phase::Runtime, || anyhow::bail!("out of threads")).unwrap_err();
+        let logs = Path::new("/tmp/NanoMail/logs");
+
+        let message = fatal_message(&error, Some(logs));
+        assert!(message.contains(Phase::Runtime.label()));
+        assert!(message.contains("out of threads"));
+        assert!(message.contains(&logs.display().to_string()));
+
+        let message = fatal_message(&error, None);
+        assert!(message.contains(tr("startup.no_logs")));
+    }
+}
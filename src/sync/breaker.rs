@@ -0,0 +1,139 @@
+/// 连续多少轮全部失败后拉长间隔
+const TRIP_AFTER: u32 = 3;
+
+/// 间隔最多拉长的倍数
+const MAX_STRETCH: u32 = 8;
+
+/// 一轮同步的总体结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundOutcome {
+    /// 至少一个账户同步成功
+    Succeeded,
+
+    /// 发起了同步但全部失败（包括网络不可用时跳过的一轮）
+    Failed,
+
+    /// 没有发起任何同步（账户都在同步中、停用重试期或速率超限），不影响熔断状态
+    Skipped,
+}
+
+/// 同步熔断器
+///
+/// 网络长时间不可用或服务持续故障时，按原间隔轮询只会一轮轮重复同样的失败。
+/// 连续 `TRIP_AFTER` 轮全部失败后拉长同步间隔（之后每多失败一轮翻倍，最多 `MAX_STRETCH` 倍），
+/// 期间托盘显示"降级"状态；任意一轮有账户同步成功即恢复原间隔
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// 连续全部失败的轮数
+    consecutive_failures: u32,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreaker {
+    /// 初始状态（未熔断）
+    pub fn new() -> Self {
+        Self {
+            consecutive_failures: 0,
+        }
+    }
+
+    /// 记录一轮的结果，返回同步间隔的倍数是否改变
+    pub fn record(&mut self, outcome: RoundOutcome) -> bool {
+        let before = self.stretch();
+        match outcome {
+            RoundOutcome::Succeeded => self.consecutive_failures = 0,
+            RoundOutcome::Failed => {
+                self.consecutive_failures = self.consecutive_failures.saturating_add(1)
+            }
+            RoundOutcome::Skipped => {}
+        }
+        self.stretch() != before
+    }
+
+    /// 是否已熔断（间隔已拉长）
+    pub fn is_open(&self) -> bool {
+        self.consecutive_failures >= TRIP_AFTER
+    }
+
+    /// 同步间隔的倍数（未熔断时为 1）
+    pub fn stretch(&self) -> u32 {
+        if !self.is_open() {
+            return 1;
+        }
+        let doublings = (self.consecutive_failures - TRIP_AFTER + 1).min(MAX_STRETCH.ilog2());
+        1 << doublings
+    }
+
+    /// 按当前倍数拉长的同步间隔（秒）
+    pub fn stretched(&self, secs: u64) -> u64 {
+        secs.saturating_mul(self.stretch() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_after_three_failed_rounds() {
+        let mut breaker = CircuitBreaker::new();
+        assert!(!breaker.record(RoundOutcome::Failed));
+        assert!(!breaker.record(RoundOutcome::Failed));
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.stretched(60), 60);
+
+        assert!(breaker.record(RoundOutcome::Failed));
+        assert!(breaker.is_open());
+        assert_eq!(breaker.stretch(), 2);
+        assert_eq!(breaker.stretched(60), 120);
+    }
+
+    #[test]
+    fn test_stretch_doubles_up_to_cap() {
+        let mut breaker = CircuitBreaker::new();
+        let stretches: Vec<u32> = (0..7)
+            .map(|_| {
+                breaker.record(RoundOutcome::Failed);
+                breaker.stretch()
+            })
+            .collect();
+        assert_eq!(stretches, [1, 1, 2, 4, 8, 8, 8]);
+
+        // 达到上限后不再改变
+        assert!(!breaker.record(RoundOutcome::Failed));
+    }
+
+    #[test]
+    fn test_success_closes_breaker() {
+        let mut breaker = CircuitBreaker::new();
+        for _ in 0..4 {
+            breaker.record(RoundOutcome::Failed);
+        }
+        assert!(breaker.record(RoundOutcome::Succeeded));
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.stretch(), 1);
+
+        // 成功后重新计数
+        breaker.record(RoundOutcome::Failed);
+        breaker.record(RoundOutcome::Failed);
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_skipped_rounds_are_neutral() {
+        let mut breaker = CircuitBreaker::new();
+        breaker.record(RoundOutcome::Failed);
+        breaker.record(RoundOutcome::Failed);
+        assert!(!breaker.record(RoundOutcome::Skipped));
+        assert!(breaker.record(RoundOutcome::Failed));
+        assert!(breaker.is_open());
+
+        assert!(!breaker.record(RoundOutcome::Skipped));
+        assert!(breaker.is_open());
+    }
+}
@@ -0,0 +1,334 @@
+/// 同步记录：最近几十轮同步的结果
+///
+/// 用户反馈"下午三点托盘是红的"时，未开启调试级别的文件日志就无从查起。同步引擎在每轮同步中
+/// 按同步事件记下开始时间、耗时、各账户的结果与未读合计，保存在内存中的环形记录里
+/// （最多 [`HISTORY_CAPACITY`] 轮），窗口的"同步记录"页面与托盘"运行诊断"的报告都从这里读取。
+/// `[app] persist_sync_history = true` 时退出前写入运行时状态文件，下次启动时接着显示
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::SyncEvent;
+use crate::config::state::RuntimeState;
+
+/// 最多保留的轮数
+pub const HISTORY_CAPACITY: usize = 50;
+
+/// 本次运行的同步记录（旧的在前）
+static HISTORY: Mutex<SyncHistory> = Mutex::new(SyncHistory::new());
+
+/// 账户在一轮同步中的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountOutcome {
+    /// 同步成功
+    Synced,
+
+    /// 同步失败（授权、服务端错误等）
+    Failed,
+
+    /// 网络不可用
+    Offline,
+
+    /// 邮件服务已被停用（按间隔重试）
+    Unavailable,
+
+    /// 睡眠恢复后首轮失败，未上报
+    Suppressed,
+}
+
+impl AccountOutcome {
+    /// 由同步事件得到结果（`Started` 不是结果）
+    fn from_event(event: &SyncEvent) -> Option<(&str, Self)> {
+        match event {
+            SyncEvent::Started(_) => None,
+            SyncEvent::Synced(email, _) => Some((email, Self::Synced)),
+            SyncEvent::Failed(email, failure) if failure.is_offline() => {
+                Some((email, Self::Offline))
+            }
+            SyncEvent::Failed(email, failure) if failure.is_account_unavailable() => {
+                Some((email, Self::Unavailable))
+            }
+            SyncEvent::Failed(email, _) => Some((email, Self::Failed)),
+            SyncEvent::FailedQuietly(email) => Some((email, Self::Suppressed)),
+        }
+    }
+
+    /// 界面使用的名称（与 sync_history.slint 中的颜色对应）
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Synced => "synced",
+            Self::Failed => "failed",
+            Self::Offline => "offline",
+            Self::Unavailable => "unavailable",
+            Self::Suppressed => "suppressed",
+        }
+    }
+}
+
+/// 单个账户的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountResult {
+    pub email: String,
+    pub outcome: AccountOutcome,
+}
+
+/// 一轮同步的记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundRecord {
+    /// 开始时间
+    pub started_at: DateTime<Utc>,
+
+    /// 耗时（毫秒）
+    pub duration_ms: u64,
+
+    /// 各账户的结果（按同步顺序）
+    pub accounts: Vec<AccountResult>,
+
+    /// 本轮同步成功的账户的未读合计
+    pub unread: u32,
+}
+
+impl RoundRecord {
+    /// 是否有账户未同步成功
+    pub fn has_failures(&self) -> bool {
+        self.accounts
+            .iter()
+            .any(|account| account.outcome != AccountOutcome::Synced)
+    }
+}
+
+/// 按同步事件记录一轮同步
+pub struct RoundRecorder {
+    started_at: DateTime<Utc>,
+    started: Instant,
+    accounts: Vec<AccountResult>,
+    unread: u32,
+}
+
+impl RoundRecorder {
+    /// 开始记录一轮同步
+    pub fn start() -> Self {
+        Self {
+            started_at: Utc::now(),
+            started: Instant::now(),
+            accounts: Vec::new(),
+            unread: 0,
+        }
+    }
+
+    /// 记下同步事件（同一账户以最后一次结果为准）
+    pub fn observe(&mut self, event: &SyncEvent) {
+        if let SyncEvent::Synced(_, info) = event {
+            self.unread = self.unread.saturating_add(info.unread());
+        }
+        let Some((email, outcome)) = AccountOutcome::from_event(event) else {
+            return;
+        };
+        match self.accounts.iter_mut().find(|a| a.email == email) {
+            Some(account) => account.outcome = outcome,
+            None => self.accounts.push(AccountResult {
+                email: email.to_string(),
+                outcome,
+            }),
+        }
+    }
+
+    /// 结束记录；没有任何账户的结果（全部跳过）时返回 `None`
+    pub fn finish(self) -> Option<RoundRecord> {
+        if self.accounts.is_empty() {
+            return None;
+        }
+        Some(RoundRecord {
+            started_at: self.started_at,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            accounts: self.accounts,
+            unread: self.unread,
+        })
+    }
+}
+
+/// 有上限的同步记录（旧的在前，超出上限时丢弃最旧的一轮）
+#[derive(Debug, Default)]
+pub struct SyncHistory {
+    rounds: VecDeque<Arc<RoundRecord>>,
+}
+
+impl SyncHistory {
+    pub const fn new() -> Self {
+        Self {
+            rounds: VecDeque::new(),
+        }
+    }
+
+    /// 追加一轮
+    pub fn push(&mut self, round: RoundRecord) {
+        self.rounds.push_back(Arc::new(round));
+        while self.rounds.len() > HISTORY_CAPACITY {
+            self.rounds.pop_front();
+        }
+    }
+
+    /// 在本次运行的记录之前补上保存的记录（超出上限时丢弃最旧的）
+    pub fn prepend(&mut self, saved: Vec<RoundRecord>) {
+        let room = HISTORY_CAPACITY.saturating_sub(self.rounds.len());
+        let skip = saved.len().saturating_sub(room);
+        for round in saved.into_iter().skip(skip).rev() {
+            self.rounds.push_front(Arc::new(round));
+        }
+    }
+
+    /// 全部记录（最新的在前，只复制引用）
+    pub fn newest_first(&self) -> Vec<Arc<RoundRecord>> {
+        self.rounds.iter().rev().cloned().collect()
+    }
+
+    /// 保存到状态文件的内容（旧的在前）
+    pub fn to_saved(&self) -> Vec<RoundRecord> {
+        self.rounds.iter().map(|round| (**round).clone()).collect()
+    }
+}
+
+fn history() -> std::sync::MutexGuard<'static, SyncHistory> {
+    HISTORY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 记录一轮同步（没有账户结果的一轮不记录）
+pub fn record(recorder: RoundRecorder) {
+    if let Some(round) = recorder.finish() {
+        history().push(round);
+    }
+}
+
+/// 最近的同步记录（最新的在前）
+pub fn snapshot() -> Vec<Arc<RoundRecord>> {
+    history().newest_first()
+}
+
+/// 启动时读取上次保存的同步记录（`[app] persist_sync_history = true` 时调用）
+pub fn load_saved(state: &RuntimeState) {
+    let saved = state.sync_history();
+    if !saved.is_empty() {
+        tracing::debug!("读取上次保存的同步记录: {} 轮", saved.len());
+        history().prepend(saved);
+    }
+}
+
+/// 退出前保存同步记录；`persist` 为 false 时清除上次保存的记录
+pub fn flush(state: &RuntimeState, persist: bool) {
+    let rounds = if persist {
+        history().to_saved()
+    } else {
+        Vec::new()
+    };
+    if let Err(e) = state.save_sync_history(rounds) {
+        tracing::warn!("保存同步记录失败: {:#}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::{AccountSyncInfo, SyncFailure};
+
+    fn round(unread: u32) -> RoundRecord {
+        RoundRecord {
+            started_at: Utc::now(),
+            duration_ms: 120,
+            accounts: vec![AccountResult {
+                email: "a@example.com".to_string(),
+                outcome: AccountOutcome::Synced,
+            }],
+            unread,
+        }
+    }
+
+    fn unread(rounds: &[Arc<RoundRecord>]) -> Vec<u32> {
+        rounds.iter().map(|round| round.unread).collect()
+    }
+
+    #[test]
+    fn test_recorder_maps_events_to_outcomes() {
+        let mut recorder = RoundRecorder::start();
+        recorder.observe(&SyncEvent::Started("a@example.com".to_string()));
+        recorder.observe(&SyncEvent::Synced(
+            "a@example.com".to_string(),
+            AccountSyncInfo::new("a@example.com", 3),
+        ));
+        recorder.observe(&SyncEvent::Failed(
+            "b@example.com".to_string(),
+            SyncFailure::offline("网络不可用"),
+        ));
+        recorder.observe(&SyncEvent::Failed(
+            "c@example.com".to_string(),
+            SyncFailure::account_unavailable(),
+        ));
+        recorder.observe(&SyncEvent::FailedQuietly("d@example.com".to_string()));
+
+        let round = recorder.finish().unwrap();
+        let outcomes: Vec<_> = round.accounts.iter().map(|a| a.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                AccountOutcome::Synced,
+                AccountOutcome::Offline,
+                AccountOutcome::Unavailable,
+                AccountOutcome::Suppressed,
+            ]
+        );
+        assert_eq!(round.unread, 3);
+        assert!(round.has_failures());
+    }
+
+    #[test]
+    fn test_empty_round_is_not_recorded() {
+        let mut recorder = RoundRecorder::start();
+        recorder.observe(&SyncEvent::Started("a@example.com".to_string()));
+        assert!(recorder.finish().is_none());
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = SyncHistory::new();
+        for unread in 0..(HISTORY_CAPACITY as u32 + 5) {
+            history.push(round(unread));
+        }
+        let rounds = history.newest_first();
+        assert_eq!(rounds.len(), HISTORY_CAPACITY);
+        assert_eq!(rounds[0].unread, HISTORY_CAPACITY as u32 + 4);
+        assert_eq!(rounds[HISTORY_CAPACITY - 1].unread, 5);
+    }
+
+    #[test]
+    fn test_prepend_saved_rounds() {
+        let mut history = SyncHistory::new();
+        history.push(round(100));
+        history.prepend(vec![round(1), round(2)]);
+        assert_eq!(unread(&history.newest_first()), [100, 2, 1]);
+
+        // 保存的记录太多时丢弃最旧的，本次运行的记录全部保留
+        let mut full = SyncHistory::new();
+        for unread in 0..(HISTORY_CAPACITY as u32 - 1) {
+            full.push(round(1000 + unread));
+        }
+        full.prepend(vec![round(1), round(2), round(3)]);
+        let rounds = full.newest_first();
+        assert_eq!(rounds.len(), HISTORY_CAPACITY);
+        assert_eq!(rounds[HISTORY_CAPACITY - 1].unread, 3);
+    }
+
+    #[test]
+    fn test_save_and_load_through_state() {
+        let state = RuntimeState::in_memory();
+        let mut history = SyncHistory::new();
+        history.push(round(7));
+        state.save_sync_history(history.to_saved()).unwrap();
+
+        let mut restored = SyncHistory::new();
+        restored.prepend(state.sync_history());
+        assert_eq!(restored.to_saved(), history.to_saved());
+    }
+}
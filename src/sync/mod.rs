@@ -2,20 +2,255 @@
 ///
 /// 负责定期同步所有账户的邮件信息（未读数、头像等）
 /// 支持后台定时轮询 + 手动触发立即同步
-use anyhow::Result;
-use std::collections::HashMap;
-use std::sync::Arc;
+mod breaker;
+pub mod history;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
-use tokio::sync::{Notify, RwLock};
-use tokio::time::interval;
+use tokio::sync::{Notify, RwLock, watch};
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::Instrument;
 
-use crate::config::storage;
-use crate::mail::gmail::{self, AccountSyncInfo};
+use self::breaker::{CircuitBreaker, RoundOutcome};
+use crate::config::Config;
+use crate::config::state::{self, NotificationBaseline, RuntimeState};
+use crate::config::storage::{self, StoredAccount};
+use crate::mail::{
+    AccountSyncInfo, MailProvider, ProviderRegistry, PushEvent, RetryContext, SyncFailure,
+    SyncOutcome, metered, quota,
+};
 use crate::notification;
+use crate::utils::connectivity;
+use crate::utils::log_throttle;
+use crate::utils::redact::redact_email;
+use crate::utils::telemetry;
 
 /// 同步间隔（10秒后台轮询）
 const SYNC_INTERVAL_SECS: u64 = 10;
 
+/// 允许配置的最短同步间隔，避免误配置导致请求风暴
+pub const MIN_SYNC_INTERVAL_SECS: u64 = 5;
+
+/// 睡眠恢复后等待网络重连的宽限期
+const RESUME_GRACE_SECS: u64 = 5;
+
+/// 推送监听重新读取账户列表的间隔（新增/删除的账户在此间隔内生效）
+const PUSH_RECONCILE_SECS: u64 = 60;
+
+/// 推送连接失败后的首次重试间隔（连续失败时逐次加倍）
+const PUSH_RETRY_SECS: u64 = 15;
+
+/// 推送连接重试间隔的上限
+const PUSH_RETRY_MAX_SECS: u64 = 15 * 60;
+
+/// 邮件服务被停用的账户的重试间隔
+const UNAVAILABLE_RETRY: chrono::Duration = chrono::Duration::hours(1);
+
+/// 本次运行中的同步轮次（`sync.round` span 的编号）
+static ROUND: AtomicU64 = AtomicU64::new(0);
+
+/// 同步循环是否因连续多轮失败而拉长了间隔（见 [`CircuitBreaker`]）
+static BACKING_OFF: AtomicBool = AtomicBool::new(false);
+
+/// 同步循环是否因连续多轮失败而拉长了间隔（托盘据此显示"降级"状态）
+pub fn is_backing_off() -> bool {
+    BACKING_OFF.load(Ordering::Relaxed)
+}
+
+/// 正在同步的账户（同一账户同时只进行一次同步）
+///
+/// 同步循环与单账户同步共用：账户已在同步中时再次请求直接跳过，
+/// 由正在进行的同步上报结果，避免重复更新界面或重复通知
+#[derive(Clone, Default)]
+struct InFlight(Arc<Mutex<HashSet<String>>>);
+
+impl InFlight {
+    /// 标记账户开始同步；已在同步中时返回 `None`
+    fn begin(&self, email: &str) -> Option<InFlightGuard> {
+        let mut emails = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        emails.insert(email.to_string()).then(|| InFlightGuard {
+            in_flight: self.clone(),
+            email: email.to_string(),
+        })
+    }
+}
+
+/// 账户同步结束（drop）时解除标记
+struct InFlightGuard {
+    in_flight: InFlight,
+    email: String,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut emails = self.in_flight.0.lock().unwrap_or_else(|e| e.into_inner());
+        emails.remove(&self.email);
+    }
+}
+
+/// 同步事件（按账户依次上报给 `start` 传入的回调）
+#[derive(Debug)]
+pub enum SyncEvent {
+    /// 开始同步账户
+    Started(String),
+
+    /// 账户同步成功（邮箱为已保存账户的邮箱）
+    Synced(String, AccountSyncInfo),
+
+    /// 账户同步失败（未开始同步就判定网络不可用时不会先发送 `Started`）
+    Failed(String, SyncFailure),
+
+    /// 账户同步失败但不上报（睡眠恢复后首轮），界面保留原有状态
+    FailedQuietly(String),
+}
+
+/// 账户在同步引擎中的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountEngineState {
+    /// 正在同步
+    Syncing,
+
+    /// 上次同步成功
+    Synced,
+
+    /// 上次同步失败，下一轮重试
+    Failed,
+
+    /// 邮件服务被停用，到 `next_attempt_at` 前不再重试
+    Backoff,
+}
+
+/// 单个账户的引擎状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountEngineStatus {
+    pub email: String,
+    pub state: AccountEngineState,
+
+    /// 下次重试时间（`None` 表示随下一轮同步）
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+/// 同步引擎状态快照（诊断用）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EngineStatus {
+    /// 同步循环是否在运行
+    pub running: bool,
+
+    /// 是否处于睡眠恢复宽限期（暂停定时同步）
+    pub paused: bool,
+
+    /// 下一轮定时同步的预计时间
+    pub next_round_at: Option<DateTime<Utc>>,
+
+    /// 本次运行中同步过的账户（按首次同步的顺序）
+    pub per_account: Vec<AccountEngineStatus>,
+}
+
+impl EngineStatus {
+    /// 按同步事件更新账户状态（`retry_at` 为邮件服务停用的账户的下次重试时间）
+    fn record(&mut self, event: &SyncEvent, retry_at: Option<DateTime<Utc>>) {
+        let (email, state) = match event {
+            SyncEvent::Started(email) => (email, AccountEngineState::Syncing),
+            SyncEvent::Synced(email, _) => (email, AccountEngineState::Synced),
+            SyncEvent::Failed(email, failure) if failure.is_account_unavailable() => {
+                (email, AccountEngineState::Backoff)
+            }
+            SyncEvent::Failed(email, _) | SyncEvent::FailedQuietly(email) => {
+                (email, AccountEngineState::Failed)
+            }
+        };
+        let next_attempt_at = match state {
+            AccountEngineState::Backoff => retry_at,
+            _ => None,
+        };
+
+        match self.per_account.iter_mut().find(|a| &a.email == email) {
+            Some(account) => {
+                account.state = state;
+                account.next_attempt_at = next_attempt_at;
+            }
+            None => self.per_account.push(AccountEngineStatus {
+                email: email.clone(),
+                state,
+                next_attempt_at,
+            }),
+        }
+    }
+
+    /// 移除已删除的账户
+    fn retain_accounts(&mut self, accounts: &[StoredAccount]) {
+        self.per_account
+            .retain(|status| accounts.iter().any(|a| a.email() == status.email));
+    }
+}
+
+/// 同步事件回调（同步循环与单账户同步共用）
+type SyncCallback = Arc<Mutex<dyn Fn(SyncEvent) + Send>>;
+
+/// 单账户同步（立即同步与推送触发的同步共用）
+#[derive(Clone)]
+struct SingleSync {
+    callback: SyncCallback,
+    registry: Arc<ProviderRegistry>,
+    runtime_state: &'static RuntimeState,
+    in_flight: InFlight,
+    status: Arc<watch::Sender<EngineStatus>>,
+}
+
+impl SingleSync {
+    /// 同步一个账户，记为只有该账户的一轮
+    ///
+    /// 与一轮完整同步一样遵守停用账户的重试间隔与计费网络的同步间隔
+    async fn run(self, email: String) {
+        let account = match load_accounts().await {
+            Ok(accounts) => accounts.into_iter().find(|a| a.email() == email),
+            Err(e) => {
+                tracing::error!("加载账户失败: {}", e);
+                return;
+            }
+        };
+        let Some(account) = account else {
+            tracing::debug!("账户已不存在，跳过同步");
+            return;
+        };
+
+        let mut recorder = history::RoundRecorder::start();
+        if connectivity::confirm_offline().await {
+            let event = SyncEvent::Failed(
+                email,
+                SyncFailure::offline("网络不可用（探测端点均无法访问）"),
+            );
+            recorder.observe(&event);
+            notify_recorded(&self.callback, &self.status, self.runtime_state, event);
+            history::record(recorder);
+            return;
+        }
+
+        self.sync_account(account, &mut recorder).await;
+        history::record(recorder);
+    }
+
+    /// 同步已加载的账户，结果记入 `recorder`
+    async fn sync_account(&self, account: StoredAccount, recorder: &mut history::RoundRecorder) {
+        in_round(sync_accounts(
+            vec![account],
+            &self.registry,
+            self.runtime_state,
+            false,
+            &self.in_flight,
+            &mut |event| {
+                recorder.observe(&event);
+                notify_recorded(&self.callback, &self.status, self.runtime_state, event);
+            },
+        ))
+        .await;
+    }
+}
+
 /// 同步引擎
 pub struct SyncEngine {
     /// 是否正在运行
@@ -27,8 +262,32 @@ pub struct SyncEngine {
     /// 立即同步触发器（使用 Notify 实现轻量级信号）
     trigger: Arc<Notify>,
 
-    /// 各账户的前一次未读数（用于检测新邮件）
-    previous_unread: Arc<RwLock<HashMap<String, u32>>>,
+    /// 运行时状态（各账户的新邮件通知基准，重启后保留）
+    state: &'static RuntimeState,
+
+    /// 睡眠恢复宽限期内（跳过定时同步，等待网络重连）
+    resume_grace: Arc<AtomicBool>,
+
+    /// 恢复后的首轮同步不将失败标记为错误
+    suppress_errors: Arc<AtomicBool>,
+
+    /// 当前同步间隔（秒，可运行时修改）
+    interval_secs: Arc<AtomicU64>,
+
+    /// 同步间隔变更信号
+    interval_changed: Arc<Notify>,
+
+    /// 邮件服务提供方（按账户类型分发）
+    registry: Arc<ProviderRegistry>,
+
+    /// 同步结果回调（`start` 时设置）
+    callback: OnceLock<SyncCallback>,
+
+    /// 正在同步的账户
+    in_flight: InFlight,
+
+    /// 引擎状态快照（`status` 读取，同步循环与单账户同步中更新）
+    status: Arc<watch::Sender<EngineStatus>>,
 }
 
 impl SyncEngine {
@@ -36,15 +295,39 @@ impl SyncEngine {
     ///
     /// # Arguments
     /// * `rt_handle` - Tokio 运行时句柄
-    pub fn new(rt_handle: tokio::runtime::Handle) -> Self {
+    /// * `registry` - 邮件服务提供方注册表（按账户类型分发同步）
+    pub fn new(rt_handle: tokio::runtime::Handle, registry: Arc<ProviderRegistry>) -> Self {
         Self {
             running: Arc::new(RwLock::new(false)),
             rt_handle,
             trigger: Arc::new(Notify::new()),
-            previous_unread: Arc::new(RwLock::new(HashMap::new())),
+            state: state::global(),
+            resume_grace: Arc::new(AtomicBool::new(false)),
+            suppress_errors: Arc::new(AtomicBool::new(false)),
+            interval_secs: Arc::new(AtomicU64::new(SYNC_INTERVAL_SECS)),
+            interval_changed: Arc::new(Notify::new()),
+            registry,
+            callback: OnceLock::new(),
+            in_flight: InFlight::default(),
+            status: Arc::new(watch::Sender::new(EngineStatus::default())),
         }
     }
 
+    /// 修改后台轮询间隔（非阻塞，可从任意线程调用，已运行时立即生效）
+    pub fn set_interval(&self, secs: u64) {
+        let secs = secs.max(MIN_SYNC_INTERVAL_SECS);
+        let old = self.interval_secs.swap(secs, Ordering::SeqCst);
+        if old != secs {
+            tracing::info!("⏱️ 同步间隔: {}秒 -> {}秒", old, secs);
+            self.interval_changed.notify_one();
+        }
+    }
+
+    /// 当前的引擎状态（读取最新快照，非阻塞，可从任意线程调用）
+    pub fn status(&self) -> EngineStatus {
+        self.status.borrow().clone()
+    }
+
     /// 触发立即同步（非阻塞，可从任意线程调用）
     ///
     /// 当窗口显示时调用此方法，会立即唤醒同步循环执行一次同步
@@ -53,20 +336,50 @@ impl SyncEngine {
         self.trigger.notify_one();
     }
 
+    /// 通知系统已从睡眠中恢复（非阻塞，可从任意线程调用）
+    ///
+    /// 宽限期内跳过定时同步，期满后立即同步一次；该轮的失败不标记为账户错误，
+    /// 避免网络尚未重连时界面整片变红
+    pub fn notify_resumed(&self) {
+        tracing::info!("💤 系统从睡眠恢复，{} 秒后重新同步", RESUME_GRACE_SECS);
+        self.resume_grace.store(true, Ordering::SeqCst);
+        self.suppress_errors.store(true, Ordering::SeqCst);
+        self.status.send_modify(|status| status.paused = true);
+        connectivity::reset();
+
+        let resume_grace = self.resume_grace.clone();
+        let trigger = self.trigger.clone();
+        let status = self.status.clone();
+        self.rt_handle.spawn(async move {
+            tokio::time::sleep(Duration::from_secs(RESUME_GRACE_SECS)).await;
+            resume_grace.store(false, Ordering::SeqCst);
+            status.send_modify(|status| status.paused = false);
+            trigger.notify_one();
+        });
+    }
+
     /// 启动同步引擎
     ///
     /// 会在后台线程中定期同步所有账户，同时监听手动触发信号
     ///
     /// # Arguments
-    /// * `sync_callback` - 同步完成后的回调函数，接收账户邮箱和同步信息
+    /// * `sync_callback` - 同步事件回调（每个账户开始与结束同步时各调用一次）
     pub fn start<F>(&self, sync_callback: F)
     where
-        F: Fn(String, Result<AccountSyncInfo, String>) + Send + 'static,
+        F: Fn(SyncEvent) + Send + 'static,
     {
+        let sync_callback: SyncCallback = Arc::new(Mutex::new(sync_callback));
         let running = self.running.clone();
         let trigger = self.trigger.clone();
         let handle = self.rt_handle.clone();
-        let previous_unread = self.previous_unread.clone();
+        let runtime_state = self.state;
+        let resume_grace = self.resume_grace.clone();
+        let suppress_errors = self.suppress_errors.clone();
+        let interval_secs = self.interval_secs.clone();
+        let interval_changed = self.interval_changed.clone();
+        let registry = self.registry.clone();
+        let in_flight = self.in_flight.clone();
+        let status = self.status.clone();
 
         // 检查是否已经在运行
         if *running.blocking_read() {
@@ -76,24 +389,42 @@ impl SyncEngine {
 
         // 标记为运行中
         *running.blocking_write() = true;
+        self.callback.set(sync_callback.clone()).ok();
+        status.send_modify(|status| status.running = true);
 
         tracing::info!(
             "🚀 启动同步引擎（后台轮询: {}秒 + 手动触发）",
-            SYNC_INTERVAL_SECS
+            interval_secs.load(Ordering::SeqCst)
         );
 
+        // 开启了推送的账户（IMAP IDLE、Gmail 的 IMAP 推送）有变化时立即同步该账户
+        let single = SingleSync {
+            callback: sync_callback.clone(),
+            registry: registry.clone(),
+            runtime_state,
+            in_flight: in_flight.clone(),
+            status: status.clone(),
+        };
+        handle.spawn(watch_push(running.clone(), registry.clone(), single));
+
         // 在 Tokio 运行时内部以异步任务启动同步循环
         handle.spawn(async move {
-            let mut timer = interval(Duration::from_secs(SYNC_INTERVAL_SECS));
+            let mut timer = new_timer(interval_secs.load(Ordering::SeqCst));
+            let mut breaker = CircuitBreaker::new();
 
             // 首次同步延迟3秒（等待UI初始化）
             tracing::debug!("等待 3 秒后开始首次同步...");
+            schedule_next_round(&status, 3);
             tokio::time::sleep(Duration::from_secs(3)).await;
 
             loop {
                 // 检查运行标志
                 if !*running.read().await {
                     tracing::info!("同步循环检测到停止标志，退出任务");
+                    status.send_modify(|status| {
+                        status.running = false;
+                        status.next_round_at = None;
+                    });
                     break;
                 }
 
@@ -107,11 +438,31 @@ impl SyncEngine {
                         // 重置定时器，避免刚手动同步完又触发定时同步
                         timer.reset();
                     }
+                    _ = interval_changed.notified() => {
+                        // 按新间隔重建定时器，下一次同步在新间隔后进行
+                        let secs = breaker.stretched(interval_secs.load(Ordering::SeqCst));
+                        timer = new_timer(secs);
+                        timer.reset();
+                        schedule_next_round(&status, secs);
+                        continue;
+                    }
+                }
+                schedule_next_round(
+                    &status,
+                    breaker.stretched(interval_secs.load(Ordering::SeqCst)),
+                );
+
+                // 睡眠恢复宽限期内网络可能尚未重连，期满后会自动触发同步
+                if resume_grace.load(Ordering::SeqCst) {
+                    tracing::debug!("睡眠恢复宽限期内，跳过本次同步");
+                    continue;
                 }
 
                 // ========== 执行同步（内联逻辑） ==========
+                let suppress_errors_this_round = suppress_errors.swap(false, Ordering::SeqCst);
+
                 // 加载所有账户
-                let accounts = match storage::load_accounts() {
+                let accounts = match load_accounts().await {
                     Ok(accounts) => accounts,
                     Err(e) => {
                         tracing::error!("加载账户失败: {}", e);
@@ -119,96 +470,112 @@ impl SyncEngine {
                     }
                 };
 
+                status.send_modify(|status| status.retain_accounts(&accounts));
                 if accounts.is_empty() {
                     tracing::debug!("没有账户需要同步");
                     continue;
                 }
 
-                tracing::info!("正在同步 {} 个账户...", accounts.len());
-
-                // 逐个同步账户
-                for account in accounts {
-                    let email = account.email.clone();
-
-                    match gmail::sync_account_info(&account).await {
-                        Ok((sync_info, updated_account)) => {
-                            tracing::info!(
-                                "✅ {} - 未读 {} 封",
-                                sync_info.email,
-                                sync_info.unread_count
-                            );
-
-                            // 如果 Token 被刷新，保存更新后的账户
-                            if let Some(updated) = updated_account {
-                                if let Err(e) = storage::save_account(&updated) {
-                                    tracing::error!("❌ 保存刷新后的账户失败: {}", e);
-                                }
-                            }
+                // 本轮各账户的结果记入同步记录
+                let mut recorder = history::RoundRecorder::start();
 
-                            tracing::info!(
-                                "[DEBUG-UNREAD] SyncEngine 准备调用回调: email={}, unread_count={}",
-                                sync_info.email,
-                                sync_info.unread_count
+                // 处于离线状态且重新探测仍失败时不发起请求，直接将账户标记为网络错误
+                if connectivity::confirm_offline().await {
+                    tracing::warn!("网络不可用，跳过本轮同步");
+                    if !suppress_errors_this_round {
+                        for account in accounts {
+                            let event = SyncEvent::Failed(
+                                account.email().to_string(),
+                                SyncFailure::offline("网络不可用（探测端点均无法访问）"),
                             );
-
-                            // 检测新邮件并发送通知
-                            {
-                                let mut prev = previous_unread.write().await;
-                                let old_count = prev.get(&sync_info.email).copied().unwrap_or(0);
-                                let new_count = sync_info.unread_count;
-                                
-                                if new_count > old_count {
-                                    let diff = new_count - old_count;
-                                    tracing::info!(
-                                        "📬 检测到新邮件: {} (+{} 封)",
-                                        sync_info.email,
-                                        diff
-                                    );
-                                    notification::show_new_mail_notification(&sync_info.email, diff);
-                                }
-                                
-                                // 更新记录
-                                prev.insert(sync_info.email.clone(), new_count);
-                            }
-
-                            // 调用回调函数更新UI（成功）
-                            sync_callback(email, Ok(sync_info));
-                        }
-                        Err(e) => {
-                            let err_str = e.to_string();
-                            tracing::error!("❌ 同步账户 {} 失败: {}", email, err_str);
-
-                            // 调用回调，传递错误信息
-                            sync_callback(email.clone(), Err(err_str.clone()));
-
-                            // 如果是网络检测最终失败，则立即终止本轮同步
-                            if err_str.contains("网络检测失败") || err_str.contains("网络不可用") {
-                                tracing::warn!(
-                                    "检测到网络不可用，终止本轮同步并将 N 标记为错误（红色）"
-                                );
-                                break;
-                            }
+                            recorder.observe(&event);
+                            notify_recorded(&sync_callback, &status, runtime_state, event);
                         }
                     }
+                    history::record(recorder);
+                    record_round(
+                        &mut breaker,
+                        &mut timer,
+                        &interval_secs,
+                        RoundOutcome::Failed,
+                    );
+                    continue;
                 }
 
+                tracing::info!("正在同步 {} 个账户...", accounts.len());
+
+                let outcome = in_round(sync_accounts(
+                    accounts,
+                    &registry,
+                    runtime_state,
+                    suppress_errors_this_round,
+                    &in_flight,
+                    &mut |event| {
+                        recorder.observe(&event);
+                        notify_recorded(&sync_callback, &status, runtime_state, event);
+                    },
+                ))
+                .await;
+                history::record(recorder);
+
                 tracing::info!("✅ 本轮同步完成");
+                if record_round(&mut breaker, &mut timer, &interval_secs, outcome) {
+                    schedule_next_round(
+                        &status,
+                        breaker.stretched(interval_secs.load(Ordering::SeqCst)),
+                    );
+                }
             }
         });
     }
 
+    /// 立即同步单个账户（非阻塞，不经过同步循环，结果交给 `start` 传入的回调）
+    ///
+    /// 与一轮完整同步的处理相同（保存刷新后的 Token、免打扰到期、新邮件通知）；
+    /// 该账户已在同步中（重复请求或正在进行的一轮同步）时合并到进行中的同步。
+    /// 引擎尚未启动或账户不存在时忽略
+    pub fn sync_one(&self, email: &str) {
+        let Some(sync_callback) = self.callback.get().cloned() else {
+            tracing::warn!("同步引擎尚未启动，忽略单账户同步");
+            return;
+        };
+        let single = SingleSync {
+            callback: sync_callback,
+            registry: self.registry.clone(),
+            runtime_state: self.state,
+            in_flight: self.in_flight.clone(),
+            status: self.status.clone(),
+        };
+
+        tracing::info!("🔄 立即同步账户: {}", redact_email(email));
+        self.rt_handle.spawn(single.run(email.to_string()));
+    }
+
+    /// 用户要求立即同步单个账户：先清除停用账户的重试间隔与计费网络的同步记录，再同步
+    ///
+    /// 只用于用户明确的"立即同步"操作；其他刷新（修改设置、免打扰到期等）使用 `sync_one`
+    pub fn retry_now(&self, email: &str) {
+        if let Err(e) = self.state.update_account(email, |state| {
+            state.unavailable_until = None;
+        }) {
+            tracing::warn!("保存运行时状态失败: {:#}", e);
+        }
+        metered::forget(email);
+        self.sync_one(email);
+    }
+
     /// 立即执行一次同步（异步版本，适用于已在 async 上下文中）
     ///
     /// # Arguments
-    /// * `sync_callback` - 同步完成后的回调函数
-    pub async fn sync_now<F>(&self, sync_callback: F) -> Result<()>
+    /// * `sync_callback` - 同步事件回调
+    pub async fn sync_now<F>(&self, mut sync_callback: F) -> Result<()>
     where
-        F: Fn(String, Result<AccountSyncInfo, String>) + Send,
+        F: Fn(SyncEvent) + Send,
     {
         tracing::info!("🔄 立即同步所有账户...");
 
         // 加载所有账户
-        let accounts = storage::load_accounts()?;
+        let accounts = load_accounts().await?;
 
         if accounts.is_empty() {
             tracing::info!("📭 没有账户需要同步");
@@ -217,37 +584,15 @@ impl SyncEngine {
 
         tracing::info!("正在同步 {} 个账户...", accounts.len());
 
-        for account in accounts {
-            let email = account.email.clone();
-
-            match gmail::sync_account_info(&account).await {
-                Ok((sync_info, updated_account)) => {
-                    tracing::info!(
-                        "✅ {} - 未读 {} 封",
-                        sync_info.email,
-                        sync_info.unread_count
-                    );
-
-                    if let Some(updated) = updated_account {
-                        if let Err(e) = storage::save_account(&updated) {
-                            tracing::error!("❌ 保存刷新后的账户失败: {}", e);
-                        }
-                    }
-
-                    sync_callback(email, Ok(sync_info));
-                }
-                Err(e) => {
-                    let err_str = e.to_string();
-                    tracing::error!("❌ 同步账户 {} 失败: {}", email, err_str);
-                    sync_callback(email.clone(), Err(err_str.clone()));
-
-                    if err_str.contains("网络检测失败") || err_str.contains("网络不可用") {
-                        tracing::warn!("检测到网络不可用，本轮同步终止");
-                        break;
-                    }
-                }
-            }
-        }
+        in_round(sync_accounts(
+            accounts,
+            &self.registry,
+            self.state,
+            false,
+            &self.in_flight,
+            &mut sync_callback,
+        ))
+        .await;
 
         tracing::info!("✅ 立即同步完成");
         Ok(())
@@ -256,28 +601,1129 @@ impl SyncEngine {
     /// 停止同步引擎
     pub async fn stop(&self) {
         *self.running.write().await = false;
+        self.status.send_modify(|status| status.running = false);
         tracing::info!("🛑 同步引擎已停止");
     }
 
     /// 同步请求停止（同步接口，适用于在非 async 环境调用）
     pub fn request_stop(&self) {
         *self.running.blocking_write() = false;
+        self.status.send_modify(|status| status.running = false);
         tracing::info!("🛑 已请求停止同步引擎（同步接口）");
     }
 }
 
+/// 应用配置中影响同步结果的进程级设置（Gmail 服务地址覆盖、计数上限、API 请求速率阈值、重试预算、
+/// 自动邮件发件人规则与计费网络下的节流）
+///
+/// 须在首次同步前调用；界面与无界面模式（`--print-counts`）共用
+pub fn configure(cfg: &Config) {
+    crate::mail::gmail::endpoints::configure(&cfg.gmail_endpoints);
+    crate::mail::gmail::api::set_count_ceiling(cfg.app.count_ceiling);
+    quota::set_rate_warning(cfg.app.api_rate_warning);
+    crate::mail::retry::set_round_budget(cfg.app.retry_budget);
+    crate::mail::gmail::people::set_patterns(&cfg.app.automated_sender_patterns);
+    metered::set_respect(cfg.app.respect_metered);
+    notification::webhook::configure(&cfg.automation);
+}
+
+/// 以 `sync.round` span 进行一轮同步，结束后记录各阶段的耗时
+async fn in_round<T>(round_future: impl Future<Output = T>) -> T {
+    let round = ROUND.fetch_add(1, Ordering::Relaxed) + 1;
+    let output = round_future
+        .instrument(tracing::info_span!("sync.round", sync.round = round))
+        .await;
+
+    // 只有注册了 PhaseTimingLayer（界面模式的日志系统）时才有汇总
+    if let Some(summary) = telemetry::last_round().filter(|s| s.round == round) {
+        let total_ms = summary.total.as_millis() as u64;
+        tracing::info!(
+            sync.round = round,
+            total_ms,
+            phases = %summary,
+            "⏱️ 本轮同步耗时 {} ms",
+            total_ms
+        );
+    }
+    output
+}
+
+/// 调用同步事件回调
+fn notify(sync_callback: &SyncCallback, event: SyncEvent) {
+    let callback = sync_callback.lock().unwrap_or_else(|e| e.into_inner());
+    callback(event);
+}
+
+/// 记录账户的引擎状态后调用同步事件回调
+fn notify_recorded(
+    sync_callback: &SyncCallback,
+    status: &watch::Sender<EngineStatus>,
+    runtime_state: &RuntimeState,
+    event: SyncEvent,
+) {
+    let retry_at = match &event {
+        SyncEvent::Failed(email, failure) if failure.is_account_unavailable() => {
+            runtime_state.account(email).unavailable_until
+        }
+        _ => None,
+    };
+    status.send_modify(|status| status.record(&event, retry_at));
+    notify(sync_callback, event);
+}
+
+/// 把一轮的结果记入熔断器；同步间隔的倍数改变时按新间隔重建定时器，返回是否重建
+fn record_round(
+    breaker: &mut CircuitBreaker,
+    timer: &mut tokio::time::Interval,
+    interval_secs: &AtomicU64,
+    outcome: RoundOutcome,
+) -> bool {
+    if !breaker.record(outcome) {
+        return false;
+    }
+
+    let secs = breaker.stretched(interval_secs.load(Ordering::SeqCst));
+    if breaker.is_open() {
+        tracing::warn!(
+            "连续多轮同步全部失败，同步间隔拉长为 {} 秒（{} 倍）",
+            secs,
+            breaker.stretch()
+        );
+    } else {
+        tracing::info!("同步恢复，同步间隔恢复为 {} 秒", secs);
+    }
+    BACKING_OFF.store(breaker.is_open(), Ordering::Relaxed);
+
+    // 与修改间隔时相同：下一次同步在新间隔后进行
+    *timer = new_timer(secs);
+    timer.reset();
+    true
+}
+
+/// 按间隔创建同步定时器
+fn new_timer(secs: u64) -> tokio::time::Interval {
+    let mut timer = interval(Duration::from_secs(secs));
+    // 睡眠恢复后不补发错过的 tick
+    timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    timer
+}
+
+/// 记录下一轮定时同步的预计时间（`secs` 秒后）
+fn schedule_next_round(status: &watch::Sender<EngineStatus>, secs: u64) {
+    let at = Utc::now() + chrono::Duration::seconds(secs as i64);
+    status.send_modify(|status| status.next_round_at = Some(at));
+}
+
+/// 在阻塞线程中读取账户（读文件与解析 TOML 不占用异步运行时的工作线程）
+async fn load_accounts() -> Result<Vec<StoredAccount>> {
+    tokio::task::spawn_blocking(storage::load_accounts)
+        .await
+        .context("读取账户的任务失败")?
+}
+
+/// 为支持推送的账户维护监听任务
+///
+/// 定期重新读取账户列表：为新账户（或推送设置变化的账户）启动监听，停止已删除账户的监听；
+/// 引擎停止时全部终止
+async fn watch_push(
+    running: Arc<RwLock<bool>>,
+    registry: Arc<ProviderRegistry>,
+    single: SingleSync,
+) {
+    // 以推送设置区分：开启或关闭推送后旧的监听被取消、按新设置重新启动
+    let mut watchers: HashMap<(String, String, bool), tokio::task::JoinHandle<()>> = HashMap::new();
+
+    while *running.read().await {
+        let accounts = load_accounts().await.unwrap_or_default();
+        let key_of = |account: &StoredAccount| {
+            (
+                account.kind().to_string(),
+                account.email().to_string(),
+                account.push_enabled(),
+            )
+        };
+
+        watchers.retain(|key, watcher| {
+            let exists = accounts.iter().any(|a| key_of(a) == *key);
+            if !exists {
+                watcher.abort();
+            }
+            exists
+        });
+
+        for account in accounts {
+            let key = key_of(&account);
+            if watchers.contains_key(&key) {
+                continue;
+            }
+            if let Some(provider) = registry.for_account(&account) {
+                let watcher = tokio::spawn(push_loop(provider, account, single.clone()));
+                watchers.insert(key, watcher);
+            }
+        }
+
+        tokio::time::sleep(Duration::from_secs(PUSH_RECONCILE_SECS)).await;
+    }
+
+    for watcher in watchers.values() {
+        watcher.abort();
+    }
+}
+
+/// 单个账户的推送监听循环（账户不支持推送时立即结束）
+///
+/// 每次等待前重新读取账户（使用同步中刷新后的 Token 等最新数据）；收到推送时只同步该账户，
+/// 连接失败后按 [`push_retry_delay`] 逐次延长重试间隔
+async fn push_loop(provider: Arc<dyn MailProvider>, account: StoredAccount, single: SingleSync) {
+    let email = account.email().to_string();
+    let mut account = account;
+    let mut failures = 0;
+    loop {
+        match provider.wait_for_change(&account).await {
+            Ok(PushEvent::Changed) => {
+                failures = 0;
+                tracing::info!("📨 {} 收到推送，立即同步", redact_email(&email));
+                tokio::spawn(single.clone().run(email.clone()));
+            }
+            Ok(PushEvent::Timeout) => failures = 0,
+            Ok(PushEvent::Unsupported) => break,
+            Err(e) => {
+                failures += 1;
+                let delay = push_retry_delay(failures);
+                tracing::warn!(
+                    "推送监听失败 {}: {:#}，{} 秒后重试",
+                    redact_email(&email),
+                    e,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        match load_accounts().await {
+            Ok(accounts) => match accounts.into_iter().find(|a| a.email() == email) {
+                Some(latest) => account = latest,
+                None => break,
+            },
+            Err(e) => tracing::debug!("重新读取账户失败，沿用上次的账户数据: {:#}", e),
+        }
+    }
+}
+
+/// 推送连接第 `failures` 次连续失败后的重试间隔（从 [`PUSH_RETRY_SECS`] 起逐次加倍，不超过上限）
+fn push_retry_delay(failures: u32) -> Duration {
+    let secs = PUSH_RETRY_SECS.saturating_mul(1 << failures.saturating_sub(1).min(16));
+    Duration::from_secs(secs.min(PUSH_RETRY_MAX_SECS))
+}
+
+/// 逐个同步账户（定时轮询与立即同步共用）
+///
+/// 按账户类型从注册表取出提供方；检测到的新邮件在本轮结束时统一通知，检测到网络不可用或
+/// 本轮的重试预算用完时终止本轮。
+/// `suppress_errors` 为 true 时（睡眠恢复后首轮）失败不上报，保留原有界面状态；
+/// 已在 `in_flight` 中的账户跳过，不上报结果。返回本轮的总体结果（供熔断器使用）
+async fn sync_accounts<F>(
+    accounts: Vec<StoredAccount>,
+    registry: &ProviderRegistry,
+    runtime_state: &RuntimeState,
+    suppress_errors: bool,
+    in_flight: &InFlight,
+    sync_callback: &mut F,
+) -> RoundOutcome
+where
+    F: FnMut(SyncEvent),
+{
+    // 新邮件通知在本轮结束时统一发送（多个账户时合并为一条）
+    let mut notifications = notification::RoundNotifications::new();
+
+    // 本轮所有额外尝试（网络探测、强制刷新 Token、停用账户的重试）共用一份预算
+    let retry = RetryContext::for_round();
+    let mut outcome = RoundOutcome::Skipped;
+
+    for account in accounts {
+        let email = account.email().to_string();
+        let baseline = account.unread_baseline();
+
+        let Some(_guard) = in_flight.begin(&email) else {
+            tracing::debug!("{} 正在同步中，合并到进行中的同步", redact_email(&email));
+            continue;
+        };
+
+        let Some(provider) = registry.for_account(&account) else {
+            tracing::warn!(
+                "不支持的账户类型 {}，跳过 {}",
+                account.kind(),
+                redact_email(&email)
+            );
+            continue;
+        };
+
+        // 邮件服务被停用的账户每小时只重试一次，期间直接沿用上次的结果；
+        // 到期后的重试计入本轮的重试预算，预算用完时推迟到下一轮
+        if let Some(until) = runtime_state.account(&email).unavailable_until {
+            if until > Utc::now() {
+                tracing::debug!(
+                    "{} 的邮件服务已停用，{} 前不再重试",
+                    redact_email(&email),
+                    until
+                );
+                sync_callback(SyncEvent::Failed(email, SyncFailure::account_unavailable()));
+                continue;
+            }
+            if retry.spend("重试邮件服务已停用的账户").is_err() {
+                sync_callback(SyncEvent::Failed(email, SyncFailure::account_unavailable()));
+                continue;
+            }
+        }
+
+        // 最近 1 分钟的 API 请求过多时暂缓同步，等速率回落（相当于拉长该账户的同步间隔）
+        if quota::is_over_limit(&email) {
+            tracing::info!("{} 的 API 请求速率超过阈值，本轮跳过", redact_email(&email));
+            continue;
+        }
+
+        // 按流量计费的网络下拉长同步间隔（账户设置 ignore_metered 时照常同步）
+        let policy = metered::current(account.ignore_metered());
+        if !metered::is_due(&email, policy) {
+            tracing::debug!("按流量计费的网络，{} 本轮跳过", redact_email(&email));
+            continue;
+        }
+        metered::record(&email, policy);
+
+        sync_callback(SyncEvent::Started(email.clone()));
+
+        let result = provider
+            .sync(&account, &retry)
+            .instrument(tracing::info_span!(
+                "sync.account",
+                account.email = %redact_email(&email),
+                account.kind = account.kind()
+            ))
+            .await;
+        quota::persist(&email);
+
+        match result {
+            Ok(SyncOutcome {
+                info: mut sync_info,
+                mut updated_account,
+            }) => {
+                tracing::info!(
+                    "✅ {} - 未读 {} 封",
+                    redact_email(sync_info.email()),
+                    sync_info.unread()
+                );
+                log_throttle::recovered(&email);
+
+                // 免打扰到期后自动解除（与 Token 刷新后的账户一并保存）
+                let snoozed = account.is_snoozed();
+                if account.snoozed_until().is_some() && !snoozed {
+                    tracing::info!("⏰ {} 免打扰已到期", redact_email(&email));
+                    let mut unsnoozed = updated_account.unwrap_or_else(|| account.clone());
+                    unsnoozed.set_snoozed_until(None);
+                    updated_account = Some(unsnoozed);
+                }
+
+                // 保存更新后的账户（Token 被刷新或免打扰到期）
+                if let Some(updated) = updated_account
+                    && let Err(e) = storage::save_account_async(updated).await
+                {
+                    tracing::error!("❌ 保存刷新后的账户失败: {}", e);
+                }
+
+                tracing::info!(
+                    "[DEBUG-UNREAD] SyncEngine 准备调用回调: email={}, unread_count={}",
+                    redact_email(sync_info.email()),
+                    sync_info.unread()
+                );
+
+                // 检测新邮件并发送通知（按通知标签的未读数，与徽章使用相同的计数方式；
+                // 未单独指定通知标签时即徽章的未读数）
+                // 免打扰期间不通知也不更新记录，到期后的首轮同步把期间的新邮件合并为一条通知
+                let new_count = sync_info.unread();
+                let record = NotificationBaseline {
+                    unread: sync_info.notify_unread(),
+                    counted_since: baseline,
+                    label: account.notify_label(),
+                };
+                // 已通知过的邮件不再列入摘要（去重记录只保存邮件 ID 与加盐摘要）
+                let salt = runtime_state.salt();
+                let mut notified = Vec::new();
+                if snoozed {
+                    tracing::debug!("{} 处于免打扰期，跳过通知", redact_email(&email));
+                } else {
+                    let previous = runtime_state.account(&email).notification;
+                    let diff = new_mail_delta(previous.as_ref(), &record);
+
+                    if diff > 0 {
+                        tracing::info!(
+                            "📬 检测到新邮件: {} (+{} 封)",
+                            redact_email(sync_info.email()),
+                            diff
+                        );
+                        let name = match account.display_name() {
+                            "" => sync_info.email().to_string(),
+                            name => name.to_string(),
+                        };
+                        notified = runtime_state
+                            .account(&email)
+                            .unseen_previews(&salt, &sync_info.previews);
+                        // 点击通知打开收件箱；Gmail 只有一封新邮件时直接打开这封邮件
+                        let open_url = match (&account, notified.as_slice()) {
+                            (StoredAccount::Gmail(_), [only])
+                                if diff == 1 && !only.id.is_empty() =>
+                            {
+                                crate::mail::gmail::urls::message_url(
+                                    Some(sync_info.email()),
+                                    &only.id,
+                                )
+                            }
+                            _ => provider.open_inbox_url(&account),
+                        };
+                        notifications.push(notification::NewMail {
+                            email: sync_info.email().to_string(),
+                            name,
+                            count: diff,
+                            previews: notified.clone(),
+                            avatar: crate::utils::avatar::get_cached_avatar_path(&email)
+                                .map(std::path::PathBuf::from),
+                            open_url,
+                        });
+                        // 配置了 Webhook 时在后台通知（不影响本轮同步）
+                        notification::webhook::fire(&email, diff, new_count);
+                    }
+                }
+
+                // 更新记录（写入运行时状态文件，重启后不重复通知）、未读数历史与本轮结果
+                // （下次启动时在首次同步完成前显示；用户信息获取失败时保留上次的资料）
+                let profile = sync_info.saved_profile();
+                let alert_threshold = account.alert_threshold();
+                let mut alert = false;
+                if let Err(e) = runtime_state.update_account(&email, |state| {
+                    let now = Utc::now();
+                    if !snoozed {
+                        state.notification = Some(record);
+                        alert = state.check_alert(alert_threshold, new_count);
+                        if !notified.is_empty() {
+                            state.record_notified(&salt, &notified);
+                        }
+                    }
+                    state.record_unread(now, new_count);
+                    state.record_sync(now, new_count, profile);
+                    state.unavailable_until = None;
+                }) {
+                    tracing::warn!("保存运行时状态失败: {:#}", e);
+                }
+
+                // 未读数越过提醒阈值：每次越过只提醒一次（触发记录随运行时状态保存，重启后不重复提醒）
+                if alert && let Some(threshold) = alert_threshold {
+                    let avatar = crate::utils::avatar::get_cached_avatar_path(&email);
+                    notification::show_alert_notification(
+                        sync_info.email(),
+                        threshold,
+                        avatar.as_deref().map(std::path::Path::new),
+                    );
+                }
+                sync_info.health.snoozed = snoozed;
+
+                // 调用回调函数更新UI（成功）
+                sync_callback(SyncEvent::Synced(email, sync_info));
+                outcome = RoundOutcome::Succeeded;
+            }
+            Err(failure) => {
+                // 断网时每轮都会得到同样的错误：首次完整记录，之后只定期记录重复次数
+                log_throttle::error(
+                    &email,
+                    &failure.kind_label(),
+                    format_args!("同步失败: {}", failure.detail),
+                );
+
+                if failure.is_account_unavailable() {
+                    tracing::warn!(
+                        "{} 的邮件服务已停用，{} 分钟后再重试",
+                        redact_email(&email),
+                        UNAVAILABLE_RETRY.num_minutes()
+                    );
+                    if let Err(e) = runtime_state.update_account(&email, |state| {
+                        state.unavailable_until = Some(Utc::now() + UNAVAILABLE_RETRY);
+                    }) {
+                        tracing::warn!("保存运行时状态失败: {:#}", e);
+                    }
+                }
+
+                // 调用回调，传递错误信息（睡眠恢复后的首轮失败不上报，保留原有界面状态）
+                if suppress_errors {
+                    tracing::warn!("睡眠恢复后首轮同步失败，暂不标记账户错误");
+                    sync_callback(SyncEvent::FailedQuietly(email));
+                } else {
+                    sync_callback(SyncEvent::Failed(email, failure.clone()));
+                }
+
+                if outcome == RoundOutcome::Skipped {
+                    outcome = RoundOutcome::Failed;
+                }
+
+                // 如果是网络检测最终失败，则立即终止本轮同步
+                if failure.is_offline() {
+                    tracing::warn!("检测到网络不可用，终止本轮同步并将 N 标记为错误（红色）");
+                    break;
+                }
+
+                // 重试预算用完：其余账户留到下一轮，不再逐个失败
+                if failure.is_budgeted() {
+                    tracing::warn!("本轮重试预算已用完，终止本轮同步");
+                    break;
+                }
+            }
+        }
+    }
+
+    notifications.finish();
+    outcome
+}
+
+/// 计算免打扰截止时间
+///
+/// # Arguments
+/// * `choice` - "30m" | "1h" | "tomorrow"（次日早上 8 点）；其他值表示取消免打扰
+/// * `now` - 当前本地时间
+pub fn snooze_deadline(choice: &str, now: DateTime<Local>) -> Option<DateTime<Utc>> {
+    let deadline = match choice {
+        "30m" => now + chrono::Duration::minutes(30),
+        "1h" => now + chrono::Duration::hours(1),
+        "tomorrow" => {
+            let morning = now.date_naive().succ_opt()?.and_hms_opt(8, 0, 0)?;
+            Local.from_local_datetime(&morning).earliest()?
+        }
+        _ => return None,
+    };
+    Some(deadline.with_timezone(&Utc))
+}
+
+/// 计算需要通知的新邮件数
+///
+/// 计数基准变化（切换计数方式、重置计数或更换通知标签）时未读数的变化并非新邮件，只更新记录不通知
+fn new_mail_delta(previous: Option<&NotificationBaseline>, current: &NotificationBaseline) -> u32 {
+    match previous {
+        Some(old) if old.counted_since != current.counted_since || old.label != current.label => 0,
+        Some(old) => current.unread.saturating_sub(old.unread),
+        None => current.unread,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mail::MailProvider;
+    use crate::mail::gmail::types::GmailAccount;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// 测试用提供方：按邮箱返回固定结果，不发起网络请求
+    struct MockProvider {
+        /// 返回"网络不可用"的邮箱
+        offline_email: &'static str,
+
+        /// 返回"邮件服务已停用"的邮箱
+        unavailable_email: &'static str,
+
+        /// 每次同步的耗时
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl MailProvider for MockProvider {
+        fn kind(&self) -> &'static str {
+            "gmail"
+        }
+
+        async fn sync(
+            &self,
+            account: &StoredAccount,
+            _retry: &RetryContext,
+        ) -> Result<SyncOutcome, SyncFailure> {
+            tokio::time::sleep(self.delay).await;
+            if account.email() == self.offline_email {
+                return Err(SyncFailure::offline("mock offline"));
+            }
+            if account.email() == self.unavailable_email {
+                return Err(SyncFailure::account_unavailable());
+            }
+            Ok(SyncOutcome {
+                info: AccountSyncInfo::new(account.email(), 0),
+                updated_account: None,
+            })
+        }
+
+        async fn authenticate(&self) -> anyhow::Result<Option<StoredAccount>> {
+            anyhow::bail!("mock provider does not authenticate")
+        }
+
+        fn open_inbox_url(&self, account: &StoredAccount) -> String {
+            format!("mock://{}", account.email())
+        }
+    }
+
+    /// 测试用提供方：每个账户先做几次额外尝试（从本轮预算中扣除）后失败
+    struct RetryingProvider {
+        /// 每个账户的额外尝试次数
+        attempts: u32,
+    }
+
+    #[async_trait]
+    impl MailProvider for RetryingProvider {
+        fn kind(&self) -> &'static str {
+            "gmail"
+        }
+
+        async fn sync(
+            &self,
+            _account: &StoredAccount,
+            retry: &RetryContext,
+        ) -> Result<SyncOutcome, SyncFailure> {
+            for _ in 0..self.attempts {
+                if let Err(budgeted) = retry.spend("mock retry") {
+                    return Err(SyncFailure::from_error(&anyhow::Error::new(budgeted)));
+                }
+            }
+            Err(SyncFailure::from_error(&anyhow::anyhow!("mock failure")))
+        }
+
+        async fn authenticate(&self) -> anyhow::Result<Option<StoredAccount>> {
+            anyhow::bail!("mock provider does not authenticate")
+        }
+
+        fn open_inbox_url(&self, account: &StoredAccount) -> String {
+            format!("mock://{}", account.email())
+        }
+    }
+
+    fn mock_account(email: &str) -> StoredAccount {
+        StoredAccount::Gmail(GmailAccount {
+            email: email.to_string(),
+            display_name: email.to_string(),
+            access_token: "encrypted:mock".to_string(),
+            refresh_token: "encrypted:mock".to_string(),
+            expires_at: chrono::Utc::now(),
+            count_mode: Default::default(),
+            counted_since: None,
+            snoozed_until: None,
+            accent: None,
+            watch_spam: false,
+            people_only: false,
+            watch_vacation: false,
+            badge_label: "INBOX".to_string(),
+            notify_label: "INBOX".to_string(),
+            is_active: true,
+            refreshed_at: None,
+            refresh_token_issued_at: None,
+            testing_client: false,
+            scopes: Vec::new(),
+            pending_delete_at: None,
+            alert_threshold: None,
+            group: None,
+            ignore_metered: false,
+            push: false,
+        })
+    }
+
+    /// 同步一组账户并收集回调结果：(邮箱, 是否成功)
+    fn run_round(registry: &ProviderRegistry, emails: &[&str]) -> Vec<(String, bool)> {
+        run_round_events(registry, emails, false)
+            .into_iter()
+            .filter_map(|event| match event {
+                SyncEvent::Synced(email, _) => Some((email, true)),
+                SyncEvent::Failed(email, _) => Some((email, false)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 同步一组账户并收集全部同步事件
+    fn run_round_events(
+        registry: &ProviderRegistry,
+        emails: &[&str],
+        suppress_errors: bool,
+    ) -> Vec<SyncEvent> {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let accounts = emails.iter().map(|email| mock_account(email)).collect();
+        let runtime_state = RuntimeState::in_memory();
+        let events = Mutex::new(Vec::new());
+
+        rt.block_on(sync_accounts(
+            accounts,
+            registry,
+            &runtime_state,
+            suppress_errors,
+            &InFlight::default(),
+            &mut |event| events.lock().unwrap().push(event),
+        ));
+        events.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_sync_dispatches_through_registry() {
+        let mut registry = ProviderRegistry::default();
+        registry.register(Arc::new(MockProvider {
+            offline_email: "b@example.com",
+            unavailable_email: "",
+            delay: Duration::ZERO,
+        }));
+
+        // b 报告网络不可用后本轮终止，c 不再同步
+        let results = run_round(
+            &registry,
+            &["a@example.com", "b@example.com", "c@example.com"],
+        );
+        assert_eq!(
+            results,
+            vec![
+                ("a@example.com".to_string(), true),
+                ("b@example.com".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_every_started_account_reports_an_end() {
+        let mut registry = ProviderRegistry::default();
+        registry.register(Arc::new(MockProvider {
+            offline_email: "b@example.com",
+            unavailable_email: "",
+            delay: Duration::ZERO,
+        }));
+        let summarize = |events: Vec<SyncEvent>| -> Vec<String> {
+            events
+                .into_iter()
+                .map(|event| match event {
+                    SyncEvent::Started(email) => format!("start {}", email),
+                    SyncEvent::Synced(email, _) => format!("ok {}", email),
+                    SyncEvent::Failed(email, _) => format!("fail {}", email),
+                    SyncEvent::FailedQuietly(email) => format!("quiet {}", email),
+                })
+                .collect()
+        };
+
+        let events = run_round_events(&registry, &["a@example.com", "b@example.com"], false);
+        assert_eq!(
+            summarize(events),
+            vec![
+                "start a@example.com",
+                "ok a@example.com",
+                "start b@example.com",
+                "fail b@example.com",
+            ]
+        );
+
+        // 睡眠恢复后首轮的失败不上报，但仍然结束该账户的同步状态
+        let events = run_round_events(&registry, &["b@example.com"], true);
+        assert_eq!(
+            summarize(events),
+            vec!["start b@example.com", "quiet b@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_unavailable_account_is_retried_hourly() {
+        let mut registry = ProviderRegistry::default();
+        registry.register(Arc::new(MockProvider {
+            offline_email: "",
+            unavailable_email: "b@example.com",
+            delay: Duration::ZERO,
+        }));
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime_state = RuntimeState::in_memory();
+        let round = || {
+            let events = Mutex::new(Vec::new());
+            rt.block_on(sync_accounts(
+                vec![mock_account("a@example.com"), mock_account("b@example.com")],
+                &registry,
+                &runtime_state,
+                false,
+                &InFlight::default(),
+                &mut |event| events.lock().unwrap().push(event),
+            ));
+            events.into_inner().unwrap()
+        };
+        let started = |events: &[SyncEvent]| -> Vec<String> {
+            events
+                .iter()
+                .filter_map(|event| match event {
+                    SyncEvent::Started(email) => Some(email.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let events = round();
+        assert_eq!(started(&events), vec!["a@example.com", "b@example.com"]);
+        assert!(
+            runtime_state
+                .account("b@example.com")
+                .unavailable_until
+                .is_some()
+        );
+
+        // 重试间隔内不再请求，但仍然上报停用状态
+        let events = round();
+        assert_eq!(started(&events), vec!["a@example.com"]);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            SyncEvent::Failed(email, failure)
+                if email == "b@example.com" && failure.is_account_unavailable()
+        )));
+
+        // 重试间隔过后再次请求
+        runtime_state
+            .update_account("b@example.com", |state| {
+                state.unavailable_until = Some(Utc::now() - chrono::Duration::minutes(1));
+            })
+            .unwrap();
+        let events = round();
+        assert_eq!(started(&events), vec!["a@example.com", "b@example.com"]);
+    }
+
+    /// 测试用提供方：只记录同步次数
+    #[derive(Default)]
+    struct CountingProvider {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl MailProvider for CountingProvider {
+        fn kind(&self) -> &'static str {
+            "gmail"
+        }
+
+        async fn sync(
+            &self,
+            account: &StoredAccount,
+            _retry: &RetryContext,
+        ) -> Result<SyncOutcome, SyncFailure> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(SyncOutcome {
+                info: AccountSyncInfo::new(account.email(), 0),
+                updated_account: None,
+            })
+        }
+
+        async fn authenticate(&self) -> anyhow::Result<Option<StoredAccount>> {
+            anyhow::bail!("mock provider does not authenticate")
+        }
+
+        fn open_inbox_url(&self, account: &StoredAccount) -> String {
+            format!("mock://{}", account.email())
+        }
+    }
+
+    #[test]
+    fn test_single_sync_respects_unavailable_backoff() {
+        let provider = Arc::new(CountingProvider::default());
+        let mut registry = ProviderRegistry::default();
+        registry.register(provider.clone());
+        let runtime_state: &'static RuntimeState = Box::leak(Box::new(RuntimeState::in_memory()));
+        runtime_state
+            .update_account("a@example.com", |state| {
+                state.unavailable_until = Some(Utc::now() + UNAVAILABLE_RETRY);
+            })
+            .unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let single = SingleSync {
+            callback: Arc::new(Mutex::new({
+                let events = events.clone();
+                move |event: SyncEvent| events.lock().unwrap().push(event)
+            })),
+            registry: Arc::new(registry),
+            runtime_state,
+            in_flight: InFlight::default(),
+            status: Arc::new(watch::Sender::new(EngineStatus::default())),
+        };
+
+        // 单账户同步（sync_one、推送）在重试间隔内不发起请求，仍然上报停用状态
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(single.sync_account(
+            mock_account("a@example.com"),
+            &mut history::RoundRecorder::start(),
+        ));
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 0);
+        assert!(events.lock().unwrap().iter().any(|event| matches!(
+            event,
+            SyncEvent::Failed(_, failure) if failure.is_account_unavailable()
+        )));
+        assert!(
+            runtime_state
+                .account("a@example.com")
+                .unavailable_until
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_exhausted_retry_budget_ends_round() {
+        let mut registry = ProviderRegistry::default();
+        registry.register(Arc::new(RetryingProvider {
+            attempts: crate::mail::retry::DEFAULT_RETRY_BUDGET / 2 - 1,
+        }));
+
+        // 前两个账户用掉大部分预算，第三个账户用完后以 Budgeted 失败，本轮随即结束
+        let events = run_round_events(
+            &registry,
+            &[
+                "a@example.com",
+                "b@example.com",
+                "c@example.com",
+                "d@example.com",
+            ],
+            false,
+        );
+        let failures: Vec<(String, bool)> = events
+            .into_iter()
+            .filter_map(|event| match event {
+                SyncEvent::Failed(email, failure) => Some((email, failure.is_budgeted())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            failures,
+            vec![
+                ("a@example.com".to_string(), false),
+                ("b@example.com".to_string(), false),
+                ("c@example.com".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_outcome() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let outcome = |registry: &ProviderRegistry, emails: &[&str]| {
+            let accounts = emails.iter().map(|email| mock_account(email)).collect();
+            rt.block_on(sync_accounts(
+                accounts,
+                registry,
+                &RuntimeState::in_memory(),
+                false,
+                &InFlight::default(),
+                &mut |_| {},
+            ))
+        };
+        let mut registry = ProviderRegistry::default();
+        registry.register(Arc::new(MockProvider {
+            offline_email: "b@example.com",
+            unavailable_email: "",
+            delay: Duration::ZERO,
+        }));
+
+        assert_eq!(
+            outcome(&registry, &["a@example.com", "b@example.com"]),
+            RoundOutcome::Succeeded
+        );
+        assert_eq!(outcome(&registry, &["b@example.com"]), RoundOutcome::Failed);
+        // 没有发起任何同步的一轮不计入熔断
+        assert_eq!(
+            outcome(&ProviderRegistry::default(), &["a@example.com"]),
+            RoundOutcome::Skipped
+        );
+    }
+
+    #[test]
+    fn test_push_retry_delay_backs_off() {
+        let secs = |failures| push_retry_delay(failures).as_secs();
+        assert_eq!(secs(1), PUSH_RETRY_SECS);
+        assert_eq!(secs(2), PUSH_RETRY_SECS * 2);
+        assert_eq!(secs(3), PUSH_RETRY_SECS * 4);
+        assert_eq!(secs(10), PUSH_RETRY_MAX_SECS);
+        assert_eq!(secs(u32::MAX), PUSH_RETRY_MAX_SECS);
+    }
+
+    #[test]
+    fn test_sync_skips_accounts_without_provider() {
+        let results = run_round(&ProviderRegistry::default(), &["a@example.com"]);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_syncs_of_one_account_coalesce() {
+        let mut registry = ProviderRegistry::default();
+        registry.register(Arc::new(MockProvider {
+            offline_email: "",
+            unavailable_email: "",
+            delay: Duration::from_millis(50),
+        }));
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let runtime_state = RuntimeState::in_memory();
+        let in_flight = InFlight::default();
+        let results = Mutex::new(Vec::new());
+
+        let round = |emails: &[&str]| {
+            let accounts: Vec<_> = emails.iter().map(|email| mock_account(email)).collect();
+            let (registry, runtime_state, in_flight, results) =
+                (&registry, &runtime_state, &in_flight, &results);
+            async move {
+                let mut record = |event| {
+                    if let SyncEvent::Synced(email, _) = event {
+                        results.lock().unwrap().push(email);
+                    }
+                };
+                sync_accounts(
+                    accounts,
+                    registry,
+                    runtime_state,
+                    false,
+                    in_flight,
+                    &mut record,
+                )
+                .await
+            }
+        };
+
+        // 完整一轮与两次单账户同步重叠：每个账户只同步、上报一次
+        rt.block_on(async {
+            tokio::join!(
+                round(&["a@example.com", "b@example.com"]),
+                round(&["a@example.com"]),
+                round(&["a@example.com"]),
+            )
+        });
+        let mut synced = std::mem::take(&mut *results.lock().unwrap());
+        synced.sort();
+        assert_eq!(synced, vec!["a@example.com", "b@example.com"]);
+
+        // 同步结束后解除标记，可以再次同步
+        rt.block_on(round(&["a@example.com"]));
+        assert_eq!(*results.lock().unwrap(), vec!["a@example.com"]);
+    }
+
+    fn record(
+        unread: u32,
+        counted_since: Option<DateTime<Utc>>,
+        label: Option<&str>,
+    ) -> NotificationBaseline {
+        NotificationBaseline {
+            unread,
+            counted_since,
+            label: label.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_new_mail_delta_ignores_baseline_change() {
+        let added = Some(Utc::now());
+        let delta =
+            |previous: NotificationBaseline, current| new_mail_delta(Some(&previous), &current);
+
+        assert_eq!(new_mail_delta(None, &record(3, None, None)), 3);
+        assert_eq!(delta(record(3, None, None), record(5, None, None)), 2);
+        assert_eq!(delta(record(5, None, None), record(4, None, None)), 0);
+
+        // 切换到 since_added 或切回全部未读都不产生通知
+        assert_eq!(delta(record(2000, None, None), record(0, added, None)), 0);
+        assert_eq!(delta(record(0, added, None), record(2000, None, None)), 0);
+        assert_eq!(delta(record(0, added, None), record(1, added, None)), 1);
+
+        // 更换通知标签后只更新记录，之后按新标签的未读数检测
+        let urgent = Some("Label_7");
+        assert_eq!(delta(record(0, None, None), record(12, None, urgent)), 0);
+        assert_eq!(delta(record(12, None, urgent), record(13, None, urgent)), 1);
+    }
+
+    #[test]
+    fn test_snooze_deadline() {
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 22, 15, 0).unwrap();
+
+        assert_eq!(
+            snooze_deadline("30m", now),
+            Some((now + chrono::Duration::minutes(30)).with_timezone(&Utc))
+        );
+        assert_eq!(
+            snooze_deadline("1h", now),
+            Some((now + chrono::Duration::hours(1)).with_timezone(&Utc))
+        );
+
+        let tomorrow = snooze_deadline("tomorrow", now)
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!(
+            tomorrow.naive_local(),
+            chrono::NaiveDate::from_ymd_opt(2026, 3, 11)
+                .unwrap()
+                .and_hms_opt(8, 0, 0)
+                .unwrap()
+        );
+
+        assert_eq!(snooze_deadline("", now), None);
+    }
 
     #[test]
     fn test_sync_engine_creation() {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let engine = SyncEngine::new(rt.handle().clone());
+        let engine = SyncEngine::new(rt.handle().clone(), Arc::new(ProviderRegistry::default()));
 
         assert!(!*engine.running.blocking_read());
     }
 
+    #[test]
+    fn test_notify_resumed_enters_grace() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let engine = SyncEngine::new(rt.handle().clone(), Arc::new(ProviderRegistry::default()));
+
+        engine.notify_resumed();
+        assert!(engine.resume_grace.load(Ordering::SeqCst));
+        assert!(engine.suppress_errors.load(Ordering::SeqCst));
+        assert!(engine.status().paused);
+    }
+
+    #[test]
+    fn test_engine_status_records_events() {
+        let retry_at = Utc::now() + UNAVAILABLE_RETRY;
+        let mut status = EngineStatus::default();
+
+        status.record(&SyncEvent::Started("a@example.com".to_string()), None);
+        status.record(
+            &SyncEvent::Failed(
+                "b@example.com".to_string(),
+                SyncFailure::account_unavailable(),
+            ),
+            Some(retry_at),
+        );
+        status.record(&SyncEvent::FailedQuietly("a@example.com".to_string()), None);
+
+        // 同一账户只保留最新状态，停用的账户带上重试时间
+        assert_eq!(
+            status.per_account,
+            vec![
+                AccountEngineStatus {
+                    email: "a@example.com".to_string(),
+                    state: AccountEngineState::Failed,
+                    next_attempt_at: None,
+                },
+                AccountEngineStatus {
+                    email: "b@example.com".to_string(),
+                    state: AccountEngineState::Backoff,
+                    next_attempt_at: Some(retry_at),
+                },
+            ]
+        );
+
+        status.retain_accounts(&[mock_account("b@example.com")]);
+        assert_eq!(status.per_account.len(), 1);
+        assert_eq!(status.per_account[0].email, "b@example.com");
+    }
+
+    #[test]
+    fn test_set_interval_clamps_minimum() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let engine = SyncEngine::new(rt.handle().clone(), Arc::new(ProviderRegistry::default()));
+
+        engine.set_interval(60);
+        assert_eq!(engine.interval_secs.load(Ordering::SeqCst), 60);
+
+        engine.set_interval(1);
+        assert_eq!(
+            engine.interval_secs.load(Ordering::SeqCst),
+            MIN_SYNC_INTERVAL_SECS
+        );
+    }
+
     #[test]
     fn test_sync_interval() {
         assert_eq!(SYNC_INTERVAL_SECS, 10); // 10秒
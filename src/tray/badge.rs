@@ -0,0 +1,188 @@
+/// 托盘未读圆点的分档颜色
+///
+/// 有未读邮件时托盘图标右下角的圆点按未读合计分档变色（默认 1-5 绿色、6-20 琥珀色、21 以上红色），
+/// 分界与颜色来自 `config.toml` 的 `[tray]`（`thresholds` 最多两个且严格递增，`colors` 比分界多一个）。
+/// 配置无效时记录警告并使用默认值。图标只在所在档位变化时重新生成，同一档内未读数变化不重绘
+use std::sync::RwLock;
+
+use anyhow::{Result, bail};
+use image::Rgba;
+
+use crate::config::TrayConfig;
+use crate::utils::avatar::parse_accent;
+
+/// 最多几个分界（即最多三档）
+pub const MAX_THRESHOLDS: usize = 2;
+
+/// 默认分界
+pub const DEFAULT_THRESHOLDS: [u32; 2] = [5, 20];
+
+/// 默认各档颜色：绿色、琥珀色、红色
+pub const DEFAULT_COLORS: [&str; 3] = ["#34A853", "#F29900", "#EA4335"];
+
+/// 当前的分档设置（随配置更新）
+static BADGE: RwLock<Option<BadgeLevels>> = RwLock::new(None);
+
+/// 已校验的分档设置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadgeLevels {
+    thresholds: Vec<u32>,
+    colors: Vec<Rgba<u8>>,
+}
+
+impl Default for BadgeLevels {
+    fn default() -> Self {
+        Self {
+            thresholds: DEFAULT_THRESHOLDS.to_vec(),
+            colors: DEFAULT_COLORS.iter().filter_map(|hex| rgba(hex)).collect(),
+        }
+    }
+}
+
+impl BadgeLevels {
+    /// 校验 `[tray]` 配置
+    pub fn from_config(config: &TrayConfig) -> Result<Self> {
+        let thresholds = &config.thresholds;
+        check_thresholds(thresholds)?;
+        if config.colors.len() != thresholds.len() + 1 {
+            bail!(
+                "colors 须比 thresholds 多一个（应为 {} 个，当前 {} 个）",
+                thresholds.len() + 1,
+                config.colors.len()
+            );
+        }
+        let colors = config
+            .colors
+            .iter()
+            .map(|hex| {
+                rgba(hex).ok_or_else(|| anyhow::anyhow!("无效的颜色 {:?}（应为 #RRGGBB）", hex))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            thresholds: thresholds.clone(),
+            colors,
+        })
+    }
+
+    /// 未读合计所在的档位（0 起，超过几个分界即第几档）
+    pub fn bucket(&self, unread: u32) -> usize {
+        self.thresholds
+            .iter()
+            .filter(|&&limit| unread > limit)
+            .count()
+    }
+
+    /// 档位的颜色
+    pub fn color(&self, bucket: usize) -> Rgba<u8> {
+        self.colors[bucket.min(self.colors.len() - 1)]
+    }
+}
+
+/// 校验分界（个数不超过上限且严格递增）
+pub fn check_thresholds(thresholds: &[u32]) -> Result<()> {
+    if thresholds.len() > MAX_THRESHOLDS {
+        bail!(
+            "thresholds 最多 {} 个，当前 {} 个",
+            MAX_THRESHOLDS,
+            thresholds.len()
+        );
+    }
+    if thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+        bail!("thresholds 须严格递增: {:?}", thresholds);
+    }
+    Ok(())
+}
+
+/// `#RRGGBB` 转换为不透明颜色
+fn rgba(hex: &str) -> Option<Rgba<u8>> {
+    let hex = parse_accent(hex)?;
+    let rgb = u32::from_str_radix(&hex[1..], 16).ok()?;
+    let [_, r, g, b] = rgb.to_be_bytes();
+    Some(Rgba([r, g, b, 0xFF]))
+}
+
+/// 应用 `[tray]` 配置（无效时记录警告并使用默认值）
+pub fn configure(config: &TrayConfig) {
+    let levels = BadgeLevels::from_config(config).unwrap_or_else(|e| {
+        tracing::warn!("[tray] 未读分档配置无效，使用默认值: {:#}", e);
+        BadgeLevels::default()
+    });
+    *BADGE.write().unwrap_or_else(|e| e.into_inner()) = Some(levels);
+}
+
+/// 当前的分档设置
+pub fn levels() -> BadgeLevels {
+    BADGE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml_text: &str) -> TrayConfig {
+        toml::from_str(toml_text).unwrap()
+    }
+
+    #[test]
+    fn test_bucket_boundaries() {
+        let levels = BadgeLevels::default();
+        assert_eq!(levels.bucket(1), 0);
+        assert_eq!(levels.bucket(5), 0);
+        assert_eq!(levels.bucket(6), 1);
+        assert_eq!(levels.bucket(20), 1);
+        assert_eq!(levels.bucket(21), 2);
+        assert_eq!(levels.bucket(u32::MAX), 2);
+        assert_eq!(levels.color(0), Rgba([0x34, 0xA8, 0x53, 0xFF]));
+        assert_eq!(levels.color(2), Rgba([0xEA, 0x43, 0x35, 0xFF]));
+    }
+
+    #[test]
+    fn test_defaults_match_config_defaults() {
+        let levels = BadgeLevels::from_config(&TrayConfig::default()).unwrap();
+        assert_eq!(levels, BadgeLevels::default());
+        assert_eq!(levels.colors.len(), DEFAULT_COLORS.len());
+    }
+
+    #[test]
+    fn test_parse_custom_levels() {
+        let levels = BadgeLevels::from_config(&config(
+            "thresholds = [10]\ncolors = [\"#00ff00\", \"#FF0000\"]",
+        ))
+        .unwrap();
+        assert_eq!(levels.bucket(10), 0);
+        assert_eq!(levels.bucket(11), 1);
+        assert_eq!(levels.color(1), Rgba([0xFF, 0, 0, 0xFF]));
+
+        // 没有分界时只有一种颜色
+        let single =
+            BadgeLevels::from_config(&config("thresholds = []\ncolors = [\"#123456\"]")).unwrap();
+        assert_eq!(single.bucket(1000), 0);
+
+        // 缺少的字段取默认值
+        assert_eq!(
+            BadgeLevels::from_config(&config("")).unwrap(),
+            BadgeLevels::default()
+        );
+    }
+
+    #[test]
+    fn test_invalid_levels_are_rejected() {
+        for invalid in [
+            "thresholds = [5, 10, 20]\ncolors = [\"#000000\", \"#000000\", \"#000000\", \"#000000\"]",
+            "thresholds = [20, 5]",
+            "thresholds = [5, 5]",
+            "thresholds = [5, 20]\ncolors = [\"#000000\", \"#000000\"]",
+            "colors = [\"#000000\", \"red\", \"#000000\"]",
+        ] {
+            assert!(
+                BadgeLevels::from_config(&config(invalid)).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+}
@@ -3,24 +3,94 @@
 use std::sync::mpsc;
 use tray_icon::{TrayIconEvent, menu::MenuEvent};
 
+use crate::ui::layout::Point;
+
+/// 托盘 → 主线程命令通道的容量（事件循环卡住时最多积压的命令数）
+pub const COMMAND_CAPACITY: usize = 16;
+
+/// 托盘命令的发送端（有界通道，见 [`send_command`]）
+pub type CommandSender = mpsc::SyncSender<TrayCommand>;
+
 /// 托盘 → Slint 窗口的命令
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TrayCommand {
     ToggleWindow,
     ShowWindow,
     HideWindow,
+    SyncNow,
     OpenGmail,
+    CopySummary,
+    RunDiagnostics,
+    OpenDataFolder,
+    ViewLogs,
+    /// 显示窗口并打开同步记录
+    ShowSyncHistory,
     ShowAbout,
+    /// 账户子菜单：打开该账户的收件箱
+    OpenInbox(String),
+    /// 账户子菜单：立即同步该账户
+    SyncAccount(String),
+    /// 账户子菜单：免打扰（时长见 `sync::snooze_deadline`，为空表示恢复提醒）
+    SnoozeAccount {
+        email: String,
+        duration: String,
+    },
     Exit,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum TrayIconState {
-    Normal,
+impl TrayCommand {
+    /// 命令名称（日志使用，不含邮箱）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ToggleWindow => "ToggleWindow",
+            Self::ShowWindow => "ShowWindow",
+            Self::HideWindow => "HideWindow",
+            Self::SyncNow => "SyncNow",
+            Self::OpenGmail => "OpenGmail",
+            Self::CopySummary => "CopySummary",
+            Self::RunDiagnostics => "RunDiagnostics",
+            Self::OpenDataFolder => "OpenDataFolder",
+            Self::ViewLogs => "ViewLogs",
+            Self::ShowSyncHistory => "ShowSyncHistory",
+            Self::ShowAbout => "ShowAbout",
+            Self::OpenInbox(_) => "OpenInbox",
+            Self::SyncAccount(_) => "SyncAccount",
+            Self::SnoozeAccount { .. } => "SnoozeAccount",
+            Self::Exit => "Exit",
+        }
+    }
+}
+
+/// 创建托盘命令通道
+pub fn command_channel() -> (CommandSender, mpsc::Receiver<TrayCommand>) {
+    mpsc::sync_channel(COMMAND_CAPACITY)
+}
+
+/// 发送托盘命令
+///
+/// 通道已满说明事件循环卡住、命令正在积压：界面类命令可以重复点击，丢弃新命令并记录，
+/// 不阻塞托盘线程；`Exit` 不能丢，阻塞等待事件循环腾出位置
+pub fn send_command(tx: &CommandSender, cmd: TrayCommand) {
+    if matches!(cmd, TrayCommand::Exit) {
+        if tx.send(cmd).is_err() {
+            tracing::error!("托盘命令通道已关闭，无法发送 Exit");
+        }
+        return;
+    }
+
+    match tx.try_send(cmd) {
+        Ok(()) => {}
+        Err(mpsc::TrySendError::Full(cmd)) => {
+            tracing::warn!("托盘命令积压，丢弃 {}", cmd.name());
+        }
+        Err(mpsc::TrySendError::Disconnected(cmd)) => {
+            tracing::error!("托盘命令通道已关闭，无法发送 {}", cmd.name());
+        }
+    }
 }
 
 /// 运行托盘事件循环
-pub fn run_event_loop(menu_ids: super::menu::MenuIds, tx: mpsc::Sender<TrayCommand>) {
+pub fn run_event_loop(menu_ids: super::menu::MenuIds, tx: CommandSender) {
     let menu_channel = tray_icon::menu::MenuEvent::receiver();
     let tray_channel = tray_icon::TrayIconEvent::receiver();
 
@@ -28,12 +98,21 @@ pub fn run_event_loop(menu_ids: super::menu::MenuIds, tx: mpsc::Sender<TrayComma
         // 检查菜单事件
         if let Ok(event) = menu_channel.try_recv() {
             tracing::debug!("托盘菜单事件: {:?}", event);
+            super::set_menu_open(false);
             handle_menu_event(event, &menu_ids, &tx);
         }
 
         // 检查托盘图标事件
         if let Ok(event) = tray_channel.try_recv() {
             tracing::debug!("托盘图标事件: {:?}", event);
+            // 右键点击后弹出菜单；菜单显示期间图标收不到鼠标事件，再次收到时菜单已关闭
+            super::set_menu_open(matches!(
+                event,
+                TrayIconEvent::Click {
+                    button: tray_icon::MouseButton::Right,
+                    ..
+                }
+            ));
             handle_tray_event(event, &tx);
         }
 
@@ -42,46 +121,90 @@ pub fn run_event_loop(menu_ids: super::menu::MenuIds, tx: mpsc::Sender<TrayComma
     }
 }
 
-fn handle_menu_event(
-    event: MenuEvent,
-    menu_ids: &super::menu::MenuIds,
-    tx: &mpsc::Sender<TrayCommand>,
-) {
+fn handle_menu_event(event: MenuEvent, menu_ids: &super::menu::MenuIds, tx: &CommandSender) {
     // 记录完整的事件信息
     tracing::info!("收到托盘菜单事件: {:?}", event);
 
     let menu_id = event.id;
 
     // 直接比较菜单 ID，不再依赖字符串匹配
+    #[cfg(target_os = "linux")]
+    if menu_id == menu_ids.show_window {
+        tracing::info!("菜单事件: 显示窗口");
+        send_command(tx, TrayCommand::ShowWindow);
+        return;
+    }
+
     if menu_id == menu_ids.open_gmail {
         tracing::info!("菜单事件: 打开 Gmail");
-        if let Err(e) = tx.send(TrayCommand::OpenGmail) {
-            tracing::error!("发送 OpenGmail 命令失败: {:?}", e);
-        }
+        send_command(tx, TrayCommand::OpenGmail);
+    } else if menu_id == menu_ids.copy_summary {
+        tracing::info!("菜单事件: 复制未读摘要");
+        send_command(tx, TrayCommand::CopySummary);
+    } else if menu_id == menu_ids.diagnostics {
+        tracing::info!("菜单事件: 运行诊断");
+        send_command(tx, TrayCommand::RunDiagnostics);
+    } else if menu_id == menu_ids.open_data_folder {
+        tracing::info!("菜单事件: 打开数据文件夹");
+        send_command(tx, TrayCommand::OpenDataFolder);
+    } else if menu_id == menu_ids.view_logs {
+        tracing::info!("菜单事件: 查看日志");
+        send_command(tx, TrayCommand::ViewLogs);
+    } else if menu_id == menu_ids.sync_history {
+        tracing::info!("菜单事件: 同步记录");
+        send_command(tx, TrayCommand::ShowSyncHistory);
     } else if menu_id == menu_ids.about {
         tracing::info!("菜单事件: 关于");
-        if let Err(e) = tx.send(TrayCommand::ShowAbout) {
-            tracing::error!("发送 ShowAbout 命令失败: {:?}", e);
-        }
+        send_command(tx, TrayCommand::ShowAbout);
     } else if menu_id == menu_ids.quit {
         tracing::info!("菜单事件: 退出");
-        if let Err(e) = tx.send(TrayCommand::Exit) {
-            tracing::error!("发送 Exit 命令失败: {:?}", e);
-        }
+        send_command(tx, TrayCommand::Exit);
+    } else if let Some(command) = super::menu::account_command(&menu_id) {
+        tracing::info!("菜单事件: 账户操作 {:?}", menu_id.0.split(':').nth(1));
+        send_command(tx, command);
     } else {
         tracing::warn!("未识别的菜单 ID: {:?}", menu_id);
     }
 }
 
-fn handle_tray_event(event: TrayIconEvent, tx: &mpsc::Sender<TrayCommand>) {
+fn handle_tray_event(event: TrayIconEvent, tx: &CommandSender) {
     tracing::debug!("handle_tray_event: {:?}", event);
     if let TrayIconEvent::Click {
         button: tray_icon::MouseButton::Left,
         button_state: tray_icon::MouseButtonState::Up,  // 只在释放时触发，避免按下+释放双重触发
+        position,
         ..
     } = event
     {
         tracing::debug!("托盘左键点击 -> ToggleWindow");
-        tx.send(TrayCommand::ToggleWindow).ok();
+        // 窗口贴近被点击的图标显示（任务栏可能在任意一边）
+        super::set_click_anchor(Point {
+            x: position.x as i32,
+            y: position.y as i32,
+        });
+        send_command(tx, TrayCommand::ToggleWindow);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_is_not_dropped_when_channel_is_full() {
+        let (tx, rx) = command_channel();
+        for _ in 0..COMMAND_CAPACITY {
+            send_command(&tx, TrayCommand::ShowAbout);
+        }
+        // 界面类命令在通道满时被丢弃
+        send_command(&tx, TrayCommand::ShowAbout);
+
+        let consumer = std::thread::spawn(move || rx.iter().collect::<Vec<_>>());
+        send_command(&tx, TrayCommand::Exit);
+        drop(tx);
+
+        let received = consumer.join().unwrap();
+        assert_eq!(received.len(), COMMAND_CAPACITY + 1);
+        assert!(matches!(received.last(), Some(TrayCommand::Exit)));
     }
 }
@@ -0,0 +1,106 @@
+/// 资源管理器重启监听（仅 Windows）
+///
+/// explorer.exe 崩溃或重启后任务栏重新创建，之前添加的托盘图标全部消失；新任务栏向所有顶层窗口
+/// 广播 "TaskbarCreated" 消息。tray-icon 收到该消息时只会尝试重新注册原有图标，任务栏尚未就绪时
+/// 会静默失败，因此在隐藏的顶层窗口中监听该消息，由 UI 线程按当前状态重新创建整个托盘图标
+use std::ffi::c_void;
+use std::sync::OnceLock;
+
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, MSG, RegisterClassW,
+    RegisterWindowMessageW, WNDCLASSW,
+};
+
+/// "TaskbarCreated" 的消息 ID（注册成功后设置）
+static TASKBAR_CREATED: OnceLock<u32> = OnceLock::new();
+
+/// 监听窗口的窗口过程：收到 "TaskbarCreated" 时转交 UI 线程重建托盘图标
+unsafe extern "system" fn window_proc(
+    hwnd: *mut c_void,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+) -> isize {
+    if TASKBAR_CREATED.get() == Some(&message) {
+        tracing::info!("任务栏已重新创建（资源管理器重启），重建托盘图标");
+        if let Err(e) = slint::invoke_from_event_loop(super::reinstall) {
+            tracing::error!("转交 UI 线程重建托盘图标失败: {:?}", e);
+        }
+    }
+    // SAFETY: 原样转交默认窗口过程
+    unsafe { DefWindowProcW(hwnd, message, wparam, lparam) }
+}
+
+/// 在独立线程中创建隐藏的顶层窗口并运行消息循环
+///
+/// "TaskbarCreated" 是广播消息，仅消息窗口（HWND_MESSAGE）收不到，因此使用从不显示的普通顶层窗口
+pub fn start_listener() {
+    let spawned = std::thread::Builder::new()
+        .name("taskbar-listener".to_string())
+        .spawn(|| {
+            let message_name: Vec<u16> = "TaskbarCreated\0".encode_utf16().collect();
+            // SAFETY: 消息名为以 NUL 结尾的 UTF-16 字符串
+            let message = unsafe { RegisterWindowMessageW(message_name.as_ptr()) };
+            if message == 0 {
+                tracing::warn!(
+                    "注册 TaskbarCreated 消息失败，资源管理器重启后需重启程序才能恢复托盘图标"
+                );
+                return;
+            }
+            TASKBAR_CREATED.set(message).ok();
+
+            let class_name: Vec<u16> = "NanoMailTaskbarListener\0".encode_utf16().collect();
+
+            // SAFETY: 传入空指针取得当前进程的模块句柄
+            let instance = unsafe { GetModuleHandleW(std::ptr::null()) };
+            // SAFETY: WNDCLASSW 为纯数据结构，全零是合法的初始值
+            let mut class: WNDCLASSW = unsafe { std::mem::zeroed() };
+            class.lpfnWndProc = Some(window_proc);
+            class.hInstance = instance;
+            class.lpszClassName = class_name.as_ptr();
+
+            // SAFETY: class 中的类名在整个线程生命周期内有效
+            if unsafe { RegisterClassW(&class) } == 0 {
+                tracing::warn!(
+                    "注册任务栏监听窗口类失败，资源管理器重启后需重启程序才能恢复托盘图标"
+                );
+                return;
+            }
+
+            // SAFETY: 类已注册；窗口从不显示，只用于接收广播消息
+            let hwnd = unsafe {
+                CreateWindowExW(
+                    0,
+                    class_name.as_ptr(),
+                    class_name.as_ptr(),
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    instance,
+                    std::ptr::null(),
+                )
+            };
+            if hwnd.is_null() {
+                tracing::warn!(
+                    "创建任务栏监听窗口失败，资源管理器重启后需重启程序才能恢复托盘图标"
+                );
+                return;
+            }
+
+            // SAFETY: MSG 为纯数据结构，全零是合法的初始值
+            let mut message: MSG = unsafe { std::mem::zeroed() };
+            // SAFETY: message 为有效输出指针；GetMessageW 出错时返回 -1，收到 WM_QUIT 时返回 0
+            while unsafe { GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) } > 0 {
+                // SAFETY: message 由 GetMessageW 填充
+                unsafe { DispatchMessageW(&message) };
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::error!("启动任务栏监听线程失败: {}", e);
+    }
+}
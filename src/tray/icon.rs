@@ -1,29 +1,66 @@
 // 托盘图标资源加载模块
 
 use anyhow::Result;
-use image::GenericImageView;
+use image::{GenericImageView, Rgba, RgbaImage};
 use tray_icon::Icon;
 
-use super::events::TrayIconState; // 保留以兼容现有接口
+use super::summary::AppStatus;
 
 /// 编译时嵌入托盘图标文件（避免运行时依赖外部文件）
 const ICON_BYTES: &[u8] = include_bytes!("../../assets/icons/NanoMail.ico");
 
-/// 加载托盘图标(忽略状态,始终使用 NanoMail.ico)
-pub fn load_icon(_state: TrayIconState) -> Result<Icon> {
-    load_icon_from_memory(ICON_BYTES)
+/// 加载托盘图标（非正常状态在右下角叠加与标题栏 N 相同颜色的圆点）
+///
+/// `unread_color` 为未读状态的圆点颜色（按未读合计分档，见 `badge`），其他状态忽略
+pub fn load_icon(status: AppStatus, unread_color: Option<Rgba<u8>>) -> Result<Icon> {
+    let dot = match (status, unread_color) {
+        (AppStatus::Unread, Some(color)) => Some(color),
+        _ => status_color(status),
+    };
+    load_icon_from_memory(ICON_BYTES, dot)
+}
+
+/// 状态圆点颜色（与 Theme 中的 status-* 颜色一致，正常状态不显示）
+fn status_color(status: AppStatus) -> Option<Rgba<u8>> {
+    match status {
+        AppStatus::Normal => None,
+        AppStatus::Unread => Some(Rgba([0xEA, 0x43, 0x35, 0xFF])),
+        AppStatus::Attention => Some(Rgba([0xAF, 0x52, 0xDE, 0xFF])),
+        AppStatus::Degraded => Some(Rgba([0xF2, 0x99, 0x00, 0xFF])),
+        AppStatus::Error => Some(Rgba([0xFB, 0xBC, 0x05, 0xFF])),
+        AppStatus::Paused => Some(Rgba([0x8E, 0x8E, 0x93, 0xFF])),
+    }
+}
+
+/// 在图标右下角绘制状态圆点
+fn draw_status_dot(image: &mut RgbaImage, color: Rgba<u8>) {
+    let (width, height) = image.dimensions();
+    let radius = width.min(height) as f32 * 0.22;
+    let center_x = width as f32 - radius - 1.0;
+    let center_y = height as f32 - radius - 1.0;
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - center_x;
+        let dy = y as f32 + 0.5 - center_y;
+        if dx * dx + dy * dy <= radius * radius {
+            *pixel = color;
+        }
+    }
 }
 
 /// 从内存加载图标（支持 PNG/ICO 等格式）
-fn load_icon_from_memory(img_bytes: &[u8]) -> Result<Icon> {
+fn load_icon_from_memory(img_bytes: &[u8], dot: Option<Rgba<u8>>) -> Result<Icon> {
     tracing::debug!("从嵌入资源加载托盘图标（{} bytes）", img_bytes.len());
 
     // 使用 image crate 从内存解码（自动检测格式）
     let img = image::load_from_memory(img_bytes)
         .map_err(|e| anyhow::anyhow!("图标解码失败: {}", e))?;
 
-    let rgba = img.to_rgba8();
+    let mut rgba = img.to_rgba8();
     let (width, height) = img.dimensions();
+    if let Some(color) = dot {
+        draw_status_dot(&mut rgba, color);
+    }
 
     let icon = Icon::from_rgba(rgba.into_raw(), width, height)
         .map_err(|e| anyhow::anyhow!("图标创建失败: {:?}", e))?;
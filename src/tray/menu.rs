@@ -1,35 +1,260 @@
 // 托盘右键菜单模块
 
 use anyhow::Result;
-use tray_icon::menu::{Menu, MenuId, MenuItem, PredefinedMenuItem};
+use tray_icon::menu::{Menu, MenuId, MenuItem, PredefinedMenuItem, Submenu};
+
+use super::TrayCommand;
+use super::summary::{AccountHealth, AccountStatus};
+use crate::utils::i18n::{tr, tr_fmt};
+
+/// 菜单项 ID 固定不变，切换语言重建菜单后事件循环无需更新
+const OPEN_GMAIL_ID: &str = "open_gmail";
+const COPY_SUMMARY_ID: &str = "copy_summary";
+const DIAGNOSTICS_ID: &str = "diagnostics";
+const OPEN_DATA_FOLDER_ID: &str = "open_data_folder";
+const VIEW_LOGS_ID: &str = "view_logs";
+const SYNC_HISTORY_ID: &str = "sync_history";
+const ABOUT_ID: &str = "about";
+const QUIT_ID: &str = "quit";
+#[cfg(target_os = "linux")]
+const SHOW_WINDOW_ID: &str = "show_window";
+
+/// 账户子菜单项的 ID 为 `account:<操作>:<邮箱>`，同样不随重建变化
+const ACCOUNT_ID_PREFIX: &str = "account:";
+
+/// 账户子菜单中的免打扰时长（见 `sync::snooze_deadline`）与菜单文字
+const SNOOZE_CHOICES: &[(&str, &str)] = &[
+    ("30m", "menu.snooze_30m"),
+    ("1h", "menu.snooze_1h"),
+    ("tomorrow", "menu.snooze_tomorrow"),
+];
+
+/// 账户子菜单显示的内容（与上次构建时相同则无需重建菜单）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuAccount {
+    pub email: String,
+
+    /// 子菜单标题（显示名称，没有时为邮箱地址）
+    pub title: String,
+
+    /// 不可点击的标题行：状态图标与未读数
+    pub header: String,
+
+    pub snoozed: bool,
+}
+
+impl MenuAccount {
+    pub fn of(status: &AccountStatus) -> Self {
+        Self {
+            email: status.email.clone(),
+            title: status.summary_name().to_string(),
+            header: header_label(status),
+            snoozed: status.snoozed,
+        }
+    }
+}
+
+/// 标题行：✓ 已同步 / ⏳ 尚未同步 / ⚠ 同步出错，尚未同步时未读数显示"—"
+fn header_label(status: &AccountStatus) -> String {
+    let icon = if status.health() != AccountHealth::Ok {
+        "⚠"
+    } else if status.synced {
+        "✓"
+    } else {
+        "⏳"
+    };
+    let count = if status.synced {
+        status.unread_count.to_string()
+    } else {
+        "—".to_string()
+    };
+    format!("{} {}", icon, tr_fmt("tray.account_unread", &[&count]))
+}
+
+/// 账户子菜单项对应的托盘命令（不是账户子菜单项时返回 `None`）
+pub fn account_command(id: &MenuId) -> Option<TrayCommand> {
+    let (action, email) = id.0.strip_prefix(ACCOUNT_ID_PREFIX)?.split_once(':')?;
+    let email = email.to_string();
+    match action {
+        "open" => Some(TrayCommand::OpenInbox(email)),
+        "sync" => Some(TrayCommand::SyncAccount(email)),
+        "unsnooze" => Some(TrayCommand::SnoozeAccount {
+            email,
+            duration: String::new(),
+        }),
+        _ => {
+            let duration = action.strip_prefix("snooze-")?;
+            Some(TrayCommand::SnoozeAccount {
+                email,
+                duration: duration.to_string(),
+            })
+        }
+    }
+}
+
+/// 单个账户的子菜单：标题行、打开收件箱、立即同步与免打扰（免打扰中时为恢复提醒）
+fn account_submenu(account: &MenuAccount) -> Result<Submenu> {
+    let id = |action: &str| MenuId::new(format!("{ACCOUNT_ID_PREFIX}{action}:{}", account.email));
+
+    let header = MenuItem::new(&account.header, false, None);
+    let open = MenuItem::with_id(id("open"), tr("menu.open_inbox"), true, None);
+    let sync = MenuItem::with_id(id("sync"), tr("menu.sync_now"), true, None);
+    let submenu = Submenu::with_items(
+        &account.title,
+        true,
+        &[&header, &PredefinedMenuItem::separator(), &open, &sync],
+    )?;
+
+    submenu.append(&PredefinedMenuItem::separator())?;
+    if account.snoozed {
+        submenu.append(&MenuItem::with_id(
+            id("unsnooze"),
+            tr("menu.unsnooze"),
+            true,
+            None,
+        ))?;
+    } else {
+        for (choice, label) in SNOOZE_CHOICES {
+            submenu.append(&MenuItem::with_id(
+                id(&format!("snooze-{choice}")),
+                tr(label),
+                true,
+                None,
+            ))?;
+        }
+    }
+    Ok(submenu)
+}
 
 pub struct MenuIds {
+    /// Linux 托盘不产生点击事件，通过菜单项打开窗口
+    #[cfg(target_os = "linux")]
+    pub show_window: MenuId,
     pub open_gmail: MenuId,
+    pub copy_summary: MenuId,
+    pub diagnostics: MenuId,
+    pub open_data_folder: MenuId,
+    pub view_logs: MenuId,
+    pub sync_history: MenuId,
     pub about: MenuId,
     pub quit: MenuId,
 }
 
-pub fn create_menu_with_ids() -> Result<(Menu, MenuIds)> {
+/// 创建托盘菜单：各账户的子菜单在最上方（没有账户时省略）
+pub fn create_menu_with_ids(accounts: &[MenuAccount]) -> Result<(Menu, MenuIds)> {
     let menu = Menu::new();
 
-    let open_gmail = MenuItem::new("打开 Gmail", true, None);
-    let about = MenuItem::new("关于 NanoMail", true, None);
+    let open_gmail = MenuItem::with_id(OPEN_GMAIL_ID, tr("tray.open_gmail"), true, None);
+    let copy_summary = MenuItem::with_id(COPY_SUMMARY_ID, tr("tray.copy_summary"), true, None);
+    let diagnostics = MenuItem::with_id(DIAGNOSTICS_ID, tr("tray.diagnostics"), true, None);
+    // 故障排查：数据文件夹与日志（窗口隐藏时也能打开）
+    let open_data_folder =
+        MenuItem::with_id(OPEN_DATA_FOLDER_ID, tr("tray.open_data_folder"), true, None);
+    let view_logs = MenuItem::with_id(VIEW_LOGS_ID, tr("tray.view_logs"), true, None);
+    let sync_history = MenuItem::with_id(SYNC_HISTORY_ID, tr("tray.sync_history"), true, None);
+    let troubleshooting = Submenu::with_items(
+        tr("tray.troubleshooting"),
+        true,
+        &[&open_data_folder, &view_logs, &sync_history],
+    )?;
+    let about = MenuItem::with_id(ABOUT_ID, tr("tray.about"), true, None);
     // 在托盘菜单中显示为“推出”——此项将真正结束程序
-    let quit = MenuItem::new("退出", true, None);
+    let quit = MenuItem::with_id(QUIT_ID, tr("tray.quit"), true, None);
+
+    #[cfg(target_os = "linux")]
+    let show_window = {
+        let item = MenuItem::with_id(SHOW_WINDOW_ID, tr("tray.show_window"), true, None);
+        menu.append_items(&[&item, &PredefinedMenuItem::separator()])?;
+        item
+    };
+
+    for account in accounts {
+        menu.append(&account_submenu(account)?)?;
+    }
+    if !accounts.is_empty() {
+        menu.append(&PredefinedMenuItem::separator())?;
+    }
 
     menu.append_items(&[
         &open_gmail,
+        &copy_summary,
         &PredefinedMenuItem::separator(),
+        &diagnostics,
+        &troubleshooting,
         &about,
         &PredefinedMenuItem::separator(),
         &quit,
     ])?;
 
     let ids = MenuIds {
+        #[cfg(target_os = "linux")]
+        show_window: show_window.id().clone(),
         open_gmail: open_gmail.id().clone(),
+        copy_summary: copy_summary.id().clone(),
+        diagnostics: diagnostics.id().clone(),
+        open_data_folder: open_data_folder.id().clone(),
+        view_logs: view_logs.id().clone(),
+        sync_history: sync_history.id().clone(),
         about: about.id().clone(),
         quit: quit.id().clone(),
     };
 
     Ok((menu, ids))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_command_from_id() {
+        let command = |id: &str| account_command(&MenuId::new(id));
+
+        assert!(matches!(
+            command("account:open:me@example.com"),
+            Some(TrayCommand::OpenInbox(email)) if email == "me@example.com"
+        ));
+        assert!(matches!(
+            command("account:sync:me@example.com"),
+            Some(TrayCommand::SyncAccount(email)) if email == "me@example.com"
+        ));
+        assert!(matches!(
+            command("account:snooze-1h:me@example.com"),
+            Some(TrayCommand::SnoozeAccount { email, duration })
+                if email == "me@example.com" && duration == "1h"
+        ));
+        assert!(matches!(
+            command("account:unsnooze:me@example.com"),
+            Some(TrayCommand::SnoozeAccount { duration, .. }) if duration.is_empty()
+        ));
+        assert!(command(QUIT_ID).is_none());
+        assert!(command("account:unknown:me@example.com").is_none());
+    }
+
+    #[test]
+    fn test_header_label_shows_state_icon() {
+        let mut status = AccountStatus {
+            email: "me@example.com".to_string(),
+            display_name: String::new(),
+            unread_count: 3,
+            synced: false,
+            failures: 0,
+            fatal: false,
+            last_error: None,
+            partial: false,
+            snoozed: false,
+            active: true,
+            spam_unread: 0,
+            alert_threshold: None,
+        };
+        assert!(header_label(&status).starts_with("⏳"));
+        assert!(header_label(&status).contains('—'));
+
+        status.synced = true;
+        assert!(header_label(&status).starts_with("✓"));
+        assert!(header_label(&status).contains('3'));
+
+        status.failures = 1;
+        assert!(header_label(&status).starts_with("⚠"));
+    }
+}
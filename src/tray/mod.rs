@@ -1,41 +1,409 @@
 // 系统托盘模块
+//
+// Windows 上托盘图标在 UI 线程创建和更新；Linux 上（libappindicator）托盘图标必须运行在
+// GTK 主循环所在的线程，因此单独启动一个 GTK 线程，更新操作通过主上下文转交过去
 
 use anyhow::Result;
 use screen_size::get_primary_screen_size;
 use slint::ComponentHandle;
-use std::sync::mpsc;
-use tray_icon::{TrayIcon, TrayIconBuilder};
+use std::cell::{Cell, RefCell};
+use std::panic::AssertUnwindSafe;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 
+pub mod badge;
 mod events;
+#[cfg(windows)]
+mod explorer;
 mod icon;
 mod menu;
+pub mod summary;
+#[cfg(target_os = "linux")]
+mod workarea;
 
-pub use events::{TrayCommand, TrayIconState};
+pub use events::{CommandSender, TrayCommand, command_channel, send_command};
+
+use crate::config::TrayConfig;
+use crate::ui::layout::{self, AnchorPoint, Point, Rect, Size};
+use crate::utils::clipboard;
+use crate::utils::i18n::{tr, tr_fmt};
+use badge::BadgeLevels;
+use summary::{AccountStatus, AppStatus};
+
+/// 临时提示文字（如"已复制"）的显示时长
+const TOOLTIP_FLASH_DURATION: Duration = Duration::from_secs(3);
+
+/// 重建托盘菜单的去抖间隔（同步期间账户状态变化频繁，Windows 上重建菜单要重新创建全部菜单句柄）
+const MENU_REBUILD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+thread_local! {
+    /// 托盘图标句柄（只在创建托盘的线程访问：Windows 为 UI 线程，Linux 为 GTK 线程）
+    static TRAY_ICON: RefCell<Option<TrayIcon>> = const { RefCell::new(None) };
+
+    /// 临时提示文字到期后恢复摘要的定时器
+    static TOOLTIP_TIMER: slint::Timer = slint::Timer::default();
+
+    /// 托盘图标当前显示的状态与未读档位（变化时才重新生成图标；`None` 表示须重新生成）
+    static ICON_KEY: Cell<Option<IconKey>> = const { Cell::new(Some((AppStatus::Normal, None))) };
+
+    /// 托盘菜单中各账户子菜单的内容（内容变化时才重建菜单）
+    static MENU_ACCOUNTS: RefCell<Vec<menu::MenuAccount>> = const { RefCell::new(Vec::new()) };
+
+    /// 合并短时间内多次菜单重建的定时器
+    static MENU_TIMER: slint::Timer = slint::Timer::default();
+}
+
+/// 决定托盘图标外观的状态与未读档位（只有未读状态有档位）
+type IconKey = (AppStatus, Option<usize>);
+
+/// 托盘菜单可能正在显示（右键点击图标后，直到选择菜单项或图标再次收到鼠标事件）
+static MENU_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// 记录托盘菜单是否正在显示（托盘事件线程调用）
+fn set_menu_open(open: bool) {
+    MENU_OPEN.store(open, Ordering::SeqCst);
+}
+
+/// 托盘守卫（drop 时移除托盘图标）
+pub struct TrayGuard;
+
+impl Drop for TrayGuard {
+    #[cfg(windows)]
+    fn drop(&mut self) {
+        TRAY_ICON.with(|tray| tray.borrow_mut().take());
+    }
+
+    #[cfg(target_os = "linux")]
+    fn drop(&mut self) {
+        gtk::glib::MainContext::default().invoke(|| {
+            TRAY_ICON.with(|tray| tray.borrow_mut().take());
+            gtk::main_quit();
+        });
+    }
+}
+
+/// 托盘是否已降级（托盘相关线程发生过 panic）
+static DEGRADED: AtomicBool = AtomicBool::new(false);
+
+/// 将托盘标记为降级状态
+pub fn mark_degraded(reason: &str) {
+    DEGRADED.store(true, Ordering::SeqCst);
+    tracing::error!("⚠️ 托盘已降级: {}", reason);
+}
+
+/// 托盘是否处于降级状态
+pub fn is_degraded() -> bool {
+    DEGRADED.load(Ordering::SeqCst)
+}
 
 /// 创建系统托盘图标
-pub fn create_tray_icon(tx: mpsc::Sender<TrayCommand>) -> Result<TrayIcon> {
+pub fn create_tray_icon(tx: CommandSender) -> Result<TrayGuard> {
     // 1. 加载图标
-    let icon = icon::load_icon(TrayIconState::Normal)?;
+    let icon = icon::load_icon(AppStatus::Normal, None)?;
+
+    // 2. 创建菜单并构建托盘图标
+    #[cfg(windows)]
+    let menu_ids = install_tray(icon)?;
+    #[cfg(target_os = "linux")]
+    let menu_ids = spawn_gtk_tray(icon)?;
+
+    tracing::info!("系统托盘图标已创建");
+
+    // 资源管理器重启后重新创建托盘图标（Linux 上由 libappindicator 在面板重启后重新注册）
+    #[cfg(windows)]
+    explorer::start_listener();
+
+    // 3. 启动事件循环（panic 时标记降级并请求优雅退出，避免留下无法操作的僵尸进程）
+    std::thread::Builder::new()
+        .name("tray-events".to_string())
+        .spawn(move || {
+            tracing::debug!("托盘事件循环已启动");
+            let loop_tx = tx.clone();
+            let result = std::panic::catch_unwind(AssertUnwindSafe(move || {
+                events::run_event_loop(menu_ids, loop_tx);
+            }));
+
+            if result.is_err() {
+                mark_degraded("托盘事件循环 panic");
+                // 退出命令不能丢弃：该线程已不再处理事件，通道满时等待即可
+                tx.send(TrayCommand::Exit).ok();
+            }
+        })?;
+
+    Ok(TrayGuard)
+}
 
-    // 2. 创建菜单
-    let (menu, menu_ids) = menu::create_menu_with_ids()?;
+/// 在当前线程创建托盘图标（菜单同样只能在该线程使用）
+///
+/// 此时还没有账户状态，账户子菜单在首次刷新状态时加入
+fn install_tray(icon: Icon) -> Result<menu::MenuIds> {
+    let (menu, menu_ids) = menu::create_menu_with_ids(&[])?;
 
-    // 3. 构建托盘图标
     let tray = TrayIconBuilder::new()
         .with_menu(Box::new(menu))
-        .with_tooltip("NanoMail - Gmail 通知客户端")
+        .with_tooltip(tooltip_text())
         .with_icon(icon)
         .build()?;
 
-    tracing::info!("系统托盘图标已创建");
+    TRAY_ICON.with(|slot| *slot.borrow_mut() = Some(tray));
+    Ok(menu_ids)
+}
 
-    // 4. 启动事件循环
-    std::thread::spawn(move || {
-        tracing::debug!("托盘事件循环已启动");
-        events::run_event_loop(menu_ids, tx);
+/// 资源管理器重启后按当前状态重新创建托盘图标、菜单与提示（须在 UI 线程调用）
+///
+/// 菜单项 ID 固定不变，托盘事件循环无需重启
+#[cfg(windows)]
+fn reinstall() {
+    let levels = badge::levels();
+    let contents = TrayContents::current(&levels);
+    let (status, bucket) = contents.key;
+
+    let icon = match icon::load_icon(status, bucket.map(|bucket| levels.color(bucket))) {
+        Ok(icon) => icon,
+        Err(e) => {
+            tracing::error!("生成托盘图标失败，无法恢复托盘: {:#}", e);
+            return;
+        }
+    };
+    let menu = match menu::create_menu_with_ids(&contents.accounts) {
+        Ok((menu, _)) => menu,
+        Err(e) => {
+            tracing::error!("创建托盘菜单失败，无法恢复托盘: {:#}", e);
+            return;
+        }
+    };
+
+    // 先移除旧图标（tray-icon 可能已自行重新注册），避免任务栏中出现两个图标
+    TRAY_ICON.with(|slot| slot.borrow_mut().take());
+    set_menu_open(false);
+
+    let built = TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip(&contents.tooltip)
+        .with_icon(icon)
+        .build();
+    match built {
+        Ok(tray) => {
+            TRAY_ICON.with(|slot| *slot.borrow_mut() = Some(tray));
+            ICON_KEY.with(|current| current.set(Some(contents.key)));
+            MENU_ACCOUNTS.with(|current| *current.borrow_mut() = contents.accounts);
+            tracing::info!("托盘图标已重新创建");
+        }
+        Err(e) => tracing::error!("重新创建托盘图标失败: {}", e),
+    }
+}
+
+/// 启动 GTK 线程并在其中创建托盘图标，等待创建完成后返回
+#[cfg(target_os = "linux")]
+fn spawn_gtk_tray(icon: Icon) -> Result<menu::MenuIds> {
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+    std::thread::Builder::new()
+        .name("tray-gtk".to_string())
+        .spawn(move || {
+            if let Err(e) = gtk::init() {
+                ready_tx
+                    .send(Err(anyhow::anyhow!("初始化 GTK 失败: {}", e)))
+                    .ok();
+                return;
+            }
+
+            let installed = install_tray(icon);
+            let ok = installed.is_ok();
+            ready_tx.send(installed).ok();
+            if ok {
+                tracing::debug!("GTK 主循环已启动");
+                gtk::main();
+            }
+        })?;
+
+    ready_rx
+        .recv()
+        .map_err(|_| anyhow::anyhow!("GTK 线程意外退出"))?
+}
+
+/// 操作托盘图标（图标尚未创建或已移除时忽略）
+#[cfg(windows)]
+fn with_tray(f: impl FnOnce(&TrayIcon)) {
+    TRAY_ICON.with(|slot| {
+        if let Some(tray) = slot.borrow().as_ref() {
+            f(tray);
+        }
+    });
+}
+
+/// 转交 GTK 线程操作托盘图标（异步执行，图标尚未创建或已移除时忽略）
+#[cfg(target_os = "linux")]
+fn with_tray(f: impl FnOnce(&TrayIcon) + Send + 'static) {
+    gtk::glib::MainContext::default().invoke(move || {
+        TRAY_ICON.with(|slot| {
+            if let Some(tray) = slot.borrow().as_ref() {
+                f(tray);
+            }
+        })
+    });
+}
+
+/// 按当前语言重建托盘菜单和提示文字（须在 UI 线程调用）
+pub fn refresh_language() {
+    request_menu_rebuild();
+    set_tooltip(&tooltip_text());
+}
+
+/// 账户子菜单的内容（未读数、状态、免打扰）变化时安排重建托盘菜单
+fn schedule_menu_rebuild(accounts: &[menu::MenuAccount]) {
+    if MENU_ACCOUNTS.with(|current| *current.borrow() != accounts) {
+        request_menu_rebuild();
+    }
+}
+
+/// 去抖后重建托盘菜单；菜单正在显示时推迟到关闭之后（替换正在显示的菜单可能导致崩溃）
+fn request_menu_rebuild() {
+    MENU_TIMER.with(|timer| {
+        timer.start(slint::TimerMode::Repeated, MENU_REBUILD_DEBOUNCE, || {
+            if MENU_OPEN.load(Ordering::SeqCst) {
+                tracing::debug!("托盘菜单正在显示，推迟重建");
+                return;
+            }
+            MENU_TIMER.with(|timer| timer.stop());
+            rebuild_menu();
+        });
     });
+}
+
+/// 按最新的账户状态与当前语言重建托盘菜单
+fn rebuild_menu() {
+    let accounts = menu_accounts();
+    MENU_ACCOUNTS.with(|current| *current.borrow_mut() = accounts.clone());
+    with_tray(move |tray| match menu::create_menu_with_ids(&accounts) {
+        Ok((menu, _)) => tray.set_menu(Some(Box::new(menu))),
+        Err(e) => tracing::warn!("重建托盘菜单失败: {:#}", e),
+    });
+}
+
+/// 各账户子菜单的内容（按账户列表顺序）
+fn menu_accounts() -> Vec<menu::MenuAccount> {
+    summary::snapshot()
+        .iter()
+        .map(menu::MenuAccount::of)
+        .collect()
+}
+
+/// 托盘提示文字（按当前账户状态）
+fn tooltip_text() -> String {
+    format_tooltip(&summary::snapshot(), summary::app_status())
+}
+
+/// 托盘提示文字：应用名称 + 状态说明 + 各账户未读摘要 + 垃圾邮件摘要（有未读的垃圾邮件时）
+fn format_tooltip(accounts: &[AccountStatus], status: AppStatus) -> String {
+    let summary = summary::format_summary(accounts);
+    let spam = summary::format_spam_summary(accounts);
+    let spam = if spam.is_empty() {
+        spam
+    } else {
+        tr_fmt("tray.spam", &[&spam])
+    };
+
+    [tr("tray.tooltip"), status.label(), &summary, &spam]
+        .into_iter()
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-    Ok(tray)
+/// 托盘的全部显示内容（图标、账户子菜单与提示文字），完全由账户状态决定
+#[derive(Debug, PartialEq)]
+struct TrayContents {
+    key: IconKey,
+    accounts: Vec<menu::MenuAccount>,
+    tooltip: String,
+}
+
+impl TrayContents {
+    /// 由各账户状态与应用状态生成（未读状态的图标按未读合计分档）
+    fn of(accounts: &[AccountStatus], status: AppStatus, levels: &BadgeLevels) -> Self {
+        let bucket = (status == AppStatus::Unread)
+            .then(|| levels.bucket(summary::compute_total(accounts).unread));
+        Self {
+            key: (status, bucket),
+            accounts: accounts.iter().map(menu::MenuAccount::of).collect(),
+            tooltip: format_tooltip(accounts, status),
+        }
+    }
+
+    /// 按最新的账户状态生成
+    fn current(levels: &BadgeLevels) -> Self {
+        Self::of(&summary::snapshot(), summary::app_status(), levels)
+    }
+}
+
+/// 按最新的账户状态刷新托盘图标、提示与账户子菜单，返回当前应用状态（须在 UI 线程调用）
+pub fn refresh_status() -> AppStatus {
+    let levels = badge::levels();
+    let contents = TrayContents::current(&levels);
+    let (status, bucket) = contents.key;
+    let key = Some(contents.key);
+
+    let previous = ICON_KEY.with(|current| current.replace(key));
+    if previous != key {
+        if previous.is_none_or(|(previous, _)| previous != status) {
+            tracing::info!("应用状态 -> {:?}", status);
+        }
+        match icon::load_icon(status, bucket.map(|bucket| levels.color(bucket))) {
+            Ok(icon) => with_tray(move |tray| {
+                if let Err(e) = tray.set_icon(Some(icon)) {
+                    tracing::warn!("更新托盘图标失败: {}", e);
+                }
+            }),
+            Err(e) => tracing::warn!("生成托盘图标失败: {:#}", e),
+        }
+    }
+
+    // 临时提示显示期间不覆盖，到期后会自动恢复为最新摘要
+    if !TOOLTIP_TIMER.with(|timer| timer.running()) {
+        set_tooltip(&contents.tooltip);
+    }
+    schedule_menu_rebuild(&contents.accounts);
+
+    status
+}
+
+/// 应用 `[tray]` 配置（须在 UI 线程调用，随后的 [`refresh_status`] 按新的分档重新生成图标）
+pub fn configure_badge(config: &TrayConfig) {
+    badge::configure(config);
+    ICON_KEY.with(|current| current.set(None));
+}
+
+/// 短暂显示一条托盘提示，随后恢复为未读摘要（须在 UI 线程调用）
+pub fn flash_tooltip(text: &str) {
+    set_tooltip(text);
+    TOOLTIP_TIMER.with(|timer| {
+        timer.start(slint::TimerMode::SingleShot, TOOLTIP_FLASH_DURATION, || {
+            set_tooltip(&tooltip_text())
+        });
+    });
+}
+
+fn set_tooltip(text: &str) {
+    let text = text.to_string();
+    with_tray(move |tray| {
+        if let Err(e) = tray.set_tooltip(Some(text)) {
+            tracing::warn!("设置托盘提示失败: {}", e);
+        }
+    });
+}
+
+/// 将未读摘要复制到剪贴板，返回复制的文本（没有账户时不复制，返回 `None`）
+pub fn copy_summary() -> Result<Option<String>> {
+    let text = summary::current();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    clipboard::set_text(&text)?;
+    Ok(Some(text))
 }
 
 /// 切换窗口显示/隐藏
@@ -52,36 +420,212 @@ pub fn toggle_window<T: ComponentHandle>(window: &T) {
     }
 }
 
+/// 窗口尺寸读取失败时使用的默认值（与 main.slint 的设计尺寸一致，逻辑像素）
+const DEFAULT_WINDOW_WIDTH: f32 = 380.0;
+const DEFAULT_WINDOW_HEIGHT: f32 = 400.0;
+
+/// 最近一次左键点击托盘图标的位置（窗口贴近该图标显示；未点击过时贴住工作区右下角）
+static CLICK_ANCHOR: Mutex<Option<Point>> = Mutex::new(None);
+
+/// 记录托盘图标的点击位置（物理像素）
+fn set_click_anchor(point: Point) {
+    *CLICK_ANCHOR.lock().unwrap_or_else(|e| e.into_inner()) = Some(point);
+}
+
 /// 在托盘附近显示窗口（尽量放置在右下角，留出任务栏空间）
 pub fn show_window_near_tray<T: ComponentHandle>(window: &T) {
     tracing::info!("show_window_near_tray: 开始显示窗口");
 
-    // 尝试动态获取主显示器分辨率，回退到默认值
-    let (screen_width, screen_height) = match get_primary_screen_size() {
+    reposition_near_tray(window);
+
+    tracing::info!("show_window_near_tray: 调用 window.show()");
+    if let Err(e) = window.show() {
+        tracing::error!("show_window_near_tray: 显示窗口失败: {:?}", e);
+    } else {
+        tracing::info!("show_window_near_tray: 窗口已显示");
+    }
+}
+
+/// 按窗口当前实际尺寸重新计算托盘附近的位置（不改变可见性）
+///
+/// 账户增删导致窗口高度变化后调用，保证窗口底部仍位于任务栏之上
+pub fn reposition_near_tray<T: ComponentHandle>(window: &T) {
+    // 读取窗口实际尺寸（高度由 Slint 根据账户数量动态计算），尚未布局时按缩放比例换算设计尺寸
+    let size = window.window().size();
+    let window_size = if size.width > 0 && size.height > 0 {
+        Size {
+            width: size.width as i32,
+            height: size.height as i32,
+        }
+    } else {
+        let scale = window.window().scale_factor();
+        Size {
+            width: (DEFAULT_WINDOW_WIDTH * scale) as i32,
+            height: (DEFAULT_WINDOW_HEIGHT * scale) as i32,
+        }
+    };
+    place_near_tray(window, window_size);
+}
+
+/// 按即将生效的窗口高度（逻辑像素）重新计算托盘附近的位置
+///
+/// 切换紧凑模式时 Slint 在下一次布局才改变窗口高度，先按新高度定位，窗口只移动一次
+pub fn reposition_for_height<T: ComponentHandle>(window: &T, height: f32) {
+    let scale = window.window().scale_factor();
+    let width = match window.window().size().width {
+        0 => (DEFAULT_WINDOW_WIDTH * scale) as i32,
+        width => width as i32,
+    };
+    let window_size = Size {
+        width,
+        height: (height * scale) as i32,
+    };
+    place_near_tray(window, window_size);
+}
+
+/// 把窗口放到托盘附近（`window_size` 为物理像素）
+fn place_near_tray<T: ComponentHandle>(window: &T, window_size: Size) {
+    let anchor = CLICK_ANCHOR
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .map_or(AnchorPoint::BottomRight, AnchorPoint::At);
+    let position = layout::compute_popup_position(work_area(), window_size, anchor, POPUP_MARGIN);
+
+    tracing::info!(
+        "reposition_near_tray: 窗口 {}x{}，锚点 {:?}，设置位置 x={}, y={}",
+        window_size.width,
+        window_size.height,
+        anchor,
+        position.x,
+        position.y
+    );
+    window
+        .window()
+        .set_position(slint::PhysicalPosition::new(position.x, position.y));
+}
+
+/// 主显示器分辨率，获取失败时回退到 1920x1080
+fn primary_screen_size() -> (i32, i32) {
+    match get_primary_screen_size() {
         Ok((w, h)) => (w as i32, h as i32),
         Err(e) => {
             tracing::warn!("无法获取屏幕尺寸: {}, 使用默认值 1920x1080", e);
             (1920, 1080)
         }
-    };
+    }
+}
 
-    // 设计的窗口尺寸
-    let window_width = 380i32;
-    let window_height = 400i32;
+/// 窗口与工作区边缘的距离
+#[cfg(windows)]
+const POPUP_MARGIN: i32 = 10;
 
-    // 在右下角上方显示（留出任务栏和边距）
-    let x = screen_width - window_width - 97;
-    let y = screen_height - window_height - 50;
+/// 底部任务栏的估计高度
+#[cfg(windows)]
+const TASKBAR_HEIGHT: i32 = 40;
 
-    tracing::info!("show_window_near_tray: 设置窗口位置 x={}, y={}", x, y);
-    window
-        .window()
-        .set_position(slint::PhysicalPosition::new(x, y));
+/// 通知区域（托盘图标与时钟）的估计宽度，默认位置让出这部分任务栏
+#[cfg(windows)]
+const NOTIFICATION_AREA_WIDTH: i32 = 87;
 
-    tracing::info!("show_window_near_tray: 调用 window.show()");
-    if let Err(e) = window.show() {
-        tracing::error!("show_window_near_tray: 显示窗口失败: {:?}", e);
-    } else {
-        tracing::info!("show_window_near_tray: 窗口已显示");
+/// 主显示器减去按默认布局估算的任务栏与通知区域
+///
+/// 右下角位置即原有的经验值：距右边缘 87 + 10 像素，距下边缘 40 + 10 像素
+#[cfg(windows)]
+fn work_area() -> Rect {
+    let (width, height) = primary_screen_size();
+    Rect {
+        x: 0,
+        y: 0,
+        width: width - NOTIFICATION_AREA_WIDTH,
+        height: height - TASKBAR_HEIGHT,
+    }
+}
+
+/// 窗口与面板之间的边距
+#[cfg(target_os = "linux")]
+const POPUP_MARGIN: i32 = 12;
+
+/// 桌面工作区（桌面减去面板）
+///
+/// 面板的位置因桌面环境而异，无法像 Windows 那样按任务栏估算；窗口系统不提供工作区时
+/// （如纯 Wayland 会话）以整个屏幕代替，此时窗口位置通常由合成器决定
+#[cfg(target_os = "linux")]
+fn work_area() -> Rect {
+    workarea::primary().unwrap_or_else(|| {
+        let (width, height) = primary_screen_size();
+        tracing::debug!("无法读取桌面工作区，使用整个屏幕");
+        Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(email: &str, unread_count: u32, spam_unread: u32) -> AccountStatus {
+        AccountStatus {
+            email: email.to_string(),
+            display_name: String::new(),
+            unread_count,
+            synced: true,
+            failures: 0,
+            fatal: false,
+            last_error: None,
+            partial: false,
+            snoozed: false,
+            active: true,
+            spam_unread,
+            alert_threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_contents_rebuilt_from_account_state() {
+        let accounts = [
+            account("work@example.com", 8, 0),
+            account("home@example.com", 0, 3),
+        ];
+        let levels = BadgeLevels::default();
+        let contents = TrayContents::of(&accounts, AppStatus::Unread, &levels);
+
+        assert_eq!(contents.key, (AppStatus::Unread, Some(levels.bucket(8))));
+        assert_eq!(
+            contents.accounts,
+            accounts
+                .iter()
+                .map(menu::MenuAccount::of)
+                .collect::<Vec<_>>()
+        );
+        let lines: Vec<&str> = contents.tooltip.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                tr("tray.tooltip"),
+                AppStatus::Unread.label(),
+                "work@example.com 8, home@example.com 0",
+                tr_fmt("tray.spam", &[&"home@example.com 3"]).as_str(),
+            ]
+        );
+
+        // 同一份状态总是得到相同的内容，重建后的托盘与重启前一致
+        assert_eq!(
+            TrayContents::of(&accounts, AppStatus::Unread, &levels),
+            contents
+        );
+    }
+
+    #[test]
+    fn test_contents_without_accounts() {
+        let contents = TrayContents::of(&[], AppStatus::Normal, &BadgeLevels::default());
+
+        assert_eq!(contents.key, (AppStatus::Normal, None));
+        assert!(contents.accounts.is_empty());
+        // 正常状态没有状态说明，提示只有应用名称
+        assert_eq!(contents.tooltip, tr("tray.tooltip"));
     }
 }
@@ -0,0 +1,583 @@
+// 未读摘要与应用状态模块
+//
+// 保存各账户最近一次同步的状态（窗口隐藏时 Slint 模型会被清空，托盘提示、复制摘要与
+// 标题栏/托盘图标的状态颜色都从这里读取）
+
+use std::sync::Mutex;
+
+use crate::config::storage::StoredAccount;
+use crate::mail::error::SyncError;
+use crate::mail::{AccountSyncInfo, SyncFailure};
+use crate::utils::connectivity::{self, ConnectivityState};
+use crate::utils::i18n::tr;
+
+/// 连续失败达到此轮数后才从"降级"升级为"错误"
+const ERROR_AFTER_FAILURES: u32 = 3;
+
+/// 应用整体状态（标题栏 N 与托盘图标的颜色）
+///
+/// 优先级从高到低：Error > Paused > Attention > Degraded > Unread > Normal。
+/// 全部账户都在免打扰时显示 Paused，只有真正的错误会覆盖它
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppStatus {
+    /// 一切正常，没有未读邮件
+    Normal,
+
+    /// 有未读邮件（免打扰中的账户不计入）
+    Unread,
+
+    /// 有账户的未读数达到了提醒阈值（免打扰中的账户不计入）
+    Attention,
+
+    /// 暂时性问题：单轮同步失败、网络缓慢、部分资料获取失败或系统报告离线
+    Degraded,
+
+    /// 持续失败：连续多轮同步失败，或登录/证书错误
+    Error,
+
+    /// 全部账户都在免打扰期
+    Paused,
+}
+
+impl AppStatus {
+    /// Slint 端使用的状态标识
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Unread => "unread",
+            Self::Attention => "attention",
+            Self::Degraded => "degraded",
+            Self::Error => "error",
+            Self::Paused => "paused",
+        }
+    }
+
+    /// 状态说明（标题栏与托盘提示使用，正常状态为空）
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "",
+            Self::Unread => tr("status.unread"),
+            Self::Attention => tr("status.attention"),
+            Self::Degraded => tr("status.degraded"),
+            Self::Error => tr("status.error"),
+            Self::Paused => tr("status.paused"),
+        }
+    }
+}
+
+/// 单个账户的健康状况
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountHealth {
+    Ok,
+    Degraded,
+    Error,
+}
+
+/// 单个账户的摘要状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountStatus {
+    /// 邮箱地址（用于匹配同步结果）
+    pub email: String,
+
+    /// 显示名称（为空时摘要中使用邮箱地址）
+    pub display_name: String,
+
+    /// 未读数（尚未同步成功过时无意义）
+    pub unread_count: u32,
+
+    /// 本次运行中至少同步成功过一次（尚未同步的账户不计入合计，摘要中显示"—"）
+    pub synced: bool,
+
+    /// 连续同步失败的轮数（成功后清零）
+    pub failures: u32,
+
+    /// 最近一次失败是登录、授权或证书错误（重试也不会自行恢复）
+    pub fatal: bool,
+
+    /// 最近一次失败的错误信息（成功后清除）
+    pub last_error: Option<String>,
+
+    /// 最近一次同步成功但部分资料获取失败（如头像、昵称）
+    pub partial: bool,
+
+    /// 处于免打扰期
+    pub snoozed: bool,
+
+    /// 账户已激活（停用的账户不计入未读合计）
+    pub active: bool,
+
+    /// 垃圾邮件未读数（未开启监视时为 0；只在托盘提示中单独列出，不计入合计与状态）
+    pub spam_unread: u32,
+
+    /// 未读提醒阈值（`None` 表示不提醒）
+    pub alert_threshold: Option<u32>,
+}
+
+/// 各账户的未读合计（窗口标题栏显示，与托盘状态使用同一份数据）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnreadTotal {
+    /// 未读数之和
+    pub unread: u32,
+
+    /// 计入合计的账户数
+    pub accounts: u32,
+}
+
+impl AccountStatus {
+    /// 新账户（尚未同步）
+    fn new(email: String, display_name: String, snoozed: bool) -> Self {
+        Self {
+            email,
+            display_name,
+            unread_count: 0,
+            synced: false,
+            failures: 0,
+            fatal: false,
+            last_error: None,
+            partial: false,
+            snoozed,
+            active: true,
+            spam_unread: 0,
+            alert_threshold: None,
+        }
+    }
+
+    /// 摘要中显示的名称（没有显示名称时使用邮箱地址）
+    pub fn summary_name(&self) -> &str {
+        if self.display_name.is_empty() {
+            &self.email
+        } else {
+            &self.display_name
+        }
+    }
+
+    /// 是否计入未读合计（尚未同步、免打扰中或已停用的账户不计入）
+    pub fn counts_toward_total(&self) -> bool {
+        self.synced && self.active && !self.snoozed
+    }
+
+    /// 未读数达到提醒阈值（不计入合计的账户不算）
+    pub fn needs_attention(&self) -> bool {
+        self.counts_toward_total()
+            && self
+                .alert_threshold
+                .is_some_and(|threshold| self.unread_count >= threshold)
+    }
+
+    /// 健康状况：登录/证书错误或连续失败多轮为错误，其他失败为降级
+    pub fn health(&self) -> AccountHealth {
+        if self.fatal || self.failures >= ERROR_AFTER_FAILURES {
+            AccountHealth::Error
+        } else if self.failures > 0 || self.partial {
+            AccountHealth::Degraded
+        } else {
+            AccountHealth::Ok
+        }
+    }
+}
+
+/// 各账户状态（按账户列表顺序）
+static STATUS: Mutex<Vec<AccountStatus>> = Mutex::new(Vec::new());
+
+/// 按已保存的账户重置状态（保留已有账户的未读数）
+pub fn set_accounts(accounts: &[StoredAccount]) {
+    let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = std::mem::take(&mut *status);
+
+    *status = accounts
+        .iter()
+        .map(
+            |account| match previous.iter().find(|s| s.email == account.email()) {
+                Some(old) => AccountStatus {
+                    display_name: account.display_name().to_string(),
+                    snoozed: account.is_snoozed(),
+                    active: account.is_active(),
+                    alert_threshold: account.alert_threshold(),
+                    ..old.clone()
+                },
+                None => AccountStatus {
+                    active: account.is_active(),
+                    alert_threshold: account.alert_threshold(),
+                    ..AccountStatus::new(
+                        account.email().to_string(),
+                        account.display_name().to_string(),
+                        account.is_snoozed(),
+                    )
+                },
+            },
+        )
+        .collect();
+}
+
+/// 记录一次成功的同步（新账户追加到末尾）
+pub fn update(info: &AccountSyncInfo) {
+    let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = match status.iter().position(|s| s.email == info.email()) {
+        Some(index) => &mut status[index],
+        None => {
+            status.push(AccountStatus::new(
+                info.email().to_string(),
+                String::new(),
+                false,
+            ));
+            status.last_mut().expect("刚追加的账户")
+        }
+    };
+
+    // 本轮没有获取到的资料与计数保留上次的值
+    if let Some(profile) = &info.profile {
+        entry.display_name = profile.display_name.clone();
+    }
+    if let Some(unread) = info.counters.unread {
+        entry.unread_count = unread;
+    }
+    entry.synced = true;
+    entry.failures = 0;
+    entry.fatal = false;
+    entry.last_error = None;
+    entry.partial = info.has_error();
+    entry.snoozed = info.health.snoozed;
+    if let Some(spam_unread) = info.counters.spam_unread {
+        entry.spam_unread = spam_unread;
+    }
+}
+
+/// 记录一次失败的同步（保留上次的未读数）
+pub fn record_failure(email: &str, failure: &SyncFailure) {
+    let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(entry) = status.iter_mut().find(|s| s.email == email) {
+        entry.failures += 1;
+        entry.last_error = Some(failure.message.clone());
+        entry.fatal = matches!(
+            failure.kind,
+            Some(
+                SyncError::AuthFailed
+                    | SyncError::Certificate
+                    | SyncError::ReauthRequired
+                    | SyncError::AccountUnavailable
+                    | SyncError::Credentials(_)
+            )
+        );
+    }
+}
+
+/// 各账户状态的副本（按账户列表顺序）
+pub fn snapshot() -> Vec<AccountStatus> {
+    STATUS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 当前应用状态
+pub fn app_status() -> AppStatus {
+    // 系统离线或同步循环因连续失败拉长了间隔时整体降级
+    let degraded =
+        connectivity::state() == ConnectivityState::Offline || crate::sync::is_backing_off();
+    compute_status(&STATUS.lock().unwrap_or_else(|e| e.into_inner()), degraded)
+}
+
+/// 由各账户状态与整体降级信号（系统离线、同步熔断）计算应用状态
+pub fn compute_status(accounts: &[AccountStatus], degraded: bool) -> AppStatus {
+    let health = |wanted| accounts.iter().any(|a| a.health() == wanted);
+
+    precedence(
+        health(AccountHealth::Error),
+        !accounts.is_empty() && accounts.iter().all(|a| a.snoozed),
+        accounts.iter().any(AccountStatus::needs_attention),
+        degraded || health(AccountHealth::Degraded),
+        compute_total(accounts).unread > 0,
+    )
+}
+
+/// 当前的未读合计
+pub fn total() -> UnreadTotal {
+    compute_total(&STATUS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// 合计计入的账户（见 [`AccountStatus::counts_toward_total`]）的未读数
+pub fn compute_total(accounts: &[AccountStatus]) -> UnreadTotal {
+    accounts
+        .iter()
+        .filter(|a| a.counts_toward_total())
+        .fold(UnreadTotal::default(), |total, a| UnreadTotal {
+            unread: total.unread + a.unread_count,
+            accounts: total.accounts + 1,
+        })
+}
+
+/// 状态优先级：Error > Paused > Attention > Degraded > Unread > Normal
+fn precedence(
+    error: bool,
+    paused: bool,
+    attention: bool,
+    degraded: bool,
+    unread: bool,
+) -> AppStatus {
+    if error {
+        AppStatus::Error
+    } else if paused {
+        AppStatus::Paused
+    } else if attention {
+        AppStatus::Attention
+    } else if degraded {
+        AppStatus::Degraded
+    } else if unread {
+        AppStatus::Unread
+    } else {
+        AppStatus::Normal
+    }
+}
+
+/// 当前摘要（托盘提示与"复制摘要"共用）
+pub fn current() -> String {
+    format_summary(&STATUS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// 格式化有未读垃圾邮件的账户（托盘提示中单独一行），如 `personal 2, work 1`；都没有时返回空字符串
+pub fn format_spam_summary(accounts: &[AccountStatus]) -> String {
+    accounts
+        .iter()
+        .filter(|account| account.spam_unread > 0)
+        .map(|account| format!("{} {}", account.summary_name(), account.spam_unread))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 格式化为一行摘要，如 `personal 2, work — ⚠, support 14`
+///
+/// 尚未同步成功的账户显示"—"而不是 0；同步出错或降级的账户追加 ⚠，免打扰中的账户追加 🕒；
+/// 没有账户时返回空字符串
+pub fn format_summary(accounts: &[AccountStatus]) -> String {
+    accounts
+        .iter()
+        .map(|account| {
+            let mut item = if account.synced {
+                format!("{} {}", account.summary_name(), account.unread_count)
+            } else {
+                format!("{} —", account.summary_name())
+            };
+            if account.health() != AccountHealth::Ok {
+                item.push_str(" ⚠");
+            }
+            if account.snoozed {
+                item.push_str(" 🕒");
+            }
+            item
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(display_name: &str, unread_count: u32) -> AccountStatus {
+        AccountStatus {
+            unread_count,
+            synced: true,
+            ..AccountStatus::new(
+                format!("{}@example.com", display_name),
+                display_name.to_string(),
+                false,
+            )
+        }
+    }
+
+    #[test]
+    fn test_format_summary() {
+        let mut work = status("work", 0);
+        work.failures = 1;
+        let mut support = status("support", 14);
+        support.snoozed = true;
+        let mut nameless = status("", 1);
+        nameless.email = "me@example.com".to_string();
+
+        assert_eq!(
+            format_summary(&[status("personal", 2), work, support, nameless]),
+            "personal 2, work 0 ⚠, support 14 🕒, me@example.com 1"
+        );
+        assert_eq!(format_summary(&[]), "");
+    }
+
+    #[test]
+    fn test_spam_is_listed_separately() {
+        let mut personal = status("personal", 2);
+        personal.spam_unread = 3;
+        let mut nameless = status("", 0);
+        nameless.email = "me@example.com".to_string();
+        nameless.spam_unread = 1;
+        let accounts = [personal, status("work", 0), nameless];
+
+        assert_eq!(
+            format_spam_summary(&accounts),
+            "personal 3, me@example.com 1"
+        );
+        assert_eq!(format_spam_summary(&[status("work", 4)]), "");
+
+        // 垃圾邮件不计入未读合计，也不影响应用状态
+        assert_eq!(compute_total(&accounts).unread, 2);
+        assert_eq!(
+            format_summary(&accounts),
+            "personal 2, work 0, me@example.com 0"
+        );
+        assert_eq!(
+            compute_status(&[status("work", 0), accounts[2].clone()], false),
+            AppStatus::Normal
+        );
+    }
+
+    #[test]
+    fn test_total_skips_snoozed_and_inactive_accounts() {
+        let mut snoozed = status("support", 14);
+        snoozed.snoozed = true;
+        let mut inactive = status("old", 5);
+        inactive.active = false;
+
+        let accounts = [status("personal", 2), status("work", 3), snoozed, inactive];
+        assert_eq!(
+            compute_total(&accounts),
+            UnreadTotal {
+                unread: 5,
+                accounts: 2
+            }
+        );
+        assert_eq!(compute_total(&[]), UnreadTotal::default());
+
+        // 只有免打扰中的账户有未读时不显示"有未读"
+        assert_eq!(compute_status(&accounts[2..], false), AppStatus::Normal);
+    }
+
+    #[test]
+    fn test_never_synced_is_not_counted_as_zero() {
+        let mut pending = status("work", 0);
+        pending.synced = false;
+        let accounts = [status("personal", 2), pending.clone()];
+
+        assert_eq!(format_summary(&accounts), "personal 2, work —");
+        assert_eq!(
+            compute_total(&accounts),
+            UnreadTotal {
+                unread: 2,
+                accounts: 1
+            }
+        );
+
+        // 尚未同步的账户不影响应用状态
+        assert_eq!(compute_status(&[pending], false), AppStatus::Normal);
+    }
+
+    #[test]
+    fn test_precedence_exhaustive() {
+        for bits in 0..32u8 {
+            let (error, paused, attention, degraded, unread) = (
+                bits & 16 != 0,
+                bits & 8 != 0,
+                bits & 4 != 0,
+                bits & 2 != 0,
+                bits & 1 != 0,
+            );
+
+            // 取置位的最高优先级状态
+            let expected = [
+                (error, AppStatus::Error),
+                (paused, AppStatus::Paused),
+                (attention, AppStatus::Attention),
+                (degraded, AppStatus::Degraded),
+                (unread, AppStatus::Unread),
+            ]
+            .into_iter()
+            .find_map(|(set, status)| set.then_some(status))
+            .unwrap_or(AppStatus::Normal);
+
+            assert_eq!(
+                precedence(error, paused, attention, degraded, unread),
+                expected,
+                "error={} paused={} attention={} degraded={} unread={}",
+                error,
+                paused,
+                attention,
+                degraded,
+                unread
+            );
+        }
+    }
+
+    #[test]
+    fn test_account_health_escalates_only_when_sustained() {
+        let mut account = status("work", 0);
+        assert_eq!(account.health(), AccountHealth::Ok);
+
+        account.partial = true;
+        assert_eq!(account.health(), AccountHealth::Degraded);
+
+        account.partial = false;
+        for round in 1..ERROR_AFTER_FAILURES {
+            account.failures = round;
+            assert_eq!(account.health(), AccountHealth::Degraded);
+        }
+        account.failures = ERROR_AFTER_FAILURES;
+        assert_eq!(account.health(), AccountHealth::Error);
+
+        // 登录失败一次即为错误
+        let mut rejected = status("imap", 0);
+        rejected.failures = 1;
+        rejected.fatal = true;
+        assert_eq!(rejected.health(), AccountHealth::Error);
+    }
+
+    #[test]
+    fn test_compute_status() {
+        assert_eq!(compute_status(&[], false), AppStatus::Normal);
+        assert_eq!(compute_status(&[], true), AppStatus::Degraded);
+        assert_eq!(
+            compute_status(&[status("a", 0), status("b", 2)], false),
+            AppStatus::Unread
+        );
+
+        // 免打扰账户的未读不计入；全部免打扰时为 Paused
+        let mut snoozed = status("b", 2);
+        snoozed.snoozed = true;
+        assert_eq!(
+            compute_status(&[status("a", 0), snoozed.clone()], false),
+            AppStatus::Normal
+        );
+        assert_eq!(compute_status(&[snoozed.clone()], false), AppStatus::Paused);
+
+        let mut failing = status("c", 0);
+        failing.failures = 1;
+        assert_eq!(
+            compute_status(&[status("b", 2), failing.clone()], false),
+            AppStatus::Degraded
+        );
+        failing.failures = ERROR_AFTER_FAILURES;
+        assert_eq!(compute_status(&[snoozed, failing], false), AppStatus::Error);
+    }
+
+    #[test]
+    fn test_attention_when_unread_reaches_threshold() {
+        let mut support = status("support", 19);
+        support.alert_threshold = Some(20);
+        let accounts = [status("personal", 2), support.clone()];
+        assert_eq!(compute_status(&accounts, false), AppStatus::Unread);
+
+        support.unread_count = 20;
+        assert!(support.needs_attention());
+        assert_eq!(
+            compute_status(&[status("personal", 2), support.clone()], false),
+            AppStatus::Attention
+        );
+
+        // 比降级优先，免打扰中的账户不计入
+        let mut failing = status("work", 0);
+        failing.failures = 1;
+        assert_eq!(
+            compute_status(&[support.clone(), failing], true),
+            AppStatus::Attention
+        );
+        support.snoozed = true;
+        assert!(!support.needs_attention());
+        assert_eq!(
+            compute_status(&[status("personal", 2), support], false),
+            AppStatus::Unread
+        );
+    }
+}
@@ -0,0 +1,78 @@
+// 桌面工作区读取模块（Linux / X11）
+//
+// 工作区是屏幕减去面板（任务栏、Dock）后的区域，由窗口管理器写在根窗口的
+// _NET_WORKAREA 属性中。多显示器时该区域覆盖整个虚拟桌面
+
+use std::ffi::c_ulong;
+use x11::xlib;
+
+use crate::ui::layout::Rect;
+
+/// 读取当前桌面的工作区（物理像素）
+///
+/// 无法连接 X 服务器（纯 Wayland 会话）或窗口管理器未提供该属性时返回 `None`
+pub fn primary() -> Option<Rect> {
+    // SAFETY: 连接只在本函数内使用，并在返回前关闭
+    unsafe {
+        let display = xlib::XOpenDisplay(std::ptr::null());
+        if display.is_null() {
+            return None;
+        }
+        let area = read_work_area(display);
+        xlib::XCloseDisplay(display);
+        area
+    }
+}
+
+/// 读取根窗口的 _NET_WORKAREA（取第一个桌面的值）
+///
+/// # Safety
+/// `display` 必须是有效的 X 连接
+unsafe fn read_work_area(display: *mut xlib::Display) -> Option<Rect> {
+    let mut actual_type: c_ulong = 0;
+    let mut actual_format = 0;
+    let mut items: c_ulong = 0;
+    let mut bytes_after: c_ulong = 0;
+    let mut data: *mut u8 = std::ptr::null_mut();
+
+    // SAFETY: 所有输出参数都指向有效的局部变量；返回的 data 由下方 XFree 释放
+    let status = unsafe {
+        let atom = xlib::XInternAtom(display, c"_NET_WORKAREA".as_ptr(), xlib::True);
+        if atom == 0 {
+            return None;
+        }
+        xlib::XGetWindowProperty(
+            display,
+            xlib::XDefaultRootWindow(display),
+            atom,
+            0,
+            4,
+            xlib::False,
+            xlib::XA_CARDINAL,
+            &mut actual_type,
+            &mut actual_format,
+            &mut items,
+            &mut bytes_after,
+            &mut data,
+        )
+    };
+    if status != xlib::Success as i32 || data.is_null() {
+        return None;
+    }
+
+    // 格式为 32 的属性在客户端以 long 数组返回
+    let area = (actual_format == 32 && items >= 4).then(|| {
+        // SAFETY: 已确认至少包含 4 个元素
+        let values = unsafe { std::slice::from_raw_parts(data as *const c_ulong, 4) };
+        Rect {
+            x: values[0] as i32,
+            y: values[1] as i32,
+            width: values[2] as i32,
+            height: values[3] as i32,
+        }
+    });
+    // SAFETY: data 由 XGetWindowProperty 分配
+    unsafe { xlib::XFree(data.cast()) };
+
+    area.filter(|area| area.width > 0 && area.height > 0)
+}
@@ -0,0 +1,122 @@
+/// 界面更新合并
+///
+/// 多个账户几乎同时完成同步时，各自的结果先按邮箱合并到待应用表中，
+/// 由一次事件循环回调统一应用，避免连续多次重建账户行和重新计算状态
+use std::sync::Mutex;
+
+/// 按键合并的待应用更新（可从任意线程调用）
+#[derive(Default)]
+pub struct Coalescer<T> {
+    state: Mutex<Pending<T>>,
+}
+
+#[derive(Default)]
+struct Pending<T> {
+    /// 待应用的更新（按首次加入的顺序）
+    updates: Vec<(String, T)>,
+
+    /// 是否已安排应用
+    armed: bool,
+}
+
+impl<T: Default> Coalescer<T> {
+    /// 修改指定键的待应用更新（不存在时从默认值开始）
+    ///
+    /// 返回 true 表示调用方需要安排一次应用（每次应用之前只返回一次）
+    pub fn update(&self, key: &str, change: impl FnOnce(&mut T)) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        match state.updates.iter_mut().find(|(k, _)| k == key) {
+            Some((_, pending)) => change(pending),
+            None => {
+                let mut pending = T::default();
+                change(&mut pending);
+                state.updates.push((key.to_string(), pending));
+            }
+        }
+
+        !std::mem::replace(&mut state.armed, true)
+    }
+
+    /// 取出全部待应用更新，之后的修改会重新安排应用
+    pub fn drain(&self) -> Vec<(String, T)> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.armed = false;
+        std::mem::take(&mut state.updates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Row {
+        loading: bool,
+        unread: Option<u32>,
+    }
+
+    #[test]
+    fn test_updates_for_one_key_collapse() {
+        let coalescer = Coalescer::<Row>::default();
+
+        // 只有第一次修改需要安排应用
+        assert!(coalescer.update("a", |row| row.loading = true));
+        assert!(!coalescer.update("b", |row| row.unread = Some(1)));
+        assert!(!coalescer.update("a", |row| {
+            row.loading = false;
+            row.unread = Some(3);
+        }));
+        assert!(!coalescer.update("a", |row| row.unread = Some(4)));
+
+        assert_eq!(
+            coalescer.drain(),
+            vec![
+                (
+                    "a".to_string(),
+                    Row {
+                        loading: false,
+                        unread: Some(4)
+                    }
+                ),
+                (
+                    "b".to_string(),
+                    Row {
+                        loading: false,
+                        unread: Some(1)
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drain_rearms() {
+        let coalescer = Coalescer::<Row>::default();
+        assert!(coalescer.update("a", |row| row.loading = true));
+        assert_eq!(coalescer.drain().len(), 1);
+        assert!(coalescer.drain().is_empty());
+
+        // 应用之后的修改重新安排
+        assert!(coalescer.update("a", |row| row.loading = false));
+    }
+
+    #[test]
+    fn test_later_change_keeps_earlier_fields() {
+        let coalescer = Coalescer::<Row>::default();
+
+        // 结果之后紧接着开始下一次同步：保留结果，只更新同步中标记
+        coalescer.update("a", |row| row.unread = Some(2));
+        coalescer.update("a", |row| row.loading = true);
+        assert_eq!(
+            coalescer.drain(),
+            vec![(
+                "a".to_string(),
+                Row {
+                    loading: true,
+                    unread: Some(2)
+                }
+            )]
+        );
+    }
+}
@@ -0,0 +1,337 @@
+/// 账户分组（如"工作"、"个人"）
+///
+/// 账户列表仍是一个扁平的 Slint 模型：有账户设置了分组时，在每组账户前插入一个标题行
+/// （`header` 为 true，`unread_count` 为组内未读合计），没有分组的账户归入最后的"其他"组。
+/// 折叠的组中账户行保留在模型里但高度为 0，键盘导航跳过这些行。
+/// 组的折叠状态保存在配置中；未读合计与托盘状态仍来自 `tray::summary`，不受分组影响。
+/// 紧凑模式下账户行只有一半高度，行高相关的计算都以 `compact` 区分
+use std::sync::Mutex;
+
+use crate::Account;
+
+/// 账户行高度（逻辑像素，与 `AccountCard` 一致）
+pub const ROW_HEIGHT: f32 = 80.0;
+
+/// 紧凑模式的账户行高度（逻辑像素，与 `AccountCard` 一致）
+pub const COMPACT_ROW_HEIGHT: f32 = 40.0;
+
+/// 分组标题行高度（逻辑像素，与 `GroupHeader` 一致）
+pub const HEADER_HEIGHT: f32 = 28.0;
+
+/// 已折叠的组（空字符串表示"其他"组）
+static COLLAPSED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// 设置已折叠的组（来自配置）
+pub fn set_collapsed(groups: &[String]) {
+    *COLLAPSED.lock().unwrap_or_else(|e| e.into_inner()) = groups.to_vec();
+}
+
+/// 当前已折叠的组
+pub fn collapsed() -> Vec<String> {
+    COLLAPSED.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 切换组的折叠状态，返回切换后的全部折叠组（用于保存到配置）
+pub fn toggle(group: &str) -> Vec<String> {
+    let mut collapsed = COLLAPSED.lock().unwrap_or_else(|e| e.into_inner());
+    match collapsed.iter().position(|g| g == group) {
+        Some(index) => {
+            collapsed.remove(index);
+        }
+        None => collapsed.push(group.to_string()),
+    }
+    collapsed.clone()
+}
+
+/// 规范化输入的分组名称（去掉首尾空白，留空表示取消分组）
+pub fn normalize(input: &str) -> Option<String> {
+    let group = input.trim();
+    (!group.is_empty()).then(|| group.to_string())
+}
+
+/// 按分组排列账户行
+///
+/// `rows` 中已有的标题行会被丢弃后重新生成。没有任何账户设置分组时不插入标题行；
+/// 否则各组按首个账户出现的顺序排列，组内保持原有顺序，"其他"组排在最后
+pub fn arrange(rows: Vec<Account>, collapsed: &[String]) -> Vec<Account> {
+    let accounts: Vec<Account> = rows.into_iter().filter(|row| !row.header).collect();
+    if accounts.iter().all(|row| row.group.is_empty()) {
+        return accounts
+            .into_iter()
+            .map(|row| Account {
+                collapsed: false,
+                ..row
+            })
+            .collect();
+    }
+
+    let mut order: Vec<&str> = Vec::new();
+    for row in &accounts {
+        if !row.group.is_empty() && !order.contains(&row.group.as_str()) {
+            order.push(row.group.as_str());
+        }
+    }
+    order.push("");
+
+    let mut arranged = Vec::with_capacity(accounts.len() + order.len());
+    for group in order {
+        let members: Vec<&Account> = accounts.iter().filter(|row| row.group == group).collect();
+        if members.is_empty() {
+            continue;
+        }
+        let folded = collapsed.iter().any(|g| g == group);
+        arranged.push(Account {
+            group: group.into(),
+            header: true,
+            collapsed: folded,
+            unread_count: unread_sum(members.iter().copied()),
+            ..Account::default()
+        });
+        arranged.extend(members.into_iter().map(|row| Account {
+            collapsed: folded,
+            ..row.clone()
+        }));
+    }
+    arranged
+}
+
+/// 重新计算各标题行的未读合计，返回合计有变化的标题行（位置与新内容）
+pub fn summarize(rows: &[Account]) -> Vec<(usize, Account)> {
+    rows.iter()
+        .enumerate()
+        .filter(|(_, row)| row.header)
+        .filter_map(|(index, header)| {
+            let members = rows[index + 1..].iter().take_while(|row| !row.header);
+            let sum = unread_sum(members);
+            (header.unread_count != sum).then(|| {
+                (
+                    index,
+                    Account {
+                        unread_count: sum,
+                        ..header.clone()
+                    },
+                )
+            })
+        })
+        .collect()
+}
+
+/// 组内未读合计（免打扰中的账户不计入，与窗口标题栏的合计一致）
+fn unread_sum<'a>(members: impl Iterator<Item = &'a Account>) -> i32 {
+    members
+        .filter(|row| !row.snoozed)
+        .map(|row| row.unread_count.max(0))
+        .sum()
+}
+
+/// 行高（折叠组中的账户行为 0）
+pub fn row_height(row: &Account, compact: bool) -> f32 {
+    if row.header {
+        HEADER_HEIGHT
+    } else if row.collapsed {
+        0.0
+    } else if compact {
+        COMPACT_ROW_HEIGHT
+    } else {
+        ROW_HEIGHT
+    }
+}
+
+/// 全部行的总高度
+pub fn rows_height(rows: &[Account], compact: bool) -> f32 {
+    rows.iter().map(|row| row_height(row, compact)).sum()
+}
+
+/// 行顶部在列表中的位置
+pub fn row_top(rows: &[Account], index: usize, compact: bool) -> f32 {
+    rows.iter()
+        .take(index)
+        .map(|row| row_height(row, compact))
+        .sum()
+}
+
+/// 键盘导航：从 `current` 向前（`step` 为 1）或向后（-1）找到下一个可见行，到达两端时循环
+///
+/// `current` 为 -1 表示还没有焦点行；没有可见行时返回 -1
+pub fn next_row(rows: &[Account], current: i32, step: i32) -> i32 {
+    let count = rows.len() as i32;
+    if count == 0 {
+        return -1;
+    }
+    let step = if step < 0 { -1 } else { 1 };
+    let mut index = if current < 0 && step < 0 {
+        count
+    } else {
+        current
+    };
+    for _ in 0..count {
+        index = (index + step).rem_euclid(count);
+        // 行是否可见与紧凑模式无关
+        if row_height(&rows[index as usize], false) > 0.0 {
+            return index;
+        }
+    }
+    -1
+}
+
+/// 重新排列后原来的焦点行所在的位置
+///
+/// 账户行按邮箱、标题行按组名查找；账户所在的组已折叠时落在该组的标题行上，找不到时返回 -1
+pub fn locate(rows: &[Account], previous: &Account) -> i32 {
+    let found = if previous.header {
+        rows.iter()
+            .position(|row| row.header && row.group == previous.group)
+    } else {
+        rows.iter()
+            .position(|row| !row.header && row.email == previous.email)
+            .map(|index| {
+                if rows[index].collapsed {
+                    rows[..index]
+                        .iter()
+                        .rposition(|row| row.header)
+                        .unwrap_or(index)
+                } else {
+                    index
+                }
+            })
+    };
+    found.map_or(-1, |index| index as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(email: &str, group: &str, unread: i32) -> Account {
+        Account {
+            email: email.into(),
+            group: group.into(),
+            unread_count: unread,
+            ..Account::default()
+        }
+    }
+
+    /// 每行的简写：标题行为 `#组名(合计)`，折叠的账户行加 `-` 前缀
+    fn layout(rows: &[Account]) -> Vec<String> {
+        rows.iter()
+            .map(|row| match (row.header, row.collapsed) {
+                (true, _) => format!("#{}({})", row.group, row.unread_count),
+                (false, true) => format!("-{}", row.email),
+                (false, false) => row.email.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_no_groups_no_headers() {
+        let rows = arrange(vec![account("a", "", 1), account("b", "", 2)], &[]);
+        assert_eq!(layout(&rows), ["a", "b"]);
+        assert_eq!(rows_height(&rows, false), 2.0 * ROW_HEIGHT);
+        assert_eq!(rows_height(&rows, true), 2.0 * COMPACT_ROW_HEIGHT);
+    }
+
+    #[test]
+    fn test_groups_in_first_appearance_order_other_last() {
+        let rows = arrange(
+            vec![
+                account("a", "", 1),
+                account("b", "Work", 2),
+                account("c", "Personal", 3),
+                account("d", "Work", 4),
+            ],
+            &[],
+        );
+        assert_eq!(
+            layout(&rows),
+            ["#Work(6)", "b", "d", "#Personal(3)", "c", "#(1)", "a"]
+        );
+
+        // 重新排列时丢弃旧的标题行
+        let again = arrange(rows.clone(), &[]);
+        assert_eq!(layout(&again), layout(&rows));
+    }
+
+    #[test]
+    fn test_collapsed_group_hides_members() {
+        let rows = arrange(
+            vec![account("a", "Work", 1), account("b", "", 2)],
+            &["Work".to_string()],
+        );
+        assert_eq!(layout(&rows), ["#Work(1)", "-a", "#(2)", "b"]);
+        assert_eq!(rows_height(&rows, false), 2.0 * HEADER_HEIGHT + ROW_HEIGHT);
+        assert_eq!(row_top(&rows, 3, false), 2.0 * HEADER_HEIGHT);
+
+        // 紧凑模式只改变账户行的高度，标题行与折叠的行不变
+        assert_eq!(
+            rows_height(&rows, true),
+            2.0 * HEADER_HEIGHT + COMPACT_ROW_HEIGHT
+        );
+        assert_eq!(
+            row_top(&rows, 4, true),
+            2.0 * HEADER_HEIGHT + COMPACT_ROW_HEIGHT
+        );
+
+        // 最后一个分组被取消后，折叠标记随标题行一起消失
+        let ungrouped = arrange(
+            rows.into_iter()
+                .map(|row| Account {
+                    group: "".into(),
+                    ..row
+                })
+                .collect(),
+            &["Work".to_string()],
+        );
+        assert_eq!(layout(&ungrouped), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_summarize_skips_snoozed_and_reports_changes() {
+        let mut rows = arrange(vec![account("a", "Work", 1), account("b", "Work", 2)], &[]);
+        assert!(summarize(&rows).is_empty());
+
+        rows[1].unread_count = 5;
+        rows[2].snoozed = true;
+        let changed = summarize(&rows);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].0, 0);
+        assert_eq!(changed[0].1.unread_count, 5);
+    }
+
+    #[test]
+    fn test_next_row_skips_hidden_rows_and_wraps() {
+        let rows = arrange(
+            vec![account("a", "Work", 0), account("b", "", 0)],
+            &["Work".to_string()],
+        );
+        // #Work, -a, #, b
+        assert_eq!(next_row(&rows, -1, 1), 0);
+        assert_eq!(next_row(&rows, 0, 1), 2);
+        assert_eq!(next_row(&rows, 3, 1), 0);
+        assert_eq!(next_row(&rows, -1, -1), 3);
+        assert_eq!(next_row(&rows, 2, -1), 0);
+        assert_eq!(next_row(&[], -1, 1), -1);
+    }
+
+    #[test]
+    fn test_locate_falls_back_to_header() {
+        let open = arrange(vec![account("a", "Work", 0), account("b", "", 0)], &[]);
+        let folded = arrange(open.clone(), &["Work".to_string()]);
+
+        assert_eq!(locate(&folded, &open[1]), 0);
+        assert_eq!(locate(&folded, &open[3]), 3);
+        assert_eq!(locate(&folded, &open[2]), 2);
+        assert_eq!(locate(&folded, &account("gone", "", 0)), -1);
+    }
+
+    #[test]
+    fn test_toggle_and_normalize() {
+        set_collapsed(&["Work".to_string()]);
+        assert_eq!(toggle(""), ["Work", ""]);
+        assert_eq!(toggle("Work"), [""]);
+        assert_eq!(collapsed(), [""]);
+        set_collapsed(&[]);
+
+        assert_eq!(normalize("  Work "), Some("Work".to_string()));
+        assert_eq!(normalize("   "), None);
+    }
+}
@@ -0,0 +1,123 @@
+/// 同步记录页面
+///
+/// 把 `sync::history` 的记录转换为 Slint 的 `HistoryRound`：相对时间（"3 分钟前"）与本地时间并列显示，
+/// 各账户的结果显示为带颜色的标记（标记上是邮箱 @ 之前的部分，颜色见 sync_history.slint）
+use std::sync::Arc;
+
+use chrono::{DateTime, Local, Utc};
+use slint::{ModelRc, VecModel};
+
+use crate::sync::history::{self, RoundRecord};
+use crate::utils::i18n::{tr, tr_fmt};
+use crate::{HistoryChip, HistoryRound};
+
+/// 相对时间（不足 1 分钟显示"刚刚"）
+pub fn relative_time(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now - at;
+    if age.num_minutes() < 1 {
+        tr("history.just_now").to_string()
+    } else if age.num_hours() < 1 {
+        tr_fmt("history.minutes_ago", &[&age.num_minutes()])
+    } else if age.num_days() < 1 {
+        tr_fmt("history.hours_ago", &[&age.num_hours()])
+    } else {
+        tr_fmt("history.days_ago", &[&age.num_days()])
+    }
+}
+
+/// 邮箱 @ 之前的部分
+fn account_label(email: &str) -> &str {
+    email.split('@').next().unwrap_or(email)
+}
+
+/// 转换一轮同步的记录
+pub fn history_round(round: &RoundRecord, now: DateTime<Utc>) -> HistoryRound {
+    let chips: Vec<HistoryChip> = round
+        .accounts
+        .iter()
+        .map(|account| HistoryChip {
+            label: account_label(&account.email).into(),
+            outcome: account.outcome.as_str().into(),
+        })
+        .collect();
+
+    HistoryRound {
+        when: relative_time(round.started_at, now).into(),
+        clock: round
+            .started_at
+            .with_timezone(&Local)
+            .format("%H:%M")
+            .to_string()
+            .into(),
+        duration: tr_fmt("history.duration_ms", &[&round.duration_ms]).into(),
+        unread: round.unread.min(i32::MAX as u32) as i32,
+        chips: ModelRc::new(VecModel::from(chips)),
+    }
+}
+
+/// 转换全部记录（保持最新的在前）
+pub fn history_rounds(rounds: &[Arc<RoundRecord>], now: DateTime<Utc>) -> Vec<HistoryRound> {
+    rounds
+        .iter()
+        .map(|round| history_round(round, now))
+        .collect()
+}
+
+/// 读取最新的同步记录并填入窗口（须在 UI 线程调用）
+pub fn apply_sync_history(window: &crate::MainWindow) {
+    let rounds = history_rounds(&history::snapshot(), Utc::now());
+    window.set_sync_history(ModelRc::new(VecModel::from(rounds)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::history::{AccountOutcome, AccountResult};
+    use slint::Model;
+
+    #[test]
+    fn test_relative_time() {
+        let now = Utc::now();
+        let ago = |minutes| relative_time(now - chrono::Duration::minutes(minutes), now);
+        assert_eq!(ago(0), tr("history.just_now"));
+        assert_eq!(ago(3), tr_fmt("history.minutes_ago", &[&3]));
+        assert_eq!(ago(125), tr_fmt("history.hours_ago", &[&2]));
+        assert_eq!(ago(3 * 24 * 60), tr_fmt("history.days_ago", &[&3]));
+    }
+
+    #[test]
+    fn test_history_round_chips() {
+        let now = Utc::now();
+        let round = RoundRecord {
+            started_at: now - chrono::Duration::minutes(5),
+            duration_ms: 420,
+            accounts: vec![
+                AccountResult {
+                    email: "alice@example.com".to_string(),
+                    outcome: AccountOutcome::Synced,
+                },
+                AccountResult {
+                    email: "bob@example.com".to_string(),
+                    outcome: AccountOutcome::Offline,
+                },
+            ],
+            unread: 12,
+        };
+
+        let row = history_round(&round, now);
+        assert_eq!(row.unread, 12);
+        assert!(row.duration.contains("420"));
+        let chips: Vec<(String, String)> = row
+            .chips
+            .iter()
+            .map(|chip| (chip.label.to_string(), chip.outcome.to_string()))
+            .collect();
+        assert_eq!(
+            chips,
+            [
+                ("alice".to_string(), "synced".to_string()),
+                ("bob".to_string(), "offline".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,322 @@
+// 弹出窗口位置计算
+//
+// 只做纯计算：工作区、窗口尺寸与锚点由托盘模块收集后传入（均为物理像素），
+// 便于针对任务栏在各个边、锚点部分超出屏幕等情况编写测试
+
+/// 坐标点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// 尺寸
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    pub width: i32,
+    pub height: i32,
+}
+
+/// 矩形区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+}
+
+/// 窗口停靠的参照点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorPoint {
+    /// 工作区右下角（不知道托盘图标位置时）
+    BottomRight,
+
+    /// 托盘图标的位置（点击托盘时的鼠标坐标）
+    At(Point),
+}
+
+/// 任务栏所在的边（由锚点相对工作区的位置推断）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// 计算弹出窗口的左上角位置
+///
+/// - `BottomRight`：贴住工作区右下角，与右、下边缘保持 `margin`
+/// - `At`：锚点在哪条边外（或离哪条边最近），就把窗口贴在工作区的那条边上，
+///   并沿该边对齐锚点（以锚点为中心）
+///
+/// 结果总是落在工作区内：靠右、靠下时保留 `margin`；窗口比工作区还大时贴住左上角，
+/// 保证标题栏与账户列表顶部可见
+pub fn compute_popup_position(
+    work_area: Rect,
+    window_size: Size,
+    anchor: AnchorPoint,
+    margin: i32,
+) -> Point {
+    let right = work_area.right() - window_size.width - margin;
+    let bottom = work_area.bottom() - window_size.height - margin;
+
+    let (x, y) = match anchor {
+        AnchorPoint::BottomRight => (right, bottom),
+        AnchorPoint::At(point) => {
+            let centered_x = point.x - window_size.width / 2;
+            let centered_y = point.y - window_size.height / 2;
+            match nearest_edge(work_area, point) {
+                Edge::Bottom => (centered_x, bottom),
+                Edge::Top => (centered_x, work_area.y + margin),
+                Edge::Left => (work_area.x + margin, centered_y),
+                Edge::Right => (right, centered_y),
+            }
+        }
+    };
+
+    Point {
+        x: clamp_axis(x, work_area.x, right),
+        y: clamp_axis(y, work_area.y, bottom),
+    }
+}
+
+/// 锚点所在（或最近）的工作区边缘
+///
+/// 托盘图标位于任务栏上，通常在工作区之外；自动隐藏的任务栏会让锚点落在工作区内，
+/// 此时取距离最近的边
+fn nearest_edge(area: Rect, point: Point) -> Edge {
+    let distances = [
+        (Edge::Bottom, point.y - area.bottom()),
+        (Edge::Top, area.y - point.y),
+        (Edge::Right, point.x - area.right()),
+        (Edge::Left, area.x - point.x),
+    ];
+
+    // 距离为正表示在该边之外；取最大值即"最靠外"的边（并列时按上面的顺序优先）
+    distances
+        .into_iter()
+        .fold(
+            None,
+            |best: Option<(Edge, i32)>, (edge, distance)| match best {
+                Some((_, best_distance)) if best_distance >= distance => best,
+                _ => Some((edge, distance)),
+            },
+        )
+        .map_or(Edge::Bottom, |(edge, _)| edge)
+}
+
+/// 把坐标限制在 `[start, end]` 内（`end` 小于 `start` 时窗口放不下，贴住起点）
+fn clamp_axis(value: i32, start: i32, end: i32) -> i32 {
+    value.min(end).max(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARGIN: i32 = 12;
+
+    /// 1920x1080 屏幕，底部 40 像素任务栏
+    const BOTTOM_BAR: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1040,
+    };
+
+    const WINDOW: Size = Size {
+        width: 380,
+        height: 400,
+    };
+
+    fn at(x: i32, y: i32) -> AnchorPoint {
+        AnchorPoint::At(Point { x, y })
+    }
+
+    #[test]
+    fn test_bottom_right_default() {
+        let position = compute_popup_position(BOTTOM_BAR, WINDOW, AnchorPoint::BottomRight, MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 1920 - 380 - 12,
+                y: 1040 - 400 - 12
+            }
+        );
+    }
+
+    #[test]
+    fn test_bottom_taskbar_centers_on_anchor() {
+        let position = compute_popup_position(BOTTOM_BAR, WINDOW, at(1000, 1060), MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 1000 - 190,
+                y: 1040 - 400 - 12
+            }
+        );
+    }
+
+    #[test]
+    fn test_top_taskbar() {
+        let area = Rect {
+            x: 0,
+            y: 40,
+            width: 1920,
+            height: 1040,
+        };
+        let position = compute_popup_position(area, WINDOW, at(1800, 20), MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 1920 - 380 - 12,
+                y: 40 + 12
+            }
+        );
+    }
+
+    #[test]
+    fn test_left_taskbar() {
+        let area = Rect {
+            x: 60,
+            y: 0,
+            width: 1860,
+            height: 1080,
+        };
+        let position = compute_popup_position(area, WINDOW, at(30, 600), MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 60 + 12,
+                y: 600 - 200
+            }
+        );
+    }
+
+    #[test]
+    fn test_right_taskbar() {
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 1860,
+            height: 1080,
+        };
+        let position = compute_popup_position(area, WINDOW, at(1890, 500), MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 1860 - 380 - 12,
+                y: 500 - 200
+            }
+        );
+    }
+
+    #[test]
+    fn test_anchor_near_corner_stays_inside() {
+        // 托盘图标在右下角：窗口不能越过右边缘
+        let position = compute_popup_position(BOTTOM_BAR, WINDOW, at(1910, 1060), MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 1920 - 380 - 12,
+                y: 1040 - 400 - 12
+            }
+        );
+
+        // 右侧任务栏的图标在底部：窗口不能越过下边缘
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 1860,
+            height: 1080,
+        };
+        let position = compute_popup_position(area, WINDOW, at(1890, 1075), MARGIN);
+        assert_eq!(position.y, 1080 - 400 - 12);
+    }
+
+    #[test]
+    fn test_anchor_off_screen() {
+        // 锚点在另一块显示器上（远在工作区左侧）：贴住左边缘
+        let position = compute_popup_position(BOTTOM_BAR, WINDOW, at(-2000, 500), MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 12,
+                y: 500 - 200
+            }
+        );
+
+        // 锚点在工作区下方很远处，横坐标超出左边缘
+        let position = compute_popup_position(BOTTOM_BAR, WINDOW, at(-50, 5000), MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 0,
+                y: 1040 - 400 - 12
+            }
+        );
+    }
+
+    #[test]
+    fn test_anchor_inside_work_area_uses_nearest_edge() {
+        // 自动隐藏的任务栏：锚点落在工作区内，靠近底边
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        };
+        let position = compute_popup_position(area, WINDOW, at(960, 1075), MARGIN);
+        assert_eq!(
+            position,
+            Point {
+                x: 960 - 190,
+                y: 1080 - 400 - 12
+            }
+        );
+    }
+
+    #[test]
+    fn test_tiny_work_area_pins_top_left() {
+        let area = Rect {
+            x: 100,
+            y: 50,
+            width: 300,
+            height: 200,
+        };
+        for anchor in [AnchorPoint::BottomRight, at(250, 400), at(0, 0)] {
+            assert_eq!(
+                compute_popup_position(area, WINDOW, anchor, MARGIN),
+                Point { x: 100, y: 50 },
+                "anchor: {:?}",
+                anchor
+            );
+        }
+    }
+
+    #[test]
+    fn test_window_fits_only_without_margin() {
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 390,
+            height: 405,
+        };
+        assert_eq!(
+            compute_popup_position(area, WINDOW, AnchorPoint::BottomRight, MARGIN),
+            Point { x: 0, y: 0 }
+        );
+    }
+}
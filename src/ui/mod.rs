@@ -1,9 +1,109 @@
 // UI 模块 - Rust-Slint 数据桥接
 
-use slint::{Image, SharedString};
+pub mod coalesce;
+pub mod groups;
+pub mod history;
+pub mod layout;
 
-/// 编译时嵌入占位头像（避免运行时依赖外部文件）
-const PLACEHOLDER_AVATAR_BYTES: &[u8] = include_bytes!("../../assets/icons/placeholder-avatar.svg");
+use chrono::{DateTime, Local, Utc};
+use slint::{ComponentHandle, ModelRc, SharedString, Timer, TimerMode, VecModel};
+use std::cell::RefCell;
+use std::time::Duration;
+
+use crate::config::oauth_config::OAuthConfig;
+use crate::config::state::{self, HISTORY_WINDOW, SyncSnapshot, UnreadSample};
+use crate::config::storage::StoredAccount;
+use crate::mail::gmail::labels::{Label, LabelPlan};
+use crate::mail::oauth::{OAuthAccount, reauth};
+use crate::mail::{PeopleFilter, VacationInfo, gmail, quota};
+use crate::utils::accessibility;
+use crate::utils::i18n::{tr, tr_fmt};
+
+/// 未读数走势图的坐标范围（与 account_card.slint 中 Path 的 viewbox 一致）
+const SPARKLINE_WIDTH: f64 = 100.0;
+const SPARKLINE_HEIGHT: f64 = 20.0;
+
+/// 提示条显示时长
+const SNACKBAR_DURATION: Duration = Duration::from_secs(4);
+
+thread_local! {
+    /// 提示条自动隐藏定时器（重复显示时重新计时）
+    static SNACKBAR_TIMER: Timer = Timer::default();
+
+    /// 提示条按钮的操作（显示新的提示条或提示条隐藏后失效）
+    static SNACKBAR_ACTION: RefCell<Option<Box<dyn FnOnce()>>> = RefCell::new(None);
+}
+
+/// 显示底部提示条，到时自动隐藏（须在 UI 线程调用）
+pub fn show_snackbar(window: &crate::MainWindow, text: &str) {
+    SNACKBAR_ACTION.with(|slot| slot.borrow_mut().take());
+    window.set_snackbar_action(SharedString::new());
+    show_snackbar_for(window, text, SNACKBAR_DURATION);
+}
+
+/// 显示带操作按钮（如"撤销"）的提示条，`duration` 后自动隐藏；点击按钮时执行 `action`
+pub fn show_snackbar_with_action(
+    window: &crate::MainWindow,
+    text: &str,
+    action_text: &str,
+    duration: Duration,
+    action: impl FnOnce() + 'static,
+) {
+    SNACKBAR_ACTION.with(|slot| *slot.borrow_mut() = Some(Box::new(action)));
+    window.set_snackbar_action(action_text.into());
+    show_snackbar_for(window, text, duration);
+}
+
+/// 执行提示条按钮的操作并隐藏提示条（绑定到 `snackbar-action-clicked` 回调）
+pub fn run_snackbar_action(window: &crate::MainWindow) {
+    window.set_snackbar_visible(false);
+    if let Some(action) = SNACKBAR_ACTION.with(|slot| slot.borrow_mut().take()) {
+        action();
+    }
+}
+
+fn show_snackbar_for(window: &crate::MainWindow, text: &str, duration: Duration) {
+    window.set_snackbar_text(text.into());
+    window.set_snackbar_visible(true);
+
+    let weak = window.as_weak();
+    SNACKBAR_TIMER.with(|timer| {
+        timer.start(TimerMode::SingleShot, duration, move || {
+            SNACKBAR_ACTION.with(|slot| slot.borrow_mut().take());
+            if let Some(window) = weak.upgrade() {
+                window.set_snackbar_visible(false);
+            }
+        });
+    });
+}
+
+/// 应用系统辅助功能设置（高对比度配色、关闭过渡动画）
+pub fn apply_accessibility(window: &crate::MainWindow, settings: accessibility::Settings) {
+    let globals = window.global::<crate::Accessibility>();
+    globals.set_high_contrast(settings.high_contrast);
+    globals.set_reduce_motion(settings.reduce_motion);
+}
+
+/// 账户行的同步状态（对应 Slint Account 的 `sync-state`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncState {
+    /// 启动或添加后尚未收到同步结果（徽章显示"—"，不计入合计）
+    NeverSynced,
+    /// 最近一次同步成功（显示未读数）
+    Synced,
+    /// 最近一次同步失败
+    Error,
+}
+
+impl SyncState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NeverSynced => "never",
+            Self::Synced => "synced",
+            Self::Error => "error",
+        }
+    }
+}
 
 /// Account 结构体（对应 Slint 的 Account struct）
 #[derive(Clone, Debug)]
@@ -44,40 +144,533 @@ impl Account {
     }
 }
 
-/// 加载占位头像（从嵌入的资源）
-fn load_placeholder_avatar() -> Image {
-    match Image::load_from_svg_data(PLACEHOLDER_AVATAR_BYTES) {
-        Ok(img) => img,
-        Err(e) => {
-            tracing::warn!("加载嵌入的占位头像失败: {}", e);
-            Image::default()
-        }
-    }
-}
-
 /// 将 Rust Account 转换为 Slint Account
 impl From<Account> for crate::Account {
     fn from(account: Account) -> Self {
         // 尝试将本地路径转换为 Slint Image；失败时使用嵌入的占位图
-        let avatar_image: Image = if account.avatar_url.is_empty() {
-            load_placeholder_avatar()
-        } else {
-            match Image::load_from_path(std::path::Path::new(&account.avatar_url)) {
-                Ok(img) => img,
-                Err(e) => {
-                    tracing::warn!("加载头像失败 [{}]: {}", account.avatar_url, e);
-                    load_placeholder_avatar()
-                }
-            }
-        };
+        let avatar_image = crate::utils::avatar::load_avatar_from_path(&account.avatar_url);
 
         Self {
+            accent: crate::utils::avatar::accent_color(&account.email, None),
+            accent_choice: SharedString::from("auto"),
             email: SharedString::from(account.email),
             display_name: SharedString::from(account.display_name),
             avatar_image,
             unread_count: account.unread_count,
             is_loading: account.is_loading,
-            has_error: account.has_error,
+            sync_state: if account.has_error {
+                SyncState::Error
+            } else {
+                SyncState::Synced
+            }
+            .as_str()
+            .into(),
+            count_mode: SharedString::new(),
+            snoozed: false,
+            sparkline: SharedString::new(),
+            sparkline_tip: SharedString::new(),
+            notice: SharedString::new(),
+            watch_spam: SharedString::new(),
+            spam_caption: SharedString::new(),
+            people_only: SharedString::new(),
+            filter_caption: SharedString::new(),
+            watch_vacation: SharedString::new(),
+            vacation: SharedString::new(),
+            push: SharedString::new(),
+            cached: false,
+            stale: false,
+            alert_threshold: 0,
+            reauth_reminder: SharedString::new(),
+            group: SharedString::new(),
+            header: false,
+            collapsed: false,
+        }
+    }
+}
+
+/// 按运行时状态中的未读数历史填充账户行的走势图与最低/最高提示
+pub fn apply_unread_history(account: &mut crate::Account) {
+    let history = state::global().account(&account.email).history;
+    let now = Utc::now();
+
+    account.sparkline = sparkline_commands(&history, now).into();
+    account.sparkline_tip = match unread_range(&history) {
+        Some((min, max)) if !account.sparkline.is_empty() => {
+            crate::utils::i18n::tr_fmt("sparkline.range", &[&min, &max]).into()
+        }
+        _ => SharedString::new(),
+    };
+}
+
+/// 按运行时状态中上次同步的结果填充账户行（显示"更新中"，直到本轮同步结果到达）
+///
+/// 超过 `max_age` 的结果徽章灰显；没有记录（从未同步过或状态文件无法读取）时保持未读数 0 与原头像
+pub fn apply_cached_sync(account: &mut crate::Account, max_age: chrono::Duration) {
+    if let Some(snapshot) = state::global().account(&account.email).last_sync {
+        apply_snapshot(account, &snapshot, Utc::now(), max_age);
+    }
+}
+
+fn apply_snapshot(
+    account: &mut crate::Account,
+    snapshot: &SyncSnapshot,
+    now: DateTime<Utc>,
+    max_age: chrono::Duration,
+) {
+    account.unread_count = snapshot.unread as i32;
+    account.sync_state = SyncState::Synced.as_str().into();
+    account.cached = true;
+    account.stale = snapshot.is_stale(now, max_age);
+
+    // 用户重命名过的名称优先
+    if account.display_name.is_empty() {
+        account.display_name = snapshot.display_name.as_str().into();
+    }
+
+    // 头像通常就是按邮箱缓存的缩略图（转换账户时已加载），只在不同且文件存在时重新加载
+    let cached_avatar = crate::utils::avatar::get_cached_avatar_path(account.email.as_str());
+    if std::path::Path::new(&snapshot.avatar_path).is_file()
+        && cached_avatar.as_deref() != Some(snapshot.avatar_path.as_str())
+    {
+        account.avatar_image = crate::utils::avatar::load_avatar_from_path(&snapshot.avatar_path);
+    }
+}
+
+/// 账户行的开关选项，如垃圾邮件监视（`None` 表示账户不支持，菜单中不显示该项）
+pub fn toggle_setting(enabled: Option<bool>) -> &'static str {
+    match enabled {
+        Some(true) => "on",
+        Some(false) => "off",
+        None => "",
+    }
+}
+
+/// 账户菜单中的自动回复提示设置："on" / "off"，没有读取设置的权限时为 "no-scope"（显示为不可用），
+/// 账户不支持时为空（不显示）
+pub fn vacation_setting(account: &StoredAccount) -> &'static str {
+    match account.watch_vacation() {
+        Some(_) if !account.can_watch_vacation() => "no-scope",
+        watch => toggle_setting(watch),
+    }
+}
+
+/// 账户菜单中的实时推送设置："on" / "off"，没有完整邮箱权限时为 "no-scope"（显示为不可用），
+/// 账户不支持时为空（不显示）
+pub fn push_setting(account: &StoredAccount) -> &'static str {
+    match account.push() {
+        Some(_) if !account.can_push() => "no-scope",
+        push => toggle_setting(push),
+    }
+}
+
+/// 账户行自动回复标记的提示（自动回复未生效时为空，不显示标记）
+pub fn vacation_tip(vacation: &VacationInfo) -> String {
+    match vacation.subject.as_str() {
+        _ if !vacation.active => String::new(),
+        "" => tr("account.vacation_on").to_string(),
+        subject => tr_fmt("account.vacation_subject", &[&subject]),
+    }
+}
+
+/// 账户行的未读提醒阈值（Slint 端以 0 表示未设置）
+pub fn alert_threshold_setting(threshold: Option<u32>) -> i32 {
+    threshold.map_or(0, |threshold| {
+        i32::try_from(threshold).unwrap_or(i32::MAX)
+    })
+}
+
+/// 解析详情面板中输入的未读提醒阈值：留空表示不提醒，否则须为正整数（错误时返回提示文字）
+pub fn parse_alert_threshold(input: &str) -> Result<Option<u32>, &'static str> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    match input.parse::<u32>() {
+        Ok(threshold) if threshold > 0 => Ok(Some(threshold)),
+        _ => Err(tr("details.alert_invalid")),
+    }
+}
+
+/// 账户行下方的垃圾邮件说明（没有未读的垃圾邮件时为空，不显示）
+pub fn spam_caption(spam_unread: u32) -> String {
+    if spam_unread == 0 {
+        String::new()
+    } else {
+        tr_fmt("account.spam", &[&spam_unread])
+    }
+}
+
+/// 账户行下方的过滤说明（开启了"只统计真人来信"但本轮未能过滤时显示，否则为空）
+pub fn filter_caption(filter: Option<PeopleFilter>) -> String {
+    match filter {
+        Some(PeopleFilter::Unfiltered) => {
+            tr_fmt("account.unfiltered", &[&gmail::people::FILTER_CEILING])
+        }
+        Some(PeopleFilter::Applied) | None => String::new(),
+    }
+}
+
+/// 账户详情面板中的 Token 信息（只读取元数据，不显示任何 Token）
+pub fn token_details(account: &OAuthAccount, now: DateTime<Utc>) -> crate::TokenDetails {
+    let local_time = |at: DateTime<Utc>| {
+        at.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+
+    crate::TokenDetails {
+        email: account.email.as_str().into(),
+        expires_at: local_time(account.expires_at).into(),
+        remaining: remaining_text(account.expires_at, now).into(),
+        expired: account.expires_at <= now,
+        has_refresh_token: account
+            .decrypt_refresh_token()
+            .is_ok_and(|token| !token.is_empty()),
+        refreshed_at: account
+            .refreshed_at
+            .map(local_time)
+            .unwrap_or_default()
+            .into(),
+        refresh_token_issued_at: account
+            .refresh_token_issued_at
+            .map(|at| {
+                tr_fmt(
+                    "details.issued_value",
+                    &[&local_time(at), &reauth::age_text(at, now)],
+                )
+            })
+            .unwrap_or_default()
+            .into(),
+        reauth_reminder: SharedString::new(),
+        scopes: account.scopes.join("\n").into(),
+        mail_access: SharedString::new(),
+        api_usage: SharedString::new(),
+        alert_threshold: account
+            .alert_threshold
+            .map(|threshold| threshold.to_string())
+            .unwrap_or_default()
+            .into(),
+        group: account.group.clone().unwrap_or_default().into(),
+        labels: ModelRc::default(),
+        badge_label_index: 0,
+        notify_label_index: 0,
+    }
+}
+
+/// Gmail 标签选择的选项：已读取的标签在前，列表中没有的当前标签（尚未读取列表或标签已被删除）按 ID 补在末尾
+pub fn label_choices(available: &[Label], plan: &LabelPlan) -> Vec<Label> {
+    let mut choices = available.to_vec();
+    let notify = plan.notify.as_deref().unwrap_or(&plan.badge);
+    for id in [plan.badge.as_str(), notify] {
+        if !choices.iter().any(|label| label.id == id) {
+            choices.push(Label {
+                id: id.to_string(),
+                name: id.to_string(),
+                kind: String::new(),
+            });
+        }
+    }
+    choices
+}
+
+/// 在详情中填写标签选择（收件箱显示为本地化名称）
+pub fn set_label_pickers(details: &mut crate::TokenDetails, choices: &[Label], plan: &LabelPlan) {
+    let position = |id: &str| {
+        choices
+            .iter()
+            .position(|label| label.id == id)
+            .map_or(0, |index| index as i32)
+    };
+    let names: Vec<SharedString> = choices
+        .iter()
+        .map(|label| match label.id.as_str() {
+            gmail::labels::INBOX => tr("details.label_inbox").into(),
+            _ => label.name.as_str().into(),
+        })
+        .collect();
+    details.labels = ModelRc::new(VecModel::from(names));
+    details.badge_label_index = position(&plan.badge);
+    details.notify_label_index = position(plan.notify.as_deref().unwrap_or(&plan.badge));
+}
+
+/// OAuth 客户端处于测试状态、Refresh Token 临近到期时的重新授权提醒（不需要提醒时为空）
+///
+/// 只有 Gmail 账户参考 `[oauth]` 中的 `testing_mode` 设置，其他 OAuth 账户只看是否检测到测试状态
+pub fn reauth_reminder(account: &StoredAccount, now: DateTime<Utc>) -> String {
+    let due = match account {
+        StoredAccount::Gmail(gmail) => {
+            gmail.reauth_due(gmail.testing_client || OAuthConfig::testing_mode())
         }
+        StoredAccount::Outlook(outlook) => outlook.reauth_due(false),
+        StoredAccount::Imap(_) => None,
+    };
+    due.and_then(|due| reauth::countdown(due, now))
+        .map(reauth::Countdown::label)
+        .unwrap_or_default()
+}
+
+/// 账户的邮件权限说明（只有 Gmail 区分只读与可修改，其他账户为空）
+pub fn mail_access(account: &StoredAccount) -> String {
+    match account {
+        StoredAccount::Gmail(gmail) if gmail::oauth::can_modify(gmail) => {
+            tr("details.can_modify").to_string()
+        }
+        StoredAccount::Gmail(_) => tr("details.read_only").to_string(),
+        _ => String::new(),
+    }
+}
+
+/// 账户的 API 请求数（目前只统计 Gmail，其他账户为空）
+pub fn api_usage(account: &StoredAccount) -> String {
+    match account {
+        StoredAccount::Gmail(gmail) => usage_text(quota::usage(&gmail.email)),
+        _ => String::new(),
+    }
+}
+
+/// API 请求数的说明文字
+fn usage_text(usage: quota::Usage) -> String {
+    tr_fmt(
+        "details.api_usage_value",
+        &[&usage.last_minute, &usage.last_day],
+    )
+}
+
+/// 剩余有效时间（按分钟向下取整；已过期时显示过期了多久）
+fn remaining_text(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let minutes = (expires_at - now).num_minutes();
+    if expires_at > now {
+        tr_fmt("details.expires_in", &[&minutes])
+    } else {
+        tr_fmt("details.expired_ago", &[&-minutes])
+    }
+}
+
+/// 最近 24 小时未读数的最低值与最高值
+fn unread_range(history: &[UnreadSample]) -> Option<(u32, u32)> {
+    let min = history.iter().map(|s| s.unread).min()?;
+    let max = history.iter().map(|s| s.unread).max()?;
+    Some((min, max))
+}
+
+/// 生成走势图的 SVG 路径命令（横轴为最近 24 小时，纵轴按最低/最高值缩放）
+///
+/// 采样点少于两个时返回空字符串（不显示走势图）
+fn sparkline_commands(history: &[UnreadSample], now: DateTime<Utc>) -> String {
+    let Some((min, max)) = unread_range(history) else {
+        return String::new();
+    };
+    if history.len() < 2 {
+        return String::new();
+    }
+
+    let window = HISTORY_WINDOW.num_seconds() as f64;
+    let start = now - HISTORY_WINDOW;
+    let points: Vec<String> = history
+        .iter()
+        .map(|sample| {
+            let elapsed = (sample.at - start).num_seconds() as f64;
+            let x = (elapsed / window).clamp(0.0, 1.0) * SPARKLINE_WIDTH;
+            // 未读数没有变化时画在中间
+            let y = if max == min {
+                SPARKLINE_HEIGHT / 2.0
+            } else {
+                let ratio = f64::from(sample.unread - min) / f64::from(max - min);
+                SPARKLINE_HEIGHT * (1.0 - ratio)
+            };
+            format!("{:.1} {:.1}", x, y)
+        })
+        .collect();
+
+    format!("M {}", points.join(" L "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_account_with_unreadable_avatar_uses_placeholder() {
+        let account = Account {
+            avatar_url: std::env::temp_dir()
+                .join("nanomail-avatar-not-written-yet.png")
+                .display()
+                .to_string(),
+            ..Account::mock()
+        };
+
+        let row: crate::Account = account.into();
+        let size = row.avatar_image.size();
+        assert_ne!(size, slint::Image::default().size());
+        assert_eq!(size, crate::utils::avatar::load_placeholder_avatar().size());
+    }
+
+    #[test]
+    fn test_snapshot_fills_cached_row() {
+        let now = Utc::now();
+        let max_age = chrono::Duration::hours(24);
+        let snapshot = SyncSnapshot {
+            unread: 7,
+            display_name: "Me".to_string(),
+            avatar_path: String::new(),
+            synced_at: now - chrono::Duration::hours(2),
+        };
+
+        let mut row = crate::Account {
+            email: "me@example.com".into(),
+            display_name: "Work".into(),
+            ..Default::default()
+        };
+        apply_snapshot(&mut row, &snapshot, now, max_age);
+        assert_eq!(row.unread_count, 7);
+        assert_eq!(row.sync_state, SyncState::Synced.as_str());
+        assert!(row.cached);
+        assert!(!row.stale);
+        assert_eq!(row.display_name, "Work");
+
+        // 超过有效期的结果灰显
+        let old = SyncSnapshot {
+            synced_at: now - chrono::Duration::hours(30),
+            ..snapshot
+        };
+        let mut row = crate::Account::default();
+        apply_snapshot(&mut row, &old, now, max_age);
+        assert!(row.cached && row.stale);
+        assert_eq!(row.display_name, "Me");
+    }
+
+    #[test]
+    fn test_sparkline_commands() {
+        let now = Utc::now();
+        let sample = |hours_ago: i64, unread: u32| UnreadSample {
+            at: now - chrono::Duration::hours(hours_ago),
+            unread,
+        };
+
+        assert_eq!(sparkline_commands(&[], now), "");
+        assert_eq!(sparkline_commands(&[sample(1, 3)], now), "");
+
+        // 最高值在顶部，最低值在底部
+        assert_eq!(
+            sparkline_commands(&[sample(24, 10), sample(12, 0), sample(0, 5)], now),
+            "M 0.0 0.0 L 50.0 20.0 L 100.0 10.0"
+        );
+
+        // 未读数没有变化时画在中间
+        assert_eq!(
+            sparkline_commands(&[sample(12, 2), sample(0, 2)], now),
+            "M 50.0 10.0 L 100.0 10.0"
+        );
+        assert_eq!(unread_range(&[sample(12, 2), sample(0, 7)]), Some((2, 7)));
+    }
+
+    #[test]
+    fn test_token_details_show_metadata_only() {
+        let mut account = OAuthAccount::new(
+            "me@example.com".to_string(),
+            "me".to_string(),
+            "secret-access-token".to_string(),
+            "secret-refresh-token".to_string(),
+            3600,
+        )
+        .unwrap();
+        account.scopes = vec!["openid".to_string(), "email".to_string()];
+
+        let now = Utc::now();
+        let details = token_details(&account, now);
+        assert_eq!(details.email, "me@example.com");
+        assert!(details.has_refresh_token);
+        assert!(!details.expired);
+        assert_eq!(details.refreshed_at, "");
+        assert!(!details.refresh_token_issued_at.is_empty());
+        assert_eq!(details.reauth_reminder, "");
+        assert_eq!(details.scopes, "openid\nemail");
+        let shown = format!("{:?}", details);
+        assert!(!shown.contains("secret"));
+
+        account.expires_at = now - chrono::Duration::minutes(5);
+        assert!(token_details(&account, now).expired);
+    }
+
+    #[test]
+    fn test_remaining_text() {
+        let now = Utc::now();
+        assert_eq!(
+            remaining_text(now + chrono::Duration::seconds(42 * 60 + 30), now),
+            tr_fmt("details.expires_in", &[&42])
+        );
+        assert_eq!(
+            remaining_text(now - chrono::Duration::minutes(5), now),
+            tr_fmt("details.expired_ago", &[&5])
+        );
+    }
+
+    #[test]
+    fn test_parse_alert_threshold() {
+        assert_eq!(parse_alert_threshold(" 20 "), Ok(Some(20)));
+        assert_eq!(parse_alert_threshold(""), Ok(None));
+        assert!(parse_alert_threshold("0").is_err());
+        assert!(parse_alert_threshold("-3").is_err());
+        assert!(parse_alert_threshold("many").is_err());
+        assert_eq!(alert_threshold_setting(Some(20)), 20);
+        assert_eq!(alert_threshold_setting(None), 0);
+    }
+
+    #[test]
+    fn test_push_setting_requires_full_access() {
+        let mut account = gmail::types::GmailAccount::new(
+            "me@example.com".to_string(),
+            "me".to_string(),
+            "token".to_string(),
+            "refresh".to_string(),
+            3600,
+        )
+        .unwrap();
+        account.push = true;
+        // 没有记录授权范围的旧账户先尝试推送
+        assert_eq!(push_setting(&StoredAccount::Gmail(account.clone())), "on");
+
+        account.scopes = vec!["https://www.googleapis.com/auth/gmail.readonly".to_string()];
+        let stored = StoredAccount::Gmail(account.clone());
+        assert_eq!(push_setting(&stored), "no-scope");
+        assert!(!stored.push_enabled());
+
+        account.scopes.push("https://mail.google.com/".to_string());
+        let stored = StoredAccount::Gmail(account);
+        assert_eq!(push_setting(&stored), "on");
+        assert!(stored.push_enabled());
+    }
+
+    #[test]
+    fn test_label_choices_keep_current_labels() {
+        use slint::Model;
+
+        let label = |id: &str, name: &str| Label {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind: "user".to_string(),
+        };
+        let ids = |choices: Vec<Label>| choices.into_iter().map(|l| l.id).collect::<Vec<_>>();
+
+        // 尚未读取标签列表时只有当前的标签
+        let plan = LabelPlan::new("INBOX", "INBOX");
+        assert_eq!(ids(label_choices(&[], &plan)), ["INBOX"]);
+
+        let available = [label("INBOX", "INBOX"), label("Label_7", "urgent")];
+        assert_eq!(ids(label_choices(&available, &plan)), ["INBOX", "Label_7"]);
+
+        // 已被删除的通知标签补在末尾，仍显示为当前选择
+        let plan = LabelPlan::new("INBOX", "Label_9");
+        let choices = label_choices(&available, &plan);
+        assert_eq!(ids(choices.clone()), ["INBOX", "Label_7", "Label_9"]);
+
+        let mut details = crate::TokenDetails::default();
+        set_label_pickers(&mut details, &choices, &plan);
+        assert_eq!(details.badge_label_index, 0);
+        assert_eq!(details.notify_label_index, 2);
+        assert_eq!(
+            details.labels.row_data(0).as_deref(),
+            Some(tr("details.label_inbox"))
+        );
     }
 }
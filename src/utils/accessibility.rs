@@ -0,0 +1,219 @@
+/// 系统辅助功能设置模块
+///
+/// 读取系统的高对比度与"减少动画"设置，供界面切换高对比度配色并关闭过渡动画。
+/// Windows 上通过 `SystemParametersInfoW`（`SPI_GETHIGHCONTRAST` / `SPI_GETCLIENTAREAANIMATION`）读取，
+/// 并在隐藏的顶层窗口中监听 `WM_SETTINGCHANGE`，切换系统设置后无需重启即可生效；
+/// Linux 上启动时通过 `gsettings` 读取 GNOME 的对应设置，不监听变更
+#[cfg(windows)]
+use std::ffi::c_void;
+#[cfg(windows)]
+use std::sync::OnceLock;
+
+#[cfg(windows)]
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+#[cfg(windows)]
+use windows_sys::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+#[cfg(windows)]
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, MSG, RegisterClassW,
+    SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST, SystemParametersInfoW, WM_SETTINGCHANGE,
+    WNDCLASSW,
+};
+
+/// 辅助功能设置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Settings {
+    /// 系统启用了高对比度主题
+    pub high_contrast: bool,
+
+    /// 系统关闭了界面动画
+    pub reduce_motion: bool,
+}
+
+/// 读取当前的辅助功能设置（读取失败的项按未启用处理）
+#[cfg(windows)]
+pub fn current() -> Settings {
+    let mut contrast = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        dwFlags: 0,
+        lpszDefaultScheme: std::ptr::null_mut(),
+    };
+    // SAFETY: pvParam 指向 cbSize 已正确设置的 HIGHCONTRASTW，uiParam 为其大小
+    let high_contrast = unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            contrast.cbSize,
+            &mut contrast as *mut HIGHCONTRASTW as *mut c_void,
+            0,
+        )
+    } != 0
+        && contrast.dwFlags & HCF_HIGHCONTRASTON != 0;
+
+    let mut animation: i32 = 1;
+    // SAFETY: SPI_GETCLIENTAREAANIMATION 向 pvParam 写入一个 BOOL
+    let read = unsafe {
+        SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            &mut animation as *mut i32 as *mut c_void,
+            0,
+        )
+    } != 0;
+
+    Settings {
+        high_contrast,
+        reduce_motion: read && animation == 0,
+    }
+}
+
+/// 读取当前的辅助功能设置（`gsettings` 不可用时按未启用处理）
+#[cfg(not(windows))]
+pub fn current() -> Settings {
+    let get = |schema: &str, key: &str| {
+        let output = std::process::Command::new("gsettings")
+            .args(["get", schema, key])
+            .output()
+            .ok()?;
+        parse_gsettings_bool(&String::from_utf8_lossy(&output.stdout))
+    };
+
+    Settings {
+        high_contrast: get("org.gnome.desktop.a11y.interface", "high-contrast").unwrap_or(false),
+        reduce_motion: get("org.gnome.desktop.interface", "enable-animations") == Some(false),
+    }
+}
+
+/// 解析 `gsettings get` 输出的布尔值
+#[cfg(not(windows))]
+fn parse_gsettings_bool(output: &str) -> Option<bool> {
+    match output.trim() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+type ChangeHandler = Box<dyn Fn(Settings) + Send + Sync + 'static>;
+
+/// 设置变更回调（监听窗口的窗口过程中调用）
+#[cfg(windows)]
+static ON_CHANGE: OnceLock<ChangeHandler> = OnceLock::new();
+
+/// 监听窗口的窗口过程：收到 WM_SETTINGCHANGE 时重新读取设置（是否变化由回调判断）
+#[cfg(windows)]
+unsafe extern "system" fn window_proc(
+    hwnd: *mut c_void,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+) -> isize {
+    if message == WM_SETTINGCHANGE
+        && let Some(handler) = ON_CHANGE.get()
+    {
+        handler(current());
+    }
+    // SAFETY: 原样转交默认窗口过程
+    unsafe { DefWindowProcW(hwnd, message, wparam, lparam) }
+}
+
+/// 在独立线程中创建隐藏的顶层窗口并运行消息循环
+///
+/// 仅消息窗口（HWND_MESSAGE）收不到广播消息，因此使用从不显示的普通顶层窗口
+#[cfg(windows)]
+fn spawn_settings_window() -> std::io::Result<()> {
+    std::thread::Builder::new()
+        .name("a11y-listener".to_string())
+        .spawn(|| {
+            let class_name: Vec<u16> = "NanoMailSettingsListener\0".encode_utf16().collect();
+
+            // SAFETY: 传入空指针取得当前进程的模块句柄
+            let instance = unsafe { GetModuleHandleW(std::ptr::null()) };
+            // SAFETY: WNDCLASSW 为纯数据结构，全零是合法的初始值
+            let mut class: WNDCLASSW = unsafe { std::mem::zeroed() };
+            class.lpfnWndProc = Some(window_proc);
+            class.hInstance = instance;
+            class.lpszClassName = class_name.as_ptr();
+
+            // SAFETY: class 中的类名在整个线程生命周期内有效
+            if unsafe { RegisterClassW(&class) } == 0 {
+                tracing::warn!("注册辅助功能监听窗口类失败，切换系统设置需重启生效");
+                return;
+            }
+
+            // SAFETY: 类已注册；窗口从不显示，只用于接收广播消息
+            let hwnd = unsafe {
+                CreateWindowExW(
+                    0,
+                    class_name.as_ptr(),
+                    class_name.as_ptr(),
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    instance,
+                    std::ptr::null(),
+                )
+            };
+            if hwnd.is_null() {
+                tracing::warn!("创建辅助功能监听窗口失败，切换系统设置需重启生效");
+                return;
+            }
+
+            // SAFETY: MSG 为纯数据结构，全零是合法的初始值
+            let mut message: MSG = unsafe { std::mem::zeroed() };
+            // SAFETY: message 为有效输出指针；GetMessageW 出错时返回 -1，收到 WM_QUIT 时返回 0
+            while unsafe { GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) } > 0 {
+                // SAFETY: message 由 GetMessageW 填充
+                unsafe { DispatchMessageW(&message) };
+            }
+        })?;
+    Ok(())
+}
+
+/// 监听辅助功能设置的变更（仅在设置确实变化时调用 `on_change`）
+///
+/// `on_change` 在监听线程中调用，更新界面须转交 Slint 事件循环；Linux 上不监听
+pub fn start_listener<F>(on_change: F)
+where
+    F: Fn(Settings) + Send + 'static,
+{
+    #[cfg(windows)]
+    {
+        let listener = std::sync::Mutex::new((current(), on_change));
+        let handler: ChangeHandler = Box::new(move |settings| {
+            let mut listener = listener.lock().unwrap_or_else(|e| e.into_inner());
+            let (last, on_change) = &mut *listener;
+            if *last != settings {
+                tracing::info!("辅助功能设置变更: {:?}", settings);
+                *last = settings;
+                on_change(settings);
+            }
+        });
+        if ON_CHANGE.set(handler).is_err() {
+            tracing::warn!("辅助功能设置监听已启动");
+            return;
+        }
+        if let Err(e) = spawn_settings_window() {
+            tracing::error!("启动辅助功能设置监听线程失败: {}", e);
+        }
+    }
+
+    #[cfg(not(windows))]
+    let _ = on_change;
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gsettings_bool() {
+        assert_eq!(parse_gsettings_bool("true\n"), Some(true));
+        assert_eq!(parse_gsettings_bool("false\n"), Some(false));
+        assert_eq!(parse_gsettings_bool(""), None);
+    }
+}
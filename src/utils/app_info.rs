@@ -0,0 +1,62 @@
+/// 应用信息模块
+///
+/// 版本号等信息在编译期从 Cargo.toml 读取，避免各处硬编码的字面量随版本升级而失配
+/// 应用名称
+pub const APP_NAME: &str = "NanoMail";
+
+/// 应用版本号（来自 Cargo.toml）
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 构建时的 git 短哈希（由 build.rs 注入，非 git 工作区构建时为 "unknown"）
+pub const GIT_HASH: &str = match option_env!("NANOMAIL_GIT_HASH") {
+    Some(hash) => hash,
+    None => "unknown",
+};
+
+/// HTTP 请求使用的 User-Agent
+#[cfg(windows)]
+pub const USER_AGENT: &str = concat!(
+    "NanoMail/",
+    env!("CARGO_PKG_VERSION"),
+    " (Windows; U; Rust) Gecko"
+);
+
+/// HTTP 请求使用的 User-Agent（Linux 等其他平台）
+#[cfg(not(windows))]
+pub const USER_AGENT: &str = concat!(
+    "NanoMail/",
+    env!("CARGO_PKG_VERSION"),
+    " (Linux; U; Rust) Gecko"
+);
+
+/// 用默认程序打开文件或目录的命令
+#[cfg(windows)]
+pub const FILE_OPENER: &str = "explorer";
+
+/// 用默认程序打开文件或目录的命令（freedesktop 桌面通用）
+#[cfg(not(windows))]
+pub const FILE_OPENER: &str = "xdg-open";
+
+/// 完整版本描述，例如 "NanoMail 0.1.0 (a1b2c3d)"
+pub fn version_string() -> String {
+    format!("{} {} ({})", APP_NAME, VERSION, GIT_HASH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_agent_contains_version() {
+        assert!(USER_AGENT.starts_with(APP_NAME));
+        assert!(USER_AGENT.contains(VERSION));
+        assert_eq!(USER_AGENT.contains("Windows"), cfg!(windows));
+    }
+
+    #[test]
+    fn test_version_string() {
+        let text = version_string();
+        assert!(text.contains(VERSION));
+        assert!(text.contains(GIT_HASH));
+    }
+}
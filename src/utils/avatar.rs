@@ -3,54 +3,141 @@
 /// 负责下载头像并生成缩略图，减少内存占用
 use image::imageops::FilterType;
 use image::GenericImageView;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use thiserror::Error;
 
 use super::http_client;
+use super::redact::redact_email;
 
 /// 缩略图尺寸（与 UI 中头像显示尺寸匹配）
 const THUMBNAIL_SIZE: u32 = 48;
 
+/// 头像缓存目录的总大小上限（超出时从最旧的文件开始删除）
+const CACHE_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// 账户强调色调色板（与 `ui/components/accent_picker.slint` 保持一致）
+pub const ACCENT_PALETTE: [&str; 8] = [
+    "#E53935", "#FB8C00", "#F6BF26", "#33B679", "#039BE5", "#3F51B5", "#8E24AA", "#616161",
+];
+
+/// 头像源图片的大小上限（超出时不再下载，也不解码）
+const MAX_SOURCE_BYTES: usize = 5 * 1024 * 1024;
+
+/// 头像源图片的边长上限（解码前从文件头读取尺寸检查，防止异常图片耗尽内存）
+const MAX_SOURCE_DIMENSION: u32 = 4096;
+
+/// 头像下载与缓存失败的原因（只影响头像，不算同步失败）
+#[derive(Debug, Error)]
+pub enum AvatarError {
+    /// 请求失败或服务返回错误状态
+    #[error("下载头像失败: {0}")]
+    Http(String),
+
+    /// 图片超过大小或尺寸上限
+    #[error("头像图片过大: {0}")]
+    TooLarge(String),
+
+    /// 无法识别或解码图片，或无法编码缩略图
+    #[error("解码头像失败: {0}")]
+    Decode(#[from] image::ImageError),
+
+    /// 无法写入缓存目录
+    #[error("保存头像缓存失败: {0}")]
+    Io(#[from] std::io::Error),
+}
+
 /// 下载头像并生成缩略图，返回本地缓存路径
 ///
 /// # Arguments
 /// * `url` - 头像 URL
 /// * `email` - 用户邮箱（用于生成文件名）
 ///
-/// # Returns
-/// 成功返回本地缓存路径，失败返回 None
-pub async fn download_and_resize_avatar(url: &str, email: &str) -> Option<String> {
-    tracing::debug!("下载头像: {} -> {}", email, url);
-
-    // 1. 下载图片
-    let resp = match http_client::get_client().get(url).send().await {
-        Ok(r) => r,
-        Err(e) => {
-            tracing::warn!("下载头像失败（请求失败）: {}: {}", url, e);
-            return None;
-        }
-    };
+/// # Errors
+/// 请求失败、图片超过上限、无法解码或无法写入缓存时返回对应的 [`AvatarError`]
+pub async fn download_and_resize_avatar(url: &str, email: &str) -> Result<PathBuf, AvatarError> {
+    tracing::debug!("下载头像: {}", redact_email(email));
+
+    // 1. 下载图片（超过大小上限时立即停止读取）
+    let mut resp = http_client::get_client()
+        .get(url)
+        .timeout(http_client::avatar_timeout())
+        .send()
+        .await
+        .map_err(|e| AvatarError::Http(e.to_string()))?;
 
     if !resp.status().is_success() {
-        tracing::warn!("下载头像失败（HTTP {}）: {}", resp.status(), url);
-        return None;
+        return Err(AvatarError::Http(format!("HTTP {}", resp.status())));
+    }
+    if let Some(length) = resp.content_length() {
+        check_source_size(length as usize)?;
     }
 
-    let bytes = match resp.bytes().await {
-        Ok(b) => b,
-        Err(e) => {
-            tracing::warn!("读取头像响应体失败: {}", e);
-            return None;
-        }
-    };
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .map_err(|e| AvatarError::Http(e.to_string()))?
+    {
+        check_source_size(bytes.len() + chunk.len())?;
+        bytes.extend_from_slice(&chunk);
+    }
 
-    // 2. 解码图片
-    let img = match image::load_from_memory(&bytes) {
-        Ok(img) => img,
-        Err(e) => {
-            tracing::warn!("解码头像失败: {}", e);
-            return None;
-        }
-    };
+    cache_avatar_bytes_async(bytes, email).await
+}
+
+/// 把已下载的头像图片生成缩略图并缓存，返回本地缓存路径
+///
+/// 解码、缩放与写文件都在阻塞线程中进行，不占用异步运行时的工作线程。
+/// 也用于需要鉴权才能下载头像的服务（如 Microsoft Graph 的 `/me/photo/$value`）
+pub async fn cache_avatar_bytes_async(bytes: Vec<u8>, email: &str) -> Result<PathBuf, AvatarError> {
+    let email = email.to_string();
+    tokio::task::spawn_blocking(move || cache_avatar_bytes(&bytes, &email))
+        .await
+        .map_err(|e| AvatarError::Io(std::io::Error::other(e)))?
+}
+
+/// 记录头像失败（只是警告：同步照常成功，界面沿用远程地址、缓存头像或占位符）
+pub fn warn_failed(email: &str, error: &AvatarError) {
+    tracing::warn!("{} 的头像不可用: {}", redact_email(email), error);
+}
+
+/// 检查源图片的字节数
+fn check_source_size(len: usize) -> Result<(), AvatarError> {
+    if len > MAX_SOURCE_BYTES {
+        return Err(AvatarError::TooLarge(format!(
+            "{} 字节（上限 {} 字节）",
+            len, MAX_SOURCE_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// 解码前从文件头读取尺寸并检查（不分配像素缓冲区）
+fn check_source_dimensions(bytes: &[u8]) -> Result<(), AvatarError> {
+    let (width, height) = image::ImageReader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_dimensions()?;
+    if width > MAX_SOURCE_DIMENSION || height > MAX_SOURCE_DIMENSION {
+        return Err(AvatarError::TooLarge(format!(
+            "{}x{}（上限 {}x{}）",
+            width, height, MAX_SOURCE_DIMENSION, MAX_SOURCE_DIMENSION
+        )));
+    }
+    Ok(())
+}
+
+/// 生成缩略图并缓存（阻塞）
+fn cache_avatar_bytes(bytes: &[u8], email: &str) -> Result<PathBuf, AvatarError> {
+    // 2. 检查大小与尺寸后解码图片
+    check_source_size(bytes.len())?;
+    check_source_dimensions(bytes)?;
+    let img = image::load_from_memory(bytes)?;
 
     // 3. 生成缩略图（48x48）
     let thumbnail = img.resize_exact(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Lanczos3);
@@ -62,45 +149,46 @@ pub async fn download_and_resize_avatar(url: &str, email: &str) -> Option<String
         THUMBNAIL_SIZE
     );
 
-    // 4. 构建缓存路径
-    let cache_dir = match dirs::config_dir() {
-        Some(d) => d.join("NanoMail").join("avatars"),
-        None => {
-            tracing::warn!("无法获取配置目录，跳过头像缓存");
-            return None;
-        }
-    };
-
-    if let Err(e) = std::fs::create_dir_all(&cache_dir) {
-        tracing::warn!("创建头像缓存目录失败: {}", e);
-        return None;
+    // 4. 构建缓存路径（文件名为邮箱哈希 + 固定 PNG 格式）
+    let path = thumbnail_path(email)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "无法获取配置目录"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
     }
 
-    // 文件名使用邮箱安全化 + 固定 PNG 格式（缩略图统一格式）
-    let safe_name = email.replace('@', "_").replace('.', "_");
-    let path: PathBuf = cache_dir.join(format!("{}_thumb.png", safe_name));
-
     // 5. 保存缩略图（PNG 格式，质量好且支持透明）
-    if let Err(e) = thumbnail.save(&path) {
-        tracing::warn!("保存头像缩略图失败: {}", e);
-        return None;
-    }
+    let mut png = Vec::new();
+    thumbnail.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+    write_atomically(&path, &png)?;
 
-    let file_size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
     tracing::info!(
         "✓ 头像缩略图已缓存: {} ({} bytes)",
         path.display(),
-        file_size
+        png.len()
     );
 
-    Some(path.display().to_string())
+    Ok(path)
+}
+
+/// 原子写入缓存文件：先写临时文件并落盘，再替换目标文件
+///
+/// 目标路径存在即代表内容完整，界面加载头像时不会读到写了一半的文件
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("png.tmp");
+    let written = std::fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        std::fs::remove_file(&tmp).ok();
+        return Err(e);
+    }
+    std::fs::rename(&tmp, path)
 }
 
 /// 获取已缓存的头像路径（如果存在）
 pub fn get_cached_avatar_path(email: &str) -> Option<String> {
-    let cache_dir = dirs::config_dir()?.join("NanoMail").join("avatars");
-    let safe_name = email.replace('@', "_").replace('.', "_");
-    let path = cache_dir.join(format!("{}_thumb.png", safe_name));
+    let path = thumbnail_path(email)?;
 
     if path.exists() {
         Some(path.display().to_string())
@@ -109,6 +197,356 @@ pub fn get_cached_avatar_path(email: &str) -> Option<String> {
     }
 }
 
+/// 头像缓存目录：`%APPDATA%\NanoMail\avatars`
+fn cache_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("NanoMail").join("avatars"))
+}
+
+/// 邮箱对应的缩略图缓存路径（写入缓存与查找缓存都经过这里）
+fn thumbnail_path(email: &str) -> Option<PathBuf> {
+    Some(cache_dir()?.join(thumbnail_file_name(email)))
+}
+
+/// 邮箱对应的缩略图文件名（`<缓存键>.png`）
+fn thumbnail_file_name(email: &str) -> String {
+    format!("{}.png", cache_key(email))
+}
+
+/// 邮箱对应的缓存键：小写邮箱的 SHA-256 的前 16 个十六进制字符
+///
+/// 旧版本把 `@` 与 `.` 替换为 `_` 作为文件名，`a.b@x.com` 与 `a_b@x.com` 会得到同一个文件、
+/// 互相覆盖头像；按哈希命名后不同邮箱不再冲突，只有大小写不同的同一邮箱共用一个文件
+fn cache_key(email: &str) -> String {
+    Sha256::digest(email.trim().to_lowercase().as_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 旧版本的文件名前缀（`@` 与 `.` 替换为 `_`，只用于迁移与清理）
+fn legacy_prefix(email: &str) -> String {
+    email.replace(['@', '.'], "_")
+}
+
+/// 头像缓存清理结果
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CleanupSummary {
+    /// 不属于任何现有账户的文件
+    orphans: usize,
+
+    /// 现有账户的旧格式文件（原图、其他扩展名）
+    stale: usize,
+
+    /// 超出总大小上限而删除的文件
+    over_cap: usize,
+
+    /// 从旧文件名迁移的缩略图
+    migrated: usize,
+
+    /// 释放的字节数
+    freed_bytes: u64,
+}
+
+/// 清理头像缓存目录
+///
+/// 先把现有账户按旧方式命名的缩略图改为按哈希命名，再删除不属于现有账户的文件与
+/// 现有账户的旧格式文件（只保留 `<缓存键>.png`），总大小仍超过上限时从最旧的文件开始删除。`active_emails` 须来自成功读取的账户文件，
+/// 传入空列表会删除全部头像
+pub fn cleanup(active_emails: &[String]) {
+    let Some(dir) = cache_dir() else {
+        return;
+    };
+    if !dir.exists() {
+        return;
+    }
+
+    let summary = cleanup_dir(&dir, active_emails, CACHE_MAX_BYTES);
+    if summary == CleanupSummary::default() {
+        tracing::debug!("头像缓存无需清理");
+    } else {
+        tracing::info!(
+            "🧹 头像缓存已清理: 迁移旧文件名 {} 个，无主文件 {} 个，旧格式文件 {} 个，超出容量 {} 个，共释放 {} 字节",
+            summary.migrated,
+            summary.orphans,
+            summary.stale,
+            summary.over_cap,
+            summary.freed_bytes
+        );
+    }
+}
+
+/// 清理指定目录（见 [`cleanup`]）
+fn cleanup_dir(dir: &Path, active_emails: &[String], max_bytes: u64) -> CleanupSummary {
+    let mut summary = CleanupSummary {
+        migrated: migrate_legacy_names(dir, active_emails),
+        ..CleanupSummary::default()
+    };
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!("读取头像缓存目录失败: {}", e);
+            return summary;
+        }
+    };
+
+    let keep: Vec<String> = active_emails
+        .iter()
+        .map(|email| thumbnail_file_name(email))
+        .collect();
+    let keys: Vec<String> = active_emails.iter().map(|email| cache_key(email)).collect();
+    let prefixes: Vec<String> = active_emails
+        .iter()
+        .map(|email| legacy_prefix(email))
+        .collect();
+
+    // 保留的文件：(修改时间, 大小, 路径)
+    let mut kept: Vec<(SystemTime, u64, PathBuf)> = Vec::new();
+
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+
+        if keep.contains(&name) {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            kept.push((modified, metadata.len(), path));
+            continue;
+        }
+
+        let stale = keys.iter().chain(&prefixes).any(|prefix| {
+            name.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.starts_with('.') || rest.starts_with("_thumb."))
+        });
+        if remove_file(&path) {
+            summary.freed_bytes += metadata.len();
+            if stale {
+                summary.stale += 1;
+            } else {
+                summary.orphans += 1;
+            }
+        }
+    }
+
+    // 超出容量时从最旧的文件开始删除
+    kept.sort();
+    let mut total: u64 = kept.iter().map(|(_, size, _)| size).sum();
+    for (_, size, path) in kept {
+        if total <= max_bytes {
+            break;
+        }
+        if remove_file(&path) {
+            total -= size;
+            summary.freed_bytes += size;
+            summary.over_cap += 1;
+        }
+    }
+
+    summary
+}
+
+/// 把现有账户按旧方式命名的缩略图（`<邮箱>_thumb.png`）改名为 `<缓存键>.png`，返回迁移的个数
+///
+/// 多个现有账户对应同一个旧文件名时无法判断头像属于谁，不迁移（随后作为旧格式文件删除，
+/// 下次同步重新下载）；新文件已存在时以新文件为准
+fn migrate_legacy_names(dir: &Path, active_emails: &[String]) -> usize {
+    let mut migrated = 0;
+    for email in active_emails {
+        let prefix = legacy_prefix(email);
+        let shared = active_emails
+            .iter()
+            .filter(|other| legacy_prefix(other) == prefix)
+            .count()
+            > 1;
+        let from = dir.join(format!("{}_thumb.png", prefix));
+        let to = dir.join(thumbnail_file_name(email));
+        if shared || !from.is_file() || to.exists() {
+            continue;
+        }
+        match std::fs::rename(&from, &to) {
+            Ok(()) => migrated += 1,
+            Err(e) => tracing::warn!("迁移头像缓存失败: {}: {}", from.display(), e),
+        }
+    }
+    migrated
+}
+
+/// 删除文件，失败时记录警告
+fn remove_file(path: &Path) -> bool {
+    match std::fs::remove_file(path) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("删除头像缓存失败: {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// 加载账户头像（优先使用缓存的缩略图，没有缓存时使用占位符）
+pub fn load_avatar_image(email: &str) -> slint::Image {
+    let Some(cached_path) = get_cached_avatar_path(email) else {
+        // 没有缓存，使用占位符
+        return load_placeholder_avatar();
+    };
+
+    tracing::debug!("从缓存加载头像: {}", cached_path);
+    load_avatar_from_path(&cached_path)
+}
+
+/// 加载本地头像文件（路径为空或加载失败时使用占位符，不显示空白头像）
+pub fn load_avatar_from_path(path: &str) -> slint::Image {
+    if path.is_empty() {
+        return load_placeholder_avatar();
+    }
+    match slint::Image::load_from_path(Path::new(path)) {
+        Ok(img) => img,
+        Err(e) => {
+            tracing::warn!("加载头像失败: {} - {}", path, e);
+            load_placeholder_avatar()
+        }
+    }
+}
+
+/// 占位符头像 SVG（线条颜色为 `currentColor`，栅格化前按主题替换）
+const PLACEHOLDER_SVG: &str = include_str!("../../assets/icons/placeholder-avatar.svg");
+
+/// 占位符头像在浅色 / 深色主题下的线条颜色
+const PLACEHOLDER_COLOR_LIGHT: &str = "#3C4043";
+const PLACEHOLDER_COLOR_DARK: &str = "#E8EAED";
+
+/// 占位符头像的显示方式（由界面在主题切换与缩放比例变化时更新）
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PlaceholderStyle {
+    dark: bool,
+    scale: f32,
+}
+
+static PLACEHOLDER_STYLE: Mutex<PlaceholderStyle> = Mutex::new(PlaceholderStyle {
+    dark: false,
+    scale: 1.0,
+});
+
+thread_local! {
+    /// 已栅格化的占位符头像（按像素尺寸与主题缓存，`slint::Image` 不能跨线程共享）
+    static PLACEHOLDERS: RefCell<HashMap<(u32, bool), slint::Image>> = RefCell::new(HashMap::new());
+}
+
+/// 更新占位符头像的主题与窗口缩放比例
+///
+/// 没有变化时返回 `None`；有变化时清空缓存并返回此前生成的占位符，
+/// 调用方据此替换已显示的占位符，之后 [`load_placeholder_avatar`] 按新的样式重新栅格化
+pub fn set_placeholder_style(dark: bool, scale: f32) -> Option<Vec<slint::Image>> {
+    let scale = if scale.is_finite() && scale > 0.0 {
+        scale
+    } else {
+        1.0
+    };
+    let style = PlaceholderStyle { dark, scale };
+    let mut current = PLACEHOLDER_STYLE.lock().unwrap_or_else(|e| e.into_inner());
+    if *current == style {
+        return None;
+    }
+    tracing::debug!(
+        "占位符头像样式: {} @{}x -> {} @{}x",
+        if current.dark { "dark" } else { "light" },
+        current.scale,
+        if dark { "dark" } else { "light" },
+        scale
+    );
+    *current = style;
+    Some(PLACEHOLDERS.with(|cache| cache.borrow_mut().drain().map(|(_, image)| image).collect()))
+}
+
+/// 加载占位符头像
+///
+/// 按头像显示尺寸乘以窗口缩放比例栅格化，高 DPI 下同样清晰；线条颜色随主题变化
+pub fn load_placeholder_avatar() -> slint::Image {
+    let style = *PLACEHOLDER_STYLE.lock().unwrap_or_else(|e| e.into_inner());
+    let size = ((THUMBNAIL_SIZE as f32 * style.scale).round() as u32).max(1);
+    cached_placeholder(size, style.dark)
+}
+
+/// 指定像素尺寸与主题的占位符头像（同一组合只栅格化一次）
+fn cached_placeholder(size: u32, dark: bool) -> slint::Image {
+    PLACEHOLDERS.with(|cache| {
+        cache
+            .borrow_mut()
+            .entry((size, dark))
+            .or_insert_with(|| {
+                rasterize_placeholder(size, dark).unwrap_or_else(|| {
+                    // 栅格化失败时退回由 Slint 直接渲染 SVG
+                    slint::Image::load_from_svg_data(PLACEHOLDER_SVG.as_bytes()).unwrap_or_default()
+                })
+            })
+            .clone()
+    })
+}
+
+/// 按主题替换线条颜色后把占位符 SVG 栅格化为 `size`×`size` 像素
+fn rasterize_placeholder(size: u32, dark: bool) -> Option<slint::Image> {
+    let color = if dark {
+        PLACEHOLDER_COLOR_DARK
+    } else {
+        PLACEHOLDER_COLOR_LIGHT
+    };
+    let svg = PLACEHOLDER_SVG.replace("currentColor", color);
+
+    let tree = match resvg::usvg::Tree::from_str(&svg, &resvg::usvg::Options::default()) {
+        Ok(tree) => tree,
+        Err(e) => {
+            tracing::warn!("解析占位符头像失败: {}", e);
+            return None;
+        }
+    };
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size, size)?;
+    let view = tree.size();
+    let transform = resvg::tiny_skia::Transform::from_scale(
+        size as f32 / view.width(),
+        size as f32 / view.height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    let buffer =
+        slint::SharedPixelBuffer::<slint::Rgba8Pixel>::clone_from_slice(pixmap.data(), size, size);
+    Some(slint::Image::from_rgba8_premultiplied(buffer))
+}
+
+/// 校验强调色（`#RRGGBB`），返回统一为大写的写法
+pub fn parse_accent(value: &str) -> Option<String> {
+    let hex = value.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!("#{}", hex.to_ascii_uppercase()))
+}
+
+/// 由邮箱地址散列得到的默认强调色（同一邮箱始终得到同一颜色）
+pub fn auto_accent(email: &str) -> &'static str {
+    // FNV-1a：结果不随 Rust 版本变化，重启后颜色保持一致
+    let hash = email
+        .trim()
+        .to_ascii_lowercase()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    ACCENT_PALETTE[(hash % ACCENT_PALETTE.len() as u64) as usize]
+}
+
+/// 账户实际使用的强调色（未设置或格式无效时使用默认颜色）
+pub fn accent_color(email: &str, accent: Option<&str>) -> slint::Color {
+    let hex = accent
+        .and_then(parse_accent)
+        .unwrap_or_else(|| auto_accent(email).to_string());
+    let rgb = u32::from_str_radix(&hex[1..], 16).unwrap_or_default();
+    slint::Color::from_argb_encoded(0xFF00_0000 | rgb)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +562,242 @@ mod tests {
         // 可能存在也可能不存在，只测试不会 panic
         let _ = result;
     }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::RgbImage::new(width, height)
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_oversized_source_is_rejected_before_decoding() {
+        assert!(matches!(
+            check_source_size(MAX_SOURCE_BYTES + 1),
+            Err(AvatarError::TooLarge(_))
+        ));
+        assert!(check_source_size(MAX_SOURCE_BYTES).is_ok());
+
+        assert!(check_source_dimensions(&png(64, 64)).is_ok());
+        assert!(matches!(
+            check_source_dimensions(&png(MAX_SOURCE_DIMENSION + 1, 1)),
+            Err(AvatarError::TooLarge(_))
+        ));
+        assert!(matches!(
+            cache_avatar_bytes(&png(1, MAX_SOURCE_DIMENSION + 1), "me@example.com"),
+            Err(AvatarError::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_broken_image_is_a_decode_error() {
+        let error = cache_avatar_bytes(b"<html>not an image</html>", "me@example.com").unwrap_err();
+        assert!(matches!(error, AvatarError::Decode(_)), "{error}");
+    }
+
+    /// 在临时目录中写入文件，返回目录
+    fn seeded_dir(name: &str, files: &[(&str, usize)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("nanomail-avatars-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, size) in files {
+            std::fs::write(dir.join(file), vec![0u8; *size]).unwrap();
+            // 保证修改时间按写入顺序递增
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        dir
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_write_atomically_replaces_only_complete_files() {
+        let dir = seeded_dir("atomic", &[]);
+        let path = dir.join(thumbnail_file_name("me@example.com"));
+
+        write_atomically(&path, b"first").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"first");
+        assert!(!path.with_extension("png.tmp").exists());
+
+        // 临时文件写不进去时不动目标文件：目标路径上只会出现写完并落盘的内容
+        std::fs::create_dir(path.with_extension("png.tmp")).unwrap();
+        assert!(write_atomically(&path, b"second").is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"first");
+    }
+
+    #[test]
+    fn test_missing_avatar_file_falls_back_to_placeholder() {
+        let placeholder = load_placeholder_avatar().size();
+        assert_ne!(placeholder, slint::Image::default().size());
+
+        let missing = std::env::temp_dir().join("nanomail-avatar-missing.png");
+        assert_eq!(
+            load_avatar_from_path(&missing.display().to_string()).size(),
+            placeholder
+        );
+        assert_eq!(load_avatar_from_path("").size(), placeholder);
+    }
+
+    #[test]
+    fn test_placeholder_rasterized_per_size_and_theme() {
+        let light = cached_placeholder(96, false);
+        assert_eq!((light.size().width, light.size().height), (96, 96));
+        assert_eq!(cached_placeholder(96, false), light);
+
+        // 深色主题使用浅色线条
+        let dark = cached_placeholder(96, true);
+        assert_eq!(dark.size(), light.size());
+        assert_ne!(dark, light);
+    }
+
+    #[test]
+    fn test_cache_names_do_not_collide() {
+        let pairs = [
+            ("a.b@x.com", "a_b@x.com"),
+            ("ab@x.c.om", "ab@x_c.om"),
+            ("a@b.c_d", "a@b_c.d"),
+            ("a_@x.com", "a.@x.com"),
+        ];
+        for (first, second) in pairs {
+            // 旧方式下这些邮箱得到同一个文件名
+            assert_eq!(legacy_prefix(first), legacy_prefix(second));
+            assert_ne!(
+                thumbnail_file_name(first),
+                thumbnail_file_name(second),
+                "{first} / {second}"
+            );
+        }
+
+        let name = thumbnail_file_name("me@example.com");
+        assert_eq!(name.len(), 16 + ".png".len());
+        assert!(name[..16].chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(thumbnail_file_name(" Me@Example.COM "), name);
+    }
+
+    #[test]
+    fn test_legacy_files_are_migrated() {
+        let dir = seeded_dir(
+            "migrate",
+            &[
+                ("me_example_com_thumb.png", 100),
+                // 两个现有账户的旧文件名相同：无法判断属于谁，不迁移
+                ("a_b_x_com_thumb.png", 200),
+            ],
+        );
+        let active = ["me@example.com", "a.b@x.com", "a_b@x.com"].map(String::from);
+
+        let summary = cleanup_dir(&dir, &active, CACHE_MAX_BYTES);
+        assert_eq!(summary.migrated, 1);
+        assert_eq!(summary.stale, 1);
+        assert_eq!(
+            file_names(&dir),
+            vec![thumbnail_file_name("me@example.com")]
+        );
+
+        // 迁移后按新文件名能找到原来的缩略图
+        let migrated = dir.join(thumbnail_file_name("me@example.com"));
+        assert_eq!(std::fs::metadata(migrated).unwrap().len(), 100);
+
+        // 再次运行时没有需要迁移的文件
+        assert_eq!(migrate_legacy_names(&dir, &active), 0);
+    }
+
+    #[test]
+    fn test_cleanup_removes_orphans_and_stale_files() {
+        let me = thumbnail_file_name("me@example.com");
+        let gone = thumbnail_file_name("gone@example.com");
+        let dir = seeded_dir(
+            "mess",
+            &[
+                (me.as_str(), 100),
+                ("me_example_com.jpg", 4000),
+                ("me_example_com_thumb.jpeg", 100),
+                (gone.as_str(), 100),
+                ("gone_example_com.png", 3000),
+                ("desktop.ini", 10),
+                // 邮箱前缀相同但属于另一个账户
+                ("me_example_com_au_thumb.png", 100),
+            ],
+        );
+        std::fs::create_dir_all(dir.join("subdir")).unwrap();
+
+        let summary = cleanup_dir(&dir, &["me@example.com".to_string()], CACHE_MAX_BYTES);
+        assert_eq!(
+            summary,
+            CleanupSummary {
+                orphans: 4,
+                stale: 2,
+                over_cap: 0,
+                migrated: 0,
+                freed_bytes: 7310,
+            }
+        );
+        assert_eq!(file_names(&dir), vec![me, "subdir".to_string()]);
+
+        // 再次清理时没有可删除的文件
+        let summary = cleanup_dir(&dir, &["me@example.com".to_string()], CACHE_MAX_BYTES);
+        assert_eq!(summary, CleanupSummary::default());
+    }
+
+    #[test]
+    fn test_cleanup_enforces_size_cap() {
+        let active = ["a@example.com", "b@example.com", "c@example.com"].map(String::from);
+        let names = active.clone().map(|email| thumbnail_file_name(&email));
+        let dir = seeded_dir(
+            "cap",
+            &[
+                (names[0].as_str(), 400),
+                (names[1].as_str(), 400),
+                (names[2].as_str(), 400),
+            ],
+        );
+
+        // 上限 1000 字节：删除最旧的一个
+        let summary = cleanup_dir(&dir, &active, 1000);
+        assert_eq!(summary.over_cap, 1);
+        assert_eq!(summary.freed_bytes, 400);
+        let mut expected = vec![names[1].clone(), names[2].clone()];
+        expected.sort();
+        assert_eq!(file_names(&dir), expected);
+    }
+
+    #[test]
+    fn test_parse_accent() {
+        assert_eq!(parse_accent("#33b679"), Some("#33B679".to_string()));
+        assert_eq!(parse_accent(" #039BE5 "), Some("#039BE5".to_string()));
+        assert_eq!(parse_accent("039BE5"), None);
+        assert_eq!(parse_accent("#03F"), None);
+        assert_eq!(parse_accent("#GGGGGG"), None);
+        assert_eq!(parse_accent("auto"), None);
+    }
+
+    #[test]
+    fn test_auto_accent_is_stable() {
+        let color = auto_accent("me@example.com");
+        assert!(ACCENT_PALETTE.contains(&color));
+        assert_eq!(auto_accent("Me@Example.com"), color);
+        assert!(ACCENT_PALETTE.iter().all(|hex| parse_accent(hex).is_some()));
+    }
+
+    #[test]
+    fn test_accent_color_falls_back_to_auto() {
+        let auto = accent_color("me@example.com", None);
+        assert_eq!(accent_color("me@example.com", Some("not a color")), auto);
+        assert_eq!(
+            accent_color("me@example.com", Some("#33B679")),
+            slint::Color::from_rgb_u8(0x33, 0xB6, 0x79)
+        );
+    }
 }
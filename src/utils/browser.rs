@@ -0,0 +1,114 @@
+/// 在浏览器中打开链接
+///
+/// 没有默认浏览器关联的系统（全新安装的 Windows、展台模式等）上 `webbrowser::open` 会失败。
+/// 所有打开链接的地方都经过 [`open_url`]，失败时按用途退而求其次：授权链接弹出对话框显示完整的
+/// 地址并可一键复制，用户在本机任意浏览器中打开即可（授权完成后仍跳回本机的回调地址）；
+/// 普通链接复制到剪贴板，并通过注册的提示方式（窗口提示条或托盘提示）说明发生了什么
+use std::sync::Mutex;
+
+use super::i18n::{tr, tr_fmt};
+use super::{clipboard, dialog};
+
+/// 打开失败时的提示方式（界面模式下由 `app` 注册；持有窗口句柄，只要求 `Send`）
+static NOTICE: Mutex<Option<Box<dyn Fn(String) + Send>>> = Mutex::new(None);
+
+/// 链接的用途（决定打开失败时如何处理）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlPurpose {
+    /// OAuth 授权链接：没有它就无法添加账户，失败时弹出对话框
+    Authorization,
+
+    /// 普通链接（收件箱、发布页、帮助等）：失败时复制到剪贴板并提示
+    Link,
+}
+
+/// 注册打开失败时的提示方式；只有第一次注册有效
+pub fn set_notice_handler(handler: impl Fn(String) + Send + 'static) {
+    let mut slot = NOTICE.lock().unwrap_or_else(|e| e.into_inner());
+    if slot.is_some() {
+        tracing::debug!("已注册过打开链接失败的提示方式，忽略");
+        return;
+    }
+    *slot = Some(Box::new(handler));
+}
+
+/// 在默认浏览器中打开链接，返回是否成功打开（失败时已按用途处理）
+pub fn open_url(url: &str, purpose: UrlPurpose) -> bool {
+    let Err(e) = webbrowser::open(url) else {
+        return true;
+    };
+    tracing::warn!("无法打开浏览器（{:?}）: {}", purpose, e);
+
+    match purpose {
+        UrlPurpose::Authorization => {
+            // 对话框是模态的，放到独立线程，授权流程继续等待回调
+            let url = url.to_string();
+            std::thread::spawn(move || {
+                if dialog::ask(&fallback_message(&url, purpose, false)) {
+                    copy_link(&url);
+                }
+            });
+        }
+        UrlPurpose::Link => {
+            let copied = copy_link(url);
+            let message = fallback_message(url, purpose, copied);
+            let notice = NOTICE.lock().unwrap_or_else(|e| e.into_inner());
+            match notice.as_ref() {
+                Some(notice) => notice(message),
+                None => tracing::warn!("{}", message),
+            }
+        }
+    }
+    false
+}
+
+/// 复制链接，返回是否成功
+fn copy_link(url: &str) -> bool {
+    match clipboard::set_text(url) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("复制链接失败: {:#}", e);
+            false
+        }
+    }
+}
+
+/// 打开失败时的说明
+///
+/// 授权链接总是包含完整地址（对话框中可复制）；普通链接复制成功时只说明已复制，失败时附上地址
+pub fn fallback_message(url: &str, purpose: UrlPurpose, copied: bool) -> String {
+    match (purpose, copied) {
+        (UrlPurpose::Authorization, _) => tr_fmt("browser.auth_fallback", &[&url]),
+        (UrlPurpose::Link, true) => tr("browser.link_copied").to_string(),
+        (UrlPurpose::Link, false) => tr_fmt("browser.link_failed", &[&url]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth?client_id=abc&redirect_uri=http%3A%2F%2Flocalhost%3A8765&state=xyz";
+
+    #[test]
+    fn test_authorization_message_contains_full_url() {
+        for copied in [false, true] {
+            let message = fallback_message(AUTH_URL, UrlPurpose::Authorization, copied);
+            assert!(message.contains(AUTH_URL), "{message}");
+            // 地址单独一行，便于选中
+            assert!(message.lines().any(|line| line == AUTH_URL));
+        }
+    }
+
+    #[test]
+    fn test_link_message_depends_on_copy_result() {
+        let url = "https://github.com/Keriyar/NanoMail/releases";
+        let copied = fallback_message(url, UrlPurpose::Link, true);
+        assert!(!copied.contains(url));
+        assert!(!copied.is_empty());
+
+        let failed = fallback_message(url, UrlPurpose::Link, false);
+        assert!(failed.contains(url));
+        assert_ne!(copied, failed);
+    }
+}
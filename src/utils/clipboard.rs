@@ -0,0 +1,22 @@
+/// 剪贴板
+///
+/// Windows 上直接写入系统剪贴板；Linux 上由 GTK 线程（托盘所在的线程）持有剪贴板内容，
+/// 其他程序粘贴时由它提供，因此只有托盘运行时才能复制
+use anyhow::Result;
+
+/// 把文本复制到剪贴板
+pub fn set_text(text: &str) -> Result<()> {
+    #[cfg(windows)]
+    clipboard_win::set_clipboard_string(text)
+        .map_err(|e| anyhow::anyhow!("写入剪贴板失败: {}", e))?;
+
+    #[cfg(target_os = "linux")]
+    {
+        let copied = text.to_string();
+        gtk::glib::MainContext::default().invoke(move || {
+            gtk::Clipboard::get(&gtk::gdk::SELECTION_CLIPBOARD).set_text(&copied);
+        });
+    }
+
+    Ok(())
+}
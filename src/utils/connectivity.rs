@@ -0,0 +1,442 @@
+/// 网络连接状态模块
+///
+/// 通过 `NotifyNetworkConnectivityHintChange` 订阅系统的网络连接提示，
+/// 以极低成本维护一份缓存的 [`ConnectivityState`]；同步引擎据此决定是否发起请求。
+/// 只有在真实请求失败、且最近没有 Gmail API 请求成功时，才对配置中的端点列表做一次主动探测，
+/// 避免单一探测地址（如 generate_204）被屏蔽时误报"网络不可用"。
+/// 连接提示同时带有费用信息：按流量计费（手机热点、漫游、接近或超出流量上限）时记录下来，
+/// 供 `mail::metered` 节流同步。
+/// Linux 上没有订阅连接提示，状态保持 Unknown、视为不计费，由请求结果与主动探测更新
+#[cfg(windows)]
+use std::ffi::c_void;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+use windows_sys::Win32::Foundation::{ERROR_SUCCESS, HANDLE};
+#[cfg(windows)]
+use windows_sys::Win32::NetworkManagement::IpHelper::{
+    GetNetworkConnectivityHint, NotifyNetworkConnectivityHintChange,
+};
+#[cfg(windows)]
+use windows_sys::Win32::Networking::WinSock::{
+    NL_NETWORK_CONNECTIVITY_HINT, NL_NETWORK_CONNECTIVITY_LEVEL_HINT,
+    NetworkConnectivityCostHintFixed, NetworkConnectivityCostHintVariable,
+    NetworkConnectivityLevelHintConstrainedInternetAccess,
+    NetworkConnectivityLevelHintInternetAccess, NetworkConnectivityLevelHintLocalAccess,
+    NetworkConnectivityLevelHintNone,
+};
+
+use super::{http_client, log_throttle};
+use crate::config::{self, NetworkConfig};
+
+/// 单个探测端点的超时时间
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Gmail API 请求成功后多久内视为网络可用（期间请求失败不再主动探测）
+const PROBE_TTL: Duration = Duration::from_secs(60);
+
+/// 网络探测失败时日志限流使用的来源
+const PROBE_LOG_SOURCE: &str = "网络探测";
+
+/// 网络连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectivityState {
+    /// 尚未获知（系统不支持连接提示，或刚从睡眠恢复）
+    Unknown,
+    /// 可访问互联网
+    Online,
+    /// 仅局域网连接（可能处于需要代理或认证的网络，请求仍可尝试）
+    Limited,
+    /// 无网络连接
+    Offline,
+}
+
+impl ConnectivityState {
+    fn to_u8(self) -> u8 {
+        match self {
+            ConnectivityState::Unknown => 0,
+            ConnectivityState::Online => 1,
+            ConnectivityState::Limited => 2,
+            ConnectivityState::Offline => 3,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ConnectivityState::Online,
+            2 => ConnectivityState::Limited,
+            3 => ConnectivityState::Offline,
+            _ => ConnectivityState::Unknown,
+        }
+    }
+
+    /// 由系统连接提示级别换算
+    #[cfg(windows)]
+    fn from_hint_level(level: NL_NETWORK_CONNECTIVITY_LEVEL_HINT) -> Self {
+        if level == NetworkConnectivityLevelHintInternetAccess
+            || level == NetworkConnectivityLevelHintConstrainedInternetAccess
+        {
+            ConnectivityState::Online
+        } else if level == NetworkConnectivityLevelHintLocalAccess {
+            ConnectivityState::Limited
+        } else if level == NetworkConnectivityLevelHintNone {
+            ConnectivityState::Offline
+        } else {
+            ConnectivityState::Unknown
+        }
+    }
+}
+
+/// 缓存的连接状态
+static STATE: AtomicU8 = AtomicU8::new(0);
+
+/// 当前连接是否按流量计费
+static METERED: AtomicBool = AtomicBool::new(false);
+
+/// 计费状态变化时的操作（更新窗口底部的提示；持有窗口句柄，只要求 `Send`）
+static METERED_HANDLER: Mutex<Option<Box<dyn Fn(bool) + Send>>> = Mutex::new(None);
+
+/// 最近一次 Gmail API 请求成功的时间
+static LAST_API_SUCCESS: Mutex<ApiSuccess> = Mutex::new(ApiSuccess { at: None });
+
+/// 最近一次 API 请求成功的记录（成功的请求本身就证明网络可用）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ApiSuccess {
+    at: Option<Instant>,
+}
+
+impl ApiSuccess {
+    fn record(&mut self, now: Instant) {
+        self.at = Some(now);
+    }
+
+    /// 主动探测失败后作废（之后的失败重新探测）
+    fn invalidate(&mut self) {
+        self.at = None;
+    }
+
+    /// 是否需要主动探测：从未成功过，或最近一次成功已超过 `PROBE_TTL`
+    fn needs_probe(&self, now: Instant) -> bool {
+        self.at
+            .is_none_or(|at| now.saturating_duration_since(at) >= PROBE_TTL)
+    }
+}
+
+fn last_api_success() -> std::sync::MutexGuard<'static, ApiSuccess> {
+    LAST_API_SUCCESS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 记录一次成功的 Gmail API 请求
+pub fn record_api_success() {
+    last_api_success().record(Instant::now());
+    set_state(ConnectivityState::Online);
+}
+
+/// 获取缓存的连接状态
+pub fn state() -> ConnectivityState {
+    ConnectivityState::from_u8(STATE.load(Ordering::SeqCst))
+}
+
+/// 更新缓存的连接状态
+fn set_state(new_state: ConnectivityState) {
+    let old = ConnectivityState::from_u8(STATE.swap(new_state.to_u8(), Ordering::SeqCst));
+    if old != new_state {
+        tracing::info!("网络连接状态: {:?} -> {:?}", old, new_state);
+    }
+}
+
+/// 当前连接是否按流量计费
+pub fn is_metered() -> bool {
+    METERED.load(Ordering::SeqCst)
+}
+
+/// 更新计费状态，变化时调用注册的操作
+fn set_metered(metered: bool) {
+    if METERED.swap(metered, Ordering::SeqCst) != metered {
+        tracing::info!(
+            "网络计费状态: {}",
+            if metered {
+                "按流量计费"
+            } else {
+                "不计费"
+            }
+        );
+        let handler = METERED_HANDLER.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(handler) = handler.as_ref() {
+            handler(metered);
+        }
+    }
+}
+
+/// 注册计费状态变化时的操作（可能在系统线程中调用）；只有第一次注册有效
+pub fn set_metered_handler(handler: impl Fn(bool) + Send + 'static) {
+    let mut slot = METERED_HANDLER.lock().unwrap_or_else(|e| e.into_inner());
+    if slot.is_some() {
+        tracing::debug!("已注册过计费状态变化的操作，忽略");
+        return;
+    }
+    *slot = Some(Box::new(handler));
+}
+
+/// 系统连接提示是否表示按流量计费（固定流量套餐、按量计费、漫游、接近或超出流量上限）
+#[cfg(windows)]
+fn is_metered_hint(hint: &NL_NETWORK_CONNECTIVITY_HINT) -> bool {
+    hint.ConnectivityCost == NetworkConnectivityCostHintFixed
+        || hint.ConnectivityCost == NetworkConnectivityCostHintVariable
+        || hint.ApproachingDataLimit != 0
+        || hint.OverDataLimit != 0
+        || hint.Roaming != 0
+}
+
+/// 重置缓存状态为未知（睡眠恢复后调用，等待系统重新通知或下一次请求确认）
+pub fn reset() {
+    set_state(ConnectivityState::Unknown);
+}
+
+/// 系统连接提示变化回调（在系统线程中调用）
+#[cfg(windows)]
+unsafe extern "system" fn on_hint_change(
+    _context: *const c_void,
+    hint: NL_NETWORK_CONNECTIVITY_HINT,
+) {
+    set_state(ConnectivityState::from_hint_level(hint.ConnectivityLevel));
+    set_metered(is_metered_hint(&hint));
+}
+
+/// 订阅系统网络连接提示（启动时调用一次）
+///
+/// 需要 Windows 10 2004 及以上；订阅失败时状态保持 Unknown，同步照常进行
+#[cfg(windows)]
+pub fn start_monitor() {
+    let mut hint: NL_NETWORK_CONNECTIVITY_HINT = unsafe { std::mem::zeroed() };
+    // SAFETY: hint 为有效输出参数
+    if unsafe { GetNetworkConnectivityHint(&mut hint) } == ERROR_SUCCESS {
+        set_state(ConnectivityState::from_hint_level(hint.ConnectivityLevel));
+        set_metered(is_metered_hint(&hint));
+    }
+
+    let mut handle: HANDLE = std::ptr::null_mut();
+    // SAFETY: 回调为 'static 函数且不使用上下文；通知句柄在进程生命周期内保持有效，不取消订阅
+    let result = unsafe {
+        NotifyNetworkConnectivityHintChange(Some(on_hint_change), std::ptr::null(), 0, &mut handle)
+    };
+
+    if result == ERROR_SUCCESS {
+        tracing::info!(
+            "已订阅系统网络连接提示，当前状态: {:?}{}",
+            state(),
+            if is_metered() {
+                "（按流量计费）"
+            } else {
+                ""
+            }
+        );
+    } else {
+        tracing::warn!(
+            "订阅网络连接提示失败 (错误码 {})，仅在请求失败时主动探测",
+            result
+        );
+    }
+}
+
+/// 当前平台不支持订阅连接提示：状态保持 Unknown，同步照常进行
+#[cfg(not(windows))]
+pub fn start_monitor() {
+    tracing::info!("当前平台未订阅网络连接提示，仅在请求失败时主动探测");
+}
+
+/// 请求失败后主动探测网络是否可用
+///
+/// 依次尝试配置中的端点（HEAD 请求），任一端点返回任意 HTTP 响应即视为可达。
+/// 探测结果同步写回缓存状态
+pub async fn probe(endpoints: &[String]) -> bool {
+    let client = http_client::get_client();
+
+    let mut failures = Vec::new();
+    for endpoint in endpoints {
+        tracing::debug!("网络探测: {}", endpoint);
+        match client.head(endpoint).timeout(PROBE_TIMEOUT).send().await {
+            Ok(resp) => {
+                tracing::debug!("网络探测成功: {} (HTTP {})", endpoint, resp.status());
+                log_throttle::recovered(PROBE_LOG_SOURCE);
+                set_state(ConnectivityState::Online);
+                return true;
+            }
+            Err(e) => {
+                tracing::debug!("网络探测失败: {} - {}", endpoint, e);
+                failures.push(format!("{} - {}", endpoint, e));
+            }
+        }
+    }
+
+    // 断网期间每次请求失败都会探测：首次完整记录，之后只定期记录重复次数
+    log_throttle::error(
+        PROBE_LOG_SOURCE,
+        "offline",
+        format_args!(
+            "网络不可用：{} 个探测端点均无法访问\n{}",
+            endpoints.len(),
+            failures.join("\n")
+        ),
+    );
+    set_state(ConnectivityState::Offline);
+    last_api_success().invalidate();
+    false
+}
+
+/// 请求失败后确认网络时是否需要主动探测（最近 `PROBE_TTL` 内没有 Gmail API 请求成功）
+pub fn needs_probe() -> bool {
+    last_api_success().needs_probe(Instant::now())
+}
+
+/// 请求失败后确认网络是否可用
+///
+/// 最近 `PROBE_TTL` 内有 Gmail API 请求成功时直接视为可用（失败出在这个请求本身），
+/// 否则使用配置中的端点主动探测
+#[tracing::instrument(name = "network.ensure", skip_all)]
+pub async fn ensure_network_available() -> bool {
+    if !needs_probe() {
+        tracing::debug!("最近有 API 请求成功，跳过网络探测");
+        return true;
+    }
+    probe_configured().await
+}
+
+/// 使用配置文件中的端点列表主动探测
+pub async fn probe_configured() -> bool {
+    probe(&configured_endpoints()).await
+}
+
+/// 配置文件中的探测端点（读取失败时使用默认列表）
+fn configured_endpoints() -> Vec<String> {
+    config::load()
+        .map(|cfg| cfg.network.probe_endpoints)
+        .unwrap_or_else(|_| NetworkConfig::default().probe_endpoints)
+}
+
+/// 同步前确认是否离线
+///
+/// 离线状态可能只来自一次失败的探测，而 Linux 上没有系统提示、Windows 上的提示也可能不再变化；
+/// 只看缓存状态会一直跳过同步直到重启或睡眠恢复，因此缓存状态为 Offline 时先重新探测一次
+pub async fn confirm_offline() -> bool {
+    recheck_offline(configured_endpoints).await
+}
+
+async fn recheck_offline(endpoints: impl FnOnce() -> Vec<String>) -> bool {
+    if state() != ConnectivityState::Offline {
+        return false;
+    }
+    tracing::debug!("缓存的连接状态为离线，同步前重新探测");
+    !probe(&endpoints()).await
+}
+
+/// 判断错误是否为传输层失败（连接、超时等，而非服务器返回的错误状态）
+pub fn is_transport_error(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect() || e.is_timeout() || e.is_request())
+}
+
+/// 判断错误是否为请求超时
+pub fn is_timeout_error(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_timeout())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::gmail::mock::MockGmail;
+
+    #[test]
+    fn test_state_roundtrip() {
+        for s in [
+            ConnectivityState::Unknown,
+            ConnectivityState::Online,
+            ConnectivityState::Limited,
+            ConnectivityState::Offline,
+        ] {
+            assert_eq!(ConnectivityState::from_u8(s.to_u8()), s);
+        }
+    }
+
+    #[test]
+    fn test_needs_probe() {
+        let start = Instant::now();
+        let mut success = ApiSuccess { at: None };
+
+        // 从未成功过：探测
+        assert!(success.needs_probe(start));
+
+        // 刚刚成功：跳过
+        success.record(start);
+        assert!(!success.needs_probe(start));
+        assert!(!success.needs_probe(start + PROBE_TTL - Duration::from_secs(1)));
+
+        // 成功已过期：探测
+        assert!(success.needs_probe(start + PROBE_TTL));
+
+        // 之后再次成功：重新计时
+        success.record(start + PROBE_TTL);
+        assert!(!success.needs_probe(start + PROBE_TTL + Duration::from_secs(1)));
+
+        // 探测失败后作废：即使刚成功过也重新探测
+        success.invalidate();
+        assert!(success.needs_probe(start + PROBE_TTL + Duration::from_secs(1)));
+    }
+
+    #[tokio::test]
+    async fn test_offline_recovers_when_probe_succeeds() {
+        let server = MockGmail::start().await;
+        let endpoints = || vec![server.base_url().to_string()];
+
+        // 探测得到的离线状态不会一直保持：端点可达（任意 HTTP 响应）后恢复为在线
+        set_state(ConnectivityState::Offline);
+        assert!(!recheck_offline(endpoints).await);
+        assert_eq!(state(), ConnectivityState::Online);
+        assert_eq!(server.requests("/").len(), 1);
+
+        // 不是离线状态时不探测
+        assert!(!recheck_offline(endpoints).await);
+        assert_eq!(server.requests("/").len(), 1);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_is_metered_hint() {
+        use windows_sys::Win32::Networking::WinSock::NetworkConnectivityCostHintUnrestricted;
+
+        let mut hint: NL_NETWORK_CONNECTIVITY_HINT = unsafe { std::mem::zeroed() };
+        hint.ConnectivityCost = NetworkConnectivityCostHintUnrestricted;
+        assert!(!is_metered_hint(&hint));
+
+        hint.Roaming = 1;
+        assert!(is_metered_hint(&hint));
+
+        hint.Roaming = 0;
+        hint.ConnectivityCost = NetworkConnectivityCostHintFixed;
+        assert!(is_metered_hint(&hint));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_from_hint_level() {
+        assert_eq!(
+            ConnectivityState::from_hint_level(NetworkConnectivityLevelHintInternetAccess),
+            ConnectivityState::Online
+        );
+        assert_eq!(
+            ConnectivityState::from_hint_level(NetworkConnectivityLevelHintNone),
+            ConnectivityState::Offline
+        );
+        assert_eq!(
+            ConnectivityState::from_hint_level(NetworkConnectivityLevelHintLocalAccess),
+            ConnectivityState::Limited
+        );
+    }
+}
@@ -0,0 +1,132 @@
+/// 崩溃报告模块
+///
+/// release 构建没有控制台，panic 信息会直接丢失。此模块安装自定义 panic hook，
+/// 将 panic 信息、堆栈、版本号与时间写入 `logs\crash-<时间>.txt`，并同步追加一行到日志文件
+use chrono::Local;
+use std::backtrace::Backtrace;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+
+use super::{app_info, dialog, i18n, logging};
+
+/// 安装全局 panic hook
+///
+/// - 强制捕获堆栈（不依赖 `RUST_BACKTRACE` 环境变量）
+/// - 写入崩溃报告文件
+/// - 经由同一个轮转写入器直接追加到日志文件（不经过后台写入线程，进程随即退出也不会丢失）；
+///   文件日志不可用时才经由 tracing 记录，同一条 panic 不会在日志文件中出现两次
+/// - 主线程 panic 时弹出原生提示框，告知报告文件位置
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("<unnamed>").to_string();
+
+        let report = format_report(
+            &panic_message(info),
+            &info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+                .unwrap_or_else(|| "<unknown>".to_string()),
+            &thread_name,
+            &Backtrace::force_capture().to_string(),
+        );
+
+        let report_path = write_report(&report);
+
+        let message = format!(
+            "💥 线程 '{}' 发生 panic: {}（崩溃报告: {}）",
+            thread_name,
+            panic_message(info),
+            report_path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "写入失败".to_string())
+        );
+        match logging::append_now(&message) {
+            // 已直接写入日志文件；debug 构建另外输出到控制台
+            Ok(()) => {
+                if cfg!(debug_assertions) {
+                    eprintln!("{}", message);
+                }
+            }
+            Err(e) => {
+                tracing::error!("{}", message);
+                tracing::warn!("同步写入日志失败: {:#}", e);
+            }
+        }
+
+        if thread_name == "main" {
+            show_crash_message(report_path.as_ref());
+        }
+    }));
+}
+
+/// 提取 panic 消息（兼容 &str 与 String 两种 payload）
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<非字符串 panic payload>".to_string()
+    }
+}
+
+/// 生成崩溃报告文本
+fn format_report(message: &str, location: &str, thread: &str, backtrace: &str) -> String {
+    format!(
+        "NanoMail 崩溃报告\n\
+         版本: {} ({})\n\
+         时间: {}\n\
+         线程: {}\n\
+         位置: {}\n\
+         消息: {}\n\
+         \n\
+         堆栈:\n{}\n",
+        app_info::VERSION,
+        app_info::GIT_HASH,
+        Local::now().format("%Y-%m-%d %H:%M:%S%.3f %:z"),
+        thread,
+        location,
+        message,
+        backtrace
+    )
+}
+
+/// 写入崩溃报告文件，返回文件路径
+fn write_report(report: &str) -> Option<PathBuf> {
+    let dir = logging::logs_dir().ok()?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, report).ok()?;
+    Some(path)
+}
+
+/// 弹出原生提示框（进程即将退出前的最后提示）
+fn show_crash_message(report_path: Option<&PathBuf>) {
+    let text = match report_path {
+        Some(path) => i18n::tr_fmt("crash.with_report", &[&path.display()]),
+        None => i18n::tr("crash.plain").to_string(),
+    };
+
+    dialog::show_error(&text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report_contains_fields() {
+        let report = format_report("boom", "src/main.rs:1:1", "tray-events", "frame0");
+
+        assert!(report.contains(env!("CARGO_PKG_VERSION")));
+        assert!(report.contains("boom"));
+        assert!(report.contains("src/main.rs:1:1"));
+        assert!(report.contains("tray-events"));
+        assert!(report.contains("frame0"));
+    }
+}
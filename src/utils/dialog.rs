@@ -0,0 +1,135 @@
+/// 原生对话框模块
+///
+/// 在没有 UI（启动前、崩溃时、命令行模式）的场景下使用 Win32 MessageBox 提示用户。
+/// Linux 上依次尝试 zenity（GNOME）与 kdialog（KDE），都不可用时输出到标准错误
+#[cfg(windows)]
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    IDYES, MB_ICONERROR, MB_ICONINFORMATION, MB_ICONWARNING, MB_OK, MB_YESNO, MESSAGEBOX_STYLE,
+    MessageBoxW,
+};
+
+use super::app_info;
+
+/// 对话框标题
+const CAPTION: &str = app_info::APP_NAME;
+
+/// 显示消息框并返回用户点击的按钮 ID
+#[cfg(windows)]
+fn message_box(text: &str, style: MESSAGEBOX_STYLE) -> i32 {
+    let to_wide = |s: &str| {
+        s.encode_utf16()
+            .chain(std::iter::once(0))
+            .collect::<Vec<u16>>()
+    };
+    let text = to_wide(text);
+    let caption = to_wide(CAPTION);
+
+    // SAFETY: 两个参数均为以 0 结尾的 UTF-16 字符串，且在调用期间保持有效
+    unsafe { MessageBoxW(std::ptr::null_mut(), text.as_ptr(), caption.as_ptr(), style) }
+}
+
+/// 显示错误提示
+#[cfg(windows)]
+pub fn show_error(text: &str) {
+    message_box(text, MB_OK | MB_ICONERROR);
+}
+
+/// 显示普通信息
+#[cfg(windows)]
+pub fn show_info(text: &str) {
+    message_box(text, MB_OK | MB_ICONINFORMATION);
+}
+
+/// 显示确认框，用户点击"是"时返回 true
+#[cfg(windows)]
+pub fn confirm(text: &str) -> bool {
+    message_box(text, MB_YESNO | MB_ICONWARNING) == IDYES
+}
+
+/// 显示普通询问框（信息图标），用户点击"是"时返回 true
+#[cfg(windows)]
+pub fn ask(text: &str) -> bool {
+    message_box(text, MB_YESNO | MB_ICONINFORMATION) == IDYES
+}
+
+/// 对话框类型
+#[cfg(not(windows))]
+#[derive(Debug, Clone, Copy)]
+enum DialogKind {
+    Error,
+    Info,
+    Confirm,
+    Ask,
+}
+
+/// 用外部对话框程序显示消息，返回用户是否点击了"确定"/"是"
+///
+/// 两个程序都不可用时把消息写到标准错误，询问类对话框视为"否"
+#[cfg(not(windows))]
+fn message_box(text: &str, kind: DialogKind) -> bool {
+    use std::process::Command;
+
+    let zenity_kind = match kind {
+        DialogKind::Error => "--error",
+        DialogKind::Info => "--info",
+        DialogKind::Confirm | DialogKind::Ask => "--question",
+    };
+    let mut zenity = Command::new("zenity");
+    zenity
+        .arg(zenity_kind)
+        .arg("--no-markup")
+        .arg(format!("--title={}", CAPTION))
+        .arg(format!("--text={}", text));
+    if matches!(kind, DialogKind::Confirm) {
+        zenity.arg("--icon-name=dialog-warning");
+    }
+
+    let kdialog_kind = match kind {
+        DialogKind::Error => "--error",
+        DialogKind::Info => "--msgbox",
+        DialogKind::Confirm => "--warningyesno",
+        DialogKind::Ask => "--yesno",
+    };
+    let mut kdialog = Command::new("kdialog");
+    kdialog
+        .arg("--title")
+        .arg(CAPTION)
+        .arg(kdialog_kind)
+        .arg(text);
+
+    for mut command in [zenity, kdialog] {
+        match command.status() {
+            // 退出码 0 表示"确定"/"是"，1 表示"否"或关闭
+            Ok(status) => return status.success(),
+            Err(e) => tracing::debug!("对话框程序不可用 {:?}: {}", command.get_program(), e),
+        }
+    }
+
+    tracing::warn!("未找到 zenity 或 kdialog，对话框内容输出到标准错误");
+    eprintln!("{}: {}", CAPTION, text);
+    false
+}
+
+/// 显示错误提示
+#[cfg(not(windows))]
+pub fn show_error(text: &str) {
+    message_box(text, DialogKind::Error);
+}
+
+/// 显示普通信息
+#[cfg(not(windows))]
+pub fn show_info(text: &str) {
+    message_box(text, DialogKind::Info);
+}
+
+/// 显示确认框，用户点击"是"时返回 true
+#[cfg(not(windows))]
+pub fn confirm(text: &str) -> bool {
+    message_box(text, DialogKind::Confirm)
+}
+
+/// 显示普通询问框（信息图标），用户点击"是"时返回 true
+#[cfg(not(windows))]
+pub fn ask(text: &str) -> bool {
+    message_box(text, DialogKind::Ask)
+}
@@ -5,29 +5,259 @@
 /// 2. 避免每个 API 调用都创建新客户端（reqwest::Client 初始化成本高）
 /// 3. 自动处理连接池管理和 Keep-Alive
 ///
-/// reqwest 官方推荐：共享单个 Client 实例而不是为每个请求创建新实例
-use once_cell::sync::Lazy;
-use reqwest::Client;
+/// reqwest 官方推荐：共享单个 Client 实例而不是为每个请求创建新实例。
+/// 客户端由 [`HttpClientFactory`] 根据 `[network]` 配置构建，代理设置变化时可重建；
+/// Gmail API、OAuth2 与头像下载均通过此处获取客户端，保证代理行为一致
+use anyhow::{Context, Result};
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy, redirect};
+use std::sync::RwLock;
 use std::time::Duration;
 
-/// 全局 HTTP 客户端实例（使用懒初始化）
-pub static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
-    Client::builder()
-        // 连接池配置
-        .pool_max_idle_per_host(2) // 每个主机最多保留 2 个空闲连接（只需连接 Google 服务器）
-        .pool_idle_timeout(Duration::from_secs(300)) // 连接空闲 5 分钟后关闭
-        // 超时配置
-        .timeout(Duration::from_secs(30)) // 整体请求超时 30 秒
-        .connect_timeout(Duration::from_secs(10)) // 连接建立超时 10 秒
-        // 重定向配置
-        .redirect(reqwest::redirect::Policy::limited(5)) // 最多跟随 5 个重定向
-        // 用户代理
-        .user_agent("NanoMail/0.1.0 (Windows; U; Rust) Gecko")
-        .build()
-        .expect("构建全局 HTTP 客户端失败")
+use super::{app_info, proxy};
+use crate::config::{self, NetworkConfig};
+
+/// 全局 HTTP 客户端实例（首次使用或启动时构建）
+static HTTP_CLIENT: RwLock<Option<Client>> = RwLock::new(None);
+
+/// OAuth2 请求专用的客户端（不跟随重定向，与共享客户端一起构建）
+static OAUTH_CLIENT: RwLock<Option<Client>> = RwLock::new(None);
+
+/// 按请求类型覆盖的超时时间（随客户端一起更新）
+static REQUEST_TIMEOUTS: RwLock<RequestTimeouts> = RwLock::new(RequestTimeouts {
+    count: Duration::from_secs(8),
+    avatar: Duration::from_secs(60),
 });
 
+/// 按请求类型覆盖的超时时间
+#[derive(Debug, Clone, Copy)]
+struct RequestTimeouts {
+    /// 未读数查询（快速截止）
+    count: Duration,
+    /// 头像下载（允许较慢的链路）
+    avatar: Duration,
+}
+
+/// HTTP 客户端工厂
+pub struct HttpClientFactory;
+
+impl HttpClientFactory {
+    /// 根据网络配置构建客户端
+    ///
+    /// 代理优先级：配置文件 `[network] proxy` > 系统代理（环境变量 / Internet 选项 / WinHTTP）
+    pub fn build(network: &NetworkConfig) -> Result<Client> {
+        Self::builder(network)?
+            .redirect(redirect::Policy::limited(5)) // 最多跟随 5 个重定向
+            .build()
+            .context("构建 HTTP 客户端失败")
+    }
+
+    /// 构建 OAuth2 请求使用的客户端（代理与超时同 [`Self::build`]）
+    ///
+    /// 令牌端点与撤销端点的请求携带授权码和令牌，不跟随重定向，避免凭据被转发到其他地址
+    pub fn build_oauth(network: &NetworkConfig) -> Result<Client> {
+        Self::builder(network)?
+            .redirect(redirect::Policy::none())
+            .build()
+            .context("构建 OAuth2 HTTP 客户端失败")
+    }
+
+    /// 共享的客户端配置（不含重定向策略）
+    fn builder(network: &NetworkConfig) -> Result<ClientBuilder> {
+        let builder = Client::builder()
+            // 连接池配置
+            .pool_max_idle_per_host(2) // 每个主机最多保留 2 个空闲连接（只需连接 Google 服务器）
+            .pool_idle_timeout(Duration::from_secs(300)) // 连接空闲 5 分钟后关闭
+            // 超时配置（单个请求可通过 RequestBuilder::timeout 覆盖整体超时）
+            .timeout(Duration::from_secs(network.request_timeout_secs))
+            .connect_timeout(Duration::from_secs(network.connect_timeout_secs))
+            // 用户代理
+            .user_agent(app_info::USER_AGENT);
+
+        Ok(match Self::resolve_proxy(network)? {
+            Some(proxy) => builder.proxy(proxy),
+            None => builder.no_proxy(),
+        })
+    }
+
+    /// 从配置文件构建并安装全局客户端（启动时调用）
+    ///
+    /// 配置无效时记录错误并退回到不带显式代理的默认客户端，不阻止启动
+    pub fn init() {
+        let network = config::load().map(|cfg| cfg.network).unwrap_or_default();
+        if let Err(e) = Self::rebuild(&network) {
+            tracing::error!("按配置构建 HTTP 客户端失败，使用默认设置: {:#}", e);
+            if let Err(e) = Self::rebuild(&NetworkConfig::default()) {
+                tracing::error!("构建默认 HTTP 客户端失败: {:#}", e);
+            }
+        }
+    }
+
+    /// 用新的网络配置重建全局客户端（代理设置变化时调用）
+    ///
+    /// 已发出的请求继续使用旧客户端直至完成
+    pub fn rebuild(network: &NetworkConfig) -> Result<()> {
+        let client = Self::build(network)?;
+        let oauth = Self::build_oauth(network)?;
+        *HTTP_CLIENT.write().unwrap_or_else(|e| e.into_inner()) = Some(client);
+        *OAUTH_CLIENT.write().unwrap_or_else(|e| e.into_inner()) = Some(oauth);
+        *REQUEST_TIMEOUTS.write().unwrap_or_else(|e| e.into_inner()) = RequestTimeouts {
+            count: Duration::from_secs(network.count_timeout_secs),
+            avatar: Duration::from_secs(network.avatar_timeout_secs),
+        };
+        tracing::info!("HTTP 客户端已重建");
+        Ok(())
+    }
+
+    /// 解析代理设置
+    fn resolve_proxy(network: &NetworkConfig) -> Result<Option<Proxy>> {
+        let explicit = network
+            .proxy
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+
+        let proxy = match explicit {
+            Some(value) if value.eq_ignore_ascii_case("direct") => {
+                tracing::info!("代理: 已在配置中禁用（direct）");
+                return Ok(None);
+            }
+            Some(value) => {
+                tracing::info!("代理: 使用配置文件指定的代理 {}", value);
+                Proxy::all(value).with_context(|| format!("代理地址无效: {}", value))?
+            }
+            None => match proxy::detect_system_proxy() {
+                Some(system) => {
+                    tracing::info!(
+                        "代理: 使用系统代理 {}（来源: {}）",
+                        system.url,
+                        system.source
+                    );
+                    let proxy = Proxy::all(&system.url)
+                        .with_context(|| format!("系统代理地址无效: {}", system.url))?;
+                    match system.bypass.as_deref().and_then(NoProxy::from_string) {
+                        Some(bypass) => proxy.no_proxy(Some(bypass)),
+                        None => proxy,
+                    }
+                }
+                None => {
+                    tracing::info!("代理: 未检测到系统代理，直接连接");
+                    return Ok(None);
+                }
+            },
+        };
+
+        let proxy = match &network.proxy_username {
+            Some(username) => {
+                let password = network
+                    .decrypt_proxy_password()
+                    .context("解密代理密码失败")?
+                    .unwrap_or_default();
+                proxy.basic_auth(username, &password)
+            }
+            None => proxy,
+        };
+
+        Ok(Some(proxy))
+    }
+}
+
 /// 获取全局 HTTP 客户端
-pub fn get_client() -> &'static Client {
-    &HTTP_CLIENT
+///
+/// 返回共享客户端的句柄（内部为 Arc，克隆开销很小）；尚未初始化时按当前配置构建
+pub fn get_client() -> Client {
+    if let Some(client) = HTTP_CLIENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        return client.clone();
+    }
+
+    HttpClientFactory::init();
+    HTTP_CLIENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// 获取 OAuth2 请求专用的客户端（不跟随重定向）
+///
+/// 尚未初始化时按当前配置构建，与 [`get_client`] 相同
+pub fn oauth_client() -> Client {
+    if let Some(client) = OAUTH_CLIENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        return client.clone();
+    }
+
+    HttpClientFactory::init();
+    OAUTH_CLIENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| {
+            Client::builder()
+                .redirect(redirect::Policy::none())
+                .build()
+                .unwrap_or_default()
+        })
+}
+
+/// 未读数查询的超时时间（较短，超出即视为网络缓慢）
+pub fn count_timeout() -> Duration {
+    REQUEST_TIMEOUTS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .count
+}
+
+/// 头像下载的超时时间（较长）
+pub fn avatar_timeout() -> Duration {
+    REQUEST_TIMEOUTS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .avatar
+}
+
+/// 供 oauth2 使用的异步 HTTP 函数（替代 `oauth2::reqwest::async_http_client`）
+///
+/// oauth2 自带的实现会创建独立的客户端，绕过代理配置；这里使用与共享客户端相同代理设置的
+/// [`oauth_client`]，与 oauth2 自带实现一样不跟随重定向
+pub async fn oauth_http_client(
+    request: oauth2::HttpRequest,
+) -> Result<oauth2::HttpResponse, reqwest::Error> {
+    // oauth2 与 reqwest 依赖的 http 版本不同，按字符串/字节转换
+    let method = reqwest::Method::from_bytes(request.method.as_str().as_bytes())
+        .unwrap_or(reqwest::Method::POST);
+
+    let mut builder = oauth_client()
+        .request(method, request.url.as_str())
+        .body(request.body);
+    for (name, value) in request.headers.iter() {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+
+    let response = builder.send().await?;
+
+    let status_code = oauth2::http::StatusCode::from_u16(response.status().as_u16())
+        .unwrap_or(oauth2::http::StatusCode::BAD_GATEWAY);
+    let mut headers = oauth2::http::HeaderMap::new();
+    for (name, value) in response.headers() {
+        if let (Ok(name), Ok(value)) = (
+            oauth2::http::HeaderName::from_bytes(name.as_str().as_bytes()),
+            oauth2::http::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    let body = response.bytes().await?.to_vec();
+
+    Ok(oauth2::HttpResponse {
+        status_code,
+        headers,
+        body,
+    })
 }
@@ -0,0 +1,981 @@
+/// 界面文本本地化模块
+///
+/// Rust 端生成的界面文本（托盘菜单、系统通知、提示条、对话框）统一通过 `tr` 按当前语言取出；
+/// Slint 端的文本通过 `Strings` 全局对象由 `apply_to_ui` 填充。
+/// 语言来自配置 `app.language`："auto"（跟随系统界面语言）| "en" | "zh-CN"
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    ZhCn = 0,
+    En = 1,
+}
+
+impl Language {
+    /// 解析配置值（"auto" 及无法识别的值跟随系统界面语言）
+    pub fn from_setting(setting: &str) -> Self {
+        match setting.trim().to_ascii_lowercase().as_str() {
+            "en" | "en-us" | "en-gb" => Self::En,
+            "zh" | "zh-cn" | "zh-hans" => Self::ZhCn,
+            "auto" => Self::system(),
+            other => {
+                tracing::warn!("未知的界面语言 \"{}\"，跟随系统语言", other);
+                Self::system()
+            }
+        }
+    }
+
+    /// 系统界面语言（中文以外一律使用英文）
+    #[cfg(windows)]
+    pub fn system() -> Self {
+        /// LANGID 的主语言部分：LANG_CHINESE
+        const LANG_CHINESE: u16 = 0x04;
+
+        // SAFETY: 无参数的只读查询
+        let lang_id = unsafe { windows_sys::Win32::Globalization::GetUserDefaultUILanguage() };
+        if lang_id & 0x3FF == LANG_CHINESE {
+            Self::ZhCn
+        } else {
+            Self::En
+        }
+    }
+
+    /// 系统界面语言（按 POSIX 语言环境变量，中文以外一律使用英文）
+    #[cfg(not(windows))]
+    pub fn system() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        if locale.starts_with("zh") {
+            Self::ZhCn
+        } else {
+            Self::En
+        }
+    }
+}
+
+/// 当前语言
+static CURRENT: AtomicU8 = AtomicU8::new(Language::ZhCn as u8);
+
+/// 设置当前语言
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::SeqCst);
+    tracing::info!("界面语言: {:?}", language);
+}
+
+/// 获取当前语言
+pub fn language() -> Language {
+    if CURRENT.load(Ordering::SeqCst) == Language::En as u8 {
+        Language::En
+    } else {
+        Language::ZhCn
+    }
+}
+
+/// 文本表：(键, 中文, 英文)，`{}` 为按顺序填充的占位符
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("tray.show_window", "显示窗口", "Show window"),
+    ("tray.open_gmail", "打开 Gmail", "Open Gmail"),
+    ("tray.copy_summary", "复制未读摘要", "Copy summary"),
+    ("tray.summary_copied", "未读摘要已复制", "Summary copied"),
+    ("tray.summary_empty", "还没有账户", "No accounts yet"),
+    ("tray.diagnostics", "运行诊断", "Run diagnostics"),
+    ("tray.troubleshooting", "故障排查", "Troubleshooting"),
+    (
+        "tray.open_data_folder",
+        "打开数据文件夹",
+        "Open data folder",
+    ),
+    ("tray.view_logs", "查看日志", "View logs"),
+    ("tray.sync_history", "同步记录", "Sync history"),
+    ("tray.open_failed", "无法打开 {}", "Could not open {}"),
+    ("tray.about", "关于 NanoMail", "About NanoMail"),
+    ("tray.quit", "退出", "Quit"),
+    ("tray.account_unread", "{} 封未读", "{} unread"),
+    (
+        "tray.tooltip",
+        "NanoMail - Gmail 通知客户端",
+        "NanoMail - Gmail notifier",
+    ),
+    (
+        "notification.title",
+        "📬 NanoMail - 新邮件",
+        "📬 NanoMail - New mail",
+    ),
+    (
+        "notification.body_one",
+        "{} 收到 1 封新邮件",
+        "{} received 1 new email",
+    ),
+    (
+        "notification.body_many",
+        "{} 收到 {} 封新邮件",
+        "{} received {} new emails",
+    ),
+    ("notification.preview", "{}：{}", "{}: {}"),
+    ("notification.no_subject", "（无主题）", "(no subject)"),
+    ("notification.more", "还有 {} 封", "and {} more"),
+    (
+        "notification.combined",
+        "{} 个账户收到新邮件：{}",
+        "New mail in {} accounts: {}",
+    ),
+    ("notification.open_window", "打开 NanoMail", "Open NanoMail"),
+    (
+        "notification.alert_title",
+        "🔔 NanoMail - 未读提醒",
+        "🔔 NanoMail - Unread alert",
+    ),
+    (
+        "notification.alert_body",
+        "{} 的未读邮件已达 {} 封",
+        "{} passed {} unread",
+    ),
+    ("ui.new_version", "新版本", "New version"),
+    (
+        "ui.metered_note",
+        "按流量计费 · 已减少同步",
+        "Metered · reduced syncing",
+    ),
+    (
+        "ui.tray_missing_note",
+        "托盘图标创建失败，程序以无托盘模式运行。窗口隐藏后再次运行 NanoMail 即可重新打开。",
+        "Couldn't create the tray icon; NanoMail is running without one. Run NanoMail again to reopen the window after hiding it.",
+    ),
+    (
+        "config.persist_failed",
+        "设置未能保存到 config.toml（文件可能被其他程序占用），本次运行中仍然有效",
+        "Couldn't save the setting to config.toml (the file may be locked by another program); it stays in effect until NanoMail exits",
+    ),
+    (
+        "ui.config_issues_note",
+        "配置文件中有无效或不生效的设置（详见日志）：",
+        "Some settings in config.toml are invalid or have no effect (see the log): ",
+    ),
+    ("status.unread", "有未读邮件", "Unread mail"),
+    (
+        "status.attention",
+        "未读邮件超过提醒阈值",
+        "Unread mail over alert threshold",
+    ),
+    (
+        "status.degraded",
+        "连接不稳定，正在重试",
+        "Connection issues, retrying",
+    ),
+    ("status.error", "同步失败", "Sync failing"),
+    (
+        "status.paused",
+        "全部账户已暂停提醒",
+        "All accounts snoozed",
+    ),
+    ("imap.title", "添加 IMAP 账户", "Add IMAP account"),
+    (
+        "credentials.title",
+        "无法使用保存的凭据",
+        "Saved credentials can't be used",
+    ),
+    (
+        "credentials.key_unavailable",
+        "无法取得本机加密密钥（{}）。常见于注册表权限变化或无法读取机器 ID。",
+        "This machine's encryption key is unavailable ({}). This usually means registry permissions changed or the machine ID can't be read.",
+    ),
+    (
+        "credentials.roundtrip",
+        "加密自检失败（{}）。",
+        "The encryption self-test failed ({}).",
+    ),
+    (
+        "credentials.key_changed",
+        "本机的加密密钥已变化（如重装系统、机器 ID 变化或更新后密钥派生方式不同），之前保存的账户凭据都无法解密。",
+        "This machine's encryption key has changed (after reinstalling, a machine ID change or an update that derives the key differently), so none of the saved credentials can be decrypted.",
+    ),
+    (
+        "credentials.body",
+        "同步已暂停。重新授权全部账户后恢复同步；IMAP 账户需要删除后重新添加。",
+        "Syncing is paused. Re-authorize all accounts to resume; IMAP accounts need to be removed and added again.",
+    ),
+    (
+        "credentials.reauth_all",
+        "重新授权全部账户",
+        "Re-authorize all accounts",
+    ),
+    ("credentials.help", "查看说明", "Learn more"),
+    (
+        "credentials.accept_failed",
+        "仍然无法使用本机密钥：{}",
+        "This machine's key still can't be used: {}",
+    ),
+    ("imap.email", "邮箱地址", "Email address"),
+    ("imap.host", "IMAP 服务器", "IMAP server"),
+    ("imap.port", "端口", "Port"),
+    (
+        "imap.username",
+        "用户名（留空使用邮箱地址）",
+        "Username (defaults to the email address)",
+    ),
+    (
+        "imap.password",
+        "密码或应用专用密码",
+        "Password or app password",
+    ),
+    (
+        "imap.idle",
+        "实时推送新邮件（IDLE）",
+        "Push new mail (IDLE)",
+    ),
+    ("imap.test", "测试连接", "Test connection"),
+    ("imap.save", "保存", "Save"),
+    ("imap.cancel", "取消", "Cancel"),
+    (
+        "auth.declined",
+        "已取消授权，未添加账户",
+        "Authorization cancelled, no account was added",
+    ),
+    (
+        "auth.failed",
+        "添加账户失败：{}",
+        "Failed to add the account: {}",
+    ),
+    (
+        "auth.ports_occupied",
+        "无法接收授权回调：本机端口 {} 均被其他程序占用。\n\n{}\n\n\
+         请关闭这些程序后重试，或在 config.toml 的 [oauth] 中设置 redirect_port 为空闲端口，\
+         并把 redirect_uri 改为 http://localhost:<端口>（服务端也需登记同一回调地址）。",
+        "Cannot receive the authorization callback: local ports {} are all in use by other programs.\n\n{}\n\n\
+         Close these programs and try again, or set redirect_port in the [oauth] section of config.toml \
+         to a free port and change redirect_uri to http://localhost:<port> \
+         (the same redirect URI must also be registered with the provider).",
+    ),
+    (
+        "auth.ports_owner_unknown",
+        "（无法查询占用端口的程序）",
+        "(Could not determine which programs hold the ports)",
+    ),
+    ("imap.connecting", "正在连接…", "Connecting…"),
+    ("imap.test_ok", "连接成功", "Connection succeeded"),
+    (
+        "imap.incomplete",
+        "请填写邮箱地址、服务器和密码",
+        "Please fill in the email address, server and password",
+    ),
+    ("imap.invalid_port", "端口无效", "Invalid port"),
+    ("imap.failed", "连接失败：{}", "Connection failed: {}"),
+    ("menu.count_all_unread", "统计全部未读", "Count all unread"),
+    (
+        "menu.count_since_added",
+        "只统计添加账户后的新邮件",
+        "Only count mail since added",
+    ),
+    ("menu.reset_counter", "将计数清零", "Reset counter to zero"),
+    (
+        "menu.snooze_30m",
+        "暂停提醒 30 分钟",
+        "Snooze for 30 minutes",
+    ),
+    ("menu.snooze_1h", "暂停提醒 1 小时", "Snooze for 1 hour"),
+    (
+        "menu.snooze_tomorrow",
+        "暂停提醒到明天早上",
+        "Snooze until tomorrow morning",
+    ),
+    ("menu.unsnooze", "恢复提醒", "Unsnooze"),
+    ("menu.accent_color", "强调色", "Accent color"),
+    ("menu.watch_spam", "监视垃圾邮件", "Watch spam folder"),
+    (
+        "menu.people_only",
+        "只统计真人来信",
+        "Count only mail from people",
+    ),
+    (
+        "menu.watch_vacation",
+        "提示自动回复",
+        "Show auto-reply status",
+    ),
+    (
+        "menu.watch_vacation_no_scope",
+        "提示自动回复（需要重新授权）",
+        "Show auto-reply status (reauthorize first)",
+    ),
+    ("menu.push", "实时推送", "Instant push"),
+    (
+        "menu.push_no_scope",
+        "实时推送（需要完整邮箱权限并重新授权）",
+        "Instant push (needs full mailbox access; reauthorize)",
+    ),
+    (
+        "sparkline.range",
+        "24 小时内未读：最低 {} · 最高 {}",
+        "Unread in the last 24h: min {} · max {}",
+    ),
+    ("menu.open_inbox", "打开收件箱", "Open inbox"),
+    ("menu.sync_now", "立即同步", "Sync now"),
+    ("menu.reauthorize", "重新授权", "Re-authorize"),
+    ("menu.rename", "重命名", "Rename"),
+    ("menu.group", "设置分组", "Set group"),
+    ("menu.remove", "删除账户", "Remove account"),
+    ("menu.token_details", "Token 详情", "Token details"),
+    ("details.title", "Token 详情", "Token details"),
+    ("details.expires_at", "过期时间", "Expires at"),
+    ("details.remaining", "剩余时间", "Remaining"),
+    ("details.refresh_token", "Refresh Token", "Refresh token"),
+    ("details.present", "已保存", "Stored"),
+    (
+        "details.missing",
+        "缺失（需要重新授权）",
+        "Missing (re-authorize required)",
+    ),
+    ("details.refreshed_at", "上次刷新", "Last refreshed"),
+    ("details.refresh_token_issued", "Token 签发", "Token issued"),
+    ("details.issued_value", "{}（{}）", "{} ({})"),
+    ("details.scopes", "权限范围", "Scopes"),
+    ("details.mail_access", "邮件权限", "Mail access"),
+    (
+        "details.read_only",
+        "只读（重新授权以授予修改权限）",
+        "Read-only (re-authorize to grant modify access)",
+    ),
+    ("details.can_modify", "可修改邮件", "Can modify mail"),
+    ("details.api_usage", "API 请求", "API requests"),
+    (
+        "details.api_usage_value",
+        "最近 1 分钟 {} 次，24 小时 {} 次",
+        "{} in the last minute, {} in 24 hours",
+    ),
+    ("details.alert_threshold", "未读提醒", "Unread alert"),
+    (
+        "details.alert_placeholder",
+        "达到此未读数时提醒，留空不提醒",
+        "Alert at this many unread (blank = off)",
+    ),
+    (
+        "details.alert_invalid",
+        "提醒阈值须为正整数",
+        "The alert threshold must be a positive number",
+    ),
+    (
+        "details.alert_saved",
+        "未读邮件达到 {} 封时提醒",
+        "Will alert at {} unread",
+    ),
+    (
+        "details.alert_cleared",
+        "已关闭未读提醒",
+        "Unread alert turned off",
+    ),
+    ("details.group", "分组", "Group"),
+    (
+        "details.group_placeholder",
+        "如 工作、个人，留空不分组",
+        "e.g. Work, Personal (blank = none)",
+    ),
+    (
+        "details.group_saved",
+        "已移到分组「{}」",
+        "Moved to group \"{}\"",
+    ),
+    ("details.group_cleared", "已取消分组", "Removed from group"),
+    ("details.badge_label", "徽章标签", "Badge label"),
+    ("details.notify_label", "通知标签", "Notify label"),
+    ("details.label_inbox", "收件箱", "Inbox"),
+    ("details.labels_refresh", "刷新", "Refresh"),
+    (
+        "details.labels_refreshed",
+        "已重新读取标签列表",
+        "Label list refreshed",
+    ),
+    (
+        "details.labels_saved",
+        "已保存标签设置，正在重新同步",
+        "Label settings saved, syncing again",
+    ),
+    (
+        "details.labels_failed",
+        "无法读取标签列表: {}",
+        "Couldn't load labels: {}",
+    ),
+    ("details.unknown", "未知", "Unknown"),
+    ("details.refresh", "立即刷新 Token", "Refresh token now"),
+    ("details.close", "关闭", "Close"),
+    ("history.title", "同步记录", "Sync history"),
+    ("history.synced", "成功", "Synced"),
+    ("history.failed", "失败", "Failed"),
+    ("history.offline", "离线", "Offline"),
+    ("history.unavailable", "服务已停用", "Service disabled"),
+    ("history.suppressed", "未上报", "Not reported"),
+    (
+        "history.other",
+        "已停用或未上报",
+        "Disabled or not reported",
+    ),
+    ("history.time", "时间", "Time"),
+    ("history.duration", "耗时", "Duration"),
+    ("history.unread", "未读", "Unread"),
+    ("history.accounts", "账户", "Accounts"),
+    (
+        "history.empty",
+        "本次运行中还没有同步记录",
+        "No sync rounds recorded yet",
+    ),
+    ("history.just_now", "刚刚", "Just now"),
+    ("history.minutes_ago", "{} 分钟前", "{} min ago"),
+    ("history.hours_ago", "{} 小时前", "{} h ago"),
+    ("history.days_ago", "{} 天前", "{} days ago"),
+    ("history.duration_ms", "{} ms", "{} ms"),
+    ("details.expires_in", "还剩 {} 分钟", "{} min left"),
+    (
+        "reauth.in_days",
+        "{} 天后需要重新授权",
+        "Re-authorization needed in {} days",
+    ),
+    (
+        "reauth.in_hours",
+        "{} 小时内需要重新授权",
+        "Re-authorization needed within {} h",
+    ),
+    ("reauth.due", "需要重新授权", "Re-authorization needed"),
+    ("reauth.issued_days_ago", "{} 天前", "{} days ago"),
+    ("reauth.issued_hours_ago", "{} 小时前", "{} h ago"),
+    (
+        "details.expired_ago",
+        "已过期 {} 分钟",
+        "Expired {} min ago",
+    ),
+    ("details.refreshing", "正在刷新…", "Refreshing…"),
+    (
+        "details.refreshed",
+        "已刷新，新的过期时间: {}",
+        "Refreshed, now expires at {}",
+    ),
+    (
+        "details.refresh_failed",
+        "刷新失败: {}",
+        "Refresh failed: {}",
+    ),
+    ("account.removed", "已删除账户 {}", "Removed {}"),
+    ("account.undo", "撤销", "Undo"),
+    ("account.renamed", "已重命名为 {}", "Renamed to {}"),
+    ("account.reauthorized", "已重新授权 {}", "Re-authorized {}"),
+    (
+        "account.reauth_mismatch",
+        "授权的是另一个账户（{}），已将其添加到列表",
+        "A different account ({}) was authorized and has been added",
+    ),
+    (
+        "account.reauth_failed",
+        "重新授权失败：{}",
+        "Re-authorization failed: {}",
+    ),
+    (
+        "header.total_unread",
+        "共 {} 封未读（{} 个账户）",
+        "{} unread across {} accounts",
+    ),
+    ("account.spam", "垃圾邮件: {}", "spam: {}"),
+    (
+        "account.unfiltered",
+        "未读超过 {} 封，显示全部未读",
+        "over {} unread, showing all",
+    ),
+    ("account.updating", "更新中…", "updating…"),
+    ("account.vacation_on", "自动回复中", "Auto-reply on"),
+    (
+        "account.vacation_subject",
+        "自动回复中: {}",
+        "Auto-reply on: {}",
+    ),
+    ("account.group_other", "其他", "Other"),
+    ("tray.spam", "垃圾邮件: {}", "Spam: {}"),
+    (
+        "account.unavailable",
+        "管理员已停用此账户的 Gmail",
+        "Gmail is disabled for this account by your administrator",
+    ),
+    (
+        "account.foreign_credentials",
+        "凭据是在另一台电脑上创建的，请重新授权",
+        "Credentials were created on a different computer; please re-authorize",
+    ),
+    (
+        "account.unreadable_credentials",
+        "凭据无法读取，请重新授权",
+        "Credentials cannot be read; please re-authorize",
+    ),
+    (
+        "account.unencrypted_credentials",
+        "账户文件中的凭据是明文且无法加密，请检查本机密钥后重新授权",
+        "Credentials in the account file are plaintext and could not be encrypted; check this computer's key and re-authorize",
+    ),
+    ("doctor.config", "配置文件", "Config file"),
+    ("doctor.config_ok", "格式正确", "Parsed successfully"),
+    (
+        "doctor.config_missing",
+        "不存在，将使用默认配置",
+        "Not found, defaults will be used",
+    ),
+    ("doctor.config_bad", "无法解析：{}", "Cannot be parsed: {}"),
+    (
+        "doctor.config_hint",
+        "修正 config.toml 中的错误，或使用 --reset-config 重置",
+        "Fix the error in config.toml, or run with --reset-config",
+    ),
+    ("doctor.accounts", "账户文件", "Accounts file"),
+    ("doctor.accounts_ok", "{} 个账户", "{} account(s)"),
+    ("doctor.accounts_bad", "无法读取：{}", "Cannot be read: {}"),
+    (
+        "doctor.accounts_hint",
+        "accounts.toml 可能已损坏，备份后删除并重新添加账户",
+        "accounts.toml may be corrupt; back it up, delete it and add the accounts again",
+    ),
+    ("doctor.oauth", "{} OAuth 配置", "{} OAuth config"),
+    ("doctor.oauth_ok", "已配置", "Configured"),
+    (
+        "doctor.oauth_placeholder",
+        "仍为默认占位符",
+        "Still the default placeholder",
+    ),
+    (
+        "doctor.oauth_hint_gmail",
+        "设置环境变量 GMAIL_CLIENT_ID / GMAIL_CLIENT_SECRET，或在 config.toml 的 [oauth] 段填写",
+        "Set GMAIL_CLIENT_ID / GMAIL_CLIENT_SECRET, or fill in the [oauth] section of config.toml",
+    ),
+    (
+        "doctor.oauth_hint_outlook",
+        "设置环境变量 OUTLOOK_CLIENT_ID，或在 config.toml 的 [oauth.outlook] 段填写",
+        "Set OUTLOOK_CLIENT_ID, or fill in the [oauth.outlook] section of config.toml",
+    ),
+    ("doctor.secrets", "凭据 {}", "Credentials for {}"),
+    ("doctor.secrets_ok", "可以解密", "Decrypted successfully"),
+    (
+        "doctor.secrets_bad",
+        "无法解密：{}",
+        "Cannot be decrypted: {}",
+    ),
+    (
+        "doctor.secrets_hint",
+        "本机密钥与加密时不同（常见于更换电脑、重装系统或账户文件随漫游配置文件同步到另一台电脑），请在账户菜单中重新授权（IMAP 账户请删除后重新添加）",
+        "This machine's key differs from the one used to encrypt (common after moving to a new PC, reinstalling Windows or roaming the accounts file to another computer); re-authorize from the account menu (remove and re-add IMAP accounts)",
+    ),
+    ("doctor.self_test", "凭据自检", "Credential self-test"),
+    (
+        "doctor.self_test_ok",
+        "本机密钥可以正常加密与解密",
+        "This machine's key encrypts and decrypts correctly",
+    ),
+    (
+        "doctor.self_test_hint",
+        "在主界面选择「重新授权全部账户」（IMAP 账户请删除后重新添加），或参阅 README 中的「凭据自检」一节",
+        "Choose \"Re-authorize all accounts\" in the main window (remove and re-add IMAP accounts), or see \"Credential self-test\" in the README",
+    ),
+    ("doctor.api_usage", "API 请求 {}", "API requests {}"),
+    (
+        "doctor.api_usage_hint",
+        "每分钟请求数超过阈值 {}，该账户的同步会暂缓；请调大 sync_interval 或 api_rate_warning",
+        "More than {} requests per minute; syncing this account is paused until the rate drops. Increase sync_interval or api_rate_warning",
+    ),
+    ("doctor.reach", "连接 {}", "Reach {}"),
+    (
+        "doctor.reach_ok",
+        "可以访问（HTTP {}）",
+        "Reachable (HTTP {})",
+    ),
+    ("doctor.reach_bad", "无法访问：{}", "Unreachable: {}"),
+    (
+        "doctor.reach_hint",
+        "检查网络和防火墙，或在 config.toml 的 [network] 段配置代理",
+        "Check the network and firewall, or set a proxy in the [network] section of config.toml",
+    ),
+    ("doctor.toast", "系统通知", "System notifications"),
+    ("doctor.toast_ok", "已启用", "Enabled"),
+    (
+        "doctor.toast_app_off",
+        "Windows PowerShell 的通知已关闭（NanoMail 借用它发送通知）",
+        "Notifications for Windows PowerShell are off (NanoMail sends notifications through it)",
+    ),
+    (
+        "doctor.toast_all_off",
+        "系统通知已全部关闭",
+        "All system notifications are off",
+    ),
+    (
+        "doctor.toast_hint",
+        "在 Windows 设置 → 系统 → 通知 中打开",
+        "Turn them on in Windows Settings → System → Notifications",
+    ),
+    ("doctor.toast_service_ok", "已启用（{}）", "Enabled ({})"),
+    (
+        "doctor.toast_service_missing",
+        "桌面通知服务不可用：{}",
+        "Desktop notification service unavailable: {}",
+    ),
+    (
+        "doctor.toast_service_hint",
+        "确认桌面环境的通知服务（如 GNOME Shell、Plasma 或 dunst）正在运行",
+        "Make sure the desktop's notification service (e.g. GNOME Shell, Plasma or dunst) is running",
+    ),
+    (
+        "doctor.runtime_hint",
+        "重新启动 NanoMail 后再试",
+        "Restart NanoMail and try again",
+    ),
+    (
+        "doctor.summary",
+        "{} 项通过，{} 项警告，{} 项失败",
+        "{} passed, {} warnings, {} failed",
+    ),
+    ("doctor.engine", "同步引擎", "Sync engine"),
+    ("doctor.engine_running", "运行中", "Running"),
+    (
+        "doctor.engine_paused",
+        "已暂停（睡眠恢复后等待网络重连）",
+        "Paused (waiting for the network after resume)",
+    ),
+    ("doctor.engine_stopped", "未运行", "Not running"),
+    (
+        "doctor.engine_next_round",
+        "下一轮同步：{}（{} 秒后）",
+        "Next round: {} (in {}s)",
+    ),
+    (
+        "doctor.engine_next_round_due",
+        "下一轮同步：即将开始",
+        "Next round: due now",
+    ),
+    (
+        "doctor.engine_no_accounts",
+        "本次运行中尚未同步任何账户",
+        "No account synced yet in this session",
+    ),
+    ("doctor.engine_syncing", "同步中", "Syncing"),
+    (
+        "doctor.engine_synced",
+        "上次同步成功",
+        "Last sync succeeded",
+    ),
+    (
+        "doctor.engine_failed",
+        "上次同步失败，下一轮重试",
+        "Last sync failed, retrying next round",
+    ),
+    (
+        "doctor.engine_backoff",
+        "邮件服务已停用，{} 后重试",
+        "Mail service disabled, retrying at {}",
+    ),
+    (
+        "doctor.last_round",
+        "最近一轮同步（第 {} 轮）耗时 {} ms：{}",
+        "Last sync round (#{}) took {} ms: {}",
+    ),
+    (
+        "doctor.last_round_none",
+        "本次运行中尚未完成一轮同步",
+        "No sync round has finished yet in this session",
+    ),
+    (
+        "doctor.history",
+        "最近 {} 轮同步（共记录 {} 轮）：",
+        "Last {} sync rounds ({} recorded):",
+    ),
+    (
+        "doctor.history_round",
+        "{}（{}）{} ms，未读 {}：{}",
+        "{} ({}) {} ms, {} unread: {}",
+    ),
+    (
+        "counts.not_synced",
+        "本轮没有同步该账户",
+        "Account was not synced in this round",
+    ),
+    (
+        "config.invalid",
+        "配置文件格式错误，已保留之前的设置：{}",
+        "Invalid config file, keeping previous settings: {}",
+    ),
+    (
+        "browser.auth_fallback",
+        "无法打开浏览器。请在浏览器中手动打开以下授权链接，完成后回到 NanoMail：\n\n{}\n\n\
+         点击「是」复制链接。",
+        "Couldn't open a browser. Open this authorization link in any browser, \
+         then come back to NanoMail:\n\n{}\n\nClick \"Yes\" to copy the link.",
+    ),
+    (
+        "browser.link_copied",
+        "无法打开浏览器，链接已复制到剪贴板",
+        "Couldn't open a browser. The link was copied to the clipboard",
+    ),
+    (
+        "browser.link_failed",
+        "无法打开浏览器，请手动打开: {}",
+        "Couldn't open a browser. Open this link manually: {}",
+    ),
+    (
+        "about.text",
+        "{}\nGmail 通知客户端\n\n\
+         快捷键：\n\
+         Esc　隐藏窗口\n\
+         F5　同步全部账户\n\
+         ↑/↓　选择账户\n\
+         Enter　打开所选账户的收件箱\n\
+         Delete　删除所选账户\n\
+         Ctrl+,　打开设置\n\n\
+         项目主页：{}\n\n是否打开项目主页？",
+        "{}\nGmail notifier\n\n\
+         Shortcuts:\n\
+         Esc  Hide the window\n\
+         F5  Sync all accounts\n\
+         Up/Down  Select an account\n\
+         Enter  Open the selected account's inbox\n\
+         Delete  Remove the selected account\n\
+         Ctrl+,  Open settings\n\n\
+         Homepage: {}\n\nOpen the project homepage?",
+    ),
+    (
+        "cli.help",
+        concat!(
+            "用法: nanomail [选项]\n\n",
+            "选项:\n",
+            "  --hidden        启动时不显示主窗口（仅驻留托盘）\n",
+            "  --sync-now      通知正在运行的实例立即同步；无运行实例时正常启动\n",
+            "  --reset-config  备份并重置 config.toml 为默认配置\n",
+            "  --doctor        检查配置、账户凭据、网络连通性和系统通知设置\n",
+            "  --print-counts  以 JSON 输出各账户未读数（不显示界面）；已有实例运行时读取其缓存结果\n",
+            "  --version       显示版本号\n",
+            "  --help          显示此帮助",
+        ),
+        concat!(
+            "Usage: nanomail [options]\n\n",
+            "Options:\n",
+            "  --hidden        Start without showing the main window (tray only)\n",
+            "  --sync-now      Ask the running instance to sync now; start normally if none is running\n",
+            "  --reset-config  Back up config.toml and reset it to defaults\n",
+            "  --doctor        Check the config, account credentials, connectivity and notification settings\n",
+            "  --print-counts  Print each account's unread count as JSON (no window); reads the running instance's cached results if there is one\n",
+            "  --version       Show the version\n",
+            "  --help          Show this help",
+        ),
+    ),
+    (
+        "cli.unknown_argument",
+        "未知参数: {}\n\n{}",
+        "Unknown argument: {}\n\n{}",
+    ),
+    (
+        "reset.confirm",
+        "将备份并重置配置文件为默认值：\n{}\n\n[oauth] 段会保留，账户数据不受影响。是否继续？",
+        "The config file will be backed up and reset to defaults:\n{}\n\nThe [oauth] section is kept and account data is not affected. Continue?",
+    ),
+    (
+        "reset.done",
+        "配置已重置，原文件备份为：\n{}",
+        "Config reset. The previous file was backed up to:\n{}",
+    ),
+    (
+        "reset.created",
+        "配置文件不存在，已创建默认配置",
+        "No config file found, created a default one",
+    ),
+    (
+        "reset.failed",
+        "重置配置失败: {}",
+        "Failed to reset config: {}",
+    ),
+    (
+        "crash.with_report",
+        "NanoMail 遇到意外错误，即将退出。\n\n崩溃报告已保存到：\n{}\n\n反馈问题时请附上此文件。",
+        "NanoMail ran into an unexpected error and will exit.\n\nA crash report was saved to:\n{}\n\nPlease attach this file when reporting the issue.",
+    ),
+    (
+        "crash.plain",
+        "NanoMail 遇到意外错误，即将退出。",
+        "NanoMail ran into an unexpected error and will exit.",
+    ),
+    (
+        "startup.fatal",
+        "NanoMail 启动失败：{}时出错。\n\n{}\n\n详细信息见日志目录：\n{}",
+        "NanoMail failed to start while {}.\n\n{}\n\nSee the log folder for details:\n{}",
+    ),
+    (
+        "startup.no_logs",
+        "（日志目录不可用）",
+        "(log folder unavailable)",
+    ),
+    ("startup.phase_logger", "初始化日志", "initializing logging"),
+    (
+        "startup.phase_runtime",
+        "创建后台运行时",
+        "creating the background runtime",
+    ),
+    (
+        "startup.phase_window",
+        "创建主窗口",
+        "creating the main window",
+    ),
+    (
+        "startup.phase_tray",
+        "创建托盘图标",
+        "creating the tray icon",
+    ),
+    (
+        "startup.phase_commands",
+        "启动托盘命令处理线程",
+        "starting the tray command thread",
+    ),
+];
+
+/// 按当前语言取文本（未登记的键原样返回，便于发现遗漏）
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(key, language())
+}
+
+/// 按当前语言取文本并依次填充 `{}` 占位符
+pub fn tr_fmt(key: &'static str, args: &[&dyn std::fmt::Display]) -> String {
+    fill(tr(key), args)
+}
+
+fn lookup(key: &'static str, language: Language) -> &'static str {
+    MESSAGES
+        .iter()
+        .find(|(k, _, _)| *k == key)
+        .map(|(_, zh, en)| match language {
+            Language::ZhCn => *zh,
+            Language::En => *en,
+        })
+        .unwrap_or(key)
+}
+
+fn fill(template: &str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+
+    if let Some(first) = parts.next() {
+        result.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+/// 把当前语言的文本填充到 Slint 的 `Strings` 全局对象（须在 UI 线程调用）
+pub fn apply_to_ui(window: &crate::MainWindow) {
+    use slint::Global;
+
+    let strings = crate::Strings::get(window);
+    strings.set_new_version(tr("ui.new_version").into());
+    strings.set_metered_note(tr("ui.metered_note").into());
+    strings.set_tray_missing_note(tr("ui.tray_missing_note").into());
+    strings.set_config_issues_note(tr("ui.config_issues_note").into());
+    strings.set_updating(tr("account.updating").into());
+    strings.set_group_other(tr("account.group_other").into());
+    strings.set_imap_title(tr("imap.title").into());
+    strings.set_credentials_title(tr("credentials.title").into());
+    strings.set_credentials_body(tr("credentials.body").into());
+    strings.set_credentials_reauth_all(tr("credentials.reauth_all").into());
+    strings.set_credentials_help(tr("credentials.help").into());
+    strings.set_imap_email(tr("imap.email").into());
+    strings.set_imap_host(tr("imap.host").into());
+    strings.set_imap_port(tr("imap.port").into());
+    strings.set_imap_username(tr("imap.username").into());
+    strings.set_imap_password(tr("imap.password").into());
+    strings.set_imap_idle(tr("imap.idle").into());
+    strings.set_imap_test(tr("imap.test").into());
+    strings.set_imap_save(tr("imap.save").into());
+    strings.set_imap_cancel(tr("imap.cancel").into());
+    strings.set_count_all_unread(tr("menu.count_all_unread").into());
+    strings.set_count_since_added(tr("menu.count_since_added").into());
+    strings.set_reset_counter(tr("menu.reset_counter").into());
+    strings.set_snooze_30m(tr("menu.snooze_30m").into());
+    strings.set_snooze_1h(tr("menu.snooze_1h").into());
+    strings.set_snooze_tomorrow(tr("menu.snooze_tomorrow").into());
+    strings.set_unsnooze(tr("menu.unsnooze").into());
+    strings.set_accent_color(tr("menu.accent_color").into());
+    strings.set_watch_spam(tr("menu.watch_spam").into());
+    strings.set_people_only(tr("menu.people_only").into());
+    strings.set_watch_vacation(tr("menu.watch_vacation").into());
+    strings.set_watch_vacation_no_scope(tr("menu.watch_vacation_no_scope").into());
+    strings.set_push(tr("menu.push").into());
+    strings.set_push_no_scope(tr("menu.push_no_scope").into());
+    strings.set_open_inbox(tr("menu.open_inbox").into());
+    strings.set_sync_now(tr("menu.sync_now").into());
+    strings.set_reauthorize(tr("menu.reauthorize").into());
+    strings.set_rename(tr("menu.rename").into());
+    strings.set_group(tr("menu.group").into());
+    strings.set_remove_account(tr("menu.remove").into());
+    strings.set_token_details(tr("menu.token_details").into());
+    strings.set_details_title(tr("details.title").into());
+    strings.set_details_expires_at(tr("details.expires_at").into());
+    strings.set_details_remaining(tr("details.remaining").into());
+    strings.set_details_refresh_token(tr("details.refresh_token").into());
+    strings.set_details_present(tr("details.present").into());
+    strings.set_details_missing(tr("details.missing").into());
+    strings.set_details_refreshed_at(tr("details.refreshed_at").into());
+    strings.set_details_refresh_token_issued(tr("details.refresh_token_issued").into());
+    strings.set_details_scopes(tr("details.scopes").into());
+    strings.set_details_mail_access(tr("details.mail_access").into());
+    strings.set_details_api_usage(tr("details.api_usage").into());
+    strings.set_details_alert_threshold(tr("details.alert_threshold").into());
+    strings.set_details_alert_placeholder(tr("details.alert_placeholder").into());
+    strings.set_details_group(tr("details.group").into());
+    strings.set_details_group_placeholder(tr("details.group_placeholder").into());
+    strings.set_details_badge_label(tr("details.badge_label").into());
+    strings.set_details_notify_label(tr("details.notify_label").into());
+    strings.set_details_labels_refresh(tr("details.labels_refresh").into());
+    strings.set_details_unknown(tr("details.unknown").into());
+    strings.set_details_refresh(tr("details.refresh").into());
+    strings.set_details_close(tr("details.close").into());
+    strings.set_history_title(tr("history.title").into());
+    strings.set_history_synced(tr("history.synced").into());
+    strings.set_history_failed(tr("history.failed").into());
+    strings.set_history_offline(tr("history.offline").into());
+    strings.set_history_other(tr("history.other").into());
+    strings.set_history_time(tr("history.time").into());
+    strings.set_history_duration(tr("history.duration").into());
+    strings.set_history_unread(tr("history.unread").into());
+    strings.set_history_accounts(tr("history.accounts").into());
+    strings.set_history_empty(tr("history.empty").into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_match_between_languages() {
+        for (key, zh, en) in MESSAGES {
+            assert_eq!(
+                zh.matches("{}").count(),
+                en.matches("{}").count(),
+                "占位符数量不一致: {}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_lookup_and_fill() {
+        assert_eq!(lookup("tray.quit", Language::En), "Quit");
+        assert_eq!(lookup("tray.quit", Language::ZhCn), "退出");
+        assert_eq!(lookup("missing.key", Language::En), "missing.key");
+        assert_eq!(
+            fill(
+                lookup("notification.body_many", Language::En),
+                &[&"a@b.c", &3]
+            ),
+            "a@b.c received 3 new emails"
+        );
+    }
+
+    #[test]
+    fn test_explicit_settings() {
+        assert_eq!(Language::from_setting("en"), Language::En);
+        assert_eq!(Language::from_setting("zh-CN"), Language::ZhCn);
+    }
+}
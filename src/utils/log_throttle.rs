@@ -0,0 +1,246 @@
+/// 重复错误日志限流
+///
+/// 断网时每轮同步都会为每个账户记录同样的多行错误，网络探测也会重复失败，
+/// 整晚下来日志文件里全是相同的内容。按（账户, 错误类型）计数：首次出现时完整记录，
+/// 之后最多每 5 分钟输出一行"重复了 N 次"的摘要，恢复后清零
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::utils::redact::redact_email;
+
+/// 重复错误摘要的最短间隔
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// 一次错误应如何记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// 首次出现，完整记录
+    First,
+
+    /// 距上次输出已超过间隔，输出摘要（期间重复的次数）
+    Summary(u32),
+
+    /// 不输出
+    Suppress,
+}
+
+/// 单个键的计数
+#[derive(Debug)]
+struct Entry {
+    /// 上次输出后又出现的次数
+    repeats: u32,
+
+    /// 上次输出的时间
+    last_report: Instant,
+}
+
+/// 按（来源, 错误类型）计数的限流器（时间由调用方传入，便于测试）
+#[derive(Debug)]
+pub struct LogThrottle {
+    /// 摘要的最短间隔
+    interval: Duration,
+
+    /// 各键的计数
+    entries: HashMap<(String, String), Entry>,
+}
+
+impl LogThrottle {
+    /// 创建限流器
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// 记录一次错误并决定是否输出
+    pub fn record(&mut self, source: &str, kind: &str, now: Instant) -> Decision {
+        let key = (source.to_string(), kind.to_string());
+        let Some(entry) = self.entries.get_mut(&key) else {
+            self.entries.insert(
+                key,
+                Entry {
+                    repeats: 0,
+                    last_report: now,
+                },
+            );
+            return Decision::First;
+        };
+
+        entry.repeats += 1;
+        if now.saturating_duration_since(entry.last_report) < self.interval {
+            return Decision::Suppress;
+        }
+
+        let repeats = entry.repeats;
+        entry.repeats = 0;
+        entry.last_report = now;
+        Decision::Summary(repeats)
+    }
+
+    /// 来源已恢复：清除它的所有计数，返回尚未输出过的重复次数
+    pub fn reset(&mut self, source: &str) -> u32 {
+        let mut pending = 0;
+        self.entries.retain(|(entry_source, _), entry| {
+            if entry_source == source {
+                pending += entry.repeats;
+                false
+            } else {
+                true
+            }
+        });
+        pending
+    }
+}
+
+/// 进程内共享的限流器
+static THROTTLE: LazyLock<Mutex<LogThrottle>> =
+    LazyLock::new(|| Mutex::new(LogThrottle::new(SUMMARY_INTERVAL)));
+
+/// 记录错误：首次以 error 级别完整输出，之后只定期输出重复次数
+///
+/// `source` 为邮箱地址或其他来源标识（输出时对邮箱地址脱敏）
+pub fn error(source: &str, kind: &str, message: impl Display) {
+    match decide(source, kind) {
+        Decision::First => tracing::error!("❌ {}: {}", display_source(source), message),
+        Decision::Summary(repeats) => tracing::warn!(
+            "{} 的上一条错误（{}）又重复了 {} 次",
+            display_source(source),
+            kind,
+            repeats
+        ),
+        Decision::Suppress => {}
+    }
+}
+
+/// 与 [`error`] 相同的限流，但只以 debug 级别输出（用于不影响同步的后台操作，如新邮件 Webhook）
+pub fn debug(source: &str, kind: &str, message: impl Display) {
+    match decide(source, kind) {
+        Decision::First => tracing::debug!("{}: {}", display_source(source), message),
+        Decision::Summary(repeats) => tracing::debug!(
+            "{} 的上一条错误（{}）又重复了 {} 次",
+            display_source(source),
+            kind,
+            repeats
+        ),
+        Decision::Suppress => {}
+    }
+}
+
+/// 用进程内共享的限流器记录一次错误
+fn decide(source: &str, kind: &str) -> Decision {
+    THROTTLE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .record(source, kind, Instant::now())
+}
+
+/// 来源已恢复：清除计数，之后再出错时重新完整记录
+pub fn recovered(source: &str) {
+    let pending = THROTTLE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .reset(source);
+    if pending > 0 {
+        tracing::info!(
+            "{} 已恢复（此前错误又重复了 {} 次）",
+            display_source(source),
+            pending
+        );
+    }
+}
+
+/// 日志中显示的来源（邮箱地址脱敏）
+fn display_source(source: &str) -> String {
+    if source.contains('@') {
+        redact_email(source)
+    } else {
+        source.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeats_are_summarized() {
+        let start = Instant::now();
+        let mut throttle = LogThrottle::new(Duration::from_secs(300));
+
+        assert_eq!(
+            throttle.record("a@example.com", "offline", start),
+            Decision::First
+        );
+        for minute in 1..5 {
+            let now = start + Duration::from_secs(60 * minute);
+            assert_eq!(
+                throttle.record("a@example.com", "offline", now),
+                Decision::Suppress
+            );
+        }
+        // 5 分钟后输出期间的重复次数（含本次）
+        let now = start + Duration::from_secs(300);
+        assert_eq!(
+            throttle.record("a@example.com", "offline", now),
+            Decision::Summary(5)
+        );
+        // 摘要后重新计数
+        let now = start + Duration::from_secs(360);
+        assert_eq!(
+            throttle.record("a@example.com", "offline", now),
+            Decision::Suppress
+        );
+        let now = start + Duration::from_secs(600);
+        assert_eq!(
+            throttle.record("a@example.com", "offline", now),
+            Decision::Summary(2)
+        );
+    }
+
+    #[test]
+    fn test_keys_are_independent() {
+        let now = Instant::now();
+        let mut throttle = LogThrottle::new(Duration::from_secs(300));
+
+        assert_eq!(
+            throttle.record("a@example.com", "offline", now),
+            Decision::First
+        );
+        assert_eq!(
+            throttle.record("b@example.com", "offline", now),
+            Decision::First
+        );
+        // 同一账户换了一种错误也完整记录
+        assert_eq!(
+            throttle.record("a@example.com", "auth", now),
+            Decision::First
+        );
+    }
+
+    #[test]
+    fn test_reset_on_recovery() {
+        let start = Instant::now();
+        let mut throttle = LogThrottle::new(Duration::from_secs(300));
+
+        throttle.record("a@example.com", "offline", start);
+        throttle.record("a@example.com", "offline", start + Duration::from_secs(60));
+        throttle.record("a@example.com", "timeout", start + Duration::from_secs(120));
+        throttle.record("b@example.com", "offline", start);
+
+        assert_eq!(throttle.reset("a@example.com"), 1);
+        assert_eq!(throttle.reset("a@example.com"), 0);
+        // 恢复后再出错时重新完整记录，其他来源不受影响
+        let now = start + Duration::from_secs(180);
+        assert_eq!(
+            throttle.record("a@example.com", "offline", now),
+            Decision::First
+        );
+        assert_eq!(
+            throttle.record("b@example.com", "offline", now),
+            Decision::Suppress
+        );
+    }
+}
@@ -0,0 +1,325 @@
+/// 文件日志模块
+///
+/// release 构建使用 `windows_subsystem = "windows"`，没有控制台，
+/// 日志写入 `%APPDATA%\NanoMail\logs\nanomail.log`，按天或按大小轮转
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, TryLockError};
+
+/// 当前日志文件名
+const LOG_FILE_NAME: &str = "nanomail.log";
+
+/// 单个日志文件大小上限（5 MB）
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 保留的历史日志文件数量
+const MAX_ARCHIVED_FILES: usize = 5;
+
+/// 获取日志目录
+///
+/// 返回：`%APPDATA%\NanoMail\logs`
+pub fn logs_dir() -> Result<PathBuf> {
+    let dir = dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?
+        .join("NanoMail")
+        .join("logs");
+
+    std::fs::create_dir_all(&dir).context("创建日志目录失败")?;
+    Ok(dir)
+}
+
+/// 后台写入线程与 panic hook 共用的日志文件写入器（`SharedWriter::install` 设置）
+static SHARED_WRITER: OnceLock<Arc<Mutex<RollingFileWriter>>> = OnceLock::new();
+
+/// 绕过后台写入线程，经由同一个轮转写入器直接追加一行（ERROR 级别）
+///
+/// 供 panic hook 使用：主线程 panic 后进程随即退出，经由非阻塞写入器的日志可能来不及落盘。
+/// 写入计入轮转大小，文件日志未初始化或写入器正被占用时返回错误
+pub fn append_now(line: &str) -> Result<()> {
+    let writer = SHARED_WRITER.get().context("文件日志未初始化")?;
+    append_line(writer, line)
+}
+
+fn append_line(writer: &Mutex<RollingFileWriter>, line: &str) -> Result<()> {
+    // panic 可能发生在持有写入器的线程中：不等待，避免 panic hook 死锁
+    let mut writer = match writer.try_lock() {
+        Ok(writer) => writer,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => anyhow::bail!("日志写入器正被占用"),
+    };
+    writeln!(
+        writer,
+        "{} ERROR {}",
+        Local::now().format("%Y-%m-%dT%H:%M:%S%.6f%:z"),
+        line
+    )?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// 可在线程间共享的轮转写入器（交给非阻塞写入器的后台线程）
+pub struct SharedWriter(Arc<Mutex<RollingFileWriter>>);
+
+impl SharedWriter {
+    /// 共享写入器，并登记为 [`append_now`] 使用的写入器
+    pub fn install(writer: RollingFileWriter) -> Self {
+        let shared = Arc::new(Mutex::new(writer));
+        if SHARED_WRITER.set(shared.clone()).is_err() {
+            tracing::warn!("日志写入器已登记，忽略重复登记");
+        }
+        Self(shared)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RollingFileWriter> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush()
+    }
+}
+
+/// 按大小和日期轮转的日志写入器
+///
+/// 当前文件为 `nanomail.log`，轮转后依次重命名为 `nanomail.1.log` ... `nanomail.N.log`，
+/// 编号越大越旧，超出保留数量的文件会被删除
+pub struct RollingFileWriter {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+    date: NaiveDate,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RollingFileWriter {
+    /// 在指定目录创建写入器（默认 5 MB / 5 个历史文件）
+    pub fn new(dir: &Path) -> io::Result<Self> {
+        Self::with_limits(dir, MAX_FILE_BYTES, MAX_ARCHIVED_FILES)
+    }
+
+    /// 使用自定义轮转上限创建写入器
+    pub fn with_limits(dir: &Path, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let path = dir.join(LOG_FILE_NAME);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            size,
+            date: Local::now().date_naive(),
+            max_bytes,
+            max_files,
+        })
+    }
+
+    /// 历史文件路径：`nanomail.{index}.log`
+    fn archive_path(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("nanomail.{}.log", index))
+    }
+
+    /// 执行轮转：依次后移历史文件，并重新创建当前文件
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        // 删除最旧的文件，再把 N-1..1 依次后移
+        let oldest = self.archive_path(self.max_files);
+        if oldest.exists() {
+            std::fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.max_files).rev() {
+            let from = self.archive_path(index);
+            if from.exists() {
+                std::fs::rename(&from, self.archive_path(index + 1))?;
+            }
+        }
+
+        let current = self.dir.join(LOG_FILE_NAME);
+        if self.max_files > 0 {
+            std::fs::rename(&current, self.archive_path(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&current)?;
+        self.size = 0;
+        self.date = Local::now().date_naive();
+
+        Ok(())
+    }
+}
+
+impl Write for RollingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let day_changed = Local::now().date_naive() != self.date;
+        let too_large = self.size > 0 && self.size + buf.len() as u64 > self.max_bytes;
+
+        if day_changed || too_large {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// 日志级别重载函数（由 init_logger 注册，内部持有各层的 reload 句柄）
+pub type LevelReloader = Box<dyn Fn(&str) -> Result<()> + Send + Sync>;
+
+static LEVEL_RELOADER: OnceLock<LevelReloader> = OnceLock::new();
+
+/// 注册日志级别重载函数（仅首次调用生效）
+pub fn set_level_reloader(reloader: LevelReloader) {
+    if LEVEL_RELOADER.set(reloader).is_err() {
+        tracing::warn!("日志级别重载函数已注册，忽略重复注册");
+    }
+}
+
+/// 运行时修改日志级别（设置了 RUST_LOG 时以环境变量为准，不做修改）
+pub fn reload_level(level: &str) -> Result<()> {
+    if std::env::var_os("RUST_LOG").is_some() {
+        tracing::info!("已设置 RUST_LOG，忽略配置文件中的日志级别变更");
+        return Ok(());
+    }
+
+    let reloader = LEVEL_RELOADER.get().context("日志系统未注册级别重载")?;
+    reloader(level)?;
+    tracing::info!("日志级别已切换为: {}", level);
+    Ok(())
+}
+
+/// 在资源管理器中打开日志目录
+pub fn open_logs_dir() {
+    match logs_dir() {
+        Ok(dir) => {
+            if let Err(e) = super::shell::open(&dir) {
+                tracing::error!("打开日志目录失败: {:#}", e);
+            }
+        }
+        Err(e) => tracing::error!("获取日志目录失败: {}", e),
+    }
+}
+
+/// "查看日志"打开的位置：只有一个日志文件时打开该文件，已轮转出多个文件时打开日志目录
+pub fn log_view_target(dir: &Path) -> PathBuf {
+    let logs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| {
+                    path.is_file()
+                        && path.extension().is_some_and(|ext| ext == "log")
+                        && path
+                            .file_name()
+                            .is_some_and(|name| name.to_string_lossy().starts_with("nanomail"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    match logs.as_slice() {
+        [only] => only.clone(),
+        _ => dir.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nanomail-log-test-{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let dir = temp_log_dir("size");
+        let mut writer = RollingFileWriter::with_limits(&dir, 16, 2).unwrap();
+
+        writer.write_all(b"0123456789").unwrap();
+        writer.write_all(b"abcdefghij").unwrap(); // 超过 16 字节，触发轮转
+        writer.flush().unwrap();
+
+        assert_eq!(
+            std::fs::read(dir.join("nanomail.1.log")).unwrap(),
+            b"0123456789"
+        );
+        assert_eq!(
+            std::fs::read(dir.join(LOG_FILE_NAME)).unwrap(),
+            b"abcdefghij"
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_append_line_counts_toward_rotation() {
+        let dir = temp_log_dir("append");
+        let writer = Mutex::new(RollingFileWriter::with_limits(&dir, 64, 2).unwrap());
+
+        append_line(&writer, "panic").unwrap();
+        let appended = std::fs::read(dir.join(LOG_FILE_NAME)).unwrap();
+        assert!(appended.ends_with(b"ERROR panic\n"));
+
+        // 追加的行计入大小：再写入超出上限时轮转，追加的行随之归档
+        writer.lock().unwrap().write_all(&[b'x'; 40]).unwrap();
+        writer.lock().unwrap().flush().unwrap();
+        assert_eq!(std::fs::read(dir.join("nanomail.1.log")).unwrap(), appended);
+        assert_eq!(std::fs::read(dir.join(LOG_FILE_NAME)).unwrap(), [b'x'; 40]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_log_view_target() {
+        let dir = temp_log_dir("view");
+        assert_eq!(log_view_target(&dir), dir);
+
+        std::fs::write(dir.join(LOG_FILE_NAME), b"a").unwrap();
+        assert_eq!(log_view_target(&dir), dir.join(LOG_FILE_NAME));
+
+        // 轮转后有多个文件：打开目录
+        std::fs::write(dir.join("nanomail.1.log"), b"b").unwrap();
+        assert_eq!(log_view_target(&dir), dir);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rotation_keeps_limited_files() {
+        let dir = temp_log_dir("limit");
+        let mut writer = RollingFileWriter::with_limits(&dir, 4, 2).unwrap();
+
+        for chunk in [b"aaaa", b"bbbb", b"cccc", b"dddd"] {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(std::fs::read(dir.join(LOG_FILE_NAME)).unwrap(), b"dddd");
+        assert_eq!(std::fs::read(dir.join("nanomail.1.log")).unwrap(), b"cccc");
+        assert_eq!(std::fs::read(dir.join("nanomail.2.log")).unwrap(), b"bbbb");
+        assert!(!dir.join("nanomail.3.log").exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,20 +1,31 @@
 /// 机器指纹与加密密钥派生模块
 ///
-/// 从 Windows 注册表读取机器 GUID，使用 Argon2 派生加密密钥
+/// 从 Windows 注册表读取机器 GUID（Linux 上读取 /etc/machine-id），使用 Argon2 派生加密密钥
 
-use anyhow::{Context, Result};
+#[cfg(windows)]
+use anyhow::Context;
+use anyhow::Result;
 use argon2::{
     password_hash::{PasswordHasher, SaltString},
     Argon2,
 };
-use winreg::enums::*;
+use std::sync::OnceLock;
+#[cfg(windows)]
 use winreg::RegKey;
+#[cfg(windows)]
+use winreg::enums::*;
 
 /// 固定盐值（编译时确定，用于密钥派生的一致性）
 ///
 /// 注意：这个盐值对所有用户相同，真正的唯一性来自机器 GUID
 const FIXED_SALT: &[u8] = b"NanoMail.v1.2025";
 
+/// 已派生的密钥
+///
+/// 每次派生都要计算一遍 Argon2（几十毫秒 CPU 与约 19 MiB 内存），而结果在同一台机器上固定不变，
+/// 因此只在首次加解密时派生一次；派生失败时不缓存，下次重试
+static DERIVED_KEY: OnceLock<[u8; 32]> = OnceLock::new();
+
 /// 从 Windows 注册表获取机器 GUID
 ///
 /// 读取路径：`HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Cryptography\MachineGuid`
@@ -22,6 +33,7 @@ const FIXED_SALT: &[u8] = b"NanoMail.v1.2025";
 /// # Errors
 /// - 无法打开注册表键（权限不足）
 /// - MachineGuid 值不存在
+#[cfg(windows)]
 fn get_machine_guid() -> Result<String> {
     tracing::debug!("正在从注册表读取机器 GUID");
 
@@ -39,6 +51,45 @@ fn get_machine_guid() -> Result<String> {
     Ok(guid)
 }
 
+/// Linux 上由 systemd（或 D-Bus）生成的机器 ID 文件，按顺序尝试
+#[cfg(not(windows))]
+const MACHINE_ID_PATHS: [&str; 2] = ["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+/// 读取机器 ID（Linux）
+///
+/// 内容为 32 位十六进制字符串，系统安装时生成、之后保持不变
+///
+/// # Errors
+/// - 两个文件都不存在或内容为空
+#[cfg(not(windows))]
+fn get_machine_guid() -> Result<String> {
+    for path in MACHINE_ID_PATHS {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                if let Some(id) = parse_machine_id(&content) {
+                    tracing::debug!("机器 ID 读取成功: {}", path);
+                    return Ok(id);
+                }
+                tracing::warn!("机器 ID 文件为空: {}", path);
+            }
+            Err(e) => tracing::debug!("无法读取 {}: {}", path, e),
+        }
+    }
+
+    anyhow::bail!("无法读取机器 ID（{}）", MACHINE_ID_PATHS.join("、"))
+}
+
+/// 取机器 ID 文件的第一行（空文件返回 `None`）
+#[cfg(not(windows))]
+fn parse_machine_id(content: &str) -> Option<String> {
+    content
+        .lines()
+        .next()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
 /// 从机器 GUID 派生 256-bit 加密密钥
 ///
 /// 使用 Argon2id 算法从机器 GUID 派生密钥，确保：
@@ -59,6 +110,15 @@ fn get_machine_guid() -> Result<String> {
 /// assert_eq!(key.len(), 32);
 /// ```
 pub fn derive_encryption_key() -> Result<[u8; 32]> {
+    if let Some(key) = DERIVED_KEY.get() {
+        return Ok(*key);
+    }
+    let key = derive_from_machine_guid()?;
+    Ok(*DERIVED_KEY.get_or_init(|| key))
+}
+
+/// 实际的密钥派生（Argon2id）
+fn derive_from_machine_guid() -> Result<[u8; 32]> {
     // 1. 获取机器 GUID
     let guid = get_machine_guid()?;
 
@@ -99,7 +159,7 @@ mod tests {
     use super::*;
 
     #[test]
-    #[ignore] // 需要在 Windows 环境运行
+    #[ignore] // 需要在 Windows 环境运行（或存在 /etc/machine-id 的 Linux）
     fn test_get_machine_guid() {
         let guid = get_machine_guid().unwrap();
         assert!(!guid.is_empty());
@@ -108,7 +168,7 @@ mod tests {
     }
 
     #[test]
-    #[ignore] // 需要在 Windows 环境运行
+    #[ignore] // 需要在 Windows 环境运行（或存在 /etc/machine-id 的 Linux）
     fn test_derive_encryption_key() {
         let key1 = derive_encryption_key().unwrap();
         let key2 = derive_encryption_key().unwrap();
@@ -128,4 +188,15 @@ mod tests {
         assert_eq!(FIXED_SALT, b"NanoMail.v1.2025");
         assert_eq!(FIXED_SALT.len(), 16);
     }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_parse_machine_id() {
+        assert_eq!(
+            parse_machine_id("0123456789abcdef0123456789abcdef\n").as_deref(),
+            Some("0123456789abcdef0123456789abcdef")
+        );
+        assert_eq!(parse_machine_id("\n"), None);
+        assert_eq!(parse_machine_id(""), None);
+    }
 }
@@ -1,4 +1,23 @@
 /// 工具模块
+pub mod accessibility;
+pub mod app_info;
 pub mod avatar;
+pub mod browser;
+pub mod clipboard;
+pub mod connectivity;
+pub mod crash;
+pub mod dialog;
 pub mod http_client;
+pub mod i18n;
+pub mod log_throttle;
+pub mod logging;
 pub mod machine_id;
+pub mod netstat;
+pub mod power;
+pub mod proxy;
+pub mod redact;
+pub mod shell;
+pub mod single_instance;
+pub mod tasks;
+pub mod telemetry;
+pub mod updates;
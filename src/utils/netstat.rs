@@ -0,0 +1,382 @@
+/// 本机 TCP 监听端口查询
+///
+/// OAuth 回调端口全部被占用时，用来告诉用户是哪些程序占用了端口。
+/// Windows 上通过 `GetExtendedTcpTable` 读取 IPv4 监听表，Linux 上读取 `/proc/net/tcp`
+/// 并按 socket inode 找到所属进程。查询失败时返回空列表，只影响提示内容
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(windows)]
+use windows_sys::Win32::Foundation::{CloseHandle, ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+#[cfg(windows)]
+use windows_sys::Win32::NetworkManagement::IpHelper::{
+    GetExtendedTcpTable, TCP_TABLE_OWNER_PID_LISTENER,
+};
+#[cfg(windows)]
+use windows_sys::Win32::Networking::WinSock::AF_INET;
+#[cfg(windows)]
+use windows_sys::Win32::System::Threading::{
+    OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameW,
+};
+
+/// 占用端口的进程
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortOwner {
+    /// 端口
+    pub port: u16,
+
+    /// 进程 ID
+    pub pid: u32,
+
+    /// 进程名（没有权限查询或进程已退出时为 `None`）
+    pub process: Option<String>,
+}
+
+impl fmt::Display for PortOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.process {
+            Some(name) => write!(f, "{}: {} (PID {})", self.port, name, self.pid),
+            None => write!(f, "{}: PID {}", self.port, self.pid),
+        }
+    }
+}
+
+/// TCP 表中的一个监听项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Listener {
+    port: u16,
+    pid: u32,
+}
+
+/// 查询监听指定端口的进程（按端口排序，同一端口可能有多个进程）
+pub fn port_owners(ports: &[u16]) -> Vec<PortOwner> {
+    owners_of(&listeners(), ports, process_name)
+}
+
+/// 从监听表中挑出指定端口的项并补上进程名（同一进程只查询一次）
+fn owners_of(
+    listeners: &[Listener],
+    ports: &[u16],
+    name_of: impl Fn(u32) -> Option<String>,
+) -> Vec<PortOwner> {
+    let mut names: HashMap<u32, Option<String>> = HashMap::new();
+    let mut owners: Vec<PortOwner> = Vec::new();
+    for listener in listeners.iter().filter(|l| ports.contains(&l.port)) {
+        if owners
+            .iter()
+            .any(|o| o.port == listener.port && o.pid == listener.pid)
+        {
+            continue;
+        }
+        let process = names
+            .entry(listener.pid)
+            .or_insert_with(|| name_of(listener.pid))
+            .clone();
+        owners.push(PortOwner {
+            port: listener.port,
+            pid: listener.pid,
+            process,
+        });
+    }
+    owners.sort_by_key(|o| (o.port, o.pid));
+    owners
+}
+
+/// `MIB_TCP_STATE_LISTEN`
+#[cfg_attr(not(windows), allow(dead_code))]
+const MIB_TCP_STATE_LISTEN: u32 = 2;
+
+/// `MIB_TCPROW_OWNER_PID` 的大小（6 个 DWORD）
+#[cfg_attr(not(windows), allow(dead_code))]
+const TCP_ROW_LEN: usize = 24;
+
+/// 解析 `GetExtendedTcpTable` 返回的 `MIB_TCPTABLE_OWNER_PID`
+///
+/// 布局：`dwNumEntries` 之后是连续的 `MIB_TCPROW_OWNER_PID`
+/// （state, localAddr, localPort, remoteAddr, remotePort, owningPid）。
+/// 端口以网络字节序存放在 `dwLocalPort` 的前两个字节；缓冲区不完整时只返回完整的行
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_tcp_table(buf: &[u8]) -> Vec<Listener> {
+    let dword = |bytes: &[u8]| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    if buf.len() < 4 {
+        return Vec::new();
+    }
+    let count = dword(&buf[..4]) as usize;
+
+    buf[4..]
+        .chunks_exact(TCP_ROW_LEN)
+        .take(count)
+        .filter(|row| dword(&row[0..4]) == MIB_TCP_STATE_LISTEN)
+        .map(|row| Listener {
+            port: u16::from_be_bytes([row[8], row[9]]),
+            pid: dword(&row[20..24]),
+        })
+        .collect()
+}
+
+/// 读取 IPv4 监听表（回调服务器只监听 127.0.0.1，IPv6 监听不冲突）
+#[cfg(windows)]
+fn listeners() -> Vec<Listener> {
+    let mut size: u32 = 0;
+    let mut buf: Vec<u8> = Vec::new();
+    // 两次调用之间表可能变大，缓冲区不足时按新的大小重试
+    for _ in 0..3 {
+        // SAFETY: 缓冲区长度与 size 一致；size 为 0 时只查询所需大小
+        let result = unsafe {
+            GetExtendedTcpTable(
+                if buf.is_empty() {
+                    std::ptr::null_mut()
+                } else {
+                    buf.as_mut_ptr().cast()
+                },
+                &mut size,
+                0,
+                AF_INET as u32,
+                TCP_TABLE_OWNER_PID_LISTENER,
+                0,
+            )
+        };
+        match result {
+            NO_ERROR => return parse_tcp_table(&buf[..(size as usize).min(buf.len())]),
+            ERROR_INSUFFICIENT_BUFFER => buf = vec![0; size as usize],
+            code => {
+                tracing::warn!("读取 TCP 监听表失败: 错误码 {}", code);
+                return Vec::new();
+            }
+        }
+    }
+    tracing::warn!("读取 TCP 监听表失败: 表持续变化");
+    Vec::new()
+}
+
+/// 进程名（可执行文件名）
+#[cfg(windows)]
+fn process_name(pid: u32) -> Option<String> {
+    // SAFETY: 句柄在使用后关闭；缓冲区长度以字符数传入并由系统更新为实际长度
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+        let mut buf = [0u16; 260];
+        let mut len = buf.len() as u32;
+        let ok = QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, buf.as_mut_ptr(), &mut len);
+        CloseHandle(handle);
+        if ok == 0 {
+            return None;
+        }
+        let path = String::from_utf16_lossy(&buf[..len as usize]);
+        file_name(&path)
+    }
+}
+
+/// `/proc/net/tcp` 中的 LISTEN 状态
+#[cfg_attr(windows, allow(dead_code))]
+const PROC_TCP_LISTEN: &str = "0A";
+
+/// 解析 `/proc/net/tcp`，返回监听项的 `(端口, socket inode)`
+///
+/// 每行依次为 sl、local_address（`地址:端口`，十六进制）、rem_address、st、队列、计时器、
+/// retrnsmt、uid、timeout、inode；第一行是表头
+#[cfg_attr(windows, allow(dead_code))]
+fn parse_proc_net_tcp(content: &str) -> Vec<(u16, u64)> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.get(3) != Some(&PROC_TCP_LISTEN) {
+                return None;
+            }
+            let (_, port) = fields.get(1)?.rsplit_once(':')?;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some((port, inode))
+        })
+        .collect()
+}
+
+/// 从 `/proc/{pid}/fd/*` 的链接目标中取出 socket inode（形如 `socket:[12345]`）
+#[cfg_attr(windows, allow(dead_code))]
+fn socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// 读取 IPv4 监听表，按 socket inode 在各进程的文件描述符中找到所属进程
+///
+/// 其他用户的进程没有权限读取，找不到所属进程的监听项不返回
+#[cfg(not(windows))]
+fn listeners() -> Vec<Listener> {
+    let content = match std::fs::read_to_string("/proc/net/tcp") {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::warn!("读取 TCP 监听表失败: {}", e);
+            return Vec::new();
+        }
+    };
+    let ports: HashMap<u64, u16> = parse_proc_net_tcp(&content)
+        .into_iter()
+        .map(|(port, inode)| (inode, port))
+        .collect();
+    if ports.is_empty() {
+        return Vec::new();
+    }
+
+    let Ok(processes) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let mut listeners = Vec::new();
+    for entry in processes.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let inode = std::fs::read_link(fd.path())
+                .ok()
+                .and_then(|link| socket_inode(&link.to_string_lossy()));
+            if let Some(&port) = inode.and_then(|inode| ports.get(&inode)) {
+                listeners.push(Listener { port, pid });
+            }
+        }
+    }
+    listeners
+}
+
+/// 进程名
+#[cfg(not(windows))]
+fn process_name(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// 路径中的文件名部分
+#[cfg_attr(not(windows), allow(dead_code))]
+fn file_name(path: &str) -> Option<String> {
+    path.rsplit(['\\', '/'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按 `MIB_TCPTABLE_OWNER_PID` 的布局构造缓冲区：(state, port, pid)
+    fn tcp_table(rows: &[(u32, u16, u32)]) -> Vec<u8> {
+        let mut buf = (rows.len() as u32).to_ne_bytes().to_vec();
+        for &(state, port, pid) in rows {
+            let mut local_port = [0u8; 4];
+            local_port[..2].copy_from_slice(&port.to_be_bytes());
+            buf.extend_from_slice(&state.to_ne_bytes());
+            buf.extend_from_slice(&u32::from_ne_bytes([127, 0, 0, 1]).to_ne_bytes());
+            buf.extend_from_slice(&local_port);
+            buf.extend_from_slice(&0u32.to_ne_bytes());
+            buf.extend_from_slice(&0u32.to_ne_bytes());
+            buf.extend_from_slice(&pid.to_ne_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn test_parse_tcp_table() {
+        let buf = tcp_table(&[(2, 8080, 4321), (5, 8081, 99), (2, 8089, 7)]);
+        assert_eq!(
+            parse_tcp_table(&buf),
+            [
+                Listener {
+                    port: 8080,
+                    pid: 4321
+                },
+                Listener { port: 8089, pid: 7 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_truncated_tcp_table() {
+        let buf = tcp_table(&[(2, 8080, 1), (2, 8081, 2)]);
+        assert_eq!(parse_tcp_table(&buf[..buf.len() - 1]).len(), 1);
+        assert!(parse_tcp_table(&buf[..3]).is_empty());
+
+        // 行数以 dwNumEntries 为准，多余的字节忽略
+        let mut padded = tcp_table(&[(2, 8080, 1)]);
+        padded.extend_from_slice(&[2; TCP_ROW_LEN]);
+        assert_eq!(parse_tcp_table(&padded).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_proc_net_tcp() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 00000000:1F99 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 555 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:1F91 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 777 1 0000000000000000 20 4 30 10 -1
+";
+        assert_eq!(parse_proc_net_tcp(content), [(8080, 41234), (8089, 555)]);
+        assert!(parse_proc_net_tcp("").is_empty());
+    }
+
+    #[test]
+    fn test_socket_inode() {
+        assert_eq!(socket_inode("socket:[41234]"), Some(41234));
+        assert_eq!(socket_inode("pipe:[41234]"), None);
+        assert_eq!(socket_inode("/dev/null"), None);
+    }
+
+    #[test]
+    fn test_owners_filters_ports_and_caches_names() {
+        let listeners = [
+            Listener {
+                port: 8081,
+                pid: 20,
+            },
+            Listener { port: 443, pid: 30 },
+            Listener {
+                port: 8080,
+                pid: 10,
+            },
+            // 同时监听 IPv4 与 IPv6 等重复项只列一次
+            Listener {
+                port: 8080,
+                pid: 10,
+            },
+            Listener {
+                port: 8082,
+                pid: 10,
+            },
+        ];
+        let lookups = std::cell::Cell::new(0);
+        let owners = owners_of(&listeners, &[8080, 8081, 8082], |pid| {
+            lookups.set(lookups.get() + 1);
+            (pid == 10).then(|| "node.exe".to_string())
+        });
+
+        assert_eq!(
+            owners.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "8080: node.exe (PID 10)",
+                "8081: PID 20",
+                "8082: node.exe (PID 10)"
+            ]
+        );
+        assert_eq!(lookups.get(), 2);
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name(r"C:\Program Files\nodejs\node.exe").as_deref(),
+            Some("node.exe")
+        );
+        assert_eq!(file_name("/usr/bin/python3").as_deref(), Some("python3"));
+        assert_eq!(file_name(""), None);
+    }
+}
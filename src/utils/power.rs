@@ -0,0 +1,120 @@
+/// 电源事件模块
+///
+/// 监听系统从睡眠/休眠恢复的事件。优先使用 `PowerRegisterSuspendResumeNotification`
+/// 回调（无需窗口）；注册失败时退回到墙钟漂移检测：后台线程定期休眠，
+/// 若醒来时墙钟流逝远超预期，则认为系统刚从睡眠中恢复。Linux 上只使用墙钟漂移检测
+#[cfg(windows)]
+use std::ffi::c_void;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+#[cfg(windows)]
+use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+#[cfg(windows)]
+use windows_sys::Win32::System::Power::{
+    DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS, PowerRegisterSuspendResumeNotification,
+};
+#[cfg(windows)]
+use windows_sys::Win32::UI::WindowsAndMessaging::{DEVICE_NOTIFY_CALLBACK, PBT_APMRESUMEAUTOMATIC};
+
+/// 漂移检测的轮询间隔
+const DRIFT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 墙钟流逝超出轮询间隔多少时判定为睡眠恢复
+const DRIFT_THRESHOLD: Duration = Duration::from_secs(30);
+
+type ResumeHandler = Arc<dyn Fn() + Send + Sync + 'static>;
+
+/// 系统电源通知回调（在系统线程中调用）
+#[cfg(windows)]
+unsafe extern "system" fn on_power_event(
+    context: *const c_void,
+    event_type: u32,
+    _setting: *const c_void,
+) -> u32 {
+    // 恢复时总会收到 PBT_APMRESUMEAUTOMATIC；PBT_APMRESUMESUSPEND 仅在有用户输入时补发，忽略以免重复
+    if event_type == PBT_APMRESUMEAUTOMATIC {
+        // SAFETY: context 是 start_resume_listener 中泄漏的 ResumeHandler，进程存活期间一直有效
+        let handler = unsafe { &*(context as *const ResumeHandler) };
+        tracing::info!("收到系统恢复通知 (PBT_APMRESUMEAUTOMATIC)");
+        handler();
+    }
+    ERROR_SUCCESS
+}
+
+/// 注册系统睡眠恢复通知
+#[cfg(windows)]
+fn register_power_notification(handler: ResumeHandler) -> Result<(), u32> {
+    // 回调上下文与订阅参数需在整个进程生命周期内有效，故有意泄漏
+    let context = Box::into_raw(Box::new(handler));
+    let params = Box::into_raw(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+        Callback: Some(on_power_event),
+        Context: context as *mut c_void,
+    }));
+
+    let mut registration = std::ptr::null_mut();
+    // SAFETY: params 指向有效的订阅参数，registration 为有效输出指针
+    let result = unsafe {
+        PowerRegisterSuspendResumeNotification(
+            DEVICE_NOTIFY_CALLBACK,
+            params as *mut c_void,
+            &mut registration,
+        )
+    };
+
+    if result == ERROR_SUCCESS {
+        Ok(())
+    } else {
+        // SAFETY: 注册失败时系统未持有这两个指针，可安全回收
+        unsafe {
+            drop(Box::from_raw(params));
+            drop(Box::from_raw(context));
+        }
+        Err(result)
+    }
+}
+
+/// 墙钟漂移检测（后备方案）
+fn spawn_drift_detector(handler: ResumeHandler) -> std::io::Result<()> {
+    std::thread::Builder::new()
+        .name("resume-detector".to_string())
+        .spawn(move || {
+            loop {
+                let before = SystemTime::now();
+                std::thread::sleep(DRIFT_POLL_INTERVAL);
+                let elapsed = SystemTime::now().duration_since(before).unwrap_or_default();
+
+                if elapsed > DRIFT_POLL_INTERVAL + DRIFT_THRESHOLD {
+                    tracing::info!("检测到墙钟跳变 {:?}，推断系统刚从睡眠恢复", elapsed);
+                    handler();
+                }
+            }
+        })?;
+    Ok(())
+}
+
+/// 监听系统睡眠恢复事件
+///
+/// `on_resume` 可能在系统线程中调用，应尽快返回（耗时工作请转交异步任务）
+pub fn start_resume_listener<F>(on_resume: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let handler: ResumeHandler = Arc::new(on_resume);
+
+    #[cfg(not(windows))]
+    if let Err(e) = spawn_drift_detector(handler) {
+        tracing::error!("启动睡眠恢复检测线程失败: {}", e);
+    }
+
+    #[cfg(windows)]
+    match register_power_notification(handler.clone()) {
+        Ok(()) => tracing::info!("已注册系统睡眠恢复通知"),
+        Err(code) => {
+            tracing::warn!("注册睡眠恢复通知失败 (错误码 {})，改用墙钟漂移检测", code);
+            if let Err(e) = spawn_drift_detector(handler) {
+                tracing::error!("启动睡眠恢复检测线程失败: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+/// 系统代理检测模块
+///
+/// 按优先级依次检查：
+/// 1. 环境变量 `https_proxy` / `http_proxy` / `all_proxy`（大小写均可）
+/// 2. 当前用户的 Internet 选项代理（WinHttpGetIEProxyConfigForCurrentUser）
+/// 3. WinHTTP 默认代理（`netsh winhttp set proxy` 设置）
+///
+/// 不处理 PAC 自动配置脚本，此类环境请在配置文件 `[network] proxy` 中显式指定。
+/// Linux 上只检查环境变量（桌面环境的代理设置通常也会导出到环境变量）
+#[cfg(windows)]
+use windows_sys::Win32::Foundation::GlobalFree;
+#[cfg(windows)]
+use windows_sys::Win32::Networking::WinHttp::{
+    WINHTTP_ACCESS_TYPE_NAMED_PROXY, WINHTTP_CURRENT_USER_IE_PROXY_CONFIG, WINHTTP_PROXY_INFO,
+    WinHttpGetDefaultProxyConfiguration, WinHttpGetIEProxyConfigForCurrentUser,
+};
+#[cfg(windows)]
+use windows_sys::core::PWSTR;
+
+/// 检测到的系统代理
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemProxy {
+    /// 代理地址（带 scheme，例如 "http://proxy.corp:8080"）
+    pub url: String,
+    /// 不走代理的主机列表（reqwest `NoProxy` 格式，逗号分隔）
+    pub bypass: Option<String>,
+    /// 来源说明（用于日志）
+    pub source: &'static str,
+}
+
+/// 检测系统代理
+#[cfg(windows)]
+pub fn detect_system_proxy() -> Option<SystemProxy> {
+    detect_from_env()
+        .or_else(detect_from_ie_config)
+        .or_else(detect_from_winhttp_default)
+}
+
+/// 检测系统代理
+#[cfg(not(windows))]
+pub fn detect_system_proxy() -> Option<SystemProxy> {
+    detect_from_env()
+}
+
+/// 从环境变量读取代理
+fn detect_from_env() -> Option<SystemProxy> {
+    const VARS: [&str; 6] = [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+    ];
+
+    let url = VARS
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())?;
+
+    Some(SystemProxy {
+        url: normalize_proxy_url(url.trim()),
+        bypass: std::env::var("NO_PROXY")
+            .or_else(|_| std::env::var("no_proxy"))
+            .ok(),
+        source: "环境变量",
+    })
+}
+
+/// 从当前用户的 Internet 选项读取代理
+#[cfg(windows)]
+fn detect_from_ie_config() -> Option<SystemProxy> {
+    let mut config = WINHTTP_CURRENT_USER_IE_PROXY_CONFIG {
+        fAutoDetect: 0,
+        lpszAutoConfigUrl: std::ptr::null_mut(),
+        lpszProxy: std::ptr::null_mut(),
+        lpszProxyBypass: std::ptr::null_mut(),
+    };
+
+    // SAFETY: config 为有效输出参数，返回的字符串由下方 take_wide_string 释放
+    if unsafe { WinHttpGetIEProxyConfigForCurrentUser(&mut config) } == 0 {
+        return None;
+    }
+
+    let proxy = unsafe { take_wide_string(config.lpszProxy) };
+    let bypass = unsafe { take_wide_string(config.lpszProxyBypass) };
+    unsafe { take_wide_string(config.lpszAutoConfigUrl) };
+
+    Some(SystemProxy {
+        url: parse_proxy_list(&proxy?)?,
+        bypass: bypass.and_then(|b| convert_bypass_list(&b)),
+        source: "Internet 选项",
+    })
+}
+
+/// 从 WinHTTP 默认配置读取代理
+#[cfg(windows)]
+fn detect_from_winhttp_default() -> Option<SystemProxy> {
+    let mut info = WINHTTP_PROXY_INFO {
+        dwAccessType: 0,
+        lpszProxy: std::ptr::null_mut(),
+        lpszProxyBypass: std::ptr::null_mut(),
+    };
+
+    // SAFETY: info 为有效输出参数，返回的字符串由下方 take_wide_string 释放
+    if unsafe { WinHttpGetDefaultProxyConfiguration(&mut info) } == 0 {
+        return None;
+    }
+
+    let proxy = unsafe { take_wide_string(info.lpszProxy) };
+    let bypass = unsafe { take_wide_string(info.lpszProxyBypass) };
+
+    if info.dwAccessType != WINHTTP_ACCESS_TYPE_NAMED_PROXY {
+        return None;
+    }
+
+    Some(SystemProxy {
+        url: parse_proxy_list(&proxy?)?,
+        bypass: bypass.and_then(|b| convert_bypass_list(&b)),
+        source: "WinHTTP 默认代理",
+    })
+}
+
+/// 读取 WinHTTP 分配的宽字符串并用 GlobalFree 释放
+///
+/// # Safety
+/// `ptr` 必须为空或由 WinHTTP 分配的以 0 结尾的 UTF-16 字符串
+#[cfg(windows)]
+unsafe fn take_wide_string(ptr: PWSTR) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    let value = unsafe {
+        let len = (0..).take_while(|&i| *ptr.add(i) != 0).count();
+        String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len))
+    };
+    unsafe { GlobalFree(ptr as _) };
+
+    Some(value).filter(|s| !s.trim().is_empty())
+}
+
+/// 为缺少 scheme 的代理地址补全 `http://`
+fn normalize_proxy_url(value: &str) -> String {
+    if value.contains("://") {
+        value.to_string()
+    } else {
+        format!("http://{}", value)
+    }
+}
+
+/// 解析 Windows 代理列表
+///
+/// 支持 `host:port` 以及 `http=host:port;https=host:port` 两种格式，
+/// 优先使用 https 条目，其次 http 条目，再次无前缀条目；socks/ftp 条目忽略
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_proxy_list(list: &str) -> Option<String> {
+    let entries: Vec<&str> = list
+        .split([';', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let scoped = |scheme: &str| {
+        entries.iter().find_map(|entry| {
+            let (key, value) = entry.split_once('=')?;
+            key.eq_ignore_ascii_case(scheme).then_some(value)
+        })
+    };
+
+    let chosen = scoped("https")
+        .or_else(|| scoped("http"))
+        .or_else(|| entries.iter().copied().find(|entry| !entry.contains('=')))?;
+
+    Some(normalize_proxy_url(chosen))
+}
+
+/// 将 Windows 代理例外列表转换为 `NoProxy` 格式
+///
+/// `<local>` 表示不含点号的本地主机名，此处以 localhost 与回环地址近似
+#[cfg_attr(not(windows), allow(dead_code))]
+fn convert_bypass_list(list: &str) -> Option<String> {
+    let hosts: Vec<String> = list
+        .split([';', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .flat_map(|entry| {
+            if entry.eq_ignore_ascii_case("<local>") {
+                vec!["localhost".to_string(), "127.0.0.1".to_string()]
+            } else {
+                vec![entry.trim_start_matches('*').to_string()]
+            }
+        })
+        .collect();
+
+    (!hosts.is_empty()).then(|| hosts.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proxy_list_plain() {
+        assert_eq!(
+            parse_proxy_list("proxy.corp:8080"),
+            Some("http://proxy.corp:8080".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_proxy_list_per_scheme() {
+        assert_eq!(
+            parse_proxy_list("ftp=ftp.corp:21;http=web.corp:80;https=secure.corp:443"),
+            Some("http://secure.corp:443".to_string())
+        );
+        assert_eq!(
+            parse_proxy_list("socks=socks.corp:1080;http=web.corp:80"),
+            Some("http://web.corp:80".to_string())
+        );
+        assert_eq!(parse_proxy_list("socks=socks.corp:1080"), None);
+    }
+
+    #[test]
+    fn test_convert_bypass_list() {
+        assert_eq!(
+            convert_bypass_list("*.corp.local;<local>"),
+            Some(".corp.local,localhost,127.0.0.1".to_string())
+        );
+        assert_eq!(convert_bypass_list(" ; "), None);
+    }
+}
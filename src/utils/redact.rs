@@ -0,0 +1,137 @@
+/// 日志脱敏模块
+///
+/// 日志会被用户直接粘贴到 Issue 中，邮箱地址、Token、授权码等敏感信息
+/// 在写入日志前统一经过此模块处理
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// 允许记录原始响应体的环境变量（仅用于本地调试）
+const LOG_BODIES_ENV: &str = "NANOMAIL_LOG_BODIES";
+
+/// 邮箱脱敏：仅保留用户名首字符与域名
+///
+/// # Example
+/// ```
+/// assert_eq!(redact_email("alice@gmail.com"), "a***@gmail.com");
+/// ```
+pub fn redact_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first = local.chars().next().map(String::from).unwrap_or_default();
+            format!("{}***@{}", first, domain)
+        }
+        None => "***".to_string(),
+    }
+}
+
+/// Token / 授权码脱敏：只输出长度与哈希前缀
+///
+/// 哈希前缀足以在日志中区分"是否为同一个 Token"，但无法还原原文
+///
+/// # Example
+/// ```
+/// let s = redact_token("ya29.secret");
+/// assert!(s.starts_with("<len=11 hash="));
+/// ```
+pub fn redact_token(token: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    token.hash(&mut hasher);
+    let hash = format!("{:016x}", hasher.finish());
+    format!("<len={} hash={}>", token.len(), &hash[..8])
+}
+
+/// 是否允许记录 API 原始响应体（`NANOMAIL_LOG_BODIES=1`）
+pub fn log_bodies_enabled() -> bool {
+    std::env::var(LOG_BODIES_ENV).is_ok_and(|v| v == "1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_email() {
+        assert_eq!(redact_email("alice@gmail.com"), "a***@gmail.com");
+        assert_eq!(redact_email("@gmail.com"), "***@gmail.com");
+        assert_eq!(redact_email("not-an-email"), "***");
+    }
+
+    #[test]
+    fn test_redact_email_hides_local_part() {
+        let redacted = redact_email("crayonape@gmail.com");
+        assert!(!redacted.contains("crayonape"));
+    }
+
+    #[test]
+    fn test_redact_token_never_contains_secret() {
+        let secrets = [
+            "ya29.a0AfH6SMBx-very-secret-access-token",
+            "1//0gLongRefreshTokenValue",
+            "4/0AX4XfWh-auth-code",
+            "abc",
+        ];
+
+        for secret in secrets {
+            let redacted = redact_token(secret);
+            assert!(!redacted.contains(secret));
+            // 也不应泄露前后缀片段
+            assert!(!redacted.contains(&secret[..3]));
+            assert!(redacted.contains(&format!("len={}", secret.len())));
+        }
+    }
+
+    #[test]
+    fn test_redact_token_is_stable() {
+        assert_eq!(redact_token("same"), redact_token("same"));
+        assert_ne!(redact_token("same"), redact_token("other"));
+    }
+
+    /// 源码检查：日志宏中不得直接输出 access_token / refresh_token（必须经过 redact_token）
+    #[test]
+    fn test_no_raw_tokens_in_log_macros() {
+        let sources = [
+            ("main.rs", include_str!("../main.rs")),
+            ("api.rs", include_str!("../mail/gmail/api.rs")),
+            ("oauth.rs", include_str!("../mail/gmail/oauth.rs")),
+            ("oauth/flow.rs", include_str!("../mail/oauth/flow.rs")),
+            ("oauth/token.rs", include_str!("../mail/oauth/token.rs")),
+            ("outlook/api.rs", include_str!("../mail/outlook/api.rs")),
+            ("outlook/oauth.rs", include_str!("../mail/outlook/oauth.rs")),
+            ("sync/mod.rs", include_str!("../sync/mod.rs")),
+        ];
+
+        for (name, source) in sources {
+            let mut rest = source;
+            while let Some(start) = rest.find("tracing::") {
+                let call = &rest[start..];
+                // `#[tracing::instrument(...)]` 属性到 `)]` 为止：只检查属性本身，不延伸到函数体
+                let terminator = if rest[..start].ends_with("#[") {
+                    ")]"
+                } else {
+                    ");"
+                };
+                let end = call.find(terminator).unwrap_or(call.len());
+                let invocation = &call[..end];
+
+                for field in ["access_token", "refresh_token"] {
+                    for (pos, _) in invocation.match_indices(field) {
+                        // 回退到标识符开头（如 new_access_token）
+                        let ident_start = invocation[..pos]
+                            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+                            .map_or(0, |i| i + 1);
+                        let before = &invocation[..ident_start];
+                        assert!(
+                            before.ends_with("redact_token(&") || before.ends_with("redact_token("),
+                            "{} 的日志宏中直接输出了 {}: {}",
+                            name,
+                            field,
+                            invocation
+                        );
+                    }
+                }
+
+                rest = &call[end..];
+            }
+        }
+    }
+}
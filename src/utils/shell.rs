@@ -0,0 +1,67 @@
+/// 用系统外壳打开文件与目录
+///
+/// Windows 上通过资源管理器打开（`explorer /select,` 可选中文件）；
+/// 其他平台使用 `xdg-open`，无法选中文件时打开所在目录
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+use super::app_info::FILE_OPENER;
+
+/// 用默认程序打开文件或目录
+pub fn open(path: &Path) -> Result<()> {
+    ensure_exists(path)?;
+    Command::new(FILE_OPENER)
+        .arg(path)
+        .spawn()
+        .with_context(|| format!("无法打开 {}", path.display()))?;
+    Ok(())
+}
+
+/// 在文件管理器中显示：文件所在目录中选中该文件，目录直接打开
+pub fn reveal(path: &Path) -> Result<()> {
+    ensure_exists(path)?;
+    if path.is_dir() {
+        return open(path);
+    }
+
+    #[cfg(windows)]
+    {
+        // explorer 要求 /select, 与路径连在一起作为一个参数
+        let mut arg = std::ffi::OsString::from("/select,");
+        arg.push(path);
+        Command::new(FILE_OPENER)
+            .arg(arg)
+            .spawn()
+            .with_context(|| format!("无法显示 {}", path.display()))?;
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    match path.parent() {
+        Some(dir) => open(dir),
+        None => open(path),
+    }
+}
+
+fn ensure_exists(path: &Path) -> Result<()> {
+    if !path.exists() {
+        anyhow::bail!("{} 不存在", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_path_is_reported() {
+        let path = std::env::temp_dir().join("nanomail-shell-test-missing");
+        let _ = std::fs::remove_file(&path);
+
+        let error = reveal(&path).unwrap_err();
+        assert!(error.to_string().contains("nanomail-shell-test-missing"));
+        assert!(open(&path).is_err());
+    }
+}
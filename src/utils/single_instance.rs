@@ -0,0 +1,335 @@
+/// 单实例守卫模块
+///
+/// 通过命名互斥量（Linux 上为锁文件）判断是否已有实例在运行；若已存在，则通过本地套接字
+/// （Windows 命名管道 / Linux 抽象命名空间套接字）通知正在运行的实例显示窗口，然后当前进程直接退出
+use anyhow::{Context, Result};
+use interprocess::local_socket::{GenericNamespaced, ListenerOptions, Stream, prelude::*};
+use std::io::{BufRead, BufReader, Write};
+use std::time::Duration;
+#[cfg(windows)]
+use windows_sys::Win32::Foundation::{CloseHandle, ERROR_ALREADY_EXISTS, GetLastError, HANDLE};
+#[cfg(windows)]
+use windows_sys::Win32::System::Threading::CreateMutexW;
+
+/// 互斥量名称（Local 命名空间：仅对当前登录会话生效）
+#[cfg(windows)]
+const MUTEX_NAME: &str = "Local\\NanoMail.instance";
+
+/// 锁文件名称（位于 `$XDG_RUNTIME_DIR`，仅对当前用户会话生效）
+#[cfg(not(windows))]
+const LOCK_FILE_NAME: &str = "nanomail.instance.lock";
+
+/// 本地套接字名称
+const SOCKET_NAME: &str = "NanoMail.instance";
+
+/// 已有实例正在退出时，等待后重试的间隔
+const RETRY_DELAY: Duration = Duration::from_millis(1000);
+
+/// 等待正在运行的实例回复的最长时间
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 实例间消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceMessage {
+    /// 显示主窗口
+    Show,
+    /// 立即同步
+    SyncNow,
+    /// 查询缓存的各账户未读数（回复一行 JSON）
+    Counts,
+}
+
+impl InstanceMessage {
+    /// 序列化为单行文本
+    fn as_str(&self) -> &'static str {
+        match self {
+            InstanceMessage::Show => "show",
+            InstanceMessage::SyncNow => "sync",
+            InstanceMessage::Counts => "counts",
+        }
+    }
+
+    /// 从单行文本解析
+    fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "show" => Some(InstanceMessage::Show),
+            "sync" => Some(InstanceMessage::SyncNow),
+            "counts" => Some(InstanceMessage::Counts),
+            _ => None,
+        }
+    }
+}
+
+/// 单实例守卫（持有命名互斥量，进程退出或 drop 时释放）
+#[cfg(windows)]
+pub struct InstanceGuard {
+    handle: HANDLE,
+}
+
+/// 单实例守卫（持有锁文件上的独占锁，进程退出或 drop 时释放）
+#[cfg(not(windows))]
+pub struct InstanceGuard {
+    _lock: Option<std::fs::File>,
+}
+
+impl InstanceGuard {
+    /// 不持有任何锁的守卫（单实例检查失败时使用）
+    #[cfg(windows)]
+    fn unlocked() -> Self {
+        Self {
+            handle: std::ptr::null_mut(),
+        }
+    }
+
+    /// 不持有任何锁的守卫（单实例检查失败时使用）
+    #[cfg(not(windows))]
+    fn unlocked() -> Self {
+        Self { _lock: None }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            // SAFETY: handle 由 CreateMutexW 返回且只在此处关闭一次
+            unsafe {
+                CloseHandle(self.handle);
+            }
+        }
+    }
+}
+
+/// 单实例检查结果
+pub enum InstanceRole {
+    /// 当前进程是唯一实例，继续启动
+    Primary(InstanceGuard),
+    /// 已有实例在运行并已收到通知，当前进程应退出
+    Secondary,
+}
+
+/// 尝试创建命名互斥量
+///
+/// # Returns
+/// - `Ok(Some(guard))`：当前为首个实例
+/// - `Ok(None)`：互斥量已存在（已有实例）
+#[cfg(windows)]
+fn try_acquire() -> Result<Option<InstanceGuard>> {
+    let name: Vec<u16> = MUTEX_NAME
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+
+    // SAFETY: name 为以 0 结尾的 UTF-16 字符串，在调用期间保持有效
+    let (handle, last_error) = unsafe {
+        let handle = CreateMutexW(std::ptr::null(), 0, name.as_ptr());
+        (handle, GetLastError())
+    };
+
+    if handle.is_null() {
+        anyhow::bail!("创建单实例互斥量失败（错误码 {}）", last_error);
+    }
+
+    let guard = InstanceGuard { handle };
+    if last_error == ERROR_ALREADY_EXISTS {
+        return Ok(None);
+    }
+
+    Ok(Some(guard))
+}
+
+/// 尝试独占锁文件
+///
+/// # Returns
+/// - `Ok(Some(guard))`：当前为首个实例
+/// - `Ok(None)`：锁已被其他进程持有（已有实例）
+#[cfg(not(windows))]
+fn try_acquire() -> Result<Option<InstanceGuard>> {
+    let dir = dirs::runtime_dir().unwrap_or_else(std::env::temp_dir);
+    let path = dir.join(LOCK_FILE_NAME);
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("打开单实例锁文件失败: {}", path.display()))?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(InstanceGuard { _lock: Some(file) })),
+        Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+        Err(std::fs::TryLockError::Error(e)) => {
+            Err(e).with_context(|| format!("锁定单实例锁文件失败: {}", path.display()))
+        }
+    }
+}
+
+/// 连接正在运行的实例并发送消息
+fn connect_and_send(message: InstanceMessage) -> Result<Stream> {
+    let name = SOCKET_NAME
+        .to_ns_name::<GenericNamespaced>()
+        .context("构造本地套接字名称失败")?;
+    let mut stream = Stream::connect(name).context("连接正在运行的实例失败")?;
+    stream
+        .write_all(format!("{}\n", message.as_str()).as_bytes())
+        .context("发送实例消息失败")?;
+    Ok(stream)
+}
+
+/// 向正在运行的实例发送消息
+fn send_to_running(message: InstanceMessage) -> Result<()> {
+    connect_and_send(message).map(drop)
+}
+
+/// 向正在运行的实例发送消息并等待一行回复
+///
+/// 实例在 [`REPLY_TIMEOUT`] 内未回复（如旧版本不支持该消息）时返回错误
+pub fn request(message: InstanceMessage) -> Result<String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("instance-request".to_string())
+        .spawn(move || {
+            let result = connect_and_send(message).and_then(|stream| {
+                let mut line = String::new();
+                BufReader::new(stream)
+                    .read_line(&mut line)
+                    .context("读取实例回复失败")?;
+                Ok(line)
+            });
+            tx.send(result).ok();
+        })?;
+
+    let line = rx
+        .recv_timeout(REPLY_TIMEOUT)
+        .map_err(|_| anyhow::anyhow!("正在运行的实例未回复"))??;
+    if line.trim().is_empty() {
+        anyhow::bail!("正在运行的实例不支持该请求，请先退出它");
+    }
+    Ok(line.trim_end().to_string())
+}
+
+/// 确保单实例运行
+///
+/// 已有实例时发送 `message`（为 `None` 时不通知）并返回 [`InstanceRole::Secondary`]。
+/// 若已有实例正处于退出过程中（互斥量仍在但套接字已关闭），等待片刻后重试一次。
+/// 互斥量创建失败时不阻止启动，仅记录警告
+pub fn ensure_single_instance(message: Option<InstanceMessage>) -> InstanceRole {
+    for attempt in 1..=2 {
+        match try_acquire() {
+            Ok(Some(guard)) => return InstanceRole::Primary(guard),
+            Ok(None) => match message.map_or(Ok(()), send_to_running) {
+                Ok(()) => {
+                    tracing::info!("检测到已有实例，已通知其 {:?}，当前进程退出", message);
+                    return InstanceRole::Secondary;
+                }
+                Err(e) => {
+                    tracing::warn!("已有实例未响应（可能正在退出）: {}", e);
+                    if attempt == 1 {
+                        std::thread::sleep(RETRY_DELAY);
+                    }
+                }
+            },
+            Err(e) => {
+                tracing::warn!("单实例检查失败，继续启动: {}", e);
+                return InstanceRole::Primary(InstanceGuard::unlocked());
+            }
+        }
+    }
+
+    tracing::error!("已有实例仍在运行但无法通信，当前进程退出");
+    InstanceRole::Secondary
+}
+
+/// 在后台线程监听其他实例发来的消息
+///
+/// 每个连接在单独的线程中读取与回复：迟迟不发送完整一行的连接只占用自己的线程，
+/// 不影响之后的"显示窗口""立即同步"等请求
+///
+/// # Arguments
+/// * `on_message` - 收到消息时的回调（在连接线程中调用），返回 `Some` 时作为一行回复发回
+pub fn start_listener<F>(on_message: F) -> Result<()>
+where
+    F: Fn(InstanceMessage) -> Option<String> + Send + Sync + 'static,
+{
+    let name = SOCKET_NAME
+        .to_ns_name::<GenericNamespaced>()
+        .context("构造本地套接字名称失败")?;
+    let listener = ListenerOptions::new()
+        .name(name)
+        .create_sync()
+        .context("创建单实例监听套接字失败")?;
+    let on_message = std::sync::Arc::new(on_message);
+
+    std::thread::Builder::new()
+        .name("single-instance".to_string())
+        .spawn(move || {
+            tracing::debug!("单实例监听已启动: {}", SOCKET_NAME);
+            for conn in listener.incoming() {
+                let conn = match conn {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        tracing::warn!("接受实例连接失败: {}", e);
+                        continue;
+                    }
+                };
+
+                let on_message = on_message.clone();
+                if let Err(e) = std::thread::Builder::new()
+                    .name("single-instance-conn".to_string())
+                    .spawn(move || handle_connection(conn, &*on_message))
+                {
+                    tracing::warn!("创建实例连接线程失败: {}", e);
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+/// 读取一个连接发来的消息并回复，处理完后关闭连接
+fn handle_connection(conn: Stream, on_message: &dyn Fn(InstanceMessage) -> Option<String>) {
+    let mut reader = BufReader::new(conn);
+    let mut line = String::new();
+    if let Err(e) = reader.read_line(&mut line) {
+        tracing::warn!("读取实例消息失败: {}", e);
+        return;
+    }
+
+    match InstanceMessage::parse(&line) {
+        Some(message) => {
+            tracing::info!("收到其他实例消息: {:?}", message);
+            if let Some(reply) = on_message(message)
+                && let Err(e) = reader
+                    .get_mut()
+                    .write_all(format!("{}\n", reply).as_bytes())
+            {
+                tracing::warn!("回复实例消息失败: {}", e);
+            }
+        }
+        None => tracing::warn!("未识别的实例消息: {:?}", line.trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        for message in [
+            InstanceMessage::Show,
+            InstanceMessage::SyncNow,
+            InstanceMessage::Counts,
+        ] {
+            assert_eq!(InstanceMessage::parse(message.as_str()), Some(message));
+        }
+    }
+
+    #[test]
+    fn test_message_parse_trims_newline() {
+        assert_eq!(
+            InstanceMessage::parse("show\n"),
+            Some(InstanceMessage::Show)
+        );
+        assert_eq!(InstanceMessage::parse("unknown"), None);
+    }
+}
@@ -0,0 +1,168 @@
+/// 界面发起的后台任务
+///
+/// 添加账户、测试 IMAP 连接、重新授权等操作都在 Tokio 运行时上以任务形式运行，并登记在这里。
+/// 退出时先取消所有任务（共享同一个 `CancellationToken`），再在限定时间内等待它们结束；
+/// 开始退出后不再接受新任务
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::runtime::Handle;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+/// 后台任务登记表
+pub struct Workers {
+    /// 运行任务的 Tokio 运行时
+    handle: Handle,
+
+    /// 正在运行的任务
+    tasks: Mutex<JoinSet<()>>,
+
+    /// 退出信号（所有任务共享）
+    cancel: CancellationToken,
+}
+
+impl Workers {
+    /// 在指定运行时上运行任务
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle,
+            tasks: Mutex::new(JoinSet::new()),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// 是否已开始退出（回调据此跳过新的操作）
+    pub fn is_shutting_down(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// 启动后台任务；已开始退出时不启动并返回 false
+    ///
+    /// 收到退出信号时任务在下一个 await 点被放弃
+    pub fn spawn(
+        &self,
+        name: &'static str,
+        task: impl Future<Output = ()> + Send + 'static,
+    ) -> bool {
+        let mut tasks = self.lock();
+        if self.cancel.is_cancelled() {
+            tracing::warn!("正在退出，跳过后台任务: {}", name);
+            return false;
+        }
+
+        // 顺便回收已结束的任务
+        while let Some(result) = tasks.try_join_next() {
+            log_join_error(result);
+        }
+
+        let cancel = self.cancel.clone();
+        tasks.spawn_on(
+            async move {
+                tokio::select! {
+                    _ = cancel.cancelled() => tracing::debug!("后台任务已取消: {}", name),
+                    _ = task => {}
+                }
+            },
+            &self.handle,
+        );
+        true
+    }
+
+    /// 发出退出信号（之后不再接受新任务）
+    pub fn cancel(&self) {
+        let _tasks = self.lock();
+        self.cancel.cancel();
+    }
+
+    /// 取消所有任务并最多等待 `timeout`，返回超时后仍未结束（已被中止）的任务数
+    pub async fn shutdown(&self, timeout: Duration) -> usize {
+        let mut tasks = {
+            let mut tasks = self.lock();
+            self.cancel.cancel();
+            std::mem::take(&mut *tasks)
+        };
+
+        let joined = tokio::time::timeout(timeout, async {
+            while let Some(result) = tasks.join_next().await {
+                log_join_error(result);
+            }
+        })
+        .await;
+
+        let remaining = tasks.len();
+        if joined.is_err() {
+            tracing::warn!("{} 个后台任务未能按时结束，已中止", remaining);
+            tasks.abort_all();
+        }
+        remaining
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, JoinSet<()>> {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 任务 panic 只记录日志，不影响其他任务与退出流程
+fn log_join_error(result: Result<(), tokio::task::JoinError>) {
+    if let Err(e) = result
+        && e.is_panic()
+    {
+        tracing::error!("后台任务 panic: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn test_quit_during_long_auth() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let workers = Workers::new(rt.handle().clone());
+
+        // 模拟等待浏览器回调的授权流程
+        let started = Arc::new(tokio::sync::Notify::new());
+        let finished = Arc::new(AtomicBool::new(false));
+        assert!(workers.spawn("fake auth", {
+            let started = started.clone();
+            let finished = finished.clone();
+            async move {
+                started.notify_one();
+                tokio::time::sleep(Duration::from_secs(300)).await;
+                finished.store(true, Ordering::SeqCst);
+            }
+        }));
+        // panic 的任务不影响退出
+        assert!(workers.spawn("panics", async { panic!("boom") }));
+
+        let remaining = rt.block_on(async {
+            started.notified().await;
+            workers.shutdown(Duration::from_secs(5)).await
+        });
+
+        assert_eq!(remaining, 0);
+        assert!(!finished.load(Ordering::SeqCst));
+        assert!(workers.is_shutting_down());
+        assert!(!workers.spawn("late", async {}));
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_finished_tasks_are_reaped() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let workers = Workers::new(rt.handle().clone());
+
+        for _ in 0..3 {
+            assert!(workers.spawn("quick", async {}));
+        }
+        rt.block_on(tokio::time::sleep(Duration::from_millis(50)));
+        assert!(workers.spawn("quick", async {}));
+        assert_eq!(workers.lock().len(), 1);
+
+        assert_eq!(rt.block_on(workers.shutdown(Duration::from_secs(1))), 0);
+    }
+}
@@ -0,0 +1,262 @@
+/// 同步耗时统计模块
+///
+/// 同步流程中的各阶段以 `tracing` span 标记：`sync.round`（一轮同步，字段 `sync.round`）、
+/// `sync.account`（单个账户，字段 `account.email` 已脱敏）、`network.ensure`（请求失败后确认网络）、
+/// `oauth.refresh`（刷新 Access Token）、`gmail.request`（字段 `gmail.endpoint`、`http.status`）
+/// 与 `avatar.download`。[`PhaseTimingLayer`] 在 span 结束时按名称累计耗时，
+/// 一轮结束时汇总为 [`RoundSummary`]，同步引擎据此记录本轮耗时，托盘"运行诊断"附上最近一轮的汇总
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::Subscriber;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// 一轮同步的 span 名称
+pub const ROUND_SPAN: &str = "sync.round";
+
+/// 最近一轮同步的汇总
+static LAST_ROUND: Mutex<Option<RoundSummary>> = Mutex::new(None);
+
+/// 同一阶段在一轮中的累计耗时
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseTiming {
+    /// span 名称
+    pub name: &'static str,
+
+    /// 次数
+    pub count: u32,
+
+    /// 累计耗时
+    pub total: Duration,
+}
+
+/// 一轮同步各阶段的耗时
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundSummary {
+    /// 轮次（`sync.round` 字段）
+    pub round: u64,
+
+    /// 整轮耗时
+    pub total: Duration,
+
+    /// 各阶段（按首次出现的顺序）
+    pub phases: Vec<PhaseTiming>,
+}
+
+impl RoundSummary {
+    fn new(round: u64) -> Self {
+        Self {
+            round,
+            total: Duration::ZERO,
+            phases: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, name: &'static str, elapsed: Duration) {
+        match self.phases.iter_mut().find(|p| p.name == name) {
+            Some(phase) => {
+                phase.count += 1;
+                phase.total += elapsed;
+            }
+            None => self.phases.push(PhaseTiming {
+                name,
+                count: 1,
+                total: elapsed,
+            }),
+        }
+    }
+}
+
+/// 各阶段依次显示为 `名称 次数×毫秒`，如 `gmail.request 3×420ms`
+impl fmt::Display for RoundSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, phase) in self.phases.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(
+                f,
+                "{} {}×{}ms",
+                phase.name,
+                phase.count,
+                phase.total.as_millis()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 最近一轮同步的汇总（未注册 [`PhaseTimingLayer`] 或尚未完成一轮时为 `None`）
+pub fn last_round() -> Option<RoundSummary> {
+    LAST_ROUND.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// span 的开始时间（存放在 span 扩展中）
+struct Started(Instant);
+
+/// 读取 `sync.round` 字段
+struct RoundVisitor(Option<u64>);
+
+impl Visit for RoundVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == ROUND_SPAN {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == ROUND_SPAN {
+            self.0 = u64::try_from(value).ok();
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+/// 按 span 统计同步各阶段耗时的 tracing 层
+#[derive(Debug, Default)]
+pub struct PhaseTimingLayer;
+
+impl<S> Layer<S> for PhaseTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        extensions.insert(Started(Instant::now()));
+
+        if attrs.metadata().name() == ROUND_SPAN {
+            let mut visitor = RoundVisitor(None);
+            attrs.record(&mut visitor);
+            extensions.insert(RoundSummary::new(visitor.0.unwrap_or_default()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(elapsed) = span
+            .extensions()
+            .get::<Started>()
+            .map(|started| started.0.elapsed())
+        else {
+            return;
+        };
+
+        if span.name() == ROUND_SPAN {
+            if let Some(mut summary) = span.extensions_mut().remove::<RoundSummary>() {
+                summary.total = elapsed;
+                *LAST_ROUND.lock().unwrap_or_else(|e| e.into_inner()) = Some(summary);
+            }
+            return;
+        }
+
+        // 计入最近的外层 `sync.round`（不在同步轮次中的 span 不统计）
+        if let Some(round) = span.scope().skip(1).find(|s| s.name() == ROUND_SPAN)
+            && let Some(summary) = round.extensions_mut().get_mut::<RoundSummary>()
+        {
+            summary.add(span.name(), elapsed);
+        }
+    }
+}
+
+/// 测试用：记录结束的 span 及其字段
+#[cfg(test)]
+pub mod testing {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use tracing::span::Record;
+
+    /// 结束的 span
+    #[derive(Debug, Clone)]
+    pub struct RecordedSpan {
+        pub name: &'static str,
+        pub fields: HashMap<&'static str, String>,
+    }
+
+    #[derive(Default)]
+    struct Fields(HashMap<&'static str, String>);
+
+    impl Visit for Fields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+    }
+
+    /// 记录 span 的测试层
+    #[derive(Clone, Default)]
+    pub struct SpanRecorder(Arc<Mutex<Vec<RecordedSpan>>>);
+
+    impl SpanRecorder {
+        /// 已结束的 span
+        pub fn spans(&self) -> Vec<RecordedSpan> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl<S> Layer<S> for SpanRecorder
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            if let Some(span) = ctx.span(id) {
+                span.extensions_mut().insert(fields);
+            }
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            if let Some(span) = ctx.span(id)
+                && let Some(fields) = span.extensions_mut().get_mut::<Fields>()
+            {
+                values.record(fields);
+            }
+        }
+
+        fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+            if let Some(span) = ctx.span(&id) {
+                let fields = span.extensions_mut().remove::<Fields>().unwrap_or_default();
+                self.0.lock().unwrap().push(RecordedSpan {
+                    name: span.name(),
+                    fields: fields.0,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_summary_accumulates_phases() {
+        let mut summary = RoundSummary::new(3);
+        summary.add("gmail.request", Duration::from_millis(100));
+        summary.add("oauth.refresh", Duration::from_millis(50));
+        summary.add("gmail.request", Duration::from_millis(20));
+
+        assert_eq!(summary.phases.len(), 2);
+        assert_eq!(summary.phases[0].count, 2);
+        assert_eq!(summary.phases[0].total, Duration::from_millis(120));
+        assert_eq!(
+            summary.to_string(),
+            "gmail.request 2×120ms, oauth.refresh 1×50ms"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+/// 更新检查模块
+///
+/// 每天最多请求一次 GitHub Releases 最新版本，结果（包括失败）缓存在配置文件的 `[updates]` 段，
+/// 避免每次启动都发起请求；请求在 Tokio 运行时中异步执行，不阻塞启动
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+use super::{app_info, http_client};
+use crate::config;
+
+/// 最新版本查询地址
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/Keriyar/NanoMail/releases/latest";
+
+/// 两次检查之间的最小间隔（秒）
+const CHECK_INTERVAL_SECS: i64 = 24 * 60 * 60;
+
+/// 可用更新
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateInfo {
+    /// 新版本标签（原样保留，例如 "v0.2.0"）
+    pub tag: String,
+    /// 发布页地址
+    pub url: String,
+}
+
+/// GitHub Releases API 响应（仅取需要的字段）
+#[derive(Debug, Deserialize)]
+struct LatestRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+/// 宽松解析的版本号
+///
+/// 接受 `v` 前缀、缺省的次/修订号以及 `-beta.1` 形式的预发布后缀，忽略 `+build` 元数据
+#[derive(Debug, PartialEq, Eq)]
+struct Version {
+    numbers: [u64; 3],
+    pre: Vec<String>,
+}
+
+impl Version {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text
+            .strip_prefix('v')
+            .or_else(|| text.strip_prefix('V'))
+            .unwrap_or(text);
+        let text = text.split('+').next().unwrap_or(text);
+
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, pre.split('.').map(str::to_string).collect()),
+            None => (text, Vec::new()),
+        };
+
+        let mut numbers = [0u64; 3];
+        let mut parts = core.split('.');
+        for slot in numbers.iter_mut() {
+            match parts.next() {
+                Some(part) => *slot = part.parse().ok()?,
+                None => break,
+            }
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self { numbers, pre })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.numbers.cmp(&other.numbers).then_with(|| {
+            // 正式版高于同号预发布版
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre(&self.pre, &other.pre),
+            }
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 逐段比较预发布标识：数字按数值比较，数字段低于字母段，前缀相同时段数多者更大
+fn compare_pre(a: &[String], b: &[String]) -> Ordering {
+    for (x, y) in a.iter().zip(b.iter()) {
+        let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// 判断 `tag` 是否比 `current` 新（任一方无法解析时视为不新）
+pub fn is_newer(tag: &str, current: &str) -> bool {
+    match (Version::parse(tag), Version::parse(current)) {
+        (Some(tag), Some(current)) => tag > current,
+        _ => false,
+    }
+}
+
+/// 查询 GitHub 最新发布
+async fn fetch_latest() -> Result<LatestRelease> {
+    let release = http_client::get_client()
+        .get(LATEST_RELEASE_URL)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .context("请求最新版本失败")?
+        .error_for_status()
+        .context("最新版本接口返回错误")?
+        .json::<LatestRelease>()
+        .await
+        .context("解析最新版本响应失败")?;
+    Ok(release)
+}
+
+/// 从缓存结果中得出可用更新
+fn cached_update(cfg: &config::UpdatesConfig) -> Option<UpdateInfo> {
+    let tag = cfg.latest_tag.as_ref()?;
+    let url = cfg.release_url.as_ref()?;
+    is_newer(tag, app_info::VERSION).then(|| UpdateInfo {
+        tag: tag.clone(),
+        url: url.clone(),
+    })
+}
+
+/// 检查更新
+///
+/// 未到检查间隔时直接返回缓存结果；请求失败同样记录检查时间，下一个间隔前不再重试。
+/// `app.check_updates = false` 时返回 `None`
+pub async fn check() -> Option<UpdateInfo> {
+    let mut cfg = match config::load() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            tracing::warn!("加载配置失败，跳过更新检查: {}", e);
+            return None;
+        }
+    };
+
+    if !cfg.app.check_updates {
+        tracing::debug!("更新检查已禁用");
+        return None;
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    if let Some(last) = cfg.updates.last_check
+        && now - last < CHECK_INTERVAL_SECS
+    {
+        tracing::debug!("距上次更新检查不足 24 小时，使用缓存结果");
+        return cached_update(&cfg.updates);
+    }
+
+    cfg.updates.last_check = Some(now);
+    match fetch_latest().await {
+        Ok(release) => {
+            tracing::info!("最新发布版本: {}", release.tag_name);
+            cfg.updates.latest_tag = Some(release.tag_name);
+            cfg.updates.release_url = Some(release.html_url);
+        }
+        Err(e) => {
+            // 失败也记录检查时间，避免每次启动都重试
+            tracing::warn!("检查更新失败: {:#}", e);
+        }
+    }
+
+    // 请求期间配置可能已被修改（如切换主题），只写回更新检查结果
+    let updates = cfg.updates.clone();
+    if let Err(e) = config::update(|latest| latest.updates = updates) {
+        tracing::warn!("保存更新检查结果失败: {}", e);
+    }
+
+    cached_update(&cfg.updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer_basic() {
+        assert!(is_newer("0.2.0", "0.1.0"));
+        assert!(is_newer("v0.2.0", "0.1.0"));
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+    }
+
+    #[test]
+    fn test_is_newer_prerelease() {
+        assert!(is_newer("0.2.0-beta.1", "0.1.0"));
+        assert!(!is_newer("0.2.0-beta.1", "0.2.0"));
+        assert!(is_newer("0.2.0", "0.2.0-beta.1"));
+        assert!(is_newer("0.2.0-beta.2", "0.2.0-beta.1"));
+        assert!(is_newer("0.2.0-beta.10", "0.2.0-beta.2"));
+        assert!(is_newer("0.2.0-rc.1", "0.2.0-beta.3"));
+    }
+
+    #[test]
+    fn test_is_newer_messy_tags() {
+        assert!(is_newer(" V0.2 ", "0.1.0"));
+        assert!(is_newer("v0.2.0+build.5", "0.1.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+        assert!(!is_newer("v0.2.0.1", "0.1.0"));
+        assert!(!is_newer("", "0.1.0"));
+    }
+}